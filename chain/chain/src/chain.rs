@@ -3074,12 +3074,14 @@ impl Chain {
         chain_store_update.commit()
     }
 
-    /// Drop all downloaded or generated state parts and headers.
+    /// Drop all downloaded or generated state parts and headers, and the
+    /// progress of their download.
     pub fn clear_all_downloaded_parts(&mut self) -> Result<(), Error> {
         tracing::debug!(target: "state_sync", "Clear old state parts");
         let mut store_update = self.chain_store.store().store_update();
         store_update.delete_all(DBCol::StateParts);
         store_update.delete_all(DBCol::StateHeaders);
+        store_update.delete_all(DBCol::StatePartsProgress);
         store_update.commit()?;
         Ok(())
     }
@@ -3366,8 +3368,7 @@ impl Chain {
     }
 
     /// Returns execution status based on the list of currently existing outcomes
-    fn get_execution_status(
-        &self,
+    pub fn get_execution_status(
         outcomes: &[ExecutionOutcomeWithIdView],
         transaction_hash: &CryptoHash,
     ) -> FinalExecutionStatus {
@@ -3432,7 +3433,7 @@ impl Chain {
     ) -> Result<FinalExecutionOutcomeView, Error> {
         let mut outcomes = Vec::new();
         self.get_recursive_transaction_results(&mut outcomes, transaction_hash, true)?;
-        let status = self.get_execution_status(&outcomes, transaction_hash);
+        let status = Self::get_execution_status(&outcomes, transaction_hash);
         let receipts_outcome = outcomes.split_off(1);
         let transaction = self.chain_store.get_transaction(transaction_hash)?.ok_or_else(|| {
             Error::DBNotFoundErr(format!("Transaction {} is not found", transaction_hash))
@@ -3464,7 +3465,7 @@ impl Chain {
             )));
         }
 
-        let status = self.get_execution_status(&outcomes, transaction_hash);
+        let status = Self::get_execution_status(&outcomes, transaction_hash);
         let receipts_outcome = outcomes.split_off(1);
         let transaction_outcome = outcomes.pop().unwrap();
         Ok(FinalExecutionOutcomeView { status, transaction, transaction_outcome, receipts_outcome })
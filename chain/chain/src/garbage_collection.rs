@@ -614,6 +614,7 @@ impl<'a> ChainStoreUpdate<'a> {
                 self.gc_col_state_parts(block_hash, shard_id, state_num_parts)?;
                 let key = borsh::to_vec(&StateHeaderKey(shard_id, block_hash))?;
                 self.gc_col(DBCol::StateHeaders, &key);
+                self.gc_col(DBCol::StatePartsProgress, &key);
             }
         }
         // gc DBCol::ChunkExtra based on shard_uid since it's indexed by shard_uid in the storage
@@ -715,6 +716,7 @@ impl<'a> ChainStoreUpdate<'a> {
                 self.gc_col_state_parts(block_hash, shard_id, state_num_parts)?;
                 let state_header_key = borsh::to_vec(&StateHeaderKey(shard_id, block_hash))?;
                 self.gc_col(DBCol::StateHeaders, &state_header_key);
+                self.gc_col(DBCol::StatePartsProgress, &state_header_key);
             }
 
             // delete flat storage columns: FlatStateChanges and FlatStateDeltaMetadata
@@ -871,7 +873,7 @@ impl<'a> ChainStoreUpdate<'a> {
                 store_update.delete(col, key);
                 self.chain_store().incoming_receipts.pop(key);
             }
-            DBCol::StateHeaders => {
+            DBCol::StateHeaders | DBCol::StatePartsProgress => {
                 store_update.delete(col, key);
             }
             DBCol::BlockHeader => {
@@ -1011,6 +1013,9 @@ impl<'a> ChainStoreUpdate<'a> {
             // because we remove unneeded keys as we add new ones.
             | DBCol::StateSyncHashes
             | DBCol::StateSyncNewChunks
+            | DBCol::AccountsByPublicKey
+            | DBCol::ColdSegmentPointers
+            | DBCol::ArchivalFlatState
             => unreachable!(),
         }
         self.merge(store_update);
@@ -14,8 +14,11 @@ use near_parameters::{ActionCosts, ExtCosts, RuntimeConfig, RuntimeConfigStore};
 use near_pool::types::TransactionGroupIterator;
 use near_primitives::account::{AccessKey, Account};
 use near_primitives::apply::ApplyChunkReason;
+use near_primitives::bandwidth_scheduler::BlockBandwidthRequests;
+use near_primitives::challenge::PartialState;
 use near_primitives::congestion_info::{
-    CongestionControl, ExtendedCongestionInfo, RejectTransactionReason, ShardAcceptsTransactions,
+    BlockCongestionInfo, CongestionControl, ExtendedCongestionInfo, RejectTransactionReason,
+    ShardAcceptsTransactions,
 };
 use near_primitives::errors::{InvalidTxError, RuntimeError, StorageError};
 use near_primitives::hash::{hash, CryptoHash};
@@ -36,16 +39,17 @@ use near_primitives::views::{
     QueryResponseKind, ViewStateResult,
 };
 use near_store::adapter::{StoreAdapter, StoreUpdateAdapter};
+use near_store::flat::archival::ArchivalFlatStateView;
 use near_store::flat::FlatStorageManager;
 use near_store::metadata::DbKind;
 use near_store::{
-    ApplyStatePartResult, DBCol, ShardTries, StateSnapshotConfig, Store, Trie, TrieConfig,
-    TrieUpdate, WrappedTrieChanges, COLD_HEAD_KEY,
+    ApplyStatePartResult, DBCol, PartialStorage, ShardTries, StateSnapshotConfig, Store, Trie,
+    TrieConfig, TrieUpdate, WrappedTrieChanges, COLD_HEAD_KEY,
 };
 use near_vm_runner::ContractCode;
 use near_vm_runner::{precompile_contract, ContractRuntimeCache};
-use node_runtime::adapter::ViewRuntimeAdapter;
-use node_runtime::state_viewer::{TrieViewer, ViewApplyState};
+use node_runtime::simulation::{SimulationError, SimulationResult};
+use node_runtime::state_viewer::{TrieViewer, ViewApplyState, MAX_VIEW_STATE_PAGE_LIMIT};
 use node_runtime::{
     validate_transaction, verify_and_charge_transaction, ApplyState, Runtime,
     ValidatorAccountsUpdate,
@@ -75,6 +79,9 @@ pub struct NightshadeRuntime {
     epoch_manager: Arc<EpochManagerHandle>,
     migration_data: Arc<MigrationData>,
     gc_num_epochs_to_keep: u64,
+    /// Whether `ViewAccount` and `ViewState` queries look into the archival
+    /// flat state, see [`ArchivalFlatStateView`].
+    archival_flat_state: bool,
 }
 
 impl NightshadeRuntime {
@@ -89,6 +96,7 @@ impl NightshadeRuntime {
         gc_num_epochs_to_keep: u64,
         trie_config: TrieConfig,
         state_snapshot_config: StateSnapshotConfig,
+        archival_flat_state: bool,
     ) -> Arc<Self> {
         let runtime_config_store = match runtime_config_store {
             Some(store) => store,
@@ -128,6 +136,7 @@ impl NightshadeRuntime {
             epoch_manager,
             migration_data,
             gc_num_epochs_to_keep: gc_num_epochs_to_keep.max(MIN_GC_NUM_EPOCHS_TO_KEEP),
+            archival_flat_state,
         })
     }
 
@@ -432,6 +441,79 @@ impl NightshadeRuntime {
         Ok(epoch_start_height)
     }
 
+    /// Returns the view of the state used to answer a query. If `record_proof`
+    /// is set, the trie nodes visited by the query are recorded.
+    fn new_query_trie_update(
+        &self,
+        shard_uid: ShardUId,
+        state_root: StateRoot,
+        record_proof: bool,
+    ) -> TrieUpdate {
+        let trie = self.tries.get_view_trie_for_shard(shard_uid, state_root);
+        if record_proof {
+            TrieUpdate::new(trie.recording_reads())
+        } else {
+            TrieUpdate::new(trie)
+        }
+    }
+
+    /// Answers `ViewAccount` and `ViewState` queries from the archival flat
+    /// state if it's enabled and covers the block. Returns `None` if the query
+    /// has to be answered from the trie.
+    fn query_archival_flat_state(
+        &self,
+        block_height: BlockHeight,
+        block_hash: &CryptoHash,
+        request: &QueryRequest,
+    ) -> Result<Option<QueryResponseKind>, crate::near_chain_primitives::error::QueryError> {
+        if !self.archival_flat_state
+            || !matches!(request, QueryRequest::ViewAccount { .. } | QueryRequest::ViewState { .. })
+        {
+            return Ok(None);
+        }
+        let view = ArchivalFlatStateView::new(self.store.clone(), block_height, block_hash)
+            .map_err(|err| crate::near_chain_primitives::error::QueryError::InternalError {
+                error_message: err.to_string(),
+                block_height,
+                block_hash: *block_hash,
+            })?;
+        let Some(view) = view else {
+            return Ok(None);
+        };
+        let kind = match request {
+            QueryRequest::ViewAccount { account_id, .. } => self
+                .trie_viewer
+                .view_account_archival(&view, account_id)
+                .map_err(|err| {
+                    crate::near_chain_primitives::error::QueryError::from_view_account_error(
+                        err,
+                        block_height,
+                        *block_hash,
+                    )
+                })?
+                .map(|account| QueryResponseKind::ViewAccount(account.into())),
+            QueryRequest::ViewState { account_id, prefix, start_key, limit, .. } => self
+                .trie_viewer
+                .view_state_archival(
+                    &view,
+                    account_id,
+                    prefix.as_ref(),
+                    start_key.as_ref().map(|key| key.as_slice()),
+                    *limit,
+                )
+                .map_err(|err| {
+                    crate::near_chain_primitives::error::QueryError::from_view_state_error(
+                        err,
+                        block_height,
+                        *block_hash,
+                    )
+                })?
+                .map(QueryResponseKind::ViewState),
+            _ => None,
+        };
+        Ok(kind)
+    }
+
     fn obtain_state_part_impl(
         &self,
         shard_id: ShardId,
@@ -917,35 +999,44 @@ impl RuntimeAdapter for NightshadeRuntime {
         epoch_id: &EpochId,
         request: &QueryRequest,
     ) -> Result<QueryResponse, crate::near_chain_primitives::error::QueryError> {
-        match request {
-            QueryRequest::ViewAccount { account_id } => {
+        if !request.include_proof() {
+            if let Some(kind) = self.query_archival_flat_state(block_height, block_hash, request)? {
+                return Ok(QueryResponse {
+                    kind,
+                    block_height,
+                    block_hash: *block_hash,
+                    proof: vec![],
+                });
+            }
+        }
+        // `view_state` collects its proof while iterating over the trie.
+        let record_proof =
+            request.include_proof() && !matches!(request, QueryRequest::ViewState { .. });
+        let mut state_update = self.new_query_trie_update(shard_uid, *state_root, record_proof);
+        let kind = match request {
+            QueryRequest::ViewAccount { account_id, .. } => {
                 let account =
-                    self.view_account(&shard_uid, *state_root, account_id).map_err(|err| {
+                    self.trie_viewer.view_account(&state_update, account_id).map_err(|err| {
                         crate::near_chain_primitives::error::QueryError::from_view_account_error(
                             err,
                             block_height,
                             *block_hash,
                         )
                     })?;
-                Ok(QueryResponse {
-                    kind: QueryResponseKind::ViewAccount(account.into()),
-                    block_height,
-                    block_hash: *block_hash,
-                })
+                QueryResponseKind::ViewAccount(account.into())
             }
-            QueryRequest::ViewCode { account_id } => {
+            QueryRequest::ViewCode { account_id, .. } => {
                 let contract_code = self
-                    .view_contract_code(&shard_uid,  *state_root, account_id)
+                    .trie_viewer
+                    .view_contract_code(&state_update, account_id)
                     .map_err(|err| crate::near_chain_primitives::error::QueryError::from_view_contract_code_error(err, block_height, *block_hash))?;
                 let hash = *contract_code.hash();
-                let contract_code_view = ContractCodeView { hash, code: contract_code.into_code() };
-                Ok(QueryResponse {
-                    kind: QueryResponseKind::ViewCode(contract_code_view),
-                    block_height,
-                    block_hash: *block_hash,
+                QueryResponseKind::ViewCode(ContractCodeView {
+                    hash,
+                    code: contract_code.into_code(),
                 })
             }
-            QueryRequest::CallFunction { account_id, method_name, args } => {
+            QueryRequest::CallFunction { account_id, method_name, args, .. } => {
                 let mut logs = vec![];
                 let (epoch_height, current_protocol_version) = {
                     let epoch_manager = self.epoch_manager.read();
@@ -958,23 +1049,28 @@ impl RuntimeAdapter for NightshadeRuntime {
                     })?;
                     (epoch_info.epoch_height(), epoch_info.protocol_version())
                 };
+                let view_state = ViewApplyState {
+                    shard_id: shard_uid.shard_id(),
+                    block_height,
+                    prev_block_hash: *prev_block_hash,
+                    block_hash: *block_hash,
+                    epoch_id: *epoch_id,
+                    epoch_height,
+                    block_timestamp,
+                    current_protocol_version,
+                    cache: Some(self.compiled_contract_cache.handle()),
+                };
 
                 let call_function_result = self
+                    .trie_viewer
                     .call_function(
-                        &shard_uid,
-                        *state_root,
-                        block_height,
-                        block_timestamp,
-                        prev_block_hash,
-                        block_hash,
-                        epoch_height,
-                        epoch_id,
+                        &mut state_update,
+                        view_state,
                         account_id,
                         method_name,
                         args.as_ref(),
                         &mut logs,
                         self.epoch_manager.as_ref(),
-                        current_protocol_version,
                     )
                     .map_err(|err| {
                         crate::near_chain_primitives::error::QueryError::from_call_function_error(
@@ -983,63 +1079,68 @@ impl RuntimeAdapter for NightshadeRuntime {
                             *block_hash,
                         )
                     })?;
-                Ok(QueryResponse {
-                    kind: QueryResponseKind::CallResult(CallResult {
-                        result: call_function_result,
-                        logs,
-                    }),
-                    block_height,
-                    block_hash: *block_hash,
-                })
+                if record_proof {
+                    // Contracts are loaded without going through the trie, so
+                    // the code is read once more to be included into the proof
+                    // which is needed to replay the call.
+                    let _ = self.trie_viewer.view_contract_code(&state_update, account_id);
+                }
+                QueryResponseKind::CallResult(CallResult { result: call_function_result, logs })
             }
-            QueryRequest::ViewState { account_id, prefix, include_proof } => {
-                let view_state_result = self
-                    .view_state(
-                        &shard_uid,
-                        *state_root,
+            QueryRequest::ViewState { account_id, prefix, start_key, limit, include_proof } => {
+                // Pages are read from the trie as well, flat storage only
+                // reflects the state at its head and not at the queried block.
+                let view_state_result = if start_key.is_some() || limit.is_some() {
+                    self.trie_viewer.view_state_page(
+                        &state_update,
                         account_id,
                         prefix.as_ref(),
+                        start_key.as_ref().map_or(&[][..], |key| key.as_slice()),
+                        limit.unwrap_or(MAX_VIEW_STATE_PAGE_LIMIT),
                         *include_proof,
                     )
-                    .map_err(|err| {
-                        crate::near_chain_primitives::error::QueryError::from_view_state_error(
-                            err,
-                            block_height,
-                            *block_hash,
-                        )
-                    })?;
-                Ok(QueryResponse {
-                    kind: QueryResponseKind::ViewState(view_state_result),
-                    block_height,
-                    block_hash: *block_hash,
-                })
+                } else {
+                    self.trie_viewer.view_state(
+                        &state_update,
+                        account_id,
+                        prefix.as_ref(),
+                        *include_proof,
+                    )
+                }
+                .map_err(|err| {
+                    crate::near_chain_primitives::error::QueryError::from_view_state_error(
+                        err,
+                        block_height,
+                        *block_hash,
+                    )
+                })?;
+                QueryResponseKind::ViewState(view_state_result)
             }
-            QueryRequest::ViewAccessKeyList { account_id } => {
-                let access_key_list =
-                    self.view_access_keys(&shard_uid, *state_root, account_id).map_err(|err| {
+            QueryRequest::ViewAccessKeyList { account_id, .. } => {
+                let access_key_list = self
+                    .trie_viewer
+                    .view_access_keys(&state_update, account_id)
+                    .map_err(|err| {
                         crate::near_chain_primitives::error::QueryError::from_view_access_key_error(
                             err,
                             block_height,
                             *block_hash,
                         )
                     })?;
-                Ok(QueryResponse {
-                    kind: QueryResponseKind::AccessKeyList(
-                        access_key_list
-                            .into_iter()
-                            .map(|(public_key, access_key)| AccessKeyInfoView {
-                                public_key,
-                                access_key: access_key.into(),
-                            })
-                            .collect(),
-                    ),
-                    block_height,
-                    block_hash: *block_hash,
-                })
+                QueryResponseKind::AccessKeyList(
+                    access_key_list
+                        .into_iter()
+                        .map(|(public_key, access_key)| AccessKeyInfoView {
+                            public_key,
+                            access_key: access_key.into(),
+                        })
+                        .collect(),
+                )
             }
-            QueryRequest::ViewAccessKey { account_id, public_key } => {
+            QueryRequest::ViewAccessKey { account_id, public_key, .. } => {
                 let access_key = self
-                    .view_access_key(&shard_uid, *state_root, account_id, public_key)
+                    .trie_viewer
+                    .view_access_key(&state_update, account_id, public_key)
                     .map_err(|err| {
                         crate::near_chain_primitives::error::QueryError::from_view_access_key_error(
                             err,
@@ -1047,13 +1148,75 @@ impl RuntimeAdapter for NightshadeRuntime {
                             *block_hash,
                         )
                     })?;
-                Ok(QueryResponse {
-                    kind: QueryResponseKind::AccessKey(access_key.into()),
+                QueryResponseKind::AccessKey(access_key.into())
+            }
+        };
+        let proof = match state_update.trie().recorded_storage() {
+            Some(PartialStorage { nodes: PartialState::TrieValues(nodes) }) => nodes,
+            None => vec![],
+        };
+        Ok(QueryResponse { kind, block_height, block_hash: *block_hash, proof })
+    }
+
+    fn simulate_transaction(
+        &self,
+        state_roots: &HashMap<ShardUId, StateRoot>,
+        block_height: BlockHeight,
+        block_timestamp: u64,
+        prev_block_hash: &CryptoHash,
+        block_hash: &CryptoHash,
+        epoch_id: &EpochId,
+        gas_price: Balance,
+        transaction: &SignedTransaction,
+        verify_signature: bool,
+        time_limit: Duration,
+    ) -> Result<SimulationResult, SimulationError> {
+        let (epoch_height, current_protocol_version) = {
+            let epoch_manager = self.epoch_manager.read();
+            let epoch_info =
+                epoch_manager.get_epoch_info(epoch_id).map_err(RuntimeError::ValidatorError)?;
+            (epoch_info.epoch_height(), epoch_info.protocol_version())
+        };
+        let config = self.runtime_config_store.get_config(current_protocol_version);
+        self.runtime.simulate_transaction(
+            transaction,
+            verify_signature,
+            self.trie_viewer.max_gas_burnt_view(),
+            time_limit.unsigned_abs(),
+            epoch_id,
+            self.epoch_manager.as_ref(),
+            |shard_id| {
+                let shard_uid = self
+                    .epoch_manager
+                    .shard_id_to_uid(shard_id, epoch_id)
+                    .map_err(RuntimeError::ValidatorError)?;
+                let Some(state_root) = state_roots.get(&shard_uid) else {
+                    return Err(SimulationError::UnavailableShard(shard_id));
+                };
+                let apply_state = ApplyState {
+                    apply_reason: ApplyChunkReason::ViewTrackedShard,
                     block_height,
+                    prev_block_hash: *prev_block_hash,
                     block_hash: *block_hash,
-                })
-            }
-        }
+                    shard_id,
+                    epoch_id: *epoch_id,
+                    epoch_height,
+                    gas_price,
+                    block_timestamp,
+                    gas_limit: None,
+                    random_seed: *block_hash,
+                    current_protocol_version,
+                    config: Arc::clone(config),
+                    cache: Some(self.compiled_contract_cache.handle()),
+                    is_new_chunk: true,
+                    migration_data: Arc::clone(&self.migration_data),
+                    migration_flags: MigrationFlags::default(),
+                    congestion_info: BlockCongestionInfo::default(),
+                    bandwidth_requests: BlockBandwidthRequests::empty(),
+                };
+                Ok((apply_state, self.tries.get_view_trie_for_shard(shard_uid, *state_root)))
+            },
+        )
     }
 
     // Wrapper to get the metrics.
@@ -1422,7 +1585,7 @@ impl node_runtime::adapter::ViewRuntimeAdapter for NightshadeRuntime {
         epoch_info_provider: &dyn EpochInfoProvider,
         current_protocol_version: ProtocolVersion,
     ) -> Result<Vec<u8>, node_runtime::state_viewer::errors::CallFunctionError> {
-        let state_update = self.tries.new_trie_update_view(*shard_uid, state_root);
+        let mut state_update = self.tries.new_trie_update_view(*shard_uid, state_root);
         let view_state = ViewApplyState {
             shard_id: shard_uid.shard_id(),
             block_height: height,
@@ -1435,7 +1598,7 @@ impl node_runtime::adapter::ViewRuntimeAdapter for NightshadeRuntime {
             cache: Some(self.compiled_contract_cache.handle()),
         };
         self.trie_viewer.call_function(
-            state_update,
+            &mut state_update,
             view_state,
             contract_id,
             method_name,
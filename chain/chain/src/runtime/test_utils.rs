@@ -36,6 +36,7 @@ impl NightshadeRuntime {
                 hot_store_path: PathBuf::from("data"),
                 state_snapshot_subdir: PathBuf::from("state_snapshot"),
             },
+            false,
         )
     }
 
@@ -65,6 +66,7 @@ impl NightshadeRuntime {
                 hot_store_path: PathBuf::from("data"),
                 state_snapshot_subdir: PathBuf::from("state_snapshot"),
             },
+            false,
         )
     }
 
@@ -31,22 +31,25 @@ use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
 
 use near_chain_configs::{
     default_produce_chunk_add_transactions_time_limit, Genesis, MutableConfigValue,
-    DEFAULT_GC_NUM_EPOCHS_TO_KEEP, NEAR_BASE,
+    NonceReplacementPolicy, DEFAULT_GC_NUM_EPOCHS_TO_KEEP, NEAR_BASE,
 };
-use near_crypto::{InMemorySigner, Signer};
+use near_crypto::{InMemorySigner, KeyType, Signature, Signer};
 use near_o11y::testonly::init_test_logger;
 use near_primitives::block::Tip;
 use near_primitives::challenge::{ChallengesResult, PartialState, SlashedValidator};
-use near_primitives::transaction::{Action, DeleteAccountAction, StakeAction, TransferAction};
+use near_primitives::transaction::{
+    Action, DeleteAccountAction, ExecutionStatus, StakeAction, TransferAction,
+};
 use near_primitives::types::{
     BlockHeightDelta, Nonce, ValidatorId, ValidatorInfoIdentifier, ValidatorKickoutReason,
 };
 use near_primitives::validator_signer::ValidatorSigner;
 use near_primitives::views::{
-    AccountView, CurrentEpochValidatorInfo, EpochValidatorInfo, NextEpochValidatorInfo,
-    ValidatorKickoutView,
+    AccessKeyView, AccountView, CurrentEpochValidatorInfo, EpochValidatorInfo,
+    NextEpochValidatorInfo, ValidatorKickoutView,
 };
 use near_store::{get_genesis_state_roots, NodeStorage, PartialStorage};
+use node_runtime::adapter::ViewRuntimeAdapter;
 
 use super::*;
 
@@ -147,6 +150,7 @@ impl TestEnv {
                 hot_store_path: PathBuf::from("data"),
                 state_snapshot_subdir: PathBuf::from("state_snapshot"),
             },
+            false,
         );
         let state_roots = get_genesis_state_roots(&store).unwrap().unwrap();
         let genesis_hash = hash(&[0]);
@@ -1583,7 +1587,8 @@ fn generate_transaction_pool(
     }
     transactions.shuffle(&mut rng);
 
-    let mut pool = TransactionPool::new(TEST_SEED, None, "");
+    let mut pool =
+        TransactionPool::new(TEST_SEED, None, None, NonceReplacementPolicy::Disabled, "");
     for transaction in transactions {
         assert_eq!(pool.insert_transaction(transaction), InsertTransactionResult::Success);
     }
@@ -1866,6 +1871,199 @@ fn test_precompile_contracts_updates_cache() {
     }
 }
 
+/// Responses to queries made with `include_proof` can be verified against the
+/// state root using only the returned trie nodes.
+#[test]
+fn test_query_with_proof() {
+    init_test_logger();
+    let account_id: AccountId = "test1".parse().unwrap();
+    let env = TestEnv::new(vec![vec![account_id.clone()]], 4, false);
+    let public_key = InMemorySigner::test(&account_id).public_key();
+    let shard_uid = env.epoch_manager.shard_id_to_uid(ShardId::new(0), &env.head.epoch_id).unwrap();
+    let state_root = env.state_roots[0];
+    let query = |request: QueryRequest| {
+        env.runtime
+            .query(
+                shard_uid,
+                &state_root,
+                env.head.height,
+                0,
+                &env.head.prev_block_hash,
+                &env.head.last_block_hash,
+                &env.head.epoch_id,
+                &request,
+            )
+            .unwrap()
+    };
+    let verified_state = |proof: Vec<Arc<[u8]>>| {
+        let partial_storage = PartialStorage { nodes: PartialState::TrieValues(proof) };
+        TrieUpdate::new(Trie::from_recorded_storage(partial_storage, state_root, false))
+    };
+
+    let response =
+        query(QueryRequest::ViewAccount { account_id: account_id.clone(), include_proof: false });
+    assert!(response.proof.is_empty());
+
+    let response =
+        query(QueryRequest::ViewAccount { account_id: account_id.clone(), include_proof: true });
+    let QueryResponseKind::ViewAccount(account) = response.kind else {
+        panic!("unexpected response {:?}", response.kind);
+    };
+    let state_update = verified_state(response.proof);
+    let verified_account = near_store::get_account(&state_update, &account_id).unwrap().unwrap();
+    assert_eq!(account, AccountView::from(verified_account));
+
+    let response = query(QueryRequest::ViewAccessKey {
+        account_id: account_id.clone(),
+        public_key: public_key.clone(),
+        include_proof: true,
+    });
+    let QueryResponseKind::AccessKey(access_key) = response.kind else {
+        panic!("unexpected response {:?}", response.kind);
+    };
+    let state_update = verified_state(response.proof);
+    let verified_access_key =
+        near_store::get_access_key(&state_update, &account_id, &public_key).unwrap().unwrap();
+    assert_eq!(access_key, AccessKeyView::from(verified_access_key));
+
+    let response = query(QueryRequest::ViewAccessKeyList {
+        account_id: account_id.clone(),
+        include_proof: true,
+    });
+    let QueryResponseKind::AccessKeyList(access_key_list) = response.kind else {
+        panic!("unexpected response {:?}", response.kind);
+    };
+    let state_update = verified_state(response.proof);
+    let verified_access_keys =
+        TrieViewer::default().view_access_keys(&state_update, &account_id).unwrap();
+    assert_eq!(access_key_list.keys.len(), 1);
+    assert_eq!(access_key_list.keys[0].public_key, verified_access_keys[0].0);
+}
+
+/// Simulated transactions are executed together with their receipts, but the
+/// state is left untouched.
+#[test]
+fn test_simulate_transaction() {
+    init_test_logger();
+    let validators: Vec<AccountId> = vec!["test1".parse().unwrap(), "test2".parse().unwrap()];
+    let mut env = TestEnv::new(vec![validators.clone()], 4, false);
+    // Nonces are bounded by the block height.
+    env.step_default(vec![]);
+    let balance = env.view_account(&validators[1]).amount;
+    let shard_uid = env.epoch_manager.shard_id_to_uid(ShardId::new(0), &env.head.epoch_id).unwrap();
+    let state_roots = HashMap::from([(shard_uid, env.state_roots[0])]);
+    let simulate = |transaction: &SignedTransaction, verify_signature: bool| {
+        env.runtime.simulate_transaction(
+            &state_roots,
+            env.head.height,
+            0,
+            &env.head.prev_block_hash,
+            &env.head.last_block_hash,
+            &env.head.epoch_id,
+            env.runtime.genesis_config.min_gas_price,
+            transaction,
+            verify_signature,
+            Duration::seconds(1),
+        )
+    };
+
+    let signer = InMemorySigner::test_signer(&validators[0]);
+    let transaction = SignedTransaction::send_money(
+        1,
+        validators[0].clone(),
+        validators[1].clone(),
+        &signer,
+        100,
+        env.head.last_block_hash,
+    );
+    let result = simulate(&transaction, true).unwrap();
+    assert_eq!(result.transaction_outcome.id, transaction.get_hash());
+    let receipt_id = result.transaction_outcome.outcome.receipt_ids[0];
+    assert_eq!(result.receipt_outcomes[0].id, receipt_id);
+    assert_matches!(result.receipt_outcomes[0].outcome.status, ExecutionStatus::SuccessValue(_));
+    let receiver_account = result
+        .state_changes
+        .iter()
+        .rev()
+        .find(|change| change.trie_key == TrieKey::Account { account_id: validators[1].clone() })
+        .and_then(|change| change.changes.last()?.data.as_ref())
+        .map(|data| Account::try_from_slice(data).unwrap())
+        .unwrap();
+    assert_eq!(receiver_account.amount(), balance + 100);
+    assert_eq!(env.view_account(&validators[1]).amount, balance);
+
+    // Unsigned transactions are only accepted without the signature check.
+    let unsigned_transaction =
+        SignedTransaction::new(Signature::empty(KeyType::ED25519), transaction.transaction);
+    assert_matches!(
+        simulate(&unsigned_transaction, true),
+        Err(SimulationError::InvalidTransaction(InvalidTxError::InvalidSignature))
+    );
+    assert!(simulate(&unsigned_transaction, false).is_ok());
+}
+
+/// The chain only accepts transactions without a priority fee for now, so they never replace or
+/// evict other transactions in the transaction pool.
+#[test]
+fn test_transaction_pool_with_validated_transactions() {
+    init_test_logger();
+    let validators: Vec<AccountId> = vec!["test1".parse().unwrap(), "test2".parse().unwrap()];
+    let env = TestEnv::new(vec![validators.clone()], 4, false);
+    let validate = |transaction: &SignedTransaction| {
+        env.runtime
+            .validate_tx(
+                env.runtime.genesis_config.min_gas_price,
+                None,
+                transaction,
+                true,
+                &env.head.epoch_id,
+                PROTOCOL_VERSION,
+                None,
+            )
+            .unwrap()
+    };
+    let send_money = |nonce: Nonce, from: usize, to: usize, deposit: Balance| {
+        SignedTransaction::send_money(
+            nonce,
+            validators[from].clone(),
+            validators[to].clone(),
+            &InMemorySigner::test_signer(&validators[from]),
+            deposit,
+            env.head.last_block_hash,
+        )
+    };
+
+    let with_priority_fee = SignedTransaction::from_actions_v1(
+        1,
+        validators[0].clone(),
+        validators[1].clone(),
+        &InMemorySigner::test_signer(&validators[0]),
+        vec![Action::Transfer(TransferAction { deposit: 100 })],
+        env.head.last_block_hash,
+        10,
+    );
+    assert_eq!(validate(&with_priority_fee), Some(InvalidTxError::InvalidTransactionVersion));
+
+    let first = send_money(1, 0, 1, 100);
+    let same_nonce = send_money(1, 0, 1, 200);
+    let other_signer = send_money(1, 1, 0, 100);
+    for transaction in [&first, &same_nonce, &other_signer] {
+        assert_eq!(validate(transaction), None);
+    }
+
+    let mut pool = TransactionPool::new(
+        [3; 32],
+        Some(first.get_size() + same_nonce.get_size()),
+        None,
+        NonceReplacementPolicy::HigherPriorityFee,
+        "",
+    );
+    assert_eq!(pool.insert_transaction(first), InsertTransactionResult::Success);
+    assert_eq!(pool.insert_transaction(same_nonce), InsertTransactionResult::Success);
+    assert_eq!(pool.insert_transaction(other_signer), InsertTransactionResult::NoSpaceLeft);
+    assert_eq!(pool.len(), 2);
+}
+
 fn stake(
     nonce: Nonce,
     signer: &Signer,
@@ -55,6 +55,7 @@ use near_store::{
     TrieChanges, WrappedTrieChanges,
 };
 use near_vm_runner::{ContractCode, ContractRuntimeCache, NoContractRuntimeCache};
+use node_runtime::simulation::{SimulationError, SimulationResult};
 use num_rational::Ratio;
 use std::cmp::Ordering;
 use std::collections::{BTreeMap, HashMap, HashSet};
@@ -1316,6 +1317,7 @@ impl RuntimeAdapter for KeyValueRuntime {
                 ),
                 block_height,
                 block_hash: *block_hash,
+                proof: vec![],
             }),
             QueryRequest::ViewCode { .. } => Ok(QueryResponse {
                 kind: QueryResponseKind::ViewCode(ContractCodeView {
@@ -1324,6 +1326,7 @@ impl RuntimeAdapter for KeyValueRuntime {
                 }),
                 block_height,
                 block_hash: *block_hash,
+                proof: vec![],
             }),
             QueryRequest::ViewAccessKeyList { .. } => Ok(QueryResponse {
                 kind: QueryResponseKind::AccessKeyList(AccessKeyList {
@@ -1334,19 +1337,23 @@ impl RuntimeAdapter for KeyValueRuntime {
                 }),
                 block_height,
                 block_hash: *block_hash,
+                proof: vec![],
             }),
             QueryRequest::ViewAccessKey { .. } => Ok(QueryResponse {
                 kind: QueryResponseKind::AccessKey(AccessKey::full_access().into()),
                 block_height,
                 block_hash: *block_hash,
+                proof: vec![],
             }),
             QueryRequest::ViewState { .. } => Ok(QueryResponse {
                 kind: QueryResponseKind::ViewState(ViewStateResult {
                     values: Default::default(),
                     proof: vec![],
+                    continuation_token: None,
                 }),
                 block_height,
                 block_hash: *block_hash,
+                proof: vec![],
             }),
             QueryRequest::CallFunction { .. } => Ok(QueryResponse {
                 kind: QueryResponseKind::CallResult(CallResult {
@@ -1355,10 +1362,76 @@ impl RuntimeAdapter for KeyValueRuntime {
                 }),
                 block_height,
                 block_hash: *block_hash,
+                proof: vec![],
             }),
         }
     }
 
+    fn simulate_transaction(
+        &self,
+        _state_roots: &HashMap<ShardUId, StateRoot>,
+        _block_height: BlockHeight,
+        _block_timestamp: u64,
+        _prev_block_hash: &CryptoHash,
+        _block_hash: &CryptoHash,
+        _epoch_id: &EpochId,
+        gas_price: Balance,
+        transaction: &SignedTransaction,
+        _verify_signature: bool,
+        _time_limit: Duration,
+    ) -> Result<SimulationResult, SimulationError> {
+        // The transaction is converted into a single receipt which always
+        // succeeds, the state is not looked at.
+        let tx = &transaction.transaction;
+        let receipt = Receipt::V0(ReceiptV0 {
+            predecessor_id: tx.signer_id().clone(),
+            receiver_id: tx.receiver_id().clone(),
+            receipt_id: create_receipt_nonce(
+                tx.signer_id().clone(),
+                tx.receiver_id().clone(),
+                0,
+                tx.nonce(),
+            ),
+            receipt: ReceiptEnum::Action(ActionReceipt {
+                signer_id: tx.signer_id().clone(),
+                signer_public_key: tx.public_key().clone(),
+                gas_price,
+                output_data_receivers: vec![],
+                input_data_ids: vec![],
+                actions: tx.actions().to_vec(),
+            }),
+        });
+        let outcome = |id, executor_id: &AccountId, status, receipt_ids| ExecutionOutcomeWithId {
+            id,
+            outcome: ExecutionOutcome {
+                status,
+                logs: vec![],
+                receipt_ids,
+                gas_burnt: 0,
+                compute_usage: Some(0),
+                tokens_burnt: 0,
+                executor_id: executor_id.clone(),
+                metadata: ExecutionMetadata::V1,
+            },
+        };
+        Ok(SimulationResult {
+            transaction_outcome: outcome(
+                transaction.get_hash(),
+                tx.signer_id(),
+                ExecutionStatus::SuccessReceiptId(*receipt.receipt_id()),
+                vec![*receipt.receipt_id()],
+            ),
+            receipt_outcomes: vec![outcome(
+                *receipt.receipt_id(),
+                tx.receiver_id(),
+                ExecutionStatus::SuccessValue(vec![]),
+                vec![],
+            )],
+            receipts: vec![receipt],
+            state_changes: vec![],
+        })
+    }
+
     fn obtain_state_part(
         &self,
         _shard_id: ShardId,
@@ -42,7 +42,9 @@ use near_store::flat::FlatStorageManager;
 use near_store::{PartialStorage, ShardTries, Store, Trie, WrappedTrieChanges};
 use near_vm_runner::ContractCode;
 use near_vm_runner::ContractRuntimeCache;
+pub use node_runtime::simulation::{SimulationError, SimulationResult};
 use num_rational::Rational32;
+use std::collections::HashMap;
 use tracing::instrument;
 
 #[derive(Eq, PartialEq, Debug, Clone)]
@@ -488,6 +490,25 @@ pub trait RuntimeAdapter: Send + Sync {
         request: &QueryRequest,
     ) -> Result<QueryResponse, near_chain_primitives::error::QueryError>;
 
+    /// Dry runs the transaction and all the receipts it produces on top of
+    /// the post state of the given block. `state_roots` hold the post state
+    /// roots of the shards available to the node, the simulation fails if
+    /// the transaction touches any other shard or takes longer than
+    /// `time_limit`. Nothing is persisted.
+    fn simulate_transaction(
+        &self,
+        state_roots: &HashMap<ShardUId, StateRoot>,
+        block_height: BlockHeight,
+        block_timestamp: u64,
+        prev_block_hash: &CryptoHash,
+        block_hash: &CryptoHash,
+        epoch_id: &EpochId,
+        gas_price: Balance,
+        transaction: &SignedTransaction,
+        verify_signature: bool,
+        time_limit: Duration,
+    ) -> Result<SimulationResult, SimulationError>;
+
     /// Get part of the state corresponding to the given state root.
     /// `prev_hash` is a block whose post state root is `state_root`.
     /// Returns error when storage is inconsistent.
@@ -595,7 +616,7 @@ mod tests {
         assert!(b1.header().verify_block_producer(&signer.public_key()));
         let other_signer = create_test_signer("other2");
         let approvals =
-            vec![Some(Box::new(Approval::new(*b1.hash(), 1, 2, &other_signer).signature))];
+            vec![Some(Box::new(Approval::new(*b1.hash(), 1, 2, &other_signer).unwrap().signature))];
         let b2 =
             TestBlockBuilder::new(Clock::real(), &b1, signer.clone()).approvals(approvals).build();
         b2.header().verify_block_producer(&signer.public_key());
@@ -3,6 +3,7 @@ use std::collections::HashMap;
 use actix::Message;
 use itertools::Itertools;
 
+use near_chain_configs::NonceReplacementPolicy;
 use near_pool::types::TransactionGroupIterator;
 use near_pool::{InsertTransactionResult, PoolIteratorWrapper, TransactionPool};
 use near_primitives::shard_layout::{account_id_to_shard_uid, ShardLayout, ShardUId};
@@ -41,14 +42,32 @@ pub struct ShardedTransactionPool {
     /// while keeping the security of randomization of transactions in pool
     rng_seed: RngSeed,
 
-    /// If set, new transactions that bring the size of the pool over this limit will be rejected.
+    /// If set, new transactions that bring the size of the pool over this limit evict groups with
+    /// a lower priority, or are rejected if there are not enough of them.
     /// The size is tracked and enforced separately for each shard.
     pool_size_limit: Option<u64>,
+
+    /// If set, a (signer, public key) pair can't have more transactions in the pool of a shard.
+    max_transactions_per_key: Option<usize>,
+
+    /// What to do with a new transaction with the same nonce as a transaction in the pool.
+    nonce_replacement: NonceReplacementPolicy,
 }
 
 impl ShardedTransactionPool {
-    pub fn new(rng_seed: RngSeed, pool_size_limit: Option<u64>) -> Self {
-        Self { tx_pools: HashMap::new(), rng_seed, pool_size_limit }
+    pub fn new(
+        rng_seed: RngSeed,
+        pool_size_limit: Option<u64>,
+        max_transactions_per_key: Option<usize>,
+        nonce_replacement: NonceReplacementPolicy,
+    ) -> Self {
+        Self {
+            tx_pools: HashMap::new(),
+            rng_seed,
+            pool_size_limit,
+            max_transactions_per_key,
+            nonce_replacement,
+        }
     }
 
     pub fn get_pool_iterator(&mut self, shard_uid: ShardUId) -> Option<PoolIteratorWrapper<'_>> {
@@ -87,6 +106,8 @@ impl ShardedTransactionPool {
             TransactionPool::new(
                 Self::random_seed(&self.rng_seed, shard_uid.shard_id()),
                 self.pool_size_limit,
+                self.max_transactions_per_key,
+                self.nonce_replacement,
                 &shard_uid.to_string(),
             )
         })
@@ -112,7 +133,9 @@ impl ShardedTransactionPool {
         for tx in transactions {
             reintroduced_count += match pool.insert_transaction(tx.clone()) {
                 InsertTransactionResult::Success | InsertTransactionResult::Duplicate => 1,
-                InsertTransactionResult::NoSpaceLeft => 0,
+                InsertTransactionResult::NoSpaceLeft
+                | InsertTransactionResult::Underpriced
+                | InsertTransactionResult::KeyLimitReached => 0,
             }
         }
         reintroduced_count
@@ -155,6 +178,7 @@ impl ShardedTransactionPool {
 #[cfg(test)]
 mod tests {
     use crate::client::ShardedTransactionPool;
+    use near_chain_configs::NonceReplacementPolicy;
     use near_crypto::{InMemorySigner, KeyType};
     use near_o11y::testonly::init_test_logger;
     use near_pool::types::TransactionGroupIterator;
@@ -196,7 +220,8 @@ mod tests {
         let old_shard_layout = ShardLayout::get_simple_nightshade_layout();
         let new_shard_layout = ShardLayout::get_simple_nightshade_layout_v2();
 
-        let mut pool = ShardedTransactionPool::new(TEST_SEED, None);
+        let mut pool =
+            ShardedTransactionPool::new(TEST_SEED, None, None, NonceReplacementPolicy::Disabled);
 
         let mut shard_id_to_accounts: HashMap<ShardId, _> = HashMap::new();
         shard_id_to_accounts.insert(ShardId::new(0), vec!["aaa", "abcd", "a-a-a-a-a"]);
@@ -2,6 +2,7 @@
 //! without backwards compatibility of JSON encoding.
 use crate::types::StatusError;
 use near_primitives::congestion_info::CongestionInfo;
+use near_primitives::network::PeerId;
 use near_primitives::types::{EpochId, ShardId};
 use near_primitives::views::{
     CatchupStatusView, ChainProcessingInfo, EpochValidatorInfo, RequestedStatePartsView,
@@ -174,6 +175,43 @@ pub struct ValidatorStatus {
     pub banned_chunk_producers: Vec<(EpochId, Vec<AccountId>)>,
 }
 
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct StateSyncProgressView {
+    // Download of the state of the node's state sync, if it's syncing state.
+    pub state_sync: Option<StateDownloadProgressView>,
+    // Downloads of the state of the shards caught up for the next epochs.
+    pub catchups: Vec<StateDownloadProgressView>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct StateDownloadProgressView {
+    pub sync_hash: CryptoHash,
+    // Shards whose state is still being synced.
+    pub shards: Vec<ShardStateDownloadProgressView>,
+    // Snapshot hosts that parts were requested from.
+    pub snapshot_hosts: Vec<SnapshotHostStatsView>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct ShardStateDownloadProgressView {
+    pub shard_id: ShardId,
+    pub status: String,
+    // Not known until the state header is downloaded.
+    pub num_parts: Option<u64>,
+    pub num_parts_done: u64,
+    pub num_failed_attempts: u64,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct SnapshotHostStatsView {
+    pub peer_id: PeerId,
+    // Moving average of the throughput of the part downloads from the host.
+    pub throughput_bytes_per_sec: Option<u64>,
+    pub num_successes: u64,
+    pub num_failures: u64,
+    pub num_requests_in_flight: usize,
+}
+
 // Different debug requests that can be sent by HTML pages, via GET.
 #[derive(Debug)]
 pub enum DebugStatus {
@@ -193,6 +231,8 @@ pub enum DebugStatus {
     ChainProcessingStatus,
     // The state parts already requested.
     RequestedStateParts,
+    // Progress of the state part downloads of state sync and catchups.
+    StateSyncProgress,
 }
 
 impl actix::Message for DebugStatus {
@@ -214,4 +254,6 @@ pub enum DebugStatusResponse {
     ChainProcessingStatus(ChainProcessingInfo),
     // The state parts already requested.
     RequestedStateParts(Vec<RequestedStatePartsView>),
+    // Progress of the state part downloads of state sync and catchups.
+    StateSyncProgress(StateSyncProgressView),
 }
@@ -4,16 +4,18 @@ use near_primitives::hash::CryptoHash;
 use near_primitives::merkle::{MerklePath, PartialMerkleTree};
 use near_primitives::network::PeerId;
 use near_primitives::sharding::{ChunkHash, ShardChunk};
+use near_primitives::transaction::SignedTransaction;
 use near_primitives::types::{
     AccountId, BlockHeight, BlockReference, EpochId, EpochReference, MaybeBlockId, ShardId,
     TransactionOrReceiptId,
 };
 use near_primitives::views::validator_stake_view::ValidatorStakeView;
 use near_primitives::views::{
-    BlockView, ChunkView, EpochValidatorInfo, ExecutionOutcomeWithIdView, GasPriceView,
-    LightClientBlockLiteView, LightClientBlockView, MaintenanceWindowsView, QueryRequest,
-    QueryResponse, ReceiptView, SplitStorageInfoView, StateChangesKindsView,
-    StateChangesRequestView, StateChangesView, StateSyncStatusView, SyncStatusView, TxStatusView,
+    AccessKeyView, BlockView, ChunkView, EpochValidatorInfo, ExecutionOutcomeWithIdView,
+    FinalExecutionOutcomeWithReceiptView, GasPriceView, LightClientBlockLiteView,
+    LightClientBlockView, MaintenanceWindowsView, QueryRequest, QueryResponse, ReceiptView,
+    SplitStorageInfoView, StateChangesKindsView, StateChangesRequestView, StateChangesView,
+    StateSyncStatusView, SyncStatusView, TxStatusView,
 };
 pub use near_primitives::views::{StatusResponse, StatusSyncInfo};
 use near_time::Duration;
@@ -619,6 +621,93 @@ impl Message for TxStatus {
     type Result = Result<TxStatusView, TxStatusError>;
 }
 
+/// Executes the transaction and all the receipts it produces on top of the
+/// state of the given block without persisting anything.
+#[derive(Debug)]
+pub struct SimulateTransaction {
+    pub block_reference: BlockReference,
+    pub transaction: SignedTransaction,
+    /// Transactions built without a private key carry an empty signature
+    /// which is not checked.
+    pub verify_signature: bool,
+}
+
+#[derive(Debug)]
+pub struct SimulateTransactionResponse {
+    pub block_hash: CryptoHash,
+    pub block_height: BlockHeight,
+    pub outcome: FinalExecutionOutcomeWithReceiptView,
+    pub state_changes: StateChangesView,
+}
+
+impl Message for SimulateTransaction {
+    type Result = Result<SimulateTransactionResponse, SimulateTransactionError>;
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum SimulateTransactionError {
+    #[error("There are no fully synchronized blocks on the node yet")]
+    NoSyncedBlocks,
+    #[error("Block either has never been observed on the node or has been garbage collected: {block_reference:?}")]
+    UnknownBlock { block_reference: BlockReference },
+    #[error("The node does not track the shard ID {requested_shard_id}")]
+    UnavailableShard { requested_shard_id: ShardId },
+    #[error("Transaction is invalid: {error}")]
+    InvalidTransaction { error: near_primitives::errors::InvalidTxError },
+    #[error("Transaction produced more than {limit} receipts")]
+    TooManyReceipts { limit: usize },
+    #[error("Transaction and its receipts burnt more than {limit} gas")]
+    GasLimitExceeded { limit: near_primitives::types::Gas },
+    #[error("Simulation took longer than {limit:?}")]
+    TimeLimitExceeded { limit: std::time::Duration },
+    #[error("The node reached its limits. Try again later. More details: {error_message}")]
+    InternalError { error_message: String },
+}
+
+impl From<near_chain_primitives::Error> for SimulateTransactionError {
+    fn from(error: near_chain_primitives::Error) -> Self {
+        Self::InternalError { error_message: error.to_string() }
+    }
+}
+
+/// Looks up the access keys with the given public key in
+/// `DBCol::AccountsByPublicKey` and returns the ones that exist in the state
+/// of the given block.
+#[derive(Debug)]
+pub struct GetAccountsByPublicKey {
+    pub public_key: near_crypto::PublicKey,
+    pub block_reference: BlockReference,
+}
+
+#[derive(Debug)]
+pub struct GetAccountsByPublicKeyResponse {
+    pub block_hash: CryptoHash,
+    pub block_height: BlockHeight,
+    pub keys: Vec<(AccountId, AccessKeyView)>,
+}
+
+impl Message for GetAccountsByPublicKey {
+    type Result = Result<GetAccountsByPublicKeyResponse, GetAccountsByPublicKeyError>;
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum GetAccountsByPublicKeyError {
+    #[error("The node does not maintain the index of accounts by public key")]
+    IndexUnavailable,
+    #[error("There are no fully synchronized blocks on the node yet")]
+    NoSyncedBlocks,
+    #[error("Block either has never been observed on the node or has been garbage collected: {block_reference:?}")]
+    UnknownBlock { block_reference: BlockReference },
+    #[error("The node reached its limits. Try again later. More details: {error_message}")]
+    InternalError { error_message: String },
+}
+
+impl From<near_chain_primitives::Error> for GetAccountsByPublicKeyError {
+    fn from(error: near_chain_primitives::Error) -> Self {
+        Self::InternalError { error_message: error.to_string() }
+    }
+}
+
 #[derive(Debug)]
 pub struct GetValidatorInfo {
     pub epoch_reference: EpochReference,
@@ -40,14 +40,14 @@ use near_chunks::logic::{
     cares_about_shard_this_or_next_epoch, decode_encoded_chunk, persist_chunk,
 };
 use near_chunks::shards_manager_actor::ShardsManagerActor;
-use near_client_primitives::debug::ChunkProduction;
+use near_client_primitives::debug::{ChunkProduction, StateSyncProgressView};
 use near_client_primitives::types::{Error, StateSyncStatus};
 use near_epoch_manager::shard_tracker::ShardTracker;
 use near_epoch_manager::EpochManagerAdapter;
 use near_network::client::ProcessTxResponse;
 use near_network::types::{AccountKeys, ChainInfo, PeerManagerMessageRequest, SetChainInfo};
 use near_network::types::{
-    HighestHeightPeerInfo, NetworkRequests, PeerManagerAdapter, ReasonForBan,
+    HighestHeightPeerInfo, NetworkRequests, PeerManagerAdapter, ReasonForBan, SnapshotHostInfo,
 };
 
 use near_pool::InsertTransactionResult;
@@ -200,6 +200,8 @@ pub struct Client {
     chunk_distribution_network: Option<ChunkDistributionNetwork>,
     /// Upgrade schedule which determines when the client starts voting for new protocol versions.
     upgrade_schedule: ProtocolUpgradeVotingSchedule,
+    /// Notified with the new head after processing blocks which changed it.
+    chain_head_sender: tokio::sync::watch::Sender<Tip>,
 }
 
 impl AsRef<Client> for Client {
@@ -282,8 +284,12 @@ impl Client {
             resharding_sender.clone(),
         )?;
         chain.init_flat_storage()?;
-        let sharded_tx_pool =
-            ShardedTransactionPool::new(rng_seed, config.transaction_pool_size_limit);
+        let sharded_tx_pool = ShardedTransactionPool::new(
+            rng_seed,
+            config.transaction_pool_size_limit,
+            config.transaction_pool_max_transactions_per_key,
+            config.transaction_pool_nonce_replacement,
+        );
         let sync_status = SyncStatus::AwaitingPeers;
         let epoch_sync = EpochSync::new(
             clock.clone(),
@@ -351,6 +357,7 @@ impl Client {
             async_computation_spawner,
         );
         let chunk_distribution_network = ChunkDistributionNetwork::from_config(&config);
+        let (chain_head_sender, _) = tokio::sync::watch::channel(chain.head()?);
         Ok(Self {
             #[cfg(feature = "test_features")]
             adv_produce_blocks: None,
@@ -403,9 +410,16 @@ impl Client {
             partial_witness_adapter,
             chunk_distribution_network,
             upgrade_schedule,
+            chain_head_sender,
         })
     }
 
+    /// Returns a receiver which sees the new head whenever processed blocks
+    /// change it.
+    pub fn subscribe_to_chain_head(&self) -> tokio::sync::watch::Receiver<Tip> {
+        self.chain_head_sender.subscribe()
+    }
+
     // Checks if it's been at least `stall_timeout` since the last time the head was updated, or
     // this method was called. If yes, rebroadcasts the current head.
     pub fn check_head_progress_stalled(&mut self, stall_timeout: Duration) -> Result<(), Error> {
@@ -820,7 +834,8 @@ impl Client {
             block_merkle_root,
             self.clock.clone(),
             sandbox_delta_time,
-        );
+        )
+        .map_err(|err| Error::BlockProducer(format!("Failed to sign the block: {err}")))?;
 
         // Update latest known even before returning block out, to prevent race conditions.
         self.chain
@@ -1106,7 +1121,13 @@ impl Client {
     ) {
         if let Some(validator_signer) = &signer {
             for body in challenges {
-                let challenge = Challenge::produce(body, &**validator_signer);
+                let challenge = match Challenge::produce(body, &**validator_signer) {
+                    Ok(challenge) => challenge,
+                    Err(err) => {
+                        error!(target: "client", ?err, "Failed to sign a challenge");
+                        continue;
+                    }
+                };
                 self.challenges.insert(challenge.hash, challenge.clone());
                 self.network_adapter.send(PeerManagerMessageRequest::NetworkRequests(
                     NetworkRequests::Challenge(challenge),
@@ -1339,24 +1360,24 @@ impl Client {
         // Send out challenge if the block was found to be invalid.
         if let Some(signer) = signer {
             if let Err(e) = &result {
-                match e {
+                let body = match e {
                     near_chain::Error::InvalidChunkProofs(chunk_proofs) => {
-                        self.network_adapter.send(PeerManagerMessageRequest::NetworkRequests(
-                            NetworkRequests::Challenge(Challenge::produce(
-                                ChallengeBody::ChunkProofs(*chunk_proofs.clone()),
-                                &*signer,
-                            )),
-                        ));
+                        Some(ChallengeBody::ChunkProofs(*chunk_proofs.clone()))
                     }
                     near_chain::Error::InvalidChunkState(chunk_state) => {
-                        self.network_adapter.send(PeerManagerMessageRequest::NetworkRequests(
-                            NetworkRequests::Challenge(Challenge::produce(
-                                ChallengeBody::ChunkState(*chunk_state.clone()),
-                                &*signer,
-                            )),
-                        ));
+                        Some(ChallengeBody::ChunkState(*chunk_state.clone()))
+                    }
+                    _ => None,
+                };
+                if let Some(body) = body {
+                    match Challenge::produce(body, &*signer) {
+                        Ok(challenge) => {
+                            self.network_adapter.send(PeerManagerMessageRequest::NetworkRequests(
+                                NetworkRequests::Challenge(challenge),
+                            ));
+                        }
+                        Err(err) => error!(target: "client", ?err, "Failed to sign a challenge"),
                     }
-                    _ => {}
                 }
             }
         }
@@ -1381,9 +1402,13 @@ impl Client {
             &mut block_processing_artifacts,
             apply_chunks_done_sender,
         );
-        if accepted_blocks.iter().any(|accepted_block| accepted_block.status.is_new_head()) {
+        let new_head = accepted_blocks
+            .iter()
+            .any(|accepted_block| accepted_block.status.is_new_head())
+            .then(|| self.chain.head().unwrap());
+        if let Some(head) = &new_head {
             self.shards_manager_adapter.send(ShardsManagerRequestFromClient::UpdateChainHeads {
-                head: self.chain.head().unwrap(),
+                head: head.clone(),
                 header_head: self.chain.header_head().unwrap(),
             });
         }
@@ -1401,6 +1426,9 @@ impl Client {
         }
         self.last_time_head_progress_made =
             max(self.chain.get_last_time_head_updated(), self.last_time_head_progress_made);
+        if let Some(head) = new_head {
+            self.chain_head_sender.send_replace(head);
+        }
         (accepted_blocks_hashes, errors)
     }
 
@@ -2422,6 +2450,14 @@ impl Client {
                             trace!(target: "client", ?shard_uid, tx_hash = ?tx.get_hash(), "Duplicate transaction, not forwarding it.");
                             return Ok(ProcessTxResponse::ValidTx);
                         }
+                        InsertTransactionResult::Underpriced => {
+                            trace!(target: "client", ?shard_uid, tx_hash = ?tx.get_hash(), "Transaction with the same nonce and a higher priority fee is in the pool, not forwarding it.");
+                            return Ok(ProcessTxResponse::ReplacementUnderpriced);
+                        }
+                        InsertTransactionResult::KeyLimitReached => {
+                            trace!(target: "client", ?shard_uid, tx_hash = ?tx.get_hash(), "Too many transactions of the access key in the pool, not forwarding it.");
+                            return Ok(ProcessTxResponse::TooManyPendingTransactions);
+                        }
                         InsertTransactionResult::NoSpaceLeft => {
                             if is_forwarded {
                                 trace!(target: "client", ?shard_uid, tx_hash = ?tx.get_hash(), "Transaction pool is full, dropping the transaction.");
@@ -2541,6 +2577,7 @@ impl Client {
     pub fn run_catchup(
         &mut self,
         highest_height_peers: &[HighestHeightPeerInfo],
+        snapshot_hosts: &[Arc<SnapshotHostInfo>],
         block_catch_up_task_scheduler: &Sender<BlockCatchUpRequest>,
         apply_chunks_done_sender: Option<Sender<ApplyChunksDoneMessage>>,
         signer: &Option<Arc<ValidatorSigner>>,
@@ -2600,6 +2637,7 @@ impl Client {
                 sync_hash,
                 status,
                 highest_height_peers,
+                snapshot_hosts,
                 state_sync_info.shards(),
             )? {
                 StateSyncResult::InProgress => {}
@@ -2812,4 +2850,21 @@ impl Client {
         }
         Ok(ret)
     }
+
+    pub fn get_state_sync_progress(&self) -> StateSyncProgressView {
+        let state_sync = match &self.sync_status {
+            SyncStatus::StateSync(status) => {
+                Some(self.state_sync.get_progress_view(status.sync_hash))
+            }
+            _ => None,
+        };
+        let catchups = self
+            .catchup_state_syncs
+            .iter()
+            .map(|(sync_hash, CatchupState { state_sync, .. })| {
+                state_sync.get_progress_view(*sync_hash)
+            })
+            .collect();
+        StateSyncProgressView { state_sync, catchups }
+    }
 }
@@ -117,6 +117,8 @@ pub struct StartClientResult {
     pub client_actor: actix::Addr<ClientActor>,
     pub client_arbiter_handle: actix::ArbiterHandle,
     pub resharding_handle: ReshardingHandle,
+    /// Sees the new head whenever processed blocks change it.
+    pub chain_head: tokio::sync::watch::Receiver<Tip>,
 }
 
 /// Starts client in a separate Arbiter (thread).
@@ -170,6 +172,7 @@ pub fn start_client(
     )
     .unwrap();
     let resharding_handle = client.chain.resharding_manager.resharding_handle.clone();
+    let chain_head = client.subscribe_to_chain_head();
 
     let client_sender_for_sync_jobs = LateBoundSender::<ClientSenderForSyncJobs>::new();
     let sync_jobs_actor = SyncJobsActor::new(client_sender_for_sync_jobs.as_multi_sender());
@@ -200,7 +203,12 @@ pub fn start_client(
     chain_sender_for_state_sync
         .bind(client_addr.clone().with_auto_span_context().into_multi_sender());
 
-    StartClientResult { client_actor: client_addr, client_arbiter_handle, resharding_handle }
+    StartClientResult {
+        client_actor: client_addr,
+        client_arbiter_handle,
+        resharding_handle,
+        chain_head,
+    }
 }
 
 #[derive(Clone, MultiSend, MultiSenderFrom)]
@@ -375,6 +383,7 @@ impl ClientActorInner {
                 known_producers: vec![],
                 tier1_accounts_keys: vec![],
                 tier1_accounts_data: vec![],
+                snapshot_hosts: vec![],
             },
             last_validator_announce_time: None,
             info_helper,
@@ -965,10 +974,15 @@ impl ClientActorInner {
         // Check client is part of the futures validators
         if self.client.is_validator(&next_epoch_id, &prev_block_hash, validator_signer) {
             debug!(target: "client", "Sending announce account for {}", signer.validator_id());
-            self.last_validator_announce_time = Some(now);
-
             let announce_account =
-                AnnounceAccount::new(signer.as_ref(), self.node_id.clone(), next_epoch_id);
+                match AnnounceAccount::new(signer.as_ref(), self.node_id.clone(), next_epoch_id) {
+                    Ok(announce_account) => announce_account,
+                    Err(err) => {
+                        error!(target: "client", ?err, "Failed to sign the account announcement");
+                        return;
+                    }
+                };
+            self.last_validator_announce_time = Some(now);
             self.network_adapter.send(PeerManagerMessageRequest::NetworkRequests(
                 NetworkRequests::AnnounceAccount(announce_account),
             ));
@@ -1570,6 +1584,7 @@ impl ClientActorInner {
             let validator_signer = self.client.validator_signer.get();
             if let Err(err) = self.client.run_catchup(
                 &self.network_info.highest_height_peers,
+                &self.network_info.snapshot_hosts,
                 &self.sync_jobs_sender.block_catch_up,
                 Some(self.myself_sender.apply_chunks_done.clone()),
                 &validator_signer,
@@ -1733,6 +1748,7 @@ impl ClientActorInner {
             sync_hash,
             state_sync_status,
             &self.network_info.highest_height_peers,
+            &self.network_info.snapshot_hosts,
             &shards_to_sync,
         );
         let state_sync_result = unwrap_and_report_state_sync_result!(state_sync_result);
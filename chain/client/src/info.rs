@@ -620,7 +620,10 @@ impl InfoHelper {
         // Sign telemetry if there is a signer present.
         if let Some(signer) = signer {
             let content = serde_json::to_string(&json).expect("Telemetry must serialize to JSON");
-            json["signature"] = signer.sign_bytes(content.as_bytes()).to_string().into();
+            match signer.sign_bytes(content.as_bytes()) {
+                Ok(signature) => json["signature"] = signature.to_string().into(),
+                Err(err) => tracing::warn!(target: "stats", ?err, "Failed to sign telemetry"),
+            }
         }
         json
     }
@@ -1055,6 +1058,7 @@ mod tests {
                 tier1_connections: vec![],
                 tier1_accounts_keys: vec![],
                 tier1_accounts_data: vec![],
+                snapshot_hosts: vec![],
             },
             &config,
             0.0,
@@ -1,11 +1,12 @@
 pub use near_client_primitives::types::{
-    Error, GetBlock, GetBlockProof, GetBlockProofResponse, GetBlockWithMerkleTree, GetChunk,
-    GetClientConfig, GetExecutionOutcome, GetExecutionOutcomeResponse,
-    GetExecutionOutcomesForBlock, GetGasPrice, GetMaintenanceWindows, GetNetworkInfo,
-    GetNextLightClientBlock, GetProtocolConfig, GetReceipt, GetShardChunk, GetSplitStorageInfo,
-    GetStateChanges, GetStateChangesInBlock, GetStateChangesWithCauseInBlock,
+    Error, GetAccountsByPublicKey, GetAccountsByPublicKeyError, GetBlock, GetBlockProof,
+    GetBlockProofResponse, GetBlockWithMerkleTree, GetChunk, GetClientConfig, GetExecutionOutcome,
+    GetExecutionOutcomeResponse, GetExecutionOutcomesForBlock, GetGasPrice, GetMaintenanceWindows,
+    GetNetworkInfo, GetNextLightClientBlock, GetProtocolConfig, GetReceipt, GetShardChunk,
+    GetSplitStorageInfo, GetStateChanges, GetStateChangesInBlock, GetStateChangesWithCauseInBlock,
     GetStateChangesWithCauseInBlockForTrackedShards, GetValidatorInfo, GetValidatorOrdered, Query,
-    QueryError, Status, StatusResponse, SyncStatus, TxStatus, TxStatusError,
+    QueryError, SimulateTransaction, SimulateTransactionError, Status, StatusResponse, SyncStatus,
+    TxStatus, TxStatusError,
 };
 
 pub use crate::client::{Client, ProduceChunkResult};
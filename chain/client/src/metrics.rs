@@ -521,6 +521,16 @@ pub(crate) static STATE_SYNC_EXTERNAL_PARTS_SIZE_DOWNLOADED: LazyLock<IntCounter
         .unwrap()
     });
 
+pub(crate) static STATE_SYNC_EXTERNAL_HTTP_MIRROR_REQUESTS: LazyLock<IntCounterVec> =
+    LazyLock::new(|| {
+        try_create_int_counter_vec(
+            "near_state_sync_external_http_mirror_requests_total",
+            "Requests to HTTP mirrors of the state dumps by result",
+            &["mirror", "result"],
+        )
+        .unwrap()
+    });
+
 pub(crate) static STATE_SYNC_DUMP_PUT_OBJECT_ELAPSED: LazyLock<HistogramVec> =
     LazyLock::new(|| {
         try_create_histogram_vec(
@@ -229,7 +229,7 @@ impl PartialWitnessActor {
                 },
                 &chunk_validators,
                 &signer,
-            );
+            )?;
         }
 
         let witness_bytes = compress_witness(&state_witness)?;
@@ -268,7 +268,7 @@ impl PartialWitnessActor {
         let encoder = self.witness_encoders.entry(chunk_validators.len());
         let (parts, encoded_length) = encoder.encode(&witness_bytes);
 
-        Ok(chunk_validators
+        chunk_validators
             .iter()
             .zip_eq(parts)
             .enumerate()
@@ -282,10 +282,10 @@ impl PartialWitnessActor {
                     part.unwrap().to_vec(),
                     encoded_length,
                     signer,
-                );
-                (chunk_validator.clone(), partial_witness)
+                )?;
+                Ok::<_, Error>((chunk_validator.clone(), partial_witness))
             })
-            .collect_vec())
+            .collect()
     }
 
     fn generate_contract_deploys_parts(
@@ -304,7 +304,7 @@ impl PartialWitnessActor {
         let (parts, encoded_length) = encoder.encode(&deploys);
         let signer = self.my_validator_signer()?;
 
-        Ok(validators
+        validators
             .into_iter()
             .zip_eq(parts)
             .enumerate()
@@ -317,10 +317,10 @@ impl PartialWitnessActor {
                         encoded_length,
                     },
                     &signer,
-                );
-                (validator, partial_deploys)
+                )?;
+                Ok::<_, Error>((validator, partial_deploys))
             })
-            .collect_vec())
+            .collect()
     }
 
     // Break the state witness into parts and send each part to the corresponding chunk validator owner.
@@ -589,7 +589,7 @@ impl PartialWitnessActor {
             missing_contract_hashes,
             accesses.main_transition().clone(),
             &signer,
-        );
+        )?;
         self.network_adapter.send(PeerManagerMessageRequest::NetworkRequests(
             NetworkRequests::ContractCodeRequest(random_chunk_producer, request),
         ));
@@ -606,7 +606,7 @@ impl PartialWitnessActor {
         main_transition: MainTransitionKey,
         chunk_validators: &[AccountId],
         my_signer: &ValidatorSigner,
-    ) {
+    ) -> Result<(), Error> {
         let chunk_producers: HashSet<AccountId> = self
             .epoch_manager
             .get_epoch_chunk_producers_for_shard(&key.epoch_id, key.shard_id)
@@ -620,12 +620,12 @@ impl PartialWitnessActor {
             .filter(|validator| !chunk_producers.contains(*validator))
             .cloned()
             .collect();
+        let accesses =
+            ChunkContractAccesses::new(key, contract_accesses, main_transition, my_signer)?;
         self.network_adapter.send(PeerManagerMessageRequest::NetworkRequests(
-            NetworkRequests::ChunkContractAccesses(
-                target_chunk_validators,
-                ChunkContractAccesses::new(key, contract_accesses, main_transition, my_signer),
-            ),
+            NetworkRequests::ChunkContractAccesses(target_chunk_validators, accesses),
         ));
+        Ok(())
     }
 
     /// Retrieves the code for the given contract hashes and distributes them to validator in parts.
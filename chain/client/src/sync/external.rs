@@ -1,4 +1,5 @@
 use crate::metrics;
+use crate::sync::external_http::HttpMirrors;
 use futures::TryStreamExt;
 use near_primitives::types::{EpochId, ShardId};
 use std::io::{Read, Write};
@@ -9,8 +10,20 @@ use std::time::Instant;
 
 #[derive(Debug, Clone)]
 pub enum StateFileType {
-    StatePart { part_id: u64, num_parts: u64 },
+    StatePart {
+        part_id: u64,
+        num_parts: u64,
+    },
     StateHeader,
+    /// Manifest of the state parts dumped with the content-addressed layout,
+    /// see `crate::sync::external_manifest`.
+    StatePartsManifest,
+    /// Entry of a dumped part in the manifest. The manifest is assembled from
+    /// the entries once all parts are dumped.
+    StatePartsManifestEntry {
+        part_id: u64,
+        num_parts: u64,
+    },
 }
 
 impl ToString for StateFileType {
@@ -18,6 +31,8 @@ impl ToString for StateFileType {
         match self {
             StateFileType::StatePart { .. } => StateFileType::part_str(),
             StateFileType::StateHeader => StateFileType::header_str(),
+            StateFileType::StatePartsManifest => StateFileType::manifest_str(),
+            StateFileType::StatePartsManifestEntry { .. } => StateFileType::manifest_entry_str(),
         }
     }
 }
@@ -31,12 +46,22 @@ impl StateFileType {
         String::from("header")
     }
 
+    pub fn manifest_str() -> String {
+        String::from("manifest")
+    }
+
+    pub fn manifest_entry_str() -> String {
+        String::from("manifest_entry")
+    }
+
     pub fn filename(&self) -> String {
         match self {
-            StateFileType::StatePart { part_id, num_parts } => {
+            StateFileType::StatePart { part_id, num_parts }
+            | StateFileType::StatePartsManifestEntry { part_id, num_parts } => {
                 format!("state_part_{:06}_of_{:06}", part_id, num_parts)
             }
             StateFileType::StateHeader => "header".to_string(),
+            StateFileType::StatePartsManifest => "manifest".to_string(),
         }
     }
 }
@@ -58,17 +83,54 @@ pub enum ExternalConnection {
         reqwest_client: Arc<reqwest::Client>,
         bucket: String,
     },
+    /// Read-only connection to HTTP(S) servers serving copies of the state dumps.
+    HTTP {
+        mirrors: Arc<HttpMirrors>,
+    },
 }
 
 const GCS_ENCODE_SET: &percent_encoding::AsciiSet =
     &percent_encoding::NON_ALPHANUMERIC.remove(b'-').remove(b'.').remove(b'_');
 
+/// Directory under the root of a filesystem connection holding the files
+/// which are being written.
+const TMP_DIR: &str = ".tmp";
+
 impl ExternalConnection {
     pub async fn get_file(
         &self,
         shard_id: ShardId,
         location: &str,
         file_type: &StateFileType,
+    ) -> Result<Vec<u8>, anyhow::Error> {
+        self.get_file_verified(shard_id, location, file_type, &|_| true).await
+    }
+
+    /// Downloads the file and checks it with `verify` before returning it.
+    /// With HTTP mirrors, a file which fails the check is downloaded from the
+    /// next mirror.
+    pub async fn get_file_verified(
+        &self,
+        shard_id: ShardId,
+        location: &str,
+        file_type: &StateFileType,
+        verify: &(dyn Fn(&[u8]) -> bool + Sync),
+    ) -> Result<Vec<u8>, anyhow::Error> {
+        let data = self.get_file_impl(shard_id, location, file_type, verify).await?;
+        // HTTP mirrors verify the data themselves, so that they can fall back
+        // to the next mirror.
+        if !matches!(self, ExternalConnection::HTTP { .. }) {
+            anyhow::ensure!(verify(&data), "Verification of {location} failed");
+        }
+        Ok(data)
+    }
+
+    async fn get_file_impl(
+        &self,
+        shard_id: ShardId,
+        location: &str,
+        file_type: &StateFileType,
+        verify: &(dyn Fn(&[u8]) -> bool + Sync),
     ) -> Result<Vec<u8>, anyhow::Error> {
         let _timer = metrics::STATE_SYNC_EXTERNAL_PARTS_REQUEST_DELAY
             .with_label_values(&[&shard_id.to_string(), &file_type.to_string()])
@@ -113,6 +175,14 @@ impl ExternalConnection {
                     }
                 }
             }
+            ExternalConnection::HTTP { mirrors } => {
+                let bytes = mirrors.get_file(location, verify).await?;
+                tracing::debug!(target: "sync", %shard_id, location, num_bytes = bytes.len(), "HTTP request finished");
+                metrics::STATE_SYNC_EXTERNAL_PARTS_SIZE_DOWNLOADED
+                    .with_label_values(&[&shard_id.to_string(), &file_type.to_string()])
+                    .inc_by(bytes.len() as u64);
+                Ok(bytes)
+            }
         }
     }
 
@@ -154,12 +224,14 @@ impl ExternalConnection {
                 if let Some(parent_dir) = path.parent() {
                     std::fs::create_dir_all(parent_dir)?;
                 }
-                let mut file = std::fs::OpenOptions::new()
-                    .write(true)
-                    .create(true)
-                    .truncate(true)
-                    .open(&path)?;
+                // The file is written under a temporary name and then renamed,
+                // so that a file which exists is always complete. Temporary
+                // files live outside of the listed directories.
+                let tmp_dir = root_dir.join(TMP_DIR);
+                std::fs::create_dir_all(&tmp_dir)?;
+                let mut file = tempfile::NamedTempFile::new_in(&tmp_dir)?;
                 file.write_all(data)?;
+                file.persist(&path)?;
                 tracing::debug!(target: "state_sync_dump", ?shard_id, part_length = data.len(), ?location, ?file_type, "Wrote a state part to a file");
                 Ok(())
             }
@@ -171,6 +243,9 @@ impl ExternalConnection {
                 tracing::debug!(target: "state_sync_dump", ?shard_id, part_length = data.len(), ?location, ?file_type, "Wrote a state part to GCS");
                 Ok(())
             }
+            ExternalConnection::HTTP { .. } => {
+                anyhow::bail!("Writing state parts to HTTP mirrors is not supported")
+            }
         }
     }
 
@@ -241,6 +316,60 @@ impl ExternalConnection {
                     .flatten()
                     .collect())
             }
+            ExternalConnection::HTTP { .. } => {
+                anyhow::bail!("Listing state parts on HTTP mirrors is not supported")
+            }
+        }
+    }
+
+    /// Returns the size of the file at the given location in the external
+    /// storage, or `None` if there is no such file. Used to skip uploading
+    /// content-addressed parts which are already stored.
+    pub async fn stored_file_size(
+        &self,
+        shard_id: ShardId,
+        location: &str,
+    ) -> Result<Option<u64>, anyhow::Error> {
+        let _timer = metrics::STATE_SYNC_DUMP_LIST_OBJECT_ELAPSED
+            .with_label_values(&[&shard_id.to_string()])
+            .start_timer();
+        match self {
+            ExternalConnection::S3 { bucket } => {
+                let list_results = bucket.list(location.to_string(), None).await?;
+                Ok(list_results.into_iter().find_map(|res| {
+                    res.contents.into_iter().find(|obj| obj.key == location).map(|obj| obj.size)
+                }))
+            }
+            ExternalConnection::Filesystem { root_dir } => {
+                match std::fs::metadata(root_dir.join(location)) {
+                    Ok(metadata) => Ok(Some(metadata.len())),
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                    Err(err) => Err(err.into()),
+                }
+            }
+            ExternalConnection::GCS { gcs_client, bucket, .. } => Ok(gcs_client
+                .object()
+                .list(
+                    bucket,
+                    cloud_storage::ListRequest {
+                        prefix: Some(location.to_string()),
+                        ..Default::default()
+                    },
+                )
+                .await?
+                .try_collect::<Vec<cloud_storage::object::ObjectList>>()
+                .await?
+                .into_iter()
+                .find_map(|object_list| {
+                    object_list
+                        .items
+                        .into_iter()
+                        .find(|obj| obj.name == location)
+                        .map(|obj| obj.size)
+                })),
+            ExternalConnection::HTTP { .. } => {
+                anyhow::bail!("Listing state parts on HTTP mirrors is not supported")
+            }
         }
     }
 
@@ -252,7 +381,43 @@ impl ExternalConnection {
         epoch_id: &EpochId,
         epoch_height: u64,
     ) -> Result<bool, anyhow::Error> {
-        let file_type = StateFileType::StateHeader;
+        self.is_file_stored_for_epoch(
+            shard_id,
+            chain_id,
+            epoch_id,
+            epoch_height,
+            StateFileType::StateHeader,
+        )
+        .await
+    }
+
+    /// Check if the manifest of the content-addressed state parts exists in
+    /// the external storage.
+    pub async fn is_state_parts_manifest_stored_for_epoch(
+        &self,
+        shard_id: ShardId,
+        chain_id: &String,
+        epoch_id: &EpochId,
+        epoch_height: u64,
+    ) -> Result<bool, anyhow::Error> {
+        self.is_file_stored_for_epoch(
+            shard_id,
+            chain_id,
+            epoch_id,
+            epoch_height,
+            StateFileType::StatePartsManifest,
+        )
+        .await
+    }
+
+    async fn is_file_stored_for_epoch(
+        &self,
+        shard_id: ShardId,
+        chain_id: &String,
+        epoch_id: &EpochId,
+        epoch_height: u64,
+        file_type: StateFileType,
+    ) -> Result<bool, anyhow::Error> {
         let directory_path = external_storage_location_directory(
             chain_id,
             epoch_id,
@@ -261,17 +426,18 @@ impl ExternalConnection {
             &file_type,
         );
         let file_names = self.list_objects(shard_id, &directory_path).await?;
-        let header_exits = file_names.contains(&file_type.filename());
+        let file_exists = file_names.contains(&file_type.filename());
         tracing::debug!(
             target: "state_sync_dump",
             ?directory_path,
+            file_type = file_type.to_string(),
             "{}",
-            match header_exits {
-                true => "Header has already been dumped.",
-                false => "Header has not been dumped.",
+            match file_exists {
+                true => "File has already been dumped.",
+                false => "File has not been dumped.",
             }
         );
-        Ok(header_exits)
+        Ok(file_exists)
     }
 }
 
@@ -316,6 +482,14 @@ pub fn location_prefix(
             "chain_id={}/epoch_height={}/epoch_id={}/headers/shard_id={}",
             chain_id, epoch_height, epoch_id.0, shard_id
         ),
+        StateFileType::StatePartsManifest => format!(
+            "chain_id={}/epoch_height={}/epoch_id={}/manifests/shard_id={}",
+            chain_id, epoch_height, epoch_id.0, shard_id
+        ),
+        StateFileType::StatePartsManifestEntry { .. } => format!(
+            "chain_id={}/epoch_height={}/epoch_id={}/manifests/shard_id={}/parts",
+            chain_id, epoch_height, epoch_id.0, shard_id
+        ),
     }
 }
 
@@ -0,0 +1,354 @@
+//! Download of state sync files from plain HTTP(S) servers, such as static
+//! file servers or CDNs which serve copies of the state dumps. The files are
+//! expected under the same paths as in the other external storages.
+use crate::metrics;
+use reqwest::header::{CONTENT_RANGE, RANGE};
+use reqwest::StatusCode;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Number of times every mirror is tried before the download of a file fails.
+const NUM_ATTEMPTS_PER_MIRROR: usize = 2;
+
+/// After a failure, a mirror is only tried after the healthy mirrors for this
+/// long, doubled with every consecutive failure up to `MAX_UNHEALTHY_PERIOD`.
+const MIN_UNHEALTHY_PERIOD: Duration = Duration::from_secs(1);
+const MAX_UNHEALTHY_PERIOD: Duration = Duration::from_secs(300);
+
+/// Mirrors serving the same state dumps. Files are downloaded from the
+/// healthiest mirror, and on failure from the next one. A download interrupted
+/// midway is resumed with a range request.
+pub struct HttpMirrors {
+    client: reqwest::Client,
+    mirrors: Vec<Mirror>,
+}
+
+struct Mirror {
+    base_url: String,
+    health: Mutex<MirrorHealth>,
+}
+
+#[derive(Default)]
+struct MirrorHealth {
+    num_consecutive_failures: u32,
+    /// Until then, the mirror is tried after the healthy mirrors.
+    unhealthy_until: Option<Instant>,
+}
+
+enum DownloadError {
+    /// The file is not on the mirror, most likely because it's not dumped
+    /// yet. It doesn't make the mirror unhealthy.
+    NotFound,
+    Other(anyhow::Error),
+}
+
+impl From<reqwest::Error> for DownloadError {
+    fn from(err: reqwest::Error) -> Self {
+        DownloadError::Other(err.into())
+    }
+}
+
+impl HttpMirrors {
+    pub fn new(base_urls: &[String], timeout: Duration) -> Result<Self, anyhow::Error> {
+        anyhow::ensure!(!base_urls.is_empty(), "No HTTP mirrors");
+        for base_url in base_urls {
+            reqwest::Url::parse(base_url)?;
+        }
+        let client = reqwest::Client::builder().timeout(timeout).build()?;
+        let mirrors = base_urls
+            .iter()
+            .map(|base_url| Mirror {
+                base_url: base_url.trim_end_matches('/').to_string(),
+                health: Mutex::new(MirrorHealth::default()),
+            })
+            .collect();
+        Ok(Self { client, mirrors })
+    }
+
+    /// Downloads the file at the given location, trying the mirrors until the
+    /// downloaded data passes `verify`. Data which fails the verification is
+    /// treated as a failure of the mirror.
+    pub async fn get_file(
+        &self,
+        location: &str,
+        verify: &(dyn Fn(&[u8]) -> bool + Sync),
+    ) -> Result<Vec<u8>, anyhow::Error> {
+        let mut errors = vec![];
+        // Data received so far. If the download fails midway, the next
+        // attempt requests only the rest of the file.
+        let mut data = vec![];
+        for _ in 0..NUM_ATTEMPTS_PER_MIRROR {
+            for mirror in self.mirrors_by_health() {
+                let url = format!("{}/{}", mirror.base_url, location);
+                let result = match self.download(&url, &mut data).await {
+                    Ok(()) if verify(&data) => {
+                        mirror.record_success();
+                        Self::inc_requests(mirror, "ok");
+                        return Ok(data);
+                    }
+                    Ok(()) => {
+                        data.clear();
+                        mirror.record_failure();
+                        errors.push(format!("{url}: verification failed"));
+                        "verification_failed"
+                    }
+                    Err(DownloadError::NotFound) => {
+                        errors.push(format!("{url}: not found"));
+                        "not_found"
+                    }
+                    Err(DownloadError::Other(err)) => {
+                        mirror.record_failure();
+                        errors.push(format!("{url}: {err}"));
+                        "error"
+                    }
+                };
+                tracing::debug!(target: "sync", %url, result, num_bytes = data.len(), "HTTP mirror request failed");
+                Self::inc_requests(mirror, result);
+            }
+        }
+        anyhow::bail!("Failed to download {location} from the HTTP mirrors: {}", errors.join(", "))
+    }
+
+    /// Downloads the file at `url`, appending it to `data`. If `data` isn't
+    /// empty, only the rest of the file is requested.
+    async fn download(&self, url: &str, data: &mut Vec<u8>) -> Result<(), DownloadError> {
+        let mut request = self.client.get(url);
+        if !data.is_empty() {
+            request = request.header(RANGE, format!("bytes={}-", data.len()));
+        }
+        let mut response = request.send().await?;
+        match response.status() {
+            StatusCode::NOT_FOUND => return Err(DownloadError::NotFound),
+            StatusCode::PARTIAL_CONTENT => {
+                let expected_range = format!("bytes {}-", data.len());
+                let content_range = response
+                    .headers()
+                    .get(CONTENT_RANGE)
+                    .and_then(|value| value.to_str().ok())
+                    .unwrap_or_default();
+                if !content_range.starts_with(&expected_range) {
+                    data.clear();
+                    return Err(DownloadError::Other(anyhow::anyhow!(
+                        "Unexpected content range: {content_range}"
+                    )));
+                }
+            }
+            // The server doesn't support range requests and sent the whole file.
+            status if status.is_success() => data.clear(),
+            StatusCode::RANGE_NOT_SATISFIABLE => {
+                data.clear();
+                return Err(DownloadError::Other(anyhow::anyhow!("Range not satisfiable")));
+            }
+            status => {
+                return Err(DownloadError::Other(anyhow::anyhow!(
+                    "Bad response status code: {status}"
+                )))
+            }
+        }
+        while let Some(chunk) = response.chunk().await? {
+            data.extend_from_slice(&chunk);
+        }
+        Ok(())
+    }
+
+    /// Mirrors in the order to try them: the healthy mirrors in the configured
+    /// order, followed by the unhealthy mirrors which recover the soonest.
+    fn mirrors_by_health(&self) -> Vec<&Mirror> {
+        let now = Instant::now();
+        let mut mirrors: Vec<_> = self
+            .mirrors
+            .iter()
+            .map(|mirror| {
+                let unhealthy_until = mirror
+                    .health
+                    .lock()
+                    .unwrap()
+                    .unhealthy_until
+                    .filter(|unhealthy_until| *unhealthy_until > now);
+                (unhealthy_until, mirror)
+            })
+            .collect();
+        // Sorting is stable, and `None` is ordered before `Some`.
+        mirrors.sort_by_key(|(unhealthy_until, _)| *unhealthy_until);
+        mirrors.into_iter().map(|(_, mirror)| mirror).collect()
+    }
+
+    fn inc_requests(mirror: &Mirror, result: &str) {
+        metrics::STATE_SYNC_EXTERNAL_HTTP_MIRROR_REQUESTS
+            .with_label_values(&[&mirror.base_url, result])
+            .inc();
+    }
+}
+
+impl Mirror {
+    fn record_success(&self) {
+        *self.health.lock().unwrap() = MirrorHealth::default();
+    }
+
+    fn record_failure(&self) {
+        let mut health = self.health.lock().unwrap();
+        let unhealthy_period = MIN_UNHEALTHY_PERIOD
+            .saturating_mul(1 << health.num_consecutive_failures.min(16))
+            .min(MAX_UNHEALTHY_PERIOD);
+        health.num_consecutive_failures += 1;
+        health.unhealthy_until = Some(Instant::now() + unhealthy_period);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HttpMirrors;
+    use hyper::header::{CONTENT_RANGE, RANGE};
+    use hyper::service::{make_service_fn, service_fn};
+    use hyper::{Body, Request, Response, Server, StatusCode};
+    use std::collections::HashMap;
+    use std::convert::Infallible;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[derive(Clone, Copy)]
+    enum Behavior {
+        /// Serves the files, with support for range requests.
+        Serve,
+        /// Serves the files ignoring range requests, and cuts off the first
+        /// response after the given number of bytes.
+        CutOffFirstResponse(usize),
+        /// Serves files with every byte flipped.
+        Corrupt,
+        /// Responds with an internal server error.
+        Fail,
+    }
+
+    /// Starts a local HTTP server serving the files, and returns its base URL
+    /// and the counter of the requests it received.
+    fn start_server(
+        files: HashMap<String, Vec<u8>>,
+        behavior: Behavior,
+    ) -> (String, Arc<AtomicUsize>) {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let files = Arc::new(files);
+        let num_requests = Arc::new(AtomicUsize::new(0));
+        let counter = num_requests.clone();
+        tokio::task::spawn(async move {
+            let make_svc = make_service_fn(move |_conn| {
+                let files = files.clone();
+                let counter = counter.clone();
+                let handle_request = move |request: Request<Body>| {
+                    let files = files.clone();
+                    let request_index = counter.fetch_add(1, Ordering::SeqCst);
+                    async move {
+                        let path = request.uri().path().trim_start_matches("/dumps/");
+                        let Some(data) = files.get(path) else {
+                            let response = Response::builder()
+                                .status(StatusCode::NOT_FOUND)
+                                .body(Body::empty());
+                            return Ok::<_, Infallible>(response.unwrap());
+                        };
+                        let range_start = request
+                            .headers()
+                            .get(RANGE)
+                            .and_then(|value| value.to_str().ok())
+                            .and_then(|value| value.strip_prefix("bytes="))
+                            .and_then(|value| value.strip_suffix('-'))
+                            .map(|start| start.parse::<usize>().unwrap());
+                        let response = match (behavior, range_start) {
+                            (Behavior::Fail, _) => Response::builder()
+                                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                                .body(Body::empty()),
+                            (Behavior::Corrupt, _) => Response::builder()
+                                .body(Body::from(data.iter().map(|b| !b).collect::<Vec<_>>())),
+                            (Behavior::CutOffFirstResponse(len), _) if request_index == 0 => {
+                                // Announce the whole file but send only a prefix of it.
+                                let (mut sender, body) = Body::channel();
+                                let prefix = data[..len].to_vec();
+                                tokio::spawn(async move {
+                                    if sender.send_data(prefix.into()).await.is_ok() {
+                                        sender.abort();
+                                    }
+                                });
+                                Response::builder().body(body)
+                            }
+                            (Behavior::Serve, Some(start)) => Response::builder()
+                                .status(StatusCode::PARTIAL_CONTENT)
+                                .header(
+                                    CONTENT_RANGE,
+                                    format!("bytes {}-{}/{}", start, data.len() - 1, data.len()),
+                                )
+                                .body(Body::from(data[start..].to_vec())),
+                            _ => Response::builder().body(Body::from(data.clone())),
+                        };
+                        Ok::<_, Infallible>(response.unwrap())
+                    }
+                };
+                async move { Ok::<_, Infallible>(service_fn(handle_request)) }
+            });
+            let server = Server::from_tcp(listener).unwrap().serve(make_svc);
+            if let Err(e) = server.await {
+                eprintln!("server error: {}", e);
+            }
+        });
+        (format!("http://127.0.0.1:{}/dumps", port), num_requests)
+    }
+
+    const LOCATION: &str = "shard_id=0/state_part_000000_of_000001";
+
+    fn files() -> HashMap<String, Vec<u8>> {
+        HashMap::from([(LOCATION.to_string(), vec![7; 10000])])
+    }
+
+    fn verify(data: &[u8]) -> bool {
+        data == vec![7; 10000]
+    }
+
+    #[tokio::test]
+    async fn test_unhealthy_mirrors_are_skipped() {
+        let (failing, failing_requests) = start_server(files(), Behavior::Fail);
+        let (corrupt, corrupt_requests) = start_server(files(), Behavior::Corrupt);
+        let (serving, serving_requests) = start_server(files(), Behavior::Serve);
+        let mirrors =
+            HttpMirrors::new(&[failing, corrupt, serving], Duration::from_secs(10)).unwrap();
+
+        assert_eq!(mirrors.get_file(LOCATION, &verify).await.unwrap(), vec![7; 10000]);
+        assert_eq!(failing_requests.load(Ordering::SeqCst), 1);
+        assert_eq!(corrupt_requests.load(Ordering::SeqCst), 1);
+        assert_eq!(serving_requests.load(Ordering::SeqCst), 1);
+
+        // The mirrors which failed are tried after the healthy one.
+        assert_eq!(mirrors.get_file(LOCATION, &verify).await.unwrap(), vec![7; 10000]);
+        assert_eq!(failing_requests.load(Ordering::SeqCst), 1);
+        assert_eq!(corrupt_requests.load(Ordering::SeqCst), 1);
+        assert_eq!(serving_requests.load(Ordering::SeqCst), 2);
+
+        // Missing files don't make the mirrors unhealthy.
+        assert!(mirrors.get_file("missing", &verify).await.is_err());
+        assert_eq!(mirrors.get_file(LOCATION, &verify).await.unwrap(), vec![7; 10000]);
+        assert_eq!(serving_requests.load(Ordering::SeqCst), 5);
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_download_is_resumed() {
+        let (cut_off, cut_off_requests) =
+            start_server(files(), Behavior::CutOffFirstResponse(4000));
+        let (serving, serving_requests) = start_server(files(), Behavior::Serve);
+        let mirrors = HttpMirrors::new(&[cut_off, serving], Duration::from_secs(10)).unwrap();
+
+        assert_eq!(mirrors.get_file(LOCATION, &verify).await.unwrap(), vec![7; 10000]);
+        assert_eq!(cut_off_requests.load(Ordering::SeqCst), 1);
+        assert_eq!(serving_requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_all_mirrors_fail() {
+        let (failing, failing_requests) = start_server(files(), Behavior::Fail);
+        let (corrupt, _) = start_server(files(), Behavior::Corrupt);
+        let mirrors = HttpMirrors::new(&[failing, corrupt], Duration::from_secs(10)).unwrap();
+
+        let err = mirrors.get_file(LOCATION, &verify).await.unwrap_err();
+        assert!(err.to_string().contains("verification failed"), "{err}");
+        assert_eq!(failing_requests.load(Ordering::SeqCst), super::NUM_ATTEMPTS_PER_MIRROR);
+        assert!(HttpMirrors::new(&[], Duration::from_secs(10)).is_err());
+        assert!(HttpMirrors::new(&["not a url".to_string()], Duration::from_secs(10)).is_err());
+    }
+}
@@ -0,0 +1,197 @@
+//! Content-addressed layout of the state parts in the external storage.
+//!
+//! Every part is stored under the hash of its content, optionally compressed
+//! with zstd, so that a part which doesn't change across epochs is stored only
+//! once. The parts of an epoch and shard are listed in a manifest, which is
+//! written once all of them are dumped.
+use near_primitives::hash::CryptoHash;
+use near_primitives::state_sync::STATE_PART_MEMORY_LIMIT;
+
+/// Upper bound of the size of a part, both before and after compression. Parts
+/// are split to use about `STATE_PART_MEMORY_LIMIT` bytes, this leaves room for
+/// the proofs and the encoding overhead. Manifests are not authenticated, so
+/// the sizes in them are checked against it before anything is allocated.
+pub const MAX_STATE_PART_SIZE: u64 = 4 * STATE_PART_MEMORY_LIMIT.0;
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatePartCompression {
+    None,
+    Zstd,
+}
+
+/// Manifest of the state parts of an epoch and shard.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct StatePartsManifest {
+    pub compression: StatePartCompression,
+    /// Parts ordered by their ids.
+    pub parts: Vec<ManifestPart>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ManifestPart {
+    /// Hash of the part, before compression.
+    pub hash: CryptoHash,
+    /// Size of the part, before compression.
+    pub size: u64,
+    /// Size of the part as stored in the external storage.
+    pub stored_size: u64,
+}
+
+impl StatePartsManifest {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec_pretty(self).unwrap()
+    }
+
+    /// Parses the manifest and checks that it's consistent.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, anyhow::Error> {
+        let manifest: Self = serde_json::from_slice(data)?;
+        anyhow::ensure!(!manifest.parts.is_empty(), "The manifest lists no parts");
+        for (part_id, part) in manifest.parts.iter().enumerate() {
+            anyhow::ensure!(part.size > 0, "Part {part_id} is empty");
+            anyhow::ensure!(
+                part.size <= MAX_STATE_PART_SIZE && part.stored_size <= MAX_STATE_PART_SIZE,
+                "Part {part_id} is larger than {MAX_STATE_PART_SIZE} bytes"
+            );
+            if manifest.compression == StatePartCompression::None {
+                anyhow::ensure!(
+                    part.stored_size == part.size,
+                    "Part {part_id} is stored uncompressed, but its sizes differ"
+                );
+            }
+        }
+        Ok(manifest)
+    }
+
+    /// Location of the content of the part in the external storage.
+    pub fn part_location(&self, chain_id: &str, part_id: u64) -> Option<String> {
+        let part = self.parts.get(part_id as usize)?;
+        Some(state_part_content_location(chain_id, &part.hash, self.compression))
+    }
+}
+
+/// Location of the content of a part with the given hash, shared by all
+/// epochs and shards.
+pub fn state_part_content_location(
+    chain_id: &str,
+    hash: &CryptoHash,
+    compression: StatePartCompression,
+) -> String {
+    match compression {
+        StatePartCompression::None => format!("chain_id={}/state_parts/{}", chain_id, hash),
+        StatePartCompression::Zstd => format!("chain_id={}/state_parts/{}.zst", chain_id, hash),
+    }
+}
+
+/// Compresses the part with zstd at the given level, if any. Returns the entry
+/// of the part in the manifest and the data to store.
+pub fn encode_state_part(
+    part: &[u8],
+    zstd_compression_level: Option<i32>,
+) -> Result<(ManifestPart, Vec<u8>), anyhow::Error> {
+    anyhow::ensure!(
+        part.len() as u64 <= MAX_STATE_PART_SIZE,
+        "Part of {} bytes is larger than {MAX_STATE_PART_SIZE} bytes",
+        part.len()
+    );
+    let stored = match zstd_compression_level {
+        Some(level) => zstd::bulk::compress(part, level)?,
+        None => part.to_vec(),
+    };
+    let entry = ManifestPart {
+        hash: CryptoHash::hash_bytes(part),
+        size: part.len() as u64,
+        stored_size: stored.len() as u64,
+    };
+    Ok((entry, stored))
+}
+
+/// Decompresses the stored part, and checks that it matches its entry in the
+/// manifest. The entry has to come from a manifest which passed
+/// `StatePartsManifest::from_bytes`.
+pub fn decode_state_part(
+    entry: &ManifestPart,
+    compression: StatePartCompression,
+    stored: &[u8],
+) -> Result<Vec<u8>, anyhow::Error> {
+    debug_assert!(entry.size <= MAX_STATE_PART_SIZE);
+    // Checked before decompressing, so that nothing is allocated for data
+    // which doesn't match the manifest.
+    anyhow::ensure!(
+        stored.len() as u64 == entry.stored_size,
+        "Expected {} bytes, got {}",
+        entry.stored_size,
+        stored.len()
+    );
+    let part = match compression {
+        StatePartCompression::None => stored.to_vec(),
+        // The capacity limits the decompressed size to the size in the manifest.
+        StatePartCompression::Zstd => zstd::bulk::decompress(stored, entry.size as usize)?,
+    };
+    anyhow::ensure!(part.len() as u64 == entry.size, "Unexpected size of the decompressed part");
+    anyhow::ensure!(CryptoHash::hash_bytes(&part) == entry.hash, "Unexpected hash of the part");
+    Ok(part)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        decode_state_part, encode_state_part, state_part_content_location, StatePartCompression,
+        StatePartsManifest, MAX_STATE_PART_SIZE,
+    };
+
+    #[test]
+    fn test_encode_decode_state_part() {
+        let part: Vec<u8> = (0..10000).map(|i| (i % 7) as u8).collect();
+        for (level, compression) in
+            [(None, StatePartCompression::None), (Some(3), StatePartCompression::Zstd)]
+        {
+            let (entry, stored) = encode_state_part(&part, level).unwrap();
+            assert_eq!(entry.size, part.len() as u64);
+            assert_eq!(entry.stored_size, stored.len() as u64);
+            assert_eq!(decode_state_part(&entry, compression, &stored).unwrap(), part);
+
+            // Corrupted or truncated data is rejected.
+            let mut corrupted = stored.clone();
+            *corrupted.last_mut().unwrap() ^= 1;
+            assert!(decode_state_part(&entry, compression, &corrupted).is_err());
+            assert!(decode_state_part(&entry, compression, &stored[1..]).is_err());
+        }
+        assert!(encode_state_part(&part, Some(3)).unwrap().0.stored_size < part.len() as u64);
+    }
+
+    #[test]
+    fn test_manifest() {
+        let parts = [vec![1; 100], vec![2; 200], vec![1; 100]];
+        let manifest = StatePartsManifest {
+            compression: StatePartCompression::Zstd,
+            parts: parts.iter().map(|part| encode_state_part(part, Some(1)).unwrap().0).collect(),
+        };
+        assert_eq!(StatePartsManifest::from_bytes(&manifest.to_bytes()).unwrap(), manifest);
+
+        // Identical parts are stored at the same location.
+        assert_eq!(manifest.part_location("test", 0), manifest.part_location("test", 2));
+        assert_ne!(manifest.part_location("test", 0), manifest.part_location("test", 1));
+        assert_eq!(
+            manifest.part_location("test", 1).unwrap(),
+            state_part_content_location(
+                "test",
+                &manifest.parts[1].hash,
+                StatePartCompression::Zstd
+            )
+        );
+        assert_eq!(manifest.part_location("test", 3), None);
+
+        let empty = StatePartsManifest { compression: StatePartCompression::Zstd, parts: vec![] };
+        assert!(StatePartsManifest::from_bytes(&empty.to_bytes()).is_err());
+        let mut inconsistent = manifest.clone();
+        inconsistent.compression = StatePartCompression::None;
+        assert!(StatePartsManifest::from_bytes(&inconsistent.to_bytes()).is_err());
+        let mut too_large = manifest.clone();
+        too_large.parts[1].size = MAX_STATE_PART_SIZE + 1;
+        assert!(StatePartsManifest::from_bytes(&too_large.to_bytes()).is_err());
+        let mut too_large = manifest.clone();
+        too_large.parts[1].stored_size = u64::MAX;
+        assert!(StatePartsManifest::from_bytes(&too_large.to_bytes()).is_err());
+        assert!(StatePartsManifest::from_bytes(b"not a manifest").is_err());
+    }
+}
@@ -1,5 +1,7 @@
 pub mod block;
 pub mod epoch;
 pub mod external;
+pub mod external_http;
+pub mod external_manifest;
 pub mod header;
 pub mod state;
@@ -174,11 +174,13 @@ impl StateSyncDownloader {
                         cancel.clone(),
                     )
                     .await?;
-                if runtime_adapter.validate_state_part(
-                    &state_root,
-                    PartId { idx: part_id, total: num_state_parts },
-                    &part,
-                ) {
+                if source.validates_parts()
+                    || runtime_adapter.validate_state_part(
+                        &state_root,
+                        PartId { idx: part_id, total: num_state_parts },
+                        &part,
+                    )
+                {
                     let mut store_update = store.store_update();
                     let key = borsh::to_vec(&StatePartKey(sync_hash, shard_id, part_id)).unwrap();
                     store_update.set(DBCol::StateParts, &key, &part);
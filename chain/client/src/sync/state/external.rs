@@ -2,16 +2,24 @@ use super::task_tracker::TaskHandle;
 use super::util::{get_state_header_if_exists_in_storage, query_epoch_id_and_height_for_block};
 use super::StateSyncDownloadSource;
 use crate::sync::external::{external_storage_location, ExternalConnection, StateFileType};
+use crate::sync::external_manifest::{
+    decode_state_part, state_part_content_location, StatePartsManifest,
+};
 use crate::sync::state::util::increment_download_count;
 use borsh::BorshDeserialize;
 use futures::future::BoxFuture;
 use futures::FutureExt;
+use lru::LruCache;
 use near_async::time::{Clock, Duration};
+use near_chain::types::RuntimeAdapter;
+use near_chain_configs::StatePartsLayout;
 use near_primitives::hash::CryptoHash;
+use near_primitives::state_part::PartId;
 use near_primitives::state_sync::ShardStateSyncResponseHeader;
-use near_primitives::types::ShardId;
+use near_primitives::types::{EpochId, ShardId};
 use near_store::Store;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use tokio_util::sync::CancellationToken;
 use tracing::Instrument;
 
@@ -21,10 +29,18 @@ pub(super) struct StateSyncDownloadSourceExternal {
     pub store: Store,
     pub chain_id: String,
     pub conn: ExternalConnection,
+    pub runtime: Arc<dyn RuntimeAdapter>,
     pub timeout: Duration,
     pub backoff: Duration,
+    pub parts_layout: StatePartsLayout,
+    /// Manifests of the content-addressed state parts, keyed by the sync hash
+    /// and shard id.
+    pub manifests: Arc<Mutex<LruCache<(CryptoHash, ShardId), Arc<StatePartsManifest>>>>,
 }
 
+/// Check of the downloaded data, before it's accepted from the connection.
+type VerifyFn = Box<dyn Fn(&[u8]) -> bool + Send + Sync>;
+
 impl StateSyncDownloadSourceExternal {
     async fn get_file_with_timeout(
         clock: Clock,
@@ -35,12 +51,15 @@ impl StateSyncDownloadSourceExternal {
         shard_id: ShardId,
         location: String,
         file_type: StateFileType,
+        verify: VerifyFn,
     ) -> Result<Vec<u8>, near_chain::Error> {
-        let fut = conn.get_file(shard_id, &location, &file_type);
+        let fut = conn.get_file_verified(shard_id, &location, &file_type, &*verify);
         let deadline = clock.now() + timeout;
         let typ = match &file_type {
             StateFileType::StateHeader => "header",
             StateFileType::StatePart { .. } => "part",
+            StateFileType::StatePartsManifest => "manifest",
+            StateFileType::StatePartsManifestEntry { .. } => "manifest_entry",
         };
         tokio::select! {
             _ = clock.sleep_until(deadline) => {
@@ -70,6 +89,116 @@ impl StateSyncDownloadSourceExternal {
             }
         }
     }
+
+    /// Returns the manifest of the content-addressed state parts of the
+    /// shard, downloading it if it's not cached yet.
+    async fn get_manifest(
+        &self,
+        cancellation: CancellationToken,
+        shard_id: ShardId,
+        sync_hash: CryptoHash,
+        epoch_id: &EpochId,
+        epoch_height: u64,
+    ) -> Result<Arc<StatePartsManifest>, near_chain::Error> {
+        let cached = self.manifests.lock().unwrap().get(&(sync_hash, shard_id)).cloned();
+        if let Some(manifest) = cached {
+            return Ok(manifest);
+        }
+        let location = external_storage_location(
+            &self.chain_id,
+            epoch_id,
+            epoch_height,
+            shard_id,
+            &StateFileType::StatePartsManifest,
+        );
+        let data = Self::get_file_with_timeout(
+            self.clock.clone(),
+            self.timeout,
+            self.backoff,
+            cancellation,
+            self.conn.clone(),
+            shard_id,
+            location,
+            StateFileType::StatePartsManifest,
+            Box::new(|data| StatePartsManifest::from_bytes(data).is_ok()),
+        )
+        .await?;
+        let manifest = StatePartsManifest::from_bytes(&data).map_err(|e| {
+            increment_download_count(shard_id, "manifest", "external", "parse_error");
+            near_chain::Error::Other(format!("Failed to parse manifest: {}", e))
+        })?;
+        let manifest = Arc::new(manifest);
+        self.manifests.lock().unwrap().put((sync_hash, shard_id), manifest.clone());
+        Ok(manifest)
+    }
+
+    /// Downloads a state part stored under the hash of its content, as listed
+    /// in the manifest.
+    async fn get_content_addressed_part(
+        &self,
+        cancellation: CancellationToken,
+        shard_id: ShardId,
+        sync_hash: CryptoHash,
+        epoch_id: &EpochId,
+        epoch_height: u64,
+        part_id: PartId,
+        state_root: CryptoHash,
+    ) -> Result<Vec<u8>, near_chain::Error> {
+        let manifest = self
+            .get_manifest(cancellation.clone(), shard_id, sync_hash, epoch_id, epoch_height)
+            .await?;
+        if manifest.parts.len() as u64 != part_id.total {
+            self.manifests.lock().unwrap().pop(&(sync_hash, shard_id));
+            return Err(near_chain::Error::Other(format!(
+                "Manifest lists {} parts, expected {}",
+                manifest.parts.len(),
+                part_id.total
+            )));
+        }
+        let entry = manifest.parts[part_id.idx as usize];
+        let compression = manifest.compression;
+        let location = state_part_content_location(&self.chain_id, &entry.hash, compression);
+        // The part is decompressed to verify it, keep the result to not
+        // decompress it again.
+        let decoded = Arc::new(Mutex::new(None));
+        let rejected = Arc::new(AtomicBool::new(false));
+        let verify = {
+            let decoded = decoded.clone();
+            let rejected = rejected.clone();
+            let runtime = self.runtime.clone();
+            move |data: &[u8]| {
+                let part = decode_state_part(&entry, compression, data)
+                    .ok()
+                    .filter(|part| runtime.validate_state_part(&state_root, part_id, part));
+                let Some(part) = part else {
+                    rejected.store(true, Ordering::Relaxed);
+                    return false;
+                };
+                *decoded.lock().unwrap() = Some(part);
+                true
+            }
+        };
+        let result = Self::get_file_with_timeout(
+            self.clock.clone(),
+            self.timeout,
+            self.backoff,
+            cancellation,
+            self.conn.clone(),
+            shard_id,
+            location,
+            StateFileType::StatePart { part_id: part_id.idx, num_parts: part_id.total },
+            Box::new(verify),
+        )
+        .await;
+        let part = decoded.lock().unwrap().take();
+        if part.is_none() && rejected.load(Ordering::Relaxed) {
+            // The cached manifest may be stale or corrupted, the next attempt
+            // downloads it again.
+            self.manifests.lock().unwrap().pop(&(sync_hash, shard_id));
+        }
+        result?;
+        part.ok_or_else(|| near_chain::Error::Other("Downloaded part was not verified".to_owned()))
+    }
 }
 
 impl StateSyncDownloadSource for StateSyncDownloadSourceExternal {
@@ -106,6 +235,7 @@ impl StateSyncDownloadSource for StateSyncDownloadSourceExternal {
                 shard_id,
                 location,
                 StateFileType::StateHeader,
+                Box::new(|data| ShardStateSyncResponseHeader::try_from_slice(data).is_ok()),
             )
             .await?;
             let header = ShardStateSyncResponseHeader::try_from_slice(&data).map_err(|e| {
@@ -133,15 +263,33 @@ impl StateSyncDownloadSource for StateSyncDownloadSourceExternal {
         let backoff = self.backoff;
         let chain_id = self.chain_id.clone();
         let conn = self.conn.clone();
+        let runtime = self.runtime.clone();
         let store = self.store.clone();
         async move {
             handle.set_status("Preparing download");
             let (epoch_id, epoch_height) = query_epoch_id_and_height_for_block(&store, sync_hash)?;
-            let num_parts = get_state_header_if_exists_in_storage(&store, sync_hash, shard_id)?
+            let header = get_state_header_if_exists_in_storage(&store, sync_hash, shard_id)?
                 .ok_or_else(|| {
                     near_chain::Error::DBNotFoundErr(format!("No shard state header {}", sync_hash))
-                })?
-                .num_state_parts();
+                })?;
+            let num_parts = header.num_state_parts();
+            let state_root = header.chunk_prev_state_root();
+            if self.parts_layout == StatePartsLayout::ContentAddressed {
+                handle.set_status("Downloading content-addressed part");
+                let data = self
+                    .get_content_addressed_part(
+                        cancel,
+                        shard_id,
+                        sync_hash,
+                        &epoch_id,
+                        epoch_height,
+                        PartId { idx: part_id, total: num_parts },
+                        state_root,
+                    )
+                    .await?;
+                increment_download_count(shard_id, "part", "external", "success");
+                return Ok(data);
+            }
             let location = external_storage_location(
                 &chain_id,
                 &epoch_id,
@@ -159,6 +307,13 @@ impl StateSyncDownloadSource for StateSyncDownloadSourceExternal {
                 shard_id,
                 location,
                 StateFileType::StatePart { part_id, num_parts },
+                Box::new(move |data| {
+                    runtime.validate_state_part(
+                        &state_root,
+                        PartId { idx: part_id, total: num_parts },
+                        data,
+                    )
+                }),
             )
             .await?;
             increment_download_count(shard_id, "part", "external", "success");
@@ -167,4 +322,10 @@ impl StateSyncDownloadSource for StateSyncDownloadSourceExternal {
         .instrument(tracing::debug_span!("StateSyncDownloadSourceExternal::download_shard_part"))
         .boxed()
     }
+
+    /// Parts are validated while downloading, so that HTTP mirrors can fall
+    /// back to the next mirror on invalid data.
+    fn validates_parts(&self) -> bool {
+        true
+    }
 }
@@ -2,26 +2,30 @@ pub mod chain_requests;
 mod downloader;
 mod external;
 mod network;
+mod progress;
 mod shard;
 mod task_tracker;
 mod util;
 
 use crate::metrics;
 use crate::sync::external::{create_bucket_readonly, ExternalConnection};
+use crate::sync::external_http::HttpMirrors;
 use chain_requests::ChainSenderForStateSync;
 use downloader::StateSyncDownloader;
 use external::StateSyncDownloadSourceExternal;
 use futures::future::BoxFuture;
+use lru::LruCache;
 use near_async::futures::{FutureSpawner, FutureSpawnerExt};
 use near_async::messaging::{AsyncSender, IntoSender};
 use near_async::time::{Clock, Duration};
 use near_chain::types::RuntimeAdapter;
 use near_chain::Chain;
 use near_chain_configs::{ExternalStorageConfig, ExternalStorageLocation, SyncConfig};
+use near_client_primitives::debug::{ShardStateDownloadProgressView, StateDownloadProgressView};
 use near_client_primitives::types::{ShardSyncStatus, StateSyncStatus};
 use near_epoch_manager::EpochManagerAdapter;
 use near_network::types::{
-    HighestHeightPeerInfo, PeerManagerMessageRequest, PeerManagerMessageResponse,
+    HighestHeightPeerInfo, PeerManagerMessageRequest, PeerManagerMessageResponse, SnapshotHostInfo,
 };
 use near_primitives::hash::CryptoHash;
 use near_primitives::network::PeerId;
@@ -32,6 +36,7 @@ use network::{StateSyncDownloadSourcePeer, StateSyncDownloadSourcePeerSharedStat
 use shard::{run_state_sync_for_shard, StateSyncShardHandle};
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
+use std::num::NonZeroUsize;
 use std::sync::{Arc, Mutex};
 use task_tracker::{TaskHandle, TaskTracker};
 use tokio::sync::oneshot;
@@ -77,6 +82,9 @@ const NUM_CONCURRENT_REQUESTS_FOR_COMPUTATION: usize = 4;
 /// during catchup. We set this to a very low value to avoid overloading the
 /// node while it is still performing normal tasks.
 const NUM_CONCURRENT_REQUESTS_FOR_COMPUTATION_DURING_CATCHUP: usize = 1;
+/// Maximum number of manifests of content-addressed state parts kept in
+/// memory, one per shard being synced.
+const MAX_CACHED_STATE_PARTS_MANIFESTS: usize = 16;
 
 impl StateSync {
     /// Note: `future_spawner` is used to spawn futures that perform state sync tasks.
@@ -100,7 +108,7 @@ impl StateSync {
         catchup: bool,
     ) -> Self {
         let peer_source_state =
-            Arc::new(Mutex::new(StateSyncDownloadSourcePeerSharedState::default()));
+            Arc::new(Mutex::new(StateSyncDownloadSourcePeerSharedState::new(clock.clone())));
         let peer_source = Arc::new(StateSyncDownloadSourcePeer {
             clock: clock.clone(),
             store: store.clone(),
@@ -114,6 +122,7 @@ impl StateSync {
                 num_concurrent_requests,
                 num_concurrent_requests_during_catchup,
                 external_storage_fallback_threshold,
+                parts_layout,
             }) = sync_config
             {
                 let external = match location {
@@ -136,6 +145,14 @@ impl StateSync {
                         reqwest_client: Arc::new(reqwest::Client::default()),
                         bucket: bucket.clone(),
                     },
+                    ExternalStorageLocation::HTTP { mirrors } => {
+                        let mirrors = HttpMirrors::new(
+                            mirrors,
+                            external_timeout.max(Duration::ZERO).unsigned_abs(),
+                        )
+                        .unwrap_or_else(|err| panic!("Failed to set up the HTTP mirrors: {}", err));
+                        ExternalConnection::HTTP { mirrors: Arc::new(mirrors) }
+                    }
                 };
                 let num_concurrent_requests = if catchup {
                     *num_concurrent_requests_during_catchup
@@ -147,8 +164,13 @@ impl StateSync {
                     store: store.clone(),
                     chain_id: chain_id.to_string(),
                     conn: external,
+                    runtime: runtime.clone(),
                     timeout: external_timeout,
                     backoff: external_backoff,
+                    parts_layout: *parts_layout,
+                    manifests: Arc::new(Mutex::new(LruCache::new(
+                        NonZeroUsize::new(MAX_CACHED_STATE_PARTS_MANIFESTS).unwrap(),
+                    ))),
                 }) as Arc<dyn StateSyncDownloadSource>;
                 (
                     Some(fallback_source),
@@ -208,21 +230,55 @@ impl StateSync {
         Ok(())
     }
 
+    /// Returns the progress of the download of the state for the given sync hash.
+    pub fn get_progress_view(&self, sync_hash: CryptoHash) -> StateDownloadProgressView {
+        let mut shards: Vec<_> = self
+            .shard_syncs
+            .iter()
+            .filter(|((shard_sync_hash, _), _)| shard_sync_hash == &sync_hash)
+            .map(|((_, shard_id), handle)| {
+                let parts_progress = handle.parts_progress.lock().unwrap();
+                ShardStateDownloadProgressView {
+                    shard_id: *shard_id,
+                    status: handle.status().to_string(),
+                    num_parts: parts_progress.as_ref().map(|progress| progress.parts.len() as u64),
+                    num_parts_done: parts_progress
+                        .as_ref()
+                        .map_or(0, |progress| progress.num_parts_done()),
+                    num_failed_attempts: parts_progress
+                        .as_ref()
+                        .map_or(0, |progress| progress.num_failed_attempts()),
+                }
+            })
+            .collect();
+        shards.sort_by_key(|shard| shard.shard_id);
+        StateDownloadProgressView {
+            sync_hash,
+            shards,
+            snapshot_hosts: self.peer_source_state.lock().unwrap().snapshot_host_views(),
+        }
+    }
+
     /// Main loop that should be called periodically.
     pub fn run(
         &mut self,
         sync_hash: CryptoHash,
         sync_status: &mut StateSyncStatus,
         highest_height_peers: &[HighestHeightPeerInfo],
+        snapshot_hosts: &[Arc<SnapshotHostInfo>],
         tracking_shards: &[ShardId],
     ) -> Result<StateSyncResult, near_chain::Error> {
         let _span =
             tracing::debug_span!(target: "sync", "run_sync", sync_type = "StateSync").entered();
         tracing::debug!(%sync_hash, ?tracking_shards, "syncing state");
 
-        self.peer_source_state.lock().unwrap().set_highest_peers(
-            highest_height_peers.iter().map(|info| info.peer_info.id.clone()).collect(),
-        );
+        {
+            let mut peer_source_state = self.peer_source_state.lock().unwrap();
+            peer_source_state.set_highest_peers(
+                highest_height_peers.iter().map(|info| info.peer_info.id.clone()).collect(),
+            );
+            peer_source_state.set_snapshot_hosts(snapshot_hosts.to_vec());
+        }
 
         let mut all_done = true;
         for shard_id in tracking_shards {
@@ -253,6 +309,7 @@ impl StateSync {
                         continue;
                     }
                     let status = Arc::new(Mutex::new(ShardSyncStatus::StateDownloadHeader));
+                    let parts_progress = Arc::new(Mutex::new(None));
                     let cancel = CancellationToken::new();
                     let shard_sync = run_state_sync_for_shard(
                         self.store.clone(),
@@ -263,6 +320,7 @@ impl StateSync {
                         self.epoch_manager.clone(),
                         self.computation_task_tracker.clone(),
                         status.clone(),
+                        parts_progress.clone(),
                         self.chain_requests_sender.clone().into_sender(),
                         cancel.clone(),
                         self.future_spawner.clone(),
@@ -272,7 +330,8 @@ impl StateSync {
                     self.future_spawner.spawn("shard sync", async move {
                         sender.send(shard_sync.await).ok();
                     });
-                    let handle = StateSyncShardHandle { status, result: receiver, cancel };
+                    let handle =
+                        StateSyncShardHandle { status, parts_progress, result: receiver, cancel };
                     let ret = handle.status();
                     entry.insert(handle);
                     ret
@@ -325,6 +384,13 @@ pub(self) trait StateSyncDownloadSource: Send + Sync + 'static {
         handle: Arc<TaskHandle>,
         cancel: CancellationToken,
     ) -> BoxFuture<Result<Vec<u8>, near_chain::Error>>;
+
+    /// Whether the parts returned by `download_shard_part` are already
+    /// validated against the state root, so that the downloader doesn't need
+    /// to validate them again.
+    fn validates_parts(&self) -> bool {
+        false
+    }
 }
 
 /// Find the hash of the first block on the same epoch (and chain) of block with hash `sync_hash`.
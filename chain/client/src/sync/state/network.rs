@@ -5,10 +5,12 @@ use crate::sync::state::util::increment_download_count;
 use futures::future::BoxFuture;
 use futures::FutureExt;
 use near_async::messaging::AsyncSender;
-use near_async::time::{Clock, Duration};
+use near_async::time::{Clock, Duration, Instant};
 use near_chain::BlockHeader;
+use near_client_primitives::debug::SnapshotHostStatsView;
 use near_network::types::{
     NetworkRequests, NetworkResponses, PeerManagerMessageRequest, PeerManagerMessageResponse,
+    SnapshotHostInfo,
 };
 use near_primitives::hash::CryptoHash;
 use near_primitives::network::PeerId;
@@ -23,6 +25,15 @@ use tokio::sync::oneshot;
 use tokio_util::sync::CancellationToken;
 use tracing::Instrument;
 
+/// Maximum number of snapshot hosts a part is requested from at the same time.
+/// If no host responds within a fraction of the request timeout, the part is
+/// also requested from the next best host, and the first response is used.
+const MAX_HOSTS_PER_PART: usize = 3;
+
+/// Weight of the latest download in the moving average of the throughput of
+/// a snapshot host.
+const THROUGHPUT_AVERAGE_WEIGHT: f64 = 0.3;
+
 /// Logic to download state sync headers and parts from peers.
 pub(super) struct StateSyncDownloadSourcePeer {
     pub clock: Clock,
@@ -32,11 +43,16 @@ pub(super) struct StateSyncDownloadSourcePeer {
     pub state: Arc<Mutex<StateSyncDownloadSourcePeerSharedState>>,
 }
 
-#[derive(Default)]
 pub(super) struct StateSyncDownloadSourcePeerSharedState {
+    clock: Clock,
     highest_height_peers: Vec<PeerId>,
+    /// Known snapshot hosts, which parts are requested from.
+    snapshot_hosts: Vec<Arc<SnapshotHostInfo>>,
+    /// Statistics of the part requests sent to each snapshot host, used to
+    /// choose the hosts to request parts from.
+    host_stats: HashMap<PeerId, SnapshotHostStats>,
     /// Tracks pending requests we have sent to peers. The requests are indexed by
-    /// (shard ID, sync hash, part ID or header), and the value is the peer ID we
+    /// (shard ID, sync hash, part ID or header), and the value is the peers we
     /// expect the response from, as well as a channel sender to complete the future
     /// waiting for the response.
     pending_requests: HashMap<PendingPeerRequestKey, PendingPeerRequestValue>,
@@ -50,11 +66,64 @@ struct PendingPeerRequestKey {
 }
 
 struct PendingPeerRequestValue {
-    peer_id: Option<PeerId>, // present for headers, not for parts
+    /// Peers the request was sent to and when. It's empty for parts whose host
+    /// was selected by the network, in which case a response from any peer
+    /// is accepted.
+    sent_to: Vec<(PeerId, Instant)>,
     sender: oneshot::Sender<ShardStateSyncResponse>,
 }
 
+#[derive(Default)]
+struct SnapshotHostStats {
+    /// Moving average of the throughput of the part downloads, in bytes per
+    /// second. None if no part was downloaded from the host yet.
+    throughput: Option<f64>,
+    num_successes: u64,
+    num_failures: u64,
+    /// Number of failures since the last success.
+    num_consecutive_failures: u32,
+    num_requests_in_flight: usize,
+}
+
+impl SnapshotHostStats {
+    /// Expected throughput of the next part download from the host. Hosts
+    /// which haven't served a part yet are assumed to be as fast as
+    /// `default_throughput`, so that every host gets a chance.
+    fn score(&self, default_throughput: f64) -> f64 {
+        let throughput = self.throughput.unwrap_or(default_throughput);
+        let failure_penalty = 2f64.powi(self.num_consecutive_failures.min(32) as i32);
+        throughput / failure_penalty / (1 + self.num_requests_in_flight) as f64
+    }
+
+    fn record_success(&mut self, num_bytes: usize, elapsed: std::time::Duration) {
+        let throughput = num_bytes as f64 / elapsed.as_secs_f64().max(0.001);
+        self.throughput = Some(match self.throughput {
+            Some(average) => {
+                THROUGHPUT_AVERAGE_WEIGHT * throughput + (1.0 - THROUGHPUT_AVERAGE_WEIGHT) * average
+            }
+            None => throughput,
+        });
+        self.num_successes += 1;
+        self.num_consecutive_failures = 0;
+    }
+
+    fn record_failure(&mut self) {
+        self.num_failures += 1;
+        self.num_consecutive_failures += 1;
+    }
+}
+
 impl StateSyncDownloadSourcePeerSharedState {
+    pub fn new(clock: Clock) -> Self {
+        Self {
+            clock,
+            highest_height_peers: vec![],
+            snapshot_hosts: vec![],
+            host_stats: HashMap::new(),
+            pending_requests: HashMap::new(),
+        }
+    }
+
     pub fn receive_peer_message(
         &mut self,
         peer_id: PeerId,
@@ -76,13 +145,24 @@ impl StateSyncDownloadSourcePeerSharedState {
             return Err(near_chain::Error::Other("Unexpected state response".to_owned()));
         };
 
-        if request.peer_id.as_ref().is_some_and(|expecting_peer_id| expecting_peer_id != &peer_id) {
+        let sent_at = request
+            .sent_to
+            .iter()
+            .find(|(expecting_peer_id, _)| expecting_peer_id == &peer_id)
+            .map(|(_, sent_at)| *sent_at);
+        if !request.sent_to.is_empty() && sent_at.is_none() {
             return Err(near_chain::Error::Other(
                 "Unexpected state response (wrong sender)".to_owned(),
             ));
         }
 
-        let value = self.pending_requests.remove(&key).unwrap();
+        let value = self.remove_pending_request(&key).unwrap();
+        if let (PartIdOrHeader::Part { .. }, Some(sent_at), Some((_, part))) =
+            (&key.kind, sent_at, data.part())
+        {
+            let elapsed = self.clock.now() - sent_at;
+            self.host_stats.entry(peer_id).or_default().record_success(part.len(), elapsed);
+        }
         let _ = value.sender.send(data);
         Ok(())
     }
@@ -91,6 +171,111 @@ impl StateSyncDownloadSourcePeerSharedState {
     pub fn set_highest_peers(&mut self, peers: Vec<PeerId>) {
         self.highest_height_peers = peers;
     }
+
+    /// Sets the known snapshot hosts, which state parts are requested from.
+    pub fn set_snapshot_hosts(&mut self, hosts: Vec<Arc<SnapshotHostInfo>>) {
+        self.snapshot_hosts = hosts;
+    }
+
+    /// Returns the statistics of the snapshot hosts parts were requested from.
+    pub fn snapshot_host_views(&self) -> Vec<SnapshotHostStatsView> {
+        let mut views: Vec<_> = self
+            .host_stats
+            .iter()
+            .map(|(peer_id, stats)| SnapshotHostStatsView {
+                peer_id: peer_id.clone(),
+                throughput_bytes_per_sec: stats.throughput.map(|throughput| throughput as u64),
+                num_successes: stats.num_successes,
+                num_failures: stats.num_failures,
+                num_requests_in_flight: stats.num_requests_in_flight,
+            })
+            .collect();
+        views.sort_by(|a, b| b.throughput_bytes_per_sec.cmp(&a.throughput_bytes_per_sec));
+        views
+    }
+
+    /// Selects the best snapshot host to request the part from, out of the
+    /// hosts which have the shard in their snapshot at `sync_prev_prev_hash`
+    /// and which the part wasn't requested from yet. The host is recorded as
+    /// a host the pending request was sent to.
+    ///
+    /// Hosts with the same score are ordered by a hash of the host and the
+    /// part, so that different nodes downloading the same part tend to send
+    /// the requests to the same host, which benefits from caching the part.
+    fn add_host_for_part(
+        &mut self,
+        key: &PendingPeerRequestKey,
+        sync_prev_prev_hash: CryptoHash,
+    ) -> Option<PeerId> {
+        let PartIdOrHeader::Part { part_id } = key.kind else {
+            return None;
+        };
+        let request = self.pending_requests.get(key)?;
+        let default_throughput = self
+            .host_stats
+            .values()
+            .filter_map(|stats| stats.throughput)
+            .max_by(f64::total_cmp)
+            .unwrap_or(1.0);
+        let default_stats = SnapshotHostStats::default();
+        let peer_id = self
+            .snapshot_hosts
+            .iter()
+            .filter(|host| {
+                host.sync_hash == sync_prev_prev_hash && host.shards.contains(&key.shard_id)
+            })
+            .filter(|host| request.sent_to.iter().all(|(peer_id, _)| peer_id != &host.peer_id))
+            .map(|host| {
+                let stats = self.host_stats.get(&host.peer_id).unwrap_or(&default_stats);
+                let priority = CryptoHash::hash_borsh((&host.peer_id, key.shard_id, part_id));
+                (stats.score(default_throughput), priority, &host.peer_id)
+            })
+            .max_by(|a, b| a.0.total_cmp(&b.0).then_with(|| a.1.cmp(&b.1)))
+            .map(|(_, _, peer_id)| peer_id.clone())?;
+
+        let now = self.clock.now();
+        self.pending_requests.get_mut(key)?.sent_to.push((peer_id.clone(), now));
+        self.host_stats.entry(peer_id.clone()).or_default().num_requests_in_flight += 1;
+        Some(peer_id)
+    }
+
+    /// Records that the part request could not be sent to the host.
+    fn remove_unreachable_host(&mut self, key: &PendingPeerRequestKey, peer_id: &PeerId) {
+        if let Some(request) = self.pending_requests.get_mut(key) {
+            request.sent_to.retain(|(sent_to, _)| sent_to != peer_id);
+        }
+        if let Some(stats) = self.host_stats.get_mut(peer_id) {
+            stats.num_requests_in_flight = stats.num_requests_in_flight.saturating_sub(1);
+            stats.record_failure();
+        }
+    }
+
+    /// Records that none of the hosts the part was requested from responded in time.
+    fn record_timeout(&mut self, key: &PendingPeerRequestKey) {
+        let Some(request) = self.pending_requests.get(key) else {
+            return;
+        };
+        for (peer_id, _) in &request.sent_to {
+            if let Some(stats) = self.host_stats.get_mut(peer_id) {
+                stats.record_failure();
+            }
+        }
+    }
+
+    fn remove_pending_request(
+        &mut self,
+        key: &PendingPeerRequestKey,
+    ) -> Option<PendingPeerRequestValue> {
+        let request = self.pending_requests.remove(key)?;
+        if let PartIdOrHeader::Part { .. } = key.kind {
+            for (peer_id, _) in &request.sent_to {
+                if let Some(stats) = self.host_stats.get_mut(peer_id) {
+                    stats.num_requests_in_flight = stats.num_requests_in_flight.saturating_sub(1);
+                }
+            }
+        }
+        Some(request)
+    }
 }
 
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
@@ -113,11 +298,15 @@ impl StateSyncDownloadSourcePeer {
         handle.set_status("Preparing request");
 
         // Sender/receiver pair used to await for the peer's response.
-        let (sender, receiver) = oneshot::channel();
+        let (sender, mut receiver) = oneshot::channel();
 
+        // For parts, the hash the snapshot hosts advertise their snapshots
+        // with, and the host selected for the first request, if any.
+        let mut sync_prev_prev_hash = CryptoHash::default();
+        let mut selected_host = None;
         let network_request = {
             let mut state_lock = state.lock().unwrap();
-            let (network_request, state_value) = match &key.kind {
+            match &key.kind {
                 PartIdOrHeader::Part { part_id } => {
                     let prev_hash = *store
                         .get_ser::<BlockHeader>(DBCol::BlockHeader, key.sync_hash.as_bytes())?
@@ -137,16 +326,21 @@ impl StateSyncDownloadSourcePeer {
                             ))
                         })?
                         .prev_hash();
-                    let network_request = PeerManagerMessageRequest::NetworkRequests(
-                        NetworkRequests::StateRequestPart {
-                            shard_id: key.shard_id,
-                            sync_hash: key.sync_hash,
-                            sync_prev_prev_hash: prev_prev_hash,
-                            part_id: *part_id,
-                        },
-                    );
-                    let state_value = PendingPeerRequestValue { peer_id: None, sender };
-                    (network_request, state_value)
+                    state_lock
+                        .pending_requests
+                        .insert(key.clone(), PendingPeerRequestValue { sent_to: vec![], sender });
+                    // If no snapshot host is known to have the part, the
+                    // network selects one.
+                    let peer_id = state_lock.add_host_for_part(&key, prev_prev_hash);
+                    sync_prev_prev_hash = prev_prev_hash;
+                    selected_host = peer_id.clone();
+                    PeerManagerMessageRequest::NetworkRequests(NetworkRequests::StateRequestPart {
+                        shard_id: key.shard_id,
+                        sync_hash: key.sync_hash,
+                        sync_prev_prev_hash: prev_prev_hash,
+                        part_id: *part_id,
+                        peer_id,
+                    })
                 }
                 PartIdOrHeader::Header => {
                     let peer_id = state_lock
@@ -156,20 +350,22 @@ impl StateSyncDownloadSourcePeer {
                         .ok_or_else(|| {
                             near_chain::Error::Other("No peer to choose from".to_owned())
                         })?;
-                    (
-                        PeerManagerMessageRequest::NetworkRequests(
-                            NetworkRequests::StateRequestHeader {
-                                shard_id: key.shard_id,
-                                sync_hash: key.sync_hash,
-                                peer_id: peer_id.clone(),
-                            },
-                        ),
-                        PendingPeerRequestValue { peer_id: Some(peer_id), sender },
+                    state_lock.pending_requests.insert(
+                        key.clone(),
+                        PendingPeerRequestValue {
+                            sent_to: vec![(peer_id.clone(), clock.now())],
+                            sender,
+                        },
+                    );
+                    PeerManagerMessageRequest::NetworkRequests(
+                        NetworkRequests::StateRequestHeader {
+                            shard_id: key.shard_id,
+                            sync_hash: key.sync_hash,
+                            peer_id,
+                        },
                     )
                 }
-            };
-            state_lock.pending_requests.insert(key.clone(), state_value);
-            network_request
+            }
         };
 
         // Whether the request succeeds, we shall remove the key from the map of pending requests afterwards.
@@ -189,6 +385,9 @@ impl StateSyncDownloadSourcePeer {
         match request_sender.send_async(network_request).await {
             Ok(response) => {
                 if let NetworkResponses::RouteNotFound = response.as_network_response() {
+                    if let Some(peer_id) = &selected_host {
+                        state.lock().unwrap().remove_unreachable_host(&key, peer_id);
+                    }
                     increment_download_count(key.shard_id, typ, "network", "route_not_found");
                     return Err(near_chain::Error::Other("Route not found".to_owned()));
                 }
@@ -199,26 +398,63 @@ impl StateSyncDownloadSourcePeer {
             }
         }
 
+        // Parts requested from a host we selected are also requested from
+        // the next best hosts if the response takes too long.
+        let hedge_delay = request_timeout / MAX_HOSTS_PER_PART as u32;
+        let mut num_hosts = 1;
+        let mut next_hedge = clock.now() + hedge_delay;
+        let mut hedge = selected_host.is_some();
+
         handle.set_status("Waiting for peer response");
-        select! {
-            _ = clock.sleep_until(deadline) => {
-                increment_download_count(key.shard_id, typ, "network", "timeout");
-                Err(near_chain::Error::Other("Timeout".to_owned()))
-            }
-            _ = cancel.cancelled() => {
-                increment_download_count(key.shard_id, typ, "network", "cancelled");
-                Err(near_chain::Error::Other("Cancelled".to_owned()))
-            }
-            result = receiver => {
-                match result {
-                    Ok(result) => {
-                        increment_download_count(key.shard_id, typ, "network", "success");
-                        Ok(result)
+        loop {
+            select! {
+                _ = clock.sleep_until(deadline) => {
+                    state.lock().unwrap().record_timeout(&key);
+                    increment_download_count(key.shard_id, typ, "network", "timeout");
+                    return Err(near_chain::Error::Other("Timeout".to_owned()));
+                }
+                _ = cancel.cancelled() => {
+                    increment_download_count(key.shard_id, typ, "network", "cancelled");
+                    return Err(near_chain::Error::Other("Cancelled".to_owned()));
+                }
+                _ = clock.sleep_until(next_hedge), if hedge => {
+                    let PartIdOrHeader::Part { part_id } = key.kind else { unreachable!() };
+                    let Some(peer_id) =
+                        state.lock().unwrap().add_host_for_part(&key, sync_prev_prev_hash)
+                    else {
+                        hedge = false;
+                        continue;
+                    };
+                    tracing::debug!(target: "sync", ?key, %peer_id, "Requesting the part from another host");
+                    let network_request =
+                        PeerManagerMessageRequest::NetworkRequests(NetworkRequests::StateRequestPart {
+                            shard_id: key.shard_id,
+                            sync_hash: key.sync_hash,
+                            sync_prev_prev_hash,
+                            part_id,
+                            peer_id: Some(peer_id.clone()),
+                        });
+                    let sent = request_sender.send_async(network_request).await.is_ok_and(
+                        |response| !matches!(response.as_network_response(), NetworkResponses::RouteNotFound),
+                    );
+                    if !sent {
+                        state.lock().unwrap().remove_unreachable_host(&key, &peer_id);
                     }
-                    Err(_) => {
-                        increment_download_count(key.shard_id, typ, "network", "sender_dropped");
-                        Err(near_chain::Error::Other("Sender dropped".to_owned()))
-                    },
+                    num_hosts += 1;
+                    hedge = num_hosts < MAX_HOSTS_PER_PART;
+                    next_hedge = clock.now() + hedge_delay;
+                }
+                result = &mut receiver => {
+                    return match result {
+                        Ok(result) => {
+                            increment_download_count(key.shard_id, typ, "network", "success");
+                            Ok(result)
+                        }
+                        Err(_) => {
+                            increment_download_count(key.shard_id, typ, "network", "sender_dropped");
+                            Err(near_chain::Error::Other("Sender dropped".to_owned()))
+                        }
+                    };
                 }
             }
         }
@@ -234,7 +470,7 @@ struct RemoveKeyUponDrop {
 impl Drop for RemoveKeyUponDrop {
     fn drop(&mut self) {
         let mut state_lock = self.state.lock().unwrap();
-        state_lock.pending_requests.remove(&self.key);
+        state_lock.remove_pending_request(&self.key);
     }
 }
 
@@ -300,3 +536,122 @@ impl StateSyncDownloadSource for StateSyncDownloadSourcePeer {
         .boxed()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        PartIdOrHeader, PendingPeerRequestKey, PendingPeerRequestValue,
+        StateSyncDownloadSourcePeerSharedState,
+    };
+    use near_async::time::{Duration, FakeClock};
+    use near_crypto::Signature;
+    use near_network::types::SnapshotHostInfo;
+    use near_primitives::hash::CryptoHash;
+    use near_primitives::network::PeerId;
+    use near_primitives::state_sync::{ShardStateSyncResponse, ShardStateSyncResponseV2};
+    use near_primitives::types::ShardId;
+    use std::sync::Arc;
+    use tokio::sync::oneshot;
+
+    fn snapshot_host(sync_hash: CryptoHash, shards: Vec<ShardId>) -> Arc<SnapshotHostInfo> {
+        Arc::new(SnapshotHostInfo {
+            peer_id: PeerId::random(),
+            sync_hash,
+            epoch_height: 1,
+            shards,
+            signature: Signature::default(),
+        })
+    }
+
+    fn part_key(part_id: u64) -> PendingPeerRequestKey {
+        PendingPeerRequestKey {
+            shard_id: ShardId::new(0),
+            sync_hash: CryptoHash::hash_bytes(b"sync"),
+            kind: PartIdOrHeader::Part { part_id },
+        }
+    }
+
+    fn add_request(
+        state: &mut StateSyncDownloadSourcePeerSharedState,
+        key: &PendingPeerRequestKey,
+    ) -> oneshot::Receiver<ShardStateSyncResponse> {
+        let (sender, receiver) = oneshot::channel();
+        state
+            .pending_requests
+            .insert(key.clone(), PendingPeerRequestValue { sent_to: vec![], sender });
+        receiver
+    }
+
+    fn part_response(part_id: u64, size: usize) -> ShardStateSyncResponse {
+        ShardStateSyncResponse::V2(ShardStateSyncResponseV2 {
+            header: None,
+            part: Some((part_id, vec![0; size])),
+        })
+    }
+
+    #[test]
+    fn test_select_snapshot_hosts() {
+        let clock = FakeClock::default();
+        let mut state = StateSyncDownloadSourcePeerSharedState::new(clock.clock());
+        let prev_prev_hash = CryptoHash::hash_bytes(b"prev_prev");
+        let host_a = snapshot_host(prev_prev_hash, vec![ShardId::new(0), ShardId::new(1)]);
+        let host_b = snapshot_host(prev_prev_hash, vec![ShardId::new(0)]);
+        state.set_snapshot_hosts(vec![
+            host_a.clone(),
+            host_b.clone(),
+            // Hosts without the shard or with another snapshot are not used.
+            snapshot_host(prev_prev_hash, vec![ShardId::new(1)]),
+            snapshot_host(CryptoHash::hash_bytes(b"other"), vec![ShardId::new(0)]),
+        ]);
+
+        // The part is requested from every host which has it, once.
+        let key = part_key(0);
+        let mut receiver = add_request(&mut state, &key);
+        let first = state.add_host_for_part(&key, prev_prev_hash).unwrap();
+        let second = state.add_host_for_part(&key, prev_prev_hash).unwrap();
+        assert_ne!(first, second);
+        assert!([&host_a.peer_id, &host_b.peer_id].contains(&&first));
+        assert!([&host_a.peer_id, &host_b.peer_id].contains(&&second));
+        assert_eq!(state.add_host_for_part(&key, prev_prev_hash), None);
+        assert!(state.snapshot_host_views().iter().all(|view| view.num_requests_in_flight == 1));
+
+        // Only the hosts the part was requested from may respond.
+        clock.advance(Duration::seconds(2));
+        assert!(state
+            .receive_peer_message(
+                PeerId::random(),
+                key.shard_id,
+                key.sync_hash,
+                part_response(0, 1000)
+            )
+            .is_err());
+        state
+            .receive_peer_message(
+                second.clone(),
+                key.shard_id,
+                key.sync_hash,
+                part_response(0, 1000),
+            )
+            .unwrap();
+        assert!(receiver.try_recv().is_ok());
+        assert!(state.pending_requests.is_empty());
+        let views = state.snapshot_host_views();
+        assert_eq!(views[0].peer_id, second);
+        assert_eq!(views[0].throughput_bytes_per_sec, Some(500));
+        assert_eq!(views[0].num_successes, 1);
+        assert!(views.iter().all(|view| view.num_requests_in_flight == 0));
+
+        // A host which timed out is not preferred for the next part.
+        let key = part_key(1);
+        let _receiver = add_request(&mut state, &key);
+        let timed_out = state.add_host_for_part(&key, prev_prev_hash).unwrap();
+        state.record_timeout(&key);
+        state.remove_pending_request(&key);
+        let key = part_key(2);
+        let _receiver = add_request(&mut state, &key);
+        assert_ne!(state.add_host_for_part(&key, prev_prev_hash).unwrap(), timed_out);
+        let views = state.snapshot_host_views();
+        let view = views.iter().find(|view| view.peer_id == timed_out).unwrap();
+        assert_eq!(view.num_failures, 1);
+    }
+}
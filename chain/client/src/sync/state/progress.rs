@@ -0,0 +1,104 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_primitives::hash::CryptoHash;
+use near_primitives::state_sync::StateHeaderKey;
+use near_primitives::types::ShardId;
+use near_store::{DBCol, Store};
+
+/// Progress of the download of the parts of a shard. It's persisted in
+/// `DBCol::StatePartsProgress`, so that after a restart the node skips the
+/// parts it already downloaded and still knows how many times the download
+/// of the other parts failed.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub(super) struct StatePartsProgress {
+    pub parts: Vec<StatePartProgress>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(super) struct StatePartProgress {
+    /// Whether the part was downloaded, validated and saved in `DBCol::StateParts`.
+    pub done: bool,
+    /// Number of failed attempts to download the part. It decides when the
+    /// fallback source is used for the part.
+    pub num_failed_attempts: u32,
+}
+
+impl StatePartsProgress {
+    pub fn new(num_parts: u64) -> Self {
+        Self { parts: vec![StatePartProgress::default(); num_parts as usize] }
+    }
+
+    /// Reads the progress of the shard. Starts from scratch if there is none,
+    /// or if it was saved for a different number of parts.
+    pub fn load(
+        store: &Store,
+        shard_id: ShardId,
+        sync_hash: CryptoHash,
+        num_parts: u64,
+    ) -> Result<Self, near_chain::Error> {
+        let key = borsh::to_vec(&StateHeaderKey(shard_id, sync_hash))?;
+        Ok(match store.get_ser::<Self>(DBCol::StatePartsProgress, &key)? {
+            Some(progress) if progress.parts.len() as u64 == num_parts => progress,
+            _ => Self::new(num_parts),
+        })
+    }
+
+    pub fn save(
+        &self,
+        store: &Store,
+        shard_id: ShardId,
+        sync_hash: CryptoHash,
+    ) -> Result<(), near_chain::Error> {
+        let key = borsh::to_vec(&StateHeaderKey(shard_id, sync_hash))?;
+        let mut store_update = store.store_update();
+        store_update.set_ser(DBCol::StatePartsProgress, &key, self)?;
+        store_update.commit()?;
+        Ok(())
+    }
+
+    pub fn num_parts_done(&self) -> u64 {
+        self.parts.iter().filter(|part| part.done).count() as u64
+    }
+
+    /// Total number of failed attempts to download the parts.
+    pub fn num_failed_attempts(&self) -> u64 {
+        self.parts.iter().map(|part| part.num_failed_attempts as u64).sum()
+    }
+
+    /// Ids of the parts which are not downloaded yet.
+    pub fn parts_to_download(&self) -> Vec<u64> {
+        (0..self.parts.len() as u64).filter(|part_id| !self.parts[*part_id as usize].done).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StatePartsProgress;
+    use near_primitives::hash::CryptoHash;
+    use near_primitives::types::ShardId;
+    use near_store::test_utils::create_test_store;
+
+    #[test]
+    fn test_save_and_load() {
+        let store = create_test_store();
+        let shard_id = ShardId::new(1);
+        let sync_hash = CryptoHash::hash_bytes(b"sync");
+
+        let mut progress = StatePartsProgress::load(&store, shard_id, sync_hash, 4).unwrap();
+        assert_eq!(progress, StatePartsProgress::new(4));
+        progress.parts[1].done = true;
+        progress.parts[2].num_failed_attempts = 3;
+        progress.save(&store, shard_id, sync_hash).unwrap();
+
+        let loaded = StatePartsProgress::load(&store, shard_id, sync_hash, 4).unwrap();
+        assert_eq!(loaded, progress);
+        assert_eq!(loaded.num_parts_done(), 1);
+        assert_eq!(loaded.num_failed_attempts(), 3);
+        assert_eq!(loaded.parts_to_download(), vec![0, 2, 3]);
+
+        // Progress saved for another shard or number of parts is not used.
+        let other_shard = StatePartsProgress::load(&store, ShardId::new(0), sync_hash, 4).unwrap();
+        assert_eq!(other_shard, StatePartsProgress::new(4));
+        let other_num_parts = StatePartsProgress::load(&store, shard_id, sync_hash, 5).unwrap();
+        assert_eq!(other_num_parts, StatePartsProgress::new(5));
+    }
+}
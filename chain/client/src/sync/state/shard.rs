@@ -1,9 +1,10 @@
 use super::downloader::StateSyncDownloader;
+use super::progress::StatePartsProgress;
 use super::task_tracker::TaskTracker;
 use crate::metrics;
 use crate::sync::state::chain_requests::ChainFinalizationRequest;
 use crate::sync::state::util::query_epoch_id_and_height_for_block;
-use futures::{StreamExt, TryStreamExt};
+use futures::{FutureExt, StreamExt, TryStreamExt};
 use near_async::futures::{FutureSpawner, FutureSpawnerExt};
 use near_async::messaging::AsyncSender;
 use near_chain::types::RuntimeAdapter;
@@ -26,6 +27,8 @@ use tokio_util::sync::CancellationToken;
 
 pub(super) struct StateSyncShardHandle {
     pub status: Arc<Mutex<ShardSyncStatus>>,
+    /// Progress of the download of the parts, set once the header is downloaded.
+    pub parts_progress: Arc<Mutex<Option<StatePartsProgress>>>,
     pub result: oneshot::Receiver<Result<(), near_chain::Error>>,
     pub cancel: CancellationToken,
 }
@@ -47,6 +50,11 @@ impl Drop for StateSyncShardHandle {
 /// balancing the shards a little.
 const MAX_PARALLELISM_PER_SHARD_FOR_FAIRNESS: usize = 6;
 
+/// Number of downloaded or failed parts after which the progress of the shard
+/// is saved. A restart loses at most this many parts of progress, which are
+/// then downloaded again.
+const SAVE_PROGRESS_EVERY_NUM_PARTS: usize = 100;
+
 macro_rules! return_if_cancelled {
     ($cancel:expr) => {
         if $cancel.is_cancelled() {
@@ -63,6 +71,7 @@ pub(super) async fn run_state_sync_for_shard(
     epoch_manager: Arc<dyn EpochManagerAdapter>,
     computation_task_tracker: TaskTracker,
     status: Arc<Mutex<ShardSyncStatus>>,
+    parts_progress: Arc<Mutex<Option<StatePartsProgress>>>,
     chain_finalization_sender: AsyncSender<ChainFinalizationRequest, Result<(), near_chain::Error>>,
     cancel: CancellationToken,
     future_spawner: Arc<dyn FutureSpawner>,
@@ -84,7 +93,10 @@ pub(super) async fn run_state_sync_for_shard(
 
     return_if_cancelled!(cancel);
     *status.lock().unwrap() = ShardSyncStatus::StateDownloadParts;
-    let mut parts_to_download: Vec<u64> = (0..num_parts).collect();
+    // Resume the download from the progress saved before a restart, if any.
+    let progress = StatePartsProgress::load(&store, shard_id, sync_hash, num_parts)?;
+    let mut parts_to_download = progress.parts_to_download();
+    *parts_progress.lock().unwrap() = Some(progress);
     {
         // Peer selection is designed such that different nodes downloading the same part will tend
         // to send the requests to the same host. It allows the host to benefit from caching the part.
@@ -95,34 +107,50 @@ pub(super) async fn run_state_sync_for_shard(
         let mut rng = thread_rng();
         parts_to_download.shuffle(&mut rng);
     }
-    let mut attempt_count = 0;
     while !parts_to_download.is_empty() {
         return_if_cancelled!(cancel);
-        let results = tokio_stream::iter(parts_to_download.clone())
+        let mut results = tokio_stream::iter(parts_to_download)
             .map(|part_id| {
+                let num_prior_attempts = parts_progress.lock().unwrap().as_ref().unwrap().parts
+                    [part_id as usize]
+                    .num_failed_attempts as usize;
                 let future = downloader.ensure_shard_part_downloaded_single_attempt(
                     shard_id,
                     sync_hash,
                     state_root,
                     num_parts,
                     part_id,
-                    attempt_count,
+                    num_prior_attempts,
                     cancel.clone(),
                 );
                 respawn_for_parallelism(&*future_spawner, "state sync download part", future)
+                    .map(move |result| (part_id, result))
             })
-            .buffered(MAX_PARALLELISM_PER_SHARD_FOR_FAIRNESS)
-            .collect::<Vec<_>>()
-            .await;
-        attempt_count += 1;
-        // Update the list of parts_to_download retaining only the ones that failed
-        parts_to_download = results
-            .iter()
-            .enumerate()
-            .filter_map(|(task_index, res)| {
-                res.as_ref().err().map(|_| parts_to_download[task_index])
-            })
-            .collect();
+            .buffered(MAX_PARALLELISM_PER_SHARD_FOR_FAIRNESS);
+        // Retry the parts that failed, saving the progress in batches of parts.
+        let mut failed_parts = vec![];
+        let mut num_unsaved_parts = 0;
+        while let Some((part_id, result)) = results.next().await {
+            return_if_cancelled!(cancel);
+            let mut progress = parts_progress.lock().unwrap();
+            let progress = progress.as_mut().unwrap();
+            let part = &mut progress.parts[part_id as usize];
+            if result.is_ok() {
+                part.done = true;
+            } else {
+                part.num_failed_attempts += 1;
+                failed_parts.push(part_id);
+            }
+            num_unsaved_parts += 1;
+            if num_unsaved_parts >= SAVE_PROGRESS_EVERY_NUM_PARTS {
+                progress.save(&store, shard_id, sync_hash)?;
+                num_unsaved_parts = 0;
+            }
+        }
+        if num_unsaved_parts > 0 {
+            parts_progress.lock().unwrap().as_ref().unwrap().save(&store, shard_id, sync_hash)?;
+        }
+        parts_to_download = failed_parts;
     }
 
     return_if_cancelled!(cancel);
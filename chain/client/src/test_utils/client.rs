@@ -250,7 +250,7 @@ pub fn create_chunk(
 
     let signer = client.validator_signer.get().unwrap();
     let endorsement =
-        ChunkEndorsement::new(EpochId::default(), &chunk.cloned_header(), signer.as_ref());
+        ChunkEndorsement::new(EpochId::default(), &chunk.cloned_header(), signer.as_ref()).unwrap();
     block_merkle_tree.insert(*last_block.hash());
     let block = Block::produce(
         PROTOCOL_VERSION,
@@ -276,7 +276,8 @@ pub fn create_chunk(
         block_merkle_tree.root(),
         client.clock.clone(),
         None,
-    );
+    )
+    .unwrap();
     (
         ProduceChunkResult {
             chunk,
@@ -304,7 +305,7 @@ pub fn run_catchup(
     let _ = System::new();
     loop {
         let signer = client.validator_signer.get();
-        client.run_catchup(highest_height_peers, &block_catch_up, None, &signer)?;
+        client.run_catchup(highest_height_peers, &[], &block_catch_up, None, &signer)?;
         let mut catchup_done = true;
         for msg in block_messages.write().unwrap().drain(..) {
             let results = do_apply_chunks(msg.block_hash, msg.block_height, msg.work)
@@ -56,7 +56,7 @@ use near_network::types::{
 use near_network::types::{NetworkInfo, PeerManagerMessageRequest, PeerManagerMessageResponse};
 use near_network::types::{PeerInfo, PeerType};
 use near_o11y::WithSpanContextExt;
-use near_primitives::block::{ApprovalInner, GenesisId};
+use near_primitives::block::{ApprovalInner, GenesisId, Tip};
 use near_primitives::epoch_info::RngSeed;
 use near_primitives::hash::{hash, CryptoHash};
 use near_primitives::network::PeerId;
@@ -103,6 +103,7 @@ pub fn setup(
     Addr<ViewClientActor>,
     ShardsManagerAdapterForTest,
     PartialWitnessSenderForNetwork,
+    tokio::sync::watch::Receiver<Tip>,
 ) {
     let store = create_test_store();
     let num_validator_seats = vs.all_block_producers().count() as NumSeats;
@@ -172,7 +173,7 @@ pub fn setup(
     let resharding_sender = resharding_sender_addr.with_auto_span_context();
 
     let shards_manager_adapter_for_client = LateBoundSender::new();
-    let StartClientResult { client_actor, .. } = start_client(
+    let StartClientResult { client_actor, chain_head, .. } = start_client(
         clock,
         config.clone(),
         chain_genesis,
@@ -215,6 +216,7 @@ pub fn setup(
         view_client_addr,
         shards_manager_adapter.into_multi_sender(),
         partial_witness_adapter.into_multi_sender(),
+        chain_head,
     )
 }
 
@@ -350,22 +352,23 @@ pub fn setup_mock_with_validity_period(
 ) -> ActorHandlesForTesting {
     let network_adapter = LateBoundSender::new();
     let vs = ValidatorSchedule::new().block_producers_per_epoch(vec![validators]);
-    let (client_addr, view_client_addr, shards_manager_adapter, partial_witness_sender) = setup(
-        clock.clone(),
-        vs,
-        10,
-        account_id,
-        skip_sync_wait,
-        MIN_BLOCK_PROD_TIME.whole_milliseconds() as u64,
-        MAX_BLOCK_PROD_TIME.whole_milliseconds() as u64,
-        enable_doomslug,
-        false,
-        true,
-        network_adapter.as_multi_sender(),
-        transaction_validity_period,
-        clock.now_utc(),
-        None,
-    );
+    let (client_addr, view_client_addr, shards_manager_adapter, partial_witness_sender, chain_head) =
+        setup(
+            clock.clone(),
+            vs,
+            10,
+            account_id,
+            skip_sync_wait,
+            MIN_BLOCK_PROD_TIME.whole_milliseconds() as u64,
+            MAX_BLOCK_PROD_TIME.whole_milliseconds() as u64,
+            enable_doomslug,
+            false,
+            true,
+            network_adapter.as_multi_sender(),
+            transaction_validity_period,
+            clock.now_utc(),
+            None,
+        );
     let client_addr1 = client_addr.clone();
 
     let network_actor =
@@ -379,6 +382,7 @@ pub fn setup_mock_with_validity_period(
         view_client_actor: view_client_addr,
         shards_manager_adapter,
         partial_witness_sender,
+        chain_head,
     }
 }
 
@@ -388,6 +392,7 @@ pub struct ActorHandlesForTesting {
     pub view_client_actor: Addr<ViewClientActor>,
     pub shards_manager_adapter: ShardsManagerAdapterForTest,
     pub partial_witness_sender: PartialWitnessSenderForNetwork,
+    pub chain_head: tokio::sync::watch::Receiver<Tip>,
 }
 
 fn send_chunks<T, I, F>(
@@ -479,6 +484,7 @@ fn process_peer_manager_message_default(
             known_producers: vec![],
             tier1_accounts_keys: vec![],
             tier1_accounts_data: vec![],
+            snapshot_hosts: vec![],
         };
         client_sender.send(SetNetworkInfo(info).with_span_context());
     }
@@ -961,7 +967,13 @@ pub fn setup_mock_all_validators(
         })
         .start();
 
-        let (client_addr, view_client_addr, shards_manager_adapter, partial_witness_sender) = setup(
+        let (
+            client_addr,
+            view_client_addr,
+            shards_manager_adapter,
+            partial_witness_sender,
+            chain_head,
+        ) = setup(
             clock.clone(),
             vs,
             epoch_length,
@@ -985,6 +997,7 @@ pub fn setup_mock_all_validators(
             view_client_actor: view_client_addr,
             shards_manager_adapter,
             partial_witness_sender,
+            chain_head,
         });
     }
     hash_to_height.write().unwrap().insert(CryptoHash::default(), 0);
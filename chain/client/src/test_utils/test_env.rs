@@ -580,7 +580,7 @@ impl TestEnv {
                         last_block.header().prev_hash(),
                         last_block.header().hash(),
                         last_block.header().epoch_id(),
-                        &QueryRequest::ViewAccount { account_id },
+                        &QueryRequest::ViewAccount { account_id, include_proof: false },
                     )
                     .unwrap();
                 match response.kind {
@@ -631,6 +631,8 @@ impl TestEnv {
                 &QueryRequest::ViewState {
                     account_id,
                     prefix: vec![].into(),
+                    start_key: None,
+                    limit: None,
                     include_proof: false,
                 },
             )
@@ -803,6 +805,10 @@ impl TestEnv {
             | ProcessTxResponse::ValidTx => (),
             ProcessTxResponse::InvalidTx(e) => return Err(e),
             ProcessTxResponse::DoesNotTrackShard => panic!("test setup is buggy"),
+            ProcessTxResponse::ReplacementUnderpriced
+            | ProcessTxResponse::TooManyPendingTransactions => {
+                panic!("transaction rejected by the pool: {:?}", response)
+            }
         }
         let max_iters = 100;
         let tip = self.clients[0].chain.head().unwrap();
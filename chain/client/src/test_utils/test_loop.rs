@@ -71,7 +71,7 @@ where
     fn query_balance(&self, account_id: &AccountId) -> Balance {
         let response = self.runtime_query(
             account_id,
-            QueryRequest::ViewAccount { account_id: account_id.clone() },
+            QueryRequest::ViewAccount { account_id: account_id.clone(), include_proof: false },
         );
         if let QueryResponseKind::ViewAccount(account_view) = response.kind {
             account_view.amount
@@ -87,6 +87,7 @@ where
                 account_id: account_id.clone(),
                 method_name: method.to_string(),
                 args: args.to_vec().into(),
+                include_proof: false,
             },
         );
         if let QueryResponseKind::CallResult(call_result) = response.kind {
@@ -35,7 +35,10 @@ fn query_client() {
         let actor = actor_handles.view_client_actor.send(
             Query::new(
                 BlockReference::latest(),
-                QueryRequest::ViewAccount { account_id: "test".parse().unwrap() },
+                QueryRequest::ViewAccount {
+                    account_id: "test".parse().unwrap(),
+                    include_proof: false,
+                },
             )
             .with_span_context(),
         );
@@ -97,7 +100,8 @@ fn query_status_not_crash() {
                 block_merkle_tree.root(),
                 Clock::real(),
                 None,
-            );
+            )
+            .unwrap();
             let timestamp = next_block.header().timestamp();
             next_block
                 .mut_header()
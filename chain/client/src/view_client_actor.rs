@@ -9,7 +9,7 @@ use actix::{Addr, SyncArbiter};
 use near_async::actix_wrapper::SyncActixWrapper;
 use near_async::messaging::{Actor, CanSend, Handler};
 use near_async::time::{Clock, Duration, Instant};
-use near_chain::types::{RuntimeAdapter, Tip};
+use near_chain::types::{RuntimeAdapter, SimulationError, Tip};
 use near_chain::{
     get_epoch_block_producers_view, Chain, ChainGenesis, ChainStoreAccess, DoomslugThresholdMode,
     MerkleProofAccess,
@@ -18,14 +18,16 @@ use near_chain::{
 use near_chain_configs::{ClientConfig, MutableValidatorSigner, ProtocolConfigView};
 use near_chain_primitives::error::EpochErrorResultToChainError;
 use near_client_primitives::types::{
-    Error, GetBlock, GetBlockError, GetBlockProof, GetBlockProofError, GetBlockProofResponse,
+    Error, GetAccountsByPublicKey, GetAccountsByPublicKeyError, GetAccountsByPublicKeyResponse,
+    GetBlock, GetBlockError, GetBlockProof, GetBlockProofError, GetBlockProofResponse,
     GetBlockWithMerkleTree, GetChunkError, GetExecutionOutcome, GetExecutionOutcomeError,
     GetExecutionOutcomesForBlock, GetGasPrice, GetGasPriceError, GetMaintenanceWindows,
     GetMaintenanceWindowsError, GetNextLightClientBlockError, GetProtocolConfig,
     GetProtocolConfigError, GetReceipt, GetReceiptError, GetSplitStorageInfo,
     GetSplitStorageInfoError, GetStateChangesError, GetStateChangesWithCauseInBlock,
     GetStateChangesWithCauseInBlockForTrackedShards, GetValidatorInfoError, Query, QueryError,
-    TxStatus, TxStatusError,
+    SimulateTransaction, SimulateTransactionError, SimulateTransactionResponse, TxStatus,
+    TxStatusError,
 };
 use near_epoch_manager::shard_tracker::ShardTracker;
 use near_epoch_manager::EpochManagerAdapter;
@@ -49,27 +51,32 @@ use near_primitives::state_sync::{
     ShardStateSyncResponse, ShardStateSyncResponseHeader, ShardStateSyncResponseV3,
 };
 use near_primitives::stateless_validation::ChunkProductionKey;
-use near_primitives::transaction::SignedTransaction;
+use near_primitives::transaction::{ExecutionOutcomeWithIdAndProof, SignedTransaction};
 use near_primitives::types::{
     AccountId, BlockHeight, BlockId, BlockReference, EpochReference, Finality, MaybeBlockId,
-    ShardId, SyncCheckpoint, TransactionOrReceiptId, ValidatorInfoIdentifier,
+    ShardId, StateChanges, StateChangesExt, SyncCheckpoint, TransactionOrReceiptId,
+    ValidatorInfoIdentifier,
 };
 use near_primitives::validator_signer::ValidatorSigner;
 use near_primitives::views::validator_stake_view::ValidatorStakeView;
 use near_primitives::views::{
-    BlockView, ChunkView, EpochValidatorInfo, ExecutionOutcomeWithIdView, ExecutionStatusView,
-    FinalExecutionOutcomeView, FinalExecutionOutcomeViewEnum, FinalExecutionStatus, GasPriceView,
-    LightClientBlockView, MaintenanceWindowsView, QueryRequest, QueryResponse, ReceiptView,
-    SignedTransactionView, SplitStorageInfoView, StateChangesKindsView, StateChangesView,
-    TxExecutionStatus, TxStatusView,
+    BlockView, ChunkView, ColdColumnRetainedRangeView, EpochValidatorInfo,
+    ExecutionOutcomeWithIdView, ExecutionStatusView, FinalExecutionOutcomeView,
+    FinalExecutionOutcomeViewEnum, FinalExecutionOutcomeWithReceiptView, FinalExecutionStatus,
+    GasPriceView, LightClientBlockView, MaintenanceWindowsView, QueryRequest, QueryResponse,
+    QueryResponseKind, ReceiptView, SignedTransactionView, SplitStorageInfoView,
+    StateChangesKindsView, StateChangesView, TxExecutionStatus, TxStatusView,
 };
 use near_store::flat::{FlatStorageReadyStatus, FlatStorageStatus};
-use near_store::{DBCol, COLD_HEAD_KEY, FINAL_HEAD_KEY, HEAD_KEY};
+use near_store::{
+    accounts_by_public_key, DBCol, COLD_COLUMN_TAILS_KEY, COLD_HEAD_KEY, FINAL_HEAD_KEY, HEAD_KEY,
+};
 use std::cmp::Ordering;
-use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
 use std::hash::Hash;
 use std::num::NonZeroUsize;
 use std::sync::{Arc, Mutex, RwLock};
+use strum::IntoEnumIterator;
 use tracing::{error, info, warn};
 
 /// Max number of queries that we keep.
@@ -461,6 +468,186 @@ impl ViewClientActorInner {
         }
     }
 
+    fn handle_simulate_transaction(
+        &mut self,
+        msg: SimulateTransaction,
+    ) -> Result<SimulateTransactionResponse, SimulateTransactionError> {
+        let header = match self.get_block_header_by_reference(&msg.block_reference) {
+            Ok(Some(header)) => header,
+            Ok(None) => return Err(SimulateTransactionError::NoSyncedBlocks),
+            Err(near_chain::near_chain_primitives::Error::DBNotFoundErr(_)) => {
+                return Err(SimulateTransactionError::UnknownBlock {
+                    block_reference: msg.block_reference,
+                })
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        // Only the shards tracked by the node have their post state available.
+        let shard_layout =
+            self.epoch_manager.get_shard_layout(header.epoch_id()).into_chain_error()?;
+        let mut state_roots = HashMap::new();
+        for shard_uid in shard_layout.shard_uids() {
+            match self.chain.get_chunk_extra(header.hash(), &shard_uid) {
+                Ok(chunk_extra) => {
+                    state_roots.insert(shard_uid, *chunk_extra.state_root());
+                }
+                Err(near_chain::near_chain_primitives::Error::DBNotFoundErr(_)) => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        let result = self
+            .runtime
+            .simulate_transaction(
+                &state_roots,
+                header.height(),
+                header.raw_timestamp(),
+                header.prev_hash(),
+                header.hash(),
+                header.epoch_id(),
+                header.next_gas_price(),
+                &msg.transaction,
+                msg.verify_signature,
+                self.config.simulation_time_limit,
+            )
+            .map_err(|err| match err {
+                SimulationError::InvalidTransaction(error) => {
+                    SimulateTransactionError::InvalidTransaction { error }
+                }
+                SimulationError::UnavailableShard(requested_shard_id) => {
+                    SimulateTransactionError::UnavailableShard { requested_shard_id }
+                }
+                SimulationError::TooManyReceipts { limit } => {
+                    SimulateTransactionError::TooManyReceipts { limit }
+                }
+                SimulationError::GasLimitExceeded { limit } => {
+                    SimulateTransactionError::GasLimitExceeded { limit }
+                }
+                SimulationError::TimeLimitExceeded { limit } => {
+                    SimulateTransactionError::TimeLimitExceeded { limit }
+                }
+                SimulationError::Runtime(err) => {
+                    SimulateTransactionError::InternalError { error_message: err.to_string() }
+                }
+            })?;
+
+        let block_hash = *header.hash();
+        let mut outcomes: Vec<_> = std::iter::once(result.transaction_outcome)
+            .chain(result.receipt_outcomes)
+            .map(|outcome_with_id| {
+                ExecutionOutcomeWithIdView::from(ExecutionOutcomeWithIdAndProof {
+                    proof: vec![],
+                    block_hash,
+                    outcome_with_id,
+                })
+            })
+            .collect();
+        let status = Chain::get_execution_status(&outcomes, &msg.transaction.get_hash());
+        let receipts_outcome = outcomes.split_off(1);
+        let transaction_outcome = outcomes.pop().unwrap();
+        let state_changes = StateChanges::from_changes(result.state_changes.into_iter().map(Ok))
+            .map_err(|err| SimulateTransactionError::InternalError {
+                error_message: err.to_string(),
+            })?;
+        Ok(SimulateTransactionResponse {
+            block_hash,
+            block_height: header.height(),
+            outcome: FinalExecutionOutcomeWithReceiptView {
+                final_outcome: FinalExecutionOutcomeView {
+                    status,
+                    transaction: SignedTransactionView::from(msg.transaction),
+                    transaction_outcome,
+                    receipts_outcome,
+                },
+                receipts: result.receipts.into_iter().map(ReceiptView::from).collect(),
+            },
+            state_changes: state_changes.into_iter().map(Into::into).collect(),
+        })
+    }
+
+    fn handle_get_accounts_by_public_key(
+        &mut self,
+        msg: GetAccountsByPublicKey,
+    ) -> Result<GetAccountsByPublicKeyResponse, GetAccountsByPublicKeyError> {
+        let store = self.chain.chain_store().store().clone();
+        let io_error = |err: std::io::Error| GetAccountsByPublicKeyError::InternalError {
+            error_message: err.to_string(),
+        };
+        if !accounts_by_public_key::is_backfilled(&store).map_err(io_error)? {
+            return Err(GetAccountsByPublicKeyError::IndexUnavailable);
+        }
+        let header = match self.get_block_header_by_reference(&msg.block_reference) {
+            Ok(Some(header)) => header,
+            Ok(None) => return Err(GetAccountsByPublicKeyError::NoSyncedBlocks),
+            Err(near_chain::near_chain_primitives::Error::DBNotFoundErr(_)) => {
+                return Err(GetAccountsByPublicKeyError::UnknownBlock {
+                    block_reference: msg.block_reference,
+                })
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        // The index is never pruned, so every candidate is checked against the
+        // state of the requested block.
+        let mut keys = vec![];
+        for account_id in
+            accounts_by_public_key::get_accounts_by_public_key(&store, &msg.public_key)
+                .map_err(io_error)?
+        {
+            let shard_id = self
+                .epoch_manager
+                .account_id_to_shard_id(&account_id, header.epoch_id())
+                .into_chain_error()?;
+            let shard_uid = self
+                .epoch_manager
+                .shard_id_to_uid(shard_id, header.epoch_id())
+                .into_chain_error()?;
+            let chunk_extra = match self.chain.get_chunk_extra(header.hash(), &shard_uid) {
+                Ok(chunk_extra) => chunk_extra,
+                // The shard is not tracked at the requested block.
+                Err(near_chain::near_chain_primitives::Error::DBNotFoundErr(_)) => continue,
+                Err(err) => return Err(err.into()),
+            };
+            let request = QueryRequest::ViewAccessKey {
+                account_id: account_id.clone(),
+                public_key: msg.public_key.clone(),
+                include_proof: false,
+            };
+            match self.runtime.query(
+                shard_uid,
+                chunk_extra.state_root(),
+                header.height(),
+                header.raw_timestamp(),
+                header.prev_hash(),
+                header.hash(),
+                header.epoch_id(),
+                &request,
+            ) {
+                Ok(QueryResponse { kind: QueryResponseKind::AccessKey(access_key), .. }) => {
+                    keys.push((account_id, access_key));
+                }
+                Ok(_) => {}
+                Err(
+                    near_chain::near_chain_primitives::error::QueryError::UnknownAccessKey {
+                        ..
+                    }
+                    | near_chain::near_chain_primitives::error::QueryError::UnknownAccount { .. },
+                ) => {}
+                Err(err) => {
+                    return Err(GetAccountsByPublicKeyError::InternalError {
+                        error_message: err.to_string(),
+                    })
+                }
+            }
+        }
+        Ok(GetAccountsByPublicKeyResponse {
+            block_hash: *header.hash(),
+            block_height: header.height(),
+            keys,
+        })
+    }
+
     // Return the lowest status the node can proof
     fn get_tx_execution_status(
         &self,
@@ -720,6 +907,34 @@ impl Handler<Query> for ViewClientActorInner {
     }
 }
 
+impl Handler<SimulateTransaction> for ViewClientActorInner {
+    #[perf]
+    fn handle(
+        &mut self,
+        msg: SimulateTransaction,
+    ) -> Result<SimulateTransactionResponse, SimulateTransactionError> {
+        tracing::debug!(target: "client", ?msg);
+        let _timer = metrics::VIEW_CLIENT_MESSAGE_TIME
+            .with_label_values(&["SimulateTransaction"])
+            .start_timer();
+        self.handle_simulate_transaction(msg)
+    }
+}
+
+impl Handler<GetAccountsByPublicKey> for ViewClientActorInner {
+    #[perf]
+    fn handle(
+        &mut self,
+        msg: GetAccountsByPublicKey,
+    ) -> Result<GetAccountsByPublicKeyResponse, GetAccountsByPublicKeyError> {
+        tracing::debug!(target: "client", ?msg);
+        let _timer = metrics::VIEW_CLIENT_MESSAGE_TIME
+            .with_label_values(&["GetAccountsByPublicKey"])
+            .start_timer();
+        self.handle_get_accounts_by_public_key(msg)
+    }
+}
+
 /// Handles retrieving block from the chain.
 impl Handler<GetBlock> for ViewClientActorInner {
     #[perf]
@@ -1566,13 +1781,32 @@ impl Handler<GetSplitStorageInfo> for ViewClientActorInner {
         let final_head = store.get_ser::<Tip>(DBCol::BlockMisc, FINAL_HEAD_KEY)?;
         let cold_head = store.get_ser::<Tip>(DBCol::BlockMisc, COLD_HEAD_KEY)?;
 
+        let cold_column_tails = store
+            .get_ser::<BTreeMap<String, BlockHeight>>(DBCol::BlockMisc, COLD_COLUMN_TAILS_KEY)?
+            .unwrap_or_default();
+
         let hot_db_kind = store.get_db_kind()?.map(|kind| kind.to_string());
 
+        // Columns without a tail were never pruned and are retained from the
+        // beginning of cold storage.
+        let cold_retained_ranges = match &cold_head {
+            Some(cold_head) => DBCol::iter()
+                .filter(|col| col.is_cold())
+                .map(|col| {
+                    let column = <&str>::from(col).to_string();
+                    let from_height = cold_column_tails.get(&column).copied();
+                    ColdColumnRetainedRangeView { column, from_height, to_height: cold_head.height }
+                })
+                .collect(),
+            None => vec![],
+        };
+
         Ok(SplitStorageInfoView {
             head_height: head.map(|tip| tip.height),
             final_head_height: final_head.map(|tip| tip.height),
             cold_head_height: cold_head.map(|tip| tip.height),
             hot_db_kind,
+            cold_retained_ranges,
         })
     }
 }
@@ -4,12 +4,14 @@ use serde_json::{to_value, Value};
 use std::fmt;
 
 #[derive(Debug, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct RpcParseError(pub String);
 
 /// This struct may be returned from JSON RPC server in case of error
 /// It is expected that this struct has impls From<_> all other RPC errors
 /// like [RpcBlockError](crate::types::blocks::RpcBlockError)
 #[derive(Debug, serde::Serialize, serde::Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(deny_unknown_fields)]
 pub struct RpcError {
     #[serde(flatten)]
@@ -24,6 +26,7 @@ pub struct RpcError {
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(tag = "name", content = "cause", rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum RpcErrorKind {
     RequestValidationError(RpcRequestValidationErrorKind),
@@ -32,14 +35,29 @@ pub enum RpcErrorKind {
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum RpcRequestValidationErrorKind {
     MethodNotFound { method_name: String },
     ParseError { error_message: String },
 }
 
+/// Returned for the requests rejected by the rate limits of the node.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RpcRateLimitError {
+    #[error("Rate limit of {method_name} requests exceeded. Try again later")]
+    TooManyRequests { method_name: String },
+    #[error(
+        "More than {limit} {method_name} requests are processed concurrently. Try again later"
+    )]
+    TooManyConcurrentRequests { method_name: String, limit: usize },
+}
+
 /// A general Server Error
 #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq, Clone, ProtocolSchema)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum ServerError {
     TxExecutionError(TxExecutionError),
     Timeout,
@@ -161,6 +179,22 @@ impl From<std::convert::Infallible> for RpcError {
     }
 }
 
+impl From<RpcRateLimitError> for RpcError {
+    fn from(error: RpcRateLimitError) -> Self {
+        let error_data = Some(Value::String(error.to_string()));
+        let error_data_value = match to_value(error) {
+            Ok(value) => value,
+            Err(err) => {
+                return Self::new_internal_error(
+                    None,
+                    format!("Failed to serialize RpcRateLimitError: {:?}", err),
+                )
+            }
+        };
+        Self::new_handler_error(error_data, error_data_value)
+    }
+}
+
 impl fmt::Display for ServerError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -142,6 +142,18 @@ pub struct Notification {
     pub params: Value,
 }
 
+/// A notification is processed as a request whose response is dropped.
+impl From<Notification> for Request {
+    fn from(notification: Notification) -> Self {
+        Request {
+            jsonrpc: Version,
+            method: notification.method,
+            params: notification.params,
+            id: Value::Null,
+        }
+    }
+}
+
 /// One message of the JSON RPC protocol.
 ///
 /// One message, directly mapped from the structures of the protocol. See the
@@ -379,6 +391,25 @@ mod tests {
                 }),
             ]),
         );
+        // A batch of responses, one of them failed
+        one(
+            r#"[
+                {"jsonrpc": "2.0", "result": 42, "id": 1},
+                {"jsonrpc": "2.0", "error": {"code": 42, "message": "Wrong!"}, "id": 2}
+            ]"#,
+            &Message::Batch(vec![
+                Message::Response(Response {
+                    jsonrpc: Version,
+                    result: Ok(json!(42)),
+                    id: json!(1),
+                }),
+                Message::Response(Response {
+                    jsonrpc: Version,
+                    result: Err(RpcError::new(42, "Wrong!".to_owned(), None)),
+                    id: json!(2),
+                }),
+            ]),
+        );
         // Some handling of broken messages inside a batch
         let parsed = from_str(
             r#"[
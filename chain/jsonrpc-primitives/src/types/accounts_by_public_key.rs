@@ -0,0 +1,53 @@
+use serde_json::Value;
+
+#[derive(Debug, Clone)]
+pub struct RpcAccountsByPublicKeyRequest {
+    pub public_key: near_crypto::PublicKey,
+    pub block_reference: near_primitives::types::BlockReference,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct RpcAccountAccessKey {
+    pub account_id: near_primitives::types::AccountId,
+    pub access_key: near_primitives::views::AccessKeyView,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct RpcAccountsByPublicKeyResponse {
+    pub keys: Vec<RpcAccountAccessKey>,
+    pub block_hash: near_primitives::hash::CryptoHash,
+    pub block_height: near_primitives::types::BlockHeight,
+}
+
+#[derive(thiserror::Error, Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RpcAccountsByPublicKeyError {
+    #[error("The node does not maintain the index of accounts by public key")]
+    IndexUnavailable,
+    #[error("There are no fully synchronized blocks on the node yet")]
+    NoSyncedBlocks,
+    #[error("Block either has never been observed on the node or has been garbage collected: {block_reference:?}")]
+    UnknownBlock { block_reference: near_primitives::types::BlockReference },
+    #[error("The node reached its limits. Try again later. More details: {error_message}")]
+    InternalError { error_message: String },
+}
+
+impl From<RpcAccountsByPublicKeyError> for crate::errors::RpcError {
+    fn from(error: RpcAccountsByPublicKeyError) -> Self {
+        let error_data = Value::String(error.to_string());
+        let error_data_value = match serde_json::to_value(error) {
+            Ok(value) => value,
+            Err(err) => {
+                return Self::new_internal_error(
+                    None,
+                    format!("Failed to serialize RpcAccountsByPublicKeyError: {:?}", err),
+                )
+            }
+        };
+
+        Self::new_internal_or_handler_error(Some(error_data), error_data_value)
+    }
+}
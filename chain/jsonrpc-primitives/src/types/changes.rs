@@ -1,10 +1,12 @@
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct RpcStateChangesInBlockRequest {
     #[serde(flatten)]
     pub block_reference: near_primitives::types::BlockReference,
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct RpcStateChangesInBlockResponse {
     pub block_hash: near_primitives::hash::CryptoHash,
     pub changes: near_primitives::views::StateChangesView,
@@ -18,18 +20,38 @@ pub struct RpcStateChangesInBlockByTypeRequest {
     pub state_changes_request: near_primitives::views::StateChangesRequestView,
 }
 
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for RpcStateChangesInBlockByTypeRequest {
+    fn schema_name() -> String {
+        "RpcStateChangesInBlockByTypeRequest".to_string()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        super::flattened_enums_schema(
+            "Changes of the given kind in the given block.",
+            vec![
+                gen.subschema_for::<near_primitives::types::BlockReference>(),
+                gen.subschema_for::<near_primitives::views::StateChangesRequestView>(),
+            ],
+        )
+    }
+}
+
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct RpcStateChangesInBlockByTypeResponse {
     pub block_hash: near_primitives::hash::CryptoHash,
     pub changes: near_primitives::views::StateChangesKindsView,
 }
 
 #[derive(thiserror::Error, Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum RpcStateChangesError {
     #[error("Block not found: {error_message}")]
     UnknownBlock {
         #[serde(skip_serializing)]
+        #[cfg_attr(feature = "schemars", schemars(skip))]
         error_message: String,
     },
     #[error("There are no fully synchronized blocks yet")]
@@ -1,6 +1,7 @@
 use serde_json::Value;
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, arbitrary::Arbitrary)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(untagged)]
 pub enum ChunkReference {
     BlockShardId {
@@ -13,18 +14,21 @@ pub enum ChunkReference {
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, arbitrary::Arbitrary)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct RpcChunkRequest {
     #[serde(flatten)]
     pub chunk_reference: ChunkReference,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct RpcChunkResponse {
     #[serde(flatten)]
     pub chunk_view: near_primitives::views::ChunkView,
 }
 
 #[derive(thiserror::Error, Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum RpcChunkError {
     #[error("The node reached its limits. Try again later. More details: {error_message}")]
@@ -32,6 +36,7 @@ pub enum RpcChunkError {
     #[error("Block either has never been observed on the node or has been garbage collected: {error_message}")]
     UnknownBlock {
         #[serde(skip_serializing)]
+        #[cfg_attr(feature = "schemars", schemars(skip))]
         error_message: String,
     },
     // TODO Should use ShardId instead of u64
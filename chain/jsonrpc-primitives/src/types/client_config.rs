@@ -2,12 +2,18 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 #[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct RpcClientConfigResponse {
     #[serde(flatten)]
+    #[cfg_attr(
+        feature = "schemars",
+        schemars(with = "serde_json::Map<String, serde_json::Value>")
+    )]
     pub client_config: near_chain_configs::ClientConfig,
 }
 
 #[derive(thiserror::Error, Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum RpcClientConfigError {
     #[error("The node reached its limits. Try again later. More details: {error_message}")]
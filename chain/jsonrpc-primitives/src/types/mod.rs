@@ -1,3 +1,4 @@
+pub mod accounts_by_public_key;
 pub mod blocks;
 pub mod changes;
 pub mod chunks;
@@ -12,7 +13,35 @@ pub mod network_info;
 pub mod query;
 pub mod receipts;
 pub mod sandbox;
+pub mod simulation;
 pub mod split_storage;
 pub mod status;
+pub mod subscriptions;
 pub mod transactions;
 pub mod validator;
+
+/// Schema of a request flattening several enums.
+///
+/// The derived schema keeps the alternatives of only one of the flattened
+/// enums, so the request is described as matching all of them instead.
+#[cfg(feature = "schemars")]
+fn flattened_enums_schema(
+    description: &str,
+    enums: Vec<schemars::schema::Schema>,
+) -> schemars::schema::Schema {
+    use schemars::schema::{InstanceType, Metadata, SchemaObject, SubschemaValidation};
+
+    SchemaObject {
+        metadata: Some(Box::new(Metadata {
+            description: Some(description.to_string()),
+            ..Default::default()
+        })),
+        instance_type: Some(InstanceType::Object.into()),
+        subschemas: Some(Box::new(SubschemaValidation {
+            all_of: Some(enums),
+            ..Default::default()
+        })),
+        ..Default::default()
+    }
+    .into()
+}
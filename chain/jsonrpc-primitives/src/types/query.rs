@@ -6,7 +6,25 @@ pub struct RpcQueryRequest {
     pub request: near_primitives::views::QueryRequest,
 }
 
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for RpcQueryRequest {
+    fn schema_name() -> String {
+        "RpcQueryRequest".to_string()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        super::flattened_enums_schema(
+            "A query of the state at a given block.",
+            vec![
+                gen.subschema_for::<near_primitives::types::BlockReference>(),
+                gen.subschema_for::<near_primitives::views::QueryRequest>(),
+            ],
+        )
+    }
+}
+
 #[derive(thiserror::Error, Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum RpcQueryError {
     #[error("There are no fully synchronized blocks on the node yet")]
@@ -64,15 +82,25 @@ pub enum RpcQueryError {
     InternalError { error_message: String },
 }
 
+#[serde_with::serde_as]
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct RpcQueryResponse {
     #[serde(flatten)]
     pub kind: QueryResponseKind,
     pub block_height: near_primitives::types::BlockHeight,
     pub block_hash: near_primitives::hash::CryptoHash,
+    /// Trie nodes visited while answering the query, present if the query
+    /// was made with `include_proof`. The proof of a `view_state` query is
+    /// reported here as well rather than in the flattened `ViewStateResult`.
+    #[serde_as(as = "Vec<serde_with::base64::Base64>")]
+    #[cfg_attr(feature = "schemars", schemars(with = "Vec<String>"))]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub proof: Vec<std::sync::Arc<[u8]>>,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(untagged)]
 pub enum QueryResponseKind {
     ViewAccount(near_primitives::views::AccountView),
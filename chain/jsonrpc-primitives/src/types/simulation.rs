@@ -0,0 +1,78 @@
+use serde_json::Value;
+
+#[derive(Debug, Clone)]
+pub struct RpcSimulateTransactionRequest {
+    pub signed_transaction: near_primitives::transaction::SignedTransaction,
+    /// Unsigned transactions are simulated with an empty signature which is
+    /// not checked.
+    pub verify_signature: bool,
+    pub block_reference: near_primitives::types::BlockReference,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct RpcSimulateTransactionResponse {
+    #[serde(flatten)]
+    pub outcome: near_primitives::views::FinalExecutionOutcomeWithReceiptView,
+    pub state_changes: near_primitives::views::StateChangesView,
+    /// The block on top of which the transaction was executed.
+    pub block_hash: near_primitives::hash::CryptoHash,
+    pub block_height: near_primitives::types::BlockHeight,
+}
+
+#[derive(thiserror::Error, Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RpcSimulateTransactionError {
+    #[error("An error happened during transaction execution: {context:?}")]
+    InvalidTransaction {
+        #[serde(skip_serializing)]
+        #[cfg_attr(feature = "schemars", schemars(skip))]
+        context: near_primitives::errors::InvalidTxError,
+    },
+    #[error("There are no fully synchronized blocks on the node yet")]
+    NoSyncedBlocks,
+    #[error("Block either has never been observed on the node or has been garbage collected: {block_reference:?}")]
+    UnknownBlock { block_reference: near_primitives::types::BlockReference },
+    #[error("The node does not track the shard ID {requested_shard_id}")]
+    UnavailableShard { requested_shard_id: near_primitives::types::ShardId },
+    #[error("Transaction produced more than {limit} receipts")]
+    TooManyReceipts { limit: usize },
+    #[error("Transaction and its receipts burnt more than {limit} gas")]
+    GasLimitExceeded { limit: near_primitives::types::Gas },
+    #[error("Simulation took longer than {limit_ms} ms")]
+    TimeLimitExceeded { limit_ms: u64 },
+    #[error("The node reached its limits. Try again later. More details: {error_message}")]
+    InternalError { error_message: String },
+}
+
+impl From<RpcSimulateTransactionError> for crate::errors::RpcError {
+    fn from(error: RpcSimulateTransactionError) -> Self {
+        let error_data = match &error {
+            RpcSimulateTransactionError::InvalidTransaction { context } => {
+                if let Ok(value) =
+                    serde_json::to_value(crate::errors::ServerError::TxExecutionError(
+                        near_primitives::errors::TxExecutionError::InvalidTxError(context.clone()),
+                    ))
+                {
+                    value
+                } else {
+                    Value::String(error.to_string())
+                }
+            }
+            _ => Value::String(error.to_string()),
+        };
+
+        let error_data_value = match serde_json::to_value(error) {
+            Ok(value) => value,
+            Err(err) => {
+                return Self::new_internal_error(
+                    None,
+                    format!("Failed to serialize RpcSimulateTransactionError: {:?}", err),
+                )
+            }
+        };
+
+        Self::new_internal_or_handler_error(Some(error_data), error_data_value)
+    }
+}
@@ -1,6 +1,6 @@
 #[cfg(feature = "debug_types")]
 use near_client_primitives::debug::{
-    DebugBlockStatusData, EpochInfoView, TrackedShardsView, ValidatorStatus,
+    DebugBlockStatusData, EpochInfoView, StateSyncProgressView, TrackedShardsView, ValidatorStatus,
 };
 #[cfg(feature = "debug_types")]
 use near_primitives::views::{
@@ -10,6 +10,7 @@ use near_primitives::views::{
 };
 
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct RpcStatusResponse {
     #[serde(flatten)]
     pub status_response: near_primitives::views::StatusResponse,
@@ -31,6 +32,8 @@ pub enum DebugStatusResponse {
     ChainProcessingStatus(ChainProcessingInfo),
     // The state parts already requested.
     RequestedStateParts(Vec<RequestedStatePartsView>),
+    // Progress of the state part downloads of state sync and catchups.
+    StateSyncProgress(StateSyncProgressView),
     NetworkGraph(NetworkGraphView),
     RecentOutboundConnections(RecentOutboundConnectionsView),
     Routes(NetworkRoutesView),
@@ -45,15 +48,20 @@ pub struct RpcDebugStatusResponse {
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct RpcHealthResponse;
 
 #[derive(thiserror::Error, Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum RpcStatusError {
     #[error("Node is syncing")]
     NodeIsSyncing,
     #[error("No blocks for {elapsed:?}")]
-    NoNewBlocks { elapsed: time::Duration },
+    NoNewBlocks {
+        #[cfg_attr(feature = "schemars", schemars(with = "(i64, i32)"))]
+        elapsed: time::Duration,
+    },
     #[error("Epoch Out Of Bounds {epoch_id:?}")]
     EpochOutOfBounds { epoch_id: near_primitives::types::EpochId },
     #[error("The node reached its limits. Try again later. More details: {error_message}")]
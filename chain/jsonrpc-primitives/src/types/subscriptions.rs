@@ -0,0 +1,152 @@
+use serde_json::Value;
+
+/// Identifier of a subscription, unique within a single WebSocket connection.
+pub type RpcSubscriptionId = u64;
+
+/// JSON-RPC method name used for notifications pushed to the subscribers.
+pub const SUBSCRIPTION_NOTIFICATION_METHOD: &str = "EXPERIMENTAL_subscription";
+
+/// The kind of events a WebSocket client wants to receive.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RpcSubscriptionKind {
+    /// Headers of new blocks as soon as they become the head of the chain
+    /// (optimistic finality). Reorgs are reported as a header with a height
+    /// that is not greater than the previously reported one.
+    NewHeads,
+    /// Headers of blocks once they become final.
+    FinalBlocks,
+    /// Headers of the new chunks included into blocks for the given shard.
+    ChunkInclusion { shard_id: near_primitives::types::ShardId },
+    /// Status updates of a single transaction. The subscription is dropped
+    /// automatically once the transaction reaches `wait_until` status.
+    TxStatus {
+        tx_hash: near_primitives::hash::CryptoHash,
+        sender_account_id: near_primitives::types::AccountId,
+        #[serde(default = "default_tx_status_wait_until")]
+        wait_until: near_primitives::views::TxExecutionStatus,
+    },
+    /// State changes and execution outcomes of the given accounts, reported
+    /// once per final block which has any. If `method_names` or `log_prefix`
+    /// are given, only the outcomes of receipts calling one of the methods and
+    /// having logs with the prefix (e.g. `EVENT_JSON:`) are reported, together
+    /// with the state changes they caused.
+    ///
+    /// To resume after a reconnect pass the height following the last
+    /// reported block as `from_block_height`. By default the subscription
+    /// starts at the next final block.
+    AccountEvents {
+        account_ids: Vec<near_primitives::types::AccountId>,
+        #[serde(default)]
+        method_names: Vec<String>,
+        #[serde(default)]
+        log_prefix: Option<String>,
+        #[serde(default)]
+        from_block_height: Option<near_primitives::types::BlockHeight>,
+    },
+}
+
+fn default_tx_status_wait_until() -> near_primitives::views::TxExecutionStatus {
+    near_primitives::views::TxExecutionStatus::Final
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct RpcSubscribeRequest {
+    #[serde(flatten)]
+    pub subscription: RpcSubscriptionKind,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct RpcSubscribeResponse {
+    pub subscription_id: RpcSubscriptionId,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct RpcUnsubscribeRequest {
+    pub subscription_id: RpcSubscriptionId,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct RpcUnsubscribeResponse {
+    pub subscription_id: RpcSubscriptionId,
+}
+
+/// Payload of a notification sent to a subscriber.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(tag = "kind", content = "data", rename_all = "snake_case")]
+pub enum RpcSubscriptionEvent {
+    NewHead(near_primitives::views::BlockHeaderView),
+    FinalBlock(near_primitives::views::BlockHeaderView),
+    ChunkIncluded {
+        block_hash: near_primitives::hash::CryptoHash,
+        block_height: near_primitives::types::BlockHeight,
+        chunk: near_primitives::views::ChunkHeaderView,
+    },
+    TxStatus {
+        tx_hash: near_primitives::hash::CryptoHash,
+        #[serde(flatten)]
+        response: crate::types::transactions::RpcTransactionResponse,
+    },
+    AccountEvents {
+        block_hash: near_primitives::hash::CryptoHash,
+        block_height: near_primitives::types::BlockHeight,
+        state_changes: near_primitives::views::StateChangesView,
+        outcomes: Vec<near_primitives::views::ExecutionOutcomeWithIdView>,
+    },
+    /// The node failed to deliver some events to this subscription (e.g. the
+    /// node skipped blocks while catching up) and the client should re-query
+    /// the missed range explicitly. For `AccountEvents` it means that the
+    /// skipped blocks were garbage collected before their events were reported.
+    Lagged {
+        skipped: u64,
+    },
+}
+
+/// Parameters of the `EXPERIMENTAL_subscription` notification.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct RpcSubscriptionNotification {
+    pub subscription_id: RpcSubscriptionId,
+    #[serde(flatten)]
+    pub event: RpcSubscriptionEvent,
+}
+
+#[derive(thiserror::Error, Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RpcSubscriptionError {
+    #[error("Subscriptions are only available over a WebSocket connection")]
+    WebSocketRequired,
+    #[error("Too many subscriptions for this connection, the limit is {limit}")]
+    TooManySubscriptions { limit: usize },
+    #[error("Invalid subscription: {error_message}")]
+    InvalidSubscription { error_message: String },
+    #[error("Subscription {subscription_id} does not exist")]
+    UnknownSubscription { subscription_id: RpcSubscriptionId },
+    #[error("The node reached its limits. Try again later. More details: {error_message}")]
+    InternalError { error_message: String },
+}
+
+impl From<RpcSubscriptionError> for crate::errors::RpcError {
+    fn from(error: RpcSubscriptionError) -> Self {
+        let error_data = Some(Value::String(error.to_string()));
+
+        let error_data_value = match serde_json::to_value(error) {
+            Ok(value) => value,
+            Err(err) => {
+                return Self::new_internal_error(
+                    None,
+                    format!("Failed to serialize RpcSubscriptionError: {:?}", err),
+                )
+            }
+        };
+
+        Self::new_internal_or_handler_error(error_data, error_data_value)
+    }
+}
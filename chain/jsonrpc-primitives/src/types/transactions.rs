@@ -3,6 +3,7 @@ use near_primitives::types::AccountId;
 use serde_json::Value;
 
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct RpcSendTransactionRequest {
     #[serde(rename = "signed_tx_base64")]
     pub signed_transaction: near_primitives::transaction::SignedTransaction,
@@ -11,6 +12,7 @@ pub struct RpcSendTransactionRequest {
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct RpcTransactionStatusRequest {
     #[serde(flatten)]
     pub transaction_info: TransactionInfo,
@@ -19,6 +21,7 @@ pub struct RpcTransactionStatusRequest {
 }
 
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(untagged)]
 #[allow(clippy::large_enum_variant)]
 pub enum TransactionInfo {
@@ -27,17 +30,20 @@ pub enum TransactionInfo {
 }
 
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum SignedTransaction {
     #[serde(rename = "signed_tx_base64")]
     SignedTransaction(near_primitives::transaction::SignedTransaction),
 }
 
 #[derive(thiserror::Error, Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum RpcTransactionError {
     #[error("An error happened during transaction execution: {context:?}")]
     InvalidTransaction {
         #[serde(skip_serializing)]
+        #[cfg_attr(feature = "schemars", schemars(skip))]
         context: near_primitives::errors::InvalidTxError,
     },
     #[error("Node doesn't track this shard. Cannot determine whether the transaction is valid")]
@@ -50,9 +56,14 @@ pub enum RpcTransactionError {
     InternalError { debug_info: String },
     #[error("Timeout")]
     TimeoutError,
+    #[error("Replacement transaction underpriced")]
+    ReplacementUnderpriced,
+    #[error("Too many pending transactions for this key")]
+    TooManyPendingTransactions,
 }
 
-#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct RpcTransactionResponse {
     #[serde(flatten)]
     pub final_execution_outcome: Option<near_primitives::views::FinalExecutionOutcomeViewEnum>,
@@ -60,6 +71,7 @@ pub struct RpcTransactionResponse {
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct RpcBroadcastTxSyncResponse {
     pub transaction_hash: near_primitives::hash::CryptoHash,
 }
@@ -0,0 +1,34 @@
+use tonic_build::manual::{Builder, Method, Service};
+
+fn main() -> anyhow::Result<()> {
+    println!("cargo:rerun-if-changed=src/grpc/rpc.proto");
+    protobuf_codegen::Codegen::new()
+        .pure()
+        .includes(["src/"])
+        .input("src/grpc/rpc.proto")
+        .cargo_out_dir("proto")
+        .run()?;
+    // rust-protobuf doesn't generate services, so the service declared in
+    // rpc.proto is repeated here for tonic.
+    let methods = [
+        ("get_block", "GetBlock", "GetBlockRequest", "GetBlockResponse"),
+        ("get_chunk", "GetChunk", "GetChunkRequest", "GetChunkResponse"),
+        ("get_receipt", "GetReceipt", "GetReceiptRequest", "GetReceiptResponse"),
+        ("get_tx_status", "GetTxStatus", "GetTxStatusRequest", "GetTxStatusResponse"),
+        ("query", "Query", "QueryRequest", "QueryResponse"),
+    ];
+    let mut service = Service::builder().name("Rpc").package("near.rpc");
+    for (name, route_name, input_type, output_type) in methods {
+        service = service.method(
+            Method::builder()
+                .name(name)
+                .route_name(route_name)
+                .input_type(format!("crate::grpc::proto::{input_type}"))
+                .output_type(format!("crate::grpc::proto::{output_type}"))
+                .codec_path("crate::grpc::ProtobufCodec")
+                .build(),
+        );
+    }
+    Builder::new().compile(&[service.build()]);
+    Ok(())
+}
@@ -5,6 +5,10 @@ use near_jsonrpc_primitives::message::{from_slice, Message};
 use near_jsonrpc_primitives::types::changes::{
     RpcStateChangesInBlockByTypeRequest, RpcStateChangesInBlockByTypeResponse,
 };
+use near_jsonrpc_primitives::types::light_client::{
+    RpcLightClientExecutionProofRequest, RpcLightClientExecutionProofResponse,
+    RpcLightClientNextBlockRequest, RpcLightClientNextBlockResponse,
+};
 use near_jsonrpc_primitives::types::transactions::{
     RpcTransactionResponse, RpcTransactionStatusRequest,
 };
@@ -267,6 +271,20 @@ impl JsonRpcClient {
         call_method(&self.client, &self.server_addr, "EXPERIMENTAL_split_storage_info", request)
     }
 
+    pub fn next_light_client_block(
+        &self,
+        request: RpcLightClientNextBlockRequest,
+    ) -> RpcRequest<RpcLightClientNextBlockResponse> {
+        call_method(&self.client, &self.server_addr, "next_light_client_block", request)
+    }
+
+    pub fn light_client_proof(
+        &self,
+        request: RpcLightClientExecutionProofRequest,
+    ) -> RpcRequest<RpcLightClientExecutionProofResponse> {
+        call_method(&self.client, &self.server_addr, "light_client_proof", request)
+    }
+
     pub fn validators(
         &self,
         epoch_id_or_block_id: Option<EpochReference>,
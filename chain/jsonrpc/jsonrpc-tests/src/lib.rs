@@ -53,12 +53,17 @@ pub fn start_all_with_validity_period(
     );
 
     let addr = tcp::ListenerAddr::reserve_for_test();
+    let mut config = RpcConfig::new(addr);
+    config.websocket_config.enable = true;
     start_http(
-        RpcConfig::new(addr),
+        config,
+        env!("CARGO_PKG_VERSION"),
         TEST_GENESIS_CONFIG.clone(),
         actor_handles.client_actor.clone().with_auto_span_context().into_multi_sender(),
         actor_handles.view_client_actor.clone().with_auto_span_context().into_multi_sender(),
+        actor_handles.view_client_actor.clone().with_auto_span_context().into_multi_sender(),
         noop().into_multi_sender(),
+        actor_handles.chain_head.clone(),
         #[cfg(feature = "test_features")]
         noop().into_multi_sender(),
         Arc::new(DummyEntityDebugHandler {}),
@@ -163,21 +163,30 @@ fn test_query_account() {
         let query_response_1 = client
             .query(near_jsonrpc_primitives::types::query::RpcQueryRequest {
                 block_reference: BlockReference::latest(),
-                request: QueryRequest::ViewAccount { account_id: "test".parse().unwrap() },
+                request: QueryRequest::ViewAccount {
+                    account_id: "test".parse().unwrap(),
+                    include_proof: false,
+                },
             })
             .await
             .unwrap();
         let query_response_2 = client
             .query(near_jsonrpc_primitives::types::query::RpcQueryRequest {
                 block_reference: BlockReference::BlockId(BlockId::Height(0)),
-                request: QueryRequest::ViewAccount { account_id: "test".parse().unwrap() },
+                request: QueryRequest::ViewAccount {
+                    account_id: "test".parse().unwrap(),
+                    include_proof: false,
+                },
             })
             .await
             .unwrap();
         let query_response_3 = client
             .query(near_jsonrpc_primitives::types::query::RpcQueryRequest {
                 block_reference: BlockReference::BlockId(BlockId::Hash(block_hash)),
-                request: QueryRequest::ViewAccount { account_id: "test".parse().unwrap() },
+                request: QueryRequest::ViewAccount {
+                    account_id: "test".parse().unwrap(),
+                    include_proof: false,
+                },
             })
             .await
             .unwrap();
@@ -226,7 +235,10 @@ fn test_query_access_keys() {
         let query_response = client
             .query(near_jsonrpc_primitives::types::query::RpcQueryRequest {
                 block_reference: BlockReference::latest(),
-                request: QueryRequest::ViewAccessKeyList { account_id: "test".parse().unwrap() },
+                request: QueryRequest::ViewAccessKeyList {
+                    account_id: "test".parse().unwrap(),
+                    include_proof: false,
+                },
             })
             .await
             .unwrap();
@@ -277,6 +289,7 @@ fn test_query_access_key() {
                     public_key: "ed25519:23vYngy8iL7q94jby3gszBnZ9JptpMf5Hgf7KVVa2yQ2"
                         .parse()
                         .unwrap(),
+                    include_proof: false,
                 },
             })
             .await
@@ -302,6 +315,8 @@ fn test_query_state() {
                 request: QueryRequest::ViewState {
                     account_id: "test".parse().unwrap(),
                     prefix: vec![].into(),
+                    start_key: None,
+                    limit: None,
                     include_proof: false,
                 },
             })
@@ -328,6 +343,7 @@ fn test_query_call_function() {
                     account_id: "test".parse().unwrap(),
                     method_name: "method".to_string(),
                     args: vec![].into(),
+                    include_proof: false,
                 },
             })
             .await
@@ -353,7 +369,10 @@ fn test_query_contract_code() {
         let query_response = client
             .query(near_jsonrpc_primitives::types::query::RpcQueryRequest {
                 block_reference: BlockReference::latest(),
-                request: QueryRequest::ViewCode { account_id: "test".parse().unwrap() },
+                request: QueryRequest::ViewCode {
+                    account_id: "test".parse().unwrap(),
+                    include_proof: false,
+                },
             })
             .await
             .unwrap();
@@ -660,3 +679,117 @@ fn test_get_chunk_with_object_in_params() {
         assert_eq!(chunk.header.chunk_hash, same_chunk.header.chunk_hash);
     });
 }
+
+#[test]
+fn test_batch_request() {
+    test_with_client!(test_utils::NodeType::NonValidator, client, async move {
+        let json = serde_json::json!([
+            {"jsonrpc": "2.0", "id": 1, "method": "block", "params": {"block_id": 0}},
+            {"jsonrpc": "2.0", "id": 2, "method": "no_such_method", "params": []},
+            {"jsonrpc": "2.0", "id": 3, "method": "gas_price", "params": [null]},
+            {"jsonrpc": "2.0", "method": "status", "params": []},
+            true,
+        ]);
+
+        let response = &mut client
+            .client
+            .post(&client.server_addr)
+            .insert_header(("Content-Type", "application/json"))
+            .send_json(&json)
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let response =
+            serde_json::from_value::<serde_json::Value>(response.json().await.unwrap()).unwrap();
+        let responses = response.as_array().unwrap();
+        assert_eq!(responses.len(), 4);
+        assert_eq!(responses[0]["id"], 1);
+        assert_eq!(responses[0]["result"]["header"]["height"], 0);
+        assert_eq!(responses[1]["id"], 2);
+        assert_eq!(responses[1]["error"]["cause"]["name"], "METHOD_NOT_FOUND");
+        assert_eq!(responses[2]["id"], 3);
+        assert!(responses[2]["result"]["gas_price"].is_string());
+        assert_eq!(responses[3]["id"], serde_json::json!(null));
+        assert_eq!(responses[3]["error"]["cause"]["name"], "PARSE_ERROR");
+    });
+}
+
+/// Checks that all the methods described by the OpenRPC document are
+/// dispatched by the handler.
+#[test]
+fn test_all_methods_are_dispatched() {
+    test_with_client!(test_utils::NodeType::NonValidator, client, async move {
+        let json: Vec<_> = near_jsonrpc::RPC_METHODS
+            .iter()
+            .enumerate()
+            .map(|(id, method)| {
+                serde_json::json!({"jsonrpc": "2.0", "id": id, "method": method, "params": {}})
+            })
+            .collect();
+
+        let response = &mut client
+            .client
+            .post(&client.server_addr)
+            .insert_header(("Content-Type", "application/json"))
+            .send_json(&json)
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let response =
+            serde_json::from_value::<serde_json::Value>(response.json().await.unwrap()).unwrap();
+        let responses = response.as_array().unwrap();
+        assert_eq!(responses.len(), near_jsonrpc::RPC_METHODS.len());
+        for response in responses {
+            let method = near_jsonrpc::RPC_METHODS[response["id"].as_u64().unwrap() as usize];
+            assert_ne!(response["error"]["cause"]["name"], "METHOD_NOT_FOUND", "{method}");
+        }
+    });
+}
+
+#[test]
+fn test_batch_of_notifications() {
+    test_with_client!(test_utils::NodeType::NonValidator, client, async move {
+        let json = serde_json::json!([
+            {"jsonrpc": "2.0", "method": "status", "params": []},
+            {"jsonrpc": "2.0", "method": "no_such_method", "params": []},
+        ]);
+
+        let response = &mut client
+            .client
+            .post(&client.server_addr)
+            .insert_header(("Content-Type", "application/json"))
+            .send_json(&json)
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert!(response.body().await.unwrap().is_empty());
+    });
+}
+
+#[test]
+fn test_batch_request_limits() {
+    test_with_client!(test_utils::NodeType::NonValidator, client, async move {
+        let max_requests = near_jsonrpc::RpcLimitsConfig::default().batch_max_requests;
+        for batch_size in [0, max_requests + 1] {
+            let request = serde_json::json!({"jsonrpc": "2.0", "id": 1, "method": "status"});
+            let json = serde_json::Value::Array(vec![request; batch_size]);
+
+            let response = &mut client
+                .client
+                .post(&client.server_addr)
+                .insert_header(("Content-Type", "application/json"))
+                .send_json(&json)
+                .await
+                .unwrap();
+
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+            let response =
+                serde_json::from_value::<serde_json::Value>(response.json().await.unwrap())
+                    .unwrap();
+            assert_eq!(response["error"]["cause"]["name"], "PARSE_ERROR");
+        }
+    });
+}
@@ -0,0 +1,239 @@
+use actix::System;
+use awc::ws;
+use futures::{Sink, SinkExt, Stream, StreamExt};
+use serde_json::{json, Value};
+
+use near_actix_test_utils::run_actix;
+use near_crypto::InMemorySigner;
+use near_jsonrpc::client::new_client;
+use near_jsonrpc_primitives::errors::{RpcError, RpcErrorKind};
+use near_jsonrpc_primitives::message::{from_slice, Message};
+use near_jsonrpc_primitives::types::subscriptions::SUBSCRIPTION_NOTIFICATION_METHOD;
+use near_o11y::testonly::init_test_logger;
+use near_primitives::serialize::to_base64;
+use near_primitives::transaction::SignedTransaction;
+use near_primitives::types::BlockReference;
+use near_time::Clock;
+
+use near_jsonrpc_tests as test_utils;
+
+trait WsConnection:
+    Stream<Item = Result<ws::Frame, ws::ProtocolError>>
+    + Sink<ws::Message, Error = ws::ProtocolError>
+    + Unpin
+{
+}
+
+impl<T> WsConnection for T where
+    T: Stream<Item = Result<ws::Frame, ws::ProtocolError>>
+        + Sink<ws::Message, Error = ws::ProtocolError>
+        + Unpin
+{
+}
+
+async fn connect(addr: &str) -> impl WsConnection {
+    let (_response, connection) =
+        awc::Client::new().ws(format!("ws://{}/ws", addr)).connect().await.unwrap();
+    connection
+}
+
+/// Reads the next JSON RPC message, answering pings on the way.
+async fn next_message(connection: &mut impl WsConnection) -> Message {
+    loop {
+        match connection.next().await.unwrap().unwrap() {
+            ws::Frame::Text(bytes) => return from_slice(&bytes).unwrap(),
+            ws::Frame::Ping(bytes) => connection.send(ws::Message::Pong(bytes)).await.unwrap(),
+            frame => panic!("unexpected frame {:?}", frame),
+        }
+    }
+}
+
+/// Sends a request and waits for its response. Notifications received in the
+/// meantime are dropped.
+async fn call(
+    connection: &mut impl WsConnection,
+    id: u64,
+    method: &str,
+    params: Value,
+) -> Result<Value, RpcError> {
+    let request = json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params});
+    connection.send(ws::Message::Text(request.to_string().into())).await.unwrap();
+    loop {
+        if let Message::Response(response) = next_message(connection).await {
+            assert_eq!(response.id, json!(id));
+            return response.result;
+        }
+    }
+}
+
+/// Waits for the next subscription notification and returns its params.
+async fn next_notification(connection: &mut impl WsConnection) -> Value {
+    loop {
+        if let Message::Notification(notification) = next_message(connection).await {
+            assert_eq!(notification.method, SUBSCRIPTION_NOTIFICATION_METHOD);
+            return notification.params;
+        }
+    }
+}
+
+fn handler_error_name(error: &RpcError) -> Option<&str> {
+    match &error.error_struct {
+        Some(RpcErrorKind::HandlerError(error_struct)) => error_struct["name"].as_str(),
+        _ => None,
+    }
+}
+
+/// Subscribe to new heads and receive headers of consecutive blocks.
+#[test]
+fn test_subscribe_new_heads() {
+    init_test_logger();
+
+    run_actix(async {
+        let (_view_client_addr, addr) =
+            test_utils::start_all(Clock::real(), test_utils::NodeType::Validator);
+
+        actix::spawn(async move {
+            let mut connection = connect(&addr.to_string()).await;
+            let response =
+                call(&mut connection, 1, "EXPERIMENTAL_subscribe", json!({"kind": "new_heads"}))
+                    .await
+                    .unwrap();
+            let subscription_id = response["subscription_id"].clone();
+
+            let mut heights = vec![];
+            for _ in 0..3 {
+                let notification = next_notification(&mut connection).await;
+                assert_eq!(notification["subscription_id"], subscription_id);
+                assert_eq!(notification["kind"], "new_head");
+                heights.push(notification["data"]["height"].as_u64().unwrap());
+            }
+            assert!(heights.windows(2).all(|pair| pair[0] < pair[1]), "{:?}", heights);
+
+            let params = json!({"subscription_id": subscription_id});
+            let response =
+                call(&mut connection, 2, "EXPERIMENTAL_unsubscribe", params.clone()).await.unwrap();
+            assert_eq!(response["subscription_id"], subscription_id);
+            let error =
+                call(&mut connection, 3, "EXPERIMENTAL_unsubscribe", params).await.unwrap_err();
+            assert_eq!(handler_error_name(&error), Some("UNKNOWN_SUBSCRIPTION"));
+
+            System::current().stop();
+        });
+    });
+}
+
+/// Account events include the outcomes of the transactions signed by the
+/// account, starting from the requested block height.
+#[test]
+fn test_subscribe_account_events() {
+    init_test_logger();
+
+    run_actix(async {
+        let (_view_client_addr, addr) =
+            test_utils::start_all(Clock::real(), test_utils::NodeType::Validator);
+
+        actix::spawn(async move {
+            let mut connection = connect(&addr.to_string()).await;
+            let params = json!({"kind": "account_events", "account_ids": []});
+            let error =
+                call(&mut connection, 1, "EXPERIMENTAL_subscribe", params).await.unwrap_err();
+            assert_eq!(handler_error_name(&error), Some("INVALID_SUBSCRIPTION"));
+            let account_ids: Vec<_> = (0..101).map(|i| format!("account{i}.near")).collect();
+            let params = json!({"kind": "account_events", "account_ids": account_ids});
+            let error =
+                call(&mut connection, 1, "EXPERIMENTAL_subscribe", params).await.unwrap_err();
+            assert_eq!(handler_error_name(&error), Some("INVALID_SUBSCRIPTION"));
+
+            let params = json!({
+                "kind": "account_events",
+                "account_ids": ["test1"],
+                "from_block_height": 1,
+            });
+            let response =
+                call(&mut connection, 2, "EXPERIMENTAL_subscribe", params).await.unwrap();
+            let subscription_id = response["subscription_id"].clone();
+
+            let client = new_client(&format!("http://{}", addr));
+            let block_hash = client.block(BlockReference::latest()).await.unwrap().header.hash;
+            let signer = InMemorySigner::test_signer(&"test1".parse().unwrap());
+            let tx = SignedTransaction::send_money(
+                1,
+                "test1".parse().unwrap(),
+                "test2".parse().unwrap(),
+                &signer,
+                100,
+                block_hash,
+            );
+            let tx_hash = json!(tx.get_hash());
+            client.broadcast_tx_async(to_base64(&borsh::to_vec(&tx).unwrap())).await.unwrap();
+
+            let mut last_height = 0;
+            loop {
+                let notification = next_notification(&mut connection).await;
+                assert_eq!(notification["subscription_id"], subscription_id);
+                assert_eq!(notification["kind"], "account_events");
+                let height = notification["data"]["block_height"].as_u64().unwrap();
+                assert!(height > last_height, "{} is not above {}", height, last_height);
+                last_height = height;
+                let outcomes = notification["data"]["outcomes"].as_array().unwrap();
+                assert!(outcomes
+                    .iter()
+                    .all(|outcome| outcome["outcome"]["executor_id"] == "test1"));
+                if outcomes.iter().any(|outcome| outcome["id"] == tx_hash) {
+                    break;
+                }
+            }
+
+            System::current().stop();
+        });
+    });
+}
+
+/// Regular JSON RPC methods are served over the WebSocket connection too.
+#[test]
+fn test_regular_method_over_websocket() {
+    init_test_logger();
+
+    run_actix(async {
+        let (_view_client_addr, addr) =
+            test_utils::start_all(Clock::real(), test_utils::NodeType::NonValidator);
+
+        actix::spawn(async move {
+            let mut connection = connect(&addr.to_string()).await;
+            let block = call(&mut connection, 1, "block", json!({"block_id": 0})).await.unwrap();
+            assert_eq!(block["author"], "test1");
+            assert_eq!(block["header"]["height"], 0);
+
+            let error = call(&mut connection, 2, "no_such_method", json!({})).await.unwrap_err();
+            assert!(matches!(error.error_struct, Some(RpcErrorKind::RequestValidationError(_))));
+
+            System::current().stop();
+        });
+    });
+}
+
+/// Subscriptions are rejected over plain HTTP.
+#[test]
+fn test_subscribe_over_http() {
+    init_test_logger();
+
+    run_actix(async {
+        let (_view_client_addr, addr) =
+            test_utils::start_all(Clock::real(), test_utils::NodeType::NonValidator);
+
+        actix::spawn(async move {
+            let client = awc::Client::new();
+            let error = test_utils::call_method::<Value>(
+                &client,
+                &format!("http://{}", addr),
+                "EXPERIMENTAL_subscribe",
+                json!({"kind": "final_blocks"}),
+            )
+            .await
+            .unwrap_err();
+            assert_eq!(handler_error_name(&error), Some("WEBSOCKET_REQUIRED"));
+
+            System::current().stop();
+        });
+    });
+}
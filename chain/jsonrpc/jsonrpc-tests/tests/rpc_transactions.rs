@@ -3,10 +3,13 @@ use std::sync::{Arc, Mutex};
 use actix::{Actor, System};
 
 use futures::{future, FutureExt, TryFutureExt};
+use serde_json::json;
 
 use near_actix_test_utils::run_actix;
 use near_crypto::InMemorySigner;
 use near_jsonrpc::client::new_client;
+use near_jsonrpc_primitives::errors::RpcErrorKind;
+use near_jsonrpc_primitives::types::simulation::RpcSimulateTransactionResponse;
 use near_jsonrpc_primitives::types::transactions::{RpcTransactionStatusRequest, TransactionInfo};
 use near_network::test_utils::WaitOrTimeoutActor;
 use near_o11y::testonly::{init_integration_logger, init_test_logger};
@@ -246,3 +249,64 @@ fn test_check_invalid_tx() {
         }
     });
 }
+
+/// Simulating a transaction returns its outcomes without submitting it.
+#[test]
+fn test_simulate_tx() {
+    init_test_logger();
+
+    run_actix(async {
+        let (_, addr) = test_utils::start_all(Clock::real(), test_utils::NodeType::Validator);
+        let server_addr = format!("http://{}", addr);
+        let client = new_client(&server_addr);
+
+        actix::spawn(async move {
+            let block_hash = client.block(BlockReference::latest()).await.unwrap().header.hash;
+            let signer = InMemorySigner::test_signer(&"test1".parse().unwrap());
+            let tx = SignedTransaction::send_money(
+                1,
+                "test1".parse().unwrap(),
+                "test2".parse().unwrap(),
+                &signer,
+                100,
+                block_hash,
+            );
+            let signed_tx_base64 = to_base64(&borsh::to_vec(&tx).unwrap());
+            let result: RpcSimulateTransactionResponse = test_utils::call_method(
+                &awc::Client::new(),
+                &server_addr,
+                "EXPERIMENTAL_simulate_tx",
+                json!({"signed_tx_base64": signed_tx_base64, "finality": "final"}),
+            )
+            .await
+            .unwrap();
+            let outcome = result.outcome.final_outcome;
+            assert_eq!(outcome.status, FinalExecutionStatus::SuccessValue(Vec::new()));
+            assert_eq!(outcome.transaction_outcome.id, tx.get_hash());
+            assert_eq!(outcome.receipts_outcome.len(), 1);
+            assert_eq!(result.outcome.receipts.len(), 1);
+
+            let request = RpcTransactionStatusRequest {
+                transaction_info: TransactionInfo::TransactionId {
+                    tx_hash: tx.get_hash(),
+                    sender_account_id: "test1".parse().unwrap(),
+                },
+                wait_until: TxExecutionStatus::None,
+            };
+            assert!(client.tx(request).await.is_err(), "transaction should not be submitted");
+
+            let tx_base64 = to_base64(&borsh::to_vec(&tx.transaction).unwrap());
+            let error = test_utils::call_method::<serde_json::Value>(
+                &awc::Client::new(),
+                &server_addr,
+                "EXPERIMENTAL_simulate_tx",
+                json!({"signed_tx_base64": signed_tx_base64, "tx_base64": tx_base64}),
+            )
+            .await
+            .unwrap_err();
+            assert!(matches!(error.error_struct, Some(RpcErrorKind::RequestValidationError(_))));
+
+            System::current().stop();
+        });
+    });
+}
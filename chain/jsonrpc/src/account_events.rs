@@ -0,0 +1,194 @@
+//! Events of the `account_events` WebSocket subscriptions.
+//!
+//! Events are collected per final block from the same state changes which are
+//! served by `EXPERIMENTAL_changes` and from the execution outcomes of the
+//! block. A subscription tracks the height of the next block to report rather
+//! than relying on the chain events, so a client which reconnects with
+//! `from_block_height` gets every event since then, as long as the blocks
+//! haven't been garbage collected. Garbage collected blocks are reported as
+//! lagged and the subscription continues from the earliest available block.
+
+use crate::{JsonRpcHandler, RpcFrom};
+use near_async::messaging::SendAsync;
+use near_client::{GetBlock, GetExecutionOutcomesForBlock, GetReceipt, GetStateChanges};
+use near_jsonrpc_primitives::types::changes::RpcStateChangesError;
+use near_jsonrpc_primitives::types::subscriptions::RpcSubscriptionEvent;
+use near_primitives::hash::CryptoHash;
+use near_primitives::types::{
+    AccountId, BlockHeight, BlockId, BlockReference, StoreKey, SyncCheckpoint,
+};
+use near_primitives::views::{
+    ActionView, BlockView, ExecutionOutcomeWithIdView, ReceiptEnumView, ReceiptView,
+    StateChangeCauseView, StateChangesRequestView, StateChangesView,
+};
+use std::collections::HashSet;
+
+/// Maximum number of blocks checked for a single subscription per final
+/// block. Limits the work done at once when a client resumes from far behind.
+pub(crate) const MAX_BLOCKS_PER_UPDATE: u64 = 32;
+
+#[derive(Clone, Debug)]
+pub(crate) struct AccountEventsFilter {
+    pub account_ids: HashSet<AccountId>,
+    pub method_names: Vec<String>,
+    pub log_prefix: Option<String>,
+}
+
+impl AccountEventsFilter {
+    fn filters_outcomes(&self) -> bool {
+        !self.method_names.is_empty() || self.log_prefix.is_some()
+    }
+}
+
+pub(crate) enum AccountEvents {
+    /// Events in the block at the requested height, `None` if there is no
+    /// block at that height or nothing in it matches the filter.
+    Block(Option<RpcSubscriptionEvent>),
+    /// The block at the requested height has been garbage collected, the
+    /// earliest block still available is at the given height.
+    GarbageCollected { earliest_height: BlockHeight },
+}
+
+/// Returns the events in the block at the given height, which must not be
+/// above `final_block`.
+pub(crate) async fn fetch_account_events(
+    handler: &JsonRpcHandler,
+    filter: &AccountEventsFilter,
+    final_block: &BlockView,
+    height: BlockHeight,
+) -> Result<AccountEvents, RpcStateChangesError> {
+    let block_hash = if height == final_block.header.height {
+        final_block.header.hash
+    } else {
+        let block_reference = BlockReference::BlockId(BlockId::Height(height));
+        let block: Result<BlockView, RpcStateChangesError> =
+            handler.view_client_send(GetBlock(block_reference)).await;
+        match block {
+            Ok(block) => block.header.hash,
+            Err(RpcStateChangesError::UnknownBlock { .. }) => {
+                let block_reference =
+                    BlockReference::SyncCheckpoint(SyncCheckpoint::EarliestAvailable);
+                let earliest_block: BlockView =
+                    handler.view_client_send(GetBlock(block_reference)).await?;
+                let earliest_height = earliest_block.header.height;
+                if height < earliest_height {
+                    return Ok(AccountEvents::GarbageCollected { earliest_height });
+                }
+                // The height was skipped.
+                return Ok(AccountEvents::Block(None));
+            }
+            Err(err) => return Err(err),
+        }
+    };
+
+    let outcomes = fetch_outcomes(handler, filter, block_hash).await?;
+    let mut state_changes = fetch_state_changes(handler, filter, block_hash).await?;
+    if filter.filters_outcomes() {
+        let outcome_ids: HashSet<&CryptoHash> =
+            outcomes.iter().map(|outcome| &outcome.id).collect();
+        state_changes.retain(|change| {
+            cause_hash(&change.cause).is_some_and(|hash| outcome_ids.contains(hash))
+        });
+    }
+    if outcomes.is_empty() && state_changes.is_empty() {
+        return Ok(AccountEvents::Block(None));
+    }
+    Ok(AccountEvents::Block(Some(RpcSubscriptionEvent::AccountEvents {
+        block_hash,
+        block_height: height,
+        state_changes,
+        outcomes,
+    })))
+}
+
+async fn fetch_state_changes(
+    handler: &JsonRpcHandler,
+    filter: &AccountEventsFilter,
+    block_hash: CryptoHash,
+) -> Result<StateChangesView, RpcStateChangesError> {
+    // Sorted so that the changes are always reported in the same order.
+    let mut account_ids: Vec<AccountId> = filter.account_ids.iter().cloned().collect();
+    account_ids.sort();
+    let requests = [
+        StateChangesRequestView::AccountChanges { account_ids: account_ids.clone() },
+        StateChangesRequestView::AllAccessKeyChanges { account_ids: account_ids.clone() },
+        StateChangesRequestView::ContractCodeChanges { account_ids: account_ids.clone() },
+        StateChangesRequestView::DataChanges { account_ids, key_prefix: StoreKey::from(vec![]) },
+    ];
+    let mut state_changes = StateChangesView::new();
+    for state_changes_request in requests {
+        let changes: StateChangesView =
+            handler.view_client_send(GetStateChanges { block_hash, state_changes_request }).await?;
+        state_changes.extend(changes);
+    }
+    Ok(state_changes)
+}
+
+async fn fetch_outcomes(
+    handler: &JsonRpcHandler,
+    filter: &AccountEventsFilter,
+    block_hash: CryptoHash,
+) -> Result<Vec<ExecutionOutcomeWithIdView>, RpcStateChangesError> {
+    let outcomes_by_shard = handler
+        .view_client_sender
+        .send_async(GetExecutionOutcomesForBlock { block_hash })
+        .await
+        .map_err(RpcStateChangesError::rpc_from)?
+        .map_err(|error_message| RpcStateChangesError::InternalError { error_message })?;
+    let mut outcomes_by_shard: Vec<_> = outcomes_by_shard.into_iter().collect();
+    outcomes_by_shard.sort_by_key(|(shard_id, _)| *shard_id);
+
+    let mut outcomes = vec![];
+    for mut outcome in outcomes_by_shard.into_iter().flat_map(|(_, outcomes)| outcomes) {
+        if !filter.account_ids.contains(&outcome.outcome.executor_id) {
+            continue;
+        }
+        if let Some(log_prefix) = &filter.log_prefix {
+            outcome.outcome.logs.retain(|log| log.starts_with(log_prefix.as_str()));
+            if outcome.outcome.logs.is_empty() {
+                continue;
+            }
+        }
+        if !filter.method_names.is_empty()
+            && !calls_any_method(handler, outcome.id, &filter.method_names).await?
+        {
+            continue;
+        }
+        outcomes.push(outcome);
+    }
+    Ok(outcomes)
+}
+
+/// Checks whether the receipt with the given id calls one of the methods.
+/// Transactions are never matched, their function calls are executed as
+/// receipts.
+async fn calls_any_method(
+    handler: &JsonRpcHandler,
+    receipt_id: CryptoHash,
+    method_names: &[String],
+) -> Result<bool, RpcStateChangesError> {
+    let receipt = handler
+        .view_client_sender
+        .send_async(GetReceipt { receipt_id })
+        .await
+        .map_err(RpcStateChangesError::rpc_from)?
+        .map_err(|err| RpcStateChangesError::InternalError { error_message: err.to_string() })?;
+    let Some(ReceiptView { receipt: ReceiptEnumView::Action { actions, .. }, .. }) = receipt else {
+        return Ok(false);
+    };
+    Ok(actions.iter().any(|action| {
+        matches!(action, ActionView::FunctionCall { method_name, .. } if method_names.contains(method_name))
+    }))
+}
+
+/// Returns the hash of the transaction or receipt which caused a state change.
+fn cause_hash(cause: &StateChangeCauseView) -> Option<&CryptoHash> {
+    match cause {
+        StateChangeCauseView::TransactionProcessing { tx_hash } => Some(tx_hash),
+        StateChangeCauseView::ActionReceiptProcessingStarted { receipt_hash }
+        | StateChangeCauseView::ActionReceiptGasReward { receipt_hash }
+        | StateChangeCauseView::ReceiptProcessing { receipt_hash }
+        | StateChangeCauseView::PostponedReceipt { receipt_hash } => Some(receipt_hash),
+        _ => None,
+    }
+}
@@ -0,0 +1,81 @@
+use near_async::messaging::AsyncSendError;
+use serde_json::Value;
+
+use near_client_primitives::types::GetAccountsByPublicKeyError;
+use near_jsonrpc_primitives::errors::RpcParseError;
+use near_jsonrpc_primitives::types::accounts_by_public_key::{
+    RpcAccountsByPublicKeyError, RpcAccountsByPublicKeyRequest,
+};
+use near_primitives::types::BlockReference;
+
+use super::{Params, RpcFrom, RpcRequest};
+
+/// Params of the method, also used to describe them in the OpenRPC document.
+#[derive(serde::Deserialize, schemars::JsonSchema)]
+pub(crate) struct AccountsByPublicKeyParams {
+    public_key: near_crypto::PublicKey,
+    #[serde(flatten)]
+    block_reference: Option<BlockReference>,
+}
+
+impl RpcRequest for RpcAccountsByPublicKeyRequest {
+    fn parse(value: Value) -> Result<Self, RpcParseError> {
+        let params: AccountsByPublicKeyParams = Params::parse(value)?;
+        Ok(Self {
+            public_key: params.public_key,
+            block_reference: params.block_reference.unwrap_or_else(BlockReference::latest),
+        })
+    }
+}
+
+impl RpcFrom<AsyncSendError> for RpcAccountsByPublicKeyError {
+    fn rpc_from(error: AsyncSendError) -> Self {
+        Self::InternalError { error_message: error.to_string() }
+    }
+}
+
+impl RpcFrom<GetAccountsByPublicKeyError> for RpcAccountsByPublicKeyError {
+    fn rpc_from(error: GetAccountsByPublicKeyError) -> Self {
+        match error {
+            GetAccountsByPublicKeyError::IndexUnavailable => Self::IndexUnavailable,
+            GetAccountsByPublicKeyError::NoSyncedBlocks => Self::NoSyncedBlocks,
+            GetAccountsByPublicKeyError::UnknownBlock { block_reference } => {
+                Self::UnknownBlock { block_reference }
+            }
+            GetAccountsByPublicKeyError::InternalError { error_message } => {
+                Self::InternalError { error_message }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::RpcRequest;
+    use near_crypto::{KeyType, PublicKey};
+    use near_jsonrpc_primitives::types::accounts_by_public_key::RpcAccountsByPublicKeyRequest;
+    use near_primitives::types::{BlockReference, Finality};
+
+    #[test]
+    fn test_parse_accounts_by_public_key_params() {
+        let public_key = PublicKey::from_seed(KeyType::ED25519, "test");
+        let params = serde_json::json!({"public_key": public_key.to_string()});
+        let request = RpcAccountsByPublicKeyRequest::parse(params).unwrap();
+        assert_eq!(request.public_key, public_key);
+        assert_eq!(request.block_reference, BlockReference::latest());
+    }
+
+    #[test]
+    fn test_parse_accounts_by_public_key_params_with_finality() {
+        let public_key = PublicKey::from_seed(KeyType::ED25519, "test");
+        let params = serde_json::json!({"public_key": public_key.to_string(), "finality": "final"});
+        let request = RpcAccountsByPublicKeyRequest::parse(params).unwrap();
+        assert_eq!(request.block_reference, BlockReference::Finality(Finality::Final));
+    }
+
+    #[test]
+    fn test_parse_accounts_by_public_key_params_invalid_key() {
+        let params = serde_json::json!({"public_key": "ed25519:invalid"});
+        assert!(RpcAccountsByPublicKeyRequest::parse(params).is_err());
+    }
+}
@@ -4,6 +4,7 @@ use serde_json::Value;
 use near_jsonrpc_primitives::errors::RpcParseError;
 use near_jsonrpc_primitives::errors::{RpcError, ServerError};
 
+mod accounts_by_public_key;
 mod blocks;
 mod changes;
 mod chunks;
@@ -17,11 +18,16 @@ mod network_info;
 mod query;
 mod receipts;
 mod sandbox;
+mod simulation;
 mod split_storage;
 mod status;
+mod subscriptions;
 mod transactions;
 mod validator;
 
+pub(crate) use accounts_by_public_key::AccountsByPublicKeyParams;
+pub(crate) use simulation::SimulateTransactionParams;
+
 pub trait RpcRequest: Sized {
     fn parse(value: Value) -> Result<Self, RpcParseError>;
 }
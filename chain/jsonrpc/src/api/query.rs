@@ -57,20 +57,23 @@ fn parse_path_data(path: String, data: String) -> Result<RpcQueryRequest, RpcPar
     let maybe_extra_arg = path_parts.next();
 
     let request = match query_command {
-        "account" => QueryRequest::ViewAccount { account_id },
+        "account" => QueryRequest::ViewAccount { account_id, include_proof: false },
         "access_key" => match maybe_extra_arg {
-            None => QueryRequest::ViewAccessKeyList { account_id },
+            None => QueryRequest::ViewAccessKeyList { account_id, include_proof: false },
             Some(pk) => QueryRequest::ViewAccessKey {
                 account_id,
                 public_key: pk
                     .parse()
                     .map_err(|_| RpcParseError("Invalid public key".to_string()))?,
+                include_proof: false,
             },
         },
-        "code" => QueryRequest::ViewCode { account_id },
+        "code" => QueryRequest::ViewCode { account_id, include_proof: false },
         "contract" => QueryRequest::ViewState {
             account_id,
             prefix: parse_data()?.into(),
+            start_key: None,
+            limit: None,
             include_proof: false,
         },
         "call" => match maybe_extra_arg {
@@ -78,6 +81,7 @@ fn parse_path_data(path: String, data: String) -> Result<RpcQueryRequest, RpcPar
                 account_id,
                 method_name: method_name.to_string(),
                 args: parse_data()?.into(),
+                include_proof: false,
             },
             None => return Err(RpcParseError("Method name is missing".to_string())),
         },
@@ -136,11 +140,13 @@ impl RpcFrom<QueryError> for RpcQueryError {
 
 impl RpcFrom<QueryResponse> for RpcQueryResponse {
     fn rpc_from(query_response: QueryResponse) -> Self {
-        Self {
-            kind: RpcFrom::rpc_from(query_response.kind),
-            block_hash: query_response.block_hash,
-            block_height: query_response.block_height,
+        let QueryResponse { mut kind, block_height, block_hash, mut proof } = query_response;
+        // Both proofs are serialized under the same key, so the one of
+        // `view_state` is moved out to not be lost when deserializing.
+        if let near_primitives::views::QueryResponseKind::ViewState(view_state_result) = &mut kind {
+            proof.append(&mut view_state_result.proof);
         }
+        Self { kind: RpcFrom::rpc_from(kind), block_hash, block_height, proof }
     }
 }
 
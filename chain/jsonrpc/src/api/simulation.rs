@@ -0,0 +1,136 @@
+use near_async::messaging::AsyncSendError;
+use serde_json::Value;
+
+use near_client_primitives::types::SimulateTransactionError;
+use near_crypto::{KeyType, Signature};
+use near_jsonrpc_primitives::errors::RpcParseError;
+use near_jsonrpc_primitives::types::simulation::{
+    RpcSimulateTransactionError, RpcSimulateTransactionRequest,
+};
+use near_primitives::borsh::BorshDeserialize;
+use near_primitives::transaction::{SignedTransaction, Transaction};
+use near_primitives::types::BlockReference;
+
+use super::transactions::decode_signed_transaction;
+use super::{Params, RpcFrom, RpcRequest};
+
+/// Params of the method, also used to describe them in the OpenRPC document.
+#[derive(serde::Deserialize, schemars::JsonSchema)]
+pub(crate) struct SimulateTransactionParams {
+    #[serde(default)]
+    signed_tx_base64: Option<String>,
+    #[serde(default)]
+    tx_base64: Option<String>,
+    #[serde(flatten)]
+    block_reference: Option<BlockReference>,
+}
+
+impl RpcRequest for RpcSimulateTransactionRequest {
+    fn parse(value: Value) -> Result<Self, RpcParseError> {
+        let params: SimulateTransactionParams = Params::parse(value)?;
+        let (signed_transaction, verify_signature) =
+            match (params.signed_tx_base64, params.tx_base64) {
+                (Some(signed_tx), None) => (decode_signed_transaction(signed_tx)?, true),
+                (None, Some(tx)) => (
+                    SignedTransaction::new(
+                        Signature::empty(KeyType::ED25519),
+                        decode_transaction(tx)?,
+                    ),
+                    false,
+                ),
+                _ => {
+                    return Err(RpcParseError(
+                        "Exactly one of signed_tx_base64 and tx_base64 must be passed".to_string(),
+                    ))
+                }
+            };
+        Ok(Self {
+            signed_transaction,
+            verify_signature,
+            block_reference: params.block_reference.unwrap_or_else(BlockReference::latest),
+        })
+    }
+}
+
+impl RpcFrom<AsyncSendError> for RpcSimulateTransactionError {
+    fn rpc_from(error: AsyncSendError) -> Self {
+        Self::InternalError { error_message: error.to_string() }
+    }
+}
+
+impl RpcFrom<SimulateTransactionError> for RpcSimulateTransactionError {
+    fn rpc_from(error: SimulateTransactionError) -> Self {
+        match error {
+            SimulateTransactionError::NoSyncedBlocks => Self::NoSyncedBlocks,
+            SimulateTransactionError::UnknownBlock { block_reference } => {
+                Self::UnknownBlock { block_reference }
+            }
+            SimulateTransactionError::UnavailableShard { requested_shard_id } => {
+                Self::UnavailableShard { requested_shard_id }
+            }
+            SimulateTransactionError::InvalidTransaction { error } => {
+                Self::InvalidTransaction { context: error }
+            }
+            SimulateTransactionError::TooManyReceipts { limit } => Self::TooManyReceipts { limit },
+            SimulateTransactionError::GasLimitExceeded { limit } => {
+                Self::GasLimitExceeded { limit }
+            }
+            SimulateTransactionError::TimeLimitExceeded { limit } => {
+                Self::TimeLimitExceeded { limit_ms: limit.as_millis() as u64 }
+            }
+            SimulateTransactionError::InternalError { error_message } => {
+                Self::InternalError { error_message }
+            }
+        }
+    }
+}
+
+fn decode_transaction(value: String) -> Result<Transaction, RpcParseError> {
+    let bytes = near_primitives::serialize::from_base64(&value)
+        .map_err(|err| RpcParseError(format!("Failed to decode transaction: {}", err)))?;
+    Transaction::try_from_slice(&bytes)
+        .map_err(|err| RpcParseError(format!("Failed to decode transaction: {}", err)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::RpcRequest;
+    use near_jsonrpc_primitives::types::simulation::RpcSimulateTransactionRequest;
+    use near_primitives::borsh;
+    use near_primitives::hash::CryptoHash;
+    use near_primitives::serialize::to_base64;
+    use near_primitives::transaction::SignedTransaction;
+    use near_primitives::types::{BlockId, BlockReference};
+
+    #[test]
+    fn test_parse_simulate_tx_params_with_signed_tx() {
+        let tx = SignedTransaction::empty(CryptoHash::new());
+        let str_tx = to_base64(&borsh::to_vec(&tx).unwrap());
+        let params = serde_json::json!({"signed_tx_base64": str_tx});
+        let request = RpcSimulateTransactionRequest::parse(params).unwrap();
+        assert!(request.verify_signature);
+        assert_eq!(request.signed_transaction, tx);
+        assert_eq!(request.block_reference, BlockReference::latest());
+    }
+
+    #[test]
+    fn test_parse_simulate_tx_params_with_unsigned_tx() {
+        let tx = SignedTransaction::empty(CryptoHash::new());
+        let str_tx = to_base64(&borsh::to_vec(&tx.transaction).unwrap());
+        let params = serde_json::json!({"tx_base64": str_tx, "block_id": 1});
+        let request = RpcSimulateTransactionRequest::parse(params).unwrap();
+        assert!(!request.verify_signature);
+        assert_eq!(request.signed_transaction.get_hash(), tx.get_hash());
+        assert_eq!(request.block_reference, BlockReference::BlockId(BlockId::Height(1)));
+    }
+
+    // The params are invalid because both a signed and an unsigned transaction are passed
+    #[test]
+    fn test_parse_simulate_tx_params_with_both_txs() {
+        let tx = SignedTransaction::empty(CryptoHash::new());
+        let str_signed_tx = to_base64(&borsh::to_vec(&tx).unwrap());
+        let str_tx = to_base64(&borsh::to_vec(&tx.transaction).unwrap());
+        let params = serde_json::json!({"signed_tx_base64": str_signed_tx, "tx_base64": str_tx});
+        assert!(RpcSimulateTransactionRequest::parse(params).is_err());
+    }
+}
@@ -0,0 +1,18 @@
+use serde_json::Value;
+
+use near_jsonrpc_primitives::errors::RpcParseError;
+use near_jsonrpc_primitives::types::subscriptions::{RpcSubscribeRequest, RpcUnsubscribeRequest};
+
+use super::{Params, RpcRequest};
+
+impl RpcRequest for RpcSubscribeRequest {
+    fn parse(value: Value) -> Result<Self, RpcParseError> {
+        Params::parse(value)
+    }
+}
+
+impl RpcRequest for RpcUnsubscribeRequest {
+    fn parse(value: Value) -> Result<Self, RpcParseError> {
+        Params::parse(value)
+    }
+}
@@ -74,7 +74,7 @@ impl RpcFrom<TxStatusError> for RpcTransactionError {
     }
 }
 
-fn decode_signed_transaction(value: String) -> Result<SignedTransaction, RpcParseError> {
+pub(super) fn decode_signed_transaction(value: String) -> Result<SignedTransaction, RpcParseError> {
     let bytes = near_primitives::serialize::from_base64(&value)
         .map_err(|err| RpcParseError(format!("Failed to decode transaction: {}", err)))?;
     SignedTransaction::try_from_slice(&bytes)
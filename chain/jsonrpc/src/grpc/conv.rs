@@ -0,0 +1,493 @@
+/// Conversions between the protobuf messages of the gRPC API and the types of
+/// the JSON RPC.
+use super::proto;
+use borsh::BorshDeserialize as _;
+use near_crypto::{PublicKey, Signature};
+use near_jsonrpc_primitives::types::blocks::RpcBlockRequest;
+use near_jsonrpc_primitives::types::chunks::{ChunkReference, RpcChunkRequest};
+use near_jsonrpc_primitives::types::query::{QueryResponseKind, RpcQueryRequest, RpcQueryResponse};
+use near_jsonrpc_primitives::types::receipts::{ReceiptReference, RpcReceiptRequest};
+use near_jsonrpc_primitives::types::transactions::{
+    RpcTransactionResponse, RpcTransactionStatusRequest, TransactionInfo,
+};
+use near_primitives::hash::CryptoHash;
+use near_primitives::types::{
+    AccountId, Balance, BlockId, BlockReference, Finality, ShardId, SyncCheckpoint,
+};
+use near_primitives::views::validator_stake_view::ValidatorStakeView;
+use near_primitives::views::{
+    AccessKeyView, AccountView, BlockHeaderView, BlockView, ChunkHeaderView, ChunkView,
+    CongestionInfoView, FinalExecutionOutcomeViewEnum, QueryRequest, ReceiptView,
+    TxExecutionStatus,
+};
+use protobuf::{EnumOrUnknown, MessageField as MF};
+use tonic::Status;
+
+fn missing(field: &str) -> Status {
+    Status::invalid_argument(format!("{field}: missing, while required"))
+}
+
+fn invalid(field: &str, err: impl std::fmt::Display) -> Status {
+    Status::invalid_argument(format!("{field}: {err}"))
+}
+
+#[allow(clippy::result_large_err)]
+fn parse_hash(field: &str, hash: &proto::CryptoHash) -> Result<CryptoHash, Status> {
+    CryptoHash::try_from(&hash.hash[..]).map_err(|err| invalid(field, err))
+}
+
+#[allow(clippy::result_large_err)]
+fn parse_required_hash(field: &str, hash: &MF<proto::CryptoHash>) -> Result<CryptoHash, Status> {
+    parse_hash(field, hash.as_ref().ok_or_else(|| missing(field))?)
+}
+
+#[allow(clippy::result_large_err)]
+fn parse_account_id(field: &str, account_id: &str) -> Result<AccountId, Status> {
+    account_id.parse().map_err(|err| invalid(field, err))
+}
+
+#[allow(clippy::result_large_err)]
+fn parse_enum<E: protobuf::Enum>(field: &str, value: EnumOrUnknown<E>) -> Result<E, Status> {
+    value.enum_value().map_err(|value| invalid(field, format!("unknown value {value}")))
+}
+
+#[allow(clippy::result_large_err)]
+fn parse_block_reference(
+    block_reference: &MF<proto::BlockReference>,
+) -> Result<BlockReference, Status> {
+    use proto::block_reference::{Finality as F, Reference, SyncCheckpoint as S};
+    let field = "block_reference";
+    let reference = block_reference.reference.as_ref().ok_or_else(|| missing(field))?;
+    Ok(match reference {
+        Reference::BlockHeight(height) => BlockReference::BlockId(BlockId::Height(*height)),
+        Reference::BlockHash(hash) => {
+            BlockReference::BlockId(BlockId::Hash(parse_hash(field, hash)?))
+        }
+        Reference::Finality(finality) => {
+            BlockReference::Finality(match parse_enum(field, *finality)? {
+                F::FINAL => Finality::Final,
+                F::NEAR_FINAL => Finality::DoomSlug,
+                F::OPTIMISTIC => Finality::None,
+            })
+        }
+        Reference::SyncCheckpoint(checkpoint) => {
+            BlockReference::SyncCheckpoint(match parse_enum(field, *checkpoint)? {
+                S::GENESIS => SyncCheckpoint::Genesis,
+                S::EARLIEST_AVAILABLE => SyncCheckpoint::EarliestAvailable,
+            })
+        }
+    })
+}
+
+impl TryFrom<&proto::GetBlockRequest> for RpcBlockRequest {
+    type Error = Status;
+    fn try_from(x: &proto::GetBlockRequest) -> Result<Self, Self::Error> {
+        Ok(Self { block_reference: parse_block_reference(&x.block_reference)? })
+    }
+}
+
+impl TryFrom<&proto::GetChunkRequest> for RpcChunkRequest {
+    type Error = Status;
+    fn try_from(x: &proto::GetChunkRequest) -> Result<Self, Self::Error> {
+        use proto::get_chunk_request::block_shard_id::Block_id;
+        use proto::get_chunk_request::Chunk_reference;
+        let chunk_reference = match &x.chunk_reference {
+            Some(Chunk_reference::ChunkHash(hash)) => {
+                ChunkReference::ChunkHash { chunk_id: parse_hash("chunk_hash", hash)? }
+            }
+            Some(Chunk_reference::BlockShardId(block_shard_id)) => {
+                let block_id = match &block_shard_id.block_id {
+                    Some(Block_id::BlockHeight(height)) => BlockId::Height(*height),
+                    Some(Block_id::BlockHash(hash)) => {
+                        BlockId::Hash(parse_hash("block_hash", hash)?)
+                    }
+                    None => return Err(missing("block_id")),
+                };
+                ChunkReference::BlockShardId {
+                    block_id,
+                    shard_id: ShardId::new(block_shard_id.shard_id),
+                }
+            }
+            None => return Err(missing("chunk_reference")),
+        };
+        Ok(Self { chunk_reference })
+    }
+}
+
+impl TryFrom<&proto::GetReceiptRequest> for RpcReceiptRequest {
+    type Error = Status;
+    fn try_from(x: &proto::GetReceiptRequest) -> Result<Self, Self::Error> {
+        let receipt_id = parse_required_hash("receipt_id", &x.receipt_id)?;
+        Ok(Self { receipt_reference: ReceiptReference { receipt_id } })
+    }
+}
+
+impl From<proto::TxExecutionStatus> for TxExecutionStatus {
+    fn from(x: proto::TxExecutionStatus) -> Self {
+        use proto::TxExecutionStatus as S;
+        match x {
+            S::TX_EXECUTION_STATUS_NONE => Self::None,
+            S::TX_EXECUTION_STATUS_INCLUDED => Self::Included,
+            S::TX_EXECUTION_STATUS_EXECUTED_OPTIMISTIC => Self::ExecutedOptimistic,
+            S::TX_EXECUTION_STATUS_INCLUDED_FINAL => Self::IncludedFinal,
+            S::TX_EXECUTION_STATUS_EXECUTED => Self::Executed,
+            S::TX_EXECUTION_STATUS_FINAL => Self::Final,
+        }
+    }
+}
+
+impl From<&TxExecutionStatus> for proto::TxExecutionStatus {
+    fn from(x: &TxExecutionStatus) -> Self {
+        match x {
+            TxExecutionStatus::None => Self::TX_EXECUTION_STATUS_NONE,
+            TxExecutionStatus::Included => Self::TX_EXECUTION_STATUS_INCLUDED,
+            TxExecutionStatus::ExecutedOptimistic => Self::TX_EXECUTION_STATUS_EXECUTED_OPTIMISTIC,
+            TxExecutionStatus::IncludedFinal => Self::TX_EXECUTION_STATUS_INCLUDED_FINAL,
+            TxExecutionStatus::Executed => Self::TX_EXECUTION_STATUS_EXECUTED,
+            TxExecutionStatus::Final => Self::TX_EXECUTION_STATUS_FINAL,
+        }
+    }
+}
+
+impl TryFrom<&proto::GetTxStatusRequest> for RpcTransactionStatusRequest {
+    type Error = Status;
+    fn try_from(x: &proto::GetTxStatusRequest) -> Result<Self, Self::Error> {
+        let transaction_info = TransactionInfo::TransactionId {
+            tx_hash: parse_required_hash("tx_hash", &x.tx_hash)?,
+            sender_account_id: parse_account_id("sender_account_id", &x.sender_account_id)?,
+        };
+        let wait_until = match x.wait_until {
+            Some(wait_until) => parse_enum("wait_until", wait_until)?.into(),
+            None => TxExecutionStatus::default(),
+        };
+        Ok(Self { transaction_info, wait_until })
+    }
+}
+
+impl TryFrom<&proto::QueryRequest> for RpcQueryRequest {
+    type Error = Status;
+    fn try_from(x: &proto::QueryRequest) -> Result<Self, Self::Error> {
+        use proto::query_request::Request;
+        let block_reference = parse_block_reference(&x.block_reference)?;
+        let include_proof = x.include_proof;
+        let request = match x.request.as_ref().ok_or_else(|| missing("request"))? {
+            Request::ViewAccount(r) => QueryRequest::ViewAccount {
+                account_id: parse_account_id("account_id", &r.account_id)?,
+                include_proof,
+            },
+            Request::ViewCode(r) => QueryRequest::ViewCode {
+                account_id: parse_account_id("account_id", &r.account_id)?,
+                include_proof,
+            },
+            Request::ViewState(r) => QueryRequest::ViewState {
+                account_id: parse_account_id("account_id", &r.account_id)?,
+                prefix: r.prefix.clone().into(),
+                start_key: r.start_key.clone().map(Into::into),
+                limit: r.limit,
+                include_proof,
+            },
+            Request::ViewAccessKey(r) => {
+                let public_key = r.public_key.as_ref().ok_or_else(|| missing("public_key"))?;
+                QueryRequest::ViewAccessKey {
+                    account_id: parse_account_id("account_id", &r.account_id)?,
+                    public_key: PublicKey::try_from_slice(&public_key.borsh)
+                        .map_err(|err| invalid("public_key", err))?,
+                    include_proof,
+                }
+            }
+            Request::ViewAccessKeyList(r) => QueryRequest::ViewAccessKeyList {
+                account_id: parse_account_id("account_id", &r.account_id)?,
+                include_proof,
+            },
+            Request::CallFunction(r) => QueryRequest::CallFunction {
+                account_id: parse_account_id("account_id", &r.account_id)?,
+                method_name: r.method_name.clone(),
+                args: r.args.clone().into(),
+                include_proof,
+            },
+        };
+        Ok(Self { block_reference, request })
+    }
+}
+
+//////////////////////////////////////////
+
+impl From<&CryptoHash> for proto::CryptoHash {
+    fn from(x: &CryptoHash) -> Self {
+        Self { hash: x.0.into(), ..Self::default() }
+    }
+}
+
+impl From<&PublicKey> for proto::PublicKey {
+    fn from(x: &PublicKey) -> Self {
+        Self { borsh: borsh::to_vec(x).unwrap(), ..Self::default() }
+    }
+}
+
+impl From<&Signature> for proto::Signature {
+    fn from(x: &Signature) -> Self {
+        Self { borsh: borsh::to_vec(x).unwrap(), ..Self::default() }
+    }
+}
+
+fn hash(x: &CryptoHash) -> MF<proto::CryptoHash> {
+    MF::some(x.into())
+}
+
+fn balance(x: Balance) -> MF<proto::Balance> {
+    MF::some(proto::Balance { decimal: x.to_string(), ..Default::default() })
+}
+
+/// Wraps the borsh encoding of a view into a message with a single `borsh`
+/// field.
+macro_rules! borsh_message {
+    ($message:ident, $view:expr) => {
+        proto::$message { borsh: borsh::to_vec($view).unwrap(), ..Default::default() }
+    };
+}
+
+impl From<&ValidatorStakeView> for proto::ValidatorStake {
+    fn from(x: &ValidatorStakeView) -> Self {
+        let ValidatorStakeView::V1(x) = x;
+        Self {
+            account_id: x.account_id.to_string(),
+            public_key: MF::some((&x.public_key).into()),
+            stake: balance(x.stake),
+            ..Self::default()
+        }
+    }
+}
+
+impl From<&BlockHeaderView> for proto::BlockHeader {
+    fn from(x: &BlockHeaderView) -> Self {
+        Self {
+            height: x.height,
+            prev_height: x.prev_height,
+            epoch_id: hash(&x.epoch_id),
+            next_epoch_id: hash(&x.next_epoch_id),
+            hash: hash(&x.hash),
+            prev_hash: hash(&x.prev_hash),
+            prev_state_root: hash(&x.prev_state_root),
+            block_body_hash: x.block_body_hash.as_ref().map(Into::into).into(),
+            chunk_receipts_root: hash(&x.chunk_receipts_root),
+            chunk_headers_root: hash(&x.chunk_headers_root),
+            chunk_tx_root: hash(&x.chunk_tx_root),
+            outcome_root: hash(&x.outcome_root),
+            chunks_included: x.chunks_included,
+            challenges_root: hash(&x.challenges_root),
+            timestamp_nanosec: x.timestamp_nanosec,
+            random_value: hash(&x.random_value),
+            validator_proposals: x.validator_proposals.iter().map(Into::into).collect(),
+            chunk_mask: x.chunk_mask.clone(),
+            gas_price: balance(x.gas_price),
+            block_ordinal: x.block_ordinal,
+            total_supply: balance(x.total_supply),
+            challenges_result: x
+                .challenges_result
+                .iter()
+                .map(|slashed| proto::SlashedValidator {
+                    account_id: slashed.account_id.to_string(),
+                    is_double_sign: slashed.is_double_sign,
+                    ..Default::default()
+                })
+                .collect(),
+            last_final_block: hash(&x.last_final_block),
+            last_ds_final_block: hash(&x.last_ds_final_block),
+            next_bp_hash: hash(&x.next_bp_hash),
+            block_merkle_root: hash(&x.block_merkle_root),
+            epoch_sync_data_hash: x.epoch_sync_data_hash.as_ref().map(Into::into).into(),
+            approvals: x
+                .approvals
+                .iter()
+                .map(|approval| proto::Approval {
+                    signature: approval.as_deref().map(Into::into).into(),
+                    ..Default::default()
+                })
+                .collect(),
+            signature: MF::some((&x.signature).into()),
+            latest_protocol_version: x.latest_protocol_version,
+            chunk_endorsements: x
+                .chunk_endorsements
+                .as_ref()
+                .map(|signatures| proto::ChunkEndorsements {
+                    signatures: signatures.clone(),
+                    ..Default::default()
+                })
+                .into(),
+            ..Self::default()
+        }
+    }
+}
+
+impl From<&CongestionInfoView> for proto::CongestionInfo {
+    fn from(x: &CongestionInfoView) -> Self {
+        Self {
+            delayed_receipts_gas: balance(x.delayed_receipts_gas),
+            buffered_receipts_gas: balance(x.buffered_receipts_gas),
+            receipt_bytes: x.receipt_bytes,
+            allowed_shard: x.allowed_shard.into(),
+            ..Self::default()
+        }
+    }
+}
+
+impl From<&ChunkHeaderView> for proto::ChunkHeader {
+    fn from(x: &ChunkHeaderView) -> Self {
+        Self {
+            chunk_hash: hash(&x.chunk_hash),
+            prev_block_hash: hash(&x.prev_block_hash),
+            outcome_root: hash(&x.outcome_root),
+            prev_state_root: hash(&x.prev_state_root),
+            encoded_merkle_root: hash(&x.encoded_merkle_root),
+            encoded_length: x.encoded_length,
+            height_created: x.height_created,
+            height_included: x.height_included,
+            shard_id: x.shard_id.into(),
+            gas_used: x.gas_used,
+            gas_limit: x.gas_limit,
+            balance_burnt: balance(x.balance_burnt),
+            outgoing_receipts_root: hash(&x.outgoing_receipts_root),
+            tx_root: hash(&x.tx_root),
+            validator_proposals: x.validator_proposals.iter().map(Into::into).collect(),
+            congestion_info: x.congestion_info.as_ref().map(Into::into).into(),
+            bandwidth_requests: x
+                .bandwidth_requests
+                .as_ref()
+                .map(|requests| borsh_message!(BandwidthRequests, requests))
+                .into(),
+            signature: MF::some((&x.signature).into()),
+            ..Self::default()
+        }
+    }
+}
+
+impl From<&BlockView> for proto::Block {
+    fn from(x: &BlockView) -> Self {
+        Self {
+            author: x.author.to_string(),
+            header: MF::some((&x.header).into()),
+            chunks: x.chunks.iter().map(Into::into).collect(),
+            ..Self::default()
+        }
+    }
+}
+
+impl From<&ReceiptView> for proto::ReceiptView {
+    fn from(x: &ReceiptView) -> Self {
+        borsh_message!(ReceiptView, x)
+    }
+}
+
+impl From<&ChunkView> for proto::Chunk {
+    fn from(x: &ChunkView) -> Self {
+        Self {
+            author: x.author.to_string(),
+            header: MF::some((&x.header).into()),
+            transactions: x
+                .transactions
+                .iter()
+                .map(|transaction| borsh_message!(SignedTransactionView, transaction))
+                .collect(),
+            receipts: x.receipts.iter().map(Into::into).collect(),
+            ..Self::default()
+        }
+    }
+}
+
+impl From<&RpcTransactionResponse> for proto::GetTxStatusResponse {
+    fn from(x: &RpcTransactionResponse) -> Self {
+        use proto::get_tx_status_response::Final_execution_outcome;
+        let final_execution_outcome =
+            x.final_execution_outcome.as_ref().map(|outcome| match outcome {
+                FinalExecutionOutcomeViewEnum::FinalExecutionOutcome(outcome) => {
+                    Final_execution_outcome::Outcome(borsh_message!(
+                        FinalExecutionOutcomeView,
+                        outcome
+                    ))
+                }
+                FinalExecutionOutcomeViewEnum::FinalExecutionOutcomeWithReceipt(outcome) => {
+                    Final_execution_outcome::OutcomeWithReceipts(borsh_message!(
+                        FinalExecutionOutcomeWithReceiptView,
+                        outcome
+                    ))
+                }
+            });
+        Self {
+            final_execution_status: proto::TxExecutionStatus::from(&x.final_execution_status)
+                .into(),
+            final_execution_outcome,
+            ..Self::default()
+        }
+    }
+}
+
+impl From<&AccountView> for proto::Account {
+    fn from(x: &AccountView) -> Self {
+        Self {
+            amount: balance(x.amount),
+            locked: balance(x.locked),
+            code_hash: hash(&x.code_hash),
+            storage_usage: x.storage_usage,
+            storage_paid_at: x.storage_paid_at,
+            ..Self::default()
+        }
+    }
+}
+
+impl From<&AccessKeyView> for proto::AccessKeyView {
+    fn from(x: &AccessKeyView) -> Self {
+        borsh_message!(AccessKeyView, x)
+    }
+}
+
+impl From<&RpcQueryResponse> for proto::QueryResponse {
+    fn from(x: &RpcQueryResponse) -> Self {
+        use proto::query_response::Kind;
+        let kind = match &x.kind {
+            QueryResponseKind::ViewAccount(account) => Kind::ViewAccount(account.into()),
+            QueryResponseKind::ViewCode(code) => Kind::ViewCode(proto::ContractCode {
+                code: code.code.clone(),
+                hash: hash(&code.hash),
+                ..Default::default()
+            }),
+            QueryResponseKind::ViewState(state) => Kind::ViewState(proto::ViewStateResult {
+                values: state
+                    .values
+                    .iter()
+                    .map(|item| proto::StateItem {
+                        key: item.key.to_vec(),
+                        value: item.value.to_vec(),
+                        ..Default::default()
+                    })
+                    .collect(),
+                continuation_token: state.continuation_token.as_ref().map(|key| key.to_vec()),
+                ..Default::default()
+            }),
+            QueryResponseKind::CallResult(result) => Kind::CallResult(proto::CallResult {
+                result: result.result.clone(),
+                logs: result.logs.clone(),
+                ..Default::default()
+            }),
+            QueryResponseKind::AccessKey(access_key) => Kind::AccessKey(access_key.into()),
+            QueryResponseKind::AccessKeyList(list) => Kind::AccessKeyList(proto::AccessKeyList {
+                keys: list
+                    .keys
+                    .iter()
+                    .map(|key| proto::AccessKeyInfo {
+                        public_key: MF::some((&key.public_key).into()),
+                        access_key: MF::some((&key.access_key).into()),
+                        ..Default::default()
+                    })
+                    .collect(),
+                ..Default::default()
+            }),
+        };
+        Self {
+            block_height: x.block_height,
+            block_hash: hash(&x.block_hash),
+            kind: Some(kind),
+            proof: x.proof.iter().map(|node| node.to_vec()).collect(),
+            ..Self::default()
+        }
+    }
+}
@@ -0,0 +1,324 @@
+//! gRPC read API of the node, see `rpc.proto`.
+//!
+//! The requests are converted to the requests of the corresponding JSON RPC
+//! methods and handled by the same [`JsonRpcHandler`] methods, share their
+//! rate limits and fail with the same errors. Only the encoding differs:
+//! responses are built straight from the views, without going through JSON.
+//!
+//! The messages are generated by rust-protobuf like the ones of the network
+//! protocol, and the service by `tonic_build::manual` with [`ProtobufCodec`],
+//! see build.rs.
+
+use crate::rate_limits::RequestPermit;
+use crate::{metrics, JsonRpcHandler};
+use near_jsonrpc_primitives::errors::{RpcError, RpcErrorKind};
+use near_jsonrpc_primitives::types::blocks::RpcBlockRequest;
+use near_jsonrpc_primitives::types::chunks::RpcChunkRequest;
+use near_jsonrpc_primitives::types::query::RpcQueryRequest;
+use near_jsonrpc_primitives::types::receipts::RpcReceiptRequest;
+use near_jsonrpc_primitives::types::transactions::RpcTransactionStatusRequest;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Instant;
+use tonic::codec::{Codec, DecodeBuf, Decoder, EncodeBuf, Encoder};
+use tonic::{Code, Request, Response, Status};
+
+mod conv;
+
+mod _proto {
+    // TODO: protobuf codegen includes `#![allow(box_pointers)]` which Clippy
+    // doesn’t like.  Allow renamed_and_removed_lints to silence that warning.
+    // Remove this once protobuf codegen is updated.
+    #![allow(renamed_and_removed_lints)]
+    include!(concat!(env!("OUT_DIR"), "/proto/mod.rs"));
+}
+
+mod _service {
+    include!(concat!(env!("OUT_DIR"), "/near.rpc.Rpc.rs"));
+}
+
+/// Messages of the gRPC API.
+pub use _proto::rpc as proto;
+/// Client of the gRPC API.
+pub use _service::rpc_client::RpcClient;
+use _service::rpc_server::{Rpc, RpcServer};
+
+/// Codec of the messages generated by rust-protobuf, tonic only comes with
+/// one for prost.
+pub struct ProtobufCodec<T, U>(PhantomData<(T, U)>);
+
+impl<T, U> Default for ProtobufCodec<T, U> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: protobuf::Message, U: protobuf::Message> Codec for ProtobufCodec<T, U> {
+    type Encode = T;
+    type Decode = U;
+    type Encoder = ProtobufCodec<T, ()>;
+    type Decoder = ProtobufCodec<(), U>;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        ProtobufCodec::default()
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        ProtobufCodec::default()
+    }
+}
+
+impl<T: protobuf::Message> Encoder for ProtobufCodec<T, ()> {
+    type Item = T;
+    type Error = Status;
+
+    fn encode(&mut self, item: T, dst: &mut EncodeBuf<'_>) -> Result<(), Status> {
+        use bytes::BufMut as _;
+        item.write_to_writer(&mut dst.writer()).map_err(|err| Status::internal(err.to_string()))
+    }
+}
+
+impl<U: protobuf::Message> Decoder for ProtobufCodec<(), U> {
+    type Item = U;
+    type Error = Status;
+
+    fn decode(&mut self, src: &mut DecodeBuf<'_>) -> Result<Option<U>, Status> {
+        use bytes::Buf as _;
+        U::parse_from_reader(&mut src.reader())
+            .map(Some)
+            .map_err(|err| Status::invalid_argument(err.to_string()))
+    }
+}
+
+/// Converts an error of the JSON RPC to a gRPC status with the name of the
+/// error as the message and its cause as JSON in the details.
+fn rpc_error_to_status(err: RpcError) -> Status {
+    let error_struct = match err.error_struct {
+        Some(RpcErrorKind::RequestValidationError(kind)) => {
+            let value = serde_json::to_value(kind).unwrap_or_default();
+            return to_status(Code::InvalidArgument, &value, "info");
+        }
+        Some(RpcErrorKind::HandlerError(value)) => value,
+        Some(RpcErrorKind::InternalError(value)) => {
+            return to_status(Code::Internal, &value, "info");
+        }
+        None => return Status::unknown(err.message),
+    };
+    let code = match error_struct.get("name").and_then(|name| name.as_str()).unwrap_or_default() {
+        "TIMEOUT_ERROR" => Code::DeadlineExceeded,
+        "TOO_MANY_REQUESTS" | "TOO_MANY_CONCURRENT_REQUESTS" => Code::ResourceExhausted,
+        "INTERNAL_ERROR" => Code::Internal,
+        "NOT_SYNCED_YET" => Code::Unavailable,
+        "GARBAGE_COLLECTED_BLOCK" | "NO_CONTRACT_CODE" => Code::NotFound,
+        name if name.starts_with("UNKNOWN_") => Code::NotFound,
+        _ => Code::FailedPrecondition,
+    };
+    to_status(code, &error_struct, "info")
+}
+
+fn to_status(code: Code, error_struct: &serde_json::Value, cause_field: &str) -> Status {
+    let name = error_struct.get("name").and_then(|name| name.as_str()).unwrap_or_default();
+    let cause = error_struct.get(cause_field).cloned().unwrap_or_default();
+    Status::with_details(code, name, serde_json::to_vec(&cause).unwrap_or_default().into())
+}
+
+struct RpcService {
+    handler: Arc<JsonRpcHandler>,
+}
+
+impl RpcService {
+    /// Checks the rate limits of the JSON RPC method corresponding to the
+    /// request.
+    #[allow(clippy::result_large_err)]
+    fn acquire<T>(
+        &self,
+        request: &Request<T>,
+        method_name: &str,
+        request_type: Option<&str>,
+    ) -> Result<RequestPermit, Status> {
+        let rate_limiter = &self.handler.rate_limiter;
+        let client = rate_limiter.grpc_client_id(request);
+        rate_limiter
+            .acquire_for_method(method_name, request_type, &client)
+            .map_err(|err| rpc_error_to_status(err.into()))
+    }
+}
+
+/// Awaits the response of a handler and updates the metrics.
+async fn observe<T, E>(
+    metrics_name: &str,
+    response: impl Future<Output = Result<T, E>>,
+) -> Result<T, Status>
+where
+    RpcError: From<E>,
+{
+    let timer = Instant::now();
+    let response = response.await.map_err(|err| rpc_error_to_status(err.into()));
+    let code = match &response {
+        Ok(_) => Code::Ok,
+        Err(status) => status.code(),
+    };
+    metrics::GRPC_REQUEST_COUNT.with_label_values(&[metrics_name, &format!("{:?}", code)]).inc();
+    metrics::GRPC_PROCESSING_TIME
+        .with_label_values(&[metrics_name])
+        .observe(timer.elapsed().as_secs_f64());
+    response
+}
+
+#[tonic::async_trait]
+impl Rpc for RpcService {
+    async fn get_block(
+        &self,
+        request: Request<proto::GetBlockRequest>,
+    ) -> Result<Response<proto::GetBlockResponse>, Status> {
+        let _permit = self.acquire(&request, "block", None)?;
+        let request = RpcBlockRequest::try_from(request.get_ref())?;
+        let response = observe("get_block", self.handler.block(request)).await?;
+        Ok(Response::new(proto::GetBlockResponse {
+            block: Some((&response.block_view).into()).into(),
+            ..Default::default()
+        }))
+    }
+
+    async fn get_chunk(
+        &self,
+        request: Request<proto::GetChunkRequest>,
+    ) -> Result<Response<proto::GetChunkResponse>, Status> {
+        let _permit = self.acquire(&request, "chunk", None)?;
+        let request = RpcChunkRequest::try_from(request.get_ref())?;
+        let response = observe("get_chunk", self.handler.chunk(request)).await?;
+        Ok(Response::new(proto::GetChunkResponse {
+            chunk: Some((&response.chunk_view).into()).into(),
+            ..Default::default()
+        }))
+    }
+
+    async fn get_receipt(
+        &self,
+        request: Request<proto::GetReceiptRequest>,
+    ) -> Result<Response<proto::GetReceiptResponse>, Status> {
+        let _permit = self.acquire(&request, "EXPERIMENTAL_receipt", None)?;
+        let request = RpcReceiptRequest::try_from(request.get_ref())?;
+        let response = observe("get_receipt", self.handler.receipt(request)).await?;
+        Ok(Response::new(proto::GetReceiptResponse {
+            receipt: Some((&response.receipt_view).into()).into(),
+            ..Default::default()
+        }))
+    }
+
+    async fn get_tx_status(
+        &self,
+        request: Request<proto::GetTxStatusRequest>,
+    ) -> Result<Response<proto::GetTxStatusResponse>, Status> {
+        let include_receipts = request.get_ref().include_receipts;
+        let method_name = if include_receipts { "EXPERIMENTAL_tx_status" } else { "tx" };
+        let _permit = self.acquire(&request, method_name, None)?;
+        let request = RpcTransactionStatusRequest::try_from(request.get_ref())?;
+        let response =
+            observe("get_tx_status", self.handler.tx_status_common(request, include_receipts))
+                .await?;
+        Ok(Response::new((&response).into()))
+    }
+
+    async fn query(
+        &self,
+        request: Request<proto::QueryRequest>,
+    ) -> Result<Response<proto::QueryResponse>, Status> {
+        use proto::query_request::Request as R;
+        let request_type = match request.get_ref().request {
+            Some(R::ViewAccount(_)) => "view_account",
+            Some(R::ViewCode(_)) => "view_code",
+            Some(R::ViewState(_)) => "view_state",
+            Some(R::ViewAccessKey(_)) => "view_access_key",
+            Some(R::ViewAccessKeyList(_)) => "view_access_key_list",
+            Some(R::CallFunction(_)) => "call_function",
+            None => "unknown",
+        };
+        let _permit = self.acquire(&request, "query", Some(request_type))?;
+        let request = RpcQueryRequest::try_from(request.get_ref())?;
+        let metrics_name = format!("query_{request_type}");
+        let response = observe(&metrics_name, self.handler.query(request)).await?;
+        Ok(Response::new((&response).into()))
+    }
+}
+
+/// Starts the gRPC server on the given address, it runs until the runtime is
+/// shut down.
+pub(crate) fn start_grpc(addr: SocketAddr, handler: Arc<JsonRpcHandler>) {
+    tracing::info!(target: "network", "Starting gRPC server at {}", addr);
+    let server = tonic::transport::Server::builder()
+        .add_service(RpcServer::new(RpcService { handler }))
+        .serve(addr);
+    tokio::spawn(async move {
+        if let Err(err) = server.await {
+            tracing::error!(target: "network", "gRPC server at {} failed: {}", addr, err);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{proto, rpc_error_to_status};
+    use near_jsonrpc_primitives::errors::RpcError;
+    use near_jsonrpc_primitives::types::blocks::{RpcBlockError, RpcBlockRequest};
+    use near_jsonrpc_primitives::types::chunks::RpcChunkError;
+    use near_jsonrpc_primitives::types::query::RpcQueryRequest;
+    use near_primitives::types::{BlockId, BlockReference, Finality};
+    use near_primitives::views::QueryRequest;
+    use tonic::Code;
+
+    #[test]
+    fn test_parse_requests() {
+        let mut request = proto::GetBlockRequest::new();
+        assert_eq!(RpcBlockRequest::try_from(&request).unwrap_err().code(), Code::InvalidArgument);
+        request.block_reference.mut_or_insert_default().set_block_height(10);
+        assert_eq!(
+            RpcBlockRequest::try_from(&request).unwrap().block_reference,
+            BlockReference::BlockId(BlockId::Height(10))
+        );
+
+        let mut request = proto::QueryRequest::new();
+        request
+            .block_reference
+            .mut_or_insert_default()
+            .set_finality(proto::block_reference::Finality::OPTIMISTIC);
+        request.mut_view_account().account_id = "alice.near".to_string();
+        request.include_proof = true;
+        let request = RpcQueryRequest::try_from(&request).unwrap();
+        assert_eq!(request.block_reference, BlockReference::Finality(Finality::None));
+        assert_eq!(
+            request.request,
+            QueryRequest::ViewAccount {
+                account_id: "alice.near".parse().unwrap(),
+                include_proof: true
+            }
+        );
+
+        let mut request = proto::QueryRequest::new();
+        request.block_reference.mut_or_insert_default().set_block_height(10);
+        request.mut_view_code().account_id = "invalid account".to_string();
+        let status = RpcQueryRequest::try_from(&request).unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+        assert!(status.message().starts_with("account_id: "));
+    }
+
+    #[test]
+    fn test_error_to_status() {
+        let err = RpcBlockError::UnknownBlock { error_message: "missing".to_string() };
+        let status = rpc_error_to_status(RpcError::from(err));
+        assert_eq!(status.code(), Code::NotFound);
+        assert_eq!(status.message(), "UNKNOWN_BLOCK");
+
+        let err = RpcChunkError::InvalidShardId { shard_id: 7 };
+        let status = rpc_error_to_status(RpcError::from(err));
+        assert_eq!(status.code(), Code::FailedPrecondition);
+        assert_eq!(status.message(), "INVALID_SHARD_ID");
+        let cause: serde_json::Value = serde_json::from_slice(status.details()).unwrap();
+        assert_eq!(cause, serde_json::json!({"shard_id": 7}));
+
+        let err = RpcError::parse_error("unexpected field".to_string());
+        assert_eq!(rpc_error_to_status(err).code(), Code::InvalidArgument);
+    }
+}
@@ -11,16 +11,17 @@ use near_async::messaging::{
 };
 use near_chain_configs::GenesisConfig;
 use near_client::{
-    DebugStatus, GetBlock, GetBlockProof, GetChunk, GetClientConfig, GetExecutionOutcome,
-    GetGasPrice, GetMaintenanceWindows, GetNetworkInfo, GetNextLightClientBlock, GetProtocolConfig,
-    GetReceipt, GetStateChanges, GetStateChangesInBlock, GetValidatorInfo, GetValidatorOrdered,
-    ProcessTxRequest, ProcessTxResponse, Query, Status, TxStatus,
+    DebugStatus, GetAccountsByPublicKey, GetBlock, GetBlockProof, GetChunk, GetClientConfig,
+    GetExecutionOutcome, GetExecutionOutcomesForBlock, GetGasPrice, GetMaintenanceWindows,
+    GetNetworkInfo, GetNextLightClientBlock, GetProtocolConfig, GetReceipt, GetStateChanges,
+    GetStateChangesInBlock, GetValidatorInfo, GetValidatorOrdered, ProcessTxRequest,
+    ProcessTxResponse, Query, SimulateTransaction, Status, TxStatus,
 };
 use near_client_primitives::types::GetSplitStorageInfo;
 pub use near_jsonrpc_client as client;
 pub use near_jsonrpc_primitives as primitives;
 use near_jsonrpc_primitives::errors::{RpcError, RpcErrorKind};
-use near_jsonrpc_primitives::message::{Message, Request};
+use near_jsonrpc_primitives::message::{Message, Notification, Request};
 use near_jsonrpc_primitives::types::blocks::RpcBlockRequest;
 use near_jsonrpc_primitives::types::config::{RpcProtocolConfigError, RpcProtocolConfigResponse};
 use near_jsonrpc_primitives::types::entity_debug::{EntityDebugHandler, EntityQueryWithParams};
@@ -28,25 +29,37 @@ use near_jsonrpc_primitives::types::query::RpcQueryRequest;
 use near_jsonrpc_primitives::types::split_storage::{
     RpcSplitStorageInfoRequest, RpcSplitStorageInfoResponse,
 };
+use near_jsonrpc_primitives::types::subscriptions::RpcSubscriptionError;
 use near_jsonrpc_primitives::types::transactions::{
     RpcSendTransactionRequest, RpcTransactionResponse,
 };
 use near_network::debug::GetDebugStatus;
 use near_network::tcp::{self, ListenerAddr};
 use near_o11y::metrics::{prometheus, Encoder, TextEncoder};
+use near_primitives::block::Tip;
 use near_primitives::hash::CryptoHash;
 use near_primitives::transaction::SignedTransaction;
 use near_primitives::types::{AccountId, BlockHeight, BlockId, BlockReference};
 use near_primitives::views::{QueryRequest, TxExecutionStatus};
+pub use openrpc::openrpc_document;
+use rate_limits::{RateLimiter, RpcClientId};
 use serde_json::{json, Value};
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
+use subscriptions::{run_chain_events_feeder, SubscriptionHub};
 use tokio::time::{sleep, timeout};
 use tracing::{error, info};
 
+mod account_events;
 mod api;
+pub mod grpc;
 mod metrics;
+mod openrpc;
+mod rate_limits;
+mod subscriptions;
+mod websocket;
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug)]
 pub struct RpcPollingConfig {
@@ -64,14 +77,140 @@ impl Default for RpcPollingConfig {
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+#[serde(default)]
 pub struct RpcLimitsConfig {
-    /// Maximum byte size of the json payload.
+    /// Maximum byte size of the json payload. Applies to a batch as a whole.
     pub json_payload_max_size: usize,
+    /// Maximum number of requests in a batch.
+    pub batch_max_requests: usize,
+    /// Maximum total byte size of the responses to a batch. Responses which
+    /// don't fit are replaced with errors.
+    pub batch_response_max_size: usize,
 }
 
 impl Default for RpcLimitsConfig {
     fn default() -> Self {
-        Self { json_payload_max_size: 10 * 1024 * 1024 }
+        Self {
+            json_payload_max_size: 10 * 1024 * 1024,
+            batch_max_requests: 100,
+            batch_response_max_size: 50 * 1024 * 1024,
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct RpcWebSocketConfig {
+    /// If true, the JSON RPC is also served over WebSocket at `/ws` which
+    /// additionally supports subscriptions to chain events. Disabled by
+    /// default.
+    pub enable: bool,
+    /// Maximum number of simultaneously open WebSocket connections.
+    pub max_connections: usize,
+    /// Maximum number of active subscriptions per connection.
+    pub max_subscriptions_per_connection: usize,
+    /// Maximum number of accounts in the filter of an `account_events`
+    /// subscription.
+    pub max_accounts_per_subscription: usize,
+    /// Maximum number of messages queued for a client. Clients which don't
+    /// read their messages fast enough are disconnected.
+    pub max_pending_messages_per_connection: usize,
+}
+
+impl Default for RpcWebSocketConfig {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            max_connections: 1000,
+            max_subscriptions_per_connection: 100,
+            max_accounts_per_subscription: 100,
+            max_pending_messages_per_connection: 1024,
+        }
+    }
+}
+
+/// Token bucket limiting the rate of requests.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct RpcTokenBucketConfig {
+    /// Maximum number of requests accepted at once after a period of inactivity.
+    pub burst: u32,
+    /// Number of requests accepted per second in the long run.
+    pub requests_per_second: f32,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct RpcMethodLimitsConfig {
+    /// Limit of the requests of all clients together.
+    pub total: Option<RpcTokenBucketConfig>,
+    /// Limit of the requests of every client, identified by API key or IP.
+    pub per_client: Option<RpcTokenBucketConfig>,
+    /// Maximum number of requests processed at the same time. Requests over
+    /// the limit are rejected rather than queued.
+    pub max_concurrent_requests: Option<usize>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct RpcRateLimitsConfig {
+    /// Limits by method name. Queries are looked up by their request type
+    /// first, e.g. `query_call_function`, and then as `query`.
+    pub methods: HashMap<String, RpcMethodLimitsConfig>,
+    /// Limits shared by all the methods which are not listed in `methods`.
+    pub other_methods: RpcMethodLimitsConfig,
+    /// Header carrying the API key of a client.
+    pub api_key_header: String,
+    /// Quotas of the clients by API key, covering the requests of all methods.
+    /// Clients without a known API key are identified by their IP address.
+    pub api_key_quotas: HashMap<String, RpcTokenBucketConfig>,
+    /// If true, the IP address of a client is taken from the `Forwarded` or
+    /// `X-Forwarded-For` headers. Only enable it behind a trusted proxy.
+    pub use_forwarded_headers: bool,
+    /// Maximum number of clients whose rate limits are tracked. The least
+    /// recently seen clients are forgotten first.
+    pub max_tracked_clients: usize,
+}
+
+impl Default for RpcRateLimitsConfig {
+    fn default() -> Self {
+        Self {
+            methods: HashMap::new(),
+            other_methods: RpcMethodLimitsConfig::default(),
+            api_key_header: "x-api-key".to_string(),
+            api_key_quotas: HashMap::new(),
+            use_forwarded_headers: false,
+            max_tracked_clients: 100_000,
+        }
+    }
+}
+
+impl RpcRateLimitsConfig {
+    /// Checks that all the token buckets can be created.
+    pub fn validate(&self) -> Result<(), String> {
+        let method_limits = self
+            .methods
+            .iter()
+            .map(|(method_name, limits)| (format!("methods.{method_name}"), limits))
+            .chain(std::iter::once(("other_methods".to_string(), &self.other_methods)));
+        let mut buckets = vec![];
+        for (path, limits) in method_limits {
+            buckets.extend(limits.total.map(|bucket| (format!("{path}.total"), bucket)));
+            buckets.extend(limits.per_client.map(|bucket| (format!("{path}.per_client"), bucket)));
+        }
+        // API keys are secrets, so they are not mentioned in the errors.
+        buckets.extend(
+            self.api_key_quotas.values().map(|bucket| ("api_key_quotas".to_string(), *bucket)),
+        );
+        let now = near_async::time::Clock::real().now();
+        for (path, bucket) in buckets {
+            rate_limits::new_token_bucket(bucket, now)
+                .map_err(|err| format!("'rpc.rate_limits_config.{path}': {err}"))?;
+        }
+        if self.max_tracked_clients == 0 {
+            return Err("'rpc.rate_limits_config.max_tracked_clients' needs to be greater than 0"
+                .to_string());
+        }
+        Ok(())
     }
 }
 
@@ -84,10 +223,18 @@ pub struct RpcConfig {
     pub addr: tcp::ListenerAddr,
     // If provided, will start an http server exporting only Prometheus metrics on that address.
     pub prometheus_addr: Option<String>,
+    // If provided, will start a gRPC server on that address serving the read API of
+    // `grpc/rpc.proto`.
+    #[serde(default)]
+    pub grpc_addr: Option<std::net::SocketAddr>,
     pub cors_allowed_origins: Vec<String>,
     pub polling_config: RpcPollingConfig,
     #[serde(default)]
     pub limits_config: RpcLimitsConfig,
+    #[serde(default)]
+    pub websocket_config: RpcWebSocketConfig,
+    #[serde(default)]
+    pub rate_limits_config: RpcRateLimitsConfig,
     // If true, enable some debug RPC endpoints (like one to get the latest block).
     // We disable it by default, as some of those endpoints might be quite CPU heavy.
     #[serde(default = "default_enable_debug_rpc")]
@@ -103,9 +250,12 @@ impl Default for RpcConfig {
         RpcConfig {
             addr: tcp::ListenerAddr::new("0.0.0.0:3030".parse().unwrap()),
             prometheus_addr: None,
+            grpc_addr: None,
             cors_allowed_origins: vec!["*".to_owned()],
             polling_config: Default::default(),
             limits_config: Default::default(),
+            websocket_config: Default::default(),
+            rate_limits_config: Default::default(),
             enable_debug_rpc: false,
             experimental_debug_pages_src_path: None,
         }
@@ -154,6 +304,8 @@ impl near_jsonrpc_primitives::types::transactions::RpcTransactionError {
             ProcessTxResponse::DoesNotTrackShard | ProcessTxResponse::RequestRouted => {
                 Self::DoesNotTrackShard
             }
+            ProcessTxResponse::ReplacementUnderpriced => Self::ReplacementUnderpriced,
+            ProcessTxResponse::TooManyPendingTransactions => Self::TooManyPendingTransactions,
             internal_error => Self::InternalError { debug_info: format!("{:?}", internal_error) },
         }
     }
@@ -244,10 +396,12 @@ pub struct ClientSenderForRpc(
 
 #[derive(Clone, near_async::MultiSend, near_async::MultiSenderFrom)]
 pub struct ViewClientSenderForRpc(
+    AsyncSender<GetAccountsByPublicKey, ActixResult<GetAccountsByPublicKey>>,
     AsyncSender<GetBlock, ActixResult<GetBlock>>,
     AsyncSender<GetBlockProof, ActixResult<GetBlockProof>>,
     AsyncSender<GetChunk, ActixResult<GetChunk>>,
     AsyncSender<GetExecutionOutcome, ActixResult<GetExecutionOutcome>>,
+    AsyncSender<GetExecutionOutcomesForBlock, ActixResult<GetExecutionOutcomesForBlock>>,
     AsyncSender<GetGasPrice, ActixResult<GetGasPrice>>,
     AsyncSender<GetMaintenanceWindows, ActixResult<GetMaintenanceWindows>>,
     AsyncSender<GetNextLightClientBlock, ActixResult<GetNextLightClientBlock>>,
@@ -263,6 +417,13 @@ pub struct ViewClientSenderForRpc(
     #[cfg(feature = "test_features")] Sender<near_client::NetworkAdversarialMessage>,
 );
 
+/// Simulations are sent to their own pool of view client actors, so that they
+/// don't block the view client serving the other requests.
+#[derive(Clone, near_async::MultiSend, near_async::MultiSenderFrom)]
+pub struct SimulationSenderForRpc(
+    AsyncSender<SimulateTransaction, ActixResult<SimulateTransaction>>,
+);
+
 #[cfg(feature = "test_features")]
 #[derive(Clone, near_async::MultiSend, near_async::MultiSenderFrom)]
 pub struct GCSenderForRpc(
@@ -275,33 +436,138 @@ pub struct GCSenderForRpc(
 #[derive(Clone, near_async::MultiSend, near_async::MultiSenderFrom)]
 pub struct PeerManagerSenderForRpc(AsyncSender<GetDebugStatus, ActixResult<GetDebugStatus>>);
 
+/// Methods served over HTTP, i.e. all but the sandbox and adversarial ones,
+/// in the order of `JsonRpcHandler::process_basic_requests_internal` followed
+/// by `query`. The OpenRPC document describes exactly these methods, and the
+/// tests check that the handler dispatches all of them.
+pub const RPC_METHODS: &[&str] = &[
+    "block",
+    "broadcast_tx_async",
+    "broadcast_tx_commit",
+    "chunk",
+    "gas_price",
+    "health",
+    "light_client_proof",
+    "next_light_client_block",
+    "network_info",
+    "send_tx",
+    "status",
+    "tx",
+    "validators",
+    "client_config",
+    "EXPERIMENTAL_accounts_by_public_key",
+    "EXPERIMENTAL_changes",
+    "EXPERIMENTAL_changes_in_block",
+    "EXPERIMENTAL_congestion_level",
+    "EXPERIMENTAL_genesis_config",
+    "EXPERIMENTAL_light_client_proof",
+    "EXPERIMENTAL_light_client_block_proof",
+    "EXPERIMENTAL_protocol_config",
+    "EXPERIMENTAL_receipt",
+    "EXPERIMENTAL_tx_status",
+    "EXPERIMENTAL_validators_ordered",
+    "EXPERIMENTAL_maintenance_windows",
+    "EXPERIMENTAL_simulate_tx",
+    "EXPERIMENTAL_split_storage_info",
+    "rpc.discover",
+    "EXPERIMENTAL_subscribe",
+    "EXPERIMENTAL_unsubscribe",
+    "query",
+];
+
 struct JsonRpcHandler {
     client_sender: ClientSenderForRpc,
     view_client_sender: ViewClientSenderForRpc,
+    simulation_sender: SimulationSenderForRpc,
     peer_manager_sender: PeerManagerSenderForRpc,
     #[cfg(feature = "test_features")]
     gc_sender: GCSenderForRpc,
     polling_config: RpcPollingConfig,
+    limits_config: RpcLimitsConfig,
     genesis_config: GenesisConfig,
     enable_debug_rpc: bool,
     debug_pages_src_path: Option<PathBuf>,
     entity_debug_handler: Arc<dyn EntityDebugHandler>,
+    /// Set if the JSON RPC is also served over WebSocket.
+    subscription_hub: Option<Arc<SubscriptionHub>>,
+    rate_limiter: Arc<RateLimiter>,
+    /// Served by `rpc.discover`, see the `openrpc` module.
+    openrpc_document: Value,
 }
 
 impl JsonRpcHandler {
-    async fn process(&self, message: Message) -> Message {
+    async fn process(&self, message: Message, client: &RpcClientId) -> Message {
         let id = message.id();
         match message {
-            Message::Request(request) => Message::response(id, self.process_request(request).await),
+            Message::Request(request) => {
+                Message::response(id, self.process_request(request, client).await)
+            }
             _ => Message::error(RpcError::parse_error(
                 "JSON RPC Request format was expected".to_owned(),
             )),
         }
     }
 
-    // `process_request` increments affected metrics but the request processing is done by
-    // `process_request_internal`.
-    async fn process_request(&self, request: Request) -> Result<Value, RpcError> {
+    /// Processes the requests of a batch concurrently. Every request gets its
+    /// own response, so a failing request doesn't affect the rest of the batch.
+    /// Returns the serialized array of the responses, or `None` if the batch
+    /// consists of notifications only.
+    async fn process_batch(
+        &self,
+        messages: Vec<Message>,
+        client: &RpcClientId,
+    ) -> Result<Option<String>, RpcError> {
+        if messages.is_empty() {
+            return Err(RpcError::parse_error("Batch must not be empty".to_owned()));
+        }
+        let max_requests = self.limits_config.batch_max_requests;
+        if messages.len() > max_requests {
+            return Err(RpcError::parse_error(format!(
+                "Batch of {} requests exceeds the limit of {} requests",
+                messages.len(),
+                max_requests
+            )));
+        }
+        metrics::RPC_BATCH_SIZE.observe(messages.len() as f64);
+
+        let responses = futures::future::join_all(messages.into_iter().map(|message| async {
+            match message {
+                Message::Request(request) => {
+                    let id = request.id.clone();
+                    Some(Message::response(id, self.process_request(request, client).await))
+                }
+                Message::Notification(notification) => {
+                    self.process_notification(notification, client).await;
+                    None
+                }
+                _ => Some(Message::error(RpcError::parse_error(
+                    "JSON RPC Request format was expected".to_owned(),
+                ))),
+            }
+        }))
+        .await;
+        let responses: Vec<Message> = responses.into_iter().flatten().collect();
+        if responses.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(serialize_batch_responses(responses, self.limits_config.batch_response_max_size)))
+    }
+
+    /// Processes a request which doesn't expect a response.
+    async fn process_notification(&self, notification: Notification, client: &RpcClientId) {
+        if let Err(err) = self.process_request(notification.into(), client).await {
+            tracing::debug!(target: "jsonrpc", ?err, "Failed to process notification");
+        }
+    }
+
+    // `process_request` checks the rate limits and increments affected metrics but the request
+    // processing is done by `process_request_internal`.
+    async fn process_request(
+        &self,
+        request: Request,
+        client: &RpcClientId,
+    ) -> Result<Value, RpcError> {
+        let _permit = self.rate_limiter.acquire(&request, client)?;
         let timer = Instant::now();
         let (metrics_name, response) = self.process_request_internal(request).await;
 
@@ -346,18 +612,17 @@ impl JsonRpcHandler {
                 let metrics_name = match params.request {
                     QueryRequest::ViewAccount { .. } => "query_view_account",
                     QueryRequest::ViewCode { .. } => "query_view_code",
-                    QueryRequest::ViewState { include_proof, .. } => {
-                        if include_proof {
-                            "query_view_state_with_proof"
-                        } else {
-                            "query_view_state"
-                        }
-                    }
+                    QueryRequest::ViewState { .. } => "query_view_state",
                     QueryRequest::ViewAccessKey { .. } => "query_view_access_key",
                     QueryRequest::ViewAccessKeyList { .. } => "query_view_access_key_list",
                     QueryRequest::CallFunction { .. } => "query_call_function",
                 };
-                (metrics_name.to_string(), process_query_response(self.query(params).await))
+                let metrics_name = if params.request.include_proof() {
+                    format!("{}_with_proof", metrics_name)
+                } else {
+                    metrics_name.to_string()
+                };
+                (metrics_name, process_query_response(self.query(params).await))
             }
             _ => {
                 ("UNSUPPORTED_METHOD".to_string(), Err(RpcError::method_not_found(request.method)))
@@ -404,6 +669,9 @@ impl JsonRpcHandler {
             "client_config" => {
                 process_method_call(request, |_params: ()| self.client_config()).await
             }
+            "EXPERIMENTAL_accounts_by_public_key" => {
+                process_method_call(request, |params| self.accounts_by_public_key(params)).await
+            }
             "EXPERIMENTAL_changes" => {
                 process_method_call(request, |params| self.changes_in_block_by_type(params)).await
             }
@@ -443,9 +711,22 @@ impl JsonRpcHandler {
             "EXPERIMENTAL_maintenance_windows" => {
                 process_method_call(request, |params| self.maintenance_windows(params)).await
             }
+            "EXPERIMENTAL_simulate_tx" => {
+                process_method_call(request, |params| self.simulate_tx(params)).await
+            }
             "EXPERIMENTAL_split_storage_info" => {
                 process_method_call(request, |params| self.split_storage_info(params)).await
             }
+            "rpc.discover" => {
+                process_method_call(request, |_params: ()| async {
+                    Result::<_, std::convert::Infallible>::Ok(&self.openrpc_document)
+                })
+                .await
+            }
+            "EXPERIMENTAL_subscribe" | "EXPERIMENTAL_unsubscribe" => {
+                // Handled by the WebSocket connection, see `websocket` module.
+                Err(RpcSubscriptionError::WebSocketRequired.into())
+            }
             #[cfg(feature = "sandbox")]
             "sandbox_patch_state" => {
                 process_method_call(request, |params| self.sandbox_patch_state(params)).await
@@ -800,6 +1081,9 @@ impl JsonRpcHandler {
                     "/debug/api/requested_state_parts" => {
                         self.client_send(DebugStatus::RequestedStateParts).await?.rpc_into()
                     }
+                    "/debug/api/state_sync_progress" => {
+                        self.client_send(DebugStatus::StateSyncProgress).await?.rpc_into()
+                    }
                     "/debug/api/peer_store" => self
                         .peer_manager_send(near_network::debug::GetDebugStatus::PeerStore)
                         .await?
@@ -1184,6 +1468,65 @@ impl JsonRpcHandler {
         let split_storage = self.view_client_send(GetSplitStorageInfo {}).await?;
         Ok(RpcSplitStorageInfoResponse { result: split_storage })
     }
+
+    async fn accounts_by_public_key(
+        &self,
+        request_data: near_jsonrpc_primitives::types::accounts_by_public_key::RpcAccountsByPublicKeyRequest,
+    ) -> Result<
+        near_jsonrpc_primitives::types::accounts_by_public_key::RpcAccountsByPublicKeyResponse,
+        near_jsonrpc_primitives::types::accounts_by_public_key::RpcAccountsByPublicKeyError,
+    > {
+        let near_jsonrpc_primitives::types::accounts_by_public_key::RpcAccountsByPublicKeyRequest {
+            public_key,
+            block_reference,
+        } = request_data;
+        let response =
+            self.view_client_send(GetAccountsByPublicKey { public_key, block_reference }).await?;
+        Ok(near_jsonrpc_primitives::types::accounts_by_public_key::RpcAccountsByPublicKeyResponse {
+            keys: response
+                .keys
+                .into_iter()
+                .map(|(account_id, access_key)| {
+                    near_jsonrpc_primitives::types::accounts_by_public_key::RpcAccountAccessKey {
+                        account_id,
+                        access_key,
+                    }
+                })
+                .collect(),
+            block_hash: response.block_hash,
+            block_height: response.block_height,
+        })
+    }
+
+    async fn simulate_tx(
+        &self,
+        request_data: near_jsonrpc_primitives::types::simulation::RpcSimulateTransactionRequest,
+    ) -> Result<
+        near_jsonrpc_primitives::types::simulation::RpcSimulateTransactionResponse,
+        near_jsonrpc_primitives::types::simulation::RpcSimulateTransactionError,
+    > {
+        let near_jsonrpc_primitives::types::simulation::RpcSimulateTransactionRequest {
+            signed_transaction,
+            verify_signature,
+            block_reference,
+        } = request_data;
+        let response = self
+            .simulation_sender
+            .send_async(SimulateTransaction {
+                block_reference,
+                transaction: signed_transaction,
+                verify_signature,
+            })
+            .await
+            .map_err(RpcFrom::rpc_from)?
+            .map_err(RpcFrom::rpc_from)?;
+        Ok(near_jsonrpc_primitives::types::simulation::RpcSimulateTransactionResponse {
+            outcome: response.outcome,
+            state_changes: response.state_changes,
+            block_hash: response.block_hash,
+            block_height: response.block_height,
+        })
+    }
 }
 
 #[cfg(feature = "sandbox")]
@@ -1385,10 +1728,29 @@ async fn handle_unknown_block(
 }
 
 async fn rpc_handler(
+    http_request: HttpRequest,
     request: web::Json<Message>,
     handler: web::Data<JsonRpcHandler>,
 ) -> HttpResponse {
-    let message = handler.process(request.0.clone()).await;
+    let client = handler.rate_limiter.client_id(&http_request);
+    let message = match request.0.clone() {
+        Message::Batch(messages) => match handler.process_batch(messages, &client).await {
+            // Errors of the individual requests are reported in their responses.
+            Ok(Some(responses)) => {
+                return HttpResponse::Ok()
+                    .insert_header(header::ContentType::json())
+                    .body(responses)
+            }
+            Ok(None) => return HttpResponse::NoContent().finish(),
+            Err(err) => Message::error(err),
+        },
+        // Notifications are never answered.
+        Message::Notification(notification) => {
+            handler.process_notification(notification, &client).await;
+            return HttpResponse::NoContent().finish();
+        }
+        message => handler.process(message, &client).await,
+    };
 
     let mut response = if let Message::Response(response) = &message {
         match &response.result {
@@ -1399,6 +1761,9 @@ async fn rpc_handler(
                     match error_struct.get("name").and_then(|name| name.as_str()) {
                         Some("UNKNOWN_BLOCK") => handle_unknown_block(request.0, handler).await,
                         Some("TIMEOUT_ERROR") => HttpResponse::RequestTimeout(),
+                        Some("TOO_MANY_REQUESTS" | "TOO_MANY_CONCURRENT_REQUESTS") => {
+                            HttpResponse::TooManyRequests()
+                        }
                         _ => HttpResponse::Ok(),
                     }
                 }
@@ -1587,46 +1952,77 @@ async fn display_debug_html(
 /// as a tuple containing a name of the server (e.g. `"JSON RPC"`) which can be
 /// used in diagnostic messages and a [`actix_web::dev::Server`] object which
 /// can be used to control the server (most notably stop it).
+///
+/// `chain_head` wakes up the WebSocket subscriptions, if enabled, whenever
+/// the client changes the chain head.
 pub fn start_http(
     config: RpcConfig,
+    node_version: &str,
     genesis_config: GenesisConfig,
     client_sender: ClientSenderForRpc,
     view_client_sender: ViewClientSenderForRpc,
+    simulation_sender: SimulationSenderForRpc,
     peer_manager_sender: PeerManagerSenderForRpc,
+    chain_head: tokio::sync::watch::Receiver<Tip>,
     #[cfg(feature = "test_features")] gc_sender: GCSenderForRpc,
     entity_debug_handler: Arc<dyn EntityDebugHandler>,
 ) -> Vec<(&'static str, actix_web::dev::ServerHandle)> {
     let RpcConfig {
         addr,
         prometheus_addr,
+        grpc_addr,
         cors_allowed_origins,
         polling_config,
         limits_config,
+        websocket_config,
+        rate_limits_config,
         enable_debug_rpc,
         experimental_debug_pages_src_path: debug_pages_src_path,
     } = config;
+    let rate_limiter =
+        Arc::new(RateLimiter::new(near_async::time::Clock::real(), rate_limits_config));
+    let subscription_hub = websocket_config.enable.then(|| {
+        let hub = SubscriptionHub::new(websocket_config, limits_config.json_payload_max_size);
+        tokio::spawn(run_chain_events_feeder(
+            Arc::downgrade(&hub),
+            view_client_sender.clone(),
+            chain_head,
+        ));
+        hub
+    });
     let prometheus_addr = prometheus_addr.filter(|it| it != &addr.to_string());
     let cors_allowed_origins_clone = cors_allowed_origins.clone();
+    let json_payload_max_size = limits_config.json_payload_max_size;
+    let handler = Arc::new(JsonRpcHandler {
+        client_sender,
+        view_client_sender,
+        simulation_sender,
+        peer_manager_sender,
+        polling_config,
+        limits_config,
+        genesis_config,
+        enable_debug_rpc,
+        debug_pages_src_path: debug_pages_src_path.map(Into::into),
+        entity_debug_handler,
+        subscription_hub,
+        rate_limiter,
+        openrpc_document: openrpc_document(node_version),
+        #[cfg(feature = "test_features")]
+        gc_sender,
+    });
+    if let Some(grpc_addr) = grpc_addr {
+        grpc::start_grpc(grpc_addr, handler.clone());
+    }
     info!(target:"network", "Starting http server at {}", addr);
     let mut servers = Vec::new();
     let listener = HttpServer::new(move || {
         App::new()
             .wrap(get_cors(&cors_allowed_origins))
-            .app_data(web::Data::new(JsonRpcHandler {
-                client_sender: client_sender.clone(),
-                view_client_sender: view_client_sender.clone(),
-                peer_manager_sender: peer_manager_sender.clone(),
-                polling_config,
-                genesis_config: genesis_config.clone(),
-                enable_debug_rpc,
-                debug_pages_src_path: debug_pages_src_path.clone().map(Into::into),
-                entity_debug_handler: entity_debug_handler.clone(),
-                #[cfg(feature = "test_features")]
-                gc_sender: gc_sender.clone(),
-            }))
-            .app_data(web::JsonConfig::default().limit(limits_config.json_payload_max_size))
+            .app_data(web::Data::from(handler.clone()))
+            .app_data(web::JsonConfig::default().limit(json_payload_max_size))
             .wrap(middleware::Logger::default())
             .service(web::resource("/").route(web::post().to(rpc_handler)))
+            .service(web::resource("/ws").route(web::get().to(websocket::websocket_handler)))
             .service(
                 web::resource("/status")
                     .route(web::get().to(status_handler))
@@ -1718,6 +2114,44 @@ pub async fn start_http_for_readonly_debug_querying(
     Ok(())
 }
 
+/// Serializes the responses of a batch into a JSON array. Once the array
+/// would exceed `max_size` bytes, this and all the following responses are
+/// replaced with errors without being serialized, so that a batch can't make
+/// the node build an arbitrarily large response.
+fn serialize_batch_responses(responses: Vec<Message>, max_size: usize) -> String {
+    let mut body = String::from("[");
+    let mut size_exceeded = false;
+    for (index, response) in responses.into_iter().enumerate() {
+        if index > 0 {
+            body.push(',');
+        }
+        let id = match &response {
+            Message::Response(response) => response.id.clone(),
+            _ => Value::Null,
+        };
+        if !size_exceeded {
+            let response: String = response.into();
+            // One more byte is taken by the closing bracket.
+            if body.len() + response.len() < max_size {
+                body.push_str(&response);
+                continue;
+            }
+            size_exceeded = true;
+        }
+        let error: String = Message::response(
+            id,
+            Err(RpcError::new_internal_error(
+                None,
+                format!("Batch response exceeds the limit of {} bytes", max_size),
+            )),
+        )
+        .into();
+        body.push_str(&error);
+    }
+    body.push(']');
+    body
+}
+
 fn tx_execution_status_meets_expectations(
     expected: &TxExecutionStatus,
     actual: &TxExecutionStatus,
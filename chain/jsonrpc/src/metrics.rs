@@ -1,4 +1,6 @@
-use near_o11y::metrics::{exponential_buckets, HistogramVec, IntCounter, IntCounterVec};
+use near_o11y::metrics::{
+    exponential_buckets, Histogram, HistogramVec, IntCounter, IntCounterVec, IntGauge,
+};
 use std::sync::LazyLock;
 
 pub static RPC_PROCESSING_TIME: LazyLock<HistogramVec> = LazyLock::new(|| {
@@ -55,3 +57,57 @@ pub static RPC_UNREACHABLE_ERROR_COUNT: LazyLock<IntCounterVec> = LazyLock::new(
     )
     .unwrap()
 });
+pub static RPC_WEBSOCKET_CONNECTIONS: LazyLock<IntGauge> = LazyLock::new(|| {
+    near_o11y::metrics::try_create_int_gauge(
+        "near_rpc_websocket_connections",
+        "Number of currently open WebSocket connections",
+    )
+    .unwrap()
+});
+pub static RPC_WEBSOCKET_REJECTED_CONNECTIONS: LazyLock<IntCounter> = LazyLock::new(|| {
+    near_o11y::metrics::try_create_int_counter(
+        "near_rpc_websocket_rejected_connections_total",
+        "Total count of WebSocket connections rejected because of the connection limit",
+    )
+    .unwrap()
+});
+pub static RPC_WEBSOCKET_SLOW_CLIENT_DISCONNECTS: LazyLock<IntCounter> = LazyLock::new(|| {
+    near_o11y::metrics::try_create_int_counter(
+        "near_rpc_websocket_slow_client_disconnects_total",
+        "Total count of WebSocket connections closed because the client didn't keep up with the notifications",
+    )
+    .unwrap()
+});
+pub static RPC_BATCH_SIZE: LazyLock<Histogram> = LazyLock::new(|| {
+    near_o11y::metrics::try_create_histogram_with_buckets(
+        "near_rpc_batch_size",
+        "Number of requests in the processed JSON RPC batches",
+        exponential_buckets(1.0, 2.0, 10).unwrap(),
+    )
+    .unwrap()
+});
+pub static RPC_RATE_LIMITED_REQUESTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    near_o11y::metrics::try_create_int_counter_vec(
+        "near_rpc_rate_limited_requests_total",
+        "Total count of RPC requests rejected by the rate limits, by method limits and the limit exceeded",
+        &["method", "reason"],
+    )
+    .unwrap()
+});
+pub static GRPC_REQUEST_COUNT: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    near_o11y::metrics::try_create_int_counter_vec(
+        "near_rpc_grpc_requests_total",
+        "Total count of gRPC requests processed, by method and status code",
+        &["method", "code"],
+    )
+    .unwrap()
+});
+pub static GRPC_PROCESSING_TIME: LazyLock<HistogramVec> = LazyLock::new(|| {
+    near_o11y::metrics::try_create_histogram_vec(
+        "near_rpc_grpc_processing_time",
+        "Time taken to process gRPC requests",
+        &["method"],
+        Some(exponential_buckets(0.001, 2.0, 16).unwrap()),
+    )
+    .unwrap()
+});
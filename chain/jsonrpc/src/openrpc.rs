@@ -0,0 +1,519 @@
+//! [OpenRPC](https://spec.open-rpc.org/) document describing the JSON RPC
+//! methods, served by the `rpc.discover` method and printed by
+//! `neard rpc-schema`.
+//!
+//! The schemas are generated from the types of `near-jsonrpc-primitives`, so
+//! the document follows the API as it changes. A method added to
+//! [`JsonRpcHandler`](crate::JsonRpcHandler) has to be added to
+//! [`RPC_METHODS`](crate::RPC_METHODS) and [`methods`] as well, which is
+//! checked by the tests below and by `jsonrpc-tests`.
+//!
+//! The params of every method are described by name. Since most of the
+//! requests flatten enums, e.g. the block reference, the exact schema of the
+//! params is also given in the `x-params-schema` extension of the method.
+//! Handler and internal errors share the `-32000` code and are told apart by
+//! their `cause`, whose schema is given in the `x-error-cause` extension of
+//! the error.
+
+use crate::api::{AccountsByPublicKeyParams, SimulateTransactionParams};
+use near_jsonrpc_primitives::errors::{RpcError, RpcRateLimitError, RpcRequestValidationErrorKind};
+use near_jsonrpc_primitives::types::accounts_by_public_key::{
+    RpcAccountsByPublicKeyError, RpcAccountsByPublicKeyResponse,
+};
+use near_jsonrpc_primitives::types::blocks::{RpcBlockError, RpcBlockRequest, RpcBlockResponse};
+use near_jsonrpc_primitives::types::changes::{
+    RpcStateChangesError, RpcStateChangesInBlockByTypeRequest,
+    RpcStateChangesInBlockByTypeResponse, RpcStateChangesInBlockRequest,
+    RpcStateChangesInBlockResponse,
+};
+use near_jsonrpc_primitives::types::chunks::{RpcChunkError, RpcChunkRequest, RpcChunkResponse};
+use near_jsonrpc_primitives::types::client_config::{
+    RpcClientConfigError, RpcClientConfigResponse,
+};
+use near_jsonrpc_primitives::types::config::{
+    RpcProtocolConfigError, RpcProtocolConfigRequest, RpcProtocolConfigResponse,
+};
+use near_jsonrpc_primitives::types::congestion::{
+    RpcCongestionLevelError, RpcCongestionLevelRequest, RpcCongestionLevelResponse,
+};
+use near_jsonrpc_primitives::types::gas_price::{
+    RpcGasPriceError, RpcGasPriceRequest, RpcGasPriceResponse,
+};
+use near_jsonrpc_primitives::types::light_client::{
+    RpcLightClientBlockProofRequest, RpcLightClientBlockProofResponse,
+    RpcLightClientExecutionProofRequest, RpcLightClientExecutionProofResponse,
+    RpcLightClientNextBlockError, RpcLightClientNextBlockRequest, RpcLightClientNextBlockResponse,
+    RpcLightClientProofError,
+};
+use near_jsonrpc_primitives::types::maintenance::{
+    RpcMaintenanceWindowsError, RpcMaintenanceWindowsRequest, RpcMaintenanceWindowsResponse,
+};
+use near_jsonrpc_primitives::types::network_info::{RpcNetworkInfoError, RpcNetworkInfoResponse};
+use near_jsonrpc_primitives::types::query::{RpcQueryError, RpcQueryRequest, RpcQueryResponse};
+use near_jsonrpc_primitives::types::receipts::{
+    RpcReceiptError, RpcReceiptRequest, RpcReceiptResponse,
+};
+use near_jsonrpc_primitives::types::simulation::{
+    RpcSimulateTransactionError, RpcSimulateTransactionResponse,
+};
+use near_jsonrpc_primitives::types::split_storage::{
+    RpcSplitStorageInfoError, RpcSplitStorageInfoRequest, RpcSplitStorageInfoResponse,
+};
+use near_jsonrpc_primitives::types::status::{
+    RpcHealthResponse, RpcStatusError, RpcStatusResponse,
+};
+use near_jsonrpc_primitives::types::subscriptions::{
+    RpcSubscribeRequest, RpcSubscribeResponse, RpcSubscriptionError, RpcUnsubscribeRequest,
+    RpcUnsubscribeResponse,
+};
+use near_jsonrpc_primitives::types::transactions::{
+    RpcSendTransactionRequest, RpcTransactionError, RpcTransactionResponse,
+    RpcTransactionStatusRequest,
+};
+use near_jsonrpc_primitives::types::validator::{
+    RpcValidatorError, RpcValidatorRequest, RpcValidatorResponse, RpcValidatorsOrderedRequest,
+    RpcValidatorsOrderedResponse,
+};
+use near_primitives::hash::CryptoHash;
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::schema::{Schema, SchemaObject};
+use schemars::visit::{visit_schema_object, Visitor};
+use schemars::JsonSchema;
+use serde_json::{json, Value};
+
+/// Version of the OpenRPC specification the document conforms to.
+const OPENRPC_VERSION: &str = "1.2.6";
+
+/// Error code of the handler and internal errors.
+const SERVER_ERROR_CODE: i64 = -32_000;
+
+/// Error code of the requests whose params couldn't be parsed.
+const PARSE_ERROR_CODE: i64 = -32_700;
+
+/// Description of a JSON RPC method.
+struct RpcMethod {
+    name: &'static str,
+    summary: &'static str,
+    params: Schema,
+    result: Schema,
+    /// Schema of the `cause` of the handler errors, `None` if the method
+    /// can't fail.
+    error_cause: Option<Schema>,
+}
+
+impl RpcMethod {
+    fn new<P: JsonSchema, R: JsonSchema, E: JsonSchema>(
+        gen: &mut SchemaGenerator,
+        name: &'static str,
+        summary: &'static str,
+    ) -> Self {
+        Self {
+            name,
+            summary,
+            params: gen.subschema_for::<P>(),
+            result: gen.subschema_for::<R>(),
+            error_cause: Some(gen.subschema_for::<E>()),
+        }
+    }
+
+    fn infallible<P: JsonSchema, R: JsonSchema>(
+        gen: &mut SchemaGenerator,
+        name: &'static str,
+        summary: &'static str,
+    ) -> Self {
+        Self {
+            name,
+            summary,
+            params: gen.subschema_for::<P>(),
+            result: gen.subschema_for::<R>(),
+            error_cause: None,
+        }
+    }
+}
+
+/// Result of the methods returning arbitrary JSON objects, e.g. configs.
+type JsonObject = serde_json::Map<String, Value>;
+
+/// Methods served over HTTP, ordered as in [`RPC_METHODS`](crate::RPC_METHODS).
+/// The sandbox and adversarial methods are left out as they are only compiled
+/// into test builds.
+fn methods(gen: &mut SchemaGenerator) -> Vec<RpcMethod> {
+    vec![
+        RpcMethod::new::<RpcBlockRequest, RpcBlockResponse, RpcBlockError>(
+            gen,
+            "block",
+            "Returns the block by its height or hash, or the latest block of the given finality.",
+        ),
+        RpcMethod::infallible::<RpcSendTransactionRequest, CryptoHash>(
+            gen,
+            "broadcast_tx_async",
+            "Sends the transaction and returns its hash without waiting for the execution.",
+        ),
+        RpcMethod::new::<RpcSendTransactionRequest, RpcTransactionResponse, RpcTransactionError>(
+            gen,
+            "broadcast_tx_commit",
+            "Sends the transaction and waits until it's executed. Use `send_tx` instead.",
+        ),
+        RpcMethod::new::<RpcChunkRequest, RpcChunkResponse, RpcChunkError>(
+            gen,
+            "chunk",
+            "Returns the chunk by its hash, or by the block and the shard it's included in.",
+        ),
+        RpcMethod::new::<RpcGasPriceRequest, RpcGasPriceResponse, RpcGasPriceError>(
+            gen,
+            "gas_price",
+            "Returns the gas price of the given block, or of the latest block.",
+        ),
+        RpcMethod::new::<(), RpcHealthResponse, RpcStatusError>(
+            gen,
+            "health",
+            "Returns `null` if the node is healthy, and an error otherwise.",
+        ),
+        RpcMethod::new::<
+            RpcLightClientExecutionProofRequest,
+            RpcLightClientExecutionProofResponse,
+            RpcLightClientProofError,
+        >(
+            gen,
+            "light_client_proof",
+            "Returns the proof of the execution outcome of a transaction or a receipt.",
+        ),
+        RpcMethod::new::<
+            RpcLightClientNextBlockRequest,
+            RpcLightClientNextBlockResponse,
+            RpcLightClientNextBlockError,
+        >(
+            gen,
+            "next_light_client_block",
+            "Returns the next light client block after the given one.",
+        ),
+        RpcMethod::new::<(), RpcNetworkInfoResponse, RpcNetworkInfoError>(
+            gen,
+            "network_info",
+            "Returns the peers the node is connected to.",
+        ),
+        RpcMethod::new::<RpcSendTransactionRequest, RpcTransactionResponse, RpcTransactionError>(
+            gen,
+            "send_tx",
+            "Sends the transaction and waits until it reaches the requested execution status.",
+        ),
+        RpcMethod::new::<(), RpcStatusResponse, RpcStatusError>(
+            gen,
+            "status",
+            "Returns the status of the node: its version, validators and sync status.",
+        ),
+        RpcMethod::new::<RpcTransactionStatusRequest, RpcTransactionResponse, RpcTransactionError>(
+            gen,
+            "tx",
+            "Returns the status of the transaction by its hash and signer.",
+        ),
+        RpcMethod::new::<RpcValidatorRequest, RpcValidatorResponse, RpcValidatorError>(
+            gen,
+            "validators",
+            "Returns the validators of the epoch of the given block or epoch id.",
+        ),
+        RpcMethod::new::<(), RpcClientConfigResponse, RpcClientConfigError>(
+            gen,
+            "client_config",
+            "Returns the client config of the node.",
+        ),
+        RpcMethod::new::<
+            AccountsByPublicKeyParams,
+            RpcAccountsByPublicKeyResponse,
+            RpcAccountsByPublicKeyError,
+        >(
+            gen,
+            "EXPERIMENTAL_accounts_by_public_key",
+            "Returns the accounts with an access key of the given public key.",
+        ),
+        RpcMethod::new::<
+            RpcStateChangesInBlockByTypeRequest,
+            RpcStateChangesInBlockByTypeResponse,
+            RpcStateChangesError,
+        >(
+            gen,
+            "EXPERIMENTAL_changes",
+            "Returns the state changes of the given kind and accounts in the block.",
+        ),
+        RpcMethod::new::<
+            RpcStateChangesInBlockRequest,
+            RpcStateChangesInBlockResponse,
+            RpcStateChangesError,
+        >(
+            gen,
+            "EXPERIMENTAL_changes_in_block",
+            "Returns the kinds of the state changes of every account changed in the block.",
+        ),
+        RpcMethod::new::<
+            RpcCongestionLevelRequest,
+            RpcCongestionLevelResponse,
+            RpcCongestionLevelError,
+        >(
+            gen,
+            "EXPERIMENTAL_congestion_level",
+            "Returns the congestion level of the shard of the given chunk.",
+        ),
+        RpcMethod::infallible::<(), JsonObject>(
+            gen,
+            "EXPERIMENTAL_genesis_config",
+            "Returns the genesis config of the network.",
+        ),
+        RpcMethod::new::<
+            RpcLightClientExecutionProofRequest,
+            RpcLightClientExecutionProofResponse,
+            RpcLightClientProofError,
+        >(
+            gen,
+            "EXPERIMENTAL_light_client_proof",
+            "Returns the proof of the execution outcome of a transaction or a receipt.",
+        ),
+        RpcMethod::new::<
+            RpcLightClientBlockProofRequest,
+            RpcLightClientBlockProofResponse,
+            RpcLightClientProofError,
+        >(
+            gen,
+            "EXPERIMENTAL_light_client_block_proof",
+            "Returns the proof of the inclusion of the block in the given light client block.",
+        ),
+        RpcMethod::new::<RpcProtocolConfigRequest, RpcProtocolConfigResponse, RpcProtocolConfigError>(
+            gen,
+            "EXPERIMENTAL_protocol_config",
+            "Returns the protocol config at the given block.",
+        ),
+        RpcMethod::new::<RpcReceiptRequest, RpcReceiptResponse, RpcReceiptError>(
+            gen,
+            "EXPERIMENTAL_receipt",
+            "Returns the receipt by its id.",
+        ),
+        RpcMethod::new::<RpcTransactionStatusRequest, RpcTransactionResponse, RpcTransactionError>(
+            gen,
+            "EXPERIMENTAL_tx_status",
+            "Returns the status of the transaction together with its receipts.",
+        ),
+        RpcMethod::new::<
+            RpcValidatorsOrderedRequest,
+            RpcValidatorsOrderedResponse,
+            RpcValidatorError,
+        >(
+            gen,
+            "EXPERIMENTAL_validators_ordered",
+            "Returns the block producers of the epoch of the given block, in order.",
+        ),
+        RpcMethod::new::<
+            RpcMaintenanceWindowsRequest,
+            RpcMaintenanceWindowsResponse,
+            RpcMaintenanceWindowsError,
+        >(
+            gen,
+            "EXPERIMENTAL_maintenance_windows",
+            "Returns the block height ranges of the current epoch in which the validator \
+             doesn't produce blocks or chunks.",
+        ),
+        RpcMethod::new::<
+            SimulateTransactionParams,
+            RpcSimulateTransactionResponse,
+            RpcSimulateTransactionError,
+        >(
+            gen,
+            "EXPERIMENTAL_simulate_tx",
+            "Executes the transaction on top of the state of the given block without sending it.",
+        ),
+        RpcMethod::new::<
+            RpcSplitStorageInfoRequest,
+            RpcSplitStorageInfoResponse,
+            RpcSplitStorageInfoError,
+        >(
+            gen,
+            "EXPERIMENTAL_split_storage_info",
+            "Returns the heads of the hot and cold databases of the split storage.",
+        ),
+        RpcMethod::infallible::<(), JsonObject>(
+            gen,
+            "rpc.discover",
+            "Returns this OpenRPC document describing the JSON RPC API.",
+        ),
+        RpcMethod::new::<RpcSubscribeRequest, RpcSubscribeResponse, RpcSubscriptionError>(
+            gen,
+            "EXPERIMENTAL_subscribe",
+            "Subscribes to the chain events. Only available over WebSocket.",
+        ),
+        RpcMethod::new::<RpcUnsubscribeRequest, RpcUnsubscribeResponse, RpcSubscriptionError>(
+            gen,
+            "EXPERIMENTAL_unsubscribe",
+            "Cancels the subscription. Only available over WebSocket.",
+        ),
+        RpcMethod::new::<RpcQueryRequest, RpcQueryResponse, RpcQueryError>(
+            gen,
+            "query",
+            "Queries the state: accounts, access keys, contract code and state, and calls \
+             the view functions.",
+        ),
+    ]
+}
+
+/// Returns the OpenRPC document of the JSON RPC API of the node of the given
+/// version.
+pub fn openrpc_document(version: &str) -> Value {
+    let mut gen = SchemaSettings::draft07()
+        .with(|settings| settings.definitions_path = "#/components/schemas/".to_string())
+        .into_generator();
+    let parse_error = gen.subschema_for::<RpcRequestValidationErrorKind>();
+    let rate_limit_error = gen.subschema_for::<RpcRateLimitError>();
+    // Not referenced by the methods, but needed to parse the error responses.
+    gen.subschema_for::<RpcError>();
+    let methods: Vec<Value> = methods(&mut gen)
+        .into_iter()
+        .map(|method| describe_method(&gen, method, &parse_error, &rate_limit_error))
+        .collect();
+    let mut definitions = gen.take_definitions();
+    for schema in definitions.values_mut() {
+        AllowFlattenedEnums.visit_schema(schema);
+    }
+    json!({
+        "openrpc": OPENRPC_VERSION,
+        "info": {
+            "title": "NEAR Protocol JSON RPC",
+            "version": version,
+            "license": { "name": "MIT OR Apache-2.0" },
+        },
+        "methods": methods,
+        "components": {
+            "schemas": definitions,
+        },
+    })
+}
+
+/// Removes `"additionalProperties": false` from the variants of the enums.
+///
+/// Schemars forbids other properties in the variants of externally tagged
+/// enums, but most of the requests flatten such enums, e.g. the block
+/// reference, next to other fields.
+#[derive(Clone, Debug)]
+struct AllowFlattenedEnums;
+
+impl Visitor for AllowFlattenedEnums {
+    fn visit_schema_object(&mut self, schema: &mut SchemaObject) {
+        if let Some(subschemas) = &mut schema.subschemas {
+            let variants = subschemas.one_of.iter_mut().chain(subschemas.any_of.iter_mut());
+            for variant in variants.flatten() {
+                if let Schema::Object(SchemaObject { object: Some(object), .. }) = variant {
+                    if object.additional_properties.as_deref() == Some(&Schema::Bool(false)) {
+                        object.additional_properties = None;
+                    }
+                }
+            }
+        }
+        visit_schema_object(self, schema);
+    }
+}
+
+fn describe_method(
+    gen: &SchemaGenerator,
+    method: RpcMethod,
+    parse_error: &Schema,
+    rate_limit_error: &Schema,
+) -> Value {
+    let mut params = vec![];
+    collect_params(gen, &method.params, true, &mut params);
+    let mut error_causes = vec![rate_limit_error.clone()];
+    error_causes.extend(method.error_cause);
+    let mut description = json!({
+        "name": method.name,
+        "summary": method.summary,
+        "paramStructure": "by-name",
+        "params": params,
+        "result": { "name": "result", "schema": method.result },
+        "errors": [
+            {
+                "code": PARSE_ERROR_CODE,
+                "message": "Parse error",
+                "x-error-cause": parse_error,
+            },
+            {
+                "code": SERVER_ERROR_CODE,
+                "message": "Server error",
+                "x-error-cause": { "anyOf": error_causes },
+            },
+        ],
+    });
+    if !params.is_empty() {
+        description["x-params-schema"] = json!(method.params);
+    }
+    description
+}
+
+/// Collects the named params from the schema of the params of a method,
+/// following the references and the subschemas of the flattened fields.
+///
+/// A param is required only if it's required in all the alternatives, so the
+/// params of enum variants (e.g. `block_id` and `finality`) are optional.
+fn collect_params(gen: &SchemaGenerator, schema: &Schema, required: bool, params: &mut Vec<Value>) {
+    let Some(Schema::Object(schema)) = gen.dereference(schema).or(Some(schema)) else {
+        return;
+    };
+    if let Some(object) = &schema.object {
+        for (name, property) in &object.properties {
+            if params.iter().any(|param| param["name"] == name.as_str()) {
+                continue;
+            }
+            params.push(json!({
+                "name": name,
+                "required": required && object.required.contains(name),
+                "schema": property,
+            }));
+        }
+    }
+    if let Some(subschemas) = &schema.subschemas {
+        for subschema in subschemas.all_of.iter().flatten() {
+            collect_params(gen, subschema, required, params);
+        }
+        let alternatives = subschemas.any_of.iter().chain(subschemas.one_of.iter()).flatten();
+        for subschema in alternatives {
+            collect_params(gen, subschema, false, params);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::openrpc_document;
+    use crate::RPC_METHODS;
+
+    #[test]
+    fn test_all_methods_are_described() {
+        let document = openrpc_document("1.2.3");
+        let methods: Vec<&str> = document["methods"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|method| method["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(methods, RPC_METHODS);
+    }
+
+    #[test]
+    fn test_references_are_defined() {
+        let document = openrpc_document("1.2.3");
+        let schemas = document["components"]["schemas"].as_object().unwrap();
+        let serialized = serde_json::to_string(&document).unwrap();
+        for reference in serialized.split("\"$ref\":\"").skip(1) {
+            let reference = &reference[..reference.find('"').unwrap()];
+            let name = reference.strip_prefix("#/components/schemas/").unwrap();
+            assert!(schemas.contains_key(name), "{name} is not defined");
+        }
+    }
+
+    #[test]
+    fn test_block_params() {
+        let document = openrpc_document("1.2.3");
+        let block = &document["methods"][0];
+        assert_eq!(block["name"], "block");
+        let params = block["params"].as_array().unwrap();
+        let names: Vec<&str> = params.iter().map(|param| param["name"].as_str().unwrap()).collect();
+        assert!(names.contains(&"block_id"), "{names:?}");
+        assert!(names.contains(&"finality"), "{names:?}");
+        assert!(params.iter().all(|param| param["required"] == false));
+        assert_eq!(block["result"]["schema"]["$ref"], "#/components/schemas/RpcBlockResponse");
+    }
+}
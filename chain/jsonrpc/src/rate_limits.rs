@@ -0,0 +1,365 @@
+//! Rate limits of the JSON RPC requests, per method and per client.
+//!
+//! Every method has a set of limits, see [`RpcMethodLimitsConfig`]: a token
+//! bucket shared by all clients, a token bucket per client and a cap on the
+//! number of requests processed concurrently. On top of that, clients
+//! identified by an API key have a quota covering all methods. Requests of the
+//! gRPC API count against the limits of the corresponding JSON RPC methods.
+
+use crate::{RpcMethodLimitsConfig, RpcRateLimitsConfig, RpcTokenBucketConfig};
+use actix_web::HttpRequest;
+use lru::LruCache;
+use near_async::time::{Clock, Instant};
+use near_jsonrpc_primitives::errors::RpcRateLimitError;
+use near_jsonrpc_primitives::message::Request;
+use near_network::{TokenBucket, TokenBucketError};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Name of the limits applied to the methods without their own limits.
+const OTHER_METHODS: &str = "other_methods";
+
+pub(crate) fn new_token_bucket(
+    config: RpcTokenBucketConfig,
+    now: Instant,
+) -> Result<TokenBucket, TokenBucketError> {
+    TokenBucket::new(config.burst, config.burst, config.requests_per_second, now)
+}
+
+/// Identity of a client for the purpose of rate limiting.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum RpcClientId {
+    /// A client with one of the API keys of `api_key_quotas`.
+    ApiKey(String),
+    Ip(IpAddr),
+    /// The address of the client couldn't be determined. All such clients
+    /// share their limits.
+    Unknown,
+}
+
+struct MethodLimiter {
+    /// Name of the limits, used in the errors and metrics.
+    name: String,
+    config: RpcMethodLimitsConfig,
+    total: Option<Mutex<TokenBucket>>,
+    concurrent_requests: Arc<AtomicUsize>,
+}
+
+pub(crate) struct RateLimiter {
+    clock: Clock,
+    api_key_header: String,
+    use_forwarded_headers: bool,
+    methods: HashMap<String, MethodLimiter>,
+    other_methods: MethodLimiter,
+    api_key_quotas: HashMap<String, Mutex<TokenBucket>>,
+    /// Token buckets of the `per_client` limits by method limits name and
+    /// client.
+    per_client: Mutex<LruCache<(String, RpcClientId), TokenBucket>>,
+}
+
+/// Holds a slot of the concurrent requests of a method until dropped.
+pub(crate) struct RequestPermit {
+    concurrent_requests: Option<Arc<AtomicUsize>>,
+}
+
+impl Drop for RequestPermit {
+    fn drop(&mut self) {
+        if let Some(concurrent_requests) = &self.concurrent_requests {
+            concurrent_requests.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+}
+
+impl MethodLimiter {
+    fn new(name: String, config: RpcMethodLimitsConfig, now: Instant) -> Self {
+        let total = config.total.map(|bucket| {
+            Mutex::new(new_token_bucket(bucket, now).expect("rate limits config is validated"))
+        });
+        Self { name, config, total, concurrent_requests: Arc::new(AtomicUsize::new(0)) }
+    }
+}
+
+impl RateLimiter {
+    pub(crate) fn new(clock: Clock, config: RpcRateLimitsConfig) -> Self {
+        let now = clock.now();
+        let methods = config
+            .methods
+            .into_iter()
+            .map(|(name, limits)| (name.clone(), MethodLimiter::new(name, limits, now)))
+            .collect();
+        let other_methods =
+            MethodLimiter::new(OTHER_METHODS.to_string(), config.other_methods, now);
+        let api_key_quotas = config
+            .api_key_quotas
+            .into_iter()
+            .map(|(api_key, bucket)| {
+                let bucket =
+                    new_token_bucket(bucket, now).expect("rate limits config is validated");
+                (api_key, Mutex::new(bucket))
+            })
+            .collect();
+        let per_client = LruCache::new(NonZeroUsize::new(config.max_tracked_clients).unwrap());
+        Self {
+            clock,
+            api_key_header: config.api_key_header,
+            use_forwarded_headers: config.use_forwarded_headers,
+            methods,
+            other_methods,
+            api_key_quotas,
+            per_client: Mutex::new(per_client),
+        }
+    }
+
+    /// Identifies the client which sent the HTTP request.
+    pub(crate) fn client_id(&self, request: &HttpRequest) -> RpcClientId {
+        let api_key =
+            request.headers().get(self.api_key_header.as_str()).and_then(|v| v.to_str().ok());
+        let ip = if self.use_forwarded_headers {
+            request.connection_info().realip_remote_addr().and_then(parse_ip)
+        } else {
+            request.peer_addr().map(|addr| addr.ip())
+        };
+        self.identify(api_key, ip)
+    }
+
+    /// Identifies the client which sent the gRPC request.
+    pub(crate) fn grpc_client_id<T>(&self, request: &tonic::Request<T>) -> RpcClientId {
+        let metadata = request.metadata();
+        let api_key = metadata.get(self.api_key_header.as_str()).and_then(|v| v.to_str().ok());
+        let ip = if self.use_forwarded_headers {
+            metadata
+                .get("x-forwarded-for")
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.split(',').next())
+                .and_then(|addr| parse_ip(addr.trim()))
+        } else {
+            request.remote_addr().map(|addr| addr.ip())
+        };
+        self.identify(api_key, ip)
+    }
+
+    fn identify(&self, api_key: Option<&str>, ip: Option<IpAddr>) -> RpcClientId {
+        if let Some(api_key) = api_key {
+            if self.api_key_quotas.contains_key(api_key) {
+                return RpcClientId::ApiKey(api_key.to_string());
+            }
+        }
+        ip.map_or(RpcClientId::Unknown, RpcClientId::Ip)
+    }
+
+    /// Checks the limits of the request. The returned permit has to be held
+    /// while the request is processed.
+    pub(crate) fn acquire(
+        &self,
+        request: &Request,
+        client: &RpcClientId,
+    ) -> Result<RequestPermit, RpcRateLimitError> {
+        let request_type = match request.method.as_str() {
+            "query" => request.params.get("request_type").and_then(|v| v.as_str()),
+            _ => None,
+        };
+        self.acquire_for_method(&request.method, request_type, client)
+    }
+
+    /// Same as [`Self::acquire`], for the requests which aren't JSON RPC
+    /// requests but are limited as if they were requests of the given method.
+    /// `request_type` is the request type of the queries.
+    pub(crate) fn acquire_for_method(
+        &self,
+        method_name: &str,
+        request_type: Option<&str>,
+        client: &RpcClientId,
+    ) -> Result<RequestPermit, RpcRateLimitError> {
+        let method = self.method_limiter(method_name, request_type);
+        let now = self.clock.now();
+
+        let mut permit = RequestPermit { concurrent_requests: None };
+        if let Some(limit) = method.config.max_concurrent_requests {
+            method
+                .concurrent_requests
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |count| {
+                    (count < limit).then_some(count + 1)
+                })
+                .map_err(|_| {
+                    reject(&method.name, "concurrency");
+                    RpcRateLimitError::TooManyConcurrentRequests {
+                        method_name: method.name.clone(),
+                        limit,
+                    }
+                })?;
+            permit.concurrent_requests = Some(method.concurrent_requests.clone());
+        }
+
+        // All the buckets are locked and checked before any of them is charged
+        // so that a request rejected by one limit doesn't use up the others.
+        let mut api_key_quota = match client {
+            RpcClientId::ApiKey(api_key) => Some(self.api_key_quotas[api_key].lock().unwrap()),
+            _ => None,
+        };
+        let mut per_client_buckets =
+            method.config.per_client.map(|config| (config, self.per_client.lock().unwrap()));
+        let per_client = per_client_buckets.as_mut().map(|(config, buckets)| {
+            buckets.get_or_insert_mut((method.name.clone(), client.clone()), || {
+                new_token_bucket(*config, now).expect("rate limits config is validated")
+            })
+        });
+        let mut total = method.total.as_ref().map(|total| total.lock().unwrap());
+        let mut buckets = [
+            ("api_key_quota", api_key_quota.as_deref_mut()),
+            ("per_client", per_client),
+            ("total", total.as_deref_mut()),
+        ];
+        for (reason, bucket) in &mut buckets {
+            if bucket.as_mut().is_some_and(|bucket| !bucket.can_acquire(1, now)) {
+                reject(&method.name, reason);
+                return Err(RpcRateLimitError::TooManyRequests {
+                    method_name: method.name.clone(),
+                });
+            }
+        }
+        for bucket in buckets.into_iter().filter_map(|(_, bucket)| bucket) {
+            // Can't fail, the bucket has just been checked at the same time.
+            bucket.acquire(1, now);
+        }
+        Ok(permit)
+    }
+
+    fn method_limiter(&self, method_name: &str, request_type: Option<&str>) -> &MethodLimiter {
+        if let Some(request_type) = request_type {
+            if let Some(limiter) = self.methods.get(&format!("{method_name}_{request_type}")) {
+                return limiter;
+            }
+        }
+        self.methods.get(method_name).unwrap_or(&self.other_methods)
+    }
+}
+
+fn reject(method_name: &str, reason: &str) {
+    crate::metrics::RPC_RATE_LIMITED_REQUESTS.with_label_values(&[method_name, reason]).inc();
+}
+
+/// Parses an address of the `Forwarded` or `X-Forwarded-For` headers, which
+/// may come with a port.
+fn parse_ip(addr: &str) -> Option<IpAddr> {
+    addr.parse().ok().or_else(|| addr.parse::<std::net::SocketAddr>().ok().map(|addr| addr.ip()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RateLimiter, RpcClientId};
+    use crate::{RpcMethodLimitsConfig, RpcRateLimitsConfig, RpcTokenBucketConfig};
+    use near_async::time::{Duration, FakeClock, Utc};
+    use near_jsonrpc_primitives::errors::RpcRateLimitError;
+    use near_jsonrpc_primitives::message::{Message, Request};
+    use serde_json::json;
+
+    fn request(method: &str, params: serde_json::Value) -> Request {
+        match Message::request(method.to_string(), params) {
+            Message::Request(request) => request,
+            _ => unreachable!(),
+        }
+    }
+
+    fn bucket(burst: u32, requests_per_second: f32) -> Option<RpcTokenBucketConfig> {
+        Some(RpcTokenBucketConfig { burst, requests_per_second })
+    }
+
+    #[test]
+    fn test_per_client_limits() {
+        let clock = FakeClock::new(Utc::UNIX_EPOCH);
+        let mut config = RpcRateLimitsConfig::default();
+        config.methods.insert(
+            "query_call_function".to_string(),
+            RpcMethodLimitsConfig { per_client: bucket(2, 1.0), ..Default::default() },
+        );
+        let limiter = RateLimiter::new(clock.clock(), config);
+        let call_function = request("query", json!({"request_type": "call_function"}));
+        let view_account = request("query", json!({"request_type": "view_account"}));
+        let alice = RpcClientId::Ip("10.0.0.1".parse().unwrap());
+        let bob = RpcClientId::Ip("10.0.0.2".parse().unwrap());
+
+        assert!(limiter.acquire(&call_function, &alice).is_ok());
+        assert!(limiter.acquire(&call_function, &alice).is_ok());
+        assert_eq!(
+            limiter.acquire(&call_function, &alice).err(),
+            Some(RpcRateLimitError::TooManyRequests {
+                method_name: "query_call_function".to_string()
+            })
+        );
+        // Other clients and methods are not affected.
+        assert!(limiter.acquire(&call_function, &bob).is_ok());
+        assert!(limiter.acquire(&view_account, &alice).is_ok());
+
+        clock.advance(Duration::seconds(1));
+        assert!(limiter.acquire(&call_function, &alice).is_ok());
+        assert!(limiter.acquire(&call_function, &alice).is_err());
+    }
+
+    #[test]
+    fn test_total_limits_and_api_key_quotas() {
+        let clock = FakeClock::new(Utc::UNIX_EPOCH);
+        let mut config = RpcRateLimitsConfig::default();
+        config.other_methods.total = bucket(3, 0.0);
+        config
+            .api_key_quotas
+            .insert("key".to_string(), RpcTokenBucketConfig { burst: 1, requests_per_second: 0.0 });
+        let limiter = RateLimiter::new(clock.clock(), config);
+        let block = request("block", json!({"finality": "final"}));
+        let client = RpcClientId::ApiKey("key".to_string());
+
+        assert!(limiter.acquire(&block, &client).is_ok());
+        // The quota of the API key is used up.
+        assert!(limiter.acquire(&block, &client).is_err());
+        assert!(limiter.acquire(&block, &RpcClientId::Unknown).is_ok());
+        // The second request of the API key client didn't consume a token.
+        assert!(limiter.acquire(&request("status", json!([])), &RpcClientId::Unknown).is_ok());
+        assert!(limiter.acquire(&block, &RpcClientId::Unknown).is_err());
+    }
+
+    #[test]
+    fn test_rejected_requests_dont_consume_tokens() {
+        let clock = FakeClock::new(Utc::UNIX_EPOCH);
+        let mut config = RpcRateLimitsConfig::default();
+        config.other_methods.total = bucket(1, 0.0);
+        config.methods.insert("status".to_string(), RpcMethodLimitsConfig::default());
+        config
+            .api_key_quotas
+            .insert("key".to_string(), RpcTokenBucketConfig { burst: 1, requests_per_second: 0.0 });
+        let limiter = RateLimiter::new(clock.clock(), config);
+        let block = request("block", json!({"finality": "final"}));
+        let client = RpcClientId::ApiKey("key".to_string());
+
+        assert!(limiter.acquire(&block, &RpcClientId::Unknown).is_ok());
+        // The total limit rejects the request, the quota of the API key is
+        // left intact.
+        assert!(limiter.acquire(&block, &client).is_err());
+        assert!(limiter.acquire_for_method("status", None, &client).is_ok());
+        assert!(limiter.acquire_for_method("status", None, &client).is_err());
+    }
+
+    #[test]
+    fn test_max_concurrent_requests() {
+        let clock = FakeClock::new(Utc::UNIX_EPOCH);
+        let mut config = RpcRateLimitsConfig::default();
+        config.methods.insert(
+            "send_tx".to_string(),
+            RpcMethodLimitsConfig { max_concurrent_requests: Some(1), ..Default::default() },
+        );
+        let limiter = RateLimiter::new(clock.clock(), config);
+        let send_tx = request("send_tx", json!({}));
+
+        let permit = limiter.acquire(&send_tx, &RpcClientId::Unknown).unwrap();
+        assert_eq!(
+            limiter.acquire(&send_tx, &RpcClientId::Unknown).err(),
+            Some(RpcRateLimitError::TooManyConcurrentRequests {
+                method_name: "send_tx".to_string(),
+                limit: 1
+            })
+        );
+        drop(permit);
+        assert!(limiter.acquire(&send_tx, &RpcClientId::Unknown).is_ok());
+    }
+}
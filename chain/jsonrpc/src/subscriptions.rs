@@ -0,0 +1,258 @@
+//! Chain events shared by all WebSocket subscribers.
+//!
+//! A single feeder task per RPC server is woken up by the client whenever the
+//! processed blocks change the chain head. It fetches the new optimistic and
+//! final blocks, together with the ids of the transactions and receipts
+//! executed in them, and broadcasts them to the connections which have active
+//! subscriptions. This way the cost of following the chain doesn't grow with
+//! the number of subscribers, and none of them has to poll the node on its
+//! own.
+
+use crate::{RpcWebSocketConfig, ViewClientSenderForRpc};
+use near_async::messaging::SendAsync;
+use near_client::{GetBlock, GetExecutionOutcomesForBlock};
+use near_primitives::block::Tip;
+use near_primitives::hash::CryptoHash;
+use near_primitives::types::{BlockHeight, BlockId, BlockReference, Finality};
+use near_primitives::views::BlockView;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Weak};
+use tokio::sync::{broadcast, watch};
+
+/// Capacity of the broadcast channel of chain events. Connections which fall
+/// behind by more than that many events get notified that they lagged.
+const CHAIN_EVENTS_CAPACITY: usize = 256;
+
+/// Maximum number of blocks the feeder fetches to fill a gap between two
+/// observed heads. Gaps longer than that are reported as lagged events.
+const MAX_BACKFILL_BLOCKS: usize = 32;
+
+#[derive(Clone, Debug)]
+pub(crate) enum ChainEvent {
+    /// A block which became the head of the chain.
+    NewHead {
+        block: Arc<BlockView>,
+        /// Ids of the transactions and receipts executed in the block, if
+        /// they could be fetched.
+        executed_ids: Option<Arc<HashSet<CryptoHash>>>,
+    },
+    /// A block which became final.
+    FinalBlock(Arc<BlockView>),
+    /// The feeder could not deliver the given number of blocks.
+    Skipped(u64),
+}
+
+pub(crate) struct SubscriptionHub {
+    config: RpcWebSocketConfig,
+    /// Maximum byte size of a single message received from a client.
+    max_message_size: usize,
+    events: broadcast::Sender<ChainEvent>,
+    open_connections: AtomicUsize,
+}
+
+impl SubscriptionHub {
+    pub fn new(config: RpcWebSocketConfig, max_message_size: usize) -> Arc<Self> {
+        let (events, _) = broadcast::channel(CHAIN_EVENTS_CAPACITY);
+        Arc::new(Self { config, max_message_size, events, open_connections: AtomicUsize::new(0) })
+    }
+
+    pub fn config(&self) -> &RpcWebSocketConfig {
+        &self.config
+    }
+
+    pub fn max_message_size(&self) -> usize {
+        self.max_message_size
+    }
+
+    pub fn subscribe_chain_events(&self) -> broadcast::Receiver<ChainEvent> {
+        self.events.subscribe()
+    }
+
+    /// Registers a new WebSocket connection, unless the limit of open
+    /// connections has been reached. The connection is unregistered when the
+    /// returned guard is dropped.
+    pub fn try_open_connection(self: &Arc<Self>) -> Option<ConnectionGuard> {
+        let max_connections = self.config.max_connections;
+        self.open_connections
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |open| {
+                (open < max_connections).then_some(open + 1)
+            })
+            .ok()?;
+        crate::metrics::RPC_WEBSOCKET_CONNECTIONS.inc();
+        Some(ConnectionGuard { hub: self.clone() })
+    }
+
+    fn publish(&self, event: ChainEvent) {
+        // Sending only fails when there are no subscribers which is fine.
+        let _ = self.events.send(event);
+    }
+
+    fn has_subscribers(&self) -> bool {
+        self.events.receiver_count() > 0
+    }
+}
+
+pub(crate) struct ConnectionGuard {
+    hub: Arc<SubscriptionHub>,
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        self.hub.open_connections.fetch_sub(1, Ordering::SeqCst);
+        crate::metrics::RPC_WEBSOCKET_CONNECTIONS.dec();
+    }
+}
+
+/// Last block reported for a given finality.
+struct ReportedTip {
+    height: BlockHeight,
+    hash: CryptoHash,
+}
+
+/// Publishes the blocks which became the chain head or final to the hub each
+/// time `chain_head` changes. Runs until the client or the RPC server stops;
+/// does nothing while there are no subscribers.
+pub(crate) async fn run_chain_events_feeder(
+    hub: Weak<SubscriptionHub>,
+    view_client_sender: ViewClientSenderForRpc,
+    mut chain_head: watch::Receiver<Tip>,
+) {
+    let mut head: Option<ReportedTip> = None;
+    let mut final_head: Option<ReportedTip> = None;
+    loop {
+        if chain_head.changed().await.is_err() {
+            return;
+        }
+        let Some(hub) = hub.upgrade() else {
+            return;
+        };
+        if !hub.has_subscribers() {
+            // Start from scratch once someone subscribes again, there is no
+            // point in back-filling blocks nobody was waiting for.
+            head = None;
+            final_head = None;
+            continue;
+        }
+        let head_hash = chain_head.borrow_and_update().last_block_hash;
+        let head_reference = BlockReference::BlockId(BlockId::Hash(head_hash));
+        if let Some(update) = fetch_new_blocks(&view_client_sender, head_reference, &head).await {
+            update.publish_skipped(&hub);
+            for block in update.blocks {
+                let executed_ids = fetch_executed_ids(&view_client_sender, block.header.hash).await;
+                head = Some(ReportedTip { height: block.header.height, hash: block.header.hash });
+                hub.publish(ChainEvent::NewHead { block, executed_ids });
+            }
+        }
+        let final_reference = BlockReference::Finality(Finality::Final);
+        if let Some(update) =
+            fetch_new_blocks(&view_client_sender, final_reference, &final_head).await
+        {
+            update.publish_skipped(&hub);
+            for block in update.blocks {
+                final_head =
+                    Some(ReportedTip { height: block.header.height, hash: block.header.hash });
+                hub.publish(ChainEvent::FinalBlock(block));
+            }
+        }
+    }
+}
+
+/// New blocks observed by the feeder for one of the finalities.
+struct TipUpdate {
+    /// Number of blocks which weren't fetched because the gap between the
+    /// previous and the new tip was too long.
+    skipped: u64,
+    /// Blocks ordered by height, the last one is the new tip.
+    blocks: Vec<Arc<BlockView>>,
+}
+
+impl TipUpdate {
+    fn publish_skipped(&self, hub: &SubscriptionHub) {
+        if self.skipped > 0 {
+            hub.publish(ChainEvent::Skipped(self.skipped));
+        }
+    }
+}
+
+/// Returns blocks up to the one referenced by `tip_reference` which haven't
+/// been reported yet. Returns `None` if the tip hasn't changed or couldn't be
+/// fetched.
+async fn fetch_new_blocks(
+    view_client_sender: &ViewClientSenderForRpc,
+    tip_reference: BlockReference,
+    last_tip: &Option<ReportedTip>,
+) -> Option<TipUpdate> {
+    let tip = fetch_block(view_client_sender, tip_reference).await?;
+    let Some(last_tip) = last_tip else {
+        return Some(TipUpdate { skipped: 0, blocks: vec![Arc::new(tip)] });
+    };
+    if tip.header.hash == last_tip.hash {
+        return None;
+    }
+
+    // Walk back from the new tip to the last reported one so that the
+    // subscribers don't miss blocks processed between two checks. On a fork
+    // switch the walk stops at the height of the last reported tip.
+    let is_missing = |prev_hash: CryptoHash, prev_height: Option<BlockHeight>| {
+        prev_hash != last_tip.hash && prev_height.is_some_and(|height| height > last_tip.height)
+    };
+    let mut prev_hash = tip.header.prev_hash;
+    let mut prev_height = tip.header.prev_height;
+    let mut blocks = vec![Arc::new(tip)];
+    while is_missing(prev_hash, prev_height) && blocks.len() < MAX_BACKFILL_BLOCKS {
+        let block_reference = BlockReference::BlockId(BlockId::Hash(prev_hash));
+        let Some(block) = fetch_block(view_client_sender, block_reference).await else {
+            break;
+        };
+        prev_hash = block.header.prev_hash;
+        prev_height = block.header.prev_height;
+        blocks.push(Arc::new(block));
+    }
+    let skipped = if is_missing(prev_hash, prev_height) {
+        let skipped = prev_height.unwrap_or_default() - last_tip.height;
+        tracing::debug!(target: "jsonrpc", skipped, "Chain events feeder skipped blocks");
+        skipped
+    } else {
+        0
+    };
+    blocks.reverse();
+    Some(TipUpdate { skipped, blocks })
+}
+
+async fn fetch_block(
+    view_client_sender: &ViewClientSenderForRpc,
+    block_reference: BlockReference,
+) -> Option<BlockView> {
+    match view_client_sender.send_async(GetBlock(block_reference)).await {
+        Ok(Ok(block)) => Some(block),
+        Ok(Err(err)) => {
+            tracing::debug!(target: "jsonrpc", ?err, "Chain events feeder failed to fetch block");
+            None
+        }
+        Err(err) => {
+            tracing::debug!(target: "jsonrpc", ?err, "Chain events feeder failed to reach view client");
+            None
+        }
+    }
+}
+
+/// Returns ids of the transactions and receipts executed in the given block.
+async fn fetch_executed_ids(
+    view_client_sender: &ViewClientSenderForRpc,
+    block_hash: CryptoHash,
+) -> Option<Arc<HashSet<CryptoHash>>> {
+    match view_client_sender.send_async(GetExecutionOutcomesForBlock { block_hash }).await {
+        Ok(Ok(outcomes)) => {
+            Some(Arc::new(outcomes.into_values().flatten().map(|outcome| outcome.id).collect()))
+        }
+        Ok(Err(err)) => {
+            tracing::debug!(target: "jsonrpc", %err, "Chain events feeder failed to fetch execution outcomes");
+            None
+        }
+        Err(err) => {
+            tracing::debug!(target: "jsonrpc", ?err, "Chain events feeder failed to reach view client");
+            None
+        }
+    }
+}
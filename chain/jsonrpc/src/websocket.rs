@@ -0,0 +1,567 @@
+//! WebSocket transport of the JSON RPC.
+//!
+//! A WebSocket connection accepts the same JSON RPC requests as the HTTP
+//! endpoint and additionally `EXPERIMENTAL_subscribe` and
+//! `EXPERIMENTAL_unsubscribe` methods. Events of the active subscriptions are
+//! pushed to the client as `EXPERIMENTAL_subscription` notifications.
+//!
+//! Requests of a single connection are processed one at a time. Messages sent
+//! to the client are queued up to `max_pending_messages_per_connection`; a
+//! client which doesn't keep up with reading them gets disconnected.
+
+use crate::account_events::{self, fetch_account_events, AccountEvents, AccountEventsFilter};
+use crate::rate_limits::RpcClientId;
+use crate::subscriptions::{ChainEvent, ConnectionGuard, SubscriptionHub};
+use crate::{process_method_call, tx_execution_status_meets_expectations, JsonRpcHandler};
+use actix_http::ws;
+use actix_web::http::header;
+use actix_web::{web, HttpRequest, HttpResponse};
+use bytes::{Bytes, BytesMut};
+use futures::{Stream, StreamExt};
+use near_client::TxStatus;
+use near_jsonrpc_primitives::errors::RpcError;
+use near_jsonrpc_primitives::message::{self, Message, Request};
+use near_jsonrpc_primitives::types::subscriptions::{
+    RpcSubscribeRequest, RpcSubscribeResponse, RpcSubscriptionError, RpcSubscriptionEvent,
+    RpcSubscriptionId, RpcSubscriptionKind, RpcSubscriptionNotification, RpcUnsubscribeRequest,
+    RpcUnsubscribeResponse, SUBSCRIPTION_NOTIFICATION_METHOD,
+};
+use near_jsonrpc_primitives::types::transactions::RpcTransactionError;
+use near_primitives::hash::CryptoHash;
+use near_primitives::types::BlockHeight;
+use near_primitives::views::{
+    BlockView, FinalExecutionOutcomeViewEnum, TxExecutionStatus, TxStatusView,
+};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{broadcast, mpsc};
+use tokio_util::codec::{Decoder, Encoder};
+
+/// Upgrades an HTTP request to a WebSocket connection and spawns a task
+/// serving it.
+pub(crate) async fn websocket_handler(
+    request: HttpRequest,
+    payload: web::Payload,
+    handler: web::Data<JsonRpcHandler>,
+) -> HttpResponse {
+    let Some(hub) = handler.subscription_hub.clone() else {
+        return HttpResponse::NotFound().finish();
+    };
+    if let Err(err) = ws::verify_handshake(request.head()) {
+        return HttpResponse::BadRequest().body(err.to_string());
+    }
+    let Some(key) = request.headers().get(header::SEC_WEBSOCKET_KEY) else {
+        return HttpResponse::BadRequest().finish();
+    };
+    let accept_key = ws::hash_key(key.as_bytes());
+    let client = handler.rate_limiter.client_id(&request);
+    let Some(connection_guard) = hub.try_open_connection() else {
+        crate::metrics::RPC_WEBSOCKET_REJECTED_CONNECTIONS.inc();
+        return HttpResponse::ServiceUnavailable().finish();
+    };
+
+    let config = hub.config();
+    let (outgoing_sender, outgoing_receiver) =
+        mpsc::channel(config.max_pending_messages_per_connection.max(1));
+    let frames = decode_frames(payload, hub.max_message_size());
+    let connection = Connection {
+        handler,
+        client,
+        hub: hub.clone(),
+        outgoing: outgoing_sender,
+        next_subscription_id: 0,
+        subscriptions: BTreeMap::new(),
+        _guard: connection_guard,
+    };
+    actix::spawn(connection.run(frames));
+
+    HttpResponse::SwitchingProtocols()
+        .upgrade("websocket")
+        .insert_header((header::SEC_WEBSOCKET_ACCEPT, &accept_key[..]))
+        .streaming(encode_frames(outgoing_receiver))
+}
+
+/// Turns the raw request payload into a stream of WebSocket frames.
+fn decode_frames(
+    payload: web::Payload,
+    max_frame_size: usize,
+) -> impl Stream<Item = Result<ws::Frame, String>> {
+    let codec = ws::Codec::new().max_size(max_frame_size);
+    futures::stream::unfold(
+        (payload, BytesMut::new(), codec),
+        |(mut payload, mut buffer, mut codec)| async move {
+            loop {
+                match codec.decode(&mut buffer) {
+                    Ok(Some(frame)) => return Some((Ok(frame), (payload, buffer, codec))),
+                    Ok(None) => {}
+                    Err(err) => return Some((Err(err.to_string()), (payload, buffer, codec))),
+                }
+                match payload.next().await {
+                    Some(Ok(bytes)) => buffer.extend_from_slice(&bytes),
+                    Some(Err(err)) => {
+                        return Some((Err(err.to_string()), (payload, buffer, codec)))
+                    }
+                    None => return None,
+                }
+            }
+        },
+    )
+}
+
+/// Turns messages queued for the client into the response body. The body
+/// ends, closing the connection, once the connection task drops the sender.
+fn encode_frames(
+    outgoing: mpsc::Receiver<ws::Message>,
+) -> impl Stream<Item = Result<Bytes, ws::ProtocolError>> {
+    futures::stream::unfold((outgoing, ws::Codec::new()), |(mut outgoing, mut codec)| async move {
+        let message = outgoing.recv().await?;
+        let mut buffer = BytesMut::new();
+        let result = codec.encode(message, &mut buffer).map(|()| buffer.freeze());
+        Some((result, (outgoing, codec)))
+    })
+}
+
+/// Returned by the connection handlers when the connection should be closed.
+struct ConnectionClosed;
+
+type HandleResult = Result<(), ConnectionClosed>;
+
+struct Subscription {
+    kind: RpcSubscriptionKind,
+    /// Last status reported for a `TxStatus` subscription.
+    last_tx_status: Option<TxExecutionStatus>,
+    /// Ids of the transaction and receipts whose execution may change the
+    /// status of a `TxStatus` subscription. Unset when the status has to be
+    /// fetched on the next head.
+    awaited_ids: Option<HashSet<CryptoHash>>,
+    /// Filter of an `AccountEvents` subscription, built when subscribing.
+    account_events_filter: Option<Arc<AccountEventsFilter>>,
+    /// Height of the next block to check for an `AccountEvents` subscription.
+    /// Unset until the first final block is observed.
+    next_block_height: Option<BlockHeight>,
+}
+
+impl Subscription {
+    /// Whether the execution of a block may have changed the status of the
+    /// transaction, given the ids executed in the block if they are known.
+    fn awaits_any_of(&self, executed_ids: Option<&HashSet<CryptoHash>>) -> bool {
+        match (&self.awaited_ids, executed_ids) {
+            (Some(awaited_ids), Some(executed_ids)) => !awaited_ids.is_disjoint(executed_ids),
+            _ => true,
+        }
+    }
+
+    /// Whether the status of the transaction may advance once more blocks
+    /// become final.
+    fn awaits_finality(&self) -> bool {
+        self.last_tx_status.as_ref().is_some_and(|status| *status != TxExecutionStatus::Final)
+    }
+}
+
+struct Connection {
+    handler: web::Data<JsonRpcHandler>,
+    /// Identity of the client for the rate limits of its requests.
+    client: RpcClientId,
+    hub: Arc<SubscriptionHub>,
+    outgoing: mpsc::Sender<ws::Message>,
+    next_subscription_id: RpcSubscriptionId,
+    subscriptions: BTreeMap<RpcSubscriptionId, Subscription>,
+    _guard: ConnectionGuard,
+}
+
+impl Connection {
+    async fn run(mut self, frames: impl Stream<Item = Result<ws::Frame, String>>) {
+        let mut frames = std::pin::pin!(frames);
+        let mut chain_events: Option<broadcast::Receiver<ChainEvent>> = None;
+        loop {
+            let result = tokio::select! {
+                frame = frames.next() => match frame {
+                    Some(Ok(frame)) => self.on_frame(frame).await,
+                    Some(Err(err)) => {
+                        tracing::debug!(target: "jsonrpc", %err, "Failed to read WebSocket frame");
+                        let _ = self.send(ws::Message::Close(Some(ws::CloseCode::Protocol.into())));
+                        Err(ConnectionClosed)
+                    }
+                    None => Err(ConnectionClosed),
+                },
+                event = recv_chain_event(&mut chain_events) => self.on_chain_event(event).await,
+            };
+            if result.is_err() {
+                break;
+            }
+            // Listen to the chain events only while there is something to
+            // report so that idle connections don't keep the feeder busy.
+            if self.subscriptions.is_empty() {
+                chain_events = None;
+            } else if chain_events.is_none() {
+                chain_events = Some(self.hub.subscribe_chain_events());
+            }
+        }
+    }
+
+    async fn on_frame(&mut self, frame: ws::Frame) -> HandleResult {
+        match frame {
+            ws::Frame::Text(bytes) | ws::Frame::Binary(bytes) => self.on_message(&bytes).await,
+            ws::Frame::Ping(bytes) => self.send(ws::Message::Pong(bytes)),
+            ws::Frame::Pong(_) => Ok(()),
+            ws::Frame::Close(reason) => {
+                let _ = self.send(ws::Message::Close(reason));
+                Err(ConnectionClosed)
+            }
+            ws::Frame::Continuation(_) => {
+                let _ = self.send(ws::Message::Close(Some(ws::CloseCode::Unsupported.into())));
+                Err(ConnectionClosed)
+            }
+        }
+    }
+
+    async fn on_message(&mut self, bytes: &[u8]) -> HandleResult {
+        let response = match message::from_slice(bytes) {
+            Ok(Message::Request(request)) => {
+                let id = request.id.clone();
+                Message::response(id, self.process_request(request).await)
+            }
+            // Notifications are never answered.
+            Ok(Message::Notification(notification)) => {
+                self.handler.process_notification(notification, &self.client).await;
+                return Ok(());
+            }
+            // Subscriptions aren't supported in batches, their requests are
+            // answered with an error.
+            Ok(Message::Batch(messages)) => {
+                match self.handler.process_batch(messages, &self.client).await {
+                    Ok(Some(responses)) => return self.send(ws::Message::Text(responses.into())),
+                    Ok(None) => return Ok(()),
+                    Err(err) => Message::error(err),
+                }
+            }
+            Ok(_) => Message::error(RpcError::parse_error(
+                "JSON RPC Request format was expected".to_owned(),
+            )),
+            Err(broken) => broken.reply(),
+        };
+        self.send_message(response)
+    }
+
+    async fn process_request(&mut self, request: Request) -> Result<Value, RpcError> {
+        match request.method.as_ref() {
+            "EXPERIMENTAL_subscribe" => {
+                process_method_call(request, |params| std::future::ready(self.subscribe(params)))
+                    .await
+            }
+            "EXPERIMENTAL_unsubscribe" => {
+                process_method_call(request, |params| std::future::ready(self.unsubscribe(params)))
+                    .await
+            }
+            _ => self.handler.process_request(request, &self.client).await,
+        }
+    }
+
+    fn subscribe(
+        &mut self,
+        request: RpcSubscribeRequest,
+    ) -> Result<RpcSubscribeResponse, RpcSubscriptionError> {
+        let limit = self.hub.config().max_subscriptions_per_connection;
+        if self.subscriptions.len() >= limit {
+            return Err(RpcSubscriptionError::TooManySubscriptions { limit });
+        }
+        let (account_events_filter, next_block_height) = match &request.subscription {
+            RpcSubscriptionKind::AccountEvents {
+                account_ids,
+                method_names,
+                log_prefix,
+                from_block_height,
+            } => {
+                if account_ids.is_empty() {
+                    return Err(RpcSubscriptionError::InvalidSubscription {
+                        error_message: "account_ids must not be empty".to_string(),
+                    });
+                }
+                let limit = self.hub.config().max_accounts_per_subscription;
+                if account_ids.len() > limit {
+                    return Err(RpcSubscriptionError::InvalidSubscription {
+                        error_message: format!(
+                            "account_ids must not hold more than {limit} accounts"
+                        ),
+                    });
+                }
+                let filter = AccountEventsFilter {
+                    account_ids: account_ids.iter().cloned().collect(),
+                    method_names: method_names.clone(),
+                    log_prefix: log_prefix.clone(),
+                };
+                (Some(Arc::new(filter)), *from_block_height)
+            }
+            _ => (None, None),
+        };
+        let subscription_id = self.next_subscription_id;
+        self.next_subscription_id += 1;
+        self.subscriptions.insert(
+            subscription_id,
+            Subscription {
+                kind: request.subscription,
+                last_tx_status: None,
+                awaited_ids: None,
+                account_events_filter,
+                next_block_height,
+            },
+        );
+        Ok(RpcSubscribeResponse { subscription_id })
+    }
+
+    fn unsubscribe(
+        &mut self,
+        request: RpcUnsubscribeRequest,
+    ) -> Result<RpcUnsubscribeResponse, RpcSubscriptionError> {
+        let subscription_id = request.subscription_id;
+        match self.subscriptions.remove(&subscription_id) {
+            Some(_) => Ok(RpcUnsubscribeResponse { subscription_id }),
+            None => Err(RpcSubscriptionError::UnknownSubscription { subscription_id }),
+        }
+    }
+
+    async fn on_chain_event(&mut self, event: Result<ChainEvent, RecvError>) -> HandleResult {
+        match event {
+            Ok(ChainEvent::NewHead { block, executed_ids }) => {
+                self.on_new_head(&block)?;
+                self.update_tx_statuses(|subscription| {
+                    subscription.awaits_any_of(executed_ids.as_deref())
+                })
+                .await
+            }
+            Ok(ChainEvent::FinalBlock(block)) => {
+                self.on_final_block(&block)?;
+                self.update_account_events(&block).await?;
+                self.update_tx_statuses(Subscription::awaits_finality).await
+            }
+            Ok(ChainEvent::Skipped(skipped)) | Err(RecvError::Lagged(skipped)) => {
+                self.on_lagged(skipped)
+            }
+            Err(RecvError::Closed) => Err(ConnectionClosed),
+        }
+    }
+
+    fn on_new_head(&self, block: &BlockView) -> HandleResult {
+        for (&subscription_id, subscription) in &self.subscriptions {
+            match &subscription.kind {
+                RpcSubscriptionKind::NewHeads => self
+                    .notify(subscription_id, RpcSubscriptionEvent::NewHead(block.header.clone()))?,
+                RpcSubscriptionKind::ChunkInclusion { shard_id } => {
+                    for chunk in &block.chunks {
+                        if chunk.shard_id != *shard_id
+                            || chunk.height_included != block.header.height
+                        {
+                            continue;
+                        }
+                        self.notify(
+                            subscription_id,
+                            RpcSubscriptionEvent::ChunkIncluded {
+                                block_hash: block.header.hash,
+                                block_height: block.header.height,
+                                chunk: chunk.clone(),
+                            },
+                        )?;
+                    }
+                }
+                RpcSubscriptionKind::FinalBlocks
+                | RpcSubscriptionKind::TxStatus { .. }
+                | RpcSubscriptionKind::AccountEvents { .. } => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn on_final_block(&self, block: &BlockView) -> HandleResult {
+        for (&subscription_id, subscription) in &self.subscriptions {
+            if subscription.kind == RpcSubscriptionKind::FinalBlocks {
+                self.notify(
+                    subscription_id,
+                    RpcSubscriptionEvent::FinalBlock(block.header.clone()),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    fn on_lagged(&mut self, skipped: u64) -> HandleResult {
+        // The missed blocks might have executed the awaited receipts, so the
+        // transaction statuses are re-fetched on the next head.
+        for subscription in self.subscriptions.values_mut() {
+            subscription.awaited_ids = None;
+        }
+        for (&subscription_id, subscription) in &self.subscriptions {
+            // Transaction statuses are re-fetched after a lag and account
+            // events are fetched by height, so they can't miss an update.
+            if !matches!(
+                subscription.kind,
+                RpcSubscriptionKind::TxStatus { .. } | RpcSubscriptionKind::AccountEvents { .. }
+            ) {
+                self.notify(subscription_id, RpcSubscriptionEvent::Lagged { skipped })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reports account events in the final blocks up to `final_block` which
+    /// haven't been checked yet.
+    async fn update_account_events(&mut self, final_block: &BlockView) -> HandleResult {
+        let account_subscriptions: Vec<_> = self
+            .subscriptions
+            .iter()
+            .filter_map(|(&subscription_id, subscription)| {
+                let filter = subscription.account_events_filter.clone()?;
+                Some((subscription_id, filter, subscription.next_block_height))
+            })
+            .collect();
+        let final_height = final_block.header.height;
+        for (subscription_id, filter, next_block_height) in account_subscriptions {
+            let mut height = next_block_height.unwrap_or(final_height);
+            let last_height =
+                final_height.min(height.saturating_add(account_events::MAX_BLOCKS_PER_UPDATE - 1));
+            while height <= last_height {
+                match fetch_account_events(&self.handler, &filter, final_block, height).await {
+                    Ok(AccountEvents::Block(Some(event))) => self.notify(subscription_id, event)?,
+                    Ok(AccountEvents::Block(None)) => {}
+                    Ok(AccountEvents::GarbageCollected { earliest_height }) => {
+                        // The events can't be re-queried anymore, the client
+                        // only learns how many blocks it missed.
+                        let skipped = earliest_height - height;
+                        self.notify(subscription_id, RpcSubscriptionEvent::Lagged { skipped })?;
+                        height = earliest_height;
+                        break;
+                    }
+                    Err(err) => {
+                        // Retried on the next final block.
+                        tracing::debug!(target: "jsonrpc", ?err, height, "Failed to fetch account events for a subscription");
+                        break;
+                    }
+                }
+                height += 1;
+            }
+            if let Some(subscription) = self.subscriptions.get_mut(&subscription_id) {
+                subscription.next_block_height = Some(height);
+            }
+        }
+        Ok(())
+    }
+
+    /// Fetches the status of the transactions whose subscriptions match
+    /// `should_update` and notifies about the changed ones. Subscriptions
+    /// whose transactions reached the awaited status are dropped.
+    async fn update_tx_statuses(
+        &mut self,
+        should_update: impl Fn(&Subscription) -> bool,
+    ) -> HandleResult {
+        let tx_subscriptions: Vec<_> = self
+            .subscriptions
+            .iter()
+            .filter(|(_, subscription)| should_update(subscription))
+            .filter_map(|(&subscription_id, subscription)| match &subscription.kind {
+                RpcSubscriptionKind::TxStatus { tx_hash, sender_account_id, wait_until } => {
+                    Some((subscription_id, *tx_hash, sender_account_id.clone(), wait_until.clone()))
+                }
+                _ => None,
+            })
+            .collect();
+        for (subscription_id, tx_hash, signer_account_id, wait_until) in tx_subscriptions {
+            let status: Result<TxStatusView, RpcTransactionError> = self
+                .handler
+                .view_client_send(TxStatus { tx_hash, signer_account_id, fetch_receipt: false })
+                .await;
+            let status = match status {
+                Ok(status) => status,
+                // The node may hear about the transaction without executing
+                // it, so it's looked up again on every head until then.
+                Err(RpcTransactionError::UnknownTransaction { .. }) => continue,
+                Err(err) => {
+                    tracing::debug!(target: "jsonrpc", ?err, ?tx_hash, "Failed to fetch transaction status for a subscription");
+                    continue;
+                }
+            };
+            let Some(subscription) = self.subscriptions.get_mut(&subscription_id) else {
+                continue;
+            };
+            subscription.awaited_ids = Some(pending_execution_ids(tx_hash, &status));
+            if subscription.last_tx_status.as_ref() == Some(&status.status) {
+                continue;
+            }
+            subscription.last_tx_status = Some(status.status.clone());
+            let is_done = tx_execution_status_meets_expectations(&wait_until, &status.status);
+            self.notify(
+                subscription_id,
+                RpcSubscriptionEvent::TxStatus { tx_hash, response: status.into() },
+            )?;
+            if is_done {
+                self.subscriptions.remove(&subscription_id);
+            }
+        }
+        Ok(())
+    }
+
+    fn notify(
+        &self,
+        subscription_id: RpcSubscriptionId,
+        event: RpcSubscriptionEvent,
+    ) -> HandleResult {
+        let notification = RpcSubscriptionNotification { subscription_id, event };
+        let params = match serde_json::to_value(notification) {
+            Ok(params) => params,
+            Err(err) => {
+                tracing::warn!(target: "jsonrpc", ?err, "Failed to serialize subscription notification");
+                return Ok(());
+            }
+        };
+        self.send_message(Message::notification(
+            SUBSCRIPTION_NOTIFICATION_METHOD.to_owned(),
+            params,
+        ))
+    }
+
+    fn send_message(&self, message: Message) -> HandleResult {
+        let text: String = message.into();
+        self.send(ws::Message::Text(text.into()))
+    }
+
+    fn send(&self, message: ws::Message) -> HandleResult {
+        match self.outgoing.try_send(message) {
+            Ok(()) => Ok(()),
+            Err(mpsc::error::TrySendError::Full(_)) => {
+                tracing::debug!(target: "jsonrpc", "Closing WebSocket connection of a client which doesn't keep up with the messages");
+                crate::metrics::RPC_WEBSOCKET_SLOW_CLIENT_DISCONNECTS.inc();
+                Err(ConnectionClosed)
+            }
+            Err(mpsc::error::TrySendError::Closed(_)) => Err(ConnectionClosed),
+        }
+    }
+}
+
+/// Returns ids of the transaction and receipts which haven't been executed yet
+/// according to the given status.
+fn pending_execution_ids(tx_hash: CryptoHash, status: &TxStatusView) -> HashSet<CryptoHash> {
+    let outcome = match &status.execution_outcome {
+        Some(FinalExecutionOutcomeViewEnum::FinalExecutionOutcome(outcome)) => outcome,
+        Some(FinalExecutionOutcomeViewEnum::FinalExecutionOutcomeWithReceipt(outcome)) => {
+            &outcome.final_outcome
+        }
+        None => return HashSet::from([tx_hash]),
+    };
+    let outcomes =
+        || std::iter::once(&outcome.transaction_outcome).chain(&outcome.receipts_outcome);
+    let executed_ids: HashSet<_> = outcomes().map(|outcome| outcome.id).collect();
+    outcomes()
+        .flat_map(|outcome| &outcome.outcome.receipt_ids)
+        .filter(|receipt_id| !executed_ids.contains(receipt_id))
+        .copied()
+        .collect()
+}
+
+async fn recv_chain_event(
+    receiver: &mut Option<broadcast::Receiver<ChainEvent>>,
+) -> Result<ChainEvent, RecvError> {
+    match receiver {
+        Some(receiver) => receiver.recv().await,
+        None => std::future::pending().await,
+    }
+}
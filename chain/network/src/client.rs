@@ -112,6 +112,11 @@ pub enum ProcessTxResponse {
     /// The node being queried does not track the shard needed and therefore cannot provide userful
     /// response.
     DoesNotTrackShard,
+    /// The transaction pool holds a transaction with the same nonce and a priority fee which is
+    /// not lower, so the transaction doesn't replace it.
+    ReplacementUnderpriced,
+    /// The transaction pool holds the maximum number of transactions of the access key.
+    TooManyPendingTransactions,
 }
 
 /// Account announcements that needs to be validated before being processed.
@@ -71,6 +71,7 @@ pub fn make_block(
         clock,
         None,
     )
+    .unwrap()
 }
 
 pub fn make_account_id<R: Rng>(rng: &mut R) -> AccountId {
@@ -107,7 +108,7 @@ pub fn make_peer_info<R: Rng>(rng: &mut R) -> PeerInfo {
 pub fn make_announce_account<R: Rng>(rng: &mut R) -> AnnounceAccount {
     let peer_id = make_peer_id(rng);
     let validator_signer = ValidatorSigner::InMemory(make_validator_signer(rng));
-    AnnounceAccount::new(&validator_signer, peer_id, EpochId::default())
+    AnnounceAccount::new(&validator_signer, peer_id, EpochId::default()).unwrap()
 }
 
 pub fn make_partial_edge<R: Rng>(rng: &mut R) -> PartialEdgeInfo {
@@ -166,6 +167,7 @@ pub fn make_challenge<R: Rng>(rng: &mut R) -> Challenge {
         }),
         &make_validator_signer(rng).into(),
     )
+    .unwrap()
 }
 
 // Based on ShardsManager::prepare_partial_encoded_chunk_response_from_chunk.
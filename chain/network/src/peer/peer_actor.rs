@@ -486,13 +486,17 @@ impl PeerActor {
                 archival: self.network_state.config.archive,
             },
             partial_edge_info: spec.partial_edge_info,
-            owned_account: self.network_state.config.validator.signer.get().map(|signer| {
+            owned_account: self.network_state.config.validator.signer.get().and_then(|signer| {
                 OwnedAccount {
                     account_key: signer.public_key(),
                     peer_id: self.network_state.config.node_id(),
                     timestamp: self.clock.now_utc(),
                 }
                 .sign(&signer)
+                .inspect_err(|err| {
+                    tracing::warn!(target: "network", ?err, "Failed to sign the owned account")
+                })
+                .ok()
             }),
         };
         let msg = match spec.tier {
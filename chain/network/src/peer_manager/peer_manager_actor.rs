@@ -738,6 +738,7 @@ impl PeerManagerActor {
                 .collect(),
             tier1_accounts_keys: self.state.accounts_data.load().keys.iter().cloned().collect(),
             tier1_accounts_data: self.state.accounts_data.load().data.values().cloned().collect(),
+            snapshot_hosts: self.state.snapshot_hosts.get_hosts(),
         }
     }
 
@@ -825,17 +826,20 @@ impl PeerManagerActor {
                 sync_hash,
                 sync_prev_prev_hash,
                 part_id,
+                peer_id,
             } => {
                 let mut success = false;
 
                 // The node needs to include its own public address in the request
                 // so that the reponse can be sent over Tier3
                 if let Some(addr) = *self.state.my_public_addr.read() {
-                    if let Some(peer_id) = self.state.snapshot_hosts.select_host_for_part(
-                        &sync_prev_prev_hash,
-                        shard_id,
-                        part_id,
-                    ) {
+                    if let Some(peer_id) = peer_id.or_else(|| {
+                        self.state.snapshot_hosts.select_host_for_part(
+                            &sync_prev_prev_hash,
+                            shard_id,
+                            part_id,
+                        )
+                    }) {
                         tracing::debug!(target: "network", "requesting {sync_prev_prev_hash} {shard_id} {part_id} from {peer_id}");
                         success =
                             self.state.send_message_to_peer(
@@ -80,6 +80,13 @@ impl TokenBucket {
         }
     }
 
+    /// Returns whether `tokens` tokens could be acquired at `now`, without
+    /// acquiring them.
+    pub fn can_acquire(&mut self, tokens: u32, now: Instant) -> bool {
+        self.refill(now);
+        self.size >= to_tokens_with_parts(tokens)
+    }
+
     /// Refills the bucket with the right number of tokens according to
     /// the `refill_rate` and the new current time `now`.
     ///
@@ -178,6 +185,19 @@ mod tests {
         assert_eq!(bucket.size, to_tokens_with_parts(0));
     }
 
+    #[test]
+    fn can_acquire() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(1, 10, 1.0, now).expect("bucket should be well formed");
+
+        assert!(bucket.can_acquire(1, now));
+        assert!(!bucket.can_acquire(2, now));
+        assert_eq!(bucket.size, to_tokens_with_parts(1));
+
+        assert!(bucket.can_acquire(2, now + Duration::seconds(1)));
+        assert_eq!(bucket.size, to_tokens_with_parts(2));
+    }
+
     #[test]
     fn max_is_zero() {
         let now = Instant::now();
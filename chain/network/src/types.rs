@@ -254,6 +254,9 @@ pub enum NetworkRequests {
         sync_hash: CryptoHash,
         sync_prev_prev_hash: CryptoHash,
         part_id: u64,
+        /// Snapshot host to request the part from. If not set, the host is
+        /// selected from the known snapshot hosts.
+        peer_id: Option<PeerId>,
     },
     /// Ban given peer.
     BanPeer { peer_id: PeerId, ban_reason: ReasonForBan },
@@ -413,6 +416,8 @@ pub struct NetworkInfo {
     pub tier1_accounts_data: Vec<Arc<SignedAccountData>>,
     /// TIER1 connections.
     pub tier1_connections: Vec<ConnectedPeerInfo>,
+    /// Known hosts of state snapshots, which serve state parts.
+    pub snapshot_hosts: Vec<Arc<SnapshotHostInfo>>,
 }
 
 #[derive(Debug, actix::MessageResponse, PartialEq, Eq)]
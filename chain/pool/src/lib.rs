@@ -1,8 +1,10 @@
+use std::cmp::Reverse;
 use std::collections::btree_map::Entry;
-use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
 
 use crate::types::{PoolKey, TransactionGroup, TransactionGroupIterator};
 
+use near_chain_configs::NonceReplacementPolicy;
 use near_crypto::PublicKey;
 use near_o11y::metrics::prometheus::core::{AtomicI64, GenericGauge};
 use near_primitives::epoch_info::RngSeed;
@@ -22,6 +24,59 @@ pub enum InsertTransactionResult {
     Duplicate,
     /// Not enough space to fit the transaction.
     NoSpaceLeft,
+    /// A transaction with the same nonce is already in the pool, and the new one pays a priority
+    /// fee which isn't higher than the one of the transaction in the pool.
+    Underpriced,
+    /// The pool already holds the maximum number of transactions of the signer and public key,
+    /// none of them with a higher nonce than the new one.
+    KeyLimitReached,
+}
+
+/// Why transactions were evicted from the pool to make room for a new transaction.
+#[derive(Clone, Copy, Debug)]
+enum EvictionReason {
+    /// Replaced by a transaction with the same nonce.
+    Replaced,
+    /// The group of the new transaction reached `max_transactions_per_key`.
+    KeyLimit,
+    /// The pool reached `total_transaction_size_limit`.
+    SizeLimit,
+}
+
+impl EvictionReason {
+    fn as_str(&self) -> &'static str {
+        match self {
+            EvictionReason::Replaced => "replaced",
+            EvictionReason::KeyLimit => "key_limit",
+            EvictionReason::SizeLimit => "size_limit",
+        }
+    }
+}
+
+/// Priority of a transaction group when the pool is full. Transactions are evicted first from the
+/// groups whose cheapest transaction pays a lower priority fee, and among them from the groups
+/// with more transactions.
+/// Transactions without a priority fee count as paying zero.
+///
+/// Only transactions which pay a priority fee themselves evict or replace other transactions.
+/// The chain doesn't accept such transactions yet, until then the pool behaves as if the size
+/// limit was strict and the nonce replacement policy `HigherPriorityFee` was `Disabled`.
+type GroupPriority = (u64, Reverse<usize>);
+
+fn group_priority<'a>(transactions: impl Iterator<Item = &'a SignedTransaction>) -> GroupPriority {
+    let mut min_priority_fee = u64::MAX;
+    let mut num_transactions = 0;
+    for tx in transactions {
+        min_priority_fee = min_priority_fee.min(tx.transaction.priority_fee().unwrap_or(0));
+        num_transactions += 1;
+    }
+    (min_priority_fee, Reverse(num_transactions))
+}
+
+/// Sorts transactions of a group by their nonces. The sort is stable, so that the same
+/// transactions are evicted when they are selected and when they are removed.
+fn sort_by_nonce<T: std::borrow::Borrow<SignedTransaction>>(transactions: &mut [T]) {
+    transactions.sort_by_key(|tx| tx.borrow().transaction.nonce());
 }
 
 /// Transaction pool: keeps track of transactions that were not yet accepted into the block chain.
@@ -32,23 +87,34 @@ pub struct TransactionPool {
     transactions: BTreeMap<PoolKey, Vec<SignedTransaction>>,
     /// Set of all hashes to quickly check if the given transaction is in the pool.
     unique_transactions: HashSet<CryptoHash>,
+    /// Groups in `transactions` ordered by their priority, lowest first, to find the groups to
+    /// evict without going over the whole pool.
+    eviction_order: BTreeSet<(GroupPriority, PoolKey)>,
     /// A uniquely generated key seed to randomize PoolKey order.
     key_seed: RngSeed,
     /// The key after which the pool iterator starts. Doesn't have to be present in the pool.
     last_used_key: PoolKey,
-    /// If set, new transactions that bring the size of the pool over this limit will be rejected.
+    /// If set, new transactions that bring the size of the pool over this limit evict groups with
+    /// a lower priority if they pay a priority fee, or are rejected otherwise.
     total_transaction_size_limit: Option<u64>,
+    /// If set, a (signer, public key) pair can't have more transactions in the pool.
+    max_transactions_per_key: Option<usize>,
+    /// What to do with a new transaction with the same nonce as a transaction in the pool.
+    nonce_replacement: NonceReplacementPolicy,
     /// Total size of transactions in the pool measured in bytes.
     total_transaction_size: u64,
     /// Metrics tracked for transaction pool.
     transaction_pool_count_metric: GenericGauge<AtomicI64>,
     transaction_pool_size_metric: GenericGauge<AtomicI64>,
+    metrics_label: String,
 }
 
 impl TransactionPool {
     pub fn new(
         key_seed: RngSeed,
         total_transaction_size_limit: Option<u64>,
+        max_transactions_per_key: Option<usize>,
+        nonce_replacement: NonceReplacementPolicy,
         metrics_label: &str,
     ) -> Self {
         let transaction_pool_count_metric =
@@ -63,11 +129,15 @@ impl TransactionPool {
             key_seed,
             transactions: BTreeMap::new(),
             unique_transactions: HashSet::new(),
+            eviction_order: BTreeSet::new(),
             last_used_key: CryptoHash::default(),
             total_transaction_size_limit,
+            max_transactions_per_key,
+            nonce_replacement,
             total_transaction_size: 0,
             transaction_pool_count_metric,
             transaction_pool_size_metric,
+            metrics_label: metrics_label.to_string(),
         }
     }
 
@@ -79,42 +149,205 @@ impl TransactionPool {
     }
 
     /// Inserts a signed transaction that passed validation into the pool.
+    ///
+    /// To make room for the transaction, it may evict a transaction with the same nonce, the
+    /// transaction with the highest nonce of its group, or the transactions with the highest
+    /// nonces of groups with a lower priority.
     #[must_use]
     pub fn insert_transaction(
         &mut self,
         signed_transaction: SignedTransaction,
     ) -> InsertTransactionResult {
-        if !self.unique_transactions.insert(signed_transaction.get_hash()) {
+        if self.unique_transactions.contains(&signed_transaction.get_hash()) {
             // The hash of this transaction was already seen, skip it.
             return InsertTransactionResult::Duplicate;
         }
+        let signer_id = signed_transaction.transaction.signer_id();
+        let signer_public_key = signed_transaction.transaction.public_key();
+        let key = self.key(signer_id, signer_public_key);
+        let group = self.transactions.get(&key).map_or(&[][..], |group| group.as_slice());
+
+        let evicted_from_group = match self.select_evicted_from_group(group, &signed_transaction) {
+            Ok(evicted_from_group) => evicted_from_group,
+            Err(result) => return result,
+        };
+        let evicted_index = evicted_from_group.map(|(index, _)| index);
+        let evicted_size = evicted_index.map_or(0, |index| group[index].get_size());
+        let new_group_priority = signed_transaction.transaction.priority_fee().map(|_| {
+            group_priority(
+                group
+                    .iter()
+                    .enumerate()
+                    .filter(|(index, _)| Some(*index) != evicted_index)
+                    .map(|(_, tx)| tx)
+                    .chain(std::iter::once(&signed_transaction)),
+            )
+        });
+
         // We never expect the total size to go over `u64` during real operation as that would
         // be more than 10^9 GiB of RAM consumed for transaction pool, so panicing here is intended
         // to catch a logic error in estimation of transaction size.
         let new_total_transaction_size = self
             .total_transaction_size
             .checked_add(signed_transaction.get_size())
-            .expect("Total transaction size is too large");
+            .expect("Total transaction size is too large")
+            .checked_sub(evicted_size)
+            .expect("Total transaction size dropped below zero");
+        let mut evicted_from_groups = vec![];
         if let Some(limit) = self.total_transaction_size_limit {
             if new_total_transaction_size > limit {
-                return InsertTransactionResult::NoSpaceLeft;
+                match self.select_evicted_from_groups(
+                    &key,
+                    new_group_priority,
+                    new_total_transaction_size - limit,
+                ) {
+                    Some(evicted) => evicted_from_groups = evicted,
+                    None => return InsertTransactionResult::NoSpaceLeft,
+                }
             }
         }
 
         // At this point transaction is accepted to the pool.
-        self.total_transaction_size = new_total_transaction_size;
-        let signer_id = signed_transaction.transaction.signer_id();
-        let signer_public_key = signed_transaction.transaction.public_key();
-        self.transactions
-            .entry(self.key(signer_id, signer_public_key))
-            .or_insert_with(Vec::new)
-            .push(signed_transaction);
+        for (evicted_key, num_evicted) in evicted_from_groups {
+            self.unindex_group(&evicted_key);
+            let mut group = self.transactions.remove(&evicted_key).expect("selected from the pool");
+            sort_by_nonce(&mut group);
+            let evicted = group.split_off(group.len() - num_evicted);
+            self.remove_evicted(&evicted, EvictionReason::SizeLimit);
+            if !group.is_empty() {
+                self.transactions.insert(evicted_key, group);
+                self.index_group(&evicted_key);
+            }
+        }
+        self.unindex_group(&key);
+        if let Some((index, reason)) = evicted_from_group {
+            let group = self.transactions.get_mut(&key).expect("selected from the group");
+            let evicted = group.swap_remove(index);
+            self.remove_evicted(&[evicted], reason);
+        }
+        self.unique_transactions.insert(signed_transaction.get_hash());
+        self.total_transaction_size = self
+            .total_transaction_size
+            .checked_add(signed_transaction.get_size())
+            .expect("Total transaction size is too large");
+        self.transactions.entry(key).or_insert_with(Vec::new).push(signed_transaction);
+        self.index_group(&key);
 
-        self.transaction_pool_count_metric.inc();
+        self.transaction_pool_count_metric.set(self.unique_transactions.len() as i64);
         self.transaction_pool_size_metric.set(self.total_transaction_size as i64);
         InsertTransactionResult::Success
     }
 
+    /// Returns the index of the transaction of the group which the new transaction evicts, if
+    /// any, or the result of the insertion if the new transaction is rejected.
+    fn select_evicted_from_group(
+        &self,
+        group: &[SignedTransaction],
+        signed_transaction: &SignedTransaction,
+    ) -> Result<Option<(usize, EvictionReason)>, InsertTransactionResult> {
+        let nonce = signed_transaction.transaction.nonce();
+        if let Some(index) = group.iter().position(|tx| tx.transaction.nonce() == nonce) {
+            match self.nonce_replacement {
+                NonceReplacementPolicy::Disabled => {}
+                NonceReplacementPolicy::Always => {
+                    return Ok(Some((index, EvictionReason::Replaced)));
+                }
+                NonceReplacementPolicy::HigherPriorityFee => {
+                    // A transaction without a priority fee can't outbid the one in the pool, so
+                    // both are kept. See `GroupPriority`.
+                    if let Some(priority_fee) = signed_transaction.transaction.priority_fee() {
+                        if priority_fee <= group[index].transaction.priority_fee().unwrap_or(0) {
+                            return Err(InsertTransactionResult::Underpriced);
+                        }
+                        return Ok(Some((index, EvictionReason::Replaced)));
+                    }
+                }
+            }
+        }
+        if let Some(limit) = self.max_transactions_per_key {
+            if group.len() >= limit {
+                // Transactions of a group are included in the order of their nonces, so the one
+                // with the highest nonce is the least useful.
+                return match group.iter().enumerate().max_by_key(|(_, tx)| tx.transaction.nonce()) {
+                    Some((index, tx)) if tx.transaction.nonce() > nonce => {
+                        Ok(Some((index, EvictionReason::KeyLimit)))
+                    }
+                    _ => Err(InsertTransactionResult::KeyLimitReached),
+                };
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns the keys of the groups with the lowest priority together with the number of their
+    /// transactions with the highest nonces, which need to be evicted to free `required_size`
+    /// bytes. Only groups with a lower priority than the group of the new transaction are evicted
+    /// from, returns `None` if that isn't enough or if the new transaction doesn't pay a priority
+    /// fee.
+    fn select_evicted_from_groups(
+        &self,
+        key: &PoolKey,
+        priority: Option<GroupPriority>,
+        required_size: u64,
+    ) -> Option<Vec<(PoolKey, usize)>> {
+        let priority = priority?;
+        let mut freed_size = 0;
+        let mut evicted = vec![];
+        for (group_priority, group_key) in &self.eviction_order {
+            if freed_size >= required_size || *group_priority >= priority {
+                break;
+            }
+            if group_key == key {
+                continue;
+            }
+            let mut group: Vec<_> = self.transactions[group_key].iter().collect();
+            sort_by_nonce(&mut group);
+            let mut num_evicted = 0;
+            // Transactions of a group are included in the order of their nonces, so the ones with
+            // the highest nonces are the least useful.
+            for tx in group.iter().rev() {
+                if freed_size >= required_size {
+                    break;
+                }
+                freed_size += tx.get_size();
+                num_evicted += 1;
+            }
+            evicted.push((*group_key, num_evicted));
+        }
+        (freed_size >= required_size).then_some(evicted)
+    }
+
+    /// Removes the group from `eviction_order`, has to be called before the group changes.
+    fn unindex_group(&mut self, key: &PoolKey) {
+        if let Some(group) = self.transactions.get(key) {
+            self.eviction_order.remove(&(group_priority(group.iter()), *key));
+        }
+    }
+
+    /// Adds the group to `eviction_order`, has to be called after the group changes.
+    fn index_group(&mut self, key: &PoolKey) {
+        if let Some(group) = self.transactions.get(key) {
+            self.eviction_order.insert((group_priority(group.iter()), *key));
+        }
+    }
+
+    /// Forgets the transactions which were removed from their group to make room for a new
+    /// transaction.
+    fn remove_evicted(&mut self, transactions: &[SignedTransaction], reason: EvictionReason) {
+        for tx in transactions {
+            self.unique_transactions.remove(&tx.get_hash());
+            // See the comment in `insert_transaction` where we increase the size for reasoning
+            // why panicing here catches a logic error.
+            self.total_transaction_size = self
+                .total_transaction_size
+                .checked_sub(tx.get_size())
+                .expect("Total transaction size dropped below zero");
+        }
+        metrics::TRANSACTION_POOL_EVICTED
+            .with_label_values(&[&self.metrics_label, reason.as_str()])
+            .inc_by(transactions.len() as u64);
+    }
+
     /// Returns a pool iterator wrapper that implements an iterator-like trait to iterate over
     /// transaction groups in the proper order defined by the protocol.
     /// When the iterator is dropped, all remaining groups are inserted back into the pool.
@@ -142,6 +375,7 @@ impl TransactionPool {
                 .insert(tx.get_hash());
         }
         for (key, hashes) in grouped_transactions {
+            self.unindex_group(&key);
             if let Entry::Occupied(mut entry) = self.transactions.entry(key) {
                 entry.get_mut().retain(|tx| {
                     if !hashes.contains(&tx.get_hash()) {
@@ -159,6 +393,7 @@ impl TransactionPool {
                     entry.remove_entry();
                 }
             }
+            self.index_group(&key);
         }
 
         // We can update metrics only once for the whole batch of transactions.
@@ -228,6 +463,7 @@ impl<'a> TransactionGroupIterator for PoolIteratorWrapper<'a> {
                         .expect("we've just checked that the map is not empty")
                 });
             self.pool.last_used_key = key;
+            self.pool.unindex_group(&key);
             let mut transactions =
                 self.pool.transactions.remove(&key).expect("just checked existence");
             transactions.sort_by_key(|st| std::cmp::Reverse(st.transaction.nonce()));
@@ -285,6 +521,7 @@ impl<'a> Drop for PoolIteratorWrapper<'a> {
 
             if !group.transactions.is_empty() {
                 self.pool.transactions.insert(group.key, group.transactions);
+                self.pool.index_group(&group.key);
             }
         }
         // We can update metrics only once for the whole batch of transactions.
@@ -337,9 +574,10 @@ mod tests {
     use rand::seq::SliceRandom;
     use rand::thread_rng;
 
-    use near_crypto::{InMemorySigner, KeyType};
+    use near_crypto::{InMemorySigner, KeyType, Signer};
 
     use near_primitives::hash::CryptoHash;
+    use near_primitives::transaction::{Action, TransferAction};
     use near_primitives::types::Balance;
 
     const TEST_SEED: RngSeed = [3; 32];
@@ -368,11 +606,33 @@ mod tests {
             .collect()
     }
 
+    fn transaction_with_priority_fee(
+        signer_id: &str,
+        nonce: u64,
+        priority_fee: u64,
+        deposit: Balance,
+    ) -> SignedTransaction {
+        let signer_id: AccountId = signer_id.parse().unwrap();
+        let signer: Signer =
+            InMemorySigner::from_seed(signer_id.clone(), KeyType::ED25519, signer_id.as_str())
+                .into();
+        SignedTransaction::from_actions_v1(
+            nonce,
+            signer_id,
+            "bob.near".parse().unwrap(),
+            &signer,
+            vec![Action::Transfer(TransferAction { deposit })],
+            CryptoHash::default(),
+            priority_fee,
+        )
+    }
+
     fn process_txs_to_nonces(
         mut transactions: Vec<SignedTransaction>,
         expected_weight: u32,
     ) -> (Vec<u64>, TransactionPool) {
-        let mut pool = TransactionPool::new(TEST_SEED, None, "");
+        let mut pool =
+            TransactionPool::new(TEST_SEED, None, None, NonceReplacementPolicy::Disabled, "");
         let mut rng = thread_rng();
         transactions.shuffle(&mut rng);
         for tx in transactions {
@@ -387,6 +647,16 @@ mod tests {
         )
     }
 
+    /// Checks that `eviction_order` matches the groups in the pool.
+    fn assert_eviction_order(pool: &TransactionPool) {
+        let expected: BTreeSet<_> = pool
+            .transactions
+            .iter()
+            .map(|(key, group)| (group_priority(group.iter()), *key))
+            .collect();
+        assert_eq!(pool.eviction_order, expected);
+    }
+
     fn sort_pairs(a: &mut [u64]) {
         for c in a.chunks_exact_mut(2) {
             if c[0] > c[1] {
@@ -482,7 +752,8 @@ mod tests {
             })
             .collect::<Vec<_>>();
 
-        let mut pool = TransactionPool::new(TEST_SEED, None, "");
+        let mut pool =
+            TransactionPool::new(TEST_SEED, None, None, NonceReplacementPolicy::Disabled, "");
         let mut rng = thread_rng();
         transactions.shuffle(&mut rng);
         for tx in transactions.clone() {
@@ -494,6 +765,7 @@ mod tests {
         transactions.shuffle(&mut rng);
         let (txs_to_remove, txs_to_check) = transactions.split_at(transactions.len() / 2);
         pool.remove_transactions(txs_to_remove);
+        assert_eviction_order(&pool);
 
         assert_eq!(pool.len(), txs_to_check.len());
 
@@ -548,9 +820,11 @@ mod tests {
             ));
         }
         assert_eq!(pool.len(), 10);
+        assert_eviction_order(&pool);
         let txs = prepare_transactions(&mut pool, 10);
         assert_eq!(txs.len(), 10);
         assert_eq!(pool.len(), 0);
+        assert!(pool.eviction_order.is_empty());
         assert_eq!(pool.transaction_size(), 0);
     }
 
@@ -584,6 +858,7 @@ mod tests {
         assert_eq!(pool.len(), 10);
         let txs = prepare_transactions(&mut pool, 5);
         assert_eq!(txs.len(), 5);
+        assert_eviction_order(&pool);
         nonces.sort();
         let mut new_nonces = txs.iter().map(|tx| tx.transaction.nonce()).collect::<Vec<_>>();
         new_nonces.sort();
@@ -592,7 +867,8 @@ mod tests {
 
     #[test]
     fn test_transaction_pool_size() {
-        let mut pool = TransactionPool::new(TEST_SEED, None, "");
+        let mut pool =
+            TransactionPool::new(TEST_SEED, None, None, NonceReplacementPolicy::Disabled, "");
         let transactions = generate_transactions("alice.near", "alice.near", 1, 100);
         let mut total_transaction_size = 0;
         // Adding transactions increases the size.
@@ -616,7 +892,13 @@ mod tests {
         // Each transaction is at least 1 byte in size, so the last transaction will not fit.
         let pool_size_limit =
             transactions.iter().map(|tx| tx.get_size()).sum::<u64>().checked_sub(1).unwrap();
-        let mut pool = TransactionPool::new(TEST_SEED, Some(pool_size_limit), "");
+        let mut pool = TransactionPool::new(
+            TEST_SEED,
+            Some(pool_size_limit),
+            None,
+            NonceReplacementPolicy::Disabled,
+            "",
+        );
         for (i, tx) in transactions.iter().cloned().enumerate() {
             if i + 1 < transactions.len() {
                 assert_eq!(pool.insert_transaction(tx), InsertTransactionResult::Success);
@@ -625,4 +907,161 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_nonce_replacement() {
+        let original = transaction_with_priority_fee("alice.near", 1, 10, 1);
+        let cheaper = transaction_with_priority_fee("alice.near", 1, 5, 2);
+        let pricier = transaction_with_priority_fee("alice.near", 1, 20, 3);
+
+        // Transactions with the same nonce are kept side by side.
+        let mut pool =
+            TransactionPool::new(TEST_SEED, None, None, NonceReplacementPolicy::Disabled, "");
+        assert_eq!(pool.insert_transaction(original.clone()), InsertTransactionResult::Success);
+        assert_eq!(pool.insert_transaction(cheaper.clone()), InsertTransactionResult::Success);
+        assert_eq!(pool.len(), 2);
+
+        // The new transaction replaces the old one.
+        let mut pool =
+            TransactionPool::new(TEST_SEED, None, None, NonceReplacementPolicy::Always, "");
+        assert_eq!(pool.insert_transaction(original.clone()), InsertTransactionResult::Success);
+        assert_eq!(pool.insert_transaction(cheaper.clone()), InsertTransactionResult::Success);
+        assert_eq!(pool.len(), 1);
+        assert_eq!(pool.transaction_size(), cheaper.get_size());
+        assert_eq!(prepare_transactions(&mut pool, 10), vec![cheaper.clone()]);
+
+        // Only a transaction with a higher priority fee replaces the old one.
+        let mut pool = TransactionPool::new(
+            TEST_SEED,
+            None,
+            None,
+            NonceReplacementPolicy::HigherPriorityFee,
+            "",
+        );
+        assert_eq!(pool.insert_transaction(original.clone()), InsertTransactionResult::Success);
+        assert_eq!(pool.insert_transaction(cheaper), InsertTransactionResult::Underpriced);
+        assert_eq!(pool.insert_transaction(original), InsertTransactionResult::Duplicate);
+        assert_eq!(pool.insert_transaction(pricier.clone()), InsertTransactionResult::Success);
+        assert_eq!(pool.len(), 1);
+        assert_eq!(pool.transaction_size(), pricier.get_size());
+        assert_eviction_order(&pool);
+        assert_eq!(prepare_transactions(&mut pool, 10), vec![pricier]);
+
+        // Transactions without a priority fee can't outbid each other, so they are kept side by
+        // side.
+        let signer: Signer = InMemorySigner::from_seed(
+            "alice.near".parse().unwrap(),
+            KeyType::ED25519,
+            "alice.near",
+        )
+        .into();
+        let without_priority_fee = |deposit| {
+            SignedTransaction::send_money(
+                1,
+                "alice.near".parse().unwrap(),
+                "bob.near".parse().unwrap(),
+                &signer,
+                deposit,
+                CryptoHash::default(),
+            )
+        };
+        let mut pool = TransactionPool::new(
+            TEST_SEED,
+            None,
+            None,
+            NonceReplacementPolicy::HigherPriorityFee,
+            "",
+        );
+        assert_eq!(
+            pool.insert_transaction(without_priority_fee(1)),
+            InsertTransactionResult::Success
+        );
+        assert_eq!(
+            pool.insert_transaction(without_priority_fee(2)),
+            InsertTransactionResult::Success
+        );
+        assert_eq!(pool.len(), 2);
+    }
+
+    #[test]
+    fn test_max_transactions_per_key() {
+        let mut pool =
+            TransactionPool::new(TEST_SEED, None, Some(3), NonceReplacementPolicy::Disabled, "");
+        for tx in generate_transactions("alice.near", "alice.near", 2, 4) {
+            assert_eq!(pool.insert_transaction(tx), InsertTransactionResult::Success);
+        }
+        // A transaction with a higher nonce than all the others is rejected.
+        let higher = generate_transactions("alice.near", "alice.near", 5, 5);
+        assert_eq!(
+            pool.insert_transaction(higher[0].clone()),
+            InsertTransactionResult::KeyLimitReached
+        );
+        // A transaction with a lower nonce evicts the one with the highest nonce.
+        let lower = generate_transactions("alice.near", "alice.near", 1, 1);
+        assert_eq!(pool.insert_transaction(lower[0].clone()), InsertTransactionResult::Success);
+        assert_eq!(pool.len(), 3);
+        // Other keys are not affected.
+        for tx in generate_transactions("alice.near", "bob.near", 1, 3) {
+            assert_eq!(pool.insert_transaction(tx), InsertTransactionResult::Success);
+        }
+        assert_eq!(pool.len(), 6);
+
+        let mut nonces: Vec<u64> =
+            prepare_transactions(&mut pool, 10).iter().map(|tx| tx.transaction.nonce()).collect();
+        nonces.sort();
+        assert_eq!(nonces, vec![1, 1, 2, 2, 3, 3]);
+    }
+
+    #[test]
+    fn test_transaction_pool_size_limit_evicts_from_lowest_priority_groups() {
+        let flood = generate_transactions("alice.near", "alice.near", 1, 10);
+        let single = generate_transactions("bob.near", "bob.near", 1, 1);
+        let pool_size_limit = flood.iter().chain(&single).map(|tx| tx.get_size()).sum::<u64>();
+        let mut pool = TransactionPool::new(
+            TEST_SEED,
+            Some(pool_size_limit),
+            None,
+            NonceReplacementPolicy::Disabled,
+            "",
+        );
+        for tx in flood.iter().chain(&single).cloned() {
+            assert_eq!(pool.insert_transaction(tx), InsertTransactionResult::Success);
+        }
+
+        // Transactions without a priority fee don't evict others.
+        let new = generate_transactions("carol.near", "carol.near", 1, 1);
+        assert_eq!(pool.insert_transaction(new[0].clone()), InsertTransactionResult::NoSpaceLeft);
+        assert_eq!(pool.len(), 11);
+
+        // The group with most transactions has the lowest priority, so its transactions with the
+        // highest nonces are evicted to make room for a transaction which pays a priority fee.
+        let paying = transaction_with_priority_fee("dave.near", 1, 100, 1);
+        let num_evicted = paying.get_size().div_ceil(flood[0].get_size()) as usize;
+        assert_eq!(pool.insert_transaction(paying.clone()), InsertTransactionResult::Success);
+        assert_eq!(pool.len(), flood.len() - num_evicted + 2);
+        assert!(pool.transaction_size() <= pool_size_limit);
+        assert_eviction_order(&pool);
+        let mut nonces: Vec<u64> = prepare_transactions(&mut pool, 20)
+            .iter()
+            .filter(|tx| tx.transaction.signer_id().as_str() == "alice.near")
+            .map(|tx| tx.transaction.nonce())
+            .collect();
+        nonces.sort();
+        assert_eq!(nonces, (1..=(flood.len() - num_evicted) as u64).collect::<Vec<_>>());
+
+        // Groups which pay at least the same priority fee are not evicted.
+        let mut pool = TransactionPool::new(
+            TEST_SEED,
+            Some(paying.get_size()),
+            None,
+            NonceReplacementPolicy::Disabled,
+            "",
+        );
+        assert_eq!(pool.insert_transaction(paying.clone()), InsertTransactionResult::Success);
+        let same_fee = transaction_with_priority_fee("erin.near", 1, 100, 1);
+        assert_eq!(pool.insert_transaction(same_fee), InsertTransactionResult::NoSpaceLeft);
+        let higher_fee = transaction_with_priority_fee("erin.near", 1, 200, 1);
+        assert_eq!(pool.insert_transaction(higher_fee.clone()), InsertTransactionResult::Success);
+        assert_eq!(prepare_transactions(&mut pool, 10), vec![higher_fee]);
+    }
 }
@@ -332,7 +332,7 @@ pub(crate) async fn query_account(
 > {
     let query = near_client::Query::new(
         block_id,
-        near_primitives::views::QueryRequest::ViewAccount { account_id },
+        near_primitives::views::QueryRequest::ViewAccount { account_id, include_proof: false },
     );
     let account_info_response = match view_client_addr.send(query.with_span_context()).await? {
         Ok(query_response) => query_response,
@@ -400,7 +400,11 @@ pub(crate) async fn query_access_key(
 > {
     let access_key_query = near_client::Query::new(
         block_id,
-        near_primitives::views::QueryRequest::ViewAccessKey { account_id, public_key },
+        near_primitives::views::QueryRequest::ViewAccessKey {
+            account_id,
+            public_key,
+            include_proof: false,
+        },
     );
     let access_key_query_response =
         match view_client_addr.send(access_key_query.with_span_context()).await? {
@@ -22,6 +22,23 @@ pub enum LogSummaryStyle {
     Colored,
 }
 
+/// What the transaction pool does when it receives a transaction with the same
+/// signer, public key and nonce as a transaction it already holds.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum NonceReplacementPolicy {
+    /// Both transactions are kept, and the first one which is included in a
+    /// chunk invalidates the other.
+    #[default]
+    Disabled,
+    /// The new transaction replaces the old one.
+    Always,
+    /// The new transaction replaces the old one only if it pays a higher
+    /// priority fee, otherwise it's rejected. New transactions without a
+    /// priority fee, which are the only ones the chain accepts for now, are
+    /// handled as with `Disabled`.
+    HigherPriorityFee,
+}
+
 /// Minimum number of epochs for which we keep store data
 pub const MIN_GC_NUM_EPOCHS_TO_KEEP: u64 = 3;
 
@@ -101,6 +118,22 @@ pub struct ExternalStorageConfig {
     /// the network before it fetches from external storage.
     #[serde(default = "default_external_storage_fallback_threshold")]
     pub external_storage_fallback_threshold: u64,
+    /// Layout of the state parts in the external storage. Must match the
+    /// layout used by the node which dumps them.
+    #[serde(default)]
+    pub parts_layout: StatePartsLayout,
+}
+
+/// How state parts are laid out in the external storage.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StatePartsLayout {
+    /// Every part is stored under its epoch, shard and part id.
+    #[default]
+    Raw,
+    /// Every part is stored under the hash of its content, optionally
+    /// compressed, so that parts which don't change across epochs are stored
+    /// only once. The parts of an epoch and shard are listed in a manifest.
+    ContentAddressed,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
@@ -117,6 +150,14 @@ pub enum ExternalStorageLocation {
     GCS {
         bucket: String,
     },
+    /// Plain HTTP(S) servers, e.g. static file servers or CDNs, which serve
+    /// copies of the state dumps under the same paths. Files are downloaded
+    /// from the healthiest of the mirrors, and from the next one on failure.
+    /// Only supported for state sync, not for dumping state.
+    HTTP {
+        /// Base URLs of the mirrors.
+        mirrors: Vec<String>,
+    },
 }
 
 /// Configures how to dump state to external storage.
@@ -137,6 +178,13 @@ pub struct DumpConfig {
     /// Location of a json file with credentials allowing write access to the bucket.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub credentials_file: Option<PathBuf>,
+    /// Layout of the dumped state parts.
+    #[serde(default)]
+    pub parts_layout: StatePartsLayout,
+    /// Compresses the state parts with zstd at this level. Only supported
+    /// with the `ContentAddressed` layout.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zstd_compression_level: Option<i32>,
 }
 
 /// Configures how to fetch state parts during state sync.
@@ -174,6 +222,7 @@ impl StateSyncConfig {
                 num_concurrent_requests_during_catchup:
                     DEFAULT_STATE_SYNC_NUM_CONCURRENT_REQUESTS_ON_CATCHUP_EXTERNAL,
                 external_storage_fallback_threshold: DEFAULT_EXTERNAL_STORAGE_FALLBACK_THRESHOLD,
+                parts_layout: StatePartsLayout::Raw,
             }),
         }
     }
@@ -370,6 +419,14 @@ pub fn default_view_client_throttle_period() -> Duration {
     Duration::seconds(30)
 }
 
+pub fn default_simulation_threads() -> usize {
+    2
+}
+
+pub fn default_simulation_time_limit() -> Duration {
+    Duration::milliseconds(250)
+}
+
 pub fn default_trie_viewer_state_size_limit() -> Option<u64> {
     Some(50_000)
 }
@@ -378,6 +435,10 @@ pub fn default_transaction_pool_size_limit() -> Option<u64> {
     Some(100_000_000) // 100 MB.
 }
 
+pub fn default_transaction_pool_max_transactions_per_key() -> Option<usize> {
+    None
+}
+
 pub fn default_tx_routing_height_horizon() -> BlockHeightDelta {
     4
 }
@@ -514,6 +575,11 @@ pub struct ClientConfig {
     pub view_client_threads: usize,
     /// Number of seconds between state requests for view client.
     pub view_client_throttle_period: Duration,
+    /// Number of threads simulating transactions for `EXPERIMENTAL_simulate_tx`.
+    /// Simulations run in their own pool so that they don't block the view client.
+    pub simulation_threads: usize,
+    /// Wall-clock time limit of a single transaction simulation.
+    pub simulation_time_limit: Duration,
     /// Upper bound of the byte size of contract state that is still viewable. None is no limit
     pub trie_viewer_state_size_limit: Option<u64>,
     /// Max burnt gas per view method.  If present, overrides value stored in
@@ -534,6 +600,12 @@ pub struct ClientConfig {
     /// Limit of the size of per-shard transaction pool measured in bytes. If not set, the size
     /// will be unbounded.
     pub transaction_pool_size_limit: Option<u64>,
+    /// Limit of the number of transactions of a (signer, public key) pair in the per-shard
+    /// transaction pool. If not set, the number will be unbounded.
+    pub transaction_pool_max_transactions_per_key: Option<usize>,
+    /// What the transaction pool does with a transaction with the same nonce as a transaction
+    /// it already holds.
+    pub transaction_pool_nonce_replacement: NonceReplacementPolicy,
     // Allows more detailed logging, for example a list of orphaned blocks.
     pub enable_multiline_logging: bool,
     // Configuration for resharding.
@@ -633,6 +705,8 @@ impl ClientConfig {
             log_summary_style: LogSummaryStyle::Colored,
             view_client_threads: 1,
             view_client_throttle_period: Duration::seconds(1),
+            simulation_threads: 1,
+            simulation_time_limit: default_simulation_time_limit(),
             trie_viewer_state_size_limit: None,
             max_gas_burnt_view: None,
             enable_statistics_export: true,
@@ -641,6 +715,8 @@ impl ClientConfig {
             state_sync: StateSyncConfig::default(),
             epoch_sync: EpochSyncConfig::default(),
             transaction_pool_size_limit: None,
+            transaction_pool_max_transactions_per_key: None,
+            transaction_pool_nonce_replacement: NonceReplacementPolicy::Disabled,
             enable_multiline_logging: false,
             resharding_config: MutableConfigValue::new(
                 ReshardingConfig::default(),
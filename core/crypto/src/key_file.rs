@@ -1,4 +1,6 @@
 use crate::{PublicKey, SecretKey};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::ChaCha20Poly1305;
 use near_account_id::AccountId;
 use std::fs::File;
 use std::io;
@@ -18,33 +20,233 @@ pub struct KeyFile {
 
 impl KeyFile {
     pub fn write_to_file(&self, path: &Path) -> io::Result<()> {
-        let data = serde_json::to_string_pretty(self)?;
-        let mut file = Self::create(path)?;
-        file.write_all(data.as_bytes())
+        write_json_to_file(self, path)
     }
 
-    #[cfg(unix)]
-    fn create(path: &Path) -> io::Result<File> {
-        use std::os::unix::fs::OpenOptionsExt;
-        std::fs::File::options().mode(0o600).write(true).create(true).truncate(true).open(path)
+    pub fn from_file(path: &Path) -> io::Result<Self> {
+        read_json_from_file(path)
+    }
+
+    /// Encrypts the secret key with a key derived from `password` using the
+    /// default KDF parameters.
+    #[cfg(feature = "rand")]
+    pub fn encrypt(&self, password: &[u8]) -> io::Result<EncryptedKeyFile> {
+        self.encrypt_with_params(password, KdfParams::default())
+    }
+
+    /// Same as [`Self::encrypt`] but with given cost of the key derivation.
+    #[cfg(feature = "rand")]
+    pub fn encrypt_with_params(
+        &self,
+        password: &[u8],
+        kdf: KdfParams,
+    ) -> io::Result<EncryptedKeyFile> {
+        use secp256k1::rand::rngs::OsRng;
+        use secp256k1::rand::RngCore;
+
+        const SALT_LEN: usize = 16;
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+        let kdf =
+            KdfConfig { algorithm: KdfAlgorithm::Argon2id, salt: hex::encode(salt), params: kdf };
+        let cipher = ChaCha20Poly1305::new(&kdf.derive_key(password)?.into());
+        let aad = associated_data(&self.account_id, &self.public_key);
+        let ciphertext = cipher
+            .encrypt(
+                &nonce.into(),
+                Payload { msg: self.secret_key.to_string().as_bytes(), aad: aad.as_bytes() },
+            )
+            .map_err(|_| io::Error::other("failed to encrypt the secret key"))?;
+        Ok(EncryptedKeyFile {
+            account_id: self.account_id.clone(),
+            public_key: self.public_key.clone(),
+            encrypted_secret_key: EncryptedSecretKey {
+                kdf,
+                cipher: CipherAlgorithm::ChaCha20Poly1305,
+                nonce: hex::encode(nonce),
+                ciphertext: hex::encode(ciphertext),
+            },
+        })
+    }
+
+    /// Returns whether the file at `path` holds an [`EncryptedKeyFile`] rather
+    /// than a plaintext key file.
+    pub fn is_encrypted(path: &Path) -> io::Result<bool> {
+        let value: serde_json::Value = read_json_from_file(path)?;
+        Ok(value.get("encrypted_secret_key").is_some())
     }
+}
+
+/// Key file whose secret key is encrypted with a key derived from a password.
+///
+/// The account id and the public key are kept in plaintext, so that it's known
+/// whose key it is without the password, but they are authenticated together
+/// with the secret key and can't be changed without invalidating the file.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct EncryptedKeyFile {
+    pub account_id: AccountId,
+    pub public_key: PublicKey,
+    pub encrypted_secret_key: EncryptedSecretKey,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct EncryptedSecretKey {
+    pub kdf: KdfConfig,
+    pub cipher: CipherAlgorithm,
+    /// Hex encoded nonce of the cipher.
+    pub nonce: String,
+    /// Hex encoded secret key in its string form, encrypted and authenticated
+    /// with the cipher.
+    pub ciphertext: String,
+}
 
-    #[cfg(not(unix))]
-    fn create(path: &Path) -> io::Result<File> {
-        std::fs::File::create(path)
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum KdfAlgorithm {
+    Argon2id,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum CipherAlgorithm {
+    #[serde(rename = "chacha20-poly1305")]
+    ChaCha20Poly1305,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct KdfConfig {
+    pub algorithm: KdfAlgorithm,
+    /// Hex encoded salt.
+    pub salt: String,
+    #[serde(flatten)]
+    pub params: KdfParams,
+}
+
+/// Cost parameters of the key derivation.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KdfParams {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    /// Parameters recommended by RFC 9106 for memory constrained environments.
+    fn default() -> Self {
+        Self { memory_kib: 64 * 1024, iterations: 3, parallelism: 4 }
+    }
+}
+
+const NONCE_LEN: usize = 12;
+const DERIVED_KEY_LEN: usize = 32;
+
+impl KdfConfig {
+    fn derive_key(&self, password: &[u8]) -> io::Result<[u8; DERIVED_KEY_LEN]> {
+        let KdfAlgorithm::Argon2id = self.algorithm;
+        let salt = decode_hex(&self.salt, "salt")?;
+        let params = argon2::Params::new(
+            self.params.memory_kib,
+            self.params.iterations,
+            self.params.parallelism,
+            Some(DERIVED_KEY_LEN),
+        )
+        .map_err(|err| invalid_data(format!("invalid KDF parameters: {err}")))?;
+        let argon2 =
+            argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params);
+        let mut key = [0u8; DERIVED_KEY_LEN];
+        argon2
+            .hash_password_into(password, &salt, &mut key)
+            .map_err(|err| invalid_data(format!("failed to derive the key: {err}")))?;
+        Ok(key)
+    }
+}
+
+impl EncryptedKeyFile {
+    pub fn write_to_file(&self, path: &Path) -> io::Result<()> {
+        write_json_to_file(self, path)
     }
 
     pub fn from_file(path: &Path) -> io::Result<Self> {
-        let mut file = File::open(path)?;
-        let mut json_config_str = String::new();
-        file.read_to_string(&mut json_config_str)?;
-        let json_str_without_comments: String =
-            near_config_utils::strip_comments_from_json_str(&json_config_str)?;
+        read_json_from_file(path)
+    }
 
-        Ok(serde_json::from_str(&json_str_without_comments)?)
+    /// Decrypts the secret key. Fails with `io::ErrorKind::PermissionDenied`
+    /// if the password is wrong or the file was tampered with.
+    pub fn decrypt(&self, password: &[u8]) -> io::Result<KeyFile> {
+        let EncryptedSecretKey { kdf, cipher, nonce, ciphertext } = &self.encrypted_secret_key;
+        let CipherAlgorithm::ChaCha20Poly1305 = cipher;
+        let nonce: [u8; NONCE_LEN] = decode_hex(nonce, "nonce")?
+            .try_into()
+            .map_err(|_| invalid_data("invalid nonce length".to_string()))?;
+        let ciphertext = decode_hex(ciphertext, "ciphertext")?;
+        let cipher = ChaCha20Poly1305::new(&kdf.derive_key(password)?.into());
+        let aad = associated_data(&self.account_id, &self.public_key);
+        let secret_key = cipher
+            .decrypt(&nonce.into(), Payload { msg: &ciphertext, aad: aad.as_bytes() })
+            .map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    "wrong password or corrupted key file",
+                )
+            })?;
+        let secret_key: SecretKey = std::str::from_utf8(&secret_key)
+            .map_err(|err| invalid_data(err.to_string()))?
+            .parse()
+            .map_err(|err: crate::ParseKeyError| invalid_data(err.to_string()))?;
+        if secret_key.public_key() != self.public_key {
+            return Err(invalid_data("secret key doesn't match the public key".to_string()));
+        }
+        Ok(KeyFile {
+            account_id: self.account_id.clone(),
+            public_key: self.public_key.clone(),
+            secret_key,
+        })
     }
 }
 
+/// Data authenticated together with the secret key. Account ids can't contain
+/// `:`, so it's unambiguous.
+fn associated_data(account_id: &AccountId, public_key: &PublicKey) -> String {
+    format!("{account_id}:{public_key}")
+}
+
+fn decode_hex(value: &str, what: &str) -> io::Result<Vec<u8>> {
+    hex::decode(value).map_err(|err| invalid_data(format!("invalid {what}: {err}")))
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn write_json_to_file<T: serde::Serialize>(value: &T, path: &Path) -> io::Result<()> {
+    let data = serde_json::to_string_pretty(value)?;
+    let mut file = create(path)?;
+    file.write_all(data.as_bytes())
+}
+
+#[cfg(unix)]
+fn create(path: &Path) -> io::Result<File> {
+    use std::os::unix::fs::OpenOptionsExt;
+    std::fs::File::options().mode(0o600).write(true).create(true).truncate(true).open(path)
+}
+
+#[cfg(not(unix))]
+fn create(path: &Path) -> io::Result<File> {
+    std::fs::File::create(path)
+}
+
+fn read_json_from_file<T: serde::de::DeserializeOwned>(path: &Path) -> io::Result<T> {
+    let mut file = File::open(path)?;
+    let mut json_config_str = String::new();
+    file.read_to_string(&mut json_config_str)?;
+    let json_str_without_comments: String =
+        near_config_utils::strip_comments_from_json_str(&json_config_str)?;
+
+    Ok(serde_json::from_str(&json_str_without_comments)?)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -111,4 +313,45 @@ mod test {
         let inner_msg = err.into_inner().unwrap().to_string();
         assert!(inner_msg.contains("duplicate field"));
     }
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("key-file");
+        let params = KdfParams { memory_kib: 64, iterations: 1, parallelism: 1 };
+
+        let secret_key: SecretKey = SECRET_KEY.parse().unwrap();
+        let key = KeyFile {
+            account_id: ACCOUNT_ID.parse().unwrap(),
+            public_key: secret_key.public_key(),
+            secret_key: secret_key.clone(),
+        };
+        key.write_to_file(&path).unwrap();
+        assert!(!KeyFile::is_encrypted(&path).unwrap());
+
+        key.encrypt_with_params(b"password", params).unwrap().write_to_file(&path).unwrap();
+        assert!(KeyFile::is_encrypted(&path).unwrap());
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(!contents.contains(SECRET_KEY));
+        assert!(KeyFile::from_file(&path).is_err());
+
+        let encrypted = EncryptedKeyFile::from_file(&path).unwrap();
+        let decrypted = encrypted.decrypt(b"password").unwrap();
+        assert_eq!(decrypted.account_id, key.account_id);
+        assert_eq!(decrypted.public_key, key.public_key);
+        assert_eq!(decrypted.secret_key, secret_key);
+
+        let err = encrypted.decrypt(b"wrong password").map(drop).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+
+        // The account id is authenticated together with the secret key.
+        let mut tampered = encrypted.clone();
+        tampered.account_id = "attacker".parse().unwrap();
+        let err = tampered.decrypt(b"password").map(drop).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+
+        // Every encryption uses a new salt and nonce.
+        let encrypted_again = key.encrypt_with_params(b"password", params).unwrap();
+        assert_ne!(encrypted, encrypted_again);
+    }
 }
@@ -2,7 +2,10 @@
 #![deny(clippy::arithmetic_side_effects)]
 
 pub use errors::{ParseKeyError, ParseKeyTypeError, ParseSignatureError};
-pub use key_file::KeyFile;
+pub use key_file::{
+    CipherAlgorithm, EncryptedKeyFile, EncryptedSecretKey, KdfAlgorithm, KdfConfig, KdfParams,
+    KeyFile,
+};
 pub use signature::{
     ED25519PublicKey, ED25519SecretKey, KeyType, PublicKey, Secp256K1PublicKey, Secp256K1Signature,
     SecretKey, Signature,
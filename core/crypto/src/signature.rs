@@ -260,6 +260,17 @@ impl<'de> serde::Deserialize<'de> for PublicKey {
     }
 }
 
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for PublicKey {
+    fn schema_name() -> String {
+        "PublicKey".to_string()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        String::json_schema(gen)
+    }
+}
+
 impl FromStr for PublicKey {
     type Err = crate::errors::ParseKeyError;
 
@@ -700,6 +711,17 @@ impl<'de> serde::Deserialize<'de> for Signature {
     }
 }
 
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for Signature {
+    fn schema_name() -> String {
+        "Signature".to_string()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        String::json_schema(gen)
+    }
+}
+
 /// Helper struct which provides Display implementation for bytes slice
 /// encoding them using base58.
 // TODO(mina86): Get rid of it once bs58 has this feature.  There’s currently PR
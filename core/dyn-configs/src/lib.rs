@@ -8,6 +8,11 @@ use std::sync::Arc;
 use tokio::sync::broadcast::Sender;
 
 mod metrics;
+mod validator_key;
+
+#[cfg(unix)]
+pub use validator_key::{serve_validator_key_unlock_socket, unlock_validator_key};
+pub use validator_key::{UnlockedValidatorKey, VALIDATOR_KEY_UNLOCK_SOCKET};
 
 #[derive(Clone, Default)]
 /// Contains the latest state of configs which can be updated at runtime.
@@ -0,0 +1,129 @@
+use near_crypto::{EncryptedKeyFile, InMemorySigner};
+use near_primitives::validator_signer::{InMemoryValidatorSigner, ValidatorSigner};
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// Path of the unix socket, relative to the home dir, on which the password of
+/// an encrypted validator key is accepted. The socket is created in its own
+/// directory which only the user running the node can access.
+pub const VALIDATOR_KEY_UNLOCK_SOCKET: &str = "validator_key_unlock/unlock.sock";
+
+/// Maximum length of the password accepted on the unlock socket.
+const MAX_PASSWORD_LEN: u64 = 4096;
+
+/// Encrypted validator key unlocked while the node is running.
+///
+/// The decrypted signer is kept next to the encrypted key file it came from,
+/// so that config reloads keep using it as long as the key file doesn't
+/// change, and lock the key again once it's replaced.
+#[derive(Clone, Default)]
+pub struct UnlockedValidatorKey(Arc<Mutex<Option<(EncryptedKeyFile, Arc<ValidatorSigner>)>>>);
+
+impl UnlockedValidatorKey {
+    /// Returns the signer if `key_file` has been unlocked.
+    pub fn get(&self, key_file: &EncryptedKeyFile) -> Option<Arc<ValidatorSigner>> {
+        let unlocked = self.0.lock().unwrap();
+        let (unlocked_key_file, signer) = unlocked.as_ref()?;
+        (unlocked_key_file == key_file).then(|| signer.clone())
+    }
+
+    /// Decrypts the key file at `validator_file` with `password` and keeps the
+    /// signer for the following config reloads.
+    pub fn unlock(&self, validator_file: &Path, password: &[u8]) -> io::Result<()> {
+        let key_file = EncryptedKeyFile::from_file(validator_file)?;
+        let signer =
+            InMemoryValidatorSigner::from_signer(InMemorySigner::from(key_file.decrypt(password)?));
+        *self.0.lock().unwrap() = Some((key_file, Arc::new(signer.into())));
+        Ok(())
+    }
+}
+
+/// Accepts passwords of the encrypted validator key on the unix socket at
+/// `socket_path` until the task is dropped.
+///
+/// A client writes the password, shuts down its writing half and gets back
+/// `OK` or the error. Every successful unlock is signalled on `tx_unlocked`,
+/// after which the configs should be reloaded to start using the key.
+#[cfg(unix)]
+pub async fn serve_validator_key_unlock_socket(
+    socket_path: std::path::PathBuf,
+    validator_file: std::path::PathBuf,
+    unlocked: UnlockedValidatorKey,
+    tx_unlocked: tokio::sync::mpsc::Sender<()>,
+) -> io::Result<()> {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    // The socket is only accessible to the user once its permissions are
+    // restricted after binding, so nobody else may be able to reach it before
+    // that through its directory.
+    if let Some(socket_dir) = socket_path.parent() {
+        match std::fs::DirBuilder::new().mode(0o700).create(socket_dir) {
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                std::fs::set_permissions(socket_dir, std::fs::Permissions::from_mode(0o700))?;
+            }
+            result => result?,
+        }
+    }
+    // Left behind by a previous run.
+    match std::fs::remove_file(&socket_path) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+        _ => {}
+    }
+    let listener = tokio::net::UnixListener::bind(&socket_path)?;
+    std::fs::set_permissions(&socket_path, std::fs::Permissions::from_mode(0o600))?;
+    tracing::info!(target: "neard", socket = %socket_path.display(), "Validator key is encrypted, waiting for the password on the unlock socket.");
+
+    loop {
+        let (mut stream, _) = listener.accept().await?;
+        let mut password = Vec::new();
+        let result = match (&mut stream).take(MAX_PASSWORD_LEN).read_to_end(&mut password).await {
+            Ok(_) => {
+                let validator_file = validator_file.clone();
+                let unlocked = unlocked.clone();
+                // The key derivation takes a while on purpose.
+                tokio::task::spawn_blocking(move || unlocked.unlock(&validator_file, &password))
+                    .await
+                    .unwrap_or_else(|err| Err(io::Error::other(err)))
+            }
+            Err(err) => Err(err),
+        };
+        let response = match &result {
+            Ok(()) => {
+                tracing::info!(target: "neard", "Unlocked the validator key.");
+                "OK\n".to_string()
+            }
+            Err(err) => {
+                tracing::warn!(target: "neard", ?err, "Failed to unlock the validator key.");
+                format!("{err}\n")
+            }
+        };
+        if let Err(err) = stream.write_all(response.as_bytes()).await {
+            tracing::debug!(target: "neard", ?err, "Failed to respond on the unlock socket.");
+        }
+        if result.is_ok() {
+            let _ = tx_unlocked.send(()).await;
+        }
+    }
+}
+
+/// Sends `password` to the unlock socket of a running node and waits until
+/// the validator key is unlocked.
+#[cfg(unix)]
+pub fn unlock_validator_key(socket_path: &Path, password: &[u8]) -> anyhow::Result<()> {
+    use anyhow::Context;
+    use std::io::{Read, Write};
+
+    let mut stream = std::os::unix::net::UnixStream::connect(socket_path).with_context(|| {
+        format!("failed to connect to {}, is the node running?", socket_path.display())
+    })?;
+    stream.write_all(password)?;
+    stream.shutdown(std::net::Shutdown::Write)?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    match response.trim_end() {
+        "OK" => Ok(()),
+        err => anyhow::bail!("failed to unlock the validator key: {err}"),
+    }
+}
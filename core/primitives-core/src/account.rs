@@ -399,6 +399,7 @@ impl BorshSerialize for Account {
     serde::Deserialize,
     ProtocolSchema,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct AccessKey {
     /// Nonce for this access key, used for tx nonce generation. When access key is created, nonce
     /// is set to `(block_height - 1) * 1e6` to avoid tx hash collision on access key re-creation.
@@ -430,6 +431,7 @@ impl AccessKey {
     serde::Deserialize,
     ProtocolSchema,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum AccessKeyPermission {
     FunctionCall(FunctionCallPermission),
 
@@ -454,6 +456,7 @@ pub enum AccessKeyPermission {
     Debug,
     ProtocolSchema,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct FunctionCallPermission {
     /// Allowance is a balance limit to use by this access key to pay for function call gas and
     /// transaction fees. When this access key is used, both account balance and the allowance is
@@ -462,6 +465,7 @@ pub struct FunctionCallPermission {
     /// NOTE: To change or increase the allowance, the old access key needs to be deleted and a new
     /// access key should be created.
     #[serde(with = "dec_format")]
+    #[cfg_attr(feature = "schemars", schemars(with = "Option<String>"))]
     pub allowance: Option<Balance>,
 
     // This isn't an AccountId because already existing records in testnet genesis have invalid
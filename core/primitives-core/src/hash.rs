@@ -162,6 +162,17 @@ impl<'de> serde::Deserialize<'de> for CryptoHash {
     }
 }
 
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for CryptoHash {
+    fn schema_name() -> String {
+        "CryptoHash".to_string()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        String::json_schema(gen)
+    }
+}
+
 impl std::str::FromStr for CryptoHash {
     type Err = Box<dyn std::error::Error + Send + Sync>;
 
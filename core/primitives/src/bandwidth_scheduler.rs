@@ -26,6 +26,7 @@ pub type Bandwidth = u64;
     Eq,
     ProtocolSchema,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum BandwidthRequests {
     V1(BandwidthRequestsV1),
 }
@@ -58,6 +59,7 @@ impl BandwidthRequests {
     Eq,
     ProtocolSchema,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct BandwidthRequestsV1 {
     pub requests: Vec<BandwidthRequest>,
 }
@@ -76,6 +78,7 @@ pub struct BandwidthRequestsV1 {
     Eq,
     ProtocolSchema,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct BandwidthRequest {
     /// Requesting bandwidth to this shard.
     pub to_shard: u16,
@@ -230,6 +233,7 @@ impl BandwidthRequestValues {
     Eq,
     ProtocolSchema,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct BandwidthRequestBitmap {
     pub data: [u8; BANDWIDTH_REQUEST_BITMAP_SIZE],
 }
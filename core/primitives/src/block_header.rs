@@ -345,11 +345,11 @@ impl Approval {
         parent_height: BlockHeight,
         target_height: BlockHeight,
         signer: &ValidatorSigner,
-    ) -> Self {
+    ) -> std::io::Result<Self> {
         let inner = ApprovalInner::new(&parent_hash, parent_height, target_height);
 
-        let signature = signer.sign_bytes(&Approval::get_data_for_sig(&inner, target_height));
-        Approval { inner, target_height, signature, account_id: signer.validator_id().clone() }
+        let signature = signer.sign_bytes(&Approval::get_data_for_sig(&inner, target_height))?;
+        Ok(Approval { inner, target_height, signature, account_id: signer.validator_id().clone() })
     }
 
     pub fn get_data_for_sig(inner: &ApprovalInner, target_height: BlockHeight) -> Vec<u8> {
@@ -571,7 +571,7 @@ impl BlockHeader {
         combine_hash(&hash_inner, &prev_hash)
     }
 
-    /// Creates BlockHeader for a newly produced block.
+    /// Creates BlockHeader for a newly produced block. Fails if the signer fails to sign it.
     pub fn new(
         this_epoch_protocol_version: ProtocolVersion,
         next_epoch_protocol_version: ProtocolVersion,
@@ -604,7 +604,7 @@ impl BlockHeader {
         block_merkle_root: CryptoHash,
         prev_height: BlockHeight,
         chunk_endorsements: Option<ChunkEndorsementsBitmap>,
-    ) -> Self {
+    ) -> std::io::Result<Self> {
         Self::new_impl(
             this_epoch_protocol_version,
             next_epoch_protocol_version,
@@ -705,7 +705,8 @@ impl BlockHeader {
             block_merkle_root,
             prev_height,
             chunk_endorsements,
-        );
+        )
+        .expect("using an existing signature can't fail");
         // Note: We do not panic but only log if the hash of the created header does not match the expected hash (From the view)
         // because there are tests that check if we can downgrade a BlockHeader's view a previous version, in which case the hash
         // of the header changes.
@@ -748,7 +749,7 @@ impl BlockHeader {
         block_merkle_root: CryptoHash,
         prev_height: BlockHeight,
         chunk_endorsements: Option<ChunkEndorsementsBitmap>,
-    ) -> Self {
+    ) -> std::io::Result<Self> {
         let inner_lite = BlockHeaderInnerLite {
             height,
             epoch_id,
@@ -786,14 +787,14 @@ impl BlockHeader {
                 chunk_endorsements,
             };
             let (hash, signature) =
-                Self::compute_hash_and_sign(signature_source, prev_hash, &inner_lite, &inner_rest);
-            Self::BlockHeaderV5(Arc::new(BlockHeaderV5 {
+                Self::compute_hash_and_sign(signature_source, prev_hash, &inner_lite, &inner_rest)?;
+            Ok(Self::BlockHeaderV5(Arc::new(BlockHeaderV5 {
                 prev_hash,
                 inner_lite,
                 inner_rest,
                 signature,
                 hash,
-            }))
+            })))
         } else if ProtocolFeature::BlockHeaderV4.enabled(this_epoch_protocol_version) {
             let inner_rest = BlockHeaderInnerRestV4 {
                 block_body_hash,
@@ -816,14 +817,14 @@ impl BlockHeader {
                 latest_protocol_version,
             };
             let (hash, signature) =
-                Self::compute_hash_and_sign(signature_source, prev_hash, &inner_lite, &inner_rest);
-            Self::BlockHeaderV4(Arc::new(BlockHeaderV4 {
+                Self::compute_hash_and_sign(signature_source, prev_hash, &inner_lite, &inner_rest)?;
+            Ok(Self::BlockHeaderV4(Arc::new(BlockHeaderV4 {
                 prev_hash,
                 inner_lite,
                 inner_rest,
                 signature,
                 hash,
-            }))
+            })))
         } else {
             // Build BlockHeaderV1-V3.
             Self::old_impl(
@@ -880,7 +881,7 @@ impl BlockHeader {
         epoch_sync_data_hash: Option<CryptoHash>,
         approvals: Vec<Option<Box<Signature>>>,
         prev_height: BlockHeight,
-    ) -> Self {
+    ) -> std::io::Result<Self> {
         let last_header_v2_version = ProtocolFeature::BlockHeaderV3.protocol_version() - 1;
         // Previously we passed next_epoch_protocol_version here, which is incorrect, but we need
         // to preserve this for archival nodes
@@ -907,14 +908,14 @@ impl BlockHeader {
                 latest_protocol_version,
             };
             let (hash, signature) =
-                Self::compute_hash_and_sign(signature_source, prev_hash, &inner_lite, &inner_rest);
-            Self::BlockHeaderV1(Arc::new(BlockHeaderV1 {
+                Self::compute_hash_and_sign(signature_source, prev_hash, &inner_lite, &inner_rest)?;
+            Ok(Self::BlockHeaderV1(Arc::new(BlockHeaderV1 {
                 prev_hash,
                 inner_lite,
                 inner_rest,
                 signature,
                 hash,
-            }))
+            })))
         } else if this_epoch_protocol_version <= last_header_v2_version {
             let inner_rest = BlockHeaderInnerRestV2 {
                 prev_chunk_outgoing_receipts_root,
@@ -936,14 +937,14 @@ impl BlockHeader {
                 latest_protocol_version,
             };
             let (hash, signature) =
-                Self::compute_hash_and_sign(signature_source, prev_hash, &inner_lite, &inner_rest);
-            Self::BlockHeaderV2(Arc::new(BlockHeaderV2 {
+                Self::compute_hash_and_sign(signature_source, prev_hash, &inner_lite, &inner_rest)?;
+            Ok(Self::BlockHeaderV2(Arc::new(BlockHeaderV2 {
                 prev_hash,
                 inner_lite,
                 inner_rest,
                 signature,
                 hash,
-            }))
+            })))
         } else {
             let inner_rest = BlockHeaderInnerRestV3 {
                 prev_chunk_outgoing_receipts_root,
@@ -965,14 +966,14 @@ impl BlockHeader {
                 latest_protocol_version,
             };
             let (hash, signature) =
-                Self::compute_hash_and_sign(signature_source, prev_hash, &inner_lite, &inner_rest);
-            Self::BlockHeaderV3(Arc::new(BlockHeaderV3 {
+                Self::compute_hash_and_sign(signature_source, prev_hash, &inner_lite, &inner_rest)?;
+            Ok(Self::BlockHeaderV3(Arc::new(BlockHeaderV3 {
                 prev_hash,
                 inner_lite,
                 inner_rest,
                 signature,
                 hash,
-            }))
+            })))
         }
     }
 
@@ -985,7 +986,7 @@ impl BlockHeader {
         prev_hash: CryptoHash,
         inner_lite: &BlockHeaderInnerLite,
         inner_rest: &T,
-    ) -> (CryptoHash, Signature)
+    ) -> std::io::Result<(CryptoHash, Signature)>
     where
         T: BorshSerialize + ?Sized,
     {
@@ -995,8 +996,8 @@ impl BlockHeader {
             &borsh::to_vec(&inner_rest).expect("Failed to serialize"),
         );
         match signature_source {
-            SignatureSource::Signer(signer) => (hash, signer.sign_bytes(hash.as_ref())),
-            SignatureSource::Signature(signature) => (hash, signature),
+            SignatureSource::Signer(signer) => Ok((hash, signer.sign_bytes(hash.as_ref())?)),
+            SignatureSource::Signature(signature) => Ok((hash, signature)),
         }
     }
 
@@ -1049,6 +1050,7 @@ impl BlockHeader {
             0,                     // prev_height
             Some(ChunkEndorsementsBitmap::genesis()),
         )
+        .expect("using an existing signature can't fail")
     }
 
     #[inline]
@@ -123,10 +123,10 @@ impl Challenge {
         self.hash = CryptoHash::hash_borsh(&self.body);
     }
 
-    pub fn produce(body: ChallengeBody, signer: &ValidatorSigner) -> Self {
+    pub fn produce(body: ChallengeBody, signer: &ValidatorSigner) -> std::io::Result<Self> {
         let hash = CryptoHash::hash_borsh(&body);
-        let signature = signer.sign_bytes(hash.as_ref());
-        Self { body, account_id: signer.validator_id().clone(), signature, hash }
+        let signature = signer.sign_bytes(hash.as_ref())?;
+        Ok(Self { body, account_id: signer.validator_id().clone(), signature, hash })
     }
 }
 
@@ -143,6 +143,7 @@ pub type Challenges = Vec<Challenge>;
     serde::Deserialize,
     ProtocolSchema,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct SlashedValidator {
     pub account_id: AccountId,
     pub is_double_sign: bool,
@@ -21,6 +21,7 @@ use std::fmt::{Debug, Display};
     serde::Serialize,
     ProtocolSchema,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum TxExecutionError {
     /// An error happened during Action execution
     ActionError(ActionError),
@@ -89,6 +90,7 @@ impl std::error::Error for RuntimeError {}
     BorshDeserialize,
     ProtocolSchema,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum MissingTrieValueContext {
     /// Missing trie value when reading from TrieIterator.
     TrieIterator,
@@ -124,6 +126,7 @@ impl MissingTrieValueContext {
     BorshDeserialize,
     ProtocolSchema,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum StorageError {
     /// Key-value db internal failure
     StorageInternalError,
@@ -170,6 +173,7 @@ impl std::error::Error for StorageError {}
     serde::Serialize,
     ProtocolSchema,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum InvalidTxError {
     /// Happens if a wrong AccessKey used or AccessKey has not enough permissions
     InvalidAccessKeyError(InvalidAccessKeyError),
@@ -201,8 +205,10 @@ pub enum InvalidTxError {
     NotEnoughBalance {
         signer_id: AccountId,
         #[serde(with = "dec_format")]
+        #[cfg_attr(feature = "schemars", schemars(with = "String"))]
         balance: Balance,
         #[serde(with = "dec_format")]
+        #[cfg_attr(feature = "schemars", schemars(with = "String"))]
         cost: Balance,
     },
     /// Signer account doesn't have enough balance after transaction.
@@ -211,6 +217,7 @@ pub enum InvalidTxError {
         signer_id: AccountId,
         /// Required balance to cover the state.
         #[serde(with = "dec_format")]
+        #[cfg_attr(feature = "schemars", schemars(with = "String"))]
         amount: Balance,
     },
     /// An integer overflow occurred during transaction cost estimation.
@@ -236,6 +243,7 @@ pub enum InvalidTxError {
         /// The congested shard.
         shard_id: u32,
         /// A value between 0 (no congestion) and 1 (max congestion).
+        #[cfg_attr(feature = "schemars", schemars(with = "f64"))]
         congestion_level: ordered_float::NotNan<f64>,
     },
     /// The receiver shard of the transaction missed several chunks and rejects
@@ -267,6 +275,7 @@ impl std::error::Error for InvalidTxError {}
     serde::Serialize,
     ProtocolSchema,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum InvalidAccessKeyError {
     /// The access key identified by the `public_key` doesn't exist for the account
     AccessKeyNotFound { account_id: AccountId, public_key: Box<PublicKey> },
@@ -281,8 +290,10 @@ pub enum InvalidAccessKeyError {
         account_id: AccountId,
         public_key: Box<PublicKey>,
         #[serde(with = "dec_format")]
+        #[cfg_attr(feature = "schemars", schemars(with = "String"))]
         allowance: Balance,
         #[serde(with = "dec_format")]
+        #[cfg_attr(feature = "schemars", schemars(with = "String"))]
         cost: Balance,
     },
     /// Having a deposit with a function call action is not allowed with a function call access key.
@@ -301,6 +312,7 @@ pub enum InvalidAccessKeyError {
     serde::Deserialize,
     ProtocolSchema,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum ActionsValidationError {
     /// The delete action must be a final aciton in transaction
     DeleteActionMustBeFinal,
@@ -349,6 +361,7 @@ pub enum ActionsValidationError {
     serde::Deserialize,
     ProtocolSchema,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum ReceiptValidationError {
     /// The `predecessor_id` of a Receipt is not valid.
     InvalidPredecessorId { account_id: String },
@@ -494,6 +507,7 @@ impl std::error::Error for ActionsValidationError {}
     serde::Serialize,
     ProtocolSchema,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct ActionError {
     /// Index of the failed action in the transaction.
     /// Action index is not defined if ActionError.kind is `ActionErrorKind::LackBalanceForState`
@@ -515,6 +529,7 @@ impl std::error::Error for ActionError {}
     serde::Serialize,
     ProtocolSchema,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum ActionErrorKind {
     /// Happens when CreateAccount action tries to create an account with account_id which is already exists in the storage
     AccountAlreadyExists { account_id: AccountId },
@@ -544,6 +559,7 @@ pub enum ActionErrorKind {
         account_id: AccountId,
         /// Balance required to complete an action.
         #[serde(with = "dec_format")]
+        #[cfg_attr(feature = "schemars", schemars(with = "String"))]
         amount: Balance,
     },
     /// Account is not yet staked, but tries to unstake
@@ -552,17 +568,22 @@ pub enum ActionErrorKind {
     TriesToStake {
         account_id: AccountId,
         #[serde(with = "dec_format")]
+        #[cfg_attr(feature = "schemars", schemars(with = "String"))]
         stake: Balance,
         #[serde(with = "dec_format")]
+        #[cfg_attr(feature = "schemars", schemars(with = "String"))]
         locked: Balance,
         #[serde(with = "dec_format")]
+        #[cfg_attr(feature = "schemars", schemars(with = "String"))]
         balance: Balance,
     },
     InsufficientStake {
         account_id: AccountId,
         #[serde(with = "dec_format")]
+        #[cfg_attr(feature = "schemars", schemars(with = "String"))]
         stake: Balance,
         #[serde(with = "dec_format")]
+        #[cfg_attr(feature = "schemars", schemars(with = "String"))]
         minimum_stake: Balance,
     },
     /// An error occurred during a `FunctionCall` Action, parameter is debug message.
@@ -1049,6 +1070,7 @@ impl From<ShardLayoutError> for EpochError {
     serde::Serialize,
     ProtocolSchema,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 /// Error that can occur while preparing or executing Wasm smart-contract.
 pub enum PrepareError {
     /// Error happened while serializing the module.
@@ -1091,6 +1113,7 @@ pub enum PrepareError {
     strum::IntoStaticStr,
     ProtocolSchema,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum WasmTrap {
     /// An `unreachable` opcode was executed.
     Unreachable,
@@ -1124,6 +1147,7 @@ pub enum WasmTrap {
     strum::IntoStaticStr,
     ProtocolSchema,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum HostError {
     /// String encoding is bad UTF-16 sequence
     BadUTF16,
@@ -1207,6 +1231,7 @@ pub enum HostError {
     strum::IntoStaticStr,
     ProtocolSchema,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum MethodResolveError {
     MethodEmptyName,
     MethodNotFound,
@@ -1225,6 +1250,7 @@ pub enum MethodResolveError {
     strum::IntoStaticStr,
     ProtocolSchema,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum CompilationError {
     CodeDoesNotExist {
         account_id: AccountId,
@@ -1253,6 +1279,7 @@ pub enum CompilationError {
     serde::Deserialize,
     ProtocolSchema,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum FunctionCallError {
     /// Wasm compilation error
     CompilationError(CompilationError),
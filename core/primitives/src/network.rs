@@ -22,6 +22,7 @@ use std::sync::Arc;
     serde::Deserialize,
     ProtocolSchema,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct PeerId(Arc<PublicKey>);
 
 impl PeerId {
@@ -68,16 +69,29 @@ pub struct AnnounceAccount {
 }
 
 impl AnnounceAccount {
-    pub fn new(signer: &ValidatorSigner, peer_id: PeerId, epoch_id: EpochId) -> Self {
-        let signature = Self::sign(signer, &peer_id, &epoch_id);
-        Self { account_id: signer.validator_id().clone(), peer_id: peer_id, epoch_id, signature }
+    pub fn new(
+        signer: &ValidatorSigner,
+        peer_id: PeerId,
+        epoch_id: EpochId,
+    ) -> std::io::Result<Self> {
+        let signature = Self::sign(signer, &peer_id, &epoch_id)?;
+        Ok(Self {
+            account_id: signer.validator_id().clone(),
+            peer_id: peer_id,
+            epoch_id,
+            signature,
+        })
     }
 
     pub fn hash(&self) -> CryptoHash {
         Self::build_header_hash(&self.account_id, &self.peer_id, &self.epoch_id)
     }
 
-    fn sign(signer: &ValidatorSigner, peer_id: &PeerId, epoch_id: &EpochId) -> Signature {
+    fn sign(
+        signer: &ValidatorSigner,
+        peer_id: &PeerId,
+        epoch_id: &EpochId,
+    ) -> std::io::Result<Signature> {
         let hash = Self::build_header_hash(signer.validator_id(), peer_id, epoch_id);
         signer.sign_bytes(hash.as_ref())
     }
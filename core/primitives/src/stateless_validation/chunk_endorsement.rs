@@ -24,7 +24,7 @@ impl ChunkEndorsement {
         epoch_id: EpochId,
         chunk_header: &ShardChunkHeader,
         signer: &ValidatorSigner,
-    ) -> ChunkEndorsement {
+    ) -> std::io::Result<ChunkEndorsement> {
         let inner = ChunkEndorsementInner::new(chunk_header.chunk_hash());
         let metadata = ChunkEndorsementMetadata {
             account_id: signer.validator_id().clone(),
@@ -32,10 +32,10 @@ impl ChunkEndorsement {
             epoch_id,
             height_created: chunk_header.height_created(),
         };
-        let signature = signer.sign_bytes(&borsh::to_vec(&inner).unwrap());
-        let metadata_signature = signer.sign_bytes(&borsh::to_vec(&metadata).unwrap());
+        let signature = signer.sign_bytes(&borsh::to_vec(&inner).unwrap())?;
+        let metadata_signature = signer.sign_bytes(&borsh::to_vec(&metadata).unwrap())?;
         let endorsement = ChunkEndorsementV2 { inner, signature, metadata, metadata_signature };
-        ChunkEndorsement::V2(endorsement)
+        Ok(ChunkEndorsement::V2(endorsement))
     }
 
     pub fn chunk_production_key(&self) -> ChunkProductionKey {
@@ -47,7 +47,7 @@ impl PartialEncodedStateWitness {
         part: Vec<u8>,
         encoded_length: usize,
         signer: &ValidatorSigner,
-    ) -> Self {
+    ) -> std::io::Result<Self> {
         let inner = PartialEncodedStateWitnessInner::new(
             epoch_id,
             chunk_header,
@@ -55,8 +55,8 @@ impl PartialEncodedStateWitness {
             part,
             encoded_length,
         );
-        let signature = signer.sign_bytes(&borsh::to_vec(&inner).unwrap());
-        Self { inner, signature }
+        let signature = signer.sign_bytes(&borsh::to_vec(&inner).unwrap())?;
+        Ok(Self { inner, signature })
     }
 
     pub fn chunk_production_key(&self) -> ChunkProductionKey {
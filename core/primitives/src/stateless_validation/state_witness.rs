@@ -203,25 +203,28 @@ impl ChunkStateWitness {
             .enabled(PROTOCOL_VERSION)
             .then_some(CongestionInfo::default());
 
-        let header = ShardChunkHeader::V3(ShardChunkHeaderV3::new(
-            PROTOCOL_VERSION,
-            prev_block_hash,
-            Default::default(),
-            Default::default(),
-            Default::default(),
-            Default::default(),
-            height,
-            shard_id,
-            Default::default(),
-            Default::default(),
-            Default::default(),
-            Default::default(),
-            Default::default(),
-            Default::default(),
-            congestion_info,
-            BandwidthRequests::default_for_protocol_version(PROTOCOL_VERSION),
-            &EmptyValidatorSigner::default().into(),
-        ));
+        let header = ShardChunkHeader::V3(
+            ShardChunkHeaderV3::new(
+                PROTOCOL_VERSION,
+                prev_block_hash,
+                Default::default(),
+                Default::default(),
+                Default::default(),
+                Default::default(),
+                height,
+                shard_id,
+                Default::default(),
+                Default::default(),
+                Default::default(),
+                Default::default(),
+                Default::default(),
+                Default::default(),
+                congestion_info,
+                BandwidthRequests::default_for_protocol_version(PROTOCOL_VERSION),
+                &EmptyValidatorSigner::default().into(),
+            )
+            .unwrap(),
+        );
         Self::new(
             "alice.near".parse().unwrap(),
             EpochId::default(),
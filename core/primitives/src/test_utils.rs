@@ -406,7 +406,7 @@ impl BlockHeader {
             &self.inner_lite_bytes(),
             &self.inner_rest_bytes(),
         );
-        let signature = signer.sign_bytes(hash.as_ref());
+        let signature = signer.sign_bytes(hash.as_ref()).unwrap();
         match self {
             BlockHeader::BlockHeaderV1(header) => {
                 let header = Arc::make_mut(header);
@@ -868,6 +868,7 @@ impl TestBlockBuilder {
             self.clock,
             None,
         )
+        .unwrap()
     }
 }
 
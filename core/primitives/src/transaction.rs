@@ -293,6 +293,18 @@ impl<'de> serde::Deserialize<'de> for SignedTransaction {
     }
 }
 
+/// Serialized as a base64 encoded string of the Borsh representation.
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for SignedTransaction {
+    fn schema_name() -> String {
+        "SignedTransaction".to_string()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        String::json_schema(gen)
+    }
+}
+
 /// The status of execution for a transaction or a receipt.
 #[derive(BorshSerialize, BorshDeserialize, PartialEq, Eq, Clone, Default, ProtocolSchema)]
 pub enum ExecutionStatus {
@@ -26,6 +26,7 @@ pub type StateRoot = CryptoHash;
 #[derive(
     serde::Serialize, serde::Deserialize, Default, Clone, Debug, PartialEq, Eq, arbitrary::Arbitrary,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum Finality {
     #[serde(rename = "optimistic")]
     None,
@@ -37,6 +38,7 @@ pub enum Finality {
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct AccountWithPublicKey {
     pub account_id: AccountId,
     pub public_key: PublicKey,
@@ -69,8 +71,13 @@ pub struct AccountInfo {
     BorshSerialize,
     BorshDeserialize,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(transparent)]
-pub struct StoreKey(#[serde_as(as = "Base64")] Vec<u8>);
+pub struct StoreKey(
+    #[serde_as(as = "Base64")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
+    Vec<u8>,
+);
 
 /// This type is used to mark values returned from store (arrays of bytes).
 ///
@@ -90,8 +97,13 @@ pub struct StoreKey(#[serde_as(as = "Base64")] Vec<u8>);
     BorshSerialize,
     BorshDeserialize,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(transparent)]
-pub struct StoreValue(#[serde_as(as = "Base64")] Vec<u8>);
+pub struct StoreValue(
+    #[serde_as(as = "Base64")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
+    Vec<u8>,
+);
 
 /// This type is used to mark function arguments.
 ///
@@ -112,8 +124,13 @@ pub struct StoreValue(#[serde_as(as = "Base64")] Vec<u8>);
     BorshSerialize,
     BorshDeserialize,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(transparent)]
-pub struct FunctionArgs(#[serde_as(as = "Base64")] Vec<u8>);
+pub struct FunctionArgs(
+    #[serde_as(as = "Base64")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
+    Vec<u8>,
+);
 
 /// A structure used to indicate the kind of state changes due to transaction/receipt processing, etc.
 #[derive(Debug, Clone)]
@@ -476,6 +493,7 @@ impl StateRootNode {
     arbitrary::Arbitrary,
     ProtocolSchema,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[as_ref(forward)]
 pub struct EpochId(pub CryptoHash);
 
@@ -989,6 +1007,7 @@ pub struct ChunkExtraV1 {
 #[derive(
     Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, arbitrary::Arbitrary,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(untagged)]
 pub enum BlockId {
     Height(BlockHeight),
@@ -1000,6 +1019,7 @@ pub type MaybeBlockId = Option<BlockId>;
 #[derive(
     Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, arbitrary::Arbitrary,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum SyncCheckpoint {
     Genesis,
@@ -1009,6 +1029,7 @@ pub enum SyncCheckpoint {
 #[derive(
     Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, arbitrary::Arbitrary,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum BlockReference {
     BlockId(BlockId),
@@ -1065,6 +1086,7 @@ pub struct BlockChunkValidatorStats {
 }
 
 #[derive(serde::Deserialize, Debug, arbitrary::Arbitrary, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum EpochReference {
     EpochId(EpochId),
@@ -1114,6 +1136,7 @@ pub enum ValidatorInfoIdentifier {
     Eq,
     ProtocolSchema,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum ValidatorKickoutReason {
     /// Slashed validators are kicked out.
     Slashed,
@@ -1126,8 +1149,10 @@ pub enum ValidatorKickoutReason {
     /// Validator stake is now below threshold
     NotEnoughStake {
         #[serde(with = "dec_format", rename = "stake_u128")]
+        #[cfg_attr(feature = "schemars", schemars(with = "String"))]
         stake: Balance,
         #[serde(with = "dec_format", rename = "threshold_u128")]
+        #[cfg_attr(feature = "schemars", schemars(with = "String"))]
         threshold: Balance,
     },
     /// Enough stake but is not chosen because of seat limits.
@@ -1137,6 +1162,7 @@ pub enum ValidatorKickoutReason {
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum TransactionOrReceiptId {
     Transaction { transaction_hash: CryptoHash, sender_id: AccountId },
@@ -6,6 +6,9 @@ use near_crypto::{InMemorySigner, KeyType, PublicKey, Signature, Signer};
 
 use crate::types::AccountId;
 
+#[cfg(feature = "remote_signer")]
+pub mod remote;
+
 /// Enum for validator signer, that holds validator id and key used for signing data.
 #[derive(Clone, Debug, PartialEq)]
 pub enum ValidatorSigner {
@@ -13,6 +16,9 @@ pub enum ValidatorSigner {
     Empty(EmptyValidatorSigner),
     /// Default validator signer that holds data in memory.
     InMemory(InMemoryValidatorSigner),
+    /// Validator signer that asks a separate signer process to sign.
+    #[cfg(feature = "remote_signer")]
+    Remote(remote::RemoteValidatorSigner),
 }
 
 /// Validator signer that is used to sign blocks and approvals.
@@ -22,6 +28,8 @@ impl ValidatorSigner {
         match self {
             ValidatorSigner::Empty(signer) => signer.validator_id(),
             ValidatorSigner::InMemory(signer) => signer.validator_id(),
+            #[cfg(feature = "remote_signer")]
+            ValidatorSigner::Remote(signer) => signer.validator_id(),
         }
     }
 
@@ -30,23 +38,31 @@ impl ValidatorSigner {
         match self {
             ValidatorSigner::Empty(signer) => signer.public_key(),
             ValidatorSigner::InMemory(signer) => signer.public_key(),
+            #[cfg(feature = "remote_signer")]
+            ValidatorSigner::Remote(signer) => signer.public_key(),
         }
     }
 
-    pub fn sign_bytes(&self, data: &[u8]) -> Signature {
+    /// Signs the data. Only fails for a remote signer which can't be reached
+    /// or refuses to sign.
+    pub fn sign_bytes(&self, data: &[u8]) -> std::io::Result<Signature> {
         match self {
-            ValidatorSigner::Empty(signer) => signer.noop_signature(),
-            ValidatorSigner::InMemory(signer) => signer.sign_bytes(data),
+            ValidatorSigner::Empty(signer) => Ok(signer.noop_signature()),
+            ValidatorSigner::InMemory(signer) => Ok(signer.sign_bytes(data)),
+            #[cfg(feature = "remote_signer")]
+            ValidatorSigner::Remote(signer) => signer.sign_bytes(data),
         }
     }
 
     pub fn compute_vrf_with_proof(
         &self,
         data: &[u8],
-    ) -> (near_crypto::vrf::Value, near_crypto::vrf::Proof) {
+    ) -> std::io::Result<(near_crypto::vrf::Value, near_crypto::vrf::Proof)> {
         match self {
             ValidatorSigner::Empty(_) => unimplemented!(),
-            ValidatorSigner::InMemory(signer) => signer.compute_vrf_with_proof(data),
+            ValidatorSigner::InMemory(signer) => Ok(signer.compute_vrf_with_proof(data)),
+            #[cfg(feature = "remote_signer")]
+            ValidatorSigner::Remote(signer) => signer.compute_vrf_with_proof(data),
         }
     }
 
@@ -55,6 +71,11 @@ impl ValidatorSigner {
         match self {
             ValidatorSigner::Empty(_) => unimplemented!(),
             ValidatorSigner::InMemory(signer) => signer.write_to_file(path),
+            #[cfg(feature = "remote_signer")]
+            ValidatorSigner::Remote(_) => Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "the key of a remote validator signer is kept by the signer process",
+            )),
         }
     }
 }
@@ -65,6 +86,13 @@ impl From<EmptyValidatorSigner> for ValidatorSigner {
     }
 }
 
+#[cfg(feature = "remote_signer")]
+impl From<remote::RemoteValidatorSigner> for ValidatorSigner {
+    fn from(signer: remote::RemoteValidatorSigner) -> Self {
+        ValidatorSigner::Remote(signer)
+    }
+}
+
 impl From<InMemoryValidatorSigner> for ValidatorSigner {
     fn from(signer: InMemoryValidatorSigner) -> Self {
         ValidatorSigner::InMemory(signer)
@@ -0,0 +1,569 @@
+//! Protocol between [`RemoteValidatorSigner`] and a signer process holding the
+//! validator key, e.g. on a separate hardened host.
+//!
+//! The connection starts with a handshake in which both sides prove that they
+//! hold their identity keys by signing the nonces chosen by the other side.
+//! After that every request and response is signed by the identity key of its
+//! sender together with the session id and a sequence number, so that messages
+//! can't be replayed or injected into the connection. The messages are borsh
+//! encoded and prefixed with their length.
+
+use crate::hash::{hash, CryptoHash};
+use crate::types::AccountId;
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_crypto::{PublicKey, Signature, Signer};
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+mod metrics;
+
+/// Version of the protocol, checked during the handshake.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Maximum size of a single message.
+const MAX_MESSAGE_SIZE: u32 = 16 * 1024 * 1024;
+
+/// Sent by the node when it connects.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct ClientHello {
+    pub version: u32,
+    /// Identity key of the node, the signer accepts only configured keys.
+    pub client_key: PublicKey,
+    pub client_nonce: [u8; 32],
+}
+
+/// Response of the signer to [`ClientHello`].
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct ServerHello {
+    pub version: u32,
+    pub account_id: AccountId,
+    pub validator_key: PublicKey,
+    pub server_nonce: [u8; 32],
+    /// Signature of [`handshake_hash`] with the identity key of the signer.
+    pub signature: Signature,
+}
+
+/// Completes the handshake.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct ClientAuth {
+    /// Signature of [`handshake_hash`] with the identity key of the node.
+    pub signature: Signature,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub enum SignerRequest {
+    SignBytes(Vec<u8>),
+    ComputeVrfWithProof(Vec<u8>),
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub enum SignerResponse {
+    Signature(Signature),
+    VrfWithProof(near_crypto::vrf::Value, near_crypto::vrf::Proof),
+    Error(String),
+}
+
+/// Side of the connection which sent a message.
+#[derive(BorshSerialize, Clone, Copy, Debug)]
+pub enum Side {
+    Client,
+    Server,
+}
+
+/// Message signed by its sender, see [`Envelope::sign`].
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct Envelope<T> {
+    pub seq: u64,
+    pub message: T,
+    pub signature: Signature,
+}
+
+impl<T: BorshSerialize> Envelope<T> {
+    pub fn sign(
+        side: Side,
+        session_id: &CryptoHash,
+        seq: u64,
+        message: T,
+        signer: &Signer,
+    ) -> Self {
+        let signature = signer.sign(envelope_hash(side, session_id, seq, &message).as_ref());
+        Self { seq, message, signature }
+    }
+
+    /// Checks that the message is the expected one in the session and was
+    /// signed by `key`.
+    pub fn verify(
+        &self,
+        side: Side,
+        session_id: &CryptoHash,
+        expected_seq: u64,
+        key: &PublicKey,
+    ) -> bool {
+        self.seq == expected_seq
+            && self
+                .signature
+                .verify(envelope_hash(side, session_id, self.seq, &self.message).as_ref(), key)
+    }
+}
+
+fn envelope_hash<T: BorshSerialize>(
+    side: Side,
+    session_id: &CryptoHash,
+    seq: u64,
+    message: &T,
+) -> CryptoHash {
+    CryptoHash::hash_borsh((b"near-remote-signer-message", side, session_id, seq, message))
+}
+
+/// Hash signed by the `side` of the connection during the handshake.
+pub fn handshake_hash(side: Side, client_nonce: &[u8; 32], server_nonce: &[u8; 32]) -> CryptoHash {
+    CryptoHash::hash_borsh((b"near-remote-signer-handshake", side, client_nonce, server_nonce))
+}
+
+/// Identifies the session in the signatures of the messages.
+pub fn session_id(client_nonce: &[u8; 32], server_nonce: &[u8; 32]) -> CryptoHash {
+    hash(&[client_nonce.as_slice(), server_nonce.as_slice()].concat())
+}
+
+pub fn write_message(stream: &mut impl Write, message: &impl BorshSerialize) -> io::Result<()> {
+    let data = borsh::to_vec(message)?;
+    let len = u32::try_from(data.len())
+        .ok()
+        .filter(|len| *len <= MAX_MESSAGE_SIZE)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "message too large"))?;
+    stream.write_all(&len.to_le_bytes())?;
+    stream.write_all(&data)?;
+    stream.flush()
+}
+
+pub fn read_message<T: BorshDeserialize>(stream: &mut impl Read) -> io::Result<T> {
+    let mut len = [0u8; 4];
+    stream.read_exact(&mut len)?;
+    let len = u32::from_le_bytes(len);
+    if len > MAX_MESSAGE_SIZE {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "message too large"));
+    }
+    let mut data = vec![0u8; len as usize];
+    stream.read_exact(&mut data)?;
+    T::try_from_slice(&data)
+}
+
+pub fn random_nonce() -> [u8; 32] {
+    use rand::RngCore;
+    let mut nonce = [0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut nonce);
+    nonce
+}
+
+/// Address of the signer, either `host:port` or `unix:<path>`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RemoteSignerAddr {
+    Tcp(String),
+    Unix(std::path::PathBuf),
+}
+
+impl FromStr for RemoteSignerAddr {
+    type Err = std::convert::Infallible;
+
+    fn from_str(addr: &str) -> Result<Self, Self::Err> {
+        Ok(match addr.strip_prefix("unix:") {
+            Some(path) => Self::Unix(path.into()),
+            None => Self::Tcp(addr.to_string()),
+        })
+    }
+}
+
+impl fmt::Display for RemoteSignerAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tcp(addr) => f.write_str(addr),
+            Self::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+/// Stream to the signer.
+pub enum RemoteSignerStream {
+    Tcp(std::net::TcpStream),
+    #[cfg(unix)]
+    Unix(std::os::unix::net::UnixStream),
+}
+
+impl RemoteSignerStream {
+    /// Connects to `addr`. Connecting over TCP fails after `connect_timeout`,
+    /// the timeouts of the reads and writes are left to the caller.
+    pub fn connect(addr: &RemoteSignerAddr, connect_timeout: Duration) -> io::Result<Self> {
+        let stream = match addr {
+            RemoteSignerAddr::Tcp(addr) => {
+                let mut last_err = None;
+                let mut stream = None;
+                for addr in std::net::ToSocketAddrs::to_socket_addrs(addr)? {
+                    match std::net::TcpStream::connect_timeout(&addr, connect_timeout) {
+                        Ok(tcp_stream) => {
+                            stream = Some(tcp_stream);
+                            break;
+                        }
+                        Err(err) => last_err = Some(err),
+                    }
+                }
+                let stream = stream.ok_or_else(|| {
+                    last_err.unwrap_or_else(|| {
+                        io::Error::new(io::ErrorKind::NotFound, "address not resolved")
+                    })
+                })?;
+                stream.set_nodelay(true)?;
+                Self::Tcp(stream)
+            }
+            #[cfg(unix)]
+            RemoteSignerAddr::Unix(path) => {
+                Self::Unix(std::os::unix::net::UnixStream::connect(path)?)
+            }
+            #[cfg(not(unix))]
+            RemoteSignerAddr::Unix(_) => {
+                return Err(io::Error::new(io::ErrorKind::Unsupported, "unix sockets unsupported"))
+            }
+        };
+        Ok(stream)
+    }
+
+    pub fn set_timeouts(&self, timeout: Duration) -> io::Result<()> {
+        match self {
+            Self::Tcp(stream) => {
+                stream.set_read_timeout(Some(timeout))?;
+                stream.set_write_timeout(Some(timeout))
+            }
+            #[cfg(unix)]
+            Self::Unix(stream) => {
+                stream.set_read_timeout(Some(timeout))?;
+                stream.set_write_timeout(Some(timeout))
+            }
+        }
+    }
+}
+
+impl Read for RemoteSignerStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Tcp(stream) => stream.read(buf),
+            #[cfg(unix)]
+            Self::Unix(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for RemoteSignerStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Tcp(stream) => stream.write(buf),
+            #[cfg(unix)]
+            Self::Unix(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Tcp(stream) => stream.flush(),
+            #[cfg(unix)]
+            Self::Unix(stream) => stream.flush(),
+        }
+    }
+}
+
+/// Stream whose reads and writes fail once the deadline has passed, so that
+/// a request takes at most until the deadline however it's split into reads.
+struct DeadlineStream<'a> {
+    stream: &'a mut RemoteSignerStream,
+    deadline: Instant,
+}
+
+impl DeadlineStream<'_> {
+    /// Limits the next read or write to the time left until the deadline.
+    fn set_timeouts(&mut self) -> io::Result<()> {
+        let timeout = self
+            .deadline
+            .checked_duration_since(Instant::now())
+            .filter(|timeout| !timeout.is_zero())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::TimedOut, "remote signer timed out"))?;
+        self.stream.set_timeouts(timeout)
+    }
+}
+
+impl Read for DeadlineStream<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.set_timeouts()?;
+        self.stream.read(buf)
+    }
+}
+
+impl Write for DeadlineStream<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.set_timeouts()?;
+        self.stream.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+/// Validator signer which sends the data to sign to a signer process, so that
+/// the validator key never enters the node.
+///
+/// Every request has to finish within the request timeout, including
+/// connecting to the signer. Connections are kept open between the requests,
+/// and concurrent requests use separate connections. If the signer can't be
+/// reached within the timeouts or refuses to sign, an error is returned.
+/// The requests are blocking, the calling thread waits for the response.
+#[derive(Clone)]
+pub struct RemoteValidatorSigner {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    account_id: AccountId,
+    public_key: PublicKey,
+    addr: RemoteSignerAddr,
+    signer_identity_key: PublicKey,
+    client_signer: Signer,
+    connect_timeout: Duration,
+    request_timeout: Duration,
+    /// Connections not used by any request at the moment. The lock is held
+    /// only to take or return a connection, never during a request.
+    idle_connections: Mutex<Vec<Connection>>,
+}
+
+struct Connection {
+    stream: RemoteSignerStream,
+    session_id: CryptoHash,
+    next_seq: u64,
+}
+
+impl RemoteValidatorSigner {
+    /// Creates the signer of `account_id` with `public_key`.
+    ///
+    /// The signer at `addr` has to prove it holds `signer_identity_key` and
+    /// the node identifies itself with `client_signer`.
+    pub fn new(
+        account_id: AccountId,
+        public_key: PublicKey,
+        addr: RemoteSignerAddr,
+        signer_identity_key: PublicKey,
+        client_signer: Signer,
+        connect_timeout: Duration,
+        request_timeout: Duration,
+    ) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                account_id,
+                public_key,
+                addr,
+                signer_identity_key,
+                client_signer,
+                connect_timeout,
+                request_timeout,
+                idle_connections: Mutex::new(vec![]),
+            }),
+        }
+    }
+
+    pub fn validator_id(&self) -> &AccountId {
+        &self.inner.account_id
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        self.inner.public_key.clone()
+    }
+
+    pub(super) fn sign_bytes(&self, data: &[u8]) -> io::Result<Signature> {
+        record_request("sign_bytes", || {
+            match self.request(SignerRequest::SignBytes(data.to_vec()))? {
+                SignerResponse::Signature(signature)
+                    if signature.verify(data, &self.inner.public_key) =>
+                {
+                    Ok(signature)
+                }
+                response => Err(unexpected_response(response)),
+            }
+        })
+    }
+
+    pub(super) fn compute_vrf_with_proof(
+        &self,
+        data: &[u8],
+    ) -> io::Result<(near_crypto::vrf::Value, near_crypto::vrf::Proof)> {
+        record_request("compute_vrf_with_proof", || {
+            match self.request(SignerRequest::ComputeVrfWithProof(data.to_vec()))? {
+                SignerResponse::VrfWithProof(value, proof)
+                    if self.is_vrf_valid(data, &value, &proof) =>
+                {
+                    Ok((value, proof))
+                }
+                response => Err(unexpected_response(response)),
+            }
+        })
+    }
+
+    fn is_vrf_valid(
+        &self,
+        data: &[u8],
+        value: &near_crypto::vrf::Value,
+        proof: &near_crypto::vrf::Proof,
+    ) -> bool {
+        near_crypto::key_conversion::convert_public_key(self.inner.public_key.unwrap_as_ed25519())
+            .is_some_and(|public_key| public_key.is_vrf_valid(&data, value, proof))
+    }
+
+    /// Sends the request on an idle connection, or on a new one if there is
+    /// no idle connection or it turns out to be broken. Fails if there is no
+    /// response within the request timeout.
+    fn request(&self, request: SignerRequest) -> io::Result<SignerResponse> {
+        let deadline = Instant::now() + self.inner.request_timeout;
+        let idle = self.inner.idle_connections.lock().unwrap().pop();
+        let (conn, response) = match idle {
+            Some(mut conn) => match conn.request(&request, &self.inner, deadline) {
+                Ok(response) => (conn, response),
+                Err(err) => {
+                    tracing::debug!(target: "remote_signer", ?err, "Reconnecting to the remote signer");
+                    self.request_on_new_connection(&request, deadline)?
+                }
+            },
+            None => self.request_on_new_connection(&request, deadline)?,
+        };
+        self.inner.idle_connections.lock().unwrap().push(conn);
+        Ok(response)
+    }
+
+    fn request_on_new_connection(
+        &self,
+        request: &SignerRequest,
+        deadline: Instant,
+    ) -> io::Result<(Connection, SignerResponse)> {
+        let mut conn = self.connect(deadline)?;
+        let response = conn.request(request, &self.inner, deadline)?;
+        Ok((conn, response))
+    }
+
+    fn connect(&self, deadline: Instant) -> io::Result<Connection> {
+        let inner = &self.inner;
+        let connect_timeout =
+            inner.connect_timeout.min(deadline.saturating_duration_since(Instant::now()));
+        let mut stream = RemoteSignerStream::connect(&inner.addr, connect_timeout)?;
+        let mut deadline_stream = DeadlineStream { stream: &mut stream, deadline };
+        let client_nonce = random_nonce();
+        write_message(
+            &mut deadline_stream,
+            &ClientHello {
+                version: PROTOCOL_VERSION,
+                client_key: inner.client_signer.public_key(),
+                client_nonce,
+            },
+        )?;
+        let hello: ServerHello = read_message(&mut deadline_stream)?;
+        if hello.version != PROTOCOL_VERSION {
+            return Err(io::Error::other(format!(
+                "remote signer uses protocol version {}, expected {PROTOCOL_VERSION}",
+                hello.version
+            )));
+        }
+        let server_hash = handshake_hash(Side::Server, &client_nonce, &hello.server_nonce);
+        if !hello.signature.verify(server_hash.as_ref(), &inner.signer_identity_key) {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "remote signer failed to prove its identity",
+            ));
+        }
+        if hello.account_id != inner.account_id || hello.validator_key != inner.public_key {
+            return Err(io::Error::other(format!(
+                "remote signer holds the key {} of {}, expected {} of {}",
+                hello.validator_key, hello.account_id, inner.public_key, inner.account_id
+            )));
+        }
+        let client_hash = handshake_hash(Side::Client, &client_nonce, &hello.server_nonce);
+        write_message(
+            &mut deadline_stream,
+            &ClientAuth { signature: inner.client_signer.sign(client_hash.as_ref()) },
+        )?;
+        tracing::info!(target: "remote_signer", addr = %inner.addr, "Connected to the remote signer");
+        Ok(Connection {
+            stream,
+            session_id: session_id(&client_nonce, &hello.server_nonce),
+            next_seq: 0,
+        })
+    }
+}
+
+/// Records the latency of the request and its failure, if any, in the metrics.
+fn record_request<T>(name: &str, request: impl FnOnce() -> io::Result<T>) -> io::Result<T> {
+    let _timer = metrics::REMOTE_SIGNER_REQUEST_LATENCY.with_label_values(&[name]).start_timer();
+    let result = request();
+    if let Err(err) = &result {
+        // Reads and writes past their timeout fail with `WouldBlock` on unix.
+        let reason = match err.kind() {
+            io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => "timeout",
+            _ => "error",
+        };
+        metrics::REMOTE_SIGNER_REQUEST_FAILURES.with_label_values(&[name, reason]).inc();
+    }
+    result
+}
+
+fn unexpected_response(response: SignerResponse) -> io::Error {
+    match response {
+        SignerResponse::Error(err) => {
+            io::Error::other(format!("the remote signer refused the request: {err}"))
+        }
+        SignerResponse::Signature(_) => {
+            io::Error::new(io::ErrorKind::InvalidData, "invalid signature from the remote signer")
+        }
+        SignerResponse::VrfWithProof(..) => {
+            io::Error::new(io::ErrorKind::InvalidData, "invalid VRF proof from the remote signer")
+        }
+    }
+}
+
+impl Connection {
+    fn request(
+        &mut self,
+        request: &SignerRequest,
+        inner: &Inner,
+        deadline: Instant,
+    ) -> io::Result<SignerResponse> {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        let mut stream = DeadlineStream { stream: &mut self.stream, deadline };
+        write_message(
+            &mut stream,
+            &Envelope::sign(Side::Client, &self.session_id, seq, request, &inner.client_signer),
+        )?;
+        let response: Envelope<SignerResponse> = read_message(&mut stream)?;
+        if !response.verify(Side::Server, &self.session_id, seq, &inner.signer_identity_key) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "invalid signature of the remote signer response",
+            ));
+        }
+        Ok(response.message)
+    }
+}
+
+impl fmt::Debug for RemoteValidatorSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RemoteValidatorSigner")
+            .field("account_id", &self.inner.account_id)
+            .field("public_key", &self.inner.public_key)
+            .field("addr", &self.inner.addr)
+            .finish()
+    }
+}
+
+impl PartialEq for RemoteValidatorSigner {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner.account_id == other.inner.account_id
+            && self.inner.public_key == other.inner.public_key
+            && self.inner.addr == other.inner.addr
+            && self.inner.signer_identity_key == other.inner.signer_identity_key
+    }
+}
@@ -0,0 +1,24 @@
+use near_o11y::metrics::{
+    exponential_buckets, try_create_histogram_vec, try_create_int_counter_vec, HistogramVec,
+    IntCounterVec,
+};
+use std::sync::LazyLock;
+
+pub(super) static REMOTE_SIGNER_REQUEST_LATENCY: LazyLock<HistogramVec> = LazyLock::new(|| {
+    try_create_histogram_vec(
+        "near_remote_signer_request_latency_sec",
+        "Latency of the requests to the remote validator signer, including reconnecting",
+        &["request"],
+        Some(exponential_buckets(0.0005, 2.0, 12).unwrap()),
+    )
+    .unwrap()
+});
+
+pub(super) static REMOTE_SIGNER_REQUEST_FAILURES: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    try_create_int_counter_vec(
+        "near_remote_signer_request_failures_total",
+        "Number of failed requests to the remote validator signer by the reason of the failure",
+        &["request", "reason"],
+    )
+    .unwrap()
+});
@@ -57,12 +57,16 @@ use validator_stake_view::ValidatorStakeView;
 
 /// A view of the account
 #[derive(serde::Serialize, serde::Deserialize, Debug, Eq, PartialEq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct AccountView {
     #[serde(with = "dec_format")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
     pub amount: Balance,
     #[serde(with = "dec_format")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
     pub locked: Balance,
     #[serde(with = "dec_format")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
     #[cfg(feature = "protocol_feature_nonrefundable_transfer_nep491")]
     pub permanent_storage_bytes: StorageUsage,
     pub code_hash: CryptoHash,
@@ -75,9 +79,11 @@ pub struct AccountView {
 /// A view of the contract code.
 #[serde_as]
 #[derive(serde::Serialize, serde::Deserialize, PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct ContractCodeView {
     #[serde(rename = "code_base64")]
     #[serde_as(as = "Base64")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
     pub code: Vec<u8>,
     pub hash: CryptoHash,
 }
@@ -135,9 +141,11 @@ impl From<AccountView> for Account {
     serde::Serialize,
     serde::Deserialize,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum AccessKeyPermissionView {
     FunctionCall {
         #[serde(with = "dec_format")]
+        #[cfg_attr(feature = "schemars", schemars(with = "Option<String>"))]
         allowance: Option<Balance>,
         receiver_id: String,
         method_names: Vec<String>,
@@ -183,6 +191,7 @@ impl From<AccessKeyPermissionView> for AccessKeyPermission {
     serde::Serialize,
     serde::Deserialize,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct AccessKeyView {
     pub nonce: Nonce,
     pub permission: AccessKeyPermissionView,
@@ -202,6 +211,7 @@ impl From<AccessKeyView> for AccessKey {
 
 /// Item of the state, key and value are serialized in base64 and proof for inclusion of given state item.
 #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct StateItem {
     pub key: StoreKey,
     pub value: StoreValue,
@@ -209,14 +219,21 @@ pub struct StateItem {
 
 #[serde_as]
 #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct ViewStateResult {
     pub values: Vec<StateItem>,
     #[serde_as(as = "Vec<Base64>")]
+    #[cfg_attr(feature = "schemars", schemars(with = "Vec<String>"))]
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub proof: Vec<Arc<[u8]>>,
+    /// Set when only a page of the state was requested and there are more
+    /// items left. Pass it as `start_key_base64` to get the next page.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub continuation_token: Option<StoreKey>,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct CallResult {
     pub result: Vec<u8>,
     pub logs: Vec<String>,
@@ -229,12 +246,14 @@ pub struct QueryError {
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct AccessKeyInfoView {
     pub public_key: PublicKey,
     pub access_key: AccessKeyView,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct AccessKeyList {
     pub keys: Vec<AccessKeyInfoView>,
 }
@@ -285,37 +304,74 @@ pub enum QueryResponseKind {
     AccessKeyList(AccessKeyList),
 }
 
+/// A query of the state at a given block.
+///
+/// If `include_proof` is set, the response carries the trie nodes visited
+/// while answering the query. Together with the state root of the chunk they
+/// allow verifying the response without trusting the node which served it.
 #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(tag = "request_type", rename_all = "snake_case")]
 pub enum QueryRequest {
     ViewAccount {
         account_id: AccountId,
+        #[serde(default, skip_serializing_if = "is_false")]
+        include_proof: bool,
     },
     ViewCode {
         account_id: AccountId,
+        #[serde(default, skip_serializing_if = "is_false")]
+        include_proof: bool,
     },
     ViewState {
         account_id: AccountId,
         #[serde(rename = "prefix_base64")]
         prefix: StoreKey,
+        /// Requests a single page of the state starting at the given key,
+        /// see `ViewStateResult::continuation_token`.
+        #[serde(default, rename = "start_key_base64", skip_serializing_if = "Option::is_none")]
+        start_key: Option<StoreKey>,
+        /// Maximum number of items of the page. Setting it requests a page
+        /// even when `start_key` isn't set.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        limit: Option<u64>,
         #[serde(default, skip_serializing_if = "is_false")]
         include_proof: bool,
     },
     ViewAccessKey {
         account_id: AccountId,
         public_key: PublicKey,
+        #[serde(default, skip_serializing_if = "is_false")]
+        include_proof: bool,
     },
     ViewAccessKeyList {
         account_id: AccountId,
+        #[serde(default, skip_serializing_if = "is_false")]
+        include_proof: bool,
     },
     CallFunction {
         account_id: AccountId,
         method_name: String,
         #[serde(rename = "args_base64")]
         args: FunctionArgs,
+        #[serde(default, skip_serializing_if = "is_false")]
+        include_proof: bool,
     },
 }
 
+impl QueryRequest {
+    pub fn include_proof(&self) -> bool {
+        match self {
+            QueryRequest::ViewAccount { include_proof, .. }
+            | QueryRequest::ViewCode { include_proof, .. }
+            | QueryRequest::ViewState { include_proof, .. }
+            | QueryRequest::ViewAccessKey { include_proof, .. }
+            | QueryRequest::ViewAccessKeyList { include_proof, .. }
+            | QueryRequest::CallFunction { include_proof, .. } => *include_proof,
+        }
+    }
+}
+
 fn is_false(v: &bool) -> bool {
     !*v
 }
@@ -325,19 +381,26 @@ pub struct QueryResponse {
     pub kind: QueryResponseKind,
     pub block_height: BlockHeight,
     pub block_hash: CryptoHash,
+    /// Trie nodes visited while answering the query, empty unless a proof
+    /// was requested. `ViewState` responses carry their proof in
+    /// `ViewStateResult` instead.
+    pub proof: Vec<Arc<[u8]>>,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct StatusSyncInfo {
     pub latest_block_hash: CryptoHash,
     pub latest_block_height: BlockHeight,
     pub latest_state_root: CryptoHash,
     #[serde(with = "near_time::serde_utc_as_iso")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
     pub latest_block_time: Utc,
     pub syncing: bool,
     pub earliest_block_hash: Option<CryptoHash>,
     pub earliest_block_height: Option<BlockHeight>,
     #[serde(with = "near_time::serde_opt_utc_as_iso")]
+    #[cfg_attr(feature = "schemars", schemars(with = "Option<String>"))]
     pub earliest_block_time: Option<Utc>,
     pub epoch_id: Option<EpochId>,
     pub epoch_start_height: Option<BlockHeight>,
@@ -345,12 +408,14 @@ pub struct StatusSyncInfo {
 
 // TODO: add more information to ValidatorInfo
 #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct ValidatorInfo {
     pub account_id: AccountId,
     pub is_slashed: bool,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct PeerInfoView {
     pub addr: String,
     pub account_id: Option<AccountId>,
@@ -373,6 +438,7 @@ pub struct PeerInfoView {
 /// Information about a Producer: its account name, peer_id and a list of connected peers that
 /// the node can use to send message for this producer.
 #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct KnownProducerView {
     pub account_id: AccountId,
     pub peer_id: PublicKey,
@@ -380,21 +446,25 @@ pub struct KnownProducerView {
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Tier1ProxyView {
     pub addr: std::net::SocketAddr,
     pub peer_id: PublicKey,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct AccountDataView {
     pub peer_id: PublicKey,
     pub proxies: Vec<Tier1ProxyView>,
     pub account_key: PublicKey,
     #[serde(with = "near_time::serde_utc_as_iso")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
     pub timestamp: Utc,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct NetworkInfoView {
     pub peer_max_count: u32,
     pub num_connected_peers: usize,
@@ -512,6 +582,7 @@ pub struct DownloadStatusView {
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct CatchupStatusView {
     // This is the first block of the epoch that we are catching up
     pub sync_block_hash: CryptoHash,
@@ -531,6 +602,7 @@ pub struct RequestedStatePartsView {
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct BlockStatusView {
     pub height: BlockHeight,
     pub hash: CryptoHash,
@@ -681,6 +753,7 @@ pub enum ChunkProcessingStatus {
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct DetailedDebugStatus {
     pub network_info: NetworkInfoView,
     pub sync_status: String,
@@ -692,6 +765,7 @@ pub struct DetailedDebugStatus {
 
 // TODO: add more information to status.
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct StatusResponse {
     /// Binary version.
     pub version: Version,
@@ -737,6 +811,7 @@ impl From<Challenge> for ChallengeView {
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct BlockHeaderView {
     pub height: BlockHeight,
     pub prev_height: Option<BlockHeight>,
@@ -755,20 +830,25 @@ pub struct BlockHeaderView {
     /// Legacy json number. Should not be used.
     pub timestamp: u64,
     #[serde(with = "dec_format")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
     pub timestamp_nanosec: u64,
     pub random_value: CryptoHash,
     pub validator_proposals: Vec<ValidatorStakeView>,
     pub chunk_mask: Vec<bool>,
     #[serde(with = "dec_format")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
     pub gas_price: Balance,
     pub block_ordinal: Option<NumBlocks>,
     /// TODO(2271): deprecated.
     #[serde(with = "dec_format")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
     pub rent_paid: Balance,
     /// TODO(2271): deprecated.
     #[serde(with = "dec_format")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
     pub validator_reward: Balance,
     #[serde(with = "dec_format")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
     pub total_supply: Balance,
     pub challenges_result: ChallengesResult,
     pub last_final_block: CryptoHash,
@@ -874,6 +954,7 @@ impl From<BlockHeaderView> for BlockHeader {
     serde::Serialize,
     serde::Deserialize,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct BlockHeaderInnerLiteView {
     pub height: BlockHeight,
     pub epoch_id: CryptoHash,
@@ -883,6 +964,7 @@ pub struct BlockHeaderInnerLiteView {
     /// Legacy json number. Should not be used.
     pub timestamp: u64,
     #[serde(with = "dec_format")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
     pub timestamp_nanosec: u64,
     pub next_bp_hash: CryptoHash,
     pub block_merkle_root: CryptoHash,
@@ -921,6 +1003,7 @@ impl From<BlockHeaderInnerLiteView> for BlockHeaderInnerLite {
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct ChunkHeaderView {
     pub chunk_hash: CryptoHash,
     pub prev_block_hash: CryptoHash,
@@ -935,11 +1018,14 @@ pub struct ChunkHeaderView {
     pub gas_limit: Gas,
     /// TODO(2271): deprecated.
     #[serde(with = "dec_format")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
     pub rent_paid: Balance,
     /// TODO(2271): deprecated.
     #[serde(with = "dec_format")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
     pub validator_reward: Balance,
     #[serde(with = "dec_format")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
     pub balance_burnt: Balance,
     pub outgoing_receipts_root: CryptoHash,
     pub tx_root: CryptoHash,
@@ -1075,6 +1161,7 @@ impl From<ChunkHeaderView> for ShardChunkHeader {
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct BlockView {
     pub author: AccountId,
     pub header: BlockHeaderView,
@@ -1092,6 +1179,7 @@ impl BlockView {
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct ChunkView {
     pub author: AccountId,
     pub header: ChunkHeaderView,
@@ -1129,10 +1217,12 @@ impl ChunkView {
     serde::Serialize,
     serde::Deserialize,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum ActionView {
     CreateAccount,
     DeployContract {
         #[serde_as(as = "Base64")]
+        #[cfg_attr(feature = "schemars", schemars(with = "String"))]
         code: Vec<u8>,
     },
     FunctionCall {
@@ -1140,19 +1230,23 @@ pub enum ActionView {
         args: FunctionArgs,
         gas: Gas,
         #[serde(with = "dec_format")]
+        #[cfg_attr(feature = "schemars", schemars(with = "String"))]
         deposit: Balance,
     },
     Transfer {
         #[serde(with = "dec_format")]
+        #[cfg_attr(feature = "schemars", schemars(with = "String"))]
         deposit: Balance,
     },
     #[cfg(feature = "protocol_feature_nonrefundable_transfer_nep491")]
     NonrefundableStorageTransfer {
         #[serde(with = "dec_format")]
+        #[cfg_attr(feature = "schemars", schemars(with = "String"))]
         deposit: Balance,
     },
     Stake {
         #[serde(with = "dec_format")]
+        #[cfg_attr(feature = "schemars", schemars(with = "String"))]
         stake: Balance,
         public_key: PublicKey,
     },
@@ -1261,6 +1355,7 @@ impl TryFrom<ActionView> for Action {
     serde::Serialize,
     serde::Deserialize,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct SignedTransactionView {
     pub signer_id: AccountId,
     pub public_key: PublicKey,
@@ -1305,6 +1400,7 @@ impl From<SignedTransaction> for SignedTransactionView {
     Clone,
     Default,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum FinalExecutionStatus {
     /// The execution has not yet started.
     #[default]
@@ -1314,7 +1410,11 @@ pub enum FinalExecutionStatus {
     /// The execution has failed with the given error.
     Failure(TxExecutionError),
     /// The execution has succeeded and returned some value or an empty vec encoded in base64.
-    SuccessValue(#[serde_as(as = "Base64")] Vec<u8>),
+    SuccessValue(
+        #[serde_as(as = "Base64")]
+        #[cfg_attr(feature = "schemars", schemars(with = "String"))]
+        Vec<u8>,
+    ),
 }
 
 impl fmt::Debug for FinalExecutionStatus {
@@ -1350,13 +1450,18 @@ pub enum ServerError {
 #[derive(
     BorshSerialize, BorshDeserialize, serde::Serialize, serde::Deserialize, PartialEq, Eq, Clone,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum ExecutionStatusView {
     /// The execution is pending or unknown.
     Unknown,
     /// The execution has failed.
     Failure(TxExecutionError),
     /// The final action succeeded and returned some value or an empty vec encoded in base64.
-    SuccessValue(#[serde_as(as = "Base64")] Vec<u8>),
+    SuccessValue(
+        #[serde_as(as = "Base64")]
+        #[cfg_attr(feature = "schemars", schemars(with = "String"))]
+        Vec<u8>,
+    ),
     /// The final action of the receipt returned a promise or the signed transaction was converted
     /// to a receipt. Contains the receipt_id of the generated receipt.
     SuccessReceiptId(CryptoHash),
@@ -1400,10 +1505,12 @@ impl From<ExecutionStatus> for ExecutionStatusView {
     serde::Serialize,
     serde::Deserialize,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct CostGasUsed {
     pub cost_category: String,
     pub cost: String,
     #[serde(with = "dec_format")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
     pub gas_used: Gas,
 }
 
@@ -1417,6 +1524,7 @@ pub struct CostGasUsed {
     serde::Serialize,
     serde::Deserialize,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct ExecutionMetadataView {
     pub version: u32,
     pub gas_profile: Option<Vec<CostGasUsed>>,
@@ -1539,6 +1647,7 @@ impl CostGasUsed {
     serde::Serialize,
     serde::Deserialize,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct ExecutionOutcomeView {
     /// Logs from this transaction or receipt.
     pub logs: Vec<String>,
@@ -1550,6 +1659,7 @@ pub struct ExecutionOutcomeView {
     /// This value doesn't always equal to the `gas_burnt` multiplied by the gas price, because
     /// the prepaid gas price might be lower than the actual gas price and it creates a deficit.
     #[serde(with = "dec_format")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
     pub tokens_burnt: Balance,
     /// The id of the account on which the execution happens. For transaction this is signer_id,
     /// for receipt this is receiver_id.
@@ -1621,6 +1731,7 @@ impl ExecutionOutcomeView {
     serde::Serialize,
     serde::Deserialize,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct ExecutionOutcomeWithIdView {
     pub proof: MerklePath,
     pub block_hash: CryptoHash,
@@ -1661,6 +1772,7 @@ pub struct TxStatusView {
     Eq,
     PartialEq,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum TxExecutionStatus {
     /// Transaction is waiting to be included into the block
@@ -1684,6 +1796,7 @@ pub enum TxExecutionStatus {
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(untagged)]
 // FinalExecutionOutcomeWithReceipt is a superset of FinalExecutionOutcome that includes additional information about receipts.
 // For proper deserialization we need to have more specific variant first.
@@ -1717,6 +1830,7 @@ impl TxStatusView {
 #[derive(
     BorshSerialize, BorshDeserialize, serde::Serialize, serde::Deserialize, PartialEq, Eq, Clone,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct FinalExecutionOutcomeView {
     /// Execution status defined by chain.rs:get_final_transaction_result
     /// FinalExecutionStatus::NotStarted - the tx is not converted to the receipt yet
@@ -1755,6 +1869,7 @@ impl fmt::Debug for FinalExecutionOutcomeView {
     serde::Serialize,
     serde::Deserialize,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct FinalExecutionOutcomeWithReceiptView {
     /// Final outcome view without receipts
     #[serde(flatten)]
@@ -1773,6 +1888,7 @@ pub mod validator_stake_view {
     #[derive(
         BorshSerialize, BorshDeserialize, serde::Serialize, Deserialize, Debug, Clone, Eq, PartialEq,
     )]
+    #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
     #[serde(tag = "validator_stake_struct_version")]
     pub enum ValidatorStakeView {
         V1(ValidatorStakeViewV1),
@@ -1829,10 +1945,12 @@ pub mod validator_stake_view {
     serde::Serialize,
     serde::Deserialize,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct ValidatorStakeViewV1 {
     pub account_id: AccountId,
     pub public_key: PublicKey,
     #[serde(with = "dec_format")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
     pub stake: Balance,
 }
 
@@ -1846,6 +1964,7 @@ pub struct ValidatorStakeViewV1 {
     serde::Serialize,
     serde::Deserialize,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct ReceiptView {
     pub predecessor_id: AccountId,
     pub receiver_id: AccountId,
@@ -1869,6 +1988,7 @@ pub struct ReceiptView {
     serde::Serialize,
     serde::Deserialize,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct DataReceiverView {
     pub data_id: CryptoHash,
     pub receiver_id: AccountId,
@@ -1885,11 +2005,13 @@ pub struct DataReceiverView {
     serde::Serialize,
     serde::Deserialize,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum ReceiptEnumView {
     Action {
         signer_id: AccountId,
         signer_public_key: PublicKey,
         #[serde(with = "dec_format")]
+        #[cfg_attr(feature = "schemars", schemars(with = "String"))]
         gas_price: Balance,
         output_data_receivers: Vec<DataReceiverView>,
         input_data_ids: Vec<CryptoHash>,
@@ -1900,6 +2022,7 @@ pub enum ReceiptEnumView {
     Data {
         data_id: CryptoHash,
         #[serde_as(as = "Option<Base64>")]
+        #[cfg_attr(feature = "schemars", schemars(with = "Option<String>"))]
         data: Option<Vec<u8>>,
         #[serde(default = "default_is_promise")]
         is_promise_resume: bool,
@@ -2018,6 +2141,7 @@ impl TryFrom<ReceiptView> for Receipt {
 
 /// Information about this epoch validators and next epoch validators
 #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq, Clone, ProtocolSchema)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct EpochValidatorInfo {
     /// Validators for the current epoch
     pub current_validators: Vec<CurrentEpochValidatorInfo>,
@@ -2048,17 +2172,20 @@ pub struct EpochValidatorInfo {
     serde::Deserialize,
     ProtocolSchema,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct ValidatorKickoutView {
     pub account_id: AccountId,
     pub reason: ValidatorKickoutReason,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq, Clone, ProtocolSchema)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct CurrentEpochValidatorInfo {
     pub account_id: AccountId,
     pub public_key: PublicKey,
     pub is_slashed: bool,
     #[serde(with = "dec_format")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
     pub stake: Balance,
     /// Shards this validator is assigned to as chunk producer in the current epoch.
     #[serde(rename = "shards")]
@@ -2101,10 +2228,12 @@ pub struct CurrentEpochValidatorInfo {
     serde::Deserialize,
     ProtocolSchema,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct NextEpochValidatorInfo {
     pub account_id: AccountId,
     pub public_key: PublicKey,
     #[serde(with = "dec_format")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
     pub stake: Balance,
     pub shards: Vec<ShardId>,
 }
@@ -2119,6 +2248,7 @@ pub struct NextEpochValidatorInfo {
     serde::Serialize,
     serde::Deserialize,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct LightClientBlockView {
     pub prev_block_hash: CryptoHash,
     pub next_block_inner_hash: CryptoHash,
@@ -2129,6 +2259,7 @@ pub struct LightClientBlockView {
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone, BorshDeserialize, BorshSerialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct LightClientBlockLiteView {
     pub prev_block_hash: CryptoHash,
     pub inner_rest_hash: CryptoHash,
@@ -2158,8 +2289,10 @@ impl LightClientBlockLiteView {
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GasPriceView {
     #[serde(with = "dec_format")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
     pub gas_price: Balance,
 }
 
@@ -2168,6 +2301,7 @@ pub struct GasPriceView {
 /// [serializable view]: ./index.html
 /// [`StateChangesRequest`]: ../types/struct.StateChangesRequest.html
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(tag = "changes_type", rename_all = "snake_case")]
 pub enum StateChangesRequestView {
     AccountChanges {
@@ -2216,6 +2350,7 @@ impl From<StateChangesRequestView> for StateChangesRequest {
 /// [serializable view]: ./index.html
 /// [`StateChangeKind`]: ../types/struct.StateChangeKind.html
 #[derive(Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case", tag = "type")]
 pub enum StateChangeKindView {
     AccountTouched { account_id: AccountId },
@@ -2243,6 +2378,7 @@ pub type StateChangesKindsView = Vec<StateChangeKindView>;
 
 /// See crate::types::StateChangeCause for details.
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case", tag = "type")]
 pub enum StateChangeCauseView {
     NotWritableToDisk,
@@ -2290,6 +2426,7 @@ impl From<StateChangeCause> for StateChangeCauseView {
 
 #[serde_as]
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case", tag = "type", content = "change")]
 pub enum StateChangeValueView {
     AccountUpdate {
@@ -2325,6 +2462,7 @@ pub enum StateChangeValueView {
         account_id: AccountId,
         #[serde(rename = "code_base64")]
         #[serde_as(as = "Base64")]
+        #[cfg_attr(feature = "schemars", schemars(with = "String"))]
         code: Vec<u8>,
     },
     ContractCodeDeletion {
@@ -2364,6 +2502,7 @@ impl From<StateChangeValue> for StateChangeValueView {
 }
 
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct StateChangeWithCauseView {
     pub cause: StateChangeCauseView,
     #[serde(flatten)]
@@ -2384,20 +2523,39 @@ pub type MaintenanceWindowsView = Vec<Range<BlockHeight>>;
 
 /// Contains the split storage information.
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct SplitStorageInfoView {
     pub head_height: Option<BlockHeight>,
     pub final_head_height: Option<BlockHeight>,
     pub cold_head_height: Option<BlockHeight>,
 
     pub hot_db_kind: Option<String>,
+
+    /// Range of heights retained in cold storage for every cold column.
+    #[serde(default)]
+    pub cold_retained_ranges: Vec<ColdColumnRetainedRangeView>,
+}
+
+/// Range of heights for which the data of a column is kept in cold storage.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ColdColumnRetainedRangeView {
+    pub column: String,
+    /// Lowest retained height. None if no data of the column was pruned.
+    pub from_height: Option<BlockHeight>,
+    /// Highest retained height, i.e. the cold head height.
+    pub to_height: BlockHeight,
 }
 
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct CongestionInfoView {
     #[serde(with = "dec_format")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
     pub delayed_receipts_gas: u128,
 
     #[serde(with = "dec_format")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
     pub buffered_receipts_gas: u128,
 
     pub receipt_bytes: u64,
@@ -121,25 +121,28 @@ fn create_chunk_header(height: u64, shard_id: ShardId) -> ShardChunkHeader {
         .enabled(PROTOCOL_VERSION)
         .then_some(CongestionInfo::default());
 
-    ShardChunkHeader::V3(ShardChunkHeaderV3::new(
-        PROTOCOL_VERSION,
-        CryptoHash::default(),
-        CryptoHash::default(),
-        CryptoHash::default(),
-        CryptoHash::default(),
-        1,
-        height,
-        shard_id,
-        0,
-        0,
-        0,
-        CryptoHash::default(),
-        CryptoHash::default(),
-        vec![],
-        congestion_info,
-        BandwidthRequests::default_for_protocol_version(PROTOCOL_VERSION),
-        &validator_signer().into(),
-    ))
+    ShardChunkHeader::V3(
+        ShardChunkHeaderV3::new(
+            PROTOCOL_VERSION,
+            CryptoHash::default(),
+            CryptoHash::default(),
+            CryptoHash::default(),
+            CryptoHash::default(),
+            1,
+            height,
+            shard_id,
+            0,
+            0,
+            0,
+            CryptoHash::default(),
+            CryptoHash::default(),
+            vec![],
+            congestion_info,
+            BandwidthRequests::default_for_protocol_version(PROTOCOL_VERSION),
+            &validator_signer().into(),
+        )
+        .unwrap(),
+    )
 }
 
 fn create_action_receipt(
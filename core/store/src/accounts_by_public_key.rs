@@ -0,0 +1,284 @@
+//! Secondary index of access keys by their public key.
+//!
+//! The index allows finding the accounts a public key has access to without
+//! scanning the whole state. When `store.index_accounts_by_public_key` is
+//! enabled, it's maintained while saving the state changes of applied chunks
+//! and backfilled from flat storage in the background after startup.
+//!
+//! State obtained through state sync isn't indexed until the next backfill,
+//! which happens on startup if the index was dropped.
+//!
+//! Entries are only ever added. An access key may be deleted on a fork which
+//! doesn't become canonical, so removing the entry could lose a key which
+//! still exists. Readers must check the entries against the state instead.
+
+use crate::adapter::StoreAdapter;
+use crate::db::{
+    ACCOUNTS_BY_PUBLIC_KEY_BACKFILLED_KEY, ACCOUNTS_BY_PUBLIC_KEY_BACKFILL_CURSOR_KEY,
+};
+use crate::flat::FlatStorageStatus;
+use crate::{DBCol, Store, StoreUpdate};
+use actix_rt::ArbiterHandle;
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_crypto::PublicKey;
+use near_primitives::shard_layout::ShardUId;
+use near_primitives::trie_key::{col, trie_key_parsers};
+use near_primitives::types::AccountId;
+use std::io;
+
+/// Number of flat state entries indexed at once while backfilling.
+const BACKFILL_BATCH_SIZE: usize = 100_000;
+
+/// Returns the row key of the index for the given access key.
+pub fn index_key(public_key: &PublicKey, account_id: &AccountId) -> Vec<u8> {
+    let mut key = borsh::to_vec(public_key).expect("Borsh serialize cannot fail");
+    key.extend(account_id.as_bytes());
+    key
+}
+
+/// Returns the accounts which had an access key with the given public key.
+pub fn get_accounts_by_public_key(
+    store: &Store,
+    public_key: &PublicKey,
+) -> io::Result<Vec<AccountId>> {
+    let prefix = borsh::to_vec(public_key).expect("Borsh serialize cannot fail");
+    store
+        .iter_prefix(DBCol::AccountsByPublicKey, &prefix)
+        .map(|item| {
+            let (key, _) = item?;
+            std::str::from_utf8(&key[prefix.len()..])
+                .ok()
+                .and_then(|account_id| account_id.parse().ok())
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("invalid account id in DBCol::AccountsByPublicKey key {key:?}"),
+                    )
+                })
+        })
+        .collect()
+}
+
+/// Whether the index has been backfilled and is kept up to date.
+pub fn is_backfilled(store: &Store) -> io::Result<bool> {
+    store.exists(DBCol::Misc, ACCOUNTS_BY_PUBLIC_KEY_BACKFILLED_KEY)
+}
+
+/// Whether the backfill has started and hasn't finished yet.
+pub fn is_backfill_in_progress(store: &Store) -> io::Result<bool> {
+    store.exists(DBCol::Misc, ACCOUNTS_BY_PUBLIC_KEY_BACKFILL_CURSOR_KEY)
+}
+
+/// Progress of the backfill, saved along with every batch so that the
+/// backfill continues where it stopped after a restart.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+struct BackfillCursor {
+    /// Lower bound of the keys in `DBCol::FlatStorageStatus` of the shards
+    /// which are yet to be backfilled.
+    shard_key: Vec<u8>,
+    /// Flat state key to continue from if the first of these shards is
+    /// partially backfilled, `None` if its deltas are yet to be indexed.
+    next_key: Option<Vec<u8>>,
+    /// Whether flat storage of all the shards backfilled so far was ready.
+    complete: bool,
+}
+
+/// Indexes the next batch of up to `batch_size` access keys found in flat
+/// storage, including the deltas which aren't applied to the flat state yet.
+/// Returns the number of indexed access keys, or `None` if the backfill is
+/// finished.
+///
+/// The index is marked as complete only if flat storage of every shard is
+/// ready, otherwise the backfill starts over on the next startup.
+pub fn backfill_batch(store: &Store, batch_size: usize) -> anyhow::Result<Option<usize>> {
+    let cursor_key = ACCOUNTS_BY_PUBLIC_KEY_BACKFILL_CURSOR_KEY;
+    let mut cursor = store
+        .get_ser::<BackfillCursor>(DBCol::Misc, cursor_key)?
+        .unwrap_or(BackfillCursor { shard_key: vec![], next_key: None, complete: true });
+    let mut store_update = store.store_update();
+    let Some(item) =
+        store.iter_range(DBCol::FlatStorageStatus, Some(&cursor.shard_key), None).next()
+    else {
+        if cursor.complete {
+            store_update.set(DBCol::Misc, ACCOUNTS_BY_PUBLIC_KEY_BACKFILLED_KEY, &[]);
+        }
+        store_update.delete(DBCol::Misc, cursor_key);
+        store_update.commit()?;
+        return Ok(None);
+    };
+    let (key, value) = item?;
+    let shard_uid = ShardUId::try_from(key.as_ref()).map_err(|err| anyhow::anyhow!(err))?;
+    let status = FlatStorageStatus::try_from_slice(&value)?;
+    // The shard the cursor pointed to may be gone, e.g. after resharding.
+    if key.as_ref() != cursor.shard_key.as_slice() {
+        cursor.next_key = None;
+    }
+    let finish_shard = |cursor: &mut BackfillCursor| {
+        cursor.shard_key = ShardUId::get_upper_bound_db_key(&shard_uid.to_bytes()).to_vec();
+        cursor.next_key = None;
+    };
+    let add_access_key = |store_update: &mut StoreUpdate, raw_key: &[u8]| -> io::Result<()> {
+        let account_id = trie_key_parsers::parse_account_id_from_access_key_key(raw_key)?;
+        let public_key =
+            trie_key_parsers::parse_public_key_from_access_key_key(raw_key, &account_id)?;
+        store_update.set(DBCol::AccountsByPublicKey, &index_key(&public_key, &account_id), &[]);
+        Ok(())
+    };
+
+    let mut indexed = 0;
+    if !matches!(status, FlatStorageStatus::Ready(_)) {
+        tracing::warn!(target: "store", ?shard_uid, ?status, "Flat storage is not ready, skipping the shard while backfilling accounts by public key");
+        cursor.complete = false;
+        finish_shard(&mut cursor);
+    } else {
+        let flat_store = store.flat_store();
+        if cursor.next_key.is_none() {
+            // Deltas are read before the flat state, so that a delta applied
+            // to the flat state in the meantime is still seen in one of them.
+            // The blocks processed later are indexed by the client itself.
+            for metadata in flat_store.get_all_deltas_metadata(shard_uid)? {
+                let Some(changes) = flat_store.get_delta(shard_uid, metadata.block.hash)? else {
+                    continue;
+                };
+                for (raw_key, value) in changes.0 {
+                    if value.is_some() && raw_key.first() == Some(&col::ACCESS_KEY) {
+                        add_access_key(&mut store_update, &raw_key)?;
+                        indexed += 1;
+                    }
+                }
+            }
+        }
+        let from = cursor.next_key.take().unwrap_or_else(|| vec![col::ACCESS_KEY]);
+        let to = [col::ACCESS_KEY + 1];
+        let mut last_key = None;
+        let mut batch_len = 0;
+        for item in flat_store.iter_range(shard_uid, Some(&from), Some(&to)).take(batch_size) {
+            let (raw_key, _) = item?;
+            add_access_key(&mut store_update, &raw_key)?;
+            batch_len += 1;
+            last_key = Some(raw_key);
+        }
+        indexed += batch_len;
+        match last_key {
+            Some(mut last_key) if batch_len == batch_size => {
+                // The smallest key after the last indexed one.
+                last_key.push(0);
+                cursor.shard_key = key.to_vec();
+                cursor.next_key = Some(last_key);
+            }
+            _ => finish_shard(&mut cursor),
+        }
+    }
+    store_update.set_ser(DBCol::Misc, cursor_key, &cursor)?;
+    store_update.commit()?;
+    Ok(Some(indexed))
+}
+
+/// Spawns a loop which backfills the index in batches of
+/// [`BACKFILL_BATCH_SIZE`], so that the node doesn't wait for it on startup.
+pub fn spawn_backfill_loop(store: Store) -> ArbiterHandle {
+    tracing::debug!(target: "store", "Spawning the accounts by public key backfill loop.");
+    let arbiter = actix_rt::Arbiter::new();
+
+    arbiter.spawn(async move {
+        tracing::info!(target: "store", "Backfilling the index of accounts by public key");
+        let mut total = 0;
+        loop {
+            match backfill_batch(&store, BACKFILL_BATCH_SIZE) {
+                Ok(Some(indexed)) => total += indexed,
+                Ok(None) => break,
+                Err(err) => {
+                    // The backfill continues from the cursor on the next
+                    // startup.
+                    tracing::error!(target: "store", ?err, "Failed to backfill the index of accounts by public key");
+                    return;
+                }
+            }
+            // Lets the arbiter stop between the batches on shutdown.
+            tokio::task::yield_now().await;
+        }
+        let complete = is_backfilled(&store).unwrap_or(false);
+        tracing::info!(target: "store", total, complete, "Backfilled the index of accounts by public key");
+    });
+
+    arbiter.handle()
+}
+
+/// Removes the index. Nothing updates it while it is disabled, so it has to
+/// be backfilled again when enabled later.
+pub fn drop_index(store: &Store) -> io::Result<()> {
+    let mut store_update = store.store_update();
+    store_update.delete_all(DBCol::AccountsByPublicKey);
+    store_update.delete(DBCol::Misc, ACCOUNTS_BY_PUBLIC_KEY_BACKFILLED_KEY);
+    store_update.delete(DBCol::Misc, ACCOUNTS_BY_PUBLIC_KEY_BACKFILL_CURSOR_KEY);
+    store_update.commit()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        backfill_batch, get_accounts_by_public_key, is_backfill_in_progress, is_backfilled,
+    };
+    use crate::adapter::StoreAdapter;
+    use crate::flat::{BlockInfo, FlatStorageReadyStatus, FlatStorageStatus};
+    use crate::test_utils::create_test_store;
+    use near_crypto::{KeyType, PublicKey};
+    use near_primitives::hash::CryptoHash;
+    use near_primitives::shard_layout::ShardUId;
+    use near_primitives::state::FlatStateValue;
+    use near_primitives::trie_key::TrieKey;
+    use near_primitives::types::AccountId;
+
+    #[test]
+    fn test_backfill() {
+        let store = create_test_store();
+        let shard_uid = ShardUId::single_shard();
+        let alice: AccountId = "alice.near".parse().unwrap();
+        let bob: AccountId = "bob.near".parse().unwrap();
+        let shared_key = PublicKey::from_seed(KeyType::ED25519, "shared");
+        let bob_key = PublicKey::from_seed(KeyType::ED25519, "bob");
+
+        let mut store_update = store.flat_store().store_update();
+        store_update.set_flat_storage_status(
+            shard_uid,
+            FlatStorageStatus::Ready(FlatStorageReadyStatus {
+                flat_head: BlockInfo::genesis(CryptoHash::default(), 0),
+            }),
+        );
+        for (account_id, public_key) in
+            [(&alice, &shared_key), (&bob, &shared_key), (&bob, &bob_key)]
+        {
+            let trie_key = TrieKey::AccessKey {
+                account_id: account_id.clone(),
+                public_key: public_key.clone(),
+            };
+            store_update.set(shard_uid, trie_key.to_vec(), Some(FlatStateValue::inlined(&[0])));
+        }
+        // Other kinds of keys are not indexed.
+        store_update.set(
+            shard_uid,
+            TrieKey::Account { account_id: alice.clone() }.to_vec(),
+            Some(FlatStateValue::inlined(&[0])),
+        );
+        store_update.commit().unwrap();
+
+        assert!(!is_backfilled(&store).unwrap());
+        // Batches of a single access key continue from the saved cursor.
+        let mut total = 0;
+        while let Some(indexed) = backfill_batch(&store, 1).unwrap() {
+            assert!(is_backfill_in_progress(&store).unwrap());
+            assert!(!is_backfilled(&store).unwrap());
+            total += indexed;
+        }
+        assert_eq!(total, 3);
+        assert!(!is_backfill_in_progress(&store).unwrap());
+        assert!(is_backfilled(&store).unwrap());
+        assert_eq!(
+            get_accounts_by_public_key(&store, &shared_key).unwrap(),
+            vec![alice, bob.clone()]
+        );
+        assert_eq!(get_accounts_by_public_key(&store, &bob_key).unwrap(), vec![bob]);
+        let unknown_key = PublicKey::from_seed(KeyType::ED25519, "unknown");
+        assert!(get_accounts_by_public_key(&store, &unknown_key).unwrap().is_empty());
+    }
+}
@@ -3,11 +3,13 @@ use std::num::NonZero;
 use std::sync::Arc;
 
 use borsh::BorshDeserialize;
+use near_crypto::PublicKey;
 use near_primitives::errors::{MissingTrieValueContext, StorageError};
 use near_primitives::hash::CryptoHash;
 use near_primitives::shard_layout::{get_block_shard_uid, ShardUId};
-use near_primitives::types::RawStateChangesWithTrieKey;
+use near_primitives::types::{AccountId, RawStateChangesWithTrieKey};
 
+use crate::accounts_by_public_key;
 use crate::{DBCol, KeyForStateChanges, Store, StoreUpdate, TrieChanges, STATE_SNAPSHOT_KEY};
 
 use super::{StoreAdapter, StoreUpdateAdapter, StoreUpdateHolder};
@@ -158,6 +160,16 @@ impl<'a> TrieStoreUpdateAdapter<'a> {
         )
     }
 
+    /// Adds the access key to the index of accounts by public key, see
+    /// [`crate::accounts_by_public_key`].
+    pub fn set_account_by_public_key(&mut self, public_key: &PublicKey, account_id: &AccountId) {
+        self.store_update.set(
+            DBCol::AccountsByPublicKey,
+            &accounts_by_public_key::index_key(public_key, account_id),
+            &[],
+        )
+    }
+
     /// Set the mapping from `child_shard_uid` to `parent_shard_uid`.
     /// Used by Resharding V3 for State mapping.
     pub fn set_shard_uid_mapping(&mut self, child_shard_uid: ShardUId, parent_shard_uid: ShardUId) {
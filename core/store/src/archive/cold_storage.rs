@@ -1,5 +1,5 @@
 use crate::columns::DBKeyType;
-use crate::db::{ColdDB, COLD_HEAD_KEY, HEAD_KEY};
+use crate::db::{ColdDB, COLD_COLUMN_TAILS_KEY, COLD_HEAD_KEY, HEAD_KEY};
 use crate::{metrics, DBCol, DBTransaction, Database, Store, TrieChanges};
 
 use borsh::BorshDeserialize;
@@ -9,7 +9,7 @@ use near_primitives::shard_layout::ShardLayout;
 use near_primitives::sharding::ShardChunk;
 use near_primitives::types::BlockHeight;
 use rayon::prelude::{IntoParallelIterator, ParallelIterator};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::io;
 use std::sync::Arc;
 use strum::IntoEnumIterator;
@@ -321,6 +321,84 @@ pub fn get_cold_head(cold_db: &ColdDB) -> io::Result<Option<Tip>> {
         .transpose()
 }
 
+/// Removes the data of `columns` for the block at `height` from cold storage.
+/// Returns if the block was pruned (false only if there is no block at
+/// `height` in `hot_store` or in `cold_db`).
+///
+/// The keys are determined the same way as in `update_cold_db`, but from the
+/// data in cold storage. This is why the columns needed for that are never
+/// pruned, see `DBCol::is_cold_prunable`.
+pub fn prune_cold_db(
+    cold_db: &Arc<ColdDB>,
+    hot_store: &Store,
+    shard_layout: &ShardLayout,
+    height: &BlockHeight,
+    columns: &[DBCol],
+) -> io::Result<bool> {
+    let _span = tracing::debug_span!(target: "cold_store", "prune cold db", height = height);
+
+    let height_key = height.to_le_bytes();
+    let Some(block_hash) = hot_store.get(DBCol::BlockHeight, &height_key)? else {
+        return Ok(false);
+    };
+    let block_hash_key = block_hash.as_slice();
+    let cold_store = Store::new(cold_db.clone());
+    if !cold_store.exists(DBCol::Block, block_hash_key)? {
+        return Ok(false);
+    }
+
+    let key_type_to_keys =
+        get_keys_from_store(&cold_store, shard_layout, &height_key, block_hash_key)?;
+    let mut transaction = DBTransaction::new();
+    for col in columns {
+        debug_assert!(col.is_cold_prunable());
+        for key in combine_keys(&key_type_to_keys, col.key_type()) {
+            transaction.delete(*col, key);
+        }
+    }
+    cold_db.prune(transaction)?;
+
+    Ok(true)
+}
+
+/// Reads the lowest heights retained in cold storage for the columns which
+/// have been pruned, keyed by column name.
+pub fn get_cold_column_tails(cold_db: &ColdDB) -> io::Result<BTreeMap<String, BlockHeight>> {
+    Ok(cold_db
+        .get_raw_bytes(DBCol::BlockMisc, COLD_COLUMN_TAILS_KEY)?
+        .as_deref()
+        .map(BTreeMap::try_from_slice)
+        .transpose()?
+        .unwrap_or_default())
+}
+
+/// Writes the column tails to the cold db and to the hot db, where they are
+/// read from by the view client, the same way as the cold head.
+pub fn update_cold_column_tails(
+    cold_db: &ColdDB,
+    hot_store: &Store,
+    tails: &BTreeMap<String, BlockHeight>,
+) -> io::Result<()> {
+    tracing::debug!(target: "cold_store", ?tails, "update column tails of cold db");
+
+    let value = borsh::to_vec(tails)?;
+    {
+        let mut transaction = DBTransaction::new();
+        transaction.set(DBCol::BlockMisc, COLD_COLUMN_TAILS_KEY.to_vec(), value.clone());
+        cold_db.write(transaction)?;
+    }
+    {
+        let mut transaction = DBTransaction::new();
+        transaction.set(DBCol::BlockMisc, COLD_COLUMN_TAILS_KEY.to_vec(), value);
+        hot_store.storage.write(transaction)?;
+    }
+
+    for (col, height) in tails {
+        crate::metrics::COLD_COLUMN_TAIL_HEIGHT.with_label_values(&[col]).set(*height as i64);
+    }
+    Ok(())
+}
+
 pub enum CopyAllDataToColdStatus {
     EverythingCopied,
     Interrupted,
@@ -393,7 +471,7 @@ pub fn test_get_store_initial_writes(column: DBCol) -> u64 {
 /// So, for every KeyType we need to capture all the keys that are related to that block.
 /// For BlockHash it is just one key -- block hash of that height.
 /// But for TransactionHash, for example, it is all of the tx hashes in that block.
-fn get_keys_from_store(
+pub(crate) fn get_keys_from_store(
     store: &Store,
     shard_layout: &ShardLayout,
     height_key: &[u8],
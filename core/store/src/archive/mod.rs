@@ -1 +1,2 @@
 pub mod cold_storage;
+pub mod segments;
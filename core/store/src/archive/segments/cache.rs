@@ -0,0 +1,124 @@
+use near_primitives::types::BlockHeight;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Size of the blocks in which the segments are fetched and cached.
+pub(super) const CACHE_BLOCK_SIZE: u64 = 4 * bytesize::MIB;
+
+/// Identifies a cached block by the first height of its segment and the index
+/// of the block within the segment.
+type BlockId = (BlockHeight, u64);
+
+/// Local on-disk cache of the blocks of the segments.
+///
+/// Every block is kept in a separate file named `<segment>.<block index>` in
+/// the cache directory. The least recently used blocks are removed when the
+/// total size of the cached blocks exceeds the capacity. The blocks already
+/// in the directory are picked up when the cache is opened.
+pub(super) struct SegmentCache {
+    dir: PathBuf,
+    capacity: u64,
+    state: Mutex<CacheState>,
+}
+
+struct CacheState {
+    blocks: lru::LruCache<BlockId, u64>,
+    size: u64,
+}
+
+impl SegmentCache {
+    pub(super) fn open(dir: &Path, capacity: u64) -> io::Result<Self> {
+        std::fs::create_dir_all(dir)?;
+        let mut state = CacheState { blocks: lru::LruCache::unbounded(), size: 0 };
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            let Some(id) = entry.file_name().to_str().and_then(parse_file_name) else {
+                // Leftovers of interrupted writes.
+                std::fs::remove_file(entry.path())?;
+                continue;
+            };
+            let size = entry.metadata()?.len();
+            state.blocks.put(id, size);
+            state.size += size;
+        }
+        let cache = Self { dir: dir.to_path_buf(), capacity, state: Mutex::new(state) };
+        cache.evict(&mut cache.state.lock().unwrap())?;
+        Ok(cache)
+    }
+
+    pub(super) fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the block from the cache or fetches it with `fetch` and adds it
+    /// to the cache.
+    pub(super) fn get(
+        &self,
+        id: BlockId,
+        fetch: impl FnOnce() -> io::Result<Vec<u8>>,
+    ) -> io::Result<Vec<u8>> {
+        let cached = self.state.lock().unwrap().blocks.get(&id).is_some();
+        if cached {
+            match std::fs::read(self.path(id)) {
+                Ok(data) => {
+                    crate::metrics::COLD_SEGMENT_CACHE_HITS.inc();
+                    return Ok(data);
+                }
+                // The block was evicted in the meantime.
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => return Err(err),
+            }
+        }
+        crate::metrics::COLD_SEGMENT_CACHE_MISSES.inc();
+
+        let data = fetch()?;
+        // Concurrent fetches of the same block write to different temporary
+        // files, their names don't parse as block ids.
+        let mut tmp_file = tempfile::Builder::new().prefix("tmp.").tempfile_in(&self.dir)?;
+        tmp_file.write_all(&data)?;
+        tmp_file.persist(self.path(id))?;
+
+        let mut state = self.state.lock().unwrap();
+        if let Some(size) = state.blocks.put(id, data.len() as u64) {
+            state.size -= size;
+        }
+        state.size += data.len() as u64;
+        self.evict(&mut state)?;
+        Ok(data)
+    }
+
+    /// Removes the block from the cache, e.g. because it's corrupted.
+    pub(super) fn remove(&self, id: BlockId) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(size) = state.blocks.pop(&id) {
+            state.size -= size;
+            std::fs::remove_file(self.path(id))?;
+        }
+        Ok(())
+    }
+
+    fn evict(&self, state: &mut CacheState) -> io::Result<()> {
+        while state.size > self.capacity {
+            let Some((id, size)) = state.blocks.pop_lru() else {
+                break;
+            };
+            state.size -= size;
+            match std::fs::remove_file(self.path(id)) {
+                Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+                _ => {}
+            }
+        }
+        crate::metrics::COLD_SEGMENT_CACHE_SIZE.set(state.size as i64);
+        Ok(())
+    }
+
+    fn path(&self, (segment, index): BlockId) -> PathBuf {
+        self.dir.join(format!("{segment:012}.{index}"))
+    }
+}
+
+fn parse_file_name(name: &str) -> Option<BlockId> {
+    let (segment, index) = name.split_once('.')?;
+    Some((segment.parse().ok()?, index.parse().ok()?))
+}
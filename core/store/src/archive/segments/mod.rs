@@ -0,0 +1,429 @@
+//! Offloading of old cold storage data to an object store.
+//!
+//! The data of cold columns for a sealed range of heights is packed into an
+//! immutable segment, which is uploaded to an object store (S3 or a local
+//! directory) and removed from the local cold storage. For every offloaded
+//! row, a [`ColdSegmentPointer`] to its value within the segment is kept in
+//! [`DBCol::ColdSegmentPointers`] of the cold storage. [`ColdDB`] reads the
+//! rows missing locally through the pointers, fetching the segments in blocks
+//! which are cached on the local disk.
+//!
+//! A segment consists of a [`SegmentHeader`] followed by the borsh encoded
+//! `(column name, key, value)` entries, so that the pointers can be rebuilt
+//! from the segments alone.
+
+mod cache;
+mod object_store;
+
+pub use object_store::{FilesystemObjectStore, ObjectStore, S3ObjectStore};
+
+use crate::archive::cold_storage::{combine_keys, get_keys_from_store};
+use crate::config::{ColdStoreSegmentsConfig, ColdStoreSegmentsLocation};
+use crate::db::{ColdDB, COLD_SEGMENTS_HEAD_KEY};
+use crate::{DBCol, DBTransaction, Database, Store};
+use borsh::{BorshDeserialize, BorshSerialize};
+use cache::{SegmentCache, CACHE_BLOCK_SIZE};
+use near_primitives::shard_layout::ShardLayout;
+use near_primitives::types::BlockHeight;
+use std::collections::HashMap;
+use std::io::{self, Seek, Write};
+use std::path::Path;
+use std::sync::Arc;
+use strum::IntoEnumIterator;
+
+const SEGMENT_MAGIC: [u8; 8] = *b"NEARCSEG";
+const SEGMENT_VERSION: u32 = 1;
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+struct SegmentHeader {
+    magic: [u8; 8],
+    version: u32,
+    /// First height of the segment.
+    start: BlockHeight,
+    /// Last height of the segment, inclusive.
+    end: BlockHeight,
+}
+
+/// Location of an offloaded value, stored in [`DBCol::ColdSegmentPointers`].
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ColdSegmentPointer {
+    /// First height of the segment holding the value.
+    pub segment: BlockHeight,
+    /// Offset of the value within the segment.
+    pub offset: u64,
+    pub len: u32,
+    /// CRC32 checksum of the value.
+    pub crc32: u32,
+}
+
+/// Returns the name of the object holding the segment starting at `start`.
+pub fn segment_object_name(start: BlockHeight) -> String {
+    format!("segments/{start:012}")
+}
+
+/// Returns the key in [`DBCol::ColdSegmentPointers`] of the pointer to the
+/// value of `key` in `col`.
+pub(crate) fn pointer_key(col: DBCol, key: &[u8]) -> Vec<u8> {
+    [&pointer_key_prefix(col), key].concat()
+}
+
+/// Returns the common prefix of the keys of the pointers to `col`. Column
+/// names don't contain '/', so the prefixes of different columns never
+/// overlap.
+pub(crate) fn pointer_key_prefix(col: DBCol) -> Vec<u8> {
+    [<&str>::from(col).as_bytes(), b"/"].concat()
+}
+
+/// Returns the exclusive upper bound of the keys of the pointers to `col`.
+pub(crate) fn pointer_key_prefix_end(col: DBCol) -> Vec<u8> {
+    // '0' is the byte following '/'.
+    [<&str>::from(col).as_bytes(), b"0"].concat()
+}
+
+/// Access to the segments in the object store with a local cache.
+pub struct ColdSegments {
+    object_store: Box<dyn ObjectStore>,
+    cache: SegmentCache,
+}
+
+impl ColdSegments {
+    /// Opens the segments in given object store, caching up to `cache_size`
+    /// bytes of them in `cache_dir`.
+    pub fn new(
+        object_store: Box<dyn ObjectStore>,
+        cache_dir: &Path,
+        cache_size: u64,
+    ) -> io::Result<Self> {
+        Ok(Self { object_store, cache: SegmentCache::open(cache_dir, cache_size)? })
+    }
+
+    /// Opens the segments as configured, resolving relative paths from
+    /// `home_dir`.
+    pub fn from_config(home_dir: &Path, config: &ColdStoreSegmentsConfig) -> io::Result<Self> {
+        let object_store: Box<dyn ObjectStore> = match &config.location {
+            ColdStoreSegmentsLocation::S3 { bucket, region, credentials_file } => {
+                Box::new(S3ObjectStore::new(
+                    bucket,
+                    region,
+                    credentials_file.as_deref(),
+                    config.request_timeout.unsigned_abs(),
+                )?)
+            }
+            ColdStoreSegmentsLocation::Filesystem { root_dir } => {
+                Box::new(FilesystemObjectStore::new(home_dir.join(root_dir)))
+            }
+        };
+        Self::new(object_store, &home_dir.join(&config.cache_path), config.cache_size.as_u64())
+    }
+
+    /// Reads the value the pointer points to.
+    ///
+    /// The checksum of the value is verified. On mismatch, the cached blocks
+    /// of the value are dropped and the value is fetched again.
+    pub(crate) fn read(&self, pointer: &ColdSegmentPointer) -> io::Result<Vec<u8>> {
+        let value = self.read_unchecked(pointer)?;
+        if crc32fast::hash(&value) == pointer.crc32 {
+            return Ok(value);
+        }
+        tracing::warn!(target: "cold_store", ?pointer, "Checksum mismatch of a value read from cold segments, fetching it again");
+        for index in Self::block_indices(pointer) {
+            self.cache.remove((pointer.segment, index))?;
+        }
+        let value = self.read_unchecked(pointer)?;
+        if crc32fast::hash(&value) != pointer.crc32 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Checksum mismatch of a value read from cold segments: {pointer:?}"),
+            ));
+        }
+        Ok(value)
+    }
+
+    fn read_unchecked(&self, pointer: &ColdSegmentPointer) -> io::Result<Vec<u8>> {
+        let name = segment_object_name(pointer.segment);
+        let start = pointer.offset;
+        let end = pointer.offset + pointer.len as u64;
+        let mut value = Vec::with_capacity(pointer.len as usize);
+        for index in Self::block_indices(pointer) {
+            let block = self.cache.get((pointer.segment, index), || {
+                let block = self.object_store.get_range(
+                    &name,
+                    index * CACHE_BLOCK_SIZE,
+                    CACHE_BLOCK_SIZE,
+                )?;
+                crate::metrics::COLD_SEGMENT_FETCHED_BYTES.inc_by(block.len() as u64);
+                Ok(block)
+            })?;
+            let block_start = index * CACHE_BLOCK_SIZE;
+            let from = (start.max(block_start) - block_start) as usize;
+            let to = (end.min(block_start + CACHE_BLOCK_SIZE) - block_start) as usize;
+            // A truncated block is detected by the checksum.
+            value.extend_from_slice(block.get(from..to.min(block.len())).unwrap_or_default());
+        }
+        Ok(value)
+    }
+
+    /// Returns the indices of the blocks of the segment holding the value.
+    fn block_indices(pointer: &ColdSegmentPointer) -> std::ops::Range<u64> {
+        let end = pointer.offset + pointer.len as u64;
+        pointer.offset / CACHE_BLOCK_SIZE..end.div_ceil(CACHE_BLOCK_SIZE)
+    }
+
+    /// Uploads the segment starting at `start` from the rest of `file`.
+    fn upload(&self, start: BlockHeight, file: &mut std::fs::File) -> io::Result<()> {
+        self.object_store.put_file(&segment_object_name(start), file)
+    }
+
+    /// Creates an anonymous file in the cache directory, which is removed
+    /// once closed, to stage a segment before uploading it.
+    fn staging_file(&self) -> io::Result<std::fs::File> {
+        tempfile::tempfile_in(self.cache.dir())
+    }
+}
+
+/// Collects the rows of the offloadable cold columns for a range of heights
+/// and seals them into a segment.
+///
+/// The keys of the rows at a height are determined the same way as in
+/// `update_cold_db`, but from the data in cold storage. The rows are written
+/// to a local staging file as they are added, so that only the pointers to
+/// them are kept in memory.
+pub struct ColdSegmentBuilder {
+    start: BlockHeight,
+    file: io::BufWriter<std::fs::File>,
+    /// Length of the data written to `file` so far.
+    len: u64,
+    pointers: HashMap<(DBCol, Vec<u8>), ColdSegmentPointer>,
+    size: usize,
+}
+
+impl ColdSegmentBuilder {
+    /// Creates a builder of the segment starting at `start`, staged in the
+    /// cache directory of the segments of `cold_db`.
+    pub fn new(cold_db: &ColdDB, start: BlockHeight) -> io::Result<Self> {
+        let Some(segments) = cold_db.segments() else {
+            return Err(io::Error::other("Cold segments are not configured"));
+        };
+        let mut file = io::BufWriter::new(segments.staging_file()?);
+        let header =
+            SegmentHeader { magic: SEGMENT_MAGIC, version: SEGMENT_VERSION, start, end: 0 };
+        let header = borsh::to_vec(&header)?;
+        file.write_all(&header)?;
+        Ok(Self { start, file, len: header.len() as u64, pointers: HashMap::new(), size: 0 })
+    }
+
+    /// Adds the locally stored rows for the block at `height` to the segment.
+    /// Returns if the block was added (false only if there is no block at
+    /// `height` in `hot_store` or in `cold_db`).
+    pub fn add_height(
+        &mut self,
+        cold_db: &Arc<ColdDB>,
+        hot_store: &Store,
+        shard_layout: &ShardLayout,
+        height: &BlockHeight,
+    ) -> io::Result<bool> {
+        let _span = tracing::debug_span!(target: "cold_store", "add height to cold segment", height = height);
+
+        let height_key = height.to_le_bytes();
+        let Some(block_hash) = hot_store.get(DBCol::BlockHeight, &height_key)? else {
+            return Ok(false);
+        };
+        let block_hash_key = block_hash.as_slice();
+        let cold_store = Store::new(cold_db.clone());
+        if !cold_store.exists(DBCol::Block, block_hash_key)? {
+            return Ok(false);
+        }
+
+        let key_type_to_keys =
+            get_keys_from_store(&cold_store, shard_layout, &height_key, block_hash_key)?;
+        for col in DBCol::iter().filter(|col| col.is_cold_offloadable()) {
+            for key in combine_keys(&key_type_to_keys, col.key_type()) {
+                // Rows offloaded to earlier segments are not stored locally.
+                let Some(value) = cold_db.get_local_raw_bytes(col, &key)? else {
+                    continue;
+                };
+                self.add_row(col, key, &value)?;
+            }
+        }
+        Ok(true)
+    }
+
+    /// Writes the row to the staging file, unless it's already added.
+    fn add_row(&mut self, col: DBCol, key: Vec<u8>, value: &[u8]) -> io::Result<()> {
+        if self.pointers.contains_key(&(col, key.clone())) {
+            return Ok(());
+        }
+        let entry = borsh::to_vec(&(<&str>::from(col), &key, value))?;
+        self.file.write_all(&entry)?;
+        self.len += entry.len() as u64;
+        // The value is the last field of the entry.
+        let pointer = ColdSegmentPointer {
+            segment: self.start,
+            offset: self.len - value.len() as u64,
+            len: value.len().try_into().map_err(io::Error::other)?,
+            crc32: crc32fast::hash(value),
+        };
+        self.size += key.len() + value.len();
+        self.pointers.insert((col, key), pointer);
+        Ok(())
+    }
+
+    /// Returns the total size of the keys and values added so far.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Uploads the segment for heights up to `end` inclusive and replaces the
+    /// local rows with the pointers to the segment.
+    pub fn seal(self, cold_db: &ColdDB, end: BlockHeight) -> io::Result<()> {
+        let _span = tracing::debug_span!(target: "cold_store", "seal cold segment", start = self.start, end, size = self.size).entered();
+
+        let Some(segments) = cold_db.segments() else {
+            return Err(io::Error::other("Cold segments are not configured"));
+        };
+        if !self.pointers.is_empty() {
+            let mut file = self.file.into_inner().map_err(|err| err.into_error())?;
+            // The end of the segment is known only now.
+            let header = SegmentHeader {
+                magic: SEGMENT_MAGIC,
+                version: SEGMENT_VERSION,
+                start: self.start,
+                end,
+            };
+            file.seek(io::SeekFrom::Start(0))?;
+            file.write_all(&borsh::to_vec(&header)?)?;
+            file.seek(io::SeekFrom::Start(0))?;
+            segments.upload(self.start, &mut file)?;
+        }
+
+        let mut transaction = DBTransaction::new();
+        for ((col, key), pointer) in self.pointers {
+            transaction.set(
+                DBCol::ColdSegmentPointers,
+                pointer_key(col, &key),
+                borsh::to_vec(&pointer)?,
+            );
+            transaction.delete(col, key);
+        }
+        transaction.set(DBCol::BlockMisc, COLD_SEGMENTS_HEAD_KEY.to_vec(), borsh::to_vec(&end)?);
+        cold_db.offload(transaction)?;
+
+        crate::metrics::COLD_SEGMENTS_HEAD_HEIGHT.set(end as i64);
+        Ok(())
+    }
+}
+
+/// Reads the last height offloaded to the segments.
+pub fn get_cold_segments_head(cold_db: &ColdDB) -> io::Result<Option<BlockHeight>> {
+    cold_db
+        .get_raw_bytes(DBCol::BlockMisc, COLD_SEGMENTS_HEAD_KEY)?
+        .as_deref()
+        .map(BlockHeight::try_from_slice)
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::TestDB;
+
+    const ONE: &[u8] = &1i64.to_le_bytes();
+
+    fn create_cold_db(dir: &Path) -> ColdDB {
+        let object_store = FilesystemObjectStore::new(dir.join("objects"));
+        let segments =
+            ColdSegments::new(Box::new(object_store), &dir.join("cache"), 2 * CACHE_BLOCK_SIZE)
+                .unwrap();
+        ColdDB::with_segments(TestDB::new(), Arc::new(segments))
+    }
+
+    fn write(cold_db: &ColdDB, rows: &[(DBCol, &[u8], &[u8])]) {
+        let mut transaction = DBTransaction::new();
+        for (col, key, value) in rows {
+            transaction.set(*col, key.to_vec(), value.to_vec());
+        }
+        cold_db.write(transaction).unwrap();
+    }
+
+    /// Offloads the rows of the columns with given keys to a new segment.
+    fn offload(cold_db: &ColdDB, start: BlockHeight, end: BlockHeight, keys: &[(DBCol, &[u8])]) {
+        let mut builder = ColdSegmentBuilder::new(cold_db, start).unwrap();
+        for (col, key) in keys {
+            let value = cold_db.get_local_raw_bytes(*col, key).unwrap().unwrap();
+            builder.add_row(*col, key.to_vec(), &value).unwrap();
+        }
+        builder.seal(cold_db, end).unwrap();
+    }
+
+    #[test]
+    fn test_read_offloaded() {
+        let dir = tempfile::tempdir().unwrap();
+        let cold_db = create_cold_db(dir.path());
+        let tx_value = [b"tx".as_slice(), ONE].concat();
+        write(
+            &cold_db,
+            &[
+                (DBCol::BlockInfo, b"a", b"value a"),
+                (DBCol::BlockInfo, b"b", b"value b"),
+                (DBCol::BlockInfo, b"c", b"value c"),
+                (DBCol::Transactions, b"tx", &tx_value),
+            ],
+        );
+        offload(&cold_db, 10, 19, &[(DBCol::BlockInfo, b"b"), (DBCol::Transactions, b"tx")]);
+        assert_eq!(get_cold_segments_head(&cold_db).unwrap(), Some(19));
+
+        // The offloaded rows are removed locally but can still be read.
+        assert!(cold_db.get_local_raw_bytes(DBCol::BlockInfo, b"b").unwrap().is_none());
+        let value = cold_db.get_raw_bytes(DBCol::BlockInfo, b"b").unwrap();
+        assert_eq!(value.as_deref(), Some(b"value b".as_slice()));
+        let value = cold_db.get_raw_bytes(DBCol::Transactions, b"tx").unwrap();
+        assert_eq!(value.as_deref(), Some(tx_value.as_slice()));
+        let value = cold_db.get_with_rc_stripped(DBCol::Transactions, b"tx").unwrap();
+        assert_eq!(value.as_deref(), Some(b"tx".as_slice()));
+        assert!(cold_db.get_raw_bytes(DBCol::BlockInfo, b"d").unwrap().is_none());
+
+        // Iterators merge the local and the offloaded rows.
+        let keys = |iter: crate::db::DBIterator| {
+            iter.map(|item| item.unwrap().0.into_vec()).collect::<Vec<_>>()
+        };
+        assert_eq!(
+            keys(cold_db.iter(DBCol::BlockInfo)),
+            vec![b"a".to_vec(), b"b".to_vec(), b"c".to_vec()]
+        );
+        assert_eq!(keys(cold_db.iter_prefix(DBCol::BlockInfo, b"b")), vec![b"b".to_vec()]);
+        assert_eq!(
+            keys(cold_db.iter_range(DBCol::BlockInfo, Some(b"b"), None)),
+            vec![b"b".to_vec(), b"c".to_vec()]
+        );
+        let values = cold_db
+            .iter(DBCol::Transactions)
+            .map(|item| item.unwrap().1.into_vec())
+            .collect::<Vec<_>>();
+        assert_eq!(values, vec![b"tx".to_vec()]);
+    }
+
+    #[test]
+    fn test_read_corrupted() {
+        let dir = tempfile::tempdir().unwrap();
+        let cold_db = create_cold_db(dir.path());
+        write(&cold_db, &[(DBCol::BlockInfo, b"a", b"value a")]);
+        offload(&cold_db, 10, 19, &[(DBCol::BlockInfo, b"a")]);
+        let value = cold_db.get_raw_bytes(DBCol::BlockInfo, b"a").unwrap();
+        assert_eq!(value.as_deref(), Some(b"value a".as_slice()));
+
+        // A corrupted block in the cache is fetched again.
+        let cache_file = dir.path().join("cache").join(format!("{:012}.0", 10));
+        let mut block = std::fs::read(&cache_file).unwrap();
+        *block.last_mut().unwrap() ^= 1;
+        std::fs::write(&cache_file, &block).unwrap();
+        let value = cold_db.get_raw_bytes(DBCol::BlockInfo, b"a").unwrap();
+        assert_eq!(value.as_deref(), Some(b"value a".as_slice()));
+
+        // A corrupted segment in the object store is an error.
+        let object_file = dir.path().join("objects").join(segment_object_name(10));
+        std::fs::write(&object_file, &block).unwrap();
+        std::fs::write(&cache_file, &block).unwrap();
+        let err = cold_db.get_raw_bytes(DBCol::BlockInfo, b"a").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,182 @@
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Size of the parts in which the segments are uploaded to S3. S3 requires
+/// all parts but the last to be at least 5 MiB.
+const S3_UPLOAD_PART_SIZE: u64 = 64 * bytesize::MIB;
+
+/// Object store holding the cold storage segments.
+///
+/// The objects are written once and never modified, so the implementations
+/// don't need to handle concurrent writes to the same object.
+pub trait ObjectStore: Send + Sync {
+    /// Writes the object with given name from the rest of `file`, replacing
+    /// the object if it already exists. The file is read in bounded chunks,
+    /// so that objects larger than memory can be written.
+    fn put_file(&self, name: &str, file: &mut std::fs::File) -> io::Result<()>;
+
+    /// Reads up to `len` bytes of the object starting at `offset`. Fewer bytes
+    /// are returned if the object ends before `offset + len`.
+    fn get_range(&self, name: &str, offset: u64, len: u64) -> io::Result<Vec<u8>>;
+}
+
+/// Object store keeping the objects as files in a local directory. Used in
+/// tests and for object stores mounted as a file system.
+pub struct FilesystemObjectStore {
+    root_dir: PathBuf,
+}
+
+impl FilesystemObjectStore {
+    pub fn new(root_dir: PathBuf) -> Self {
+        Self { root_dir }
+    }
+}
+
+impl ObjectStore for FilesystemObjectStore {
+    fn put_file(&self, name: &str, file: &mut std::fs::File) -> io::Result<()> {
+        let path = self.root_dir.join(name);
+        let parent = path.parent().unwrap_or(&self.root_dir);
+        std::fs::create_dir_all(parent)?;
+        // Write to a temporary file first so that readers never see a
+        // partially written object.
+        let mut tmp_file = tempfile::NamedTempFile::new_in(parent)?;
+        io::copy(file, &mut tmp_file)?;
+        tmp_file.persist(&path)?;
+        Ok(())
+    }
+
+    fn get_range(&self, name: &str, offset: u64, len: u64) -> io::Result<Vec<u8>> {
+        let mut file = std::fs::File::open(self.root_dir.join(name))?;
+        file.seek(SeekFrom::Start(offset))?;
+        let mut data = Vec::new();
+        file.take(len).read_to_end(&mut data)?;
+        Ok(data)
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct S3CredentialsConfig {
+    access_key: String,
+    secret_key: String,
+}
+
+/// Object store backed by an S3-compatible bucket.
+pub struct S3ObjectStore {
+    bucket: s3::Bucket,
+}
+
+impl S3ObjectStore {
+    /// Creates the store for given bucket.
+    ///
+    /// The credentials are read from `credentials_file`, a JSON file with
+    /// `access_key` and `secret_key` fields, or from the environment if the
+    /// file is not specified.
+    pub fn new(
+        bucket: &str,
+        region: &str,
+        credentials_file: Option<&std::path::Path>,
+        timeout: Duration,
+    ) -> io::Result<Self> {
+        let credentials = match credentials_file {
+            Some(credentials_file) => {
+                let config: S3CredentialsConfig =
+                    serde_json::from_slice(&std::fs::read(credentials_file)?)?;
+                s3::creds::Credentials::new(
+                    Some(&config.access_key),
+                    Some(&config.secret_key),
+                    None,
+                    None,
+                    None,
+                )
+            }
+            None => s3::creds::Credentials::default(),
+        }
+        .map_err(io::Error::other)?;
+        let region = region.parse::<s3::Region>().map_err(io::Error::other)?;
+        let mut bucket = s3::Bucket::new(bucket, region, credentials).map_err(io::Error::other)?;
+        // Ensure requests finish in finite amount of time.
+        bucket.set_request_timeout(Some(timeout));
+        Ok(Self { bucket })
+    }
+}
+
+impl ObjectStore for S3ObjectStore {
+    fn put_file(&self, name: &str, file: &mut std::fs::File) -> io::Result<()> {
+        let mut chunk = Vec::new();
+        file.by_ref().take(S3_UPLOAD_PART_SIZE).read_to_end(&mut chunk)?;
+        if (chunk.len() as u64) < S3_UPLOAD_PART_SIZE {
+            let response =
+                self.bucket.put_object_blocking(name, &chunk).map_err(io::Error::other)?;
+            if response.status_code() != 200 {
+                return Err(io::Error::other(format!(
+                    "Failed to put {name} to S3, status code {}",
+                    response.status_code()
+                )));
+            }
+            return Ok(());
+        }
+
+        let content_type = "application/octet-stream";
+        let upload = self
+            .bucket
+            .initiate_multipart_upload_blocking(name, content_type)
+            .map_err(io::Error::other)?;
+        let result = (|| {
+            let mut parts = Vec::new();
+            while !chunk.is_empty() {
+                let part_number = parts.len() as u32 + 1;
+                let part = self
+                    .bucket
+                    .put_multipart_chunk_blocking(
+                        chunk,
+                        name,
+                        part_number,
+                        &upload.upload_id,
+                        content_type,
+                    )
+                    .map_err(io::Error::other)?;
+                parts.push(part);
+                chunk = Vec::new();
+                file.by_ref().take(S3_UPLOAD_PART_SIZE).read_to_end(&mut chunk)?;
+            }
+            let response = self
+                .bucket
+                .complete_multipart_upload_blocking(name, &upload.upload_id, parts)
+                .map_err(io::Error::other)?;
+            if response.status_code() != 200 {
+                return Err(io::Error::other(format!(
+                    "Failed to complete the upload of {name} to S3, status code {}",
+                    response.status_code()
+                )));
+            }
+            Ok(())
+        })();
+        if result.is_err() {
+            // Don't leave the uploaded parts behind, they are billed until
+            // the upload is aborted.
+            if let Err(err) = self.bucket.abort_upload_blocking(name, &upload.upload_id) {
+                tracing::warn!(target: "cold_store", name, ?err, "Failed to abort the upload of a cold segment");
+            }
+        }
+        result
+    }
+
+    fn get_range(&self, name: &str, offset: u64, len: u64) -> io::Result<Vec<u8>> {
+        if len == 0 {
+            return Ok(Vec::new());
+        }
+        // The end of the range is inclusive.
+        let response = self
+            .bucket
+            .get_object_range_blocking(name, offset, Some(offset + len - 1))
+            .map_err(io::Error::other)?;
+        match response.status_code() {
+            200 | 206 => Ok(response.bytes().to_vec()),
+            404 => Err(io::Error::new(io::ErrorKind::NotFound, format!("{name} not found in S3"))),
+            status_code => Err(io::Error::other(format!(
+                "Failed to get {name} from S3, status code {status_code}"
+            ))),
+        }
+    }
+}
@@ -0,0 +1,77 @@
+//! Checkpoints of the hot database for backups.
+//!
+//! A RocksDB checkpoint can only be created by the process which has the
+//! database open for writing.  Rather than opening the database of the node,
+//! `neard database backup` requests a checkpoint by creating
+//! [`CHECKPOINT_REQUEST_FILE_NAME`] in the database directory.  The running
+//! node notices the request within [`REQUEST_CHECK_PERIOD`], creates the
+//! checkpoint in [`CHECKPOINT_DIR_NAME`] and removes the request.  From then
+//! on the checkpoint belongs to the backup, which deletes it once its files are
+//! copied.
+use crate::db::Database;
+use crate::NodeStorage;
+use actix_rt::ArbiterHandle;
+use near_time::Duration;
+use std::path::{Path, PathBuf};
+
+/// Name of the directory inside of the database directory where the checkpoint
+/// is created.  It must be on the same file system as the database so that
+/// the checkpoint can hard link the SST files.
+pub const CHECKPOINT_DIR_NAME: &str = "backup-checkpoint";
+
+/// Name of the file inside of the database directory which requests the node
+/// to create a checkpoint.
+pub const CHECKPOINT_REQUEST_FILE_NAME: &str = "backup-checkpoint.request";
+
+/// How often the node checks whether a checkpoint has been requested.
+pub const REQUEST_CHECK_PERIOD: Duration = Duration::seconds(1);
+
+/// Creates a checkpoint of the database at `db_path` if one has been requested.
+/// Returns whether the checkpoint was created.
+pub fn create_requested_checkpoint(db: &dyn Database, db_path: &Path) -> anyhow::Result<bool> {
+    let request_path = db_path.join(CHECKPOINT_REQUEST_FILE_NAME);
+    if !request_path.exists() {
+        return Ok(false);
+    }
+    // The checkpoint is created under a temporary name so that the backup
+    // never sees an incomplete one.
+    let checkpoint_path = db_path.join(CHECKPOINT_DIR_NAME);
+    let tmp_path = checkpoint_path.with_extension("tmp");
+    if tmp_path.exists() {
+        std::fs::remove_dir_all(&tmp_path)?;
+    }
+    db.create_checkpoint(&tmp_path, None)?;
+    std::fs::rename(&tmp_path, &checkpoint_path)?;
+    std::fs::remove_file(&request_path)?;
+    Ok(true)
+}
+
+/// Spawns a loop which creates the checkpoints of the hot database requested
+/// by the backups.
+pub fn spawn_backup_checkpoint_loop(storage: &NodeStorage, db_path: PathBuf) -> ArbiterHandle {
+    tracing::debug!(target: "store", "Spawning the backup checkpoint loop.");
+    let arbiter = actix_rt::Arbiter::new();
+    let db = storage.hot_storage.clone();
+
+    arbiter.spawn(async move {
+        let mut interval = actix_rt::time::interval(REQUEST_CHECK_PERIOD.unsigned_abs());
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            interval.tick().await;
+            match create_requested_checkpoint(db.as_ref(), &db_path) {
+                Ok(true) => {
+                    tracing::info!(target: "store", path = %db_path.join(CHECKPOINT_DIR_NAME).display(), "Created a checkpoint for a backup");
+                }
+                Ok(false) => {}
+                Err(err) => {
+                    tracing::warn!(target: "store", ?err, "Failed to create a checkpoint for a backup");
+                    // The backup gives up waiting for the checkpoint, there is
+                    // no point in retrying until the next request.
+                    let _ = std::fs::remove_file(db_path.join(CHECKPOINT_REQUEST_FILE_NAME));
+                }
+            }
+        }
+    });
+
+    arbiter.handle()
+}
@@ -317,6 +317,31 @@ pub enum DBCol {
     /// - *Rows*: `CryptoHash`
     /// - *Column type*: `Vec<u8>`
     StateSyncNewChunks,
+    /// Optional secondary index of the access keys by their public key, maintained only if
+    /// `store.index_accounts_by_public_key` is enabled. Entries are added for every access key
+    /// update but never removed, so they have to be checked against the state.
+    /// - *Rows*: `PublicKey || AccountId`
+    /// - *Column type*: empty
+    AccountsByPublicKey,
+    /// Locations of the rows of cold columns which were offloaded from the
+    /// cold storage to segments in an object store, see
+    /// `crate::archive::segments`. Only present in the cold storage.
+    /// - *Rows*: column name || '/' || key of the row in that column
+    /// - *Column type*: `ColdSegmentPointer`
+    ColdSegmentPointers,
+    /// History of the values of the account and contract data trie keys,
+    /// maintained in the cold storage if
+    /// `split_storage.enable_archival_flat_state` is set, see
+    /// `crate::flat::archival`. Only present in the cold storage.
+    /// - *Rows*: escaped trie key || inverted block height, or
+    ///   `crate::flat::archival::STATUS_KEY`
+    /// - *Column type*: `Option<Vec<u8>>`, or `ArchivalFlatStateStatus`
+    ArchivalFlatState,
+    /// Progress of the download of the state parts of a shard during state
+    /// sync, so that it can be resumed after a restart.
+    /// - *Rows*: StateHeaderKey (ShardId || BlockHash)
+    /// - *Content type*: `StatePartsProgress` of the state sync in near-client
+    StatePartsProgress,
 }
 
 /// Defines different logical parts of a db key.
@@ -352,6 +377,9 @@ pub enum DBKeyType {
     ColumnId,
     LatestWitnessesKey,
     LatestWitnessIndex,
+    PublicKey,
+    /// Key of a row in another column. Used in DBCol::ColdSegmentPointers.
+    ColumnKey,
 }
 
 impl DBCol {
@@ -476,6 +504,8 @@ impl DBCol {
             DBCol::InvalidChunks => false,
             // StateParts is only needed while syncing.
             DBCol::StateParts => false,
+            // StatePartsProgress is only needed while syncing.
+            DBCol::StatePartsProgress => false,
             // TrieChanges is only needed for GC.
             DBCol::TrieChanges => false,
             // StateDlInfos is only needed when syncing and it is not immutable.
@@ -523,13 +553,58 @@ impl DBCol {
             | DBCol::FlatStorageStatus
             | DBCol::EpochSyncProof
             | DBCol::StateSyncHashes
-            | DBCol::StateSyncNewChunks => false,
+            | DBCol::StateSyncNewChunks
+            | DBCol::AccountsByPublicKey => false,
+            // Maintained separately in the cold storage.
+            DBCol::ColdSegmentPointers | DBCol::ArchivalFlatState => false,
+        }
+    }
+
+    /// Whether a retention policy can be configured for this cold column, i.e.
+    /// whether its data can be pruned from the cold storage by height.
+    ///
+    /// Block, Chunks and OutcomeIds are needed to determine the keys of the
+    /// data to prune at a given height, so they are kept forever. State and
+    /// StateShardUIdMapping are not associated with a single height.
+    pub const fn is_cold_prunable(&self) -> bool {
+        match self {
+            DBCol::Block
+            | DBCol::Chunks
+            | DBCol::OutcomeIds
+            | DBCol::State
+            | DBCol::StateShardUIdMapping => false,
+            _ => self.is_cold(),
+        }
+    }
+
+    /// Whether the data of this cold column can be offloaded from the cold
+    /// storage to segments in an object store, see `crate::archive::segments`.
+    ///
+    /// State and StateShardUIdMapping are not associated with a single height
+    /// and are always kept in the cold storage.
+    pub const fn is_cold_offloadable(&self) -> bool {
+        match self {
+            DBCol::State | DBCol::StateShardUIdMapping => false,
+            _ => self.is_cold(),
         }
     }
 
     /// Whether this column exists in cold storage.
     pub(crate) const fn is_in_colddb(&self) -> bool {
-        matches!(*self, DBCol::DbVersion | DBCol::BlockMisc) || self.is_cold()
+        matches!(
+            *self,
+            DBCol::DbVersion
+                | DBCol::BlockMisc
+                | DBCol::ColdSegmentPointers
+                | DBCol::ArchivalFlatState
+        ) || self.is_cold()
+    }
+
+    /// Whether the split storage reads this column from the cold storage when
+    /// it's missing in the hot storage. Besides the cold columns, this is the
+    /// archival flat state which is written only to the cold storage.
+    pub(crate) const fn is_read_from_colddb(&self) -> bool {
+        matches!(*self, DBCol::ArchivalFlatState) || self.is_cold()
     }
 
     /// Vector of DBKeyType s concatenation of which results in key for the column.
@@ -599,6 +674,10 @@ impl DBCol {
             DBCol::StateShardUIdMapping => &[DBKeyType::ShardUId],
             DBCol::StateSyncHashes => &[DBKeyType::EpochId],
             DBCol::StateSyncNewChunks => &[DBKeyType::BlockHash],
+            DBCol::AccountsByPublicKey => &[DBKeyType::PublicKey, DBKeyType::AccountId],
+            DBCol::ColdSegmentPointers => &[DBKeyType::ColumnId, DBKeyType::ColumnKey],
+            DBCol::ArchivalFlatState => &[DBKeyType::TrieKey, DBKeyType::BlockHeight],
+            DBCol::StatePartsProgress => &[DBKeyType::ShardId, DBKeyType::BlockHash],
         }
     }
 }
@@ -3,8 +3,11 @@ use crate::trie::{
 };
 use crate::DBCol;
 use near_primitives::shard_layout::ShardUId;
+use near_primitives::types::BlockHeightDelta;
 use near_time::Duration;
+use std::collections::BTreeMap;
 use std::{collections::HashMap, iter::FromIterator};
+use strum::IntoEnumIterator;
 
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 #[serde(default)]
@@ -14,6 +17,10 @@ pub struct StoreConfig {
     /// database.
     pub path: Option<std::path::PathBuf>,
 
+    /// Key-value store the database is kept in.  A database created with one
+    /// backend cannot be opened with another.
+    pub backend: StoreBackend,
+
     /// Collect internal storage layer statistics.
     /// Minor performance impact is expected.
     pub enable_statistics: bool,
@@ -100,6 +107,18 @@ pub struct StoreConfig {
 
     // TODO (#9989): To be phased out in favor of state_snapshot_config
     pub state_snapshot_enabled: bool,
+
+    /// Maintain `DBCol::AccountsByPublicKey`, an index of the access keys of
+    /// the tracked shards by their public key. The index is backfilled from
+    /// flat storage in the background after startup, resuming after restarts,
+    /// and dropped when the option gets disabled.
+    pub index_accounts_by_public_key: bool,
+
+    /// Save in-memory tries to `mem_tries_snapshot` in the database directory
+    /// on graceful shutdown and load them from there on the next start, which
+    /// is much faster than constructing them from flat storage. A snapshot is
+    /// only used if the flat storage head did not move since it was saved.
+    pub mem_tries_snapshot_enabled: bool,
 }
 
 /// Config used to control state snapshot creation. This is used for state sync and resharding.
@@ -120,6 +139,17 @@ pub enum StateSnapshotType {
     EveryEpoch,
 }
 
+/// Embedded key-value store used to keep the database on disk.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StoreBackend {
+    #[default]
+    RocksDB,
+    /// [redb](https://github.com/cberner/redb), a B-tree store written in
+    /// Rust.  Experimental, meant for benchmarking against RocksDB.
+    Redb,
+}
+
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 #[serde(untagged)]
 pub enum MigrationSnapshot {
@@ -180,6 +210,7 @@ impl Default for StoreConfig {
     fn default() -> Self {
         Self {
             path: None,
+            backend: StoreBackend::RocksDB,
             enable_statistics: false,
             enable_statistics_export: true,
 
@@ -271,6 +302,9 @@ impl Default for StoreConfig {
 
             // TODO: To be phased out in favor of state_snapshot_config
             state_snapshot_enabled: false,
+
+            index_accounts_by_public_key: false,
+            mem_tries_snapshot_enabled: false,
         }
     }
 }
@@ -353,6 +387,132 @@ pub struct SplitStorageConfig {
 
     #[serde(default = "default_num_cold_store_read_threads")]
     pub num_cold_store_read_threads: usize,
+
+    /// Retention policies of cold columns, keyed by column name. Columns
+    /// without a policy are kept in cold storage forever.
+    #[serde(default)]
+    pub cold_store_retention: BTreeMap<String, ColdStoreRetentionPolicy>,
+
+    /// Offloading of old data from the cold storage to an object store. If
+    /// not set, all data is kept in the local cold storage.
+    ///
+    /// Once any data was offloaded, this has to stay configured, otherwise
+    /// the offloaded data can't be read.
+    #[serde(default)]
+    pub cold_store_segments: Option<ColdStoreSegmentsConfig>,
+
+    /// Maintain the archival flat state, a history of the account and
+    /// contract data values in the cold storage, so that `ViewAccount` and
+    /// `ViewState` queries at old heights don't need to read the trie. The
+    /// queries use it only if `enable_split_storage_view_client` is set.
+    /// Heights copied to the cold storage before it was enabled are filled in
+    /// with `neard database backfill-archival-flat-state`.
+    #[serde(default)]
+    pub enable_archival_flat_state: bool,
+}
+
+/// Configures offloading of old cold storage data to immutable segments in an
+/// object store, see `near_store::archive::segments`.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct ColdStoreSegmentsConfig {
+    /// Object store the segments are kept in.
+    pub location: ColdStoreSegmentsLocation,
+    /// Number of heights packed into a single segment.
+    #[serde(default = "default_cold_store_segment_length")]
+    pub segment_length: BlockHeightDelta,
+    /// Number of epochs behind the cold head for which the data is kept in
+    /// the local cold storage. Older data is offloaded to the segments.
+    #[serde(default = "default_cold_store_segments_keep_local_epochs")]
+    pub keep_local_epochs: u64,
+    /// Path to the local cache of the segments.  If relative, resolved
+    /// relative to neard home directory.
+    #[serde(default = "default_cold_store_segments_cache_path")]
+    pub cache_path: std::path::PathBuf,
+    /// Maximum size of the local cache of the segments.
+    #[serde(default = "default_cold_store_segments_cache_size")]
+    pub cache_size: bytesize::ByteSize,
+    /// Timeout of a single request to the object store.
+    #[serde(default = "default_cold_store_segments_request_timeout")]
+    #[serde(with = "near_time::serde_duration_as_std")]
+    pub request_timeout: Duration,
+}
+
+/// Object store of the cold storage segments.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub enum ColdStoreSegmentsLocation {
+    S3 {
+        bucket: String,
+        region: String,
+        /// JSON file with the `access_key` and `secret_key`. If not set, the
+        /// credentials are read from the environment.
+        #[serde(default)]
+        credentials_file: Option<std::path::PathBuf>,
+    },
+    Filesystem {
+        root_dir: std::path::PathBuf,
+    },
+}
+
+fn default_cold_store_segment_length() -> BlockHeightDelta {
+    1000
+}
+
+fn default_cold_store_segments_keep_local_epochs() -> u64 {
+    5
+}
+
+fn default_cold_store_segments_cache_path() -> std::path::PathBuf {
+    "cold-segments-cache".into()
+}
+
+fn default_cold_store_segments_cache_size() -> bytesize::ByteSize {
+    bytesize::ByteSize::gib(64)
+}
+
+fn default_cold_store_segments_request_timeout() -> Duration {
+    Duration::seconds(30)
+}
+
+/// How long the data of a column is kept in cold storage.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ColdStoreRetentionPolicy {
+    /// Number of epochs behind the cold head for which the data is kept. Older
+    /// data is removed by the cold store pruner.
+    pub keep_epochs: u64,
+}
+
+impl SplitStorageConfig {
+    /// Returns the columns with a retention policy, or an error if any of the
+    /// policies is invalid.
+    pub fn cold_store_retention_columns(
+        &self,
+    ) -> Result<Vec<(DBCol, ColdStoreRetentionPolicy)>, String> {
+        let mut columns = vec![];
+        for (name, policy) in &self.cold_store_retention {
+            let Some(col) = DBCol::iter().find(|col| <&str>::from(col) == name) else {
+                return Err(format!(
+                    "'config.split_storage.cold_store_retention': unknown column {name}"
+                ));
+            };
+            if !col.is_cold() {
+                return Err(format!(
+                    "'config.split_storage.cold_store_retention': column {name} is not copied to cold storage"
+                ));
+            }
+            if !col.is_cold_prunable() {
+                return Err(format!(
+                    "'config.split_storage.cold_store_retention': column {name} has to be kept in cold storage forever"
+                ));
+            }
+            if policy.keep_epochs == 0 {
+                return Err(format!(
+                    "'config.split_storage.cold_store_retention.{name}.keep_epochs' needs to be greater than 0"
+                ));
+            }
+            columns.push((col, *policy));
+        }
+        Ok(columns)
+    }
 }
 
 impl Default for SplitStorageConfig {
@@ -365,6 +525,9 @@ impl Default for SplitStorageConfig {
                 default_cold_store_initial_migration_loop_sleep_duration(),
             cold_store_loop_sleep_duration: default_cold_store_loop_sleep_duration(),
             num_cold_store_read_threads: default_num_cold_store_read_threads(),
+            cold_store_retention: BTreeMap::new(),
+            cold_store_segments: None,
+            enable_archival_flat_state: false,
         }
     }
 }
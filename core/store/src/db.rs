@@ -6,8 +6,10 @@ use std::io;
 pub(crate) mod rocksdb;
 
 mod colddb;
+mod diffdb;
 mod mixeddb;
 mod recoverydb;
+mod redb;
 mod splitdb;
 
 pub mod refcount;
@@ -17,8 +19,10 @@ mod testdb;
 mod database_tests;
 
 pub use self::colddb::ColdDB;
+pub use self::diffdb::{ColumnDiff, DiffDB};
 pub use self::mixeddb::{MixedDB, ReadOrder};
 pub use self::recoverydb::RecoveryDB;
+pub use self::redb::RedbDB;
 pub use self::rocksdb::RocksDB;
 pub use self::splitdb::SplitDB;
 
@@ -39,6 +43,8 @@ pub const GENESIS_JSON_HASH_KEY: &[u8; 17] = b"GENESIS_JSON_HASH";
 pub const GENESIS_STATE_ROOTS_KEY: &[u8; 19] = b"GENESIS_STATE_ROOTS";
 pub const GENESIS_CONGESTION_INFO_KEY: &[u8] = b"GENESIS_CONGESTION_INFO_KEY";
 pub const COLD_HEAD_KEY: &[u8; 9] = b"COLD_HEAD";
+pub const COLD_COLUMN_TAILS_KEY: &[u8; 17] = b"COLD_COLUMN_TAILS";
+pub const COLD_SEGMENTS_HEAD_KEY: &[u8; 18] = b"COLD_SEGMENTS_HEAD";
 pub const STATE_SYNC_DUMP_KEY: &[u8; 15] = b"STATE_SYNC_DUMP";
 pub const STATE_SNAPSHOT_KEY: &[u8; 18] = b"STATE_SNAPSHOT_KEY";
 
@@ -47,6 +53,9 @@ pub const FLAT_STATE_VALUES_INLINING_MIGRATION_STATUS_KEY: &[u8] =
     b"FLAT_STATE_VALUES_INLINING_MIGRATION_STATUS";
 pub const STATE_TRANSITION_START_HEIGHTS: &[u8] = b"STATE_TRANSITION_START_HEIGHTS";
 pub const LATEST_WITNESSES_INFO: &[u8] = b"LATEST_WITNESSES_INFO";
+pub const ACCOUNTS_BY_PUBLIC_KEY_BACKFILLED_KEY: &[u8] = b"ACCOUNTS_BY_PUBLIC_KEY_BACKFILLED";
+pub const ACCOUNTS_BY_PUBLIC_KEY_BACKFILL_CURSOR_KEY: &[u8] =
+    b"ACCOUNTS_BY_PUBLIC_KEY_BACKFILL_CURSOR";
 
 #[derive(Default, Debug)]
 pub struct DBTransaction {
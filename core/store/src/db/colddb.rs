@@ -1,7 +1,12 @@
+use std::sync::Arc;
+
 use near_o11y::{log_assert, log_assert_fail};
 
-use crate::db::refcount::set_refcount;
-use crate::db::{DBIterator, DBOp, DBSlice, DBTransaction, Database};
+use crate::archive::segments::{
+    pointer_key, pointer_key_prefix, pointer_key_prefix_end, ColdSegmentPointer, ColdSegments,
+};
+use crate::db::refcount::{iter_with_rc_logic, set_refcount};
+use crate::db::{DBIterator, DBOp, DBSlice, DBTransaction, Database, SplitDB};
 use crate::DBCol;
 
 /// A database which provides access to the cold storage.
@@ -10,16 +15,31 @@ use crate::DBCol;
 /// reference counted columns the rc is always set to 1. This struct handles
 /// setting the rc to one transparently to the user.
 ///
-/// Lastly, since no data is ever deleted from cold storage, trying to decrease
-/// reference of a value count or delete data is ignored and if debug assertions
-/// are enabled will cause a panic.
+/// If segments are configured, the rows of old heights may be offloaded from
+/// the local database to an object store through [`ColdDB::offload`]. Rows
+/// missing locally are then read from the segments and the iterators merge
+/// the local rows with the offloaded ones.
+///
+/// Lastly, since data is deleted from cold storage only by the retention
+/// policies through [`ColdDB::prune`] and when offloading it, trying to
+/// decrease reference of a value count or delete data is ignored and if debug
+/// assertions are enabled will cause a panic.
 pub struct ColdDB {
-    cold: std::sync::Arc<dyn Database>,
+    cold: Arc<dyn Database>,
+    segments: Option<Arc<ColdSegments>>,
 }
 
 impl ColdDB {
-    pub fn new(cold: std::sync::Arc<dyn Database>) -> Self {
-        Self { cold }
+    pub fn new(cold: Arc<dyn Database>) -> Self {
+        Self { cold, segments: None }
+    }
+
+    pub fn with_segments(cold: Arc<dyn Database>, segments: Arc<ColdSegments>) -> Self {
+        Self { cold, segments: Some(segments) }
+    }
+
+    pub fn segments(&self) -> Option<&Arc<ColdSegments>> {
+        self.segments.as_ref()
     }
 
     fn err_msg(col: DBCol) -> String {
@@ -34,6 +54,115 @@ impl ColdDB {
         Ok(())
     }
 
+    /// Deletes data of cold columns which is no longer retained.
+    ///
+    /// Unlike [`Database::write`], the transaction must consist only of delete
+    /// operations for columns which can be pruned, see
+    /// [`DBCol::is_cold_prunable`]. The pointers to the deleted rows which
+    /// were offloaded to segments are deleted as well.
+    pub fn prune(&self, mut transaction: DBTransaction) -> std::io::Result<()> {
+        let mut pointer_ops = vec![];
+        for op in &transaction.ops {
+            match op {
+                DBOp::Delete { col, key } if col.is_cold_prunable() => {
+                    if self.segments.is_some() {
+                        pointer_ops.push(DBOp::Delete {
+                            col: DBCol::ColdSegmentPointers,
+                            key: pointer_key(*col, key),
+                        });
+                    }
+                }
+                _ => {
+                    return Err(std::io::Error::other(format!(
+                        "Unexpected operation when pruning cold storage: {op:?}"
+                    )))
+                }
+            }
+        }
+        transaction.ops.extend(pointer_ops);
+        self.cold.write(transaction)
+    }
+
+    /// Replaces the local rows with pointers to the segments they were
+    /// offloaded to.
+    ///
+    /// Unlike [`Database::write`], the transaction must consist only of delete
+    /// operations for columns which can be offloaded, see
+    /// [`DBCol::is_cold_offloadable`], and set operations for the pointers
+    /// and BlockMisc.
+    pub(crate) fn offload(&self, transaction: DBTransaction) -> std::io::Result<()> {
+        for op in &transaction.ops {
+            match op {
+                DBOp::Delete { col, .. } if col.is_cold_offloadable() => {}
+                DBOp::Set { col: DBCol::ColdSegmentPointers | DBCol::BlockMisc, .. } => {}
+                _ => {
+                    return Err(std::io::Error::other(format!(
+                        "Unexpected operation when offloading cold storage: {op:?}"
+                    )))
+                }
+            }
+        }
+        self.cold.write(transaction)
+    }
+
+    /// Returns raw bytes for given `key` stored in the local database, i.e.
+    /// ignoring the rows offloaded to segments.
+    pub(crate) fn get_local_raw_bytes(
+        &self,
+        col: DBCol,
+        key: &[u8],
+    ) -> std::io::Result<Option<DBSlice<'_>>> {
+        Self::check_is_in_colddb(col)?;
+        self.cold.get_raw_bytes(col, key)
+    }
+
+    /// Returns the segments if the rows of the column may have been offloaded.
+    fn offloaded_segments(&self, col: DBCol) -> Option<&ColdSegments> {
+        self.segments.as_deref().filter(|_| col.is_cold_offloadable())
+    }
+
+    /// Reads the offloaded value of given `key` from the segments.
+    fn get_offloaded(&self, col: DBCol, key: &[u8]) -> std::io::Result<Option<Vec<u8>>> {
+        let Some(segments) = self.offloaded_segments(col) else {
+            return Ok(None);
+        };
+        let Some(pointer) =
+            self.cold.get_raw_bytes(DBCol::ColdSegmentPointers, &pointer_key(col, key))?
+        else {
+            return Ok(None);
+        };
+        let pointer = borsh::from_slice::<ColdSegmentPointer>(&pointer)?;
+        segments.read(&pointer).map(Some)
+    }
+
+    /// Iterates over the offloaded rows of the column whose keys are between
+    /// [lower_bound, upper_bound), bypassing reference count decoding.
+    fn iter_offloaded<'a>(
+        &'a self,
+        segments: &'a ColdSegments,
+        col: DBCol,
+        lower_bound: Option<&[u8]>,
+        upper_bound: Option<&[u8]>,
+    ) -> DBIterator<'a> {
+        let prefix = pointer_key_prefix(col);
+        let prefix_len = prefix.len();
+        let lower_bound = [prefix.as_slice(), lower_bound.unwrap_or_default()].concat();
+        let upper_bound = match upper_bound {
+            Some(upper_bound) => [prefix.as_slice(), upper_bound].concat(),
+            None => pointer_key_prefix_end(col),
+        };
+        let iter = self
+            .cold
+            .iter_range(DBCol::ColdSegmentPointers, Some(&lower_bound), Some(&upper_bound))
+            .map(move |item| {
+                let (key, pointer) = item?;
+                let pointer = borsh::from_slice::<ColdSegmentPointer>(&pointer)?;
+                let value = segments.read(&pointer)?;
+                Ok((key[prefix_len..].into(), value.into_boxed_slice()))
+            });
+        Box::new(iter)
+    }
+
     // Checks if the column is the cold db and panics if not.
     fn log_assert_is_in_colddb(col: DBCol) {
         log_assert!(col.is_in_colddb(), "{}", Self::err_msg(col));
@@ -44,31 +173,58 @@ impl Database for ColdDB {
     /// Returns raw bytes for given `key` ignoring any reference count decoding if any.
     fn get_raw_bytes(&self, col: DBCol, key: &[u8]) -> std::io::Result<Option<DBSlice<'_>>> {
         Self::check_is_in_colddb(col)?;
-        self.cold.get_raw_bytes(col, key)
+        if let Some(value) = self.cold.get_raw_bytes(col, key)? {
+            return Ok(Some(value));
+        }
+        Ok(self.get_offloaded(col, key)?.map(DBSlice::from_vec))
     }
 
     /// Returns value for given `key` forcing a reference count decoding.
     fn get_with_rc_stripped(&self, col: DBCol, key: &[u8]) -> std::io::Result<Option<DBSlice<'_>>> {
         Self::check_is_in_colddb(col)?;
-        self.cold.get_with_rc_stripped(col, key)
+        if let Some(value) = self.cold.get_with_rc_stripped(col, key)? {
+            return Ok(Some(value));
+        }
+        Ok(self.get_offloaded(col, key)?.map(DBSlice::from_vec).and_then(DBSlice::strip_refcount))
     }
 
     /// Iterates over all values in a column.
     fn iter<'a>(&'a self, col: DBCol) -> DBIterator<'a> {
         Self::log_assert_is_in_colddb(col);
-        self.cold.iter(col)
+        let Some(segments) = self.offloaded_segments(col) else {
+            return self.cold.iter(col);
+        };
+        SplitDB::merge_iter(
+            self.cold.iter(col),
+            iter_with_rc_logic(col, self.iter_offloaded(segments, col, None, None)),
+        )
     }
 
     /// Iterates over values in a given column whose key has given prefix.
     fn iter_prefix<'a>(&'a self, col: DBCol, key_prefix: &'a [u8]) -> DBIterator<'a> {
         Self::log_assert_is_in_colddb(col);
-        self.cold.iter_prefix(col, key_prefix)
+        let Some(segments) = self.offloaded_segments(col) else {
+            return self.cold.iter_prefix(col, key_prefix);
+        };
+        let offloaded = self
+            .iter_offloaded(segments, col, Some(key_prefix), None)
+            .take_while(|item| item.as_ref().map_or(true, |(key, _)| key.starts_with(key_prefix)));
+        SplitDB::merge_iter(
+            self.cold.iter_prefix(col, key_prefix),
+            iter_with_rc_logic(col, offloaded),
+        )
     }
 
     /// Iterate over items in given column bypassing reference count decoding if any.
     fn iter_raw_bytes<'a>(&'a self, col: DBCol) -> DBIterator<'a> {
         Self::log_assert_is_in_colddb(col);
-        self.cold.iter_raw_bytes(col)
+        let Some(segments) = self.offloaded_segments(col) else {
+            return self.cold.iter_raw_bytes(col);
+        };
+        SplitDB::merge_iter(
+            self.cold.iter_raw_bytes(col),
+            self.iter_offloaded(segments, col, None, None),
+        )
     }
 
     /// Iterate over items in given column whose keys are between [lower_bound, upper_bound)
@@ -79,7 +235,13 @@ impl Database for ColdDB {
         upper_bound: Option<&[u8]>,
     ) -> DBIterator<'a> {
         Self::log_assert_is_in_colddb(col);
-        self.cold.iter_range(col, lower_bound, upper_bound)
+        let Some(segments) = self.offloaded_segments(col) else {
+            return self.cold.iter_range(col, lower_bound, upper_bound);
+        };
+        SplitDB::merge_iter(
+            self.cold.iter_range(col, lower_bound, upper_bound),
+            iter_with_rc_logic(col, self.iter_offloaded(segments, col, lower_bound, upper_bound)),
+        )
     }
 
     /// Atomically applies operations in given transaction.
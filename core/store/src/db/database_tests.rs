@@ -3,24 +3,44 @@
 
 #[cfg(test)]
 mod tests {
-    use std::sync::Arc;
+    use std::num::NonZeroU32;
+    use std::sync::{Arc, LazyLock};
 
     use crate::{
-        db::{DBTransaction, Database, TestDB},
-        DBCol, NodeStorage,
+        checkpoint_hot_storage_and_cleanup_columns,
+        db::{refcount, DBTransaction, Database, TestDB},
+        DBCol, NodeStorage, Store, StoreBackend, StoreConfig,
     };
 
-    // Returns test & rocksDB databases.
-    fn test_and_rocksdb() -> Vec<Arc<dyn Database>> {
-        let (_tmp_dir, opener) = NodeStorage::test_opener();
-        let store = opener.open().unwrap().get_hot_store();
-        vec![TestDB::new(), store.storage.clone()]
+    static REDB_CONFIG: LazyLock<StoreConfig> =
+        LazyLock::new(|| StoreConfig { backend: StoreBackend::Redb, ..StoreConfig::test_config() });
+
+    // Returns an on-disk database opened with given config.
+    fn open_on_disk(config: &'static StoreConfig) -> (tempfile::TempDir, Arc<dyn Database>) {
+        let dir = tempfile::tempdir().unwrap();
+        let store = NodeStorage::opener(dir.path(), config, None).open().unwrap().get_hot_store();
+        (dir, store.storage)
+    }
+
+    // Returns RocksDB & redb databases.
+    fn on_disk() -> Vec<(tempfile::TempDir, Arc<dyn Database>)> {
+        let (tmp_dir, opener) = NodeStorage::test_opener();
+        vec![(tmp_dir, opener.open().unwrap().get_hot_store().storage), open_on_disk(&REDB_CONFIG)]
+    }
+
+    // Returns test, RocksDB & redb databases.
+    fn test_and_on_disk() -> Vec<Arc<dyn Database>> {
+        let mut dbs: Vec<Arc<dyn Database>> = vec![TestDB::new()];
+        // Temporary directories are removed right away but the databases are
+        // kept open.
+        dbs.extend(on_disk().into_iter().map(|(_tmp_dir, db)| db));
+        dbs
     }
 
     /// Tests the behavior of the iterators. Iterators don't really work over cold storage, so we're not testing it here.
     #[test]
     fn test_db_iter() {
-        for db in test_and_rocksdb() {
+        for db in test_and_on_disk() {
             let mut transaction = DBTransaction::new();
             transaction.insert(DBCol::Block, "a".into(), "val_a".into());
             transaction.insert(DBCol::Block, "aa".into(), "val_aa".into());
@@ -40,6 +60,94 @@ mod tests {
                 .map(|data| String::from_utf8(data.unwrap().0.to_vec()).unwrap())
                 .collect();
             assert_eq!(keys, vec!["aa", "aa1"]);
+
+            let keys: Vec<_> = db
+                .iter_prefix(DBCol::Block, "aa".as_bytes())
+                .map(|data| String::from_utf8(data.unwrap().0.to_vec()).unwrap())
+                .collect();
+            assert_eq!(keys, vec!["aa", "aa1"]);
+        }
+    }
+
+    #[test]
+    fn test_db_delete() {
+        for db in test_and_on_disk() {
+            let mut transaction = DBTransaction::new();
+            for key in ["a", "b", "c", "d"] {
+                transaction.set(DBCol::BlockMisc, key.into(), key.into());
+                transaction.set(DBCol::Misc, key.into(), key.into());
+            }
+            db.write(transaction).unwrap();
+
+            let mut transaction = DBTransaction::new();
+            transaction.delete(DBCol::BlockMisc, "a".into());
+            transaction.delete_range(DBCol::BlockMisc, "b".into(), "d".into());
+            transaction.delete_all(DBCol::Misc);
+            db.write(transaction).unwrap();
+
+            let keys: Vec<_> = db.iter(DBCol::BlockMisc).map(|item| item.unwrap().0).collect();
+            assert_eq!(keys, vec![b"d"[..].into()]);
+            assert_eq!(db.iter(DBCol::Misc).count(), 0);
+        }
+    }
+
+    /// Tests that reference counts of values in refcounted columns are merged
+    /// and that values are gone once their reference count drops to zero.
+    #[test]
+    fn test_db_refcount() {
+        let one = NonZeroU32::new(1).unwrap();
+        let add = refcount::add_positive_refcount(b"value", one);
+        let remove = refcount::encode_negative_refcount(one).to_vec();
+        for db in test_and_on_disk() {
+            let mut transaction = DBTransaction::new();
+            transaction.update_refcount(DBCol::State, b"key".to_vec(), add.clone());
+            transaction.update_refcount(DBCol::State, b"key".to_vec(), add.clone());
+            db.write(transaction).unwrap();
+            let value = db.get_raw_bytes(DBCol::State, b"key").unwrap().unwrap();
+            assert_eq!(refcount::decode_value_with_rc(&value), (Some(&b"value"[..]), 2));
+
+            let mut transaction = DBTransaction::new();
+            transaction.update_refcount(DBCol::State, b"key".to_vec(), remove.clone());
+            db.write(transaction).unwrap();
+            let value = db.get_with_rc_stripped(DBCol::State, b"key").unwrap().unwrap();
+            assert_eq!(&*value, b"value");
+            assert_eq!(db.iter(DBCol::State).count(), 1);
+
+            let mut transaction = DBTransaction::new();
+            transaction.update_refcount(DBCol::State, b"key".to_vec(), remove.clone());
+            db.write(transaction).unwrap();
+            assert!(db.get_with_rc_stripped(DBCol::State, b"key").unwrap().is_none());
+            assert_eq!(db.iter(DBCol::State).count(), 0);
+        }
+    }
+
+    /// Tests checkpoints used for state snapshots.
+    #[test]
+    fn test_db_checkpoint() {
+        for (tmp_dir, db) in on_disk() {
+            let mut transaction = DBTransaction::new();
+            transaction.insert(DBCol::Block, b"key".to_vec(), b"block".to_vec());
+            transaction.insert(DBCol::Chunks, b"key".to_vec(), b"chunk".to_vec());
+            db.write(transaction).unwrap();
+
+            let hot_store = Store { storage: db };
+            let checkpoint = checkpoint_hot_storage_and_cleanup_columns(
+                &hot_store,
+                &tmp_dir.path().join("checkpoint"),
+                Some(&[DBCol::Block]),
+            )
+            .unwrap()
+            .get_hot_store();
+            assert_eq!(
+                checkpoint.get(DBCol::Block, b"key").unwrap().as_deref(),
+                Some(&b"block"[..])
+            );
+            assert!(checkpoint.get(DBCol::Chunks, b"key").unwrap().is_none());
+            // The checkpoint is independent of the database.
+            let mut transaction = DBTransaction::new();
+            transaction.delete(DBCol::Block, b"key".to_vec());
+            hot_store.storage.write(transaction).unwrap();
+            assert!(checkpoint.exists(DBCol::Block, b"key").unwrap());
         }
     }
 }
@@ -0,0 +1,236 @@
+use std::collections::HashMap;
+use std::io;
+use std::sync::{Arc, Mutex};
+
+use enum_map::EnumMap;
+
+use crate::db::{DBIterator, DBOp, DBSlice, DBTransaction, Database, StoreStatistics};
+use crate::DBCol;
+
+/// Changes made to a single column.
+///
+/// The sizes are the sizes of the keys plus the values, excluding reference
+/// counts.  Replacing a value counts its old size as removed and the new one
+/// as added.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ColumnDiff {
+    pub rows_added: u64,
+    pub rows_updated: u64,
+    pub rows_removed: u64,
+    pub bytes_added: u64,
+    pub bytes_removed: u64,
+}
+
+impl ColumnDiff {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// A database which records the changes made to every column of the
+/// underlying database, used to report what a migration did.
+///
+/// Every write first reads the current values of the keys it touches, so it
+/// is much slower than writing to the database directly.
+pub struct DiffDB {
+    db: Arc<dyn Database>,
+    diff: Mutex<EnumMap<DBCol, ColumnDiff>>,
+}
+
+impl DiffDB {
+    pub fn new(db: Arc<dyn Database>) -> Self {
+        Self { db, diff: Mutex::new(EnumMap::default()) }
+    }
+
+    /// Returns the changes recorded so far.
+    pub fn diff(&self) -> EnumMap<DBCol, ColumnDiff> {
+        *self.diff.lock().unwrap()
+    }
+
+    /// Reads the value of `key`, treating values of reference counted columns
+    /// with non-positive reference count as missing.
+    fn get_value(&self, col: DBCol, key: &[u8]) -> io::Result<Option<Vec<u8>>> {
+        let value = if col.is_rc() {
+            self.db.get_with_rc_stripped(col, key)?
+        } else {
+            self.db.get_raw_bytes(col, key)?
+        };
+        Ok(value.map(|value| value.to_vec()))
+    }
+}
+
+impl Database for DiffDB {
+    fn get_raw_bytes(&self, col: DBCol, key: &[u8]) -> io::Result<Option<DBSlice<'_>>> {
+        self.db.get_raw_bytes(col, key)
+    }
+
+    fn get_with_rc_stripped(&self, col: DBCol, key: &[u8]) -> io::Result<Option<DBSlice<'_>>> {
+        self.db.get_with_rc_stripped(col, key)
+    }
+
+    fn iter<'a>(&'a self, col: DBCol) -> DBIterator<'a> {
+        self.db.iter(col)
+    }
+
+    fn iter_prefix<'a>(&'a self, col: DBCol, key_prefix: &'a [u8]) -> DBIterator<'a> {
+        self.db.iter_prefix(col, key_prefix)
+    }
+
+    fn iter_range<'a>(
+        &'a self,
+        col: DBCol,
+        lower_bound: Option<&[u8]>,
+        upper_bound: Option<&[u8]>,
+    ) -> DBIterator<'a> {
+        self.db.iter_range(col, lower_bound, upper_bound)
+    }
+
+    fn iter_raw_bytes<'a>(&'a self, col: DBCol) -> DBIterator<'a> {
+        self.db.iter_raw_bytes(col)
+    }
+
+    /// Applies the transaction and records the difference between the values
+    /// of the touched keys before and after it.
+    fn write(&self, transaction: DBTransaction) -> io::Result<()> {
+        let mut diff = EnumMap::<DBCol, ColumnDiff>::default();
+        let mut ranges = Vec::new();
+        let mut keys = Vec::new();
+        for op in &transaction.ops {
+            match op {
+                DBOp::Set { col, key, .. }
+                | DBOp::Insert { col, key, .. }
+                | DBOp::UpdateRefcount { col, key, .. }
+                | DBOp::Delete { col, key } => keys.push((*col, key.clone())),
+                DBOp::DeleteAll { col } => ranges.push((*col, None, None)),
+                DBOp::DeleteRange { col, from, to } => {
+                    ranges.push((*col, Some(from.clone()), Some(to.clone())))
+                }
+            }
+        }
+        // Everything in the deleted ranges is removed, the keys set in the
+        // same transaction are then counted as added.
+        for (col, from, to) in &ranges {
+            for item in self.db.iter_range(*col, from.as_deref(), to.as_deref()) {
+                let (key, value) = item?;
+                diff[*col].rows_removed += 1;
+                diff[*col].bytes_removed += (key.len() + value.len()) as u64;
+            }
+        }
+        let in_ranges = |col: DBCol, key: &[u8]| {
+            ranges.iter().any(|(range_col, from, to)| {
+                *range_col == col
+                    && from.as_deref().map_or(true, |from| from <= key)
+                    && to.as_deref().map_or(true, |to| key < to)
+            })
+        };
+        let mut before = HashMap::new();
+        for (col, key) in keys {
+            if !before.contains_key(&(col, key.clone())) {
+                let value = if in_ranges(col, &key) { None } else { self.get_value(col, &key)? };
+                before.insert((col, key), value);
+            }
+        }
+
+        self.db.write(transaction)?;
+
+        for ((col, key), old_value) in before {
+            let new_value = self.get_value(col, &key)?;
+            let size = |value: &Vec<u8>| (key.len() + value.len()) as u64;
+            let col_diff = &mut diff[col];
+            match (&old_value, &new_value) {
+                (None, Some(new_value)) => {
+                    col_diff.rows_added += 1;
+                    col_diff.bytes_added += size(new_value);
+                }
+                (Some(old_value), None) => {
+                    col_diff.rows_removed += 1;
+                    col_diff.bytes_removed += size(old_value);
+                }
+                (Some(old_value), Some(new_value)) if old_value != new_value => {
+                    col_diff.rows_updated += 1;
+                    col_diff.bytes_removed += size(old_value);
+                    col_diff.bytes_added += size(new_value);
+                }
+                _ => {}
+            }
+        }
+
+        let mut total = self.diff.lock().unwrap();
+        for (col, col_diff) in diff {
+            let total = &mut total[col];
+            total.rows_added += col_diff.rows_added;
+            total.rows_updated += col_diff.rows_updated;
+            total.rows_removed += col_diff.rows_removed;
+            total.bytes_added += col_diff.bytes_added;
+            total.bytes_removed += col_diff.bytes_removed;
+        }
+        Ok(())
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        self.db.flush()
+    }
+
+    fn compact(&self) -> io::Result<()> {
+        self.db.compact()
+    }
+
+    fn get_store_statistics(&self) -> Option<StoreStatistics> {
+        self.db.get_store_statistics()
+    }
+
+    fn create_checkpoint(
+        &self,
+        path: &std::path::Path,
+        columns_to_keep: Option<&[DBCol]>,
+    ) -> anyhow::Result<()> {
+        self.db.create_checkpoint(path, columns_to_keep)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::TestDB;
+
+    #[test]
+    fn test_diff() {
+        let db = DiffDB::new(TestDB::new());
+        let mut transaction = DBTransaction::new();
+        transaction.set(DBCol::Block, vec![1], vec![1; 10]);
+        transaction.set(DBCol::Block, vec![2], vec![2; 10]);
+        transaction.set(DBCol::BlockMisc, vec![1], vec![1; 10]);
+        db.write(transaction).unwrap();
+
+        let mut transaction = DBTransaction::new();
+        transaction.set(DBCol::Block, vec![1], vec![1; 20]);
+        transaction.set(DBCol::Block, vec![2], vec![2; 10]);
+        transaction.delete(DBCol::Block, vec![3]);
+        transaction.delete_all(DBCol::BlockMisc);
+        transaction.set(DBCol::BlockMisc, vec![2], vec![2; 5]);
+        db.write(transaction).unwrap();
+
+        let diff = db.diff();
+        assert_eq!(
+            diff[DBCol::Block],
+            ColumnDiff {
+                rows_added: 2,
+                rows_updated: 1,
+                rows_removed: 0,
+                bytes_added: 11 + 11 + 21,
+                bytes_removed: 11,
+            }
+        );
+        assert_eq!(
+            diff[DBCol::BlockMisc],
+            ColumnDiff {
+                rows_added: 2,
+                rows_updated: 0,
+                rows_removed: 1,
+                bytes_added: 11 + 6,
+                bytes_removed: 11,
+            }
+        );
+        assert!(diff[DBCol::Chunks].is_empty());
+    }
+}
@@ -0,0 +1,337 @@
+use crate::config::Mode;
+use crate::db::{refcount, DBIterator, DBOp, DBSlice, DBTransaction, Database};
+use crate::{metadata, DBCol, StoreConfig, StoreStatistics};
+use ::redb::{Durability, ReadableTable, TableDefinition, TableError, WriteTransaction};
+use anyhow::Context;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::io;
+use std::ops::Bound;
+use std::path::Path;
+use std::sync::RwLock;
+use strum::IntoEnumIterator;
+
+/// Name of the redb file inside of the database directory.
+///
+/// Like with RocksDB, the database is a directory so that paths in the
+/// configuration, migration snapshots and state snapshot checkpoints work the
+/// same regardless of the backend.
+const DB_FILE_NAME: &str = "data.redb";
+
+/// Number of bytes copied in a single transaction when creating a checkpoint.
+const CHECKPOINT_BATCH_BYTES: usize = 256 * 1024 * 1024;
+
+type Table<'txn> = ::redb::Table<'txn, &'static [u8], &'static [u8]>;
+
+/// Database backed by [redb](https://docs.rs/redb), an embedded B-tree store
+/// written in Rust.
+///
+/// Every column is stored in its own table.  redb has no merge operator, so
+/// reference counted columns are updated by reading the existing value within
+/// the write transaction.  Write transactions are serialised by redb which
+/// makes this safe.  Cells whose reference count drops to zero are removed
+/// immediately rather than during compaction.
+pub struct RedbDB {
+    /// The lock is only taken for writing by [`Database::compact`] which needs
+    /// exclusive access to the database.
+    db: RwLock<::redb::Database>,
+    mode: Mode,
+}
+
+impl RedbDB {
+    /// Opens the database.
+    ///
+    /// `path` specifies location of the database directory.  Similarly to
+    /// [`super::RocksDB::open`], path configuration in `store_config` is
+    /// ignored.
+    ///
+    /// Note that redb holds an exclusive lock on the database file, so unlike
+    /// with RocksDB the database cannot be opened by another process, even in
+    /// read-only mode, while the node is running.
+    pub fn open(path: &Path, store_config: &StoreConfig, mode: Mode) -> io::Result<Self> {
+        let file = path.join(DB_FILE_NAME);
+        let exists = file.is_file();
+        if mode.must_create() && exists {
+            let msg = format!("database already exists at {}", path.display());
+            return Err(io::Error::new(io::ErrorKind::AlreadyExists, msg));
+        }
+        if !mode.can_create() && !exists {
+            let msg = format!("database does not exist at {}", path.display());
+            return Err(io::Error::new(io::ErrorKind::NotFound, msg));
+        }
+        std::fs::create_dir_all(path)?;
+        let mut builder = ::redb::Builder::new();
+        builder.set_cache_size(redb_cache_size(store_config));
+        let db = builder.create(&file).map_err(io::Error::other)?;
+        Ok(Self { db: RwLock::new(db), mode })
+    }
+
+    /// Returns whether `path` holds a redb database.
+    pub(crate) fn exists(path: &Path) -> bool {
+        path.join(DB_FILE_NAME).is_file()
+    }
+
+    /// Returns metadata of the database or `None` if the db doesn’t exist.
+    pub(crate) fn get_metadata(
+        path: &Path,
+        config: &StoreConfig,
+    ) -> io::Result<Option<metadata::DbMetadata>> {
+        if !Self::exists(path) {
+            return Ok(None);
+        }
+        let db = Self::open(path, config, Mode::ReadOnly)?;
+        Some(metadata::DbMetadata::read(&db)).transpose()
+    }
+
+    fn begin_read(&self) -> io::Result<::redb::ReadTransaction> {
+        self.db.read().unwrap().begin_read().map_err(io::Error::other)
+    }
+
+    fn begin_write(&self) -> io::Result<WriteTransaction> {
+        if self.mode.read_only() {
+            return Err(io::Error::other("cannot write to database opened in read-only mode"));
+        }
+        self.db.read().unwrap().begin_write().map_err(io::Error::other)
+    }
+
+    fn iter_raw_bytes_internal(
+        &self,
+        col: DBCol,
+        lower_bound: Bound<&[u8]>,
+        upper_bound: Bound<&[u8]>,
+    ) -> DBIterator<'static> {
+        let table = match self.begin_read().and_then(|txn| open_read_table(&txn, col)) {
+            Ok(Some(table)) => table,
+            Ok(None) => return Box::new(std::iter::empty()),
+            Err(err) => return Box::new(std::iter::once(Err(err))),
+        };
+        match table.range::<&[u8]>((lower_bound, upper_bound)) {
+            Ok(range) => Box::new(range.map(|item| {
+                let (key, value) = item.map_err(io::Error::other)?;
+                Ok((key.value().into(), value.value().into()))
+            })),
+            Err(err) => Box::new(std::iter::once(Err(io::Error::other(err)))),
+        }
+    }
+
+    /// Copies contents of the column to `target`, committing every
+    /// [`CHECKPOINT_BATCH_BYTES`] so that the whole column doesn’t have to fit
+    /// in a single transaction.
+    fn copy_column(
+        source: &::redb::ReadTransaction,
+        target: &::redb::Database,
+        col: DBCol,
+    ) -> anyhow::Result<()> {
+        let Some(source) = open_read_table(source, col)? else {
+            return Ok(());
+        };
+        let mut items = source.range::<&[u8]>(..)?.peekable();
+        while items.peek().is_some() {
+            let txn = target.begin_write()?;
+            {
+                let mut table = txn.open_table(table(col))?;
+                let mut batch_bytes = 0;
+                while batch_bytes < CHECKPOINT_BATCH_BYTES {
+                    let Some(item) = items.next() else { break };
+                    let (key, value) = item?;
+                    table.insert(key.value(), value.value())?;
+                    batch_bytes += key.value().len() + value.value().len();
+                }
+            }
+            txn.commit()?;
+        }
+        Ok(())
+    }
+}
+
+impl Database for RedbDB {
+    fn get_raw_bytes(&self, col: DBCol, key: &[u8]) -> io::Result<Option<DBSlice<'_>>> {
+        let Some(table) = open_read_table(&self.begin_read()?, col)? else {
+            return Ok(None);
+        };
+        let value = table.get(key).map_err(io::Error::other)?;
+        Ok(value.map(|value| DBSlice::from_vec(value.value().to_vec())))
+    }
+
+    fn iter_raw_bytes(&self, col: DBCol) -> DBIterator {
+        self.iter_raw_bytes_internal(col, Bound::Unbounded, Bound::Unbounded)
+    }
+
+    fn iter(&self, col: DBCol) -> DBIterator {
+        refcount::iter_with_rc_logic(col, self.iter_raw_bytes(col))
+    }
+
+    fn iter_prefix(&self, col: DBCol, key_prefix: &[u8]) -> DBIterator {
+        let prefix = key_prefix.to_vec();
+        let iter = self
+            .iter_raw_bytes_internal(col, Bound::Included(key_prefix), Bound::Unbounded)
+            .take_while(move |item| {
+                item.as_ref().map_or(true, |(key, _)| key.starts_with(&prefix))
+            });
+        refcount::iter_with_rc_logic(col, iter)
+    }
+
+    fn iter_range<'a>(
+        &'a self,
+        col: DBCol,
+        lower_bound: Option<&[u8]>,
+        upper_bound: Option<&[u8]>,
+    ) -> DBIterator<'a> {
+        let lower = lower_bound.map_or(Bound::Unbounded, Bound::Included);
+        let upper = upper_bound.map_or(Bound::Unbounded, Bound::Excluded);
+        refcount::iter_with_rc_logic(col, self.iter_raw_bytes_internal(col, lower, upper))
+    }
+
+    #[tracing::instrument(target = "store::db::redb", level = "trace", "RedbDB::write", skip_all)]
+    fn write(&self, transaction: DBTransaction) -> io::Result<()> {
+        let mut txn = self.begin_write()?;
+        // Similarly to RocksDB writes which don’t sync the WAL, a crash may
+        // lose the latest transactions but leaves the database consistent.
+        // Data is synced to disk on `flush`.
+        txn.set_durability(Durability::Eventual);
+        {
+            let mut tables = HashMap::new();
+            for op in transaction.ops {
+                let table = open_write_table(&txn, &mut tables, op.col())?;
+                match op {
+                    DBOp::Set { col: _, key, value } => {
+                        table.insert(key.as_slice(), value.as_slice()).map_err(io::Error::other)?;
+                    }
+                    DBOp::Insert { col, key, value } => {
+                        if cfg!(debug_assertions) {
+                            if let Ok(Some(old_value)) = table.get(key.as_slice()) {
+                                super::assert_no_overwrite(col, &key, &value, old_value.value())
+                            }
+                        }
+                        table.insert(key.as_slice(), value.as_slice()).map_err(io::Error::other)?;
+                    }
+                    DBOp::UpdateRefcount { col: _, key, value } => {
+                        let existing = table
+                            .get(key.as_slice())
+                            .map_err(io::Error::other)?
+                            .map(|existing| existing.value().to_vec());
+                        let operands = [value.as_slice()];
+                        let merged = refcount::refcount_merge(existing.as_deref(), operands);
+                        if merged.is_empty() {
+                            table.remove(key.as_slice()).map_err(io::Error::other)?;
+                        } else {
+                            debug_assert!(
+                                refcount::decode_value_with_rc(&merged).1 > 0,
+                                "Inserting value with non-positive refcount"
+                            );
+                            table
+                                .insert(key.as_slice(), merged.as_slice())
+                                .map_err(io::Error::other)?;
+                        }
+                    }
+                    DBOp::Delete { col: _, key } => {
+                        table.remove(key.as_slice()).map_err(io::Error::other)?;
+                    }
+                    DBOp::DeleteAll { col: _ } => {
+                        table.retain(|_, _| false).map_err(io::Error::other)?;
+                    }
+                    DBOp::DeleteRange { col: _, from, to } => {
+                        if from < to {
+                            table
+                                .retain_in(from.as_slice()..to.as_slice(), |_, _| false)
+                                .map_err(io::Error::other)?;
+                        }
+                    }
+                }
+            }
+        }
+        txn.commit().map_err(io::Error::other)
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        if self.mode.read_only() {
+            return Ok(());
+        }
+        // An empty transaction with the default, immediate, durability makes
+        // all previous transactions persistent.
+        self.begin_write()?.commit().map_err(io::Error::other)
+    }
+
+    #[tracing::instrument(target = "store::db::redb", level = "info", "RedbDB::compact", skip_all)]
+    fn compact(&self) -> io::Result<()> {
+        // Compaction fails if there are any live transactions, e.g. iterators
+        // which haven’t been dropped yet.
+        self.db.write().unwrap().compact().map_err(io::Error::other)?;
+        Ok(())
+    }
+
+    fn get_store_statistics(&self) -> Option<StoreStatistics> {
+        None
+    }
+
+    /// Unlike RocksDB checkpoints which hard link the files, this copies the
+    /// data to a new database so it takes time proportional to the size of
+    /// the kept columns.
+    #[tracing::instrument(
+        target = "store::db::redb",
+        level = "debug",
+        "RedbDB::create_checkpoint",
+        skip_all,
+        fields(path = %path.display()),
+    )]
+    fn create_checkpoint(
+        &self,
+        path: &std::path::Path,
+        columns_to_keep: Option<&[DBCol]>,
+    ) -> anyhow::Result<()> {
+        std::fs::create_dir(path)
+            .with_context(|| format!("failed to create checkpoint at {}", path.display()))?;
+        let target = ::redb::Database::create(path.join(DB_FILE_NAME))
+            .with_context(|| format!("failed to create checkpoint at {}", path.display()))?;
+        // All columns are copied from the same read transaction so the
+        // checkpoint is consistent.
+        let source = self.begin_read()?;
+        for col in DBCol::iter() {
+            // DbVersion is always kept since DBOpener::get_metadata() expects
+            // it to be there.
+            let keep = columns_to_keep.map_or(true, |columns| columns.contains(&col));
+            if keep || col == DBCol::DbVersion {
+                Self::copy_column(&source, &target, col).with_context(|| {
+                    format!("failed to copy {col} to checkpoint at {}", path.display())
+                })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns size of the page cache.  redb has a single cache for all the tables,
+/// so this is the sum of the cache sizes configured for the columns.
+fn redb_cache_size(store_config: &StoreConfig) -> usize {
+    DBCol::iter().map(|col| store_config.col_cache_size(col).as_u64() as usize).sum()
+}
+
+fn table(col: DBCol) -> TableDefinition<'static, &'static [u8], &'static [u8]> {
+    TableDefinition::new(col.into())
+}
+
+/// Opens the table of the column or returns `None` if nothing has been written
+/// to the column yet.
+fn open_read_table(
+    txn: &::redb::ReadTransaction,
+    col: DBCol,
+) -> io::Result<Option<::redb::ReadOnlyTable<&'static [u8], &'static [u8]>>> {
+    match txn.open_table(table(col)) {
+        Ok(table) => Ok(Some(table)),
+        Err(TableError::TableDoesNotExist(_)) => Ok(None),
+        Err(err) => Err(io::Error::other(err)),
+    }
+}
+
+/// Opens the table of the column, creating it if necessary, reusing the table
+/// if it has already been opened in this transaction.
+fn open_write_table<'a, 'txn>(
+    txn: &'txn WriteTransaction,
+    tables: &'a mut HashMap<DBCol, Table<'txn>>,
+    col: DBCol,
+) -> io::Result<&'a mut Table<'txn>> {
+    Ok(match tables.entry(col) {
+        Entry::Occupied(entry) => entry.into_mut(),
+        Entry::Vacant(entry) => entry.insert(txn.open_table(table(col)).map_err(io::Error::other)?),
+    })
+}
@@ -92,6 +92,20 @@ impl RocksDB {
         Self::open_with_columns(path, store_config, mode, temp, &columns)
     }
 
+    /// Opens the database for writing with only the column families which
+    /// already exist, so that unlike [`Self::open`] it doesn't create the
+    /// missing ones.  Used to create checkpoints without changing the
+    /// database, since RocksDB can't create them in read-only mode.
+    pub(crate) fn open_existing_columns(
+        path: &Path,
+        store_config: &StoreConfig,
+        temp: Temperature,
+    ) -> io::Result<Self> {
+        let missing = Self::missing_columns(path)?;
+        let columns = DBCol::iter().filter(|col| !missing.contains(col)).collect_vec();
+        Self::open_with_columns(path, store_config, Mode::ReadWriteExisting, temp, &columns)
+    }
+
     /// Opens the database with given set of column families configured.
     ///
     /// With cold storage, we will need to be able to configure the database
@@ -666,6 +680,13 @@ impl RocksDB {
         Some(metadata::DbMetadata::read(&db)).transpose()
     }
 
+    /// Returns the columns which don’t have column families in the database at
+    /// `path` yet, i.e. which will be created when it’s opened for writing.
+    pub(crate) fn missing_columns(path: &Path) -> io::Result<Vec<DBCol>> {
+        let cfs = DB::list_cf(&Options::default(), path).map_err(io::Error::other)?;
+        Ok(DBCol::iter().filter(|col| !cfs.iter().any(|cf| cf == col_name(*col))).collect())
+    }
+
     /// Gets every int property in CF_PROPERTY_NAMES for every column in DBCol.
     fn get_cf_statistics(&self, result: &mut StoreStatistics) {
         for prop_name in CF_PROPERTY_NAMES.deref() {
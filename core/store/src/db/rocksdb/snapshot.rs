@@ -2,9 +2,10 @@ use std::io;
 
 use ::rocksdb::checkpoint::Checkpoint;
 
-use crate::Temperature;
+use crate::db::{Database, RedbDB};
+use crate::{Mode, StoreBackend, Temperature};
 
-/// Representation of a RocksDB checkpoint.
+/// Representation of a database checkpoint.
 ///
 /// Serves as kind of RAII type which logs information about the checkpoint when
 /// object is dropped if the checkpoint hasn’t been removed beforehand by
@@ -93,9 +94,17 @@ impl Snapshot {
             return Err(SnapshotError::AlreadyExists(snapshot_path));
         }
 
-        let db = super::RocksDB::open(db_path, config, crate::Mode::ReadWriteExisting, temp)?;
-        let cp = Checkpoint::new(&db.db).map_err(io::Error::other)?;
-        cp.create_checkpoint(&snapshot_path)?;
+        match config.backend {
+            StoreBackend::RocksDB => {
+                let db = super::RocksDB::open(db_path, config, Mode::ReadWriteExisting, temp)?;
+                let cp = Checkpoint::new(&db.db).map_err(io::Error::other)?;
+                cp.create_checkpoint(&snapshot_path)?;
+            }
+            StoreBackend::Redb => {
+                let db = RedbDB::open(db_path, config, Mode::ReadWriteExisting)?;
+                db.create_checkpoint(&snapshot_path, None).map_err(io::Error::other)?;
+            }
+        }
 
         Ok(Self(Some(snapshot_path)))
     }
@@ -80,7 +80,7 @@ impl Database for SplitDB {
         if let Some(hot_result) = self.hot.get_raw_bytes(col, key)? {
             return Ok(Some(hot_result));
         }
-        if col.is_cold() {
+        if col.is_read_from_colddb() {
             return self.cold.get_raw_bytes(col, key);
         }
         Ok(None)
@@ -98,7 +98,7 @@ impl Database for SplitDB {
         if let Some(hot_result) = self.hot.get_with_rc_stripped(col, key)? {
             return Ok(Some(hot_result));
         }
-        if col.is_cold() {
+        if col.is_read_from_colddb() {
             return self.cold.get_with_rc_stripped(col, key);
         }
         Ok(None)
@@ -110,7 +110,7 @@ impl Database for SplitDB {
     /// The returned iterator will iterate through items in both the cold store
     /// and the hot store. The items will be deduplicated and sorted.
     fn iter<'a>(&'a self, col: DBCol) -> DBIterator<'a> {
-        if !col.is_cold() {
+        if !col.is_read_from_colddb() {
             return self.hot.iter(col);
         }
 
@@ -123,7 +123,7 @@ impl Database for SplitDB {
     /// The returned iterator will iterate through items in both the cold store
     /// and the hot store. The items will be unique and sorted.
     fn iter_prefix<'a>(&'a self, col: DBCol, key_prefix: &'a [u8]) -> DBIterator<'a> {
-        if !col.is_cold() {
+        if !col.is_read_from_colddb() {
             return self.hot.iter_prefix(col, key_prefix);
         }
 
@@ -147,7 +147,7 @@ impl Database for SplitDB {
         lower_bound: Option<&[u8]>,
         upper_bound: Option<&[u8]>,
     ) -> DBIterator<'a> {
-        if !col.is_cold() {
+        if !col.is_read_from_colddb() {
             return self.hot.iter_range(col, lower_bound, upper_bound);
         }
 
@@ -163,7 +163,7 @@ impl Database for SplitDB {
     /// The returned iterator will iterate through items in both the cold store
     /// and the hot store. The items will be unique and sorted.
     fn iter_raw_bytes<'a>(&'a self, col: DBCol) -> DBIterator<'a> {
-        if !col.is_cold() {
+        if !col.is_read_from_colddb() {
             return self.hot.iter_raw_bytes(col);
         }
 
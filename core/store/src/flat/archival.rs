@@ -0,0 +1,514 @@
+//! Archival flat state keeps the history of the values of the account and
+//! contract data trie keys, so that archival nodes can answer `ViewAccount`
+//! and `ViewState` queries at old heights without traversing the trie.
+//!
+//! Every change of a value is stored in `DBCol::ArchivalFlatState` under the
+//! escaped trie key followed by the inverted block height. The versions of a
+//! key are thus ordered from the newest one and the value at a given height is
+//! found with a single seek, while the keys with a common prefix still form a
+//! contiguous range.
+//!
+//! The column lives in the cold storage. It's updated from `DBCol::StateChanges`
+//! whenever a height is copied to the cold storage, see
+//! [`update_archival_flat_state`]. Heights before it was enabled are filled in
+//! by [`backfill_archival_flat_state`].
+
+use crate::adapter::StoreAdapter;
+use crate::db::{ColdDB, DBTransaction, Database};
+use crate::{DBCol, Store, Trie, TrieDBStorage};
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_primitives::hash::CryptoHash;
+use near_primitives::shard_layout::get_block_shard_uid_rev;
+use near_primitives::trie_key::col;
+use near_primitives::types::chunk_extra::ChunkExtra;
+use near_primitives::types::{BlockHeight, RawStateChangesWithTrieKey};
+use std::io;
+use std::sync::Arc;
+
+/// Columns of the trie keys whose history is kept.
+const ARCHIVED_TRIE_KEY_COLUMNS: [u8; 2] = [col::ACCOUNT, col::CONTRACT_DATA];
+
+/// Ends the escaped trie key in the keys of the column. The zero bytes of the
+/// trie key are escaped as `[0, 0xff]`, so the terminator sorts before any
+/// continuation of the trie key.
+const TRIE_KEY_TERMINATOR: [u8; 2] = [0, 0];
+
+/// Key of the [`ArchivalFlatStateStatus`] in the column. It's kept next to
+/// the data rather than in `DBCol::BlockMisc`, which the split storage doesn't
+/// read from the cold storage. The escaped trie keys start with the trie key
+/// column byte, so they never collide with it.
+pub(crate) const STATUS_KEY: &[u8] = b"STATUS";
+
+/// Number of rows written at once when backfilling.
+const BACKFILL_BATCH_SIZE: usize = 10_000;
+
+/// Heights covered by the archival flat state, stored in the cold storage.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ArchivalFlatStateStatus {
+    /// First height whose changes are stored.
+    pub start: BlockHeight,
+    /// Last height whose changes are stored, normally the cold head.
+    pub head: BlockHeight,
+    /// Whether the values before `start` are stored as well. Otherwise only
+    /// the values which changed since `start` can be looked up.
+    pub complete: bool,
+}
+
+pub fn is_archived_trie_key(trie_key: &[u8]) -> bool {
+    trie_key.first().is_some_and(|col| ARCHIVED_TRIE_KEY_COLUMNS.contains(col))
+}
+
+fn encode_trie_key(trie_key: &[u8], key: &mut Vec<u8>) {
+    for &byte in trie_key {
+        key.push(byte);
+        if byte == 0 {
+            key.push(0xff);
+        }
+    }
+}
+
+fn archival_flat_state_key(trie_key: &[u8], height: BlockHeight) -> Vec<u8> {
+    let mut key = Vec::with_capacity(trie_key.len() + 2 + 8);
+    encode_trie_key(trie_key, &mut key);
+    key.extend_from_slice(&TRIE_KEY_TERMINATOR);
+    key.extend_from_slice(&(BlockHeight::MAX - height).to_be_bytes());
+    key
+}
+
+/// Splits the key of the column into the trie key and the height.
+fn decode_key(key: &[u8]) -> io::Result<(Vec<u8>, BlockHeight)> {
+    let invalid_key = || io::Error::other(format!("invalid archival flat state key {key:?}"));
+    let mut trie_key = Vec::with_capacity(key.len());
+    let mut rest = key;
+    loop {
+        match rest {
+            [0, 0, height @ ..] => {
+                let height = <[u8; 8]>::try_from(height).map_err(|_| invalid_key())?;
+                return Ok((trie_key, BlockHeight::MAX - BlockHeight::from_be_bytes(height)));
+            }
+            [0, 0xff, tail @ ..] => {
+                trie_key.push(0);
+                rest = tail;
+            }
+            [byte, tail @ ..] if *byte != 0 => {
+                trie_key.push(*byte);
+                rest = tail;
+            }
+            _ => return Err(invalid_key()),
+        }
+    }
+}
+
+fn get_status(cold_db: &ColdDB) -> io::Result<Option<ArchivalFlatStateStatus>> {
+    cold_db
+        .get_raw_bytes(DBCol::ArchivalFlatState, STATUS_KEY)?
+        .map(|bytes| ArchivalFlatStateStatus::try_from_slice(&bytes))
+        .transpose()
+}
+
+/// Adds the final values of the archived trie keys changed in the block to
+/// the transaction.
+fn add_block_changes(
+    store: &Store,
+    block_hash: &CryptoHash,
+    height: BlockHeight,
+    transaction: &mut DBTransaction,
+) -> io::Result<()> {
+    for item in store.iter_prefix(DBCol::StateChanges, block_hash.as_ref()) {
+        let (key, value) = item?;
+        // The key is the block hash followed by the trie key.
+        if !is_archived_trie_key(&key[CryptoHash::LENGTH..]) {
+            continue;
+        }
+        let changes = RawStateChangesWithTrieKey::try_from_slice(&value)?;
+        let Some(last_change) = changes.changes.last() else {
+            continue;
+        };
+        transaction.set(
+            DBCol::ArchivalFlatState,
+            archival_flat_state_key(&changes.trie_key.to_vec(), height),
+            borsh::to_vec(&last_change.data)?,
+        );
+    }
+    Ok(())
+}
+
+/// Writes the changes of the block at `height` to the archival flat state.
+///
+/// Called when `height` is copied to the cold storage, with `prev_height`
+/// being the previous cold head. If the archival flat state doesn't end at
+/// `prev_height`, e.g. because it was disabled for a while, it's restarted
+/// from `height`. Does nothing if it already ends at `height`.
+pub fn update_archival_flat_state(
+    cold_db: &ColdDB,
+    hot_store: &Store,
+    prev_height: BlockHeight,
+    height: BlockHeight,
+) -> io::Result<()> {
+    let _span =
+        tracing::debug_span!(target: "cold_store", "update_archival_flat_state", height).entered();
+    let block_hash = hot_store
+        .get_ser::<CryptoHash>(DBCol::BlockHeight, &height.to_le_bytes())?
+        .ok_or_else(|| io::Error::other(format!("no block at height {height}")))?;
+
+    let status = match get_status(cold_db)? {
+        // The changes are written before the cold head is updated, so they
+        // are already applied if the node stopped in between.
+        Some(status) if status.head == height => {
+            tracing::debug!(target: "cold_store", ?status, "Archival flat state is already updated");
+            return Ok(());
+        }
+        Some(status) if status.head == prev_height => {
+            ArchivalFlatStateStatus { head: height, ..status }
+        }
+        status => {
+            if let Some(status) = status {
+                tracing::warn!(target: "cold_store", ?status, prev_height, "Archival flat state doesn't end at the cold head, restarting it");
+            }
+            ArchivalFlatStateStatus { start: height, head: height, complete: false }
+        }
+    };
+
+    let mut transaction = DBTransaction::new();
+    add_block_changes(hot_store, &block_hash, height, &mut transaction)?;
+    transaction.set(DBCol::ArchivalFlatState, STATUS_KEY.to_vec(), borsh::to_vec(&status)?);
+    cold_db.write(transaction)
+}
+
+/// Fills the archival flat state with the state at `genesis_height` and the
+/// changes of all heights before its start, so that it can be used for all
+/// heights from genesis on.
+///
+/// `store` has to be the split store, since the genesis state and the changes
+/// are read from it. The cold store loop must not be running.
+pub fn backfill_archival_flat_state(
+    cold_db: &ColdDB,
+    store: &Store,
+    genesis_height: BlockHeight,
+) -> io::Result<()> {
+    let Some(status) = get_status(cold_db)? else {
+        return Err(io::Error::other("archival flat state is not enabled"));
+    };
+    if status.complete {
+        tracing::info!(target: "cold_store", ?status, "Archival flat state is already complete");
+        return Ok(());
+    }
+    let genesis_hash = store
+        .get_ser::<CryptoHash>(DBCol::BlockHeight, &genesis_height.to_le_bytes())?
+        .ok_or_else(|| io::Error::other("genesis block not found"))?;
+
+    let mut transaction = DBTransaction::new();
+    let flush = |transaction: &mut DBTransaction, force: bool| -> io::Result<()> {
+        if force || transaction.ops.len() >= BACKFILL_BATCH_SIZE {
+            cold_db.write(std::mem::take(transaction))?;
+        }
+        Ok(())
+    };
+
+    for item in store.iter_prefix_ser::<ChunkExtra>(DBCol::ChunkExtra, genesis_hash.as_ref()) {
+        let (key, chunk_extra) = item?;
+        let (_, shard_uid) = get_block_shard_uid_rev(&key).map_err(io::Error::other)?;
+        tracing::info!(target: "cold_store", %shard_uid, "Backfilling genesis state");
+        let storage = TrieDBStorage::new(store.trie_store(), shard_uid);
+        let trie = Trie::new(Arc::new(storage), *chunk_extra.state_root(), None);
+        for col in ARCHIVED_TRIE_KEY_COLUMNS {
+            let mut iter = trie.disk_iter().map_err(io::Error::other)?;
+            iter.seek_prefix([col]).map_err(io::Error::other)?;
+            for item in iter {
+                let (trie_key, value) = item.map_err(io::Error::other)?;
+                transaction.set(
+                    DBCol::ArchivalFlatState,
+                    archival_flat_state_key(&trie_key, genesis_height),
+                    borsh::to_vec(&Some(value))?,
+                );
+                flush(&mut transaction, false)?;
+            }
+        }
+    }
+
+    for height in genesis_height + 1..status.start {
+        let Some(block_hash) =
+            store.get_ser::<CryptoHash>(DBCol::BlockHeight, &height.to_le_bytes())?
+        else {
+            continue;
+        };
+        add_block_changes(store, &block_hash, height, &mut transaction)?;
+        flush(&mut transaction, false)?;
+        if height % 10_000 == 0 {
+            tracing::info!(target: "cold_store", height, end = status.start, "Backfilling changes");
+        }
+    }
+
+    let status = ArchivalFlatStateStatus { start: genesis_height, complete: true, ..status };
+    transaction.set(DBCol::ArchivalFlatState, STATUS_KEY.to_vec(), borsh::to_vec(&status)?);
+    flush(&mut transaction, true)?;
+    tracing::info!(target: "cold_store", ?status, "Archival flat state backfilled");
+    Ok(())
+}
+
+/// Reads the values of the archived trie keys at a given block.
+pub struct ArchivalFlatStateView {
+    store: Store,
+    status: ArchivalFlatStateStatus,
+    height: BlockHeight,
+}
+
+impl ArchivalFlatStateView {
+    /// Returns `None` if the archival flat state doesn't cover the block, e.g.
+    /// because it isn't enabled or the block isn't on the canonical chain.
+    ///
+    /// The store has to be the split store, the archival flat state is kept
+    /// only in the cold storage.
+    pub fn new(
+        store: Store,
+        height: BlockHeight,
+        block_hash: &CryptoHash,
+    ) -> io::Result<Option<Self>> {
+        let Some(status) =
+            store.get_ser::<ArchivalFlatStateStatus>(DBCol::ArchivalFlatState, STATUS_KEY)?
+        else {
+            return Ok(None);
+        };
+        if height < status.start || height > status.head {
+            return Ok(None);
+        }
+        let canonical_hash =
+            store.get_ser::<CryptoHash>(DBCol::BlockHeight, &height.to_le_bytes())?;
+        if canonical_hash.as_ref() != Some(block_hash) {
+            return Ok(None);
+        }
+        Ok(Some(Self { store, status, height }))
+    }
+
+    /// Returns the value of the trie key, `Some(None)` if the key doesn't
+    /// exist and `None` if it's not known whether the key exists, in which
+    /// case it has to be read from the trie.
+    pub fn get(&self, trie_key: &[u8]) -> io::Result<Option<Option<Vec<u8>>>> {
+        debug_assert!(is_archived_trie_key(trie_key));
+        let lower_bound = archival_flat_state_key(trie_key, self.height);
+        let mut upper_bound = lower_bound[..lower_bound.len() - 8].to_vec();
+        *upper_bound.last_mut().unwrap() += 1;
+        let mut iter =
+            self.store.iter_range(DBCol::ArchivalFlatState, Some(&lower_bound), Some(&upper_bound));
+        match iter.next().transpose()? {
+            Some((key, value)) => {
+                let (_, height) = decode_key(&key)?;
+                if height < self.status.start && !self.status.complete {
+                    return Ok(None);
+                }
+                Ok(Some(borsh::from_slice(&value)?))
+            }
+            None => Ok(self.status.complete.then_some(None)),
+        }
+    }
+
+    /// Iterates over the existing trie keys with given prefix which are not
+    /// smaller than `start`, in the order of the keys, along with their
+    /// values. Returns `None` if the archival flat state doesn't have the
+    /// values before its start.
+    pub fn iter_prefix<'a>(
+        &'a self,
+        prefix: &'a [u8],
+        start: &[u8],
+    ) -> Option<impl Iterator<Item = io::Result<(Vec<u8>, Vec<u8>)>> + 'a> {
+        if !self.status.complete {
+            return None;
+        }
+        let mut lower_bound = vec![];
+        encode_trie_key(std::cmp::max(prefix, start), &mut lower_bound);
+        let mut encoded_prefix = vec![];
+        encode_trie_key(prefix, &mut encoded_prefix);
+
+        // Every trie key takes at most two seeks, one to find it and one to
+        // its version at the height of the view, regardless of its history.
+        let mut cursor = Some(lower_bound);
+        let iter = std::iter::from_fn(move || loop {
+            let from = cursor.take()?;
+            match self.next_key_value(&encoded_prefix, &from) {
+                Ok(Some((versions_end, item))) => {
+                    cursor = Some(versions_end);
+                    if let Some(item) = item {
+                        return Some(Ok(item));
+                    }
+                }
+                Ok(None) => return None,
+                Err(err) => return Some(Err(err)),
+            }
+        });
+        Some(iter)
+    }
+
+    /// Finds the first trie key with the encoded prefix whose versions are
+    /// stored at or after `from`. Returns the end of its versions, and the
+    /// trie key with its value at the height of the view if it exists then.
+    fn next_key_value(
+        &self,
+        encoded_prefix: &[u8],
+        from: &[u8],
+    ) -> io::Result<Option<(Vec<u8>, Option<(Vec<u8>, Vec<u8>)>)>> {
+        let mut iter = self.store.iter_range(DBCol::ArchivalFlatState, Some(from), None);
+        let Some((key, value)) = iter.next().transpose()? else {
+            return Ok(None);
+        };
+        if !key.starts_with(encoded_prefix) {
+            return Ok(None);
+        }
+        let (trie_key, height) = decode_key(&key)?;
+        // The terminator `[0, 0]` becomes `[0, 1]`, which sorts after all
+        // versions of the trie key but before any continuation of it.
+        let mut versions_end = key[..key.len() - 8].to_vec();
+        *versions_end.last_mut().unwrap() += 1;
+        // The versions of a key are ordered from the newest, so the first one
+        // not newer than the block is the value.
+        let value = if height <= self.height {
+            Some(value)
+        } else {
+            let lower_bound = archival_flat_state_key(&trie_key, self.height);
+            let mut iter = self.store.iter_range(
+                DBCol::ArchivalFlatState,
+                Some(&lower_bound),
+                Some(&versions_end),
+            );
+            iter.next().transpose()?.map(|(_, value)| value)
+        };
+        let value = match value {
+            Some(value) => Option::<Vec<u8>>::try_from_slice(&value)?,
+            None => None,
+        };
+        Ok(Some((versions_end, value.map(|value| (trie_key, value)))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        archival_flat_state_key, decode_key, ArchivalFlatStateStatus, ArchivalFlatStateView,
+        STATUS_KEY,
+    };
+    use crate::test_utils::create_test_store;
+    use crate::DBCol;
+    use near_primitives::hash::CryptoHash;
+    use near_primitives::trie_key::col;
+    use near_primitives::types::BlockHeight;
+
+    fn write(
+        store: &crate::Store,
+        status: ArchivalFlatStateStatus,
+        rows: &[(&[u8], u64, Option<&[u8]>)],
+    ) {
+        let mut store_update = store.store_update();
+        store_update.set_ser(DBCol::ArchivalFlatState, STATUS_KEY, &status).unwrap();
+        for height in status.start..=status.head {
+            store_update
+                .set_ser(DBCol::BlockHeight, &height.to_le_bytes(), &block_hash(height))
+                .unwrap();
+        }
+        for (trie_key, height, value) in rows {
+            store_update
+                .set_ser(
+                    DBCol::ArchivalFlatState,
+                    &archival_flat_state_key(trie_key, *height),
+                    &value.map(<[u8]>::to_vec),
+                )
+                .unwrap();
+        }
+        store_update.commit().unwrap();
+    }
+
+    fn block_hash(height: BlockHeight) -> CryptoHash {
+        CryptoHash::hash_bytes(&height.to_le_bytes())
+    }
+
+    fn view(store: &crate::Store, height: BlockHeight) -> ArchivalFlatStateView {
+        ArchivalFlatStateView::new(store.clone(), height, &block_hash(height)).unwrap().unwrap()
+    }
+
+    #[test]
+    fn test_key_encoding() {
+        for trie_key in [&[col::ACCOUNT, 1, 2][..], &[col::ACCOUNT, 0, 0xff, 0], &[col::ACCOUNT]] {
+            let key = archival_flat_state_key(trie_key, 42);
+            assert_eq!(decode_key(&key).unwrap(), (trie_key.to_vec(), 42));
+        }
+        // All versions of a key come before the keys it's a prefix of and
+        // are ordered from the newest.
+        let mut keys = vec![
+            archival_flat_state_key(&[col::CONTRACT_DATA, 0], 1),
+            archival_flat_state_key(&[col::CONTRACT_DATA], 1),
+            archival_flat_state_key(&[col::CONTRACT_DATA, 0xff], 1),
+            archival_flat_state_key(&[col::CONTRACT_DATA], 2),
+        ];
+        keys.sort();
+        let decoded: Vec<_> = keys.iter().map(|key| decode_key(key).unwrap()).collect();
+        assert_eq!(
+            decoded,
+            vec![
+                (vec![col::CONTRACT_DATA], 2),
+                (vec![col::CONTRACT_DATA], 1),
+                (vec![col::CONTRACT_DATA, 0], 1),
+                (vec![col::CONTRACT_DATA, 0xff], 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_and_iter_prefix() {
+        let store = create_test_store();
+        let a = &[col::CONTRACT_DATA, b'a'][..];
+        let ab = &[col::CONTRACT_DATA, b'a', b'b'][..];
+        let b = &[col::CONTRACT_DATA, b'b'][..];
+        let status = ArchivalFlatStateStatus { start: 10, head: 20, complete: true };
+        write(
+            &store,
+            status,
+            &[
+                (a, 10, Some(b"a10")),
+                (a, 15, Some(b"a15")),
+                (ab, 12, Some(b"ab12")),
+                (ab, 18, None),
+                (b, 16, Some(b"b16")),
+            ],
+        );
+
+        assert_eq!(view(&store, 14).get(a).unwrap(), Some(Some(b"a10".to_vec())));
+        assert_eq!(view(&store, 15).get(a).unwrap(), Some(Some(b"a15".to_vec())));
+        assert_eq!(view(&store, 11).get(ab).unwrap(), Some(None));
+        assert_eq!(view(&store, 19).get(ab).unwrap(), Some(None));
+        assert_eq!(view(&store, 20).get(&[col::ACCOUNT, b'x']).unwrap(), Some(None));
+        assert!(ArchivalFlatStateView::new(store.clone(), 21, &block_hash(21)).unwrap().is_none());
+        assert!(ArchivalFlatStateView::new(store.clone(), 14, &block_hash(0)).unwrap().is_none());
+
+        let items = |height, prefix: &[u8], start: &[u8]| -> Vec<(Vec<u8>, Vec<u8>)> {
+            let view = view(&store, height);
+            let result = view.iter_prefix(prefix, start).unwrap().collect::<Result<_, _>>();
+            result.unwrap()
+        };
+        let prefix = &[col::CONTRACT_DATA][..];
+        assert_eq!(
+            items(16, prefix, &[]),
+            vec![
+                (a.to_vec(), b"a15".to_vec()),
+                (ab.to_vec(), b"ab12".to_vec()),
+                (b.to_vec(), b"b16".to_vec())
+            ]
+        );
+        assert_eq!(items(18, prefix, ab), vec![(b.to_vec(), b"b16".to_vec())]);
+        assert_eq!(
+            items(12, a, &[]),
+            vec![(a.to_vec(), b"a10".to_vec()), (ab.to_vec(), b"ab12".to_vec())]
+        );
+    }
+
+    #[test]
+    fn test_incomplete() {
+        let store = create_test_store();
+        let a = &[col::ACCOUNT, b'a'][..];
+        let b = &[col::ACCOUNT, b'b'][..];
+        let status = ArchivalFlatStateStatus { start: 10, head: 20, complete: false };
+        write(&store, status, &[(a, 5, Some(b"a5")), (a, 12, Some(b"a12"))]);
+
+        assert_eq!(view(&store, 11).get(a).unwrap(), None);
+        assert_eq!(view(&store, 12).get(a).unwrap(), Some(Some(b"a12".to_vec())));
+        assert_eq!(view(&store, 12).get(b).unwrap(), None);
+        assert!(view(&store, 12).iter_prefix(&[col::ACCOUNT], &[]).is_none());
+    }
+}
@@ -25,6 +25,7 @@
 //!                     of the chain formed by these blocks (because we can't access ChainStore
 //!                     inside flat storage).
 
+pub mod archival;
 mod chunk_view;
 pub mod delta;
 mod manager;
@@ -13,14 +13,15 @@ pub use crate::trie::{
     STATE_SNAPSHOT_COLUMNS,
 };
 use adapter::{StoreAdapter, StoreUpdateAdapter};
+use archive::segments::ColdSegments;
 use borsh::{BorshDeserialize, BorshSerialize};
 pub use columns::DBCol;
 use config::ArchivalConfig;
 use db::{SplitDB, GENESIS_CONGESTION_INFO_KEY};
 pub use db::{
-    CHUNK_TAIL_KEY, COLD_HEAD_KEY, FINAL_HEAD_KEY, FORK_TAIL_KEY, GENESIS_JSON_HASH_KEY,
-    GENESIS_STATE_ROOTS_KEY, HEADER_HEAD_KEY, HEAD_KEY, LARGEST_TARGET_HEIGHT_KEY,
-    LATEST_KNOWN_KEY, STATE_SNAPSHOT_KEY, STATE_SYNC_DUMP_KEY, TAIL_KEY,
+    CHUNK_TAIL_KEY, COLD_COLUMN_TAILS_KEY, COLD_HEAD_KEY, COLD_SEGMENTS_HEAD_KEY, FINAL_HEAD_KEY,
+    FORK_TAIL_KEY, GENESIS_JSON_HASH_KEY, GENESIS_STATE_ROOTS_KEY, HEADER_HEAD_KEY, HEAD_KEY,
+    LARGEST_TARGET_HEIGHT_KEY, LATEST_KNOWN_KEY, STATE_SNAPSHOT_KEY, STATE_SYNC_DUMP_KEY, TAIL_KEY,
 };
 use metadata::{DbKind, DbVersion, KIND_KEY, VERSION_KEY};
 use near_crypto::PublicKey;
@@ -46,8 +47,10 @@ use std::sync::LazyLock;
 use std::{fmt, io};
 use strum;
 
+pub mod accounts_by_public_key;
 pub mod adapter;
 pub mod archive;
+pub mod backup_checkpoint;
 mod columns;
 pub mod config;
 pub mod contract;
@@ -63,9 +66,10 @@ mod sync_utils;
 pub mod test_utils;
 pub mod trie;
 
-pub use crate::config::{Mode, StoreConfig};
+pub use crate::config::{Mode, StoreBackend, StoreConfig};
 pub use crate::opener::{
-    checkpoint_hot_storage_and_cleanup_columns, StoreMigrator, StoreOpener, StoreOpenerError,
+    checkpoint_hot_storage_and_cleanup_columns, DatabaseDryRun, MigrationDryRun, StoreMigrator,
+    StoreOpener, StoreOpenerError,
 };
 
 /// Specifies temperature of a storage.
@@ -134,15 +138,16 @@ impl NodeStorage {
     }
 
     /// Constructs new object backed by given database.
-    fn from_rocksdb(
-        hot_storage: crate::db::RocksDB,
-        cold_storage: Option<crate::db::RocksDB>,
+    fn from_databases(
+        hot_storage: Arc<dyn Database>,
+        cold_storage: Option<Arc<dyn Database>>,
+        cold_segments: Option<Arc<ColdSegments>>,
     ) -> Self {
-        let hot_storage = Arc::new(hot_storage);
-        let cold_storage = cold_storage.map(|storage| Arc::new(storage));
-
         let cold_db = if let Some(cold_storage) = cold_storage {
-            Some(Arc::new(crate::db::ColdDB::new(cold_storage)))
+            Some(Arc::new(match cold_segments {
+                Some(segments) => crate::db::ColdDB::with_segments(cold_storage, segments),
+                None => crate::db::ColdDB::new(cold_storage),
+            }))
         } else {
             None
         };
@@ -233,6 +233,49 @@ pub static COLD_MIGRATION_READS: LazyLock<IntCounterVec> = LazyLock::new(|| {
 pub static COLD_HEAD_HEIGHT: LazyLock<IntGauge> = LazyLock::new(|| {
     try_create_int_gauge("near_cold_head_height", "Height of the head of cold storage").unwrap()
 });
+pub static COLD_COLUMN_TAIL_HEIGHT: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    try_create_int_gauge_vec(
+        "near_cold_column_tail_height",
+        "Lowest height retained in cold storage for columns with a retention policy",
+        &["col"],
+    )
+    .unwrap()
+});
+pub static COLD_SEGMENTS_HEAD_HEIGHT: LazyLock<IntGauge> = LazyLock::new(|| {
+    try_create_int_gauge(
+        "near_cold_segments_head_height",
+        "Highest height of the cold storage data offloaded to segments in the object store",
+    )
+    .unwrap()
+});
+pub static COLD_SEGMENT_FETCHED_BYTES: LazyLock<IntCounter> = LazyLock::new(|| {
+    try_create_int_counter(
+        "near_cold_segment_fetched_bytes",
+        "Number of bytes of the cold storage segments fetched from the object store",
+    )
+    .unwrap()
+});
+pub static COLD_SEGMENT_CACHE_HITS: LazyLock<IntCounter> = LazyLock::new(|| {
+    try_create_int_counter(
+        "near_cold_segment_cache_hits",
+        "Number of reads of the cold storage segments served from the local cache",
+    )
+    .unwrap()
+});
+pub static COLD_SEGMENT_CACHE_MISSES: LazyLock<IntCounter> = LazyLock::new(|| {
+    try_create_int_counter(
+        "near_cold_segment_cache_misses",
+        "Number of reads of the cold storage segments fetched from the object store",
+    )
+    .unwrap()
+});
+pub static COLD_SEGMENT_CACHE_SIZE: LazyLock<IntGauge> = LazyLock::new(|| {
+    try_create_int_gauge(
+        "near_cold_segment_cache_size",
+        "Total size in bytes of the locally cached cold storage segments",
+    )
+    .unwrap()
+});
 pub static COLD_COPY_DURATION: LazyLock<Histogram> = LazyLock::new(|| {
     try_create_histogram(
         "near_cold_copy_duration",
@@ -1,8 +1,11 @@
-use crate::config::ArchivalConfig;
+use crate::archive::segments::ColdSegments;
+use crate::config::{ArchivalConfig, ColdStoreSegmentsConfig, StoreBackend};
 use crate::db::rocksdb::snapshot::{Snapshot, SnapshotError, SnapshotRemoveError};
 use crate::db::rocksdb::RocksDB;
+use crate::db::{ColumnDiff, Database, DiffDB, RedbDB, COLD_SEGMENTS_HEAD_KEY};
 use crate::metadata::{DbKind, DbMetadata, DbVersion, DB_VERSION};
 use crate::{DBCol, DBTransaction, Mode, NodeStorage, Store, StoreConfig, Temperature};
+use enum_map::EnumMap;
 use std::sync::Arc;
 
 #[derive(Debug, thiserror::Error)]
@@ -108,6 +111,14 @@ pub enum StoreOpenerError {
     /// Checkpointing errors.
     #[error("{0}")]
     CheckpointError(#[source] anyhow::Error),
+
+    /// Some heights of the cold database were offloaded to segments, but the
+    /// segments are not configured, so their data can't be read.
+    #[error(
+        "Cold database has data offloaded to segments up to height {segments_head}, \
+         but split_storage.cold_store_segments is not configured"
+    )]
+    ColdSegmentsNotConfigured { segments_head: u64 },
 }
 
 impl From<SnapshotError> for StoreOpenerError {
@@ -175,6 +186,10 @@ pub struct StoreOpener<'a> {
 
     /// Archival config. This is set to a valid config for archival nodes.
     archival_config: Option<ArchivalConfig<'a>>,
+
+    /// Home directory for resolving the relative paths of the cold segments
+    /// cache and object store.
+    home_dir: std::path::PathBuf,
 }
 
 /// Opener for a single RocksDB instance.
@@ -215,9 +230,15 @@ impl<'a> StoreOpener<'a> {
                 .flatten(),
             archival_config,
             migrator: None,
+            home_dir: home_dir.to_path_buf(),
         }
     }
 
+    /// Returns the config of the cold segments if they are configured.
+    fn cold_segments_config(&self) -> Option<&'a ColdStoreSegmentsConfig> {
+        self.archival_config.as_ref()?.split_storage_config?.cold_store_segments.as_ref()
+    }
+
     /// Returns true is this opener is for an archival node.
     fn is_archive(&self) -> bool {
         self.archival_config.is_some()
@@ -291,7 +312,22 @@ impl<'a> StoreOpener<'a> {
             .transpose()?
             .map(|(db, _)| db);
 
-        let storage = NodeStorage::from_rocksdb(hot_db, cold_db);
+        let cold_segments = match self.cold_segments_config() {
+            Some(config) if cold_db.is_some() => {
+                Some(Arc::new(ColdSegments::from_config(&self.home_dir, config)?))
+            }
+            _ => None,
+        };
+        if let (Some(cold_db), None) = (&cold_db, &cold_segments) {
+            if let Some(segments_head) =
+                cold_db.get_raw_bytes(DBCol::BlockMisc, COLD_SEGMENTS_HEAD_KEY)?
+            {
+                let segments_head = borsh::from_slice(&segments_head)?;
+                return Err(StoreOpenerError::ColdSegmentsNotConfigured { segments_head });
+            }
+        }
+
+        let storage = NodeStorage::from_databases(hot_db, cold_db, cold_segments);
 
         hot_snapshot.remove()?;
         cold_snapshot.remove()?;
@@ -336,6 +372,93 @@ impl<'a> StoreOpener<'a> {
         Ok((hot_snapshot, cold_snapshot))
     }
 
+    /// Runs the migrations on checkpoints of the databases in
+    /// `checkpoint_dir`, leaving the databases themselves untouched.
+    ///
+    /// RocksDB checkpoints consist of hard links to the files of the
+    /// database, so they take little space as long as `checkpoint_dir` is on
+    /// the same file system.  Returns `None` if the databases don’t need to be
+    /// migrated.  Otherwise the caller is responsible for deleting the
+    /// checkpoints once it’s done with them.
+    pub fn dry_run_migrations(
+        &self,
+        checkpoint_dir: &std::path::Path,
+    ) -> Result<Option<MigrationDryRun>, StoreOpenerError> {
+        let version = self.hot.get_metadata()?.ok_or(StoreOpenerError::DbDoesNotExist)?.version;
+        if version == DB_VERSION {
+            return Ok(None);
+        }
+        if version > DB_VERSION {
+            return Err(StoreOpenerError::DbVersionTooNew { got: version, want: DB_VERSION });
+        }
+        let migrator = self
+            .migrator
+            .ok_or(StoreOpenerError::DbVersionMismatch { got: version, want: DB_VERSION })?;
+        if let Err(release) = migrator.check_support(version) {
+            return Err(StoreOpenerError::DbVersionTooOld {
+                got: version,
+                want: DB_VERSION,
+                latest_release: release,
+            });
+        }
+
+        std::fs::create_dir_all(checkpoint_dir)?;
+        let (hot_db, hot) = Self::dry_run_migrations_of(&self.hot, checkpoint_dir, migrator)?;
+        let (cold_db, cold) = match &self.cold {
+            Some(cold) => {
+                let (db, dry_run) = Self::dry_run_migrations_of(cold, checkpoint_dir, migrator)?;
+                (Some(db), Some(dry_run))
+            }
+            None => (None, None),
+        };
+        let storage = NodeStorage::from_databases(hot_db, cold_db, None);
+        Ok(Some(MigrationDryRun { storage, hot, cold }))
+    }
+
+    fn dry_run_migrations_of(
+        opener: &DBOpener,
+        checkpoint_dir: &std::path::Path,
+        migrator: &dyn StoreMigrator,
+    ) -> Result<(Arc<dyn Database>, DatabaseDryRun), StoreOpenerError> {
+        let from_version = opener.get_metadata()?.ok_or(StoreOpenerError::DbDoesNotExist)?.version;
+        let dir_name = if opener.temp == Temperature::Hot { "data" } else { "cold-data" };
+        let checkpoint_path = checkpoint_dir.join(dir_name);
+        if checkpoint_path.exists() {
+            return Err(StoreOpenerError::SnapshotAlreadyExists(checkpoint_path));
+        }
+        // The missing columns are only created in the checkpoint, once it's
+        // opened for writing.
+        let new_columns = opener.missing_columns()?;
+        tracing::info!(target: "db_opener", path=%opener.path.display(), checkpoint_path=%checkpoint_path.display(), "Creating a checkpoint for the migration dry run");
+        opener
+            .open_for_checkpoint()?
+            .create_checkpoint(&checkpoint_path, None)
+            .map_err(StoreOpenerError::CheckpointError)?;
+
+        let checkpoint =
+            DBOpener { path: checkpoint_path.clone(), config: opener.config, temp: opener.temp };
+        let (db, _) = checkpoint.open(Mode::ReadWriteExisting, from_version)?;
+        let diff_db = Arc::new(DiffDB::new(db.clone()));
+        let store = Store { storage: diff_db.clone() };
+        for version in from_version..DB_VERSION {
+            tracing::info!(target: "db_opener", path=%checkpoint_path.display(),
+                           "Migrating the checkpoint from version {} to {}",
+                           version, version + 1);
+            migrator.migrate(&store, version).map_err(StoreOpenerError::MigrationError)?;
+            store.set_db_version(version + 1)?;
+        }
+        db.flush()?;
+
+        let dry_run = DatabaseDryRun {
+            checkpoint_path,
+            from_version,
+            to_version: DB_VERSION,
+            new_columns,
+            diff: diff_db.diff(),
+        };
+        Ok((db, dry_run))
+    }
+
     // Creates the DB if it doesn't exist.
     fn ensure_created(mode: Mode, opener: &DBOpener) -> Result<(), StoreOpenerError> {
         let meta = opener.get_metadata()?;
@@ -351,7 +474,7 @@ impl<'a> StoreOpener<'a> {
                 tracing::info!(target: "db_opener", path=%opener.path.display(), "The database doesn't exist, creating it.");
 
                 let db = opener.create()?;
-                let store = Store { storage: Arc::new(db) };
+                let store = Store { storage: db };
                 store.set_db_version(DB_VERSION)?;
                 return Ok(());
             }
@@ -479,17 +602,36 @@ impl<'a> StoreOpener<'a> {
         version: DbVersion,
     ) -> Result<Store, StoreOpenerError> {
         let (db, _) = opener.open(mode, version)?;
-        let store = Store { storage: Arc::new(db) };
+        let store = Store { storage: db };
         Ok(store)
     }
 
     fn open_store_unsafe(mode: Mode, opener: &DBOpener) -> Result<Store, StoreOpenerError> {
         let db = opener.open_unsafe(mode)?;
-        let store = Store { storage: Arc::new(db) };
+        let store = Store { storage: db };
         Ok(store)
     }
 }
 
+/// Result of [`StoreOpener::dry_run_migrations`].
+pub struct MigrationDryRun {
+    /// Storage backed by the migrated checkpoints.
+    pub storage: NodeStorage,
+    pub hot: DatabaseDryRun,
+    pub cold: Option<DatabaseDryRun>,
+}
+
+/// Migrations run on the checkpoint of a single database.
+pub struct DatabaseDryRun {
+    pub checkpoint_path: std::path::PathBuf,
+    pub from_version: DbVersion,
+    pub to_version: DbVersion,
+    /// Columns which didn’t exist in the database before the migrations.
+    pub new_columns: Vec<DBCol>,
+    /// Changes made by the migrations to every column.
+    pub diff: EnumMap<DBCol, ColumnDiff>,
+}
+
 impl<'a> DBOpener<'a> {
     /// Constructs new opener for a single RocksDB builder.
     ///
@@ -511,7 +653,10 @@ impl<'a> DBOpener<'a> {
     /// introduced, the kind is returned as `None`.  Otherwise, it’s also
     /// fetched and if it’s not there error is returned.
     fn get_metadata(&self) -> std::io::Result<Option<DbMetadata>> {
-        RocksDB::get_metadata(&self.path, self.config)
+        match self.config.backend {
+            StoreBackend::RocksDB => RocksDB::get_metadata(&self.path, self.config),
+            StoreBackend::Redb => RedbDB::get_metadata(&self.path, self.config),
+        }
     }
 
     /// Opens the database in given mode checking expected version and kind.
@@ -530,9 +675,13 @@ impl<'a> DBOpener<'a> {
     /// new version.
     ///
     /// Use [`Self::create`] to create a new database.
-    fn open(&self, mode: Mode, want_version: DbVersion) -> std::io::Result<(RocksDB, DbMetadata)> {
-        let db = RocksDB::open(&self.path, &self.config, mode, self.temp)?;
-        let metadata = DbMetadata::read(&db)?;
+    fn open(
+        &self,
+        mode: Mode,
+        want_version: DbVersion,
+    ) -> std::io::Result<(Arc<dyn Database>, DbMetadata)> {
+        let db = self.open_db(mode)?;
+        let metadata = DbMetadata::read(&*db)?;
         if want_version != metadata.version {
             let msg = format!("unexpected DbVersion {}; expected {want_version}", metadata.version);
             Err(std::io::Error::other(msg))
@@ -545,14 +694,45 @@ impl<'a> DBOpener<'a> {
     ///
     /// This is only suitable when creating the database or setting the version
     /// and kind for the first time.
-    fn open_unsafe(&self, mode: Mode) -> std::io::Result<RocksDB> {
-        let db = RocksDB::open(&self.path, &self.config, mode, self.temp)?;
-        Ok(db)
+    fn open_unsafe(&self, mode: Mode) -> std::io::Result<Arc<dyn Database>> {
+        self.open_db(mode)
     }
 
     /// Creates a new database.
-    fn create(&self) -> std::io::Result<RocksDB> {
-        RocksDB::open(&self.path, &self.config, Mode::Create, self.temp)
+    fn create(&self) -> std::io::Result<Arc<dyn Database>> {
+        self.open_db(Mode::Create)
+    }
+
+    /// Opens the database with the configured backend.
+    fn open_db(&self, mode: Mode) -> std::io::Result<Arc<dyn Database>> {
+        Ok(match self.config.backend {
+            StoreBackend::RocksDB => {
+                Arc::new(RocksDB::open(&self.path, &self.config, mode, self.temp)?)
+            }
+            StoreBackend::Redb => Arc::new(RedbDB::open(&self.path, &self.config, mode)?),
+        })
+    }
+
+    /// Opens the database for creating a checkpoint of it without changing
+    /// its contents or columns.
+    fn open_for_checkpoint(&self) -> std::io::Result<Arc<dyn Database>> {
+        Ok(match self.config.backend {
+            StoreBackend::RocksDB => {
+                Arc::new(RocksDB::open_existing_columns(&self.path, &self.config, self.temp)?)
+            }
+            // The checkpoint is copied from a read transaction.
+            StoreBackend::Redb => Arc::new(RedbDB::open(&self.path, &self.config, Mode::ReadOnly)?),
+        })
+    }
+
+    /// Returns the columns which will be created when the database is opened
+    /// for writing.
+    fn missing_columns(&self) -> std::io::Result<Vec<DBCol>> {
+        match self.config.backend {
+            StoreBackend::RocksDB => RocksDB::missing_columns(&self.path),
+            // redb creates the tables lazily.
+            StoreBackend::Redb => Ok(Vec::new()),
+        }
     }
 
     /// Creates a new snapshot for the database.
@@ -613,8 +793,12 @@ pub fn checkpoint_hot_storage_and_cleanup_columns(
         .create_checkpoint(&checkpoint_path, columns_to_keep)
         .map_err(StoreOpenerError::CheckpointError)?;
 
-    // As only path from config is used in StoreOpener, default config with custom path will do.
+    // As only path and backend from config are used in StoreOpener, default
+    // config with custom path will do.
     let mut config = StoreConfig::default();
+    if RedbDB::exists(&checkpoint_path) {
+        config.backend = StoreBackend::Redb;
+    }
     config.path = Some(checkpoint_path);
     let opener = NodeStorage::opener(checkpoint_base_path, &config, None);
     // This will create all the column families that were dropped by create_checkpoint(),
@@ -705,4 +889,54 @@ mod tests {
         check_keys_existence(&store.get_hot_store(), &DBCol::Chunks, &keys, false);
         check_keys_existence(&store.get_hot_store(), &DBCol::BlockHeader, &keys, false);
     }
+
+    struct TestMigrator;
+
+    impl StoreMigrator for TestMigrator {
+        fn check_support(&self, _version: DbVersion) -> Result<(), &'static str> {
+            Ok(())
+        }
+
+        fn migrate(&self, store: &Store, _version: DbVersion) -> anyhow::Result<()> {
+            let mut update = store.store_update();
+            update.set(DBCol::BlockMisc, b"MIGRATED", &[1]);
+            update.delete(DBCol::Block, &[1]);
+            Ok(update.commit()?)
+        }
+    }
+
+    #[test]
+    fn test_dry_run_migrations() {
+        let (home_dir, opener) = NodeStorage::test_opener();
+        let store = opener.open().unwrap().get_hot_store();
+        let mut update = store.store_update();
+        update.set(DBCol::Block, &[1], &[1; 10]);
+        update.commit().unwrap();
+        store.set_db_version(DB_VERSION - 1).unwrap();
+        drop(store);
+
+        let migrator = TestMigrator;
+        let opener = opener.with_migrator(&migrator);
+        let dry_run = opener.dry_run_migrations(&home_dir.path().join("dry-run")).unwrap().unwrap();
+        assert_eq!(dry_run.hot.from_version, DB_VERSION - 1);
+        assert_eq!(dry_run.hot.to_version, DB_VERSION);
+        assert!(dry_run.cold.is_none());
+        let diff = &dry_run.hot.diff;
+        assert_eq!(diff[DBCol::Block].rows_removed, 1);
+        assert_eq!(diff[DBCol::Block].bytes_removed, 11);
+        assert_eq!(diff[DBCol::BlockMisc].rows_added, 1);
+        assert_eq!(diff[DBCol::DbVersion].rows_updated, 1);
+
+        let checkpoint = dry_run.storage.get_hot_store();
+        assert_eq!(checkpoint.get_db_version().unwrap(), Some(DB_VERSION));
+        assert!(checkpoint.exists(DBCol::BlockMisc, b"MIGRATED").unwrap());
+        drop(checkpoint);
+        drop(dry_run);
+
+        // The database itself is left untouched.
+        assert_eq!(opener.hot.get_metadata().unwrap().unwrap().version, DB_VERSION - 1);
+        let db = opener.hot.open(Mode::ReadOnly, DB_VERSION - 1).unwrap().0;
+        assert!(db.get_raw_bytes(DBCol::Block, &[1]).unwrap().is_some());
+        assert!(db.get_raw_bytes(DBCol::BlockMisc, b"MIGRATED").unwrap().is_none());
+    }
 }
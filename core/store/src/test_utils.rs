@@ -1,4 +1,5 @@
 use crate::adapter::{StoreAdapter, StoreUpdateAdapter};
+use crate::archive::segments::{ColdSegments, FilesystemObjectStore};
 use crate::db::TestDB;
 use crate::flat::{BlockInfo, FlatStorageManager, FlatStorageReadyStatus, FlatStorageStatus};
 use crate::metadata::{DbKind, DbVersion, DB_VERSION};
@@ -63,6 +64,28 @@ pub fn create_test_node_storage_with_cold(
     (storage, hot, cold)
 }
 
+/// Creates an in-memory node storage with ColdDB which offloads data to
+/// segments kept in `segments_dir`.
+pub fn create_test_node_storage_with_cold_segments(
+    version: DbVersion,
+    hot_kind: DbKind,
+    segments_dir: &std::path::Path,
+) -> NodeStorage {
+    let object_store = FilesystemObjectStore::new(segments_dir.join("objects"));
+    let segments =
+        ColdSegments::new(Box::new(object_store), &segments_dir.join("cache"), bytesize::GIB)
+            .unwrap();
+    let storage =
+        NodeStorage::from_databases(TestDB::new(), Some(TestDB::new()), Some(Arc::new(segments)));
+
+    storage.get_hot_store().set_db_version(version).unwrap();
+    storage.get_hot_store().set_db_kind(hot_kind).unwrap();
+    storage.get_cold_store().unwrap().set_db_version(version).unwrap();
+    storage.get_cold_store().unwrap().set_db_kind(DbKind::Cold).unwrap();
+
+    storage
+}
+
 /// Creates an in-memory database.
 pub fn create_test_store() -> Store {
     create_test_node_storage(DB_VERSION, DbKind::RPC).get_hot_store()
@@ -2,6 +2,7 @@ use crate::config::{PrefetchConfig, TrieCacheConfig};
 use crate::StoreConfig;
 use near_primitives::shard_layout::ShardUId;
 use near_primitives::types::AccountId;
+use std::path::PathBuf;
 use std::str::FromStr;
 use tracing::error;
 
@@ -37,6 +38,13 @@ pub struct TrieConfig {
     pub load_mem_tries_for_shards: Vec<ShardUId>,
     /// Whether mem-trie should be loaded for each tracked shard.
     pub load_mem_tries_for_tracked_shards: bool,
+
+    /// Whether to add the updated access keys to `DBCol::AccountsByPublicKey`.
+    pub index_accounts_by_public_key: bool,
+
+    /// Directory to save mem-tries to on shutdown and to load them from on
+    /// startup. `None` disables mem-trie snapshots.
+    pub mem_tries_snapshot_dir: Option<PathBuf>,
 }
 
 impl TrieConfig {
@@ -64,6 +72,7 @@ impl TrieConfig {
         this.kaiching_prefetch_config.clone_from(&config.kaiching_prefetch_config);
         this.load_mem_tries_for_shards.clone_from(&config.load_mem_tries_for_shards);
         this.load_mem_tries_for_tracked_shards = config.load_mem_tries_for_tracked_shards;
+        this.index_accounts_by_public_key = config.index_accounts_by_public_key;
 
         this
     }
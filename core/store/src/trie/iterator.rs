@@ -107,6 +107,14 @@ impl<'a> DiskTrieIterator<'a> {
         self.seek_nibble_slice(NibbleSlice::new(key.as_ref()), true).map(drop)
     }
 
+    /// Position the iterator on the first element with key >= `key`.
+    ///
+    /// Unlike [`Self::seek_prefix`], the iteration isn't limited to the keys
+    /// starting with `key` and continues until the end of the trie.
+    pub fn seek<K: AsRef<[u8]>>(&mut self, key: K) -> Result<(), StorageError> {
+        self.seek_nibble_slice(NibbleSlice::new(key.as_ref()), false).map(drop)
+    }
+
     /// Configures whether the iterator should remember all the nodes its
     /// visiting.
     ///
@@ -526,6 +534,30 @@ mod tests {
         test_iterator(true);
     }
 
+    #[test]
+    fn test_disk_iterator_seek() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            let (trie_changes, map, trie) = gen_random_trie(&mut rng, false);
+            let mut seek_keys: Vec<_> = trie_changes.into_iter().map(|(key, _)| key).collect();
+            seek_keys.push(vec![]);
+            for _ in 0..20 {
+                let alphabet = &b"abcdefgh"[0..rng.gen_range(2..8)];
+                let key_length = rng.gen_range(1..8);
+                seek_keys
+                    .push((0..key_length).map(|_| *alphabet.choose(&mut rng).unwrap()).collect());
+            }
+            for seek_key in seek_keys {
+                let mut iterator = trie.disk_iter().unwrap();
+                iterator.seek(&seek_key).unwrap();
+                let got: Vec<_> = iterator.map(Result::unwrap).collect();
+                let want: Vec<_> =
+                    map.range(seek_key.clone()..).map(|(k, v)| (k.clone(), v.clone())).collect();
+                assert_eq!(got, want, "seek key: {seek_key:x?}");
+            }
+        }
+    }
+
     #[test]
     fn test_iterator_with_prune_condition_base() {
         let mut rng = rand::thread_rng();
@@ -6,6 +6,7 @@ use crate::trie::mem::arena::metrics::{
 };
 use crate::trie::mem::arena::ArenaMemoryMut;
 use crate::trie::mem::flexible_data::encoding::BorshFixedSize;
+use borsh::{BorshDeserialize, BorshSerialize};
 use near_o11y::metrics::IntGauge;
 
 /// Simple bump allocator with freelists.
@@ -75,6 +76,17 @@ pub(crate) const fn allocation_size(size_class: usize) -> usize {
 
 const NUM_ALLOCATION_CLASSES: usize = allocation_class(MAX_ALLOC_SIZE) + 1;
 
+/// The part of the state of an `Allocator` which is not stored in the arena
+/// memory itself. Together with the memory chunks it is enough to restore the
+/// allocator, which is used to save in-memory tries to disk.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub(crate) struct AllocatorState {
+    freelists: Vec<ArenaPos>,
+    next_alloc_pos: ArenaPos,
+    active_allocs_bytes: u64,
+    active_allocs_count: u64,
+}
+
 impl Allocator {
     pub fn new(name: String) -> Self {
         Self {
@@ -103,6 +115,42 @@ impl Allocator {
         allocator
     }
 
+    /// Restores an allocator from the state returned by `state` for the
+    /// memory it was taken from. Returns `None` if the state does not fit the
+    /// number of chunks of the memory.
+    pub(crate) fn from_state(
+        name: String,
+        state: AllocatorState,
+        memory: &STArenaMemory,
+    ) -> Option<Self> {
+        let num_chunks = memory.chunks.len();
+        let is_valid_pos = |pos: &ArenaPos| pos.is_invalid() || pos.chunk() < num_chunks;
+        if state.freelists.len() != NUM_ALLOCATION_CLASSES
+            || !state.freelists.iter().all(is_valid_pos)
+            || !is_valid_pos(&state.next_alloc_pos)
+        {
+            return None;
+        }
+        let mut allocator = Self::new_with_initial_stats(
+            name,
+            state.active_allocs_bytes as usize,
+            state.active_allocs_count as usize,
+        );
+        allocator.freelists = state.freelists.try_into().ok()?;
+        allocator.next_alloc_pos = state.next_alloc_pos;
+        allocator.update_memory_usage_gauge(memory);
+        Some(allocator)
+    }
+
+    pub(crate) fn state(&self) -> AllocatorState {
+        AllocatorState {
+            freelists: self.freelists.to_vec(),
+            next_alloc_pos: self.next_alloc_pos,
+            active_allocs_bytes: self.active_allocs_bytes as u64,
+            active_allocs_count: self.active_allocs_count as u64,
+        }
+    }
+
     pub fn update_memory_usage_gauge(&self, memory: &STArenaMemory) {
         self.memory_usage_gauge.set(memory.chunks.len() as i64 * CHUNK_SIZE as i64);
     }
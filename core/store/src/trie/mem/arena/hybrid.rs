@@ -1,7 +1,7 @@
 use std::convert::From;
 use std::sync::Arc;
 
-use super::alloc::Allocator;
+use super::alloc::{Allocator, AllocatorState};
 use super::frozen::{FrozenArena, FrozenArenaMemory};
 use super::single_thread::{STArena, STArenaMemory};
 use super::{
@@ -122,6 +122,29 @@ impl HybridArena {
         }
     }
 
+    /// Returns the memory chunks and the allocator state, from which the arena
+    /// can be restored by `from_chunks_and_allocator_state`. Arenas with shared
+    /// memory cannot be restored this way, so this panics for them.
+    pub(crate) fn chunks_and_allocator_state(&self) -> (&[Vec<u8>], AllocatorState) {
+        assert!(!self.has_shared_memory(), "Cannot save arena with shared memory");
+        (&self.memory.owned_memory.chunks, self.allocator.state())
+    }
+
+    /// Restores an arena from the parts returned by `chunks_and_allocator_state`.
+    /// Returns `None` if the allocator state does not match the chunks.
+    pub(crate) fn from_chunks_and_allocator_state(
+        name: String,
+        chunks: Vec<Vec<u8>>,
+        allocator_state: AllocatorState,
+    ) -> Option<Self> {
+        let owned_memory = STArenaMemory { chunks };
+        let allocator = Allocator::from_state(name, allocator_state, &owned_memory)?;
+        Some(Self {
+            memory: HybridArenaMemory { owned_memory, shared_memory: Arc::new(Default::default()) },
+            allocator,
+        })
+    }
+
     #[inline]
     pub fn has_shared_memory(&self) -> bool {
         self.memory.chunks_offset() > 0
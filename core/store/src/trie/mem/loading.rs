@@ -2,11 +2,14 @@ use super::arena::single_thread::STArena;
 use super::mem_tries::MemTries;
 use super::node::MemTrieNodeId;
 use crate::adapter::StoreAdapter;
-use crate::flat::FlatStorageStatus;
+use crate::flat::{BlockInfo, FlatStorageStatus};
 use crate::trie::mem::arena::Arena;
 use crate::trie::mem::construction::TrieConstructor;
 use crate::trie::mem::mem_trie_update::TrackingMode;
 use crate::trie::mem::parallel_loader::load_memtrie_in_parallel;
+use crate::trie::mem::snapshot::{
+    load_mem_tries_snapshot, mem_tries_snapshot_path, save_mem_tries_snapshot,
+};
 use crate::trie::ops::insert_delete::GenericTrieUpdateInsertDelete;
 use crate::{DBCol, NibbleSlice, Store};
 use near_primitives::errors::StorageError;
@@ -15,8 +18,9 @@ use near_primitives::shard_layout::{get_block_shard_uid, ShardUId};
 use near_primitives::types::chunk_extra::ChunkExtra;
 use near_primitives::types::{BlockHeight, StateRoot};
 use std::collections::BTreeSet;
+use std::path::Path;
 use std::time::Instant;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 /// Loads a trie from the FlatState column. The returned `MemTries` contains
 /// exactly one trie root.
@@ -108,38 +112,104 @@ fn get_state_root(
     Ok(*chunk_extra.state_root())
 }
 
+fn get_flat_head(store: &Store, shard_uid: ShardUId) -> Result<BlockInfo, StorageError> {
+    match store.flat_store().get_flat_storage_status(shard_uid)? {
+        FlatStorageStatus::Ready(status) => Ok(status.flat_head),
+        other => Err(StorageError::MemTrieLoadingError(format!(
+            "Cannot load memtries when flat storage is not ready for shard {}, actual status: {:?}",
+            shard_uid, other
+        ))),
+    }
+}
+
+/// Loads the memtries of the shard from its snapshot in `snapshot_dir`, if
+/// there is one matching the flat storage head. The snapshot is removed
+/// afterwards, because the memtries diverge from it once blocks get applied.
+fn load_trie_from_snapshot(
+    snapshot_dir: &Path,
+    shard_uid: ShardUId,
+    flat_head: BlockInfo,
+    state_root: StateRoot,
+) -> Option<MemTries> {
+    let path = mem_tries_snapshot_path(snapshot_dir, shard_uid);
+    if !path.exists() {
+        return None;
+    }
+    info!(target: "memtrie", %shard_uid, ?path, "Loading trie from snapshot...");
+    let load_start = Instant::now();
+    let result = load_mem_tries_snapshot(&path, shard_uid, flat_head, state_root);
+    if let Err(err) = std::fs::remove_file(&path) {
+        warn!(target: "memtrie", %shard_uid, ?path, %err, "Failed to remove memtrie snapshot");
+    }
+    match result {
+        Ok(mem_tries) => {
+            info!(target: "memtrie", %shard_uid, "Done loading trie from snapshot, took {:?}", load_start.elapsed());
+            Some(mem_tries)
+        }
+        Err(err) => {
+            warn!(target: "memtrie", %shard_uid, %err, "Cannot use memtrie snapshot, falling back to flat state");
+            None
+        }
+    }
+}
+
+/// Saves the memtries of the shard to a snapshot in `snapshot_dir`, so that the
+/// next `load_trie_from_flat_state_and_delta` doesn't need to construct them
+/// from flat state. Must be called when no more blocks are being applied.
+pub fn save_trie_to_snapshot(
+    store: &Store,
+    shard_uid: ShardUId,
+    mem_tries: &MemTries,
+    snapshot_dir: &Path,
+) -> anyhow::Result<()> {
+    let flat_head = get_flat_head(store, shard_uid)?;
+    let state_root = get_state_root(store, flat_head.hash, shard_uid)?;
+    if state_root == StateRoot::default() {
+        // Empty tries are loaded instantly anyway.
+        return Ok(());
+    }
+    let save_start = Instant::now();
+    std::fs::create_dir_all(snapshot_dir)?;
+    let path = mem_tries_snapshot_path(snapshot_dir, shard_uid);
+    save_mem_tries_snapshot(&path, shard_uid, mem_tries, flat_head, state_root)?;
+    info!(target: "memtrie", %shard_uid, ?path, "Saved trie to snapshot, took {:?}", save_start.elapsed());
+    Ok(())
+}
+
 /// Constructs in-memory tries for the given shard, so that they represent the
 /// same information as the flat storage, including the final state and the
 /// deltas. The returned tries would contain a root for each block that the
 /// flat storage currently has, i.e. one for the final block, and one for each
 /// block that flat storage has a delta for, possibly in more than one fork.
 /// `state_root` parameter is required if `ChunkExtra` is not available, e.g. on catchup.
+/// If `snapshot_dir` is given, the tries are loaded from a snapshot saved by
+/// `save_trie_to_snapshot` when it matches the flat storage head.
 pub fn load_trie_from_flat_state_and_delta(
     store: &Store,
     shard_uid: ShardUId,
     state_root: Option<StateRoot>,
+    snapshot_dir: Option<&Path>,
     parallelize: bool,
 ) -> Result<MemTries, StorageError> {
     debug!(target: "memtrie", %shard_uid, "Loading base trie from flat state...");
     let flat_store = store.flat_store();
-    let flat_head = match flat_store.get_flat_storage_status(shard_uid)? {
-        FlatStorageStatus::Ready(status) => status.flat_head,
-        other => {
-            return Err(StorageError::MemTrieLoadingError(format!(
-                            "Cannot load memtries when flat storage is not ready for shard {}, actual status: {:?}",
-                            shard_uid, other
-                        )));
-        }
-    };
+    let flat_head = get_flat_head(store, shard_uid)?;
 
     let state_root = match state_root {
         Some(state_root) => state_root,
         None => get_state_root(store, flat_head.hash, shard_uid)?,
     };
 
-    let mut mem_tries =
-        load_trie_from_flat_state(&store, shard_uid, state_root, flat_head.height, parallelize)
-            .unwrap();
+    let snapshot =
+        snapshot_dir.and_then(|dir| load_trie_from_snapshot(dir, shard_uid, flat_head, state_root));
+    let loaded_from_snapshot = snapshot.is_some();
+    let mut mem_tries = match snapshot {
+        Some(mem_tries) => mem_tries,
+        None => {
+            load_trie_from_flat_state(&store, shard_uid, state_root, flat_head.height, parallelize)
+                .unwrap()
+        }
+    };
 
     debug!(target: "memtrie", %shard_uid, "Loading flat state deltas...");
     // We load the deltas in order of height, so that we always have the previous state root
@@ -155,6 +225,15 @@ pub fn load_trie_from_flat_state_and_delta(
         if let Some(changes) = delta {
             let old_state_root = get_state_root(store, prev_hash, shard_uid)?;
             let new_state_root = get_state_root(store, hash, shard_uid)?;
+            if loaded_from_snapshot
+                && mem_tries
+                    .heights
+                    .get(&height)
+                    .is_some_and(|roots| roots.contains(&new_state_root))
+            {
+                // The snapshot was taken after this delta had been applied.
+                continue;
+            }
 
             let mut trie_update = mem_tries.update(old_state_root, TrackingMode::None)?;
             for (key, value) in changes.0 {
@@ -179,7 +258,7 @@ pub fn load_trie_from_flat_state_and_delta(
 
 #[cfg(test)]
 mod tests {
-    use super::load_trie_from_flat_state_and_delta;
+    use super::{load_trie_from_flat_state_and_delta, save_trie_to_snapshot};
     use crate::adapter::{StoreAdapter, StoreUpdateAdapter};
     use crate::flat::test_utils::MockChain;
     use crate::flat::{BlockInfo, FlatStorageReadyStatus, FlatStorageStatus};
@@ -190,6 +269,7 @@ mod tests {
     use crate::trie::mem::loading::load_trie_from_flat_state;
     use crate::trie::mem::lookup::memtrie_lookup;
     use crate::trie::mem::nibbles_utils::{all_two_nibble_nibbles, multi_hex_to_nibbles};
+    use crate::trie::mem::snapshot::mem_tries_snapshot_path;
     use crate::trie::update::TrieUpdateResult;
     use crate::{DBCol, KeyLookupMode, NibbleSlice, ShardTries, Store, Trie, TrieUpdate};
     use near_primitives::bandwidth_scheduler::BandwidthRequests;
@@ -446,7 +526,8 @@ mod tests {
         // Load into memory. It should load the base flat state (block 0), plus all
         // four deltas. We'll check against the state roots at each block; they should
         // all exist in the loaded memtrie.
-        let mem_tries = load_trie_from_flat_state_and_delta(&store, shard_uid, None, true).unwrap();
+        let mem_tries =
+            load_trie_from_flat_state_and_delta(&store, shard_uid, None, None, true).unwrap();
 
         assert_eq!(
             memtrie_lookup(mem_tries.get_root(&state_root_0).unwrap(), &test_key.to_vec(), None)
@@ -473,6 +554,30 @@ mod tests {
                 .map(|v| v.to_flat_value()),
             Some(FlatStateValue::inlined(&test_val4))
         );
+
+        // Save to a snapshot and load again. The snapshot already contains the
+        // roots of all deltas, so none of them should be applied twice.
+        let snapshot_dir = tempfile::tempdir().unwrap();
+        save_trie_to_snapshot(&store, shard_uid, &mem_tries, snapshot_dir.path()).unwrap();
+        let loaded_mem_tries = load_trie_from_flat_state_and_delta(
+            &store,
+            shard_uid,
+            None,
+            Some(snapshot_dir.path()),
+            false,
+        )
+        .unwrap();
+        assert_eq!(loaded_mem_tries.num_roots(), mem_tries.num_roots());
+        assert_eq!(
+            memtrie_lookup(
+                loaded_mem_tries.get_root(&state_root_4).unwrap(),
+                &test_key.to_vec(),
+                None
+            )
+            .map(|v| v.to_flat_value()),
+            Some(FlatStateValue::inlined(&test_val4))
+        );
+        assert!(!mem_tries_snapshot_path(snapshot_dir.path(), shard_uid).exists());
     }
 
     /// Makes the given changes to both the trie and flat storage.
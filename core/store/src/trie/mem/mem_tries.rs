@@ -33,12 +33,12 @@ pub struct MemTries {
     /// deduplication mechanism so we can't guarantee that nodes of the
     /// same hash are unique. During lookup, any of these nodes can be provided
     /// as they all logically represent the same trie.
-    roots: HashMap<StateRoot, Vec<MemTrieNodeId>>,
+    pub(super) roots: HashMap<StateRoot, Vec<MemTrieNodeId>>,
     /// Maps a block height to a list of state roots present at that height.
     /// This is used for GC. The invariant is that for any state root, the
     /// number of times the state root appears in this map is equal to the
     /// sum of the refcounts of each `MemTrieNodeId`s in `roots[state hash]`.
-    heights: BTreeMap<BlockHeight, Vec<StateRoot>>,
+    pub(super) heights: BTreeMap<BlockHeight, Vec<StateRoot>>,
     /// Shard UID, for exporting metrics only.
     shard_uid: ShardUId,
 }
@@ -74,6 +74,19 @@ impl MemTries {
         }
     }
 
+    /// Creates a new `MemTries` from an arena together with the roots and
+    /// heights that were stored in it. Used on loading memtries from a
+    /// snapshot on disk.
+    pub(super) fn from_parts(
+        shard_uid: ShardUId,
+        arena: HybridArena,
+        roots: HashMap<StateRoot, Vec<MemTrieNodeId>>,
+        heights: BTreeMap<BlockHeight, Vec<StateRoot>>,
+    ) -> Self {
+        MEM_TRIE_NUM_ROOTS.with_label_values(&[&shard_uid.to_string()]).set(roots.len() as i64);
+        Self { arena, roots, heights, shard_uid }
+    }
+
     pub fn new_from_arena_and_root(
         shard_uid: ShardUId,
         block_height: BlockHeight,
@@ -106,7 +119,7 @@ impl MemTries {
         }
     }
 
-    fn insert_root(
+    pub(super) fn insert_root(
         &mut self,
         state_root: StateRoot,
         mem_root: MemTrieNodeId,
@@ -13,6 +13,7 @@ pub(crate) mod nibbles_utils;
 pub mod node;
 mod parallel_loader;
 pub mod resharding;
+pub mod snapshot;
 
 /// Check this, because in the code we conveniently assume usize is 8 bytes.
 /// In-memory trie can't possibly work under 32-bit anyway.
@@ -0,0 +1,267 @@
+//! Snapshots of in-memory tries on disk.
+//!
+//! Loading memtries from flat storage takes minutes per shard because every
+//! trie node has to be reconstructed. To make restarts fast, the memtries are
+//! saved on graceful shutdown as the raw memory chunks of their arena, so that
+//! loading them back is a matter of copying the mapped file into memory.
+//!
+//! Layout of a snapshot file:
+//! * `MAGIC`;
+//! * format version, u32 little endian;
+//! * length of the header, u64 little endian;
+//! * borsh-serialized `SnapshotHeader`;
+//! * crc32 checksum of the header, u32 little endian;
+//! * the memory chunks of the arena, one after another.
+//!
+//! A snapshot is only valid for the flat storage head it was taken at. The
+//! caller is expected to fall back to loading from flat storage on any error.
+
+use super::arena::hybrid::HybridArena;
+use super::arena::{AllocatorState, ArenaPos};
+use super::mem_tries::MemTries;
+use super::node::MemTrieNodeId;
+use crate::flat::BlockInfo;
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_primitives::shard_layout::ShardUId;
+use near_primitives::types::{BlockHeight, StateRoot};
+use rayon::prelude::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+const MAGIC: &[u8; 8] = b"NEARMEMT";
+
+/// Version of the snapshot format. Must be bumped on any change of the format,
+/// including the encoding of the trie nodes and the layout of the arena.
+pub const MEM_TRIES_SNAPSHOT_VERSION: u32 = 1;
+
+#[derive(BorshSerialize, BorshDeserialize)]
+struct SnapshotHeader {
+    shard_uid: ShardUId,
+    /// Head of the flat storage at the time the snapshot was taken.
+    flat_head: BlockInfo,
+    /// State root of the shard at `flat_head`.
+    state_root: StateRoot,
+    roots: Vec<(StateRoot, Vec<ArenaPos>)>,
+    heights: Vec<(BlockHeight, Vec<StateRoot>)>,
+    allocator_state: AllocatorState,
+    /// Length and crc32 checksum of each memory chunk.
+    chunks: Vec<(u64, u32)>,
+}
+
+/// Returns the path of the snapshot of the given shard in `dir`.
+pub fn mem_tries_snapshot_path(dir: &Path, shard_uid: ShardUId) -> PathBuf {
+    dir.join(format!("{shard_uid}.memtrie"))
+}
+
+/// Writes the memtries of the shard to `path`. `state_root` must be the state
+/// root at `flat_head` and must be present in the memtries.
+///
+/// Memtries sharing memory with other memtries (after resharding) cannot be
+/// saved.
+pub fn save_mem_tries_snapshot(
+    path: &Path,
+    shard_uid: ShardUId,
+    mem_tries: &MemTries,
+    flat_head: BlockInfo,
+    state_root: StateRoot,
+) -> io::Result<()> {
+    if mem_tries.arena.has_shared_memory() {
+        return Err(invalid_data("memtries sharing memory with other shards cannot be saved"));
+    }
+    if !mem_tries.roots.contains_key(&state_root) {
+        return Err(invalid_data(format!("state root {state_root} is not in memtries")));
+    }
+    let (chunks, allocator_state) = mem_tries.arena.chunks_and_allocator_state();
+    let header = SnapshotHeader {
+        shard_uid,
+        flat_head,
+        state_root,
+        roots: mem_tries
+            .roots
+            .iter()
+            .map(|(root, ids)| (*root, ids.iter().map(|id| id.pos).collect()))
+            .collect(),
+        heights: mem_tries.heights.iter().map(|(h, roots)| (*h, roots.clone())).collect(),
+        allocator_state,
+        chunks: chunks
+            .par_iter()
+            .map(|chunk| (chunk.len() as u64, crc32fast::hash(chunk)))
+            .collect(),
+    };
+    let header = borsh::to_vec(&header)?;
+
+    // Write to a temporary file first, so that a crash never leaves a partial
+    // snapshot behind.
+    let tmp_path = path.with_extension("memtrie.tmp");
+    let mut file = io::BufWriter::new(std::fs::File::create(&tmp_path)?);
+    file.write_all(MAGIC)?;
+    file.write_all(&MEM_TRIES_SNAPSHOT_VERSION.to_le_bytes())?;
+    file.write_all(&(header.len() as u64).to_le_bytes())?;
+    file.write_all(&header)?;
+    file.write_all(&crc32fast::hash(&header).to_le_bytes())?;
+    for chunk in chunks {
+        file.write_all(chunk)?;
+    }
+    file.into_inner().map_err(|err| err.into_error())?.sync_all()?;
+    std::fs::rename(&tmp_path, path)
+}
+
+/// Loads the memtries of the shard from the snapshot at `path`.
+///
+/// Fails if the snapshot is corrupted, has a different format version or was
+/// taken at a different flat storage head or state root.
+pub fn load_mem_tries_snapshot(
+    path: &Path,
+    shard_uid: ShardUId,
+    flat_head: BlockInfo,
+    state_root: StateRoot,
+) -> io::Result<MemTries> {
+    let file = std::fs::File::open(path)?;
+    // SAFETY: snapshots are only written through a temporary file which is
+    // then renamed, so the mapped file is never modified.
+    let mmap = unsafe { memmap2::Mmap::map(&file)? };
+    let mut data: &[u8] = &mmap;
+
+    if take(&mut data, MAGIC.len())? != MAGIC {
+        return Err(invalid_data("not a memtrie snapshot"));
+    }
+    let version = u32::from_le_bytes(take(&mut data, 4)?.try_into().unwrap());
+    if version != MEM_TRIES_SNAPSHOT_VERSION {
+        return Err(invalid_data(format!(
+            "unsupported version {version}, expected {MEM_TRIES_SNAPSHOT_VERSION}"
+        )));
+    }
+    let header_len = u64::from_le_bytes(take(&mut data, 8)?.try_into().unwrap());
+    let header_len = usize::try_from(header_len).map_err(invalid_data)?;
+    let header_bytes = take(&mut data, header_len)?;
+    let header_checksum = u32::from_le_bytes(take(&mut data, 4)?.try_into().unwrap());
+    if crc32fast::hash(header_bytes) != header_checksum {
+        return Err(invalid_data("header checksum mismatch"));
+    }
+    let header = SnapshotHeader::try_from_slice(header_bytes)?;
+    if header.shard_uid != shard_uid {
+        return Err(invalid_data(format!("snapshot is for shard {}", header.shard_uid)));
+    }
+    if header.flat_head != flat_head {
+        return Err(invalid_data(format!(
+            "snapshot was taken at flat head {:?}, but the flat head is {:?}",
+            header.flat_head, flat_head
+        )));
+    }
+    if header.state_root != state_root {
+        return Err(invalid_data(format!(
+            "snapshot has state root {}, expected {}",
+            header.state_root, state_root
+        )));
+    }
+
+    let mut chunks = Vec::with_capacity(header.chunks.len());
+    for (len, checksum) in header.chunks {
+        let len = usize::try_from(len).map_err(invalid_data)?;
+        chunks.push((take(&mut data, len)?, checksum));
+    }
+    if !data.is_empty() {
+        return Err(invalid_data("unexpected data after the last chunk"));
+    }
+    let chunks = chunks
+        .into_par_iter()
+        .map(|(chunk, checksum)| {
+            if crc32fast::hash(chunk) != checksum {
+                return Err(invalid_data("chunk checksum mismatch"));
+            }
+            Ok(chunk.to_vec())
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+
+    let arena = HybridArena::from_chunks_and_allocator_state(
+        shard_uid.to_string(),
+        chunks,
+        header.allocator_state,
+    )
+    .ok_or_else(|| invalid_data("allocator state does not match the chunks"))?;
+    let roots = header
+        .roots
+        .into_iter()
+        .map(|(root, ids)| (root, ids.into_iter().map(|pos| MemTrieNodeId { pos }).collect()))
+        .collect();
+    let mem_tries =
+        MemTries::from_parts(shard_uid, arena, roots, header.heights.into_iter().collect());
+    let root = mem_tries.get_root(&state_root).map_err(invalid_data)?;
+    if root.view().node_hash() != state_root {
+        return Err(invalid_data("root node does not match the state root"));
+    }
+    Ok(mem_tries)
+}
+
+fn take<'a>(data: &mut &'a [u8], len: usize) -> io::Result<&'a [u8]> {
+    if data.len() < len {
+        return Err(invalid_data("snapshot is truncated"));
+    }
+    let (head, tail) = data.split_at(len);
+    *data = tail;
+    Ok(head)
+}
+
+fn invalid_data(err: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{load_mem_tries_snapshot, mem_tries_snapshot_path, save_mem_tries_snapshot};
+    use crate::flat::BlockInfo;
+    use crate::trie::mem::arena::Arena;
+    use crate::trie::mem::mem_tries::MemTries;
+    use crate::trie::mem::node::{InputMemTrieNode, MemTrieNodeId};
+    use crate::NibbleSlice;
+    use near_primitives::hash::CryptoHash;
+    use near_primitives::shard_layout::ShardUId;
+    use near_primitives::state::FlatStateValue;
+
+    fn leaf(mem_tries: &mut MemTries, value: &[u8]) -> (CryptoHash, MemTrieNodeId) {
+        let root = MemTrieNodeId::new(
+            &mut mem_tries.arena,
+            InputMemTrieNode::Leaf {
+                value: &FlatStateValue::Inlined(value.to_vec()),
+                extension: &NibbleSlice::new(&[]).encoded(true),
+            },
+        );
+        (root.as_ptr(mem_tries.arena.memory()).view().node_hash(), root)
+    }
+
+    #[test]
+    fn test_save_and_load_snapshot() {
+        let shard_uid = ShardUId::single_shard();
+        let mut mem_tries = MemTries::new(shard_uid);
+        let (state_root, root) = leaf(&mut mem_tries, b"head");
+        mem_tries.insert_root(state_root, root, 10);
+        let (next_root, root) = leaf(&mut mem_tries, b"next");
+        mem_tries.insert_root(next_root, root, 11);
+        let flat_head = BlockInfo {
+            hash: CryptoHash::hash_bytes(b"10"),
+            height: 10,
+            prev_hash: CryptoHash::default(),
+        };
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = mem_tries_snapshot_path(dir.path(), shard_uid);
+        save_mem_tries_snapshot(&path, shard_uid, &mem_tries, flat_head, state_root).unwrap();
+
+        let mut loaded = load_mem_tries_snapshot(&path, shard_uid, flat_head, state_root).unwrap();
+        assert_eq!(loaded.num_roots(), 2);
+        assert!(loaded.lookup(&next_root, &[], None).unwrap().is_some());
+        // The allocator must keep working on the restored memory.
+        loaded.delete_until_height(12);
+        assert_eq!(loaded.arena().num_active_allocs(), 0);
+
+        let other_head = BlockInfo { height: 11, ..flat_head };
+        assert!(load_mem_tries_snapshot(&path, shard_uid, other_head, state_root).is_err());
+
+        // Flip a byte in the last chunk.
+        let mut data = std::fs::read(&path).unwrap();
+        *data.last_mut().unwrap() ^= 1;
+        std::fs::write(&path, data).unwrap();
+        let err = load_mem_tries_snapshot(&path, shard_uid, flat_head, state_root).err().unwrap();
+        assert_eq!(err.to_string(), "chunk checksum mismatch");
+    }
+}
@@ -468,6 +468,10 @@ impl TrieRefcountAddition {
         self.trie_node_or_value.as_slice()
     }
 
+    pub fn rc(&self) -> std::num::NonZeroU32 {
+        self.rc
+    }
+
     pub fn revert(&self) -> TrieRefcountSubtraction {
         TrieRefcountSubtraction::new(self.trie_node_or_value_hash, self.rc)
     }
@@ -477,6 +481,14 @@ impl TrieRefcountSubtraction {
     pub fn new(trie_node_or_value_hash: CryptoHash, rc: std::num::NonZeroU32) -> Self {
         Self { trie_node_or_value_hash, _ignored: Default::default(), rc }
     }
+
+    pub fn hash(&self) -> &CryptoHash {
+        &self.trie_node_or_value_hash
+    }
+
+    pub fn rc(&self) -> std::num::NonZeroU32 {
+        self.rc
+    }
 }
 
 /// Helps produce a list of additions and subtractions to the trie,
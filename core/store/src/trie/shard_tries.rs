@@ -5,7 +5,7 @@ use crate::adapter::trie_store::{TrieStoreAdapter, TrieStoreUpdateAdapter};
 use crate::adapter::StoreAdapter;
 use crate::flat::{FlatStorageManager, FlatStorageStatus};
 use crate::trie::config::TrieConfig;
-use crate::trie::mem::loading::load_trie_from_flat_state_and_delta;
+use crate::trie::mem::loading::{load_trie_from_flat_state_and_delta, save_trie_to_snapshot};
 use crate::trie::prefetching_trie_storage::PrefetchingThreadsHandle;
 use crate::trie::trie_storage::{TrieCache, TrieCachingStorage};
 use crate::trie::{TrieRefcountAddition, POISONED_LOCK_ERR};
@@ -22,7 +22,7 @@ use near_primitives::types::{
 use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
 use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex, RwLock};
-use tracing::info;
+use tracing::{info, warn};
 
 struct ShardTriesInner {
     store: TrieStoreAdapter,
@@ -420,6 +420,7 @@ impl ShardTries {
             &self.0.store.store(),
             *shard_uid,
             state_root,
+            self.0.trie_config.mem_tries_snapshot_dir.as_deref(),
             parallelize,
         )?;
         self.0.mem_tries.write().unwrap().insert(*shard_uid, Arc::new(RwLock::new(mem_tries)));
@@ -485,6 +486,24 @@ impl ShardTries {
         Ok(())
     }
 
+    /// Saves the loaded in-memory tries to snapshots, if enabled, so that they
+    /// can be loaded quickly on the next start. Should be called on graceful
+    /// shutdown, once no more blocks are being applied.
+    pub fn save_mem_tries_snapshots(&self) {
+        let Some(snapshot_dir) = &self.0.trie_config.mem_tries_snapshot_dir else {
+            return;
+        };
+        let mem_tries = self.0.mem_tries.read().unwrap().clone();
+        info!(target: "memtrie", "Saving memtries for shards {:?}...", mem_tries.keys());
+        mem_tries.par_iter().for_each(|(shard_uid, mem_tries)| {
+            let store = self.0.store.store();
+            let mem_tries = mem_tries.read().unwrap();
+            if let Err(err) = save_trie_to_snapshot(&store, *shard_uid, &mem_tries, snapshot_dir) {
+                warn!(target: "memtrie", %shard_uid, ?err, "Failed to save memtrie snapshot");
+            }
+        });
+    }
+
     /// Retrieves the in-memory tries for the shard.
     pub fn get_mem_tries(&self, shard_uid: ShardUId) -> Option<Arc<RwLock<MemTries>>> {
         let guard = self.0.mem_tries.read().unwrap();
@@ -635,6 +654,18 @@ impl WrappedTrieChanges {
                 continue;
             }
 
+            if self.tries.0.trie_config.index_accounts_by_public_key {
+                if let TrieKey::AccessKey { account_id, public_key } =
+                    &change_with_trie_key.trie_key
+                {
+                    // Deletions are not reflected in the index, see
+                    // `crate::accounts_by_public_key`.
+                    if change_with_trie_key.changes.iter().any(|change| change.data.is_some()) {
+                        store_update.set_account_by_public_key(public_key, account_id);
+                    }
+                }
+            }
+
             let storage_key = match change_with_trie_key.trie_key.get_account_id() {
                 // If a TrieKey itself doesn't identify the Shard, then we need to add shard id to the row key.
                 None => KeyForStateChanges::delayed_receipt_key_from_trie_key(
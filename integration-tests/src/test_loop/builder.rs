@@ -14,7 +14,7 @@ use near_chain::types::RuntimeAdapter;
 use near_chain::ChainGenesis;
 use near_chain_configs::{
     ClientConfig, DumpConfig, ExternalStorageConfig, ExternalStorageLocation, Genesis,
-    MutableConfigValue, StateSyncConfig, SyncConfig,
+    MutableConfigValue, StatePartsLayout, StateSyncConfig, SyncConfig,
 };
 use near_chunks::shards_manager_actor::ShardsManagerActor;
 use near_client::client_actor::ClientActorInner;
@@ -511,6 +511,8 @@ impl TestLoopBuilder {
                 location: external_storage_location.clone(),
                 credentials_file: None,
                 restart_dump_for_shards: None,
+                parts_layout: StatePartsLayout::Raw,
+                zstd_compression_level: None,
             }),
             sync: SyncConfig::ExternalStorage(ExternalStorageConfig {
                 location: external_storage_location,
@@ -521,6 +523,7 @@ impl TestLoopBuilder {
                 // the clients transfer state parts "peer to peer" but we wouldn't really
                 // gain anything over having them dump parts to a tempdir.
                 external_storage_fallback_threshold: 0,
+                parts_layout: StatePartsLayout::Raw,
             }),
         };
 
@@ -182,6 +182,7 @@ fn bootstrap_node_via_epoch_sync(setup: TestNetworkSetup, source_node: usize) ->
         tier1_accounts_data: Vec::new(),
         tier1_accounts_keys: Vec::new(),
         tier1_connections: Vec::new(),
+        snapshot_hosts: Vec::new(),
     }));
 
     // Check that the new node will reach a high height as well.
@@ -136,6 +136,7 @@ fn slow_test_sync_from_genesis() {
         tier1_accounts_data: Vec::new(),
         tier1_accounts_keys: Vec::new(),
         tier1_connections: Vec::new(),
+        snapshot_hosts: Vec::new(),
     }));
 
     // Check that the new node will reach a high height as well.
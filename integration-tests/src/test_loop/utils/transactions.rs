@@ -66,7 +66,11 @@ pub fn get_next_nonce(env: &mut TestLoopEnv, account_id: &AccountId) -> u64 {
         .collect_vec();
     let response = clients.runtime_query(
         account_id,
-        QueryRequest::ViewAccessKey { account_id: account_id.clone(), public_key },
+        QueryRequest::ViewAccessKey {
+            account_id: account_id.clone(),
+            public_key,
+            include_proof: false,
+        },
     );
     let QueryResponseKind::AccessKey(access_key) = response.kind else {
         panic!("Expected AccessKey response");
@@ -646,6 +650,10 @@ impl TransactionRunner {
             ProcessTxResponse::DoesNotTrackShard => {
                 panic!("Transaction submitted to a node that doesn't track the shard")
             }
+            ProcessTxResponse::ReplacementUnderpriced
+            | ProcessTxResponse::TooManyPendingTransactions => {
+                panic!("Transaction rejected by the pool: {:?}", process_tx_response)
+            }
         };
         Some(res)
     }
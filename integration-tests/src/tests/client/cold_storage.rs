@@ -14,12 +14,17 @@ use near_primitives::transaction::{
 use near_primitives_core::hash::CryptoHash;
 use near_primitives_core::types::AccountId;
 use near_store::archive::cold_storage::{
-    copy_all_data_to_cold, test_cold_genesis_update, test_get_store_initial_writes,
+    copy_all_data_to_cold, prune_cold_db, test_cold_genesis_update, test_get_store_initial_writes,
     test_get_store_reads, update_cold_db, update_cold_head,
 };
+use near_store::archive::segments::{
+    get_cold_segments_head, segment_object_name, ColdSegmentBuilder,
+};
 use near_store::metadata::DbKind;
 use near_store::metadata::DB_VERSION;
-use near_store::test_utils::create_test_node_storage_with_cold;
+use near_store::test_utils::{
+    create_test_node_storage_with_cold, create_test_node_storage_with_cold_segments,
+};
 use near_store::{DBCol, Store, COLD_HEAD_KEY, HEAD_KEY};
 use nearcore::test_utils::TestEnvNightshadeSetupExt;
 use nearcore::{cold_storage::spawn_cold_store_loop, NearConfig};
@@ -196,6 +201,188 @@ fn test_storage_after_commit_of_cold_update() {
     }
 }
 
+/// Producing 4 epochs of blocks with send money transactions and copying them to cold storage.
+/// Then pruning Transactions and StateChanges for the first 2 epochs from cold storage.
+/// Checks that the data of the pruned heights is removed and everything else is kept.
+#[test]
+fn test_cold_db_prune() {
+    init_test_logger();
+
+    let epoch_length = 5;
+    let max_height = epoch_length * 4;
+    let prune_height = epoch_length * 2;
+    let pruned_columns = [DBCol::Transactions, DBCol::StateChanges];
+
+    let mut genesis = Genesis::test(vec![test0(), test1()], 1);
+    genesis.config.epoch_length = epoch_length;
+    genesis.config.min_gas_price = 0;
+    let mut env = TestEnv::builder(&genesis.config).nightshade_runtimes(&genesis).build();
+
+    let (storage, ..) = create_test_node_storage_with_cold(DB_VERSION, DbKind::Hot);
+    let cold_db = storage.cold_db().unwrap();
+    let cold_store = storage.get_cold_store().unwrap();
+
+    test_cold_genesis_update(&cold_db, &env.clients[0].runtime_adapter.store()).unwrap();
+
+    let mut last_hash = *env.clients[0].chain.genesis().hash();
+    for height in 1..max_height {
+        let signer = InMemorySigner::test_signer(&test0());
+        if height + 2 < max_height {
+            for i in 0..5 {
+                let tx = create_tx_send_money(height * 10 + i, &signer, last_hash);
+                assert_eq!(env.clients[0].process_tx(tx, false, false), ProcessTxResponse::ValidTx);
+            }
+        }
+
+        let block = env.clients[0].produce_block(height).unwrap().unwrap();
+        env.process_block(0, block.clone(), Provenance::PRODUCED);
+
+        let client = &env.clients[0];
+        let epoch_id = client.epoch_manager.get_epoch_id_from_prev_block(&last_hash).unwrap();
+        let shard_layout = client.epoch_manager.get_shard_layout(&epoch_id).unwrap();
+        update_cold_db(cold_db, &client.runtime_adapter.store(), &shard_layout, &height, 1)
+            .unwrap();
+
+        last_hash = *block.hash();
+    }
+
+    let client = &env.clients[0];
+    let client_store = client.runtime_adapter.store();
+    for height in 1..prune_height {
+        let block = client.chain.get_block_by_height(height).unwrap();
+        let shard_layout =
+            client.epoch_manager.get_shard_layout(block.header().epoch_id()).unwrap();
+        assert!(
+            prune_cold_db(cold_db, client_store, &shard_layout, &height, &pruned_columns).unwrap()
+        );
+    }
+
+    let (mut num_pruned, mut num_retained) = (0, 0);
+    for height in 1..max_height {
+        let block = client.chain.get_block_by_height(height).unwrap();
+        let block_hash = block.hash().as_bytes();
+        assert!(cold_store.exists(DBCol::Block, block_hash).unwrap());
+        let state_changes = cold_store.iter_prefix(DBCol::StateChanges, block_hash).count();
+        if height < prune_height {
+            assert_eq!(state_changes, 0, "height: {height}");
+        } else {
+            num_retained += state_changes;
+        }
+
+        for chunk_header in block.chunks().iter_deprecated() {
+            let chunk_hash = chunk_header.chunk_hash();
+            let chunk = cold_store
+                .get_ser::<ShardChunk>(DBCol::Chunks, chunk_hash.as_bytes())
+                .unwrap()
+                .unwrap();
+            for tx in chunk.transactions() {
+                let exists = cold_store.exists(DBCol::Transactions, tx.get_hash().as_bytes());
+                assert_eq!(exists.unwrap(), height >= prune_height, "height: {height}");
+                if height < prune_height {
+                    num_pruned += 1;
+                } else {
+                    num_retained += 1;
+                }
+            }
+        }
+    }
+    // assert that this test actually checks something
+    assert!(num_pruned > 0);
+    assert!(num_retained > 0);
+}
+
+/// Producing 4 epochs of blocks with transactions, copying them to cold storage
+/// and offloading the first 2 epochs to a segment.
+/// Checking that the data read from cold storage is the same before and after
+/// offloading.
+#[test]
+fn test_cold_db_offload() {
+    init_test_logger();
+
+    let epoch_length = 5;
+    let max_height = epoch_length * 4;
+    let offload_end = epoch_length * 2;
+
+    let mut genesis = Genesis::test(vec![test0(), test1()], 1);
+    genesis.config.epoch_length = epoch_length;
+    genesis.config.min_gas_price = 0;
+    let mut env = TestEnv::builder(&genesis.config).nightshade_runtimes(&genesis).build();
+
+    let segments_dir = tempfile::tempdir().unwrap();
+    let storage =
+        create_test_node_storage_with_cold_segments(DB_VERSION, DbKind::Hot, segments_dir.path());
+    let cold_db = storage.cold_db().unwrap();
+    let cold_store = storage.get_cold_store().unwrap();
+
+    test_cold_genesis_update(&cold_db, &env.clients[0].runtime_adapter.store()).unwrap();
+
+    let mut last_hash = *env.clients[0].chain.genesis().hash();
+    for height in 1..max_height {
+        let signer = InMemorySigner::test_signer(&test0());
+        if height + 2 < max_height {
+            for i in 0..5 {
+                let tx = create_tx_send_money(height * 10 + i, &signer, last_hash);
+                assert_eq!(env.clients[0].process_tx(tx, false, false), ProcessTxResponse::ValidTx);
+            }
+        }
+
+        let block = env.clients[0].produce_block(height).unwrap().unwrap();
+        env.process_block(0, block.clone(), Provenance::PRODUCED);
+
+        let client = &env.clients[0];
+        let epoch_id = client.epoch_manager.get_epoch_id_from_prev_block(&last_hash).unwrap();
+        let shard_layout = client.epoch_manager.get_shard_layout(&epoch_id).unwrap();
+        update_cold_db(cold_db, &client.runtime_adapter.store(), &shard_layout, &height, 1)
+            .unwrap();
+
+        last_hash = *block.hash();
+    }
+
+    let client = &env.clients[0];
+    let client_store = client.runtime_adapter.store();
+    let read_all = || {
+        let mut data = vec![];
+        for height in 1..max_height {
+            let block = client.chain.get_block_by_height(height).unwrap();
+            let block_hash = block.hash().as_bytes();
+            data.push(cold_store.get(DBCol::Block, block_hash).unwrap().unwrap().to_vec());
+            for (key, value) in
+                cold_store.iter_prefix(DBCol::StateChanges, block_hash).map(|item| item.unwrap())
+            {
+                data.push(key.to_vec());
+                data.push(value.to_vec());
+            }
+            for chunk_header in block.chunks().iter_deprecated() {
+                let chunk_hash = chunk_header.chunk_hash();
+                let chunk = cold_store
+                    .get_ser::<ShardChunk>(DBCol::Chunks, chunk_hash.as_bytes())
+                    .unwrap()
+                    .unwrap();
+                for tx in chunk.transactions() {
+                    let tx = cold_store.get(DBCol::Transactions, tx.get_hash().as_bytes());
+                    data.push(tx.unwrap().unwrap().to_vec());
+                }
+            }
+        }
+        data
+    };
+    let data_before = read_all();
+
+    let mut builder = ColdSegmentBuilder::new(cold_db, 0).unwrap();
+    for height in 0..=offload_end {
+        let block = client.chain.get_block_by_height(height).unwrap();
+        let shard_layout =
+            client.epoch_manager.get_shard_layout(block.header().epoch_id()).unwrap();
+        assert!(builder.add_height(cold_db, client_store, &shard_layout, &height).unwrap());
+    }
+    assert!(builder.size() > 0);
+    builder.seal(cold_db, offload_end).unwrap();
+
+    assert_eq!(get_cold_segments_head(cold_db).unwrap(), Some(offload_end));
+    assert!(segments_dir.path().join("objects").join(segment_object_name(0)).exists());
+    assert_eq!(read_all(), data_before);
+}
+
 /// Producing 10 * 5 blocks and updating HEAD of cold storage after each one.
 /// After every update checking that HEAD in cold db, COLD_HEAD in hot db and HEAD in hot store are equal.
 #[test]
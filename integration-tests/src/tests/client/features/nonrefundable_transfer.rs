@@ -108,6 +108,7 @@ fn get_nonce(env: &mut TestEnv, signer: &InMemorySigner) -> u64 {
     let request = QueryRequest::ViewAccessKey {
         account_id: signer.account_id.clone(),
         public_key: signer.public_key.clone(),
+        include_proof: false,
     };
     match env.query_view(request).unwrap().kind {
         QueryResponseKind::AccessKey(view) => view.nonce,
@@ -116,7 +117,7 @@ fn get_nonce(env: &mut TestEnv, signer: &InMemorySigner) -> u64 {
 }
 
 fn account_exists(env: &mut TestEnv, account_id: AccountId) -> bool {
-    let request = QueryRequest::ViewAccount { account_id };
+    let request = QueryRequest::ViewAccount { account_id, include_proof: false };
     env.query_view(request).is_ok()
 }
 
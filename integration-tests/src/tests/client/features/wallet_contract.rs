@@ -66,7 +66,7 @@ fn view_request(env: &TestEnv, request: QueryRequest) -> QueryResponse {
 }
 
 pub fn view_balance(env: &TestEnv, account: &AccountIdRef) -> u128 {
-    let request = QueryRequest::ViewAccount { account_id: account.into() };
+    let request = QueryRequest::ViewAccount { account_id: account.into(), include_proof: false };
     match view_request(&env, request).kind {
         QueryResponseKind::ViewAccount(view) => view.amount,
         _ => panic!("wrong query response"),
@@ -74,7 +74,11 @@ pub fn view_balance(env: &TestEnv, account: &AccountIdRef) -> u128 {
 }
 
 fn view_nonce(env: &TestEnv, account: &AccountIdRef, pk: PublicKey) -> u64 {
-    let request = QueryRequest::ViewAccessKey { account_id: account.into(), public_key: pk };
+    let request = QueryRequest::ViewAccessKey {
+        account_id: account.into(),
+        public_key: pk,
+        include_proof: false,
+    };
     match view_request(&env, request).kind {
         QueryResponseKind::AccessKey(view) => view.nonce,
         _ => panic!("wrong query response"),
@@ -113,7 +117,10 @@ fn test_eth_implicit_account_creation() {
 
     // Verify the ETH-implicit account has zero balance and appropriate code hash.
     // Check that the account storage fits within zero balance account limit.
-    let request = QueryRequest::ViewAccount { account_id: eth_implicit_account_id.clone() };
+    let request = QueryRequest::ViewAccount {
+        account_id: eth_implicit_account_id.clone(),
+        include_proof: false,
+    };
     match view_request(&env, request).kind {
         QueryResponseKind::ViewAccount(view) => {
             assert_eq!(view.amount, 0);
@@ -124,7 +131,8 @@ fn test_eth_implicit_account_creation() {
     }
 
     // Verify that contract code deployed to the ETH-implicit account is near[wallet contract hash].
-    let request = QueryRequest::ViewCode { account_id: eth_implicit_account_id };
+    let request =
+        QueryRequest::ViewCode { account_id: eth_implicit_account_id, include_proof: false };
     match view_request(&env, request).kind {
         QueryResponseKind::ViewCode(view) => {
             let contract_code = ContractCode::new(view.code, None);
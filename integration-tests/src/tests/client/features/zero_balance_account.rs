@@ -30,7 +30,7 @@ fn assert_zero_balance_account(env: &TestEnv, account_id: &AccountId) {
             &head.prev_block_hash,
             &head.last_block_hash,
             head_block.header().epoch_id(),
-            &QueryRequest::ViewAccount { account_id: account_id.clone() },
+            &QueryRequest::ViewAccount { account_id: account_id.clone(), include_proof: false },
         )
         .unwrap();
     match response.kind {
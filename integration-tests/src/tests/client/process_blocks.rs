@@ -346,7 +346,8 @@ fn receive_network_block() {
                 block_merkle_tree.root(),
                 Clock::real(),
                 None,
-            );
+            )
+            .unwrap();
             actor_handles.client_actor.do_send(
                 BlockResponse { block, peer_id: PeerInfo::random().id, was_requested: false }
                     .with_span_context(),
@@ -434,7 +435,8 @@ fn produce_block_with_approvals() {
                 block_merkle_tree.root(),
                 Clock::real(),
                 None,
-            );
+            )
+            .unwrap();
             actor_handles.client_actor.do_send(
                 BlockResponse {
                     block: block.clone(),
@@ -457,7 +459,8 @@ fn produce_block_with_approvals() {
                     block.header().height(),
                     10, // the height at which "test1" is producing
                     &signer,
-                );
+                )
+                .unwrap();
                 actor_handles
                     .client_actor
                     .do_send(BlockApproval(approval, PeerInfo::random().id).with_span_context());
@@ -631,7 +634,8 @@ fn invalid_blocks_common(is_requested: bool) {
                 block_merkle_tree.root(),
                 Clock::real(),
                 None,
-            );
+            )
+            .unwrap();
             // Send block with invalid chunk mask
             let mut block = valid_block.clone();
             block.mut_header().set_chunk_mask(vec![]);
@@ -979,6 +983,7 @@ fn client_sync_headers() {
                 tier1_connections: vec![],
                 tier1_accounts_keys: vec![],
                 tier1_accounts_data: vec![],
+                snapshot_hosts: vec![],
             })
             .with_span_context(),
         );
@@ -2146,7 +2151,10 @@ fn test_data_reset_before_state_sync() {
             &head.prev_block_hash,
             &head.last_block_hash,
             head_block.header().epoch_id(),
-            &QueryRequest::ViewAccount { account_id: "test_account".parse().unwrap() },
+            &QueryRequest::ViewAccount {
+                account_id: "test_account".parse().unwrap(),
+                include_proof: false,
+            },
         )
         .unwrap();
     assert_matches!(response.kind, QueryResponseKind::ViewAccount(_));
@@ -2160,7 +2168,10 @@ fn test_data_reset_before_state_sync() {
         &head.prev_block_hash,
         &head.last_block_hash,
         head_block.header().epoch_id(),
-        &QueryRequest::ViewAccount { account_id: "test_account".parse().unwrap() },
+        &QueryRequest::ViewAccount {
+            account_id: "test_account".parse().unwrap(),
+            include_proof: false,
+        },
     );
     // TODO(#3742): ViewClient still has data in cache by current design.
     assert!(response.is_ok());
@@ -2309,7 +2320,7 @@ fn test_validate_chunk_extra() {
         let outcome_root = Block::compute_outcome_root(block.chunks().iter_deprecated());
         block.mut_header().set_prev_outcome_root(outcome_root);
         let endorsement =
-            ChunkEndorsement::new(EpochId::default(), &chunk_header, &validator_signer);
+            ChunkEndorsement::new(EpochId::default(), &chunk_header, &validator_signer).unwrap();
         block.set_chunk_endorsements(vec![vec![Some(Box::new(endorsement.signature()))]]);
         let body_hash = block.compute_block_body_hash().unwrap();
         block.mut_header().set_block_body_hash(body_hash);
@@ -3175,7 +3186,7 @@ fn test_query_final_state() {
                     &final_head.prev_block_hash,
                     last_final_block.hash(),
                     last_final_block.header().epoch_id(),
-                    &QueryRequest::ViewAccount { account_id },
+                    &QueryRequest::ViewAccount { account_id, include_proof: false },
                 )
                 .unwrap();
             match response.kind {
@@ -3406,7 +3417,7 @@ fn test_header_version_downgrade() {
                     &borsh::to_vec(&header.inner_rest).expect("Failed to serialize"),
                 );
                 header.hash = hash;
-                header.signature = validator_signer.sign_bytes(hash.as_ref());
+                header.signature = validator_signer.sign_bytes(hash.as_ref()).unwrap();
             }
             _ => {
                 unreachable!();
@@ -3774,7 +3785,7 @@ mod contract_precompilation_tests {
             .runtime_adapter
             .get_trie_for_shard(shard_id, block.header().prev_hash(), state_root, false)
             .unwrap();
-        let state_update = TrieUpdate::new(trie);
+        let mut state_update = TrieUpdate::new(trie);
 
         let mut logs = vec![];
         let view_state = ViewApplyState {
@@ -3790,7 +3801,7 @@ mod contract_precompilation_tests {
         };
         viewer
             .call_function(
-                state_update,
+                &mut state_update,
                 view_state,
                 &"test0".parse().unwrap(),
                 "log_something",
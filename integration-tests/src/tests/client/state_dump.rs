@@ -4,7 +4,7 @@ use near_async::time::{Clock, Duration};
 use near_chain::near_chain_primitives::error::QueryError;
 use near_chain::{ChainGenesis, ChainStoreAccess, Provenance};
 use near_chain_configs::ExternalStorageLocation::Filesystem;
-use near_chain_configs::{DumpConfig, Genesis, MutableConfigValue, NEAR_BASE};
+use near_chain_configs::{DumpConfig, Genesis, MutableConfigValue, StatePartsLayout, NEAR_BASE};
 use near_client::sync::external::{external_storage_location, StateFileType};
 use near_client::test_utils::TestEnv;
 use near_client::ProcessTxResponse;
@@ -51,6 +51,8 @@ fn test_state_dump() {
         restart_dump_for_shards: None,
         iteration_delay: Some(Duration::ZERO),
         credentials_file: None,
+        parts_layout: StatePartsLayout::Raw,
+        zstd_compression_level: None,
     });
 
     let validator = MutableConfigValue::new(
@@ -161,6 +163,8 @@ fn run_state_sync_with_dumped_parts(
         restart_dump_for_shards: None,
         iteration_delay: Some(Duration::ZERO),
         credentials_file: None,
+        parts_layout: StatePartsLayout::Raw,
+        zstd_compression_level: None,
     });
     let mut state_sync_dumper = StateSyncDumper {
         clock: Clock::real(),
@@ -218,7 +222,10 @@ fn run_state_sync_with_dumped_parts(
             &head.prev_block_hash,
             &head.last_block_hash,
             head_block.header().epoch_id(),
-            &QueryRequest::ViewAccount { account_id: "test_account".parse().unwrap() },
+            &QueryRequest::ViewAccount {
+                account_id: "test_account".parse().unwrap(),
+                include_proof: false,
+            },
         )
         .unwrap();
     assert_matches!(response.kind, QueryResponseKind::ViewAccount(_));
@@ -325,7 +332,10 @@ fn run_state_sync_with_dumped_parts(
         &synced_block_tip.prev_block_hash,
         &synced_block_tip.last_block_hash,
         synced_block_header.epoch_id(),
-        &QueryRequest::ViewAccount { account_id: "test_account".parse().unwrap() },
+        &QueryRequest::ViewAccount {
+            account_id: "test_account".parse().unwrap(),
+            include_proof: false,
+        },
     );
 
     if is_final_block_in_new_epoch {
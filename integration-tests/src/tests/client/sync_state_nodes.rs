@@ -5,7 +5,9 @@ use near_actix_test_utils::run_actix;
 use near_async::time::Duration;
 use near_chain::Provenance;
 use near_chain_configs::ExternalStorageLocation::Filesystem;
-use near_chain_configs::{DumpConfig, ExternalStorageConfig, Genesis, SyncConfig};
+use near_chain_configs::{
+    DumpConfig, ExternalStorageConfig, Genesis, StatePartsLayout, SyncConfig,
+};
 use near_client::test_utils::TestEnv;
 use near_client::{GetBlock, ProcessTxResponse};
 use near_client_primitives::types::GetValidatorInfo;
@@ -339,6 +341,8 @@ fn ultra_slow_test_sync_state_dump() {
                 restart_dump_for_shards: None,
                 iteration_delay: Some(Duration::milliseconds(500)),
                 credentials_file: None,
+                parts_layout: StatePartsLayout::Raw,
+                zstd_compression_level: None,
             });
             near1.config.store.state_snapshot_enabled = true;
 
@@ -389,6 +393,7 @@ fn ultra_slow_test_sync_state_dump() {
                                         num_concurrent_requests: 1,
                                         num_concurrent_requests_during_catchup: 1,
                                         external_storage_fallback_threshold: 0,
+                                        parts_layout: StatePartsLayout::Raw,
                                     });
 
                                 let nearcore::NearNode {
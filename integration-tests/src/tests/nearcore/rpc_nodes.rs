@@ -274,6 +274,7 @@ fn ultra_slow_test_query_rpc_account_view_must_succeed() {
                 block_reference: near_primitives::types::BlockReference::Finality(Finality::Final),
                 request: near_primitives::views::QueryRequest::ViewAccount {
                     account_id: "near.0".parse().unwrap(),
+                    include_proof: false,
                 },
             })
             .await
@@ -313,6 +314,7 @@ fn ultra_slow_test_query_rpc_account_view_account_doesnt_exist_must_return_error
                     block_reference: near_primitives::types::BlockReference::Finality(Finality::Final),
                     request: near_primitives::views::QueryRequest::ViewAccount {
                         account_id: "accountdoesntexist.0".parse().unwrap(),
+                        include_proof: false,
                     },
                 })
                 .await;
@@ -266,6 +266,7 @@ fn ultra_slow_test_validator_kickout() {
                                         BlockReference::latest(),
                                         QueryRequest::ViewAccount {
                                             account_id: test_nodes[i as usize].account_id.clone(),
+                                            include_proof: false,
                                         },
                                     )
                                     .with_span_context(),
@@ -292,6 +293,7 @@ fn ultra_slow_test_validator_kickout() {
                                         BlockReference::latest(),
                                         QueryRequest::ViewAccount {
                                             account_id: test_nodes[i as usize].account_id.clone(),
+                                            include_proof: false,
                                         },
                                     )
                                     .with_span_context(),
@@ -432,6 +434,7 @@ fn ultra_slow_test_validator_join() {
                                     BlockReference::latest(),
                                     QueryRequest::ViewAccount {
                                         account_id: test_nodes[1].account_id.clone(),
+                                        include_proof: false,
                                     },
                                 )
                                 .with_span_context(),
@@ -451,6 +454,7 @@ fn ultra_slow_test_validator_join() {
                                     BlockReference::latest(),
                                     QueryRequest::ViewAccount {
                                         account_id: test_nodes[2].account_id.clone(),
+                                        include_proof: false,
                                     },
                                 )
                                 .with_span_context(),
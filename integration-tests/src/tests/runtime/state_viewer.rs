@@ -102,7 +102,7 @@ impl ProofVerifier {
 
 #[test]
 fn test_view_call() {
-    let (viewer, root) = get_test_trie_viewer();
+    let (viewer, mut root) = get_test_trie_viewer();
 
     let mut logs = vec![];
     let view_state = ViewApplyState {
@@ -117,7 +117,7 @@ fn test_view_call() {
         cache: None,
     };
     let result = viewer.call_function(
-        root,
+        &mut root,
         view_state,
         &"test.contract".parse().unwrap(),
         "run_test",
@@ -131,7 +131,7 @@ fn test_view_call() {
 
 #[test]
 fn test_view_call_try_changing_storage() {
-    let (viewer, root) = get_test_trie_viewer();
+    let (viewer, mut root) = get_test_trie_viewer();
 
     let mut logs = vec![];
     let view_state = ViewApplyState {
@@ -146,7 +146,7 @@ fn test_view_call_try_changing_storage() {
         cache: None,
     };
     let result = viewer.call_function(
-        root,
+        &mut root,
         view_state,
         &"test.contract".parse().unwrap(),
         "run_test_with_storage_change",
@@ -164,7 +164,7 @@ fn test_view_call_try_changing_storage() {
 
 #[test]
 fn test_view_call_with_args() {
-    let (viewer, root) = get_test_trie_viewer();
+    let (viewer, mut root) = get_test_trie_viewer();
     let args: Vec<_> = [1u64, 2u64].iter().flat_map(|x| (*x).to_le_bytes().to_vec()).collect();
     let mut logs = vec![];
     let view_state = ViewApplyState {
@@ -179,7 +179,7 @@ fn test_view_call_with_args() {
         cache: None,
     };
     let view_call_result = viewer.call_function(
-        root,
+        &mut root,
         view_state,
         &"test.contract".parse().unwrap(),
         "sum_with_input",
@@ -355,6 +355,79 @@ fn test_view_state() {
     }
 }
 
+#[test]
+fn test_view_state_page() {
+    let (_, tries, root) = get_runtime_and_trie();
+    let shard_uid = TEST_SHARD_UID;
+    let mut state_update = tries.new_trie_update(shard_uid, root);
+    let keys: [&[u8]; 5] = [b"a1", b"a2", b"a3", b"b1", b"b2"];
+    for key in &keys {
+        state_update.set(
+            TrieKey::ContractData { account_id: alice_account(), key: key.to_vec() },
+            key.to_vec(),
+        );
+    }
+    // Data of a neighbouring account is never returned.
+    state_update.set(
+        TrieKey::ContractData { account_id: "alina".parse().unwrap(), key: b"a4".to_vec() },
+        b"a4".to_vec(),
+    );
+    state_update.commit(StateChangeCause::InitialState);
+    let trie_changes = state_update.finalize().unwrap().trie_changes;
+    let mut db_changes = tries.store_update();
+    let new_root = tries.apply_all(&trie_changes, shard_uid, &mut db_changes);
+    db_changes.commit().unwrap();
+    let state_update = tries.new_trie_update(shard_uid, new_root);
+
+    // Walks over all pages and returns the keys of each of them.
+    let collect_pages = |trie_viewer: &TrieViewer, prefix: &[u8], limit: u64| {
+        let mut pages = vec![];
+        let mut start_key: Vec<u8> = vec![];
+        loop {
+            let result = trie_viewer
+                .view_state_page(&state_update, &alice_account(), prefix, &start_key, limit, false)
+                .unwrap();
+            pages.push(result.values.into_iter().map(|item| item.key.to_vec()).collect::<Vec<_>>());
+            match result.continuation_token {
+                Some(token) => start_key = token.into(),
+                None => return pages,
+            }
+        }
+    };
+    let to_vecs = |keys: &[&[u8]]| keys.iter().map(|key| key.to_vec()).collect::<Vec<_>>();
+
+    let trie_viewer = TrieViewer::default();
+    assert_eq!(
+        collect_pages(&trie_viewer, b"", 2),
+        vec![to_vecs(&keys[0..2]), to_vecs(&keys[2..4]), to_vecs(&keys[4..5])]
+    );
+    assert_eq!(collect_pages(&trie_viewer, b"", 10), vec![to_vecs(&keys)]);
+    assert_eq!(
+        collect_pages(&trie_viewer, b"a", 2),
+        vec![to_vecs(&keys[0..2]), to_vecs(&keys[2..3])]
+    );
+    assert_eq!(collect_pages(&trie_viewer, b"c", 2), vec![Vec::<Vec<u8>>::new()]);
+
+    // Every key and value take 2 bytes, so pages are cut after two items.
+    let trie_viewer = TrieViewer::new(Some(8), None);
+    assert_eq!(collect_pages(&trie_viewer, b"b", 10), vec![to_vecs(&keys[3..5])]);
+    assert_eq!(
+        collect_pages(&trie_viewer, b"", 10),
+        vec![to_vecs(&keys[0..2]), to_vecs(&keys[2..4]), to_vecs(&keys[4..5])]
+    );
+
+    // Start keys which don't exist in the state are fine too.
+    let result = trie_viewer
+        .view_state_page(&state_update, &alice_account(), b"", b"a25", 1, false)
+        .unwrap();
+    assert_eq!(result.values[0].key.to_vec(), b"a3".to_vec());
+    assert_eq!(result.continuation_token.unwrap().to_vec(), b"b1".to_vec());
+
+    let result =
+        trie_viewer.view_state_page(&state_update, &"nobody".parse().unwrap(), b"", b"", 1, false);
+    assert!(matches!(result, Err(errors::ViewStateError::AccountDoesNotExist { .. })));
+}
+
 #[test]
 fn test_view_state_too_large() {
     let (_, tries, root) = get_runtime_and_trie();
@@ -387,7 +460,7 @@ fn test_view_state_with_large_contract() {
 
 #[test]
 fn test_log_when_panic() {
-    let (viewer, root) = get_test_trie_viewer();
+    let (viewer, mut root) = get_test_trie_viewer();
     let view_state = ViewApplyState {
         block_height: 1,
         prev_block_hash: CryptoHash::default(),
@@ -402,7 +475,7 @@ fn test_log_when_panic() {
     let mut logs = vec![];
     viewer
         .call_function(
-            root,
+            &mut root,
             view_state,
             &"test.contract".parse().unwrap(),
             "panic_after_logging",
@@ -70,7 +70,8 @@ impl RpcUser {
 
 impl User for RpcUser {
     fn view_account(&self, account_id: &AccountId) -> Result<AccountView, String> {
-        let query = QueryRequest::ViewAccount { account_id: account_id.clone() };
+        let query =
+            QueryRequest::ViewAccount { account_id: account_id.clone(), include_proof: false };
         match self.query(query)?.kind {
             QueryResponseKind::ViewAccount(account_view) => Ok(account_view),
             _ => Err("Invalid type of response".into()),
@@ -81,6 +82,8 @@ impl User for RpcUser {
         let query = QueryRequest::ViewState {
             account_id: account_id.clone(),
             prefix: prefix.to_vec().into(),
+            start_key: None,
+            limit: None,
             include_proof: false,
         };
         match self.query(query)?.kind {
@@ -90,7 +93,10 @@ impl User for RpcUser {
     }
 
     fn is_locked(&self, account_id: &AccountId) -> Result<bool, String> {
-        let query = QueryRequest::ViewAccessKeyList { account_id: account_id.clone() };
+        let query = QueryRequest::ViewAccessKeyList {
+            account_id: account_id.clone(),
+            include_proof: false,
+        };
         match self.query(query)?.kind {
             QueryResponseKind::AccessKeyList(access_keys) => Ok(access_keys.keys.is_empty()),
             _ => Err("Invalid type of response".into()),
@@ -98,7 +104,7 @@ impl User for RpcUser {
     }
 
     fn view_contract_code(&self, account_id: &AccountId) -> Result<ContractCodeView, String> {
-        let query = QueryRequest::ViewCode { account_id: account_id.clone() };
+        let query = QueryRequest::ViewCode { account_id: account_id.clone(), include_proof: false };
         match self.query(query)?.kind {
             QueryResponseKind::ViewCode(contract_code_view) => Ok(contract_code_view),
             _ => Err("Invalid type of response".into()),
@@ -115,6 +121,7 @@ impl User for RpcUser {
             account_id: account_id.clone(),
             method_name: method_name.to_string(),
             args: args.to_vec().into(),
+            include_proof: false,
         };
         match self.query(query)?.kind {
             QueryResponseKind::CallResult(call_result) => Ok(call_result),
@@ -218,6 +225,7 @@ impl User for RpcUser {
         let query = QueryRequest::ViewAccessKey {
             account_id: account_id.clone(),
             public_key: public_key.clone(),
+            include_proof: false,
         };
         match self.query(query)?.kind {
             QueryResponseKind::AccessKey(access_key) => Ok(access_key),
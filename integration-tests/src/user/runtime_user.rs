@@ -306,7 +306,7 @@ impl User for RuntimeUser {
 
         let apply_state = self.apply_state();
         let client = self.client.read().expect(POISONED_LOCK_ERR);
-        let state_update = client.get_state_update();
+        let mut state_update = client.get_state_update();
         let mut result = CallResult::default();
         let view_state = ViewApplyState {
             block_height: apply_state.block_height,
@@ -322,7 +322,7 @@ impl User for RuntimeUser {
         result.result = self
             .trie_viewer
             .call_function(
-                state_update,
+                &mut state_update,
                 view_state,
                 account_id,
                 method_name,
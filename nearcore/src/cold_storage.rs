@@ -2,15 +2,19 @@ use std::sync::{atomic::AtomicBool, Arc};
 
 use near_chain::types::Tip;
 use near_epoch_manager::{EpochManagerAdapter, EpochManagerHandle};
+use near_primitives::block::BlockHeader;
 use near_primitives::errors::EpochError;
+use near_primitives::types::BlockHeightDelta;
 use near_primitives::{hash::CryptoHash, types::BlockHeight};
-use near_store::config::SplitStorageConfig;
+use near_store::config::{ColdStoreRetentionPolicy, ColdStoreSegmentsConfig, SplitStorageConfig};
 use near_store::{
     archive::cold_storage::{
-        copy_all_data_to_cold, get_cold_head, update_cold_db, update_cold_head,
-        CopyAllDataToColdStatus,
+        copy_all_data_to_cold, get_cold_column_tails, get_cold_head, prune_cold_db,
+        update_cold_column_tails, update_cold_db, update_cold_head, CopyAllDataToColdStatus,
     },
+    archive::segments::{get_cold_segments_head, ColdSegmentBuilder},
     db::ColdDB,
+    flat::archival::update_archival_flat_state,
     DBCol, NodeStorage, Store, FINAL_HEAD_KEY, TAIL_KEY,
 };
 
@@ -49,6 +53,32 @@ enum ColdStoreCopyResult {
     OtherBlockCopied,
 }
 
+/// The ColdStorePruneResult indicates if there is more data to prune.
+#[derive(Debug)]
+enum ColdStorePruneResult {
+    /// No data was pruned. All columns are within their retention policies.
+    NothingPruned,
+    /// The data of all heights outside of the retention policies was pruned.
+    AllPruned,
+    /// Some heights were pruned, there are more heights that can be pruned
+    /// immediately.
+    MorePending,
+}
+
+/// The maximum number of heights pruned from cold storage at once, so that
+/// copying new blocks is not delayed for too long.
+const MAX_HEIGHTS_PRUNED_AT_ONCE: BlockHeight = 100;
+
+/// The ColdStoreOffloadResult indicates if there is more data to offload.
+#[derive(Debug)]
+enum ColdStoreOffloadResult {
+    /// No segment was sealed. The next segment includes heights which have
+    /// to be kept in the local cold storage.
+    NothingOffloaded,
+    /// A segment was sealed, the next one may be sealed immediately.
+    SegmentSealed,
+}
+
 /// The ColdStoreError indicates what errors were encountered while copying a blocks and running sanity checks.
 #[derive(thiserror::Error, Debug)]
 pub enum ColdStoreError {
@@ -83,13 +113,14 @@ impl From<EpochError> for ColdStoreError {
 
 /// Checks if cold store head is behind the final head and if so copies data
 /// for the next available produced block after current cold store head.
-/// Updates cold store head after.
+/// Updates the archival flat state if enabled and cold store head after.
 fn cold_store_copy(
     hot_store: &Store,
     cold_db: &ColdDB,
     genesis_height: BlockHeight,
     epoch_manager: &EpochManagerHandle,
     num_threads: usize,
+    archival_flat_state: bool,
 ) -> anyhow::Result<ColdStoreCopyResult, ColdStoreError> {
     // If HEAD is not set for cold storage we default it to genesis_height.
     let cold_head = get_cold_head(cold_db)?;
@@ -136,6 +167,9 @@ fn cold_store_copy(
         }
     }
 
+    if archival_flat_state {
+        update_archival_flat_state(cold_db, hot_store, cold_head_height, next_height)?;
+    }
     update_cold_head(cold_db, hot_store, &next_height)?;
 
     let result = if next_height >= hot_final_head_height {
@@ -148,6 +182,130 @@ fn cold_store_copy(
     result
 }
 
+/// Removes data of the columns with a retention policy from cold storage for
+/// up to `MAX_HEIGHTS_PRUNED_AT_ONCE` heights which are older than the policy
+/// allows, i.e. more than `keep_epochs` epochs behind the cold head.
+/// Updates the cold column tails after.
+fn cold_store_prune(
+    hot_store: &Store,
+    cold_db: &Arc<ColdDB>,
+    genesis_height: BlockHeight,
+    epoch_length: BlockHeightDelta,
+    epoch_manager: &EpochManagerHandle,
+    retention: &[(DBCol, ColdStoreRetentionPolicy)],
+) -> anyhow::Result<ColdStorePruneResult, ColdStoreError> {
+    if retention.is_empty() {
+        return Ok(ColdStorePruneResult::NothingPruned);
+    }
+    let Some(cold_head) = get_cold_head(cold_db)? else {
+        return Ok(ColdStorePruneResult::NothingPruned);
+    };
+
+    // If the tail of a column is not set nothing was pruned from it yet.
+    let mut tails = get_cold_column_tails(cold_db)?;
+    let ranges = retention
+        .iter()
+        .map(|(col, policy)| {
+            let tail = tails.get(<&str>::from(col)).copied().unwrap_or(genesis_height);
+            let retained_from =
+                cold_head.height.saturating_sub(policy.keep_epochs.saturating_mul(epoch_length));
+            (*col, tail..retained_from)
+        })
+        .filter(|(_, range)| !range.is_empty())
+        .collect::<Vec<_>>();
+    let Some(start) = ranges.iter().map(|(_, range)| range.start).min() else {
+        return Ok(ColdStorePruneResult::NothingPruned);
+    };
+    let end = ranges.iter().map(|(_, range)| range.end).max().unwrap_or(start);
+    let end = end.min(start + MAX_HEIGHTS_PRUNED_AT_ONCE);
+
+    let _span =
+        tracing::debug_span!(target: "cold_store", "cold_store_prune", start, end).entered();
+
+    for height in start..end {
+        let columns = ranges
+            .iter()
+            .filter(|(_, range)| range.contains(&height))
+            .map(|(col, _)| *col)
+            .collect::<Vec<_>>();
+        if columns.is_empty() {
+            continue;
+        }
+        // BlockHeight and BlockHeader are never garbage collected from hot storage.
+        let Some(block_hash) =
+            hot_store.get_ser::<CryptoHash>(DBCol::BlockHeight, &height.to_le_bytes())?
+        else {
+            continue;
+        };
+        let Some(header) =
+            hot_store.get_ser::<BlockHeader>(DBCol::BlockHeader, block_hash.as_bytes())?
+        else {
+            let message = format!("Block header {block_hash} not found in hot storage");
+            return Err(ColdStoreError::Error { message });
+        };
+        let shard_layout = epoch_manager.get_shard_layout(header.epoch_id())?;
+        prune_cold_db(cold_db, hot_store, &shard_layout, &height, &columns)?;
+    }
+
+    for (col, range) in &ranges {
+        tails.insert(<&str>::from(col).to_string(), range.end.min(end).max(range.start));
+    }
+    update_cold_column_tails(cold_db, hot_store, &tails)?;
+
+    if ranges.iter().any(|(_, range)| range.end > end) {
+        Ok(ColdStorePruneResult::MorePending)
+    } else {
+        Ok(ColdStorePruneResult::AllPruned)
+    }
+}
+
+/// Offloads the data of the next segment of `segment_length` heights from the
+/// local cold storage to the object store, if all of its heights are more than
+/// `keep_local_epochs` epochs behind the cold head.
+fn cold_store_offload(
+    hot_store: &Store,
+    cold_db: &Arc<ColdDB>,
+    genesis_height: BlockHeight,
+    epoch_length: BlockHeightDelta,
+    epoch_manager: &EpochManagerHandle,
+    config: &ColdStoreSegmentsConfig,
+) -> anyhow::Result<ColdStoreOffloadResult, ColdStoreError> {
+    let Some(cold_head) = get_cold_head(cold_db)? else {
+        return Ok(ColdStoreOffloadResult::NothingOffloaded);
+    };
+    let start = get_cold_segments_head(cold_db)?.map_or(genesis_height, |head| head + 1);
+    let end = start + config.segment_length - 1;
+    let kept_from =
+        cold_head.height.saturating_sub(config.keep_local_epochs.saturating_mul(epoch_length));
+    if end >= kept_from {
+        return Ok(ColdStoreOffloadResult::NothingOffloaded);
+    }
+
+    let _span =
+        tracing::debug_span!(target: "cold_store", "cold_store_offload", start, end).entered();
+
+    let mut builder = ColdSegmentBuilder::new(cold_db, start)?;
+    for height in start..=end {
+        // BlockHeight and BlockHeader are never garbage collected from hot storage.
+        let Some(block_hash) =
+            hot_store.get_ser::<CryptoHash>(DBCol::BlockHeight, &height.to_le_bytes())?
+        else {
+            continue;
+        };
+        let Some(header) =
+            hot_store.get_ser::<BlockHeader>(DBCol::BlockHeader, block_hash.as_bytes())?
+        else {
+            let message = format!("Block header {block_hash} not found in hot storage");
+            return Err(ColdStoreError::Error { message });
+        };
+        let shard_layout = epoch_manager.get_shard_layout(header.epoch_id())?;
+        builder.add_height(cold_db, hot_store, &shard_layout, &height)?;
+    }
+    builder.seal(cold_db, end)?;
+
+    Ok(ColdStoreOffloadResult::SegmentSealed)
+}
+
 // Check some basic sanity conditions.
 // * cold head <= hot final head
 // * cold head >= hot tail
@@ -347,8 +505,9 @@ fn cold_store_migration_loop(
 
 // This method will copy data from hot storage to cold storage in a loop.
 // It will try to copy blocks as fast as possible up until cold head = final head.
-// Once the cold head reaches the final head it will sleep for one second before
-// trying to copy data at the next height.
+// Once the cold head reaches the final head it will prune data outside of the
+// retention policies, offload old data to the cold segments if configured and
+// then sleep for one second before trying to copy data at the next height.
 // TODO clean up the interface, currently we need to pass hot store, cold store and
 // cold_db which is redundant.
 fn cold_store_loop(
@@ -357,7 +516,9 @@ fn cold_store_loop(
     hot_store: Store,
     cold_db: Arc<ColdDB>,
     genesis_height: BlockHeight,
+    epoch_length: BlockHeightDelta,
     epoch_manager: &EpochManagerHandle,
+    retention: &[(DBCol, ColdStoreRetentionPolicy)],
 ) {
     tracing::info!(target : "cold_store", "Starting the cold store loop");
 
@@ -374,6 +535,7 @@ fn cold_store_loop(
             genesis_height,
             epoch_manager,
             split_storage_config.num_cold_store_read_threads,
+            split_storage_config.enable_archival_flat_state,
         );
         let duration = instant.elapsed();
 
@@ -390,13 +552,46 @@ fn cold_store_loop(
                 std::thread::sleep(sleep_duration.unsigned_abs());
             }
             // If no block was copied the cold head is up to date with final head and
-            // this loop should sleep while waiting for a new block to get finalized.
-            Ok(ColdStoreCopyResult::NoBlockCopied) => {
-                std::thread::sleep(sleep_duration.unsigned_abs());
-            }
-            // The final head block was copied. There are no more blocks to be copied now
-            // this loop should sleep while waiting for a new block to get finalized.
-            Ok(ColdStoreCopyResult::LatestBlockCopied) => {
+            // this loop should prune old data and then sleep while waiting for a new
+            // block to get finalized.
+            // The same is true if the final head block was copied.
+            Ok(ColdStoreCopyResult::NoBlockCopied | ColdStoreCopyResult::LatestBlockCopied) => {
+                let result = cold_store_prune(
+                    &hot_store,
+                    &cold_db,
+                    genesis_height,
+                    epoch_length,
+                    epoch_manager,
+                    retention,
+                );
+                match result {
+                    // There are more heights to prune, continue pruning after
+                    // checking for new blocks to copy.
+                    Ok(ColdStorePruneResult::MorePending) => continue,
+                    Ok(ColdStorePruneResult::NothingPruned | ColdStorePruneResult::AllPruned) => {}
+                    Err(err) => {
+                        tracing::error!(target : "cold_store", error = format!("{err:#?}"), "cold_store_prune failed");
+                    }
+                }
+                if let Some(segments_config) = &split_storage_config.cold_store_segments {
+                    let result = cold_store_offload(
+                        &hot_store,
+                        &cold_db,
+                        genesis_height,
+                        epoch_length,
+                        epoch_manager,
+                        segments_config,
+                    );
+                    match result {
+                        // There may be more segments to seal, continue
+                        // offloading after checking for new blocks to copy.
+                        Ok(ColdStoreOffloadResult::SegmentSealed) => continue,
+                        Ok(ColdStoreOffloadResult::NothingOffloaded) => {}
+                        Err(err) => {
+                            tracing::error!(target : "cold_store", error = format!("{err:#?}"), "cold_store_offload failed");
+                        }
+                    }
+                }
                 std::thread::sleep(sleep_duration.unsigned_abs());
             }
             // A block older than the final head was copied. We should continue copying
@@ -444,6 +639,9 @@ pub fn spawn_cold_store_loop(
     sanity_check(&hot_store, cold_db.as_ref(), genesis_height)?;
 
     let split_storage_config = config.config.split_storage.clone().unwrap_or_default();
+    let retention =
+        split_storage_config.cold_store_retention_columns().map_err(anyhow::Error::msg)?;
+    let epoch_length = config.genesis.config.epoch_length;
 
     tracing::info!(target : "cold_store", "Spawning the cold store loop");
     let join_handle =
@@ -461,7 +659,9 @@ pub fn spawn_cold_store_loop(
                 hot_store,
                 cold_db,
                 genesis_height,
+                epoch_length,
                 epoch_manager.as_ref(),
+                &retention,
             )
         })?;
 
@@ -14,20 +14,22 @@ use near_chain_configs::{
     default_header_sync_progress_timeout, default_header_sync_stall_ban_timeout,
     default_log_summary_period, default_orphan_state_witness_max_size,
     default_orphan_state_witness_pool_size, default_produce_chunk_add_transactions_time_limit,
-    default_state_sync_enabled, default_state_sync_external_backoff,
-    default_state_sync_external_timeout, default_state_sync_p2p_timeout,
-    default_state_sync_retry_backoff, default_sync_check_period, default_sync_height_threshold,
-    default_sync_max_block_requests, default_sync_step_period, default_transaction_pool_size_limit,
+    default_simulation_threads, default_simulation_time_limit, default_state_sync_enabled,
+    default_state_sync_external_backoff, default_state_sync_external_timeout,
+    default_state_sync_p2p_timeout, default_state_sync_retry_backoff, default_sync_check_period,
+    default_sync_height_threshold, default_sync_max_block_requests, default_sync_step_period,
+    default_transaction_pool_max_transactions_per_key, default_transaction_pool_size_limit,
     default_trie_viewer_state_size_limit, default_tx_routing_height_horizon,
     default_view_client_threads, default_view_client_throttle_period, get_initial_supply,
     ChunkDistributionNetworkConfig, ClientConfig, EpochSyncConfig, GCConfig, Genesis,
     GenesisConfig, GenesisValidationMode, LogSummaryStyle, MutableConfigValue,
-    MutableValidatorSigner, ReshardingConfig, StateSyncConfig, BLOCK_PRODUCER_KICKOUT_THRESHOLD,
-    CHUNK_PRODUCER_KICKOUT_THRESHOLD, CHUNK_VALIDATOR_ONLY_KICKOUT_THRESHOLD,
-    EXPECTED_EPOCH_LENGTH, FAST_EPOCH_LENGTH, FISHERMEN_THRESHOLD, GAS_PRICE_ADJUSTMENT_RATE,
-    GENESIS_CONFIG_FILENAME, INITIAL_GAS_LIMIT, MAX_INFLATION_RATE, MIN_BLOCK_PRODUCTION_DELAY,
-    MIN_GAS_PRICE, NEAR_BASE, NUM_BLOCKS_PER_YEAR, NUM_BLOCK_PRODUCER_SEATS, PROTOCOL_REWARD_RATE,
-    PROTOCOL_UPGRADE_STAKE_THRESHOLD, TRANSACTION_VALIDITY_PERIOD,
+    MutableValidatorSigner, NonceReplacementPolicy, ReshardingConfig, StateSyncConfig,
+    BLOCK_PRODUCER_KICKOUT_THRESHOLD, CHUNK_PRODUCER_KICKOUT_THRESHOLD,
+    CHUNK_VALIDATOR_ONLY_KICKOUT_THRESHOLD, EXPECTED_EPOCH_LENGTH, FAST_EPOCH_LENGTH,
+    FISHERMEN_THRESHOLD, GAS_PRICE_ADJUSTMENT_RATE, GENESIS_CONFIG_FILENAME, INITIAL_GAS_LIMIT,
+    MAX_INFLATION_RATE, MIN_BLOCK_PRODUCTION_DELAY, MIN_GAS_PRICE, NEAR_BASE, NUM_BLOCKS_PER_YEAR,
+    NUM_BLOCK_PRODUCER_SEATS, PROTOCOL_REWARD_RATE, PROTOCOL_UPGRADE_STAKE_THRESHOLD,
+    TRANSACTION_VALIDITY_PERIOD,
 };
 use near_config_utils::{DownloadConfigType, ValidationError, ValidationErrors};
 use near_crypto::{InMemorySigner, KeyFile, KeyType, PublicKey};
@@ -47,6 +49,7 @@ use near_primitives::types::{
     ShardId,
 };
 use near_primitives::utils::{from_timestamp, get_num_seats_per_shard};
+use near_primitives::validator_signer::remote::RemoteValidatorSigner;
 use near_primitives::validator_signer::{InMemoryValidatorSigner, ValidatorSigner};
 use near_primitives::version::{ProtocolVersion, PROTOCOL_VERSION};
 #[cfg(feature = "rosetta_rpc")]
@@ -225,6 +228,46 @@ impl Default for Consensus {
     }
 }
 
+/// Signer process holding the validator key, see
+/// `near_primitives::validator_signer::remote::RemoteValidatorSigner`.
+///
+/// The node identifies itself to the signer with its node key.
+///
+/// Requests to the signer block the actor which signs, e.g. the client
+/// producing a block, for up to `request_timeout` each, so the signer should
+/// have a low latency to the node. The latency and the failures of the
+/// requests are exported as the `near_remote_signer_request_latency_sec` and
+/// `near_remote_signer_request_failures_total` metrics.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RemoteSignerConfig {
+    /// Address of the signer, either `host:port` or `unix:<path>`.
+    pub addr: String,
+    /// Validator account whose key the signer holds.
+    pub account_id: AccountId,
+    /// Public validator key held by the signer.
+    pub public_key: PublicKey,
+    /// Public key with which the signer proves its identity.
+    pub signer_identity_key: PublicKey,
+    /// Timeout of establishing the connection to the signer.
+    #[serde(default = "default_remote_signer_connect_timeout")]
+    #[serde(with = "near_async::time::serde_duration_as_std")]
+    pub connect_timeout: Duration,
+    /// Timeout of a single request to the signer, including connecting to it
+    /// if needed. Blocks, chunks and approvals which can't be signed within it
+    /// are skipped.
+    #[serde(default = "default_remote_signer_request_timeout")]
+    #[serde(with = "near_async::time::serde_duration_as_std")]
+    pub request_timeout: Duration,
+}
+
+fn default_remote_signer_connect_timeout() -> Duration {
+    Duration::seconds(1)
+}
+
+fn default_remote_signer_request_timeout() -> Duration {
+    Duration::milliseconds(500)
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
 #[serde(default)]
 pub struct Config {
@@ -232,6 +275,10 @@ pub struct Config {
     pub genesis_records_file: Option<String>,
     pub validator_key_file: String,
     pub node_key_file: String,
+    /// If set, the validator key is held by a separate signer process instead
+    /// of `validator_key_file`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote_signer: Option<RemoteSignerConfig>,
     #[cfg(feature = "json_rpc")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub rpc: Option<RpcConfig>,
@@ -267,6 +314,11 @@ pub struct Config {
     pub view_client_threads: usize,
     #[serde(with = "near_async::time::serde_duration_as_std")]
     pub view_client_throttle_period: Duration,
+    /// Number of threads simulating transactions for `EXPERIMENTAL_simulate_tx`.
+    pub simulation_threads: usize,
+    /// Wall-clock time limit of a single transaction simulation.
+    #[serde(with = "near_async::time::serde_duration_as_std")]
+    pub simulation_time_limit: Duration,
     pub trie_viewer_state_size_limit: Option<u64>,
     /// If set, overrides value in genesis configuration.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -301,6 +353,17 @@ pub struct Config {
     /// Setting this value too low (<1MB) on the validator might lead to production of smaller
     /// chunks and underutilizing the capacity of the network.
     pub transaction_pool_size_limit: Option<u64>,
+    /// Limit of the number of transactions of a (signer, public key) pair in the per-shard
+    /// transaction pool. If not set, which is the default, the number will be unbounded.
+    ///
+    /// A transaction over the limit is rejected, unless it has a lower nonce than a transaction
+    /// of the pair in the pool, in which case the transaction with the highest nonce is evicted.
+    /// This prevents a single account from filling the whole pool.
+    pub transaction_pool_max_transactions_per_key: Option<usize>,
+    /// What the transaction pool does when it receives a transaction with the same signer,
+    /// public key and nonce as a transaction it already holds. Replacing transactions allows
+    /// to unstick a transaction, e.g. by sending one with a higher priority fee.
+    pub transaction_pool_nonce_replacement: NonceReplacementPolicy,
     // Configuration for resharding.
     pub resharding_config: ReshardingConfig,
     /// If the node is not a chunk producer within that many blocks, then route
@@ -353,6 +416,7 @@ impl Default for Config {
             genesis_records_file: None,
             validator_key_file: VALIDATOR_KEY_FILE.to_string(),
             node_key_file: NODE_KEY_FILE.to_string(),
+            remote_signer: None,
             #[cfg(feature = "json_rpc")]
             rpc: Some(RpcConfig::default()),
             #[cfg(feature = "rosetta_rpc")]
@@ -371,6 +435,8 @@ impl Default for Config {
             gc: GCConfig::default(),
             view_client_threads: default_view_client_threads(),
             view_client_throttle_period: default_view_client_throttle_period(),
+            simulation_threads: default_simulation_threads(),
+            simulation_time_limit: default_simulation_time_limit(),
             trie_viewer_state_size_limit: default_trie_viewer_state_size_limit(),
             max_gas_burnt_view: None,
             store: near_store::StoreConfig::default(),
@@ -382,6 +448,9 @@ impl Default for Config {
             epoch_sync: default_epoch_sync(),
             state_sync_enabled: default_state_sync_enabled(),
             transaction_pool_size_limit: default_transaction_pool_size_limit(),
+            transaction_pool_max_transactions_per_key:
+                default_transaction_pool_max_transactions_per_key(),
+            transaction_pool_nonce_replacement: NonceReplacementPolicy::default(),
             enable_multiline_logging: default_enable_multiline_logging(),
             resharding_config: ReshardingConfig::default(),
             tx_routing_height_horizon: default_tx_routing_height_horizon(),
@@ -552,6 +621,8 @@ impl NearConfig {
                 gc: config.gc,
                 view_client_threads: config.view_client_threads,
                 view_client_throttle_period: config.view_client_throttle_period,
+                simulation_threads: config.simulation_threads,
+                simulation_time_limit: config.simulation_time_limit,
                 trie_viewer_state_size_limit: config.trie_viewer_state_size_limit,
                 max_gas_burnt_view: config.max_gas_burnt_view,
                 enable_statistics_export: config.store.enable_statistics_export,
@@ -560,6 +631,9 @@ impl NearConfig {
                 state_sync: config.state_sync.unwrap_or_default(),
                 epoch_sync: config.epoch_sync.unwrap_or_default(),
                 transaction_pool_size_limit: config.transaction_pool_size_limit,
+                transaction_pool_max_transactions_per_key: config
+                    .transaction_pool_max_transactions_per_key,
+                transaction_pool_nonce_replacement: config.transaction_pool_nonce_replacement,
                 enable_multiline_logging: config.enable_multiline_logging.unwrap_or(true),
                 resharding_config: MutableConfigValue::new(
                     config.resharding_config,
@@ -651,6 +725,12 @@ impl NightshadeRuntime {
                 .unwrap_or_else(|| PathBuf::from("data")),
             state_snapshot_subdir: PathBuf::from("state_snapshot"),
         };
+        let mut trie_config = TrieConfig::from_store_config(&config.config.store);
+        if config.config.store.mem_tries_snapshot_enabled {
+            trie_config.mem_tries_snapshot_dir = Some(
+                home_dir.join(&state_snapshot_config.hot_store_path).join("mem_tries_snapshot"),
+            );
+        }
         // FIXME: this (and other contract runtime resources) should probably get constructed by
         // the caller and passed into this `NightshadeRuntime::from_config` here. But that's a big
         // refactor...
@@ -668,8 +748,12 @@ impl NightshadeRuntime {
             config.client_config.max_gas_burnt_view,
             None,
             config.config.gc.gc_num_epochs_to_keep(),
-            TrieConfig::from_store_config(&config.config.store),
+            trie_config,
             state_snapshot_config,
+            config.config.split_storage.as_ref().is_some_and(|split_storage| {
+                split_storage.enable_archival_flat_state
+                    && split_storage.enable_split_storage_view_client
+            }),
         ))
     }
 }
@@ -1379,10 +1463,16 @@ impl From<NodeKeyFile> for KeyFile {
     }
 }
 
+/// Loads the validator key. An encrypted key is treated as missing until it's
+/// unlocked, see `near_dyn_configs::UnlockedValidatorKey`.
 pub fn load_validator_key(validator_file: &Path) -> anyhow::Result<Option<Arc<ValidatorSigner>>> {
     if !validator_file.exists() {
         return Ok(None);
     }
+    if KeyFile::is_encrypted(validator_file)? {
+        info!(target: "neard", "Validator key {} is encrypted, the node runs without it until it's unlocked.", validator_file.display());
+        return Ok(None);
+    }
     match InMemoryValidatorSigner::from_file(&validator_file) {
         Ok(signer) => Ok(Some(Arc::new(signer.into()))),
         Err(_) => {
@@ -1393,6 +1483,27 @@ pub fn load_validator_key(validator_file: &Path) -> anyhow::Result<Option<Arc<Va
     }
 }
 
+/// Creates the validator signer which asks the signer process configured in
+/// `remote_signer` to sign. The node identifies itself with its node key.
+pub fn load_remote_validator_signer(
+    remote_signer: &RemoteSignerConfig,
+    node_key_file: &Path,
+) -> anyhow::Result<Arc<ValidatorSigner>> {
+    let node_key: KeyFile = NodeKeyFile::from_file(node_key_file)
+        .with_context(|| format!("Failed reading node key file from {}", node_key_file.display()))?
+        .into();
+    let signer = RemoteValidatorSigner::new(
+        remote_signer.account_id.clone(),
+        remote_signer.public_key.clone(),
+        remote_signer.addr.parse()?,
+        remote_signer.signer_identity_key.clone(),
+        InMemorySigner::from(node_key).into(),
+        remote_signer.connect_timeout.unsigned_abs(),
+        remote_signer.request_timeout.unsigned_abs(),
+    );
+    Ok(Arc::new(signer.into()))
+}
+
 pub fn load_config(
     dir: &Path,
     genesis_validation: GenesisValidationMode,
@@ -1406,8 +1517,13 @@ pub fn load_config(
         validation_errors.push_errors(e)
     };
 
-    let validator_file: PathBuf = dir.join(&config.validator_key_file);
-    let validator_signer = match load_validator_key(&validator_file) {
+    let validator_signer = match &config.remote_signer {
+        Some(remote_signer) => {
+            load_remote_validator_signer(remote_signer, &dir.join(&config.node_key_file)).map(Some)
+        }
+        None => load_validator_key(&dir.join(&config.validator_key_file)),
+    };
+    let validator_signer = match validator_signer {
         Ok(validator_signer) => validator_signer,
         Err(e) => {
             validation_errors.push_validator_key_file_error(e.to_string());
@@ -1420,8 +1536,15 @@ pub fn load_config(
     let network_signer = match network_signer_result {
         Ok(node_key_file) => Some(node_key_file),
         Err(_) => {
-            let error_message =
-                format!("Failed reading node key file from {}", node_key_path.display());
+            // Unlike the validator key, the node key is needed at startup.
+            let error_message = if KeyFile::is_encrypted(&node_key_path).unwrap_or(false) {
+                format!(
+                    "Node key file {} is encrypted, decrypt it with `neard key-file decrypt`",
+                    node_key_path.display()
+                )
+            } else {
+                format!("Failed reading node key file from {}", node_key_path.display())
+            };
             validation_errors.push_node_key_file_error(error_message);
             None
         }
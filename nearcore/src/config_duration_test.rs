@@ -1,10 +1,12 @@
 use std::str::FromStr;
 
-use crate::config::Config;
+use crate::config::{Config, RemoteSignerConfig};
+use near_crypto::{KeyType, PublicKey};
 use near_jsonrpc::RpcConfig;
 use near_network::config_json::{ExperimentalConfig, NetworkConfigOverrides};
 use near_o11y::testonly::init_test_logger;
 use near_primitives::types::AccountId;
+use near_store::config::{ColdStoreSegmentsConfig, ColdStoreSegmentsLocation, SplitStorageConfig};
 use near_store::StoreConfig;
 use serde::ser::{
     SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant, SerializeTuple,
@@ -38,11 +40,48 @@ fn test_config_duration_all_std() {
         rpc: Some(RpcConfig {
             experimental_debug_pages_src_path: Some(Default::default()),
             prometheus_addr: Some(Default::default()),
+            grpc_addr: Some("0.0.0.0:3040".parse().unwrap()),
+            rate_limits_config: near_jsonrpc::RpcRateLimitsConfig {
+                other_methods: near_jsonrpc::RpcMethodLimitsConfig {
+                    total: Some(near_jsonrpc::RpcTokenBucketConfig {
+                        burst: 0,
+                        requests_per_second: 0.0,
+                    }),
+                    per_client: Some(near_jsonrpc::RpcTokenBucketConfig {
+                        burst: 0,
+                        requests_per_second: 0.0,
+                    }),
+                    max_concurrent_requests: Some(0),
+                },
+                ..Default::default()
+            },
             ..Default::default()
         }),
+        remote_signer: Some(RemoteSignerConfig {
+            addr: Default::default(),
+            account_id: "test".parse().unwrap(),
+            public_key: PublicKey::empty(KeyType::ED25519),
+            signer_identity_key: PublicKey::empty(KeyType::ED25519),
+            connect_timeout: Default::default(),
+            request_timeout: Default::default(),
+        }),
         rosetta_rpc: Some(Default::default()),
         save_trie_changes: Some(Default::default()),
-        split_storage: Some(Default::default()),
+        split_storage: Some(SplitStorageConfig {
+            cold_store_segments: Some(ColdStoreSegmentsConfig {
+                location: ColdStoreSegmentsLocation::S3 {
+                    bucket: Default::default(),
+                    region: Default::default(),
+                    credentials_file: Some(Default::default()),
+                },
+                segment_length: Default::default(),
+                keep_local_epochs: Default::default(),
+                cache_path: Default::default(),
+                cache_size: Default::default(),
+                request_timeout: Default::default(),
+            }),
+            ..Default::default()
+        }),
         tracked_shadow_validator: Some(AccountId::from_str("test").unwrap()),
         tracked_shard_schedule: Some(Default::default()),
         transaction_pool_size_limit: Some(Default::default()),
@@ -1,4 +1,4 @@
-use near_chain_configs::{ExternalStorageLocation, SyncConfig};
+use near_chain_configs::{ExternalStorageLocation, StatePartsLayout, SyncConfig};
 use near_config_utils::{ValidationError, ValidationErrors};
 use std::collections::HashSet;
 use std::path::Path;
@@ -43,6 +43,22 @@ impl<'a> ConfigValidator<'a> {
             self.validation_errors.push_config_semantics_error(error_message);
         }
 
+        if let Some(split_storage) = &self.config.split_storage {
+            if let Err(error_message) = split_storage.cold_store_retention_columns() {
+                self.validation_errors.push_config_semantics_error(error_message);
+            }
+            if let Some(segments) = &split_storage.cold_store_segments {
+                if segments.segment_length == 0 {
+                    let error_message = "'config.split_storage.cold_store_segments.segment_length' needs to be greater than 0".to_string();
+                    self.validation_errors.push_config_semantics_error(error_message);
+                }
+                if segments.keep_local_epochs == 0 {
+                    let error_message = "'config.split_storage.cold_store_segments.keep_local_epochs' needs to be greater than 0".to_string();
+                    self.validation_errors.push_config_semantics_error(error_message);
+                }
+            }
+        }
+
         if self.config.consensus.min_block_production_delay
             > self.config.consensus.max_block_production_delay
         {
@@ -108,6 +124,21 @@ impl<'a> ConfigValidator<'a> {
                             self.validation_errors.push_config_semantics_error(error_message);
                         }
                     }
+                    ExternalStorageLocation::HTTP { .. } => {
+                        let error_message = format!("'config.state_sync.dump.location.HTTP' is not supported, state can only be synced from HTTP mirrors.");
+                        self.validation_errors.push_config_semantics_error(error_message);
+                    }
+                }
+
+                if let Some(level) = dump_config.zstd_compression_level {
+                    if dump_config.parts_layout != StatePartsLayout::ContentAddressed {
+                        let error_message = format!("'config.state_sync.dump.zstd_compression_level' is only supported with 'config.state_sync.dump.parts_layout' set to 'ContentAddressed'.");
+                        self.validation_errors.push_config_semantics_error(error_message);
+                    }
+                    if !(1..=22).contains(&level) {
+                        let error_message = format!("'config.state_sync.dump.zstd_compression_level' needs to be between 1 and 22, got {level}.");
+                        self.validation_errors.push_config_semantics_error(error_message);
+                    }
                 }
 
                 if let Some(credentials_file) = &dump_config.credentials_file {
@@ -139,6 +170,20 @@ impl<'a> ConfigValidator<'a> {
                                 self.validation_errors.push_config_semantics_error(error_message);
                             }
                         }
+                        ExternalStorageLocation::HTTP { mirrors } => {
+                            if mirrors.is_empty() {
+                                let error_message = format!("'config.state_sync.sync.ExternalStorage.location.HTTP.mirrors' needs to contain at least one URL when 'config.state_sync.sync.ExternalStorage.location.HTTP' is present.");
+                                self.validation_errors.push_config_semantics_error(error_message);
+                            }
+                            for mirror in mirrors {
+                                if !mirror.starts_with("http://") && !mirror.starts_with("https://")
+                                {
+                                    let error_message = format!("'config.state_sync.sync.ExternalStorage.location.HTTP.mirrors' contains '{mirror}', which is not an HTTP(S) URL.");
+                                    self.validation_errors
+                                        .push_config_semantics_error(error_message);
+                                }
+                            }
+                        }
                     }
                     if config.num_concurrent_requests == 0 {
                         let error_message = format!("'config.state_sync.sync.ExternalStorage.num_concurrent_requests' needs to be greater than 0");
@@ -148,6 +193,13 @@ impl<'a> ConfigValidator<'a> {
             }
         }
 
+        #[cfg(feature = "json_rpc")]
+        if let Some(rpc) = &self.config.rpc {
+            if let Err(error_message) = rpc.rate_limits_config.validate() {
+                self.validation_errors.push_config_semantics_error(error_message);
+            }
+        }
+
         let tx_routing_height_horizon = self.config.tx_routing_height_horizon;
         if tx_routing_height_horizon < 2 {
             let error_message = format!("'config.tx_routing_height_horizon' needs to be at least 2, got {tx_routing_height_horizon}.");
@@ -157,6 +209,11 @@ impl<'a> ConfigValidator<'a> {
             let error_message = format!("'config.tx_routing_height_horizon' can't be too high to avoid spamming the network. Keep it below 100. Got {tx_routing_height_horizon}.");
             self.validation_errors.push_config_semantics_error(error_message);
         }
+
+        if self.config.transaction_pool_max_transactions_per_key == Some(0) {
+            let error_message = format!("'config.transaction_pool_max_transactions_per_key' needs to be positive, set it to null to disable the limit.");
+            self.validation_errors.push_config_semantics_error(error_message);
+        }
     }
 
     fn result_with_full_error(&self) -> Result<(), ValidationError> {
@@ -233,6 +290,56 @@ mod tests {
         validate_config(&config).unwrap();
     }
 
+    #[test]
+    #[should_panic(
+        expected = "\\nconfig.json semantic issue: 'config.split_storage.cold_store_retention': column PartialChunks is not copied to cold storage"
+    )]
+    fn test_cold_store_retention_of_not_cold_column() {
+        let mut config = Config::default();
+        let mut split_storage = near_store::config::SplitStorageConfig::default();
+        split_storage.cold_store_retention.insert(
+            "PartialChunks".to_string(),
+            near_store::config::ColdStoreRetentionPolicy { keep_epochs: 5 },
+        );
+        config.split_storage = Some(split_storage);
+        validate_config(&config).unwrap();
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "\\nconfig.json semantic issue: 'config.state_sync.sync.ExternalStorage.location.HTTP.mirrors' contains 'ftp://example.com/dumps', which is not an HTTP(S) URL."
+    )]
+    fn test_state_sync_http_mirror_not_http() {
+        let mut config = Config::default();
+        config.state_sync = Some(
+            serde_json::from_value(serde_json::json!({
+                "sync": { "ExternalStorage": { "location": { "HTTP": {
+                    "mirrors": ["https://example.com/dumps", "ftp://example.com/dumps"],
+                } } } }
+            }))
+            .unwrap(),
+        );
+        validate_config(&config).unwrap();
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "\\nconfig.json semantic issue: 'config.state_sync.dump.zstd_compression_level' is only supported with 'config.state_sync.dump.parts_layout' set to 'ContentAddressed'."
+    )]
+    fn test_state_sync_dump_compression_without_content_addressed_layout() {
+        let mut config = Config::default();
+        config.state_sync = Some(
+            serde_json::from_value(serde_json::json!({
+                "dump": {
+                    "location": { "Filesystem": { "root_dir": "/tmp/state-parts" } },
+                    "zstd_compression_level": 3,
+                }
+            }))
+            .unwrap(),
+        );
+        validate_config(&config).unwrap();
+    }
+
     #[test]
     #[should_panic(
         expected = "\\nconfig.json semantic issue: 'config.tx_routing_height_horizon' needs to be at least 2, got 1."
@@ -252,4 +359,14 @@ mod tests {
         config.tx_routing_height_horizon = 1_000_000_000;
         validate_config(&config).unwrap();
     }
+
+    #[test]
+    #[should_panic(
+        expected = "\\nconfig.json semantic issue: 'config.transaction_pool_max_transactions_per_key' needs to be positive, set it to null to disable the limit."
+    )]
+    fn test_transaction_pool_max_transactions_per_key_zero() {
+        let mut config = Config::default();
+        config.transaction_pool_max_transactions_per_key = Some(0);
+        validate_config(&config).unwrap();
+    }
 }
@@ -1,6 +1,7 @@
 use crate::config::Config;
 use near_chain_configs::UpdateableClientConfig;
-use near_dyn_configs::{UpdateableConfigLoaderError, UpdateableConfigs};
+use near_crypto::{EncryptedKeyFile, KeyFile};
+use near_dyn_configs::{UnlockedValidatorKey, UpdateableConfigLoaderError, UpdateableConfigs};
 use near_o11y::log_config::LogConfig;
 use near_primitives::validator_signer::ValidatorSigner;
 use serde::Deserialize;
@@ -10,8 +11,10 @@ use std::sync::Arc;
 pub const LOG_CONFIG_FILENAME: &str = "log_config.json";
 
 /// This function gets called at the startup and each time a config needs to be reloaded.
+/// An encrypted validator key is used only if it has been unlocked.
 pub fn read_updateable_configs(
     home_dir: &Path,
+    unlocked_validator_key: &UnlockedValidatorKey,
 ) -> Result<UpdateableConfigs, UpdateableConfigLoaderError> {
     let mut errs = vec![];
     let log_config = match read_log_config(home_dir) {
@@ -34,7 +37,7 @@ pub fn read_updateable_configs(
     let updateable_client_config = config.as_ref().map(get_updateable_client_config);
 
     let validator_signer = if let Some(config) = config {
-        match read_validator_key(home_dir, &config) {
+        match read_validator_key(home_dir, &config, unlocked_validator_key) {
             Ok(validator_key) => Some(validator_key),
             Err(err) => {
                 errs.push(err);
@@ -108,8 +111,39 @@ where
 fn read_validator_key(
     home_dir: &Path,
     config: &Config,
+    unlocked_validator_key: &UnlockedValidatorKey,
 ) -> Result<Option<Arc<ValidatorSigner>>, UpdateableConfigLoaderError> {
+    if let Some(remote_signer) = &config.remote_signer {
+        let node_key_file = home_dir.join(&config.node_key_file);
+        return match crate::config::load_remote_validator_signer(remote_signer, &node_key_file) {
+            Ok(validator_signer) => {
+                tracing::info!(target: "neard", addr = %remote_signer.addr, "Hot loading remote validator signer.");
+                Ok(Some(validator_signer))
+            }
+            Err(err) => {
+                Err(UpdateableConfigLoaderError::ValidatorKeyFileError { file: node_key_file, err })
+            }
+        };
+    }
     let validator_file: PathBuf = home_dir.join(&config.validator_key_file);
+    if validator_file.exists() && KeyFile::is_encrypted(&validator_file).unwrap_or(false) {
+        return match EncryptedKeyFile::from_file(&validator_file) {
+            Ok(key_file) => match unlocked_validator_key.get(&key_file) {
+                Some(validator_signer) => {
+                    tracing::info!(target: "neard", "Hot loading unlocked validator key {}.", validator_file.display());
+                    Ok(Some(validator_signer))
+                }
+                None => {
+                    tracing::info!(target: "neard", "Validator key {} is encrypted and locked.", validator_file.display());
+                    Ok(None)
+                }
+            },
+            Err(err) => Err(UpdateableConfigLoaderError::ValidatorKeyFileError {
+                file: validator_file,
+                err: err.into(),
+            }),
+        };
+    }
     match crate::config::load_validator_key(&validator_file) {
         Ok(Some(validator_signer)) => {
             tracing::info!(target: "neard", "Hot loading validator key {}.", validator_file.display());
@@ -37,10 +37,11 @@ use near_epoch_manager::EpochManagerAdapter;
 use near_network::PeerManagerActor;
 use near_primitives::block::GenesisId;
 use near_primitives::types::EpochId;
+use near_store::backup_checkpoint::spawn_backup_checkpoint_loop;
 use near_store::genesis::initialize_sharded_genesis_state;
 use near_store::metadata::DbKind;
 use near_store::metrics::spawn_db_metrics_loop;
-use near_store::{NodeStorage, Store, StoreOpenerError};
+use near_store::{MigrationDryRun, NodeStorage, ShardTries, Store, StoreBackend, StoreOpenerError};
 use near_telemetry::TelemetryActor;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
@@ -177,12 +178,56 @@ pub fn open_storage(home_dir: &Path, near_config: &mut NearConfig) -> anyhow::Re
         Err(StoreOpenerError::CheckpointError(err)) => {
             Err(err)
         },
+        Err(err @ StoreOpenerError::ColdSegmentsNotConfigured { .. }) => {
+            Err(anyhow::anyhow!("{err}"))
+        },
     }.with_context(|| format!("unable to open database at {}", opener.path().display()))?;
 
     near_config.config.archive = storage.is_archive()?;
     Ok(storage)
 }
 
+/// Runs the database migrations on checkpoints of the databases in
+/// `checkpoint_dir` without modifying the databases, see
+/// [`near_store::StoreOpener::dry_run_migrations`].
+pub fn dry_run_storage_migrations(
+    home_dir: &Path,
+    near_config: &NearConfig,
+    checkpoint_dir: &Path,
+) -> anyhow::Result<Option<MigrationDryRun>> {
+    let migrator = migrations::Migrator::new(near_config);
+    let opener = NodeStorage::opener(
+        home_dir,
+        &near_config.config.store,
+        near_config.config.archival_config(),
+    )
+    .with_migrator(&migrator);
+    opener.dry_run_migrations(checkpoint_dir).with_context(|| {
+        format!("unable to dry run the migrations of the database at {}", opener.path().display())
+    })
+}
+
+/// Starts backfilling `DBCol::AccountsByPublicKey` in the background if
+/// `store.index_accounts_by_public_key` got enabled, or drops it if the option
+/// got disabled.
+fn sync_accounts_by_public_key_index(
+    config: &NearConfig,
+    store: &Store,
+) -> anyhow::Result<Option<ArbiterHandle>> {
+    use near_store::accounts_by_public_key;
+
+    let enabled = config.config.store.index_accounts_by_public_key;
+    let backfilled = accounts_by_public_key::is_backfilled(store)?;
+    if enabled && !backfilled {
+        return Ok(Some(accounts_by_public_key::spawn_backfill_loop(store.clone())));
+    }
+    if !enabled && (backfilled || accounts_by_public_key::is_backfill_in_progress(store)?) {
+        tracing::info!(target: "neard", "Dropping the index of accounts by public key");
+        accounts_by_public_key::drop_index(store)?;
+    }
+    Ok(None)
+}
+
 // Safely get the split store while checking that all conditions to use it are met.
 fn get_split_store(config: &NearConfig, storage: &NodeStorage) -> anyhow::Result<Option<Store>> {
     // SplitStore should only be used on archival nodes.
@@ -225,6 +270,8 @@ pub struct NearNode {
     pub resharding_handle: ReshardingHandle,
     // The threads that state sync runs in.
     pub state_sync_runtime: Arc<tokio::runtime::Runtime>,
+    /// Tries of the node, used to save the in-memory tries on shutdown.
+    pub shard_tries: ShardTries,
 }
 
 pub fn start_with_config(home_dir: &Path, config: NearConfig) -> anyhow::Result<NearNode> {
@@ -247,6 +294,11 @@ pub fn start_with_config_and_synchronization(
     } else {
         None
     };
+    let backup_checkpoint_arbiter =
+        (config.config.store.backend == StoreBackend::RocksDB).then(|| {
+            let opener = NodeStorage::opener(home_dir, &config.config.store, None);
+            spawn_backup_checkpoint_loop(&storage, opener.path().to_path_buf())
+        });
 
     let trie_metrics_arbiter = spawn_trie_metrics_loop(
         config.clone(),
@@ -335,12 +387,29 @@ pub fn start_with_config_and_synchronization(
         config.validator_signer.clone(),
         chain_genesis.clone(),
         view_epoch_manager.clone(),
-        view_shard_tracker,
+        view_shard_tracker.clone(),
         view_runtime.clone(),
         network_adapter.as_multi_sender(),
         config.client_config.clone(),
         adv.clone(),
     );
+    // Simulations of transactions are expensive, they get a separate small
+    // pool of view client actors so that they can't stall the other requests.
+    #[cfg(feature = "json_rpc")]
+    let simulation_addr = ViewClientActorInner::spawn_actix_actor(
+        Clock::real(),
+        config.validator_signer.clone(),
+        chain_genesis.clone(),
+        view_epoch_manager.clone(),
+        view_shard_tracker.clone(),
+        view_runtime.clone(),
+        network_adapter.as_multi_sender(),
+        near_chain_configs::ClientConfig {
+            view_client_threads: config.client_config.simulation_threads,
+            ..config.client_config.clone()
+        },
+        adv.clone(),
+    );
 
     let state_snapshot_sender = LateBoundSender::new();
     let state_snapshot_actor = StateSnapshotActor::new(
@@ -384,10 +453,16 @@ pub fn start_with_config_and_synchronization(
     let (resharding_sender_addr, _) =
         spawn_actix_actor(ReshardingActor::new(runtime.store().clone(), chain_genesis.height));
     let resharding_sender = resharding_sender_addr.with_auto_span_context();
+    let shard_tries = runtime.get_tries();
     let state_sync_runtime =
         Arc::new(tokio::runtime::Builder::new_multi_thread().enable_all().build().unwrap());
 
-    let StartClientResult { client_actor, client_arbiter_handle, resharding_handle } = start_client(
+    let StartClientResult {
+        client_actor,
+        client_arbiter_handle,
+        resharding_handle,
+        chain_head: _chain_head,
+    } = start_client(
         Clock::real(),
         config.client_config.clone(),
         chain_genesis.clone(),
@@ -409,6 +484,11 @@ pub fn start_with_config_and_synchronization(
         None,
         resharding_sender.into_multi_sender(),
     );
+    // Flat storage of the genesis is created when the client starts, so the
+    // index can only be backfilled now. The blocks processed concurrently are
+    // indexed by the client itself.
+    let accounts_by_public_key_arbiter =
+        sync_accounts_by_public_key_index(&config, &storage.get_hot_store())?;
     client_adapter_for_shards_manager.bind(client_actor.clone().with_auto_span_context());
     client_adapter_for_partial_witness_actor.bind(client_actor.clone().with_auto_span_context());
     let (shards_manager_actor, shards_manager_arbiter_handle) = start_shards_manager(
@@ -462,10 +542,13 @@ pub fn start_with_config_and_synchronization(
         };
         rpc_servers.extend(near_jsonrpc::start_http(
             rpc_config,
+            &config.client_config.version.version,
             config.genesis.config.clone(),
             client_actor.clone().with_auto_span_context().into_multi_sender(),
             view_client_addr.clone().with_auto_span_context().into_multi_sender(),
+            simulation_addr.with_auto_span_context().into_multi_sender(),
             network_actor.into_multi_sender(),
+            _chain_head,
             #[cfg(feature = "test_features")]
             _gc_actor.with_auto_span_context().into_multi_sender(),
             Arc::new(entity_debug_handler),
@@ -501,6 +584,12 @@ pub fn start_with_config_and_synchronization(
     if let Some(db_metrics_arbiter) = db_metrics_arbiter {
         arbiters.push(db_metrics_arbiter);
     }
+    if let Some(backup_checkpoint_arbiter) = backup_checkpoint_arbiter {
+        arbiters.push(backup_checkpoint_arbiter);
+    }
+    if let Some(accounts_by_public_key_arbiter) = accounts_by_public_key_arbiter {
+        arbiters.push(accounts_by_public_key_arbiter);
+    }
 
     Ok(NearNode {
         client: client_actor,
@@ -511,5 +600,6 @@ pub fn start_with_config_and_synchronization(
         state_sync_dumper,
         resharding_handle,
         state_sync_runtime,
+        shard_tries,
     })
 }
@@ -69,6 +69,17 @@ pub(crate) static STATE_SYNC_DUMP_NUM_PARTS_DUMPED: LazyLock<IntGaugeVec> = Lazy
     .unwrap()
 });
 
+pub(crate) static STATE_SYNC_DUMP_DEDUPLICATED_PARTS: LazyLock<IntCounterVec> = LazyLock::new(
+    || {
+        try_create_int_counter_vec(
+            "near_state_sync_dump_deduplicated_parts",
+            "Number of content-addressed parts not uploaded because a part with the same content is already stored",
+            &["shard_id"],
+        )
+        .unwrap()
+    },
+);
+
 pub(crate) static STATE_SYNC_DUMP_SIZE_TOTAL: LazyLock<IntCounterVec> = LazyLock::new(|| {
     try_create_int_counter_vec(
         "near_state_sync_dump_size_total",
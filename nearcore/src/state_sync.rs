@@ -9,7 +9,9 @@ use itertools::Itertools;
 use near_async::time::{Clock, Duration, Instant};
 use near_chain::types::RuntimeAdapter;
 use near_chain::{Chain, ChainGenesis, DoomslugThresholdMode, Error};
-use near_chain_configs::{ClientConfig, ExternalStorageLocation, MutableValidatorSigner};
+use near_chain_configs::{
+    ClientConfig, ExternalStorageLocation, MutableValidatorSigner, StatePartsLayout,
+};
 use near_client::sync::external::{
     create_bucket_readwrite, external_storage_location, StateFileType,
 };
@@ -17,6 +19,10 @@ use near_client::sync::external::{
     external_storage_location_directory, get_part_id_from_filename, is_part_filename,
     ExternalConnection,
 };
+use near_client::sync::external_manifest::{
+    encode_state_part, state_part_content_location, ManifestPart, StatePartCompression,
+    StatePartsManifest,
+};
 use near_epoch_manager::shard_tracker::ShardTracker;
 use near_epoch_manager::EpochManagerAdapter;
 use near_primitives::hash::CryptoHash;
@@ -113,6 +119,9 @@ impl StateSyncDumper {
                     bucket,
                 }
             },
+            ExternalStorageLocation::HTTP { .. } => {
+                anyhow::bail!("State can't be dumped to HTTP mirrors");
+            }
         };
 
         // Determine how many threads to start.
@@ -151,6 +160,8 @@ impl StateSyncDumper {
                         dump_config.iteration_delay.unwrap_or(Duration::seconds(10)),
                         self.validator.clone(),
                         keep_running.clone(),
+                        dump_config.parts_layout,
+                        dump_config.zstd_compression_level,
                     )
                     .boxed(),
                 )
@@ -222,15 +233,11 @@ async fn get_missing_part_ids_for_epoch(
     epoch_id: &EpochId,
     epoch_height: u64,
     total_parts: u64,
+    file_type: &StateFileType,
     external: &ExternalConnection,
 ) -> Result<Vec<u64>, anyhow::Error> {
-    let directory_path = external_storage_location_directory(
-        chain_id,
-        epoch_id,
-        epoch_height,
-        shard_id,
-        &StateFileType::StatePart { part_id: 0, num_parts: 0 },
-    );
+    let directory_path =
+        external_storage_location_directory(chain_id, epoch_id, epoch_height, shard_id, file_type);
     let file_names = external.list_objects(shard_id, &directory_path).await?;
     if !file_names.is_empty() {
         let existing_nums: HashSet<_> = file_names
@@ -344,6 +351,126 @@ async fn upload_state_header(
     }
 }
 
+/// Dumps the parts of a shard with the content-addressed layout. All state is
+/// kept in the external storage, so that several dumpers can share the work
+/// and a restarted dumper continues where it stopped: the entry of every
+/// dumped part is stored next to the manifest, and the manifest is assembled
+/// from the entries once all parts are dumped.
+struct ContentAddressedDump {
+    zstd_compression_level: Option<i32>,
+}
+
+impl ContentAddressedDump {
+    fn new(zstd_compression_level: Option<i32>) -> Self {
+        Self { zstd_compression_level }
+    }
+
+    fn compression(&self) -> StatePartCompression {
+        match self.zstd_compression_level {
+            Some(_) => StatePartCompression::Zstd,
+            None => StatePartCompression::None,
+        }
+    }
+
+    /// Returns the parts which still need to be dumped. Uploads the manifest
+    /// if all parts are dumped, but the manifest isn't stored yet.
+    async fn get_missing_part_ids(
+        &self,
+        shard_id: ShardId,
+        chain_id: &String,
+        epoch_id: &EpochId,
+        epoch_height: u64,
+        num_parts: u64,
+        external: &ExternalConnection,
+    ) -> anyhow::Result<Vec<u64>> {
+        if external
+            .is_state_parts_manifest_stored_for_epoch(shard_id, chain_id, epoch_id, epoch_height)
+            .await?
+        {
+            return Ok(vec![]);
+        }
+        let missing_parts = get_missing_part_ids_for_epoch(
+            shard_id,
+            chain_id,
+            epoch_id,
+            epoch_height,
+            num_parts,
+            &StateFileType::StatePartsManifestEntry { part_id: 0, num_parts: 0 },
+            external,
+        )
+        .await?;
+        if missing_parts.is_empty() {
+            self.put_manifest(shard_id, chain_id, epoch_id, epoch_height, num_parts, external)
+                .await?;
+        }
+        Ok(missing_parts)
+    }
+
+    /// Uploads the part under the hash of its content, unless a part with
+    /// the same content is already stored, and then its manifest entry.
+    async fn put_part(
+        &self,
+        shard_id: ShardId,
+        chain_id: &String,
+        epoch_id: &EpochId,
+        epoch_height: u64,
+        part_id: u64,
+        num_parts: u64,
+        state_part: &[u8],
+        external: &ExternalConnection,
+    ) -> anyhow::Result<()> {
+        let (entry, stored) = encode_state_part(state_part, self.zstd_compression_level)?;
+        let location = state_part_content_location(chain_id, &entry.hash, self.compression());
+        // A part of a different size under the same hash is an incomplete
+        // upload, which is replaced.
+        if external.stored_file_size(shard_id, &location).await? == Some(entry.stored_size) {
+            tracing::debug!(target: "state_sync_dump", ?shard_id, part_id, hash = ?entry.hash, "Part with the same content is already stored.");
+            metrics::STATE_SYNC_DUMP_DEDUPLICATED_PARTS
+                .with_label_values(&[&shard_id.to_string()])
+                .inc();
+        } else {
+            let file_type = StateFileType::StatePart { part_id, num_parts };
+            external.put_file(file_type, &stored, shard_id, &location).await?;
+        }
+        // The entry is stored after the content, so that a listed entry always
+        // refers to a stored part.
+        let file_type = StateFileType::StatePartsManifestEntry { part_id, num_parts };
+        let location =
+            external_storage_location(chain_id, epoch_id, epoch_height, shard_id, &file_type);
+        external.put_file(file_type, &serde_json::to_vec(&entry)?, shard_id, &location).await
+    }
+
+    /// Assembles the manifest from the stored entries of all parts of the
+    /// epoch, and uploads it.
+    async fn put_manifest(
+        &self,
+        shard_id: ShardId,
+        chain_id: &String,
+        epoch_id: &EpochId,
+        epoch_height: u64,
+        num_parts: u64,
+        external: &ExternalConnection,
+    ) -> anyhow::Result<()> {
+        let mut parts = Vec::with_capacity(num_parts as usize);
+        for part_id in 0..num_parts {
+            let file_type = StateFileType::StatePartsManifestEntry { part_id, num_parts };
+            let location =
+                external_storage_location(chain_id, epoch_id, epoch_height, shard_id, &file_type);
+            let entry = external.get_file(shard_id, &location, &file_type).await?;
+            let entry: ManifestPart = serde_json::from_slice(&entry)
+                .with_context(|| format!("Failed to parse the entry of part {part_id}"))?;
+            parts.push(entry);
+        }
+        let manifest = StatePartsManifest { compression: self.compression(), parts };
+        let file_type = StateFileType::StatePartsManifest;
+        let location =
+            external_storage_location(chain_id, epoch_id, epoch_height, shard_id, &file_type);
+        external.put_file(file_type, &manifest.to_bytes(), shard_id, &location).await?;
+        tracing::debug!(target: "state_sync_dump", ?shard_id, epoch_height, "Manifest saved to external storage.");
+        Ok(())
+    }
+}
+
 const FAILURES_ALLOWED_PER_ITERATION: u32 = 10;
 
 async fn state_sync_dump(
@@ -359,8 +486,16 @@ async fn state_sync_dump(
     iteration_delay: Duration,
     validator: MutableValidatorSigner,
     keep_running: Arc<AtomicBool>,
+    parts_layout: StatePartsLayout,
+    zstd_compression_level: Option<i32>,
 ) {
-    tracing::info!(target: "state_sync_dump", ?shard_id, "Running StateSyncDump loop");
+    tracing::info!(target: "state_sync_dump", ?shard_id, ?parts_layout, "Running StateSyncDump loop");
+    let content_addressed = match parts_layout {
+        StatePartsLayout::Raw => None,
+        StatePartsLayout::ContentAddressed => {
+            Some(ContentAddressedDump::new(zstd_compression_level))
+        }
+    };
 
     if restart_dump_for_shards.contains(&shard_id) {
         tracing::debug!(target: "state_sync_dump", ?shard_id, "Dropped existing progress");
@@ -434,16 +569,32 @@ async fn state_sync_dump(
                         };
 
                         // Upload parts
-                        let parts_upload_status = match get_missing_part_ids_for_epoch(
-                            shard_id,
-                            &chain_id,
-                            &epoch_id,
-                            epoch_height,
-                            num_parts,
-                            &external,
-                        )
-                        .await
-                        {
+                        let missing_parts = match &content_addressed {
+                            Some(dump) => {
+                                dump.get_missing_part_ids(
+                                    shard_id,
+                                    &chain_id,
+                                    &epoch_id,
+                                    epoch_height,
+                                    num_parts,
+                                    &external,
+                                )
+                                .await
+                            }
+                            None => {
+                                get_missing_part_ids_for_epoch(
+                                    shard_id,
+                                    &chain_id,
+                                    &epoch_id,
+                                    epoch_height,
+                                    num_parts,
+                                    &StateFileType::StatePart { part_id: 0, num_parts: 0 },
+                                    &external,
+                                )
+                                .await
+                            }
+                        };
+                        let parts_upload_status = match missing_parts {
                             Err(err) => {
                                 tracing::error!(target: "state_sync_dump", ?err, ?shard_id, "Failed to determine missing parts");
                                 None
@@ -493,18 +644,41 @@ async fn state_sync_dump(
                                         }
                                     };
 
-                                    let file_type = StateFileType::StatePart { part_id, num_parts };
-                                    let location = external_storage_location(
-                                        &chain_id,
-                                        &epoch_id,
-                                        epoch_height,
-                                        shard_id,
-                                        &file_type,
-                                    );
-                                    if let Err(err) = external
-                                        .put_file(file_type, &state_part, shard_id, &location)
-                                        .await
-                                    {
+                                    let put_result = match &content_addressed {
+                                        Some(dump) => {
+                                            dump.put_part(
+                                                shard_id,
+                                                &chain_id,
+                                                &epoch_id,
+                                                epoch_height,
+                                                part_id,
+                                                num_parts,
+                                                &state_part,
+                                                &external,
+                                            )
+                                            .await
+                                        }
+                                        None => {
+                                            let file_type =
+                                                StateFileType::StatePart { part_id, num_parts };
+                                            let location = external_storage_location(
+                                                &chain_id,
+                                                &epoch_id,
+                                                epoch_height,
+                                                shard_id,
+                                                &file_type,
+                                            );
+                                            external
+                                                .put_file(
+                                                    file_type,
+                                                    &state_part,
+                                                    shard_id,
+                                                    &location,
+                                                )
+                                                .await
+                                        }
+                                    };
+                                    if let Err(err) = put_result {
                                         // no need to break if there's an error, we should keep dumping other parts.
                                         // reason is we are dumping random selected parts, so it's fine if we are not able to finish all of them
                                         tracing::warn!(target: "state_sync_dump", ?shard_id, epoch_height, part_id, ?err, "Failed to put a store part into external storage. Will skip this part.");
@@ -523,7 +697,30 @@ async fn state_sync_dump(
                                     );
                                     dumped_any_state_part = true;
                                 }
-                                if parts_to_dump.is_empty() {
+                                // With the content-addressed layout, the epoch is dumped only
+                                // once the manifest is stored.
+                                let all_dumped = parts_to_dump.is_empty()
+                                    && match &content_addressed {
+                                        Some(dump) => match dump
+                                            .put_manifest(
+                                                shard_id,
+                                                &chain_id,
+                                                &epoch_id,
+                                                epoch_height,
+                                                num_parts,
+                                                &external,
+                                            )
+                                            .await
+                                        {
+                                            Ok(()) => true,
+                                            Err(err) => {
+                                                tracing::warn!(target: "state_sync_dump", ?shard_id, epoch_height, ?err, "Failed to put the manifest into external storage. Will retry next iteration.");
+                                                false
+                                            }
+                                        },
+                                        None => true,
+                                    };
+                                if all_dumped {
                                     Some(StateSyncDumpProgress::AllDumped {
                                         epoch_id,
                                         epoch_height,
@@ -5,8 +5,14 @@ use near_chain_configs::GenesisValidationMode;
 use near_client::ConfigUpdater;
 use near_cold_store_tool::ColdStoreCommand;
 use near_config_utils::DownloadConfigType;
+#[cfg(unix)]
+use near_crypto::KeyFile;
 use near_database_tool::commands::DatabaseCommand;
-use near_dyn_configs::{UpdateableConfigLoader, UpdateableConfigLoaderError, UpdateableConfigs};
+#[cfg(unix)]
+use near_dyn_configs::VALIDATOR_KEY_UNLOCK_SOCKET;
+use near_dyn_configs::{
+    UnlockedValidatorKey, UpdateableConfigLoader, UpdateableConfigLoaderError, UpdateableConfigs,
+};
 use near_flat_storage::commands::FlatStorageCommand;
 use near_fork_network::cli::ForkNetworkCommand;
 use near_jsonrpc_primitives::types::light_client::RpcLightClientExecutionProofResponse;
@@ -133,6 +139,9 @@ impl NeardCmd {
             NeardSubCommand::Database(cmd) => {
                 cmd.run(&home_dir, genesis_validation)?;
             }
+            NeardSubCommand::KeyFile(cmd) => {
+                cmd.run(&home_dir)?;
+            }
             NeardSubCommand::ForkNetwork(cmd) => {
                 cmd.run(
                     &home_dir,
@@ -147,6 +156,10 @@ impl NeardCmd {
             NeardSubCommand::ReplayArchive(cmd) => {
                 cmd.run(&home_dir, genesis_validation)?;
             }
+            #[cfg(feature = "json_rpc")]
+            NeardSubCommand::RpcSchema(cmd) => {
+                cmd.run()?;
+            }
         };
         Ok(())
     }
@@ -245,6 +258,14 @@ pub(super) enum NeardSubCommand {
     /// Set of commands to run on database
     Database(DatabaseCommand),
 
+    /// Encrypt, decrypt and re-encrypt key files, and unlock the encrypted
+    /// validator key of a running node.
+    ///
+    /// Only the key files are encrypted. The database is stored in plaintext,
+    /// so it has to be kept on an encrypted file system if it needs to be
+    /// encrypted at rest.
+    KeyFile(KeyFileCommand),
+
     /// Resets the network into a forked network at the given block height and state.
     ForkNetwork(ForkNetworkCommand),
 
@@ -253,6 +274,11 @@ pub(super) enum NeardSubCommand {
 
     /// Replays the blocks in the chain from an archival node.
     ReplayArchive(ReplayArchiveCommand),
+
+    /// Prints the OpenRPC document of the JSON RPC API, the same one as
+    /// returned by the `rpc.discover` method.
+    #[cfg(feature = "json_rpc")]
+    RpcSchema(RpcSchemaCommand),
 }
 
 #[derive(Debug, Clone)]
@@ -536,9 +562,12 @@ impl RunCmd {
         let (tx_crash, mut rx_crash) = broadcast::channel::<()>(16);
         let (tx_config_update, rx_config_update) =
             broadcast::channel::<Result<UpdateableConfigs, Arc<UpdateableConfigLoaderError>>>(16);
+        let (tx_unlocked, mut rx_unlocked) = tokio::sync::mpsc::channel::<()>(1);
+        let unlocked_validator_key = UnlockedValidatorKey::default();
+        let validator_file = home_dir.join(&near_config.config.validator_key_file);
         let sys = actix::System::new();
 
-        sys.block_on(async move {
+        let shard_tries = sys.block_on(async move {
             // Initialize the subscriber that takes care of both logging and tracing.
             let _subscriber_guard = default_subscriber_with_opentelemetry(
                 make_env_filter(verbose_target).unwrap(),
@@ -550,8 +579,9 @@ impl RunCmd {
             .await
             .global();
 
-            let updateable_configs = nearcore::dyn_config::read_updateable_configs(home_dir)
-                .unwrap_or_else(|e| panic!("Error reading dynamic configs: {:#}", e));
+            let updateable_configs =
+                nearcore::dyn_config::read_updateable_configs(home_dir, &unlocked_validator_key)
+                    .unwrap_or_else(|e| panic!("Error reading dynamic configs: {:#}", e));
             let mut updateable_config_loader =
                 UpdateableConfigLoader::new(updateable_configs.clone(), tx_config_update);
             let config_updater = ConfigUpdater::new(rx_config_update);
@@ -561,6 +591,7 @@ impl RunCmd {
                 cold_store_loop_handle,
                 mut state_sync_dumper,
                 resharding_handle,
+                shard_tries,
                 ..
             } = nearcore::start_with_config_and_synchronization(
                 home_dir,
@@ -570,17 +601,40 @@ impl RunCmd {
             )
             .expect("start_with_config");
 
+            #[cfg(unix)]
+            let unlock_socket =
+                KeyFile::is_encrypted(&validator_file).unwrap_or(false).then(|| {
+                    let socket_path = home_dir.join(VALIDATOR_KEY_UNLOCK_SOCKET);
+                    let task = tokio::spawn(near_dyn_configs::serve_validator_key_unlock_socket(
+                        socket_path.clone(),
+                        validator_file,
+                        unlocked_validator_key.clone(),
+                        tx_unlocked,
+                    ));
+                    (socket_path, task)
+                });
+            #[cfg(not(unix))]
+            drop((validator_file, tx_unlocked));
+
             let sig = loop {
-                let sig = wait_for_interrupt_signal(home_dir, &mut rx_crash).await;
-                if sig == "SIGHUP" {
-                    let maybe_updateable_configs =
-                        nearcore::dyn_config::read_updateable_configs(home_dir);
+                let sig =
+                    wait_for_interrupt_signal(home_dir, &mut rx_crash, &mut rx_unlocked).await;
+                if sig == "SIGHUP" || sig == VALIDATOR_KEY_UNLOCKED {
+                    let maybe_updateable_configs = nearcore::dyn_config::read_updateable_configs(
+                        home_dir,
+                        &unlocked_validator_key,
+                    );
                     updateable_config_loader.reload(maybe_updateable_configs);
                 } else {
                     break sig;
                 }
             };
             warn!(target: "neard", "{}, stopping... this may take a few minutes.", sig);
+            #[cfg(unix)]
+            if let Some((socket_path, task)) = unlock_socket {
+                task.abort();
+                let _ = std::fs::remove_file(socket_path);
+            }
             if let Some(handle) = cold_store_loop_handle {
                 handle.stop()
             }
@@ -594,22 +648,39 @@ impl RunCmd {
             actix::System::current().stop();
             // Disable the subscriber to properly shutdown the tracer.
             near_o11y::reload(Some("error"), None, Some("off")).unwrap();
+            shard_tries
         });
         sys.run().unwrap();
+        // Blocks are no longer applied once the actors are stopped, so the
+        // in-memory tries correspond to the flat storage now.
+        shard_tries.save_mem_tries_snapshots();
+        drop(shard_tries);
         info!(target: "neard", "Waiting for RocksDB to gracefully shutdown");
         RocksDB::block_until_all_instances_are_dropped();
     }
 }
 
+/// Returned by `wait_for_interrupt_signal` once the encrypted validator key
+/// has been unlocked and the configs need to be reloaded.
+const VALIDATOR_KEY_UNLOCKED: &str = "Validator key unlocked";
+
 #[cfg(not(unix))]
-async fn wait_for_interrupt_signal(_home_dir: &Path, mut _rx_crash: &Receiver<()>) -> &str {
+async fn wait_for_interrupt_signal(
+    _home_dir: &Path,
+    mut _rx_crash: &Receiver<()>,
+    _rx_unlocked: &mut tokio::sync::mpsc::Receiver<()>,
+) -> &str {
     // TODO(#6372): Support graceful shutdown on windows.
     tokio::signal::ctrl_c().await.unwrap();
     "Ctrl+C"
 }
 
 #[cfg(unix)]
-async fn wait_for_interrupt_signal(_home_dir: &Path, rx_crash: &mut Receiver<()>) -> &'static str {
+async fn wait_for_interrupt_signal(
+    _home_dir: &Path,
+    rx_crash: &mut Receiver<()>,
+    rx_unlocked: &mut tokio::sync::mpsc::Receiver<()>,
+) -> &'static str {
     use tokio::signal::unix::{signal, SignalKind};
     let mut sigint = signal(SignalKind::interrupt()).unwrap();
     let mut sigterm = signal(SignalKind::terminate()).unwrap();
@@ -620,6 +691,7 @@ async fn wait_for_interrupt_signal(_home_dir: &Path, rx_crash: &mut Receiver<()>
          _ = sigterm.recv() => "SIGTERM",
          _ = sighup.recv() => "SIGHUP",
          _ = rx_crash.recv() => "ClientActor died",
+         Some(()) = rx_unlocked.recv() => VALIDATOR_KEY_UNLOCKED,
     }
 }
 
@@ -693,6 +765,28 @@ pub enum VerifyProofError {
     InvalidBlockHashProof,
 }
 
+#[cfg(feature = "json_rpc")]
+#[derive(clap::Parser)]
+pub(super) struct RpcSchemaCommand {
+    /// File to write the document to, printed to stdout by default.
+    #[clap(long)]
+    output: Option<PathBuf>,
+}
+
+#[cfg(feature = "json_rpc")]
+impl RpcSchemaCommand {
+    pub(super) fn run(self) -> anyhow::Result<()> {
+        let document = near_jsonrpc::openrpc_document(crate::NEARD_VERSION);
+        let document = serde_json::to_string_pretty(&document)?;
+        match self.output {
+            Some(path) => std::fs::write(&path, document + "\n")
+                .with_context(|| format!("failed to write {}", path.display()))?,
+            None => println!("{document}"),
+        }
+        Ok(())
+    }
+}
+
 #[derive(clap::Parser)]
 pub struct VerifyProofSubCommand {
     #[clap(long)]
@@ -817,6 +911,144 @@ impl ValidateConfigCommand {
     }
 }
 
+#[derive(clap::Parser)]
+pub(super) struct KeyFileCommand {
+    #[clap(subcommand)]
+    subcmd: KeyFileSubCommand,
+}
+
+#[derive(clap::Subcommand)]
+enum KeyFileSubCommand {
+    /// Encrypt a plaintext key file in place.
+    Encrypt {
+        #[clap(flatten)]
+        key_file: KeyFileArgs,
+    },
+    /// Decrypt an encrypted key file in place.
+    Decrypt {
+        #[clap(flatten)]
+        key_file: KeyFileArgs,
+    },
+    /// Re-encrypt an encrypted key file with a new password.
+    Rotate {
+        #[clap(flatten)]
+        key_file: KeyFileArgs,
+        /// File whose whole content is used as the new password. The password
+        /// is prompted for if not set.
+        #[clap(long)]
+        new_password_file: Option<PathBuf>,
+    },
+    /// Unlock the encrypted validator key of the node running in the home
+    /// dir, without restarting it.
+    #[cfg(unix)]
+    Unlock {
+        /// File whose whole content is used as the password. The password is
+        /// prompted for if not set.
+        #[clap(long)]
+        password_file: Option<PathBuf>,
+    },
+}
+
+#[derive(clap::Args)]
+struct KeyFileArgs {
+    /// Key file, relative to the home dir.
+    #[clap(long, default_value = "validator_key.json")]
+    file: PathBuf,
+    /// File whose whole content is used as the password. The password is
+    /// prompted for if not set.
+    #[clap(long)]
+    password_file: Option<PathBuf>,
+}
+
+impl KeyFileCommand {
+    pub(super) fn run(self, home_dir: &Path) -> anyhow::Result<()> {
+        match self.subcmd {
+            KeyFileSubCommand::Encrypt { key_file } => {
+                let path = home_dir.join(&key_file.file);
+                anyhow::ensure!(
+                    !near_crypto::KeyFile::is_encrypted(&path)?,
+                    "{} is already encrypted",
+                    path.display()
+                );
+                let plaintext = near_crypto::KeyFile::from_file(&path)?;
+                let password = read_password(key_file.password_file.as_deref(), true)?;
+                replace_key_file(&path, |tmp_path| {
+                    plaintext.encrypt(&password)?.write_to_file(tmp_path)
+                })?;
+                println!("Encrypted {}", path.display());
+            }
+            KeyFileSubCommand::Decrypt { key_file } => {
+                let path = home_dir.join(&key_file.file);
+                let encrypted = read_encrypted_key_file(&path)?;
+                let password = read_password(key_file.password_file.as_deref(), false)?;
+                let plaintext = encrypted.decrypt(&password)?;
+                replace_key_file(&path, |tmp_path| plaintext.write_to_file(tmp_path))?;
+                println!("Decrypted {}", path.display());
+            }
+            KeyFileSubCommand::Rotate { key_file, new_password_file } => {
+                let path = home_dir.join(&key_file.file);
+                let encrypted = read_encrypted_key_file(&path)?;
+                let password = read_password(key_file.password_file.as_deref(), false)?;
+                let plaintext = encrypted.decrypt(&password)?;
+                println!("Enter the new password.");
+                let new_password = read_password(new_password_file.as_deref(), true)?;
+                replace_key_file(&path, |tmp_path| {
+                    plaintext.encrypt(&new_password)?.write_to_file(tmp_path)
+                })?;
+                println!("Re-encrypted {}", path.display());
+            }
+            #[cfg(unix)]
+            KeyFileSubCommand::Unlock { password_file } => {
+                let password = read_password(password_file.as_deref(), false)?;
+                near_dyn_configs::unlock_validator_key(
+                    &home_dir.join(VALIDATOR_KEY_UNLOCK_SOCKET),
+                    &password,
+                )?;
+                println!("Unlocked the validator key");
+            }
+        }
+        Ok(())
+    }
+}
+
+fn read_encrypted_key_file(path: &Path) -> anyhow::Result<near_crypto::EncryptedKeyFile> {
+    anyhow::ensure!(
+        near_crypto::KeyFile::is_encrypted(path)?,
+        "{} is not encrypted",
+        path.display()
+    );
+    Ok(near_crypto::EncryptedKeyFile::from_file(path)?)
+}
+
+/// Reads the password from `password_file` or prompts for it on the terminal,
+/// twice if `confirm` is set.
+fn read_password(password_file: Option<&Path>, confirm: bool) -> anyhow::Result<Vec<u8>> {
+    if let Some(password_file) = password_file {
+        return std::fs::read(password_file)
+            .with_context(|| format!("failed to read {}", password_file.display()));
+    }
+    let password = rpassword::prompt_password("Password: ")?;
+    anyhow::ensure!(!password.is_empty(), "the password must not be empty");
+    if confirm {
+        let repeated = rpassword::prompt_password("Repeat the password: ")?;
+        anyhow::ensure!(password == repeated, "the passwords don't match");
+    }
+    Ok(password.into_bytes())
+}
+
+/// Writes the new contents of the key file with `write` to a temporary file
+/// which then replaces the key file, so that the key is never lost halfway.
+fn replace_key_file(
+    path: &Path,
+    write: impl FnOnce(&Path) -> std::io::Result<()>,
+) -> anyhow::Result<()> {
+    let tmp_path = path.with_extension("tmp");
+    write(&tmp_path).with_context(|| format!("failed to write {}", tmp_path.display()))?;
+    std::fs::rename(&tmp_path, path)
+        .with_context(|| format!("failed to replace {}", path.display()))?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::{CryptoHash, NeardCmd, NeardSubCommand, VerifyProofError, VerifyProofSubCommand};
@@ -87,6 +87,7 @@ mod metrics;
 mod pipelining;
 mod prefetch;
 pub mod receipt_manager;
+pub mod simulation;
 pub mod state_viewer;
 #[cfg(test)]
 mod tests;
@@ -311,6 +312,7 @@ impl Runtime {
         state_update: &mut TrieUpdate,
         apply_state: &ApplyState,
         signed_transaction: &SignedTransaction,
+        verify_signature: bool,
         stats: &mut ApplyStats,
     ) -> Result<(Receipt, ExecutionOutcomeWithId), InvalidTxError> {
         let span = tracing::Span::current();
@@ -321,7 +323,7 @@ impl Runtime {
             state_update,
             apply_state.gas_price,
             signed_transaction,
-            true,
+            verify_signature,
             Some(apply_state.block_height),
             apply_state.current_protocol_version,
         ) {
@@ -999,19 +1001,15 @@ impl Runtime {
 
     fn process_receipt(
         &self,
-        processing_state: &mut ApplyProcessingReceiptState,
+        state_update: &mut TrieUpdate,
+        apply_state: &ApplyState,
+        pipeline_manager: &ReceiptPreparationPipeline,
+        stats: &mut ApplyStats,
+        epoch_info_provider: &dyn EpochInfoProvider,
         receipt: &Receipt,
         receipt_sink: &mut ReceiptSink,
         validator_proposals: &mut Vec<ValidatorStake>,
     ) -> Result<Option<ExecutionOutcomeWithId>, RuntimeError> {
-        let ApplyProcessingReceiptState {
-            ref mut state_update,
-            apply_state,
-            epoch_info_provider,
-            ref pipeline_manager,
-            ref mut stats,
-            ..
-        } = *processing_state;
         let account_id = receipt.receiver_id();
         match receipt.receipt() {
             ReceiptEnum::Data(ref data_receipt) => {
@@ -1584,6 +1582,7 @@ impl Runtime {
                 state_update,
                 apply_state,
                 signed_transaction,
+                true,
                 &mut processing_state.stats,
             );
             let (receipt, outcome_with_id) = match tx_result {
@@ -1653,7 +1652,11 @@ impl Runtime {
         let storage_proof_size_upper_bound_before =
             state_update.trie().recorded_storage_size_upper_bound();
         let result = self.process_receipt(
-            processing_state,
+            &mut processing_state.state_update,
+            processing_state.apply_state,
+            &processing_state.pipeline_manager,
+            &mut processing_state.stats,
+            processing_state.epoch_info_provider,
             receipt,
             &mut receipt_sink,
             &mut validator_proposals,
@@ -0,0 +1,243 @@
+//! Dry runs of transactions.
+//!
+//! A simulation processes a transaction and all the receipts it produces with
+//! the same logic as [`Runtime::apply`], but against `TrieUpdate`s of the
+//! involved shards which are never finalized, so nothing gets persisted.
+//! Every receipt is executed right after the one which produced it, which
+//! means that the simulation doesn't account for delays which happen on the
+//! real chain due to congestion or the gas limit of chunks.
+//!
+//! A simulation is bounded by the number of receipts it processes, the total
+//! gas burnt by the transaction and its receipts and the wall-clock time it
+//! takes, so that a single request can't keep the node busy indefinitely.
+
+use crate::congestion_control::{ReceiptSink, ReceiptSinkV1};
+use crate::pipelining::ReceiptPreparationPipeline;
+use crate::{ApplyState, ApplyStats, Runtime};
+use near_primitives::errors::{InvalidTxError, RuntimeError, StorageError};
+use near_primitives::receipt::Receipt;
+use near_primitives::transaction::{ExecutionOutcomeWithId, SignedTransaction};
+use near_primitives::types::{
+    AccountId, EpochId, EpochInfoProvider, Gas, RawStateChangesWithTrieKey, ShardId,
+    StateChangeCause,
+};
+use near_store::{Trie, TrieUpdate};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Maximum number of receipts processed by a single simulation. Protects the
+/// node from transactions which keep producing new receipts.
+pub const MAX_SIMULATED_RECEIPTS: usize = 1000;
+
+#[derive(Debug)]
+pub enum SimulationError {
+    /// The transaction would be rejected by the chain.
+    InvalidTransaction(InvalidTxError),
+    /// The state of a shard which the transaction touches is not available.
+    UnavailableShard(ShardId),
+    /// The transaction produced more than [`MAX_SIMULATED_RECEIPTS`] receipts.
+    TooManyReceipts {
+        limit: usize,
+    },
+    /// The transaction and its receipts burnt more than `limit` gas.
+    GasLimitExceeded {
+        limit: Gas,
+    },
+    /// The simulation took longer than its time limit.
+    TimeLimitExceeded {
+        limit: Duration,
+    },
+    Runtime(RuntimeError),
+}
+
+impl From<RuntimeError> for SimulationError {
+    fn from(error: RuntimeError) -> Self {
+        Self::Runtime(error)
+    }
+}
+
+impl From<StorageError> for SimulationError {
+    fn from(error: StorageError) -> Self {
+        Self::Runtime(RuntimeError::StorageError(error))
+    }
+}
+
+#[derive(Debug)]
+pub struct SimulationResult {
+    pub transaction_outcome: ExecutionOutcomeWithId,
+    /// Outcomes of the receipts in the order they were executed.
+    pub receipt_outcomes: Vec<ExecutionOutcomeWithId>,
+    /// All receipts produced by the transaction, including the ones which
+    /// don't have an outcome of their own, like data receipts.
+    pub receipts: Vec<Receipt>,
+    /// Changes of the state of all the shards touched by the transaction.
+    pub state_changes: Vec<RawStateChangesWithTrieKey>,
+}
+
+/// State of a shard touched by the simulation.
+struct SimulatedShard {
+    apply_state: ApplyState,
+    state_update: TrieUpdate,
+    stats: ApplyStats,
+}
+
+struct Simulation<'a, F> {
+    runtime: &'a Runtime,
+    epoch_id: &'a EpochId,
+    epoch_info_provider: &'a dyn EpochInfoProvider,
+    shard_state: F,
+    shards: HashMap<ShardId, SimulatedShard>,
+}
+
+impl<'a, F> Simulation<'a, F>
+where
+    F: Fn(ShardId) -> Result<(ApplyState, Trie), SimulationError>,
+{
+    fn shard(&mut self, account_id: &AccountId) -> Result<&mut SimulatedShard, SimulationError> {
+        let shard_id = self
+            .epoch_info_provider
+            .account_id_to_shard_id(account_id, self.epoch_id)
+            .map_err(RuntimeError::ValidatorError)?;
+        Ok(match self.shards.entry(shard_id) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let (apply_state, trie) = (self.shard_state)(shard_id)?;
+                entry.insert(SimulatedShard {
+                    apply_state,
+                    state_update: TrieUpdate::new(trie),
+                    stats: ApplyStats::default(),
+                })
+            }
+        })
+    }
+
+    fn process_transaction(
+        &mut self,
+        signed_transaction: &SignedTransaction,
+        verify_signature: bool,
+    ) -> Result<(Receipt, ExecutionOutcomeWithId), SimulationError> {
+        let runtime = self.runtime;
+        let shard = self.shard(signed_transaction.transaction.signer_id())?;
+        runtime
+            .process_transaction(
+                &mut shard.state_update,
+                &shard.apply_state,
+                signed_transaction,
+                verify_signature,
+                &mut shard.stats,
+            )
+            .map_err(SimulationError::InvalidTransaction)
+    }
+
+    /// Processes the receipt and returns its outcome, if it got executed, and
+    /// the receipts it produced.
+    fn process_receipt(
+        &mut self,
+        receipt: &Receipt,
+    ) -> Result<(Option<ExecutionOutcomeWithId>, Vec<Receipt>), SimulationError> {
+        let runtime = self.runtime;
+        let epoch_info_provider = self.epoch_info_provider;
+        let shard = self.shard(receipt.receiver_id())?;
+        let pipeline_manager = ReceiptPreparationPipeline::new(
+            Arc::clone(&shard.apply_state.config),
+            shard.apply_state.cache.as_ref().map(|cache| cache.handle()),
+            shard.apply_state.current_protocol_version,
+            shard.state_update.contract_storage(),
+        );
+        // All produced receipts are collected regardless of the shard they
+        // are sent to, there is no congestion to account for.
+        let mut receipt_sink = ReceiptSink::V1(ReceiptSinkV1 { outgoing_receipts: vec![] });
+        let outcome = runtime.process_receipt(
+            &mut shard.state_update,
+            &shard.apply_state,
+            &pipeline_manager,
+            &mut shard.stats,
+            epoch_info_provider,
+            receipt,
+            &mut receipt_sink,
+            &mut vec![],
+        )?;
+        // Data receipts which don't trigger the execution of a postponed
+        // receipt leave their changes uncommitted.
+        shard
+            .state_update
+            .commit(StateChangeCause::ReceiptProcessing { receipt_hash: receipt.get_hash() });
+        Ok((outcome, receipt_sink.into_outgoing_receipts()))
+    }
+
+    fn into_state_changes(self) -> Result<Vec<RawStateChangesWithTrieKey>, SimulationError> {
+        let mut shards: Vec<_> = self.shards.into_iter().collect();
+        shards.sort_by_key(|(shard_id, _)| *shard_id);
+        let mut state_changes = vec![];
+        for (_, shard) in shards {
+            // Finalizing only computes the changes of the trie, they are
+            // dropped right away.
+            state_changes.extend(shard.state_update.finalize()?.state_changes);
+        }
+        Ok(state_changes)
+    }
+}
+
+impl Runtime {
+    /// Dry runs the transaction and all the receipts it produces.
+    ///
+    /// `shard_state` provides the apply state and the state of the shards the
+    /// transaction touches. Skipping the signature check allows to simulate
+    /// transactions built without access to the private key. The gas burnt by
+    /// the transaction and all its receipts together is capped at
+    /// `max_gas_burnt`.
+    pub fn simulate_transaction(
+        &self,
+        signed_transaction: &SignedTransaction,
+        verify_signature: bool,
+        max_gas_burnt: Gas,
+        epoch_id: &EpochId,
+        epoch_info_provider: &dyn EpochInfoProvider,
+        shard_state: impl Fn(ShardId) -> Result<(ApplyState, Trie), SimulationError>,
+    ) -> Result<SimulationResult, SimulationError> {
+        let deadline = Instant::now() + time_limit;
+        let mut simulation = Simulation {
+            runtime: self,
+            epoch_id,
+            epoch_info_provider,
+            shard_state,
+            shards: HashMap::new(),
+        };
+        let (receipt, transaction_outcome) =
+            simulation.process_transaction(signed_transaction, verify_signature)?;
+        let mut gas_burnt = transaction_outcome.outcome.gas_burnt;
+        check_gas_burnt(gas_burnt, max_gas_burnt)?;
+
+        let mut receipts = vec![];
+        let mut receipt_outcomes = vec![];
+        let mut pending_receipts = VecDeque::from([receipt]);
+        while let Some(receipt) = pending_receipts.pop_front() {
+            if receipts.len() >= MAX_SIMULATED_RECEIPTS {
+                return Err(SimulationError::TooManyReceipts { limit: MAX_SIMULATED_RECEIPTS });
+            }
+            if Instant::now() > deadline {
+                return Err(SimulationError::TimeLimitExceeded { limit: time_limit });
+            }
+            let (outcome, new_receipts) = simulation.process_receipt(&receipt)?;
+            if let Some(outcome) = &outcome {
+                gas_burnt = gas_burnt.saturating_add(outcome.outcome.gas_burnt);
+                check_gas_burnt(gas_burnt, max_gas_burnt)?;
+            }
+            receipt_outcomes.extend(outcome);
+            pending_receipts.extend(new_receipts);
+            receipts.push(receipt);
+        }
+
+        let state_changes = simulation.into_state_changes()?;
+        Ok(SimulationResult { transaction_outcome, receipt_outcomes, receipts, state_changes })
+    }
+}
+
+fn check_gas_burnt(gas_burnt: Gas, max_gas_burnt: Gas) -> Result<(), SimulationError> {
+    if gas_burnt > max_gas_burnt {
+        return Err(SimulationError::GasLimitExceeded { limit: max_gas_burnt });
+    }
+    Ok(())
+}
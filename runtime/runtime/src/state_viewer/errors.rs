@@ -76,6 +76,12 @@ impl From<near_primitives::errors::StorageError> for ViewAccountError {
     }
 }
 
+impl From<std::io::Error> for ViewAccountError {
+    fn from(io_error: std::io::Error) -> Self {
+        Self::InternalError { error_message: io_error.to_string() }
+    }
+}
+
 impl From<near_primitives::errors::StorageError> for ViewContractCodeError {
     fn from(storage_error: near_primitives::errors::StorageError) -> Self {
         Self::InternalError { error_message: storage_error.to_string() }
@@ -94,6 +100,12 @@ impl From<near_primitives::errors::StorageError> for ViewStateError {
     }
 }
 
+impl From<std::io::Error> for ViewStateError {
+    fn from(io_error: std::io::Error) -> Self {
+        Self::InternalError { error_message: io_error.to_string() }
+    }
+}
+
 impl From<near_primitives::errors::StorageError> for CallFunctionError {
     fn from(storage_error: near_primitives::errors::StorageError) -> Self {
         Self::InternalError { error_message: storage_error.to_string() }
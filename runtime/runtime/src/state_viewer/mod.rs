@@ -13,13 +13,14 @@ use near_primitives::hash::CryptoHash;
 use near_primitives::receipt::{ActionReceipt, Receipt, ReceiptEnum, ReceiptV1};
 use near_primitives::runtime::migration_data::{MigrationData, MigrationFlags};
 use near_primitives::transaction::FunctionCallAction;
-use near_primitives::trie_key::trie_key_parsers;
+use near_primitives::trie_key::{trie_key_parsers, TrieKey};
 use near_primitives::types::{
     AccountId, BlockHeight, EpochHeight, EpochId, EpochInfoProvider, Gas, ShardId,
 };
 use near_primitives::version::PROTOCOL_VERSION;
 use near_primitives::views::{StateItem, ViewStateResult};
 use near_primitives_core::config::ViewConfig;
+use near_store::flat::archival::ArchivalFlatStateView;
 use near_store::{get_access_key, get_account, TrieUpdate};
 use near_vm_runner::logic::{ProtocolVersion, ReturnData};
 use near_vm_runner::{ContractCode, ContractRuntimeCache};
@@ -28,6 +29,10 @@ use tracing::debug;
 
 pub mod errors;
 
+/// Maximum number of items in a page returned by
+/// [`TrieViewer::view_state_page`].
+pub const MAX_VIEW_STATE_PAGE_LIMIT: u64 = 1000;
+
 /// State for the view call.
 #[derive(Debug)]
 pub struct ViewApplyState {
@@ -74,6 +79,10 @@ impl TrieViewer {
         Self { state_size_limit, max_gas_burnt_view }
     }
 
+    pub fn max_gas_burnt_view(&self) -> Gas {
+        self.max_gas_burnt_view
+    }
+
     pub fn view_account(
         &self,
         state_update: &TrieUpdate,
@@ -178,12 +187,149 @@ impl TrieViewer {
             values.push(StateItem { key: key[acc_sep_len..].to_vec().into(), value: value.into() });
         }
         let proof = iter.into_visited_nodes();
-        Ok(ViewStateResult { values, proof })
+        Ok(ViewStateResult { values, proof, continuation_token: None })
+    }
+
+    /// Returns a page of the contract data of the account matching the
+    /// prefix, starting with the first key not smaller than `start_key`.
+    ///
+    /// Unlike [`Self::view_state`], this works for accounts with a state of
+    /// any size. A page holds at most `limit` items and, if the state size
+    /// limit is configured, is cut once its keys and values take that many
+    /// bytes. If more items are left, the key of the next one is returned as
+    /// the continuation token.
+    pub fn view_state_page(
+        &self,
+        state_update: &TrieUpdate,
+        account_id: &AccountId,
+        prefix: &[u8],
+        start_key: &[u8],
+        limit: u64,
+        include_proof: bool,
+    ) -> Result<ViewStateResult, errors::ViewStateError> {
+        if get_account(state_update, account_id)?.is_none() {
+            return Err(errors::ViewStateError::AccountDoesNotExist {
+                requested_account_id: account_id.clone(),
+            });
+        }
+        let limit = limit.clamp(1, MAX_VIEW_STATE_PAGE_LIMIT);
+
+        let query = trie_key_parsers::get_raw_prefix_for_contract_data(account_id, prefix);
+        let acc_sep_len = query.len() - prefix.len();
+        let mut seek_key = query[..acc_sep_len].to_vec();
+        seek_key.extend_from_slice(std::cmp::max(prefix, start_key));
+
+        let mut values = vec![];
+        let mut page_size = 0;
+        let mut continuation_token = None;
+        let mut iter = state_update.trie().disk_iter()?;
+        iter.remember_visited_nodes(include_proof);
+        iter.seek(&seek_key)?;
+        for item in &mut iter {
+            let (key, value) = item?;
+            if !key.starts_with(&query) {
+                break;
+            }
+            let key = &key[acc_sep_len..];
+            if values.len() as u64 >= limit
+                || self.state_size_limit.is_some_and(|size_limit| page_size >= size_limit)
+            {
+                continuation_token = Some(key.to_vec().into());
+                break;
+            }
+            page_size += (key.len() + value.len()) as u64;
+            values.push(StateItem { key: key.to_vec().into(), value: value.into() });
+        }
+        let proof = iter.into_visited_nodes();
+        Ok(ViewStateResult { values, proof, continuation_token })
+    }
+
+    /// Same as [`Self::view_account`] but reads the account from the archival
+    /// flat state. Returns `None` if the archival flat state doesn't know the
+    /// account, in which case it has to be read from the trie.
+    pub fn view_account_archival(
+        &self,
+        view: &ArchivalFlatStateView,
+        account_id: &AccountId,
+    ) -> Result<Option<Account>, errors::ViewAccountError> {
+        let Some(account) =
+            view.get(&TrieKey::Account { account_id: account_id.clone() }.to_vec())?
+        else {
+            return Ok(None);
+        };
+        let Some(account) = account else {
+            return Err(errors::ViewAccountError::AccountDoesNotExist {
+                requested_account_id: account_id.clone(),
+            });
+        };
+        Ok(Some(Account::try_from_slice(&account)?))
+    }
+
+    /// Same as [`Self::view_state`] or, if `start_key` or `limit` is set,
+    /// [`Self::view_state_page`], but reads the state from the archival flat
+    /// state. Returns `None` if the state has to be read from the trie.
+    pub fn view_state_archival(
+        &self,
+        view: &ArchivalFlatStateView,
+        account_id: &AccountId,
+        prefix: &[u8],
+        start_key: Option<&[u8]>,
+        limit: Option<u64>,
+    ) -> Result<Option<ViewStateResult>, errors::ViewStateError> {
+        let account = match self.view_account_archival(view, account_id) {
+            Ok(Some(account)) => account,
+            Ok(None) => return Ok(None),
+            Err(errors::ViewAccountError::AccountDoesNotExist { requested_account_id }) => {
+                return Err(errors::ViewStateError::AccountDoesNotExist { requested_account_id })
+            }
+            Err(err) => {
+                return Err(errors::ViewStateError::InternalError {
+                    error_message: err.to_string(),
+                })
+            }
+        };
+        let paged = start_key.is_some() || limit.is_some();
+        // The exact check needs the length of the contract code, leave it to
+        // the trie.
+        if !paged && self.state_size_limit.is_some_and(|limit| account.storage_usage() > limit) {
+            return Ok(None);
+        }
+        let limit = if paged {
+            limit.unwrap_or(MAX_VIEW_STATE_PAGE_LIMIT).clamp(1, MAX_VIEW_STATE_PAGE_LIMIT)
+        } else {
+            u64::MAX
+        };
+
+        let query = trie_key_parsers::get_raw_prefix_for_contract_data(account_id, prefix);
+        let acc_sep_len = query.len() - prefix.len();
+        let mut seek_key = query[..acc_sep_len].to_vec();
+        seek_key.extend_from_slice(std::cmp::max(prefix, start_key.unwrap_or_default()));
+        let Some(iter) = view.iter_prefix(&query, &seek_key) else {
+            return Ok(None);
+        };
+
+        let mut values = vec![];
+        let mut page_size = 0;
+        let mut continuation_token = None;
+        for item in iter {
+            let (key, value) = item?;
+            let key = &key[acc_sep_len..];
+            if paged
+                && (values.len() as u64 >= limit
+                    || self.state_size_limit.is_some_and(|size_limit| page_size >= size_limit))
+            {
+                continuation_token = Some(key.to_vec().into());
+                break;
+            }
+            page_size += (key.len() + value.len()) as u64;
+            values.push(StateItem { key: key.to_vec().into(), value: value.into() });
+        }
+        Ok(Some(ViewStateResult { values, proof: vec![], continuation_token }))
     }
 
     pub fn call_function(
         &self,
-        mut state_update: TrieUpdate,
+        state_update: &mut TrieUpdate,
         view_state: ViewApplyState,
         contract_id: &AccountId,
         method_name: &str,
@@ -193,7 +339,7 @@ impl TrieViewer {
     ) -> Result<Vec<u8>, errors::CallFunctionError> {
         let now = Instant::now();
         let root = *state_update.get_root();
-        let account = get_account(&state_update, contract_id)?.ok_or_else(|| {
+        let account = get_account(state_update, contract_id)?.ok_or_else(|| {
             errors::CallFunctionError::AccountDoesNotExist {
                 requested_account_id: contract_id.clone(),
             }
@@ -258,7 +404,7 @@ impl TrieViewer {
         let contract = pipeline.get_contract(&receipt, account.code_hash(), 0, view_config.clone());
 
         let mut runtime_ext = RuntimeExt::new(
-            &mut state_update,
+            state_update,
             &mut receipt_manager,
             contract_id.clone(),
             account,
@@ -90,6 +90,7 @@ impl Network {
                     tier1_connections: vec![],
                     tier1_accounts_keys: vec![],
                     tier1_accounts_data: vec![],
+                    snapshot_hosts: vec![],
                 }),
                 info_futures: Default::default(),
             })),
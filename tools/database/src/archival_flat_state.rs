@@ -0,0 +1,29 @@
+use anyhow::Context;
+use near_chain_configs::GenesisValidationMode;
+use near_store::flat::archival::backfill_archival_flat_state;
+use nearcore::{load_config, open_storage};
+use std::path::Path;
+
+/// Fills the archival flat state with the heights before it was enabled, so
+/// that it can answer queries at all heights. The node must not be running.
+#[derive(clap::Args)]
+pub(crate) struct BackfillArchivalFlatStateCommand {}
+
+impl BackfillArchivalFlatStateCommand {
+    pub(crate) fn run(
+        &self,
+        home: &Path,
+        genesis_validation: GenesisValidationMode,
+    ) -> anyhow::Result<()> {
+        let mut near_config = load_config(home, genesis_validation)?;
+        let storage = open_storage(home, &mut near_config)?;
+        let cold_db = storage.cold_db().context("the archival flat state requires cold storage")?;
+        let store =
+            storage.get_split_store().context("the archival flat state requires cold storage")?;
+        let genesis_height = near_config.genesis.config.genesis_height;
+        tracing::info!(target: "database", genesis_height, "Backfilling archival flat state");
+        backfill_archival_flat_state(cold_db, &store, genesis_height)?;
+        tracing::info!(target: "database", "Backfilled archival flat state");
+        Ok(())
+    }
+}
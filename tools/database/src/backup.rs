@@ -0,0 +1,430 @@
+//! Incremental backups of the hot database and restoring from them.
+//!
+//! Layout of the backup directory:
+//! * `sst/<identity>/` – SST files shared between backups of the database
+//!   with given RocksDB identity.  SST files are immutable, so a file is only
+//!   copied by the first backup which sees it;
+//! * `backups/<id>/manifest.json` – the [`BackupManifest`];
+//! * `backups/<id>/files/` – all other files of the backup (`CURRENT`,
+//!   `MANIFEST-*`, `OPTIONS-*` and so on).
+//!
+//! A backup is taken from a RocksDB checkpoint which the running node creates
+//! on request, see [`near_store::backup_checkpoint`].  The database itself is
+//! never opened by the backup, so the node keeps running all the time.
+use anyhow::{bail, Context};
+use near_primitives::block::Tip;
+use near_primitives::hash::CryptoHash;
+use near_primitives::types::BlockHeight;
+use near_store::backup_checkpoint::{
+    CHECKPOINT_DIR_NAME, CHECKPOINT_REQUEST_FILE_NAME, REQUEST_CHECK_PERIOD,
+};
+use near_store::db::{RocksDB, HEAD_KEY};
+use near_store::metadata::{DbVersion, DB_VERSION};
+use near_store::{DBCol, Mode, NodeStorage, StoreBackend, StoreConfig, Temperature};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use strum::IntoEnumIterator;
+
+use crate::utils::resolve_column;
+
+/// Version of the backup format.  Must be bumped on incompatible changes of the
+/// layout or of the manifest.
+const BACKUP_FORMAT_VERSION: u32 = 1;
+
+const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+/// How long to wait for the node to create the requested checkpoint.
+const CHECKPOINT_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct BackupManifest {
+    format_version: u32,
+    /// Sequential number of the backup.
+    id: u64,
+    /// Identity of the RocksDB database the backup was taken from.
+    identity: String,
+    db_version: DbVersion,
+    db_kind: String,
+    head_height: BlockHeight,
+    head_hash: CryptoHash,
+    /// Columns of the database, as known to the neard which took the backup.
+    columns: Vec<String>,
+    files: Vec<BackupFile>,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+struct BackupFile {
+    name: String,
+    size: u64,
+    crc32: u32,
+    /// Whether the file is in the shared SST directory rather than in the
+    /// directory of the backup.
+    shared: bool,
+}
+
+#[derive(clap::Args)]
+pub(crate) struct BackupCommand {
+    /// Directory with the backups.  Created if it doesn't exist.
+    #[clap(long)]
+    destination: PathBuf,
+}
+
+#[derive(clap::Args)]
+pub(crate) struct RestoreCommand {
+    /// Directory with the backups.
+    #[clap(long)]
+    source: PathBuf,
+    /// Backup to restore.  The latest backup is restored by default.
+    #[clap(long)]
+    id: Option<u64>,
+}
+
+impl BackupCommand {
+    pub(crate) fn run(&self, home_dir: &Path, store_config: &StoreConfig) -> anyhow::Result<()> {
+        if store_config.backend != StoreBackend::RocksDB {
+            bail!("backups are only supported for RocksDB databases");
+        }
+        let db_path = db_path(home_dir, store_config);
+        let identity = fs::read_to_string(db_path.join("IDENTITY"))
+            .with_context(|| format!("failed to read identity of {}", db_path.display()))?
+            .trim()
+            .to_string();
+        let checkpoint_path = db_path.join(CHECKPOINT_DIR_NAME);
+        if checkpoint_path.exists() {
+            // Left over from an interrupted backup.
+            fs::remove_dir_all(&checkpoint_path)?;
+        }
+
+        request_checkpoint(&db_path)?;
+
+        let (head, db_version, db_kind) = {
+            let db =
+                RocksDB::open(&checkpoint_path, store_config, Mode::ReadOnly, Temperature::Hot)?;
+            let store = NodeStorage::new(Arc::new(db)).get_hot_store();
+            let head = store
+                .get_ser::<Tip>(DBCol::BlockMisc, HEAD_KEY)?
+                .context("the database has no head")?;
+            let db_version = store.get_db_version()?.context("the database has no version")?;
+            let db_kind = store.get_db_kind()?.context("the database has no kind")?;
+            (head, db_version, db_kind)
+        };
+        tracing::info!(target: "backup", height = head.height, "the node created the checkpoint");
+
+        let previous = latest_backup(&self.destination)?;
+        let id = previous.as_ref().map_or(1, |manifest| manifest.id + 1);
+        // Shared files are only reused if they were copied by a backup of the
+        // same database.  File numbers of different databases may clash.
+        let previous_files: HashMap<String, BackupFile> = previous
+            .filter(|manifest| manifest.identity == identity)
+            .map(|manifest| manifest.files.into_iter().filter(|file| file.shared))
+            .into_iter()
+            .flatten()
+            .map(|file| (file.name.clone(), file))
+            .collect();
+
+        let shared_dir = self.destination.join("sst").join(&identity);
+        let backup_dir = backup_dir(&self.destination, id);
+        let tmp_dir = backup_dir.with_extension("tmp");
+        if tmp_dir.exists() {
+            fs::remove_dir_all(&tmp_dir)?;
+        }
+        fs::create_dir_all(&shared_dir)?;
+        fs::create_dir_all(tmp_dir.join("files"))?;
+
+        let mut files = Vec::new();
+        let (mut copied, mut reused) = (0u64, 0u64);
+        for entry in fs::read_dir(&checkpoint_path)? {
+            let entry = entry?;
+            let name = entry.file_name().into_string().map_err(|name| {
+                anyhow::anyhow!("unexpected file name {name:?} in the checkpoint")
+            })?;
+            let size = entry.metadata()?.len();
+            let shared = name.ends_with(".sst");
+            if shared {
+                let target = shared_dir.join(&name);
+                if let Some(file) = previous_files.get(&name) {
+                    if file.size == size && fs::metadata(&target).map_or(false, |m| m.len() == size)
+                    {
+                        files.push(file.clone());
+                        reused += size;
+                        continue;
+                    }
+                }
+                let crc32 = copy_file(&entry.path(), &target)?;
+                files.push(BackupFile { name, size, crc32, shared });
+            } else {
+                let crc32 = copy_file(&entry.path(), &tmp_dir.join("files").join(&name))?;
+                files.push(BackupFile { name, size, crc32, shared });
+            }
+            copied += size;
+        }
+
+        let manifest = BackupManifest {
+            format_version: BACKUP_FORMAT_VERSION,
+            id,
+            identity,
+            db_version,
+            db_kind: db_kind.to_string(),
+            head_height: head.height,
+            head_hash: head.last_block_hash,
+            columns: DBCol::iter().map(|col| <&str>::from(col).to_string()).collect(),
+            files,
+        };
+        let mut file = fs::File::create(tmp_dir.join(MANIFEST_FILE_NAME))?;
+        serde_json::to_writer_pretty(&mut file, &manifest)?;
+        file.sync_all()?;
+        // The backup becomes visible only once it's complete.
+        fs::rename(&tmp_dir, &backup_dir)?;
+        fs::remove_dir_all(&checkpoint_path)?;
+
+        println!(
+            "Created backup {id} at height {} in {}: copied {}, reused {}",
+            head.height,
+            backup_dir.display(),
+            bytesize::ByteSize(copied),
+            bytesize::ByteSize(reused),
+        );
+        Ok(())
+    }
+}
+
+impl RestoreCommand {
+    pub(crate) fn run(&self, home_dir: &Path, store_config: &StoreConfig) -> anyhow::Result<()> {
+        if store_config.backend != StoreBackend::RocksDB {
+            bail!("backups are only supported for RocksDB databases");
+        }
+        let db_path = db_path(home_dir, store_config);
+        if db_path.exists() {
+            bail!("{} already exists, move it away to restore the backup", db_path.display());
+        }
+        let manifest = match self.id {
+            Some(id) => read_manifest(&backup_dir(&self.source, id))?,
+            None => latest_backup(&self.source)?.context("no backups found")?,
+        };
+        verify_manifest(&manifest)?;
+
+        // The database is restored to a temporary directory which is moved in
+        // place only after it's verified, so that the node never starts from
+        // a partially restored database.
+        let tmp_path = db_path.with_extension("restore");
+        if tmp_path.exists() {
+            fs::remove_dir_all(&tmp_path)?;
+        }
+        fs::create_dir_all(&tmp_path)?;
+        let shared_dir = self.source.join("sst").join(&manifest.identity);
+        let files_dir = backup_dir(&self.source, manifest.id).join("files");
+        for file in &manifest.files {
+            let source = if file.shared { &shared_dir } else { &files_dir }.join(&file.name);
+            let target = tmp_path.join(&file.name);
+            let crc32 = copy_file(&source, &target)?;
+            let size = fs::metadata(&target)?.len();
+            if size != file.size || crc32 != file.crc32 {
+                bail!("{} is corrupted", source.display());
+            }
+        }
+        // The restored database may diverge from the original one, let RocksDB
+        // generate a new identity so that their backups don't get mixed up.
+        let _ = fs::remove_file(tmp_path.join("IDENTITY"));
+
+        {
+            let db =
+                RocksDB::open(&tmp_path, store_config, Mode::ReadWriteExisting, Temperature::Hot)?;
+            let store = NodeStorage::new(Arc::new(db)).get_hot_store();
+            let head = store
+                .get_ser::<Tip>(DBCol::BlockMisc, HEAD_KEY)?
+                .context("the restored database has no head")?;
+            if head.height != manifest.head_height || head.last_block_hash != manifest.head_hash {
+                bail!(
+                    "head of the restored database is {} at height {}, the manifest says {} at height {}",
+                    head.last_block_hash,
+                    head.height,
+                    manifest.head_hash,
+                    manifest.head_height
+                );
+            }
+            if store.get_db_version()? != Some(manifest.db_version) {
+                bail!("version of the restored database does not match the manifest");
+            }
+        }
+        fs::rename(&tmp_path, &db_path)?;
+
+        println!(
+            "Restored backup {} at height {} to {}",
+            manifest.id,
+            manifest.head_height,
+            db_path.display()
+        );
+        Ok(())
+    }
+}
+
+/// Asks the running node to create a checkpoint of the database at `db_path`
+/// and waits until it's there.
+fn request_checkpoint(db_path: &Path) -> anyhow::Result<()> {
+    let request_path = db_path.join(CHECKPOINT_REQUEST_FILE_NAME);
+    fs::File::create(&request_path)
+        .with_context(|| format!("failed to create {}", request_path.display()))?;
+    let checkpoint_path = db_path.join(CHECKPOINT_DIR_NAME);
+    let started = Instant::now();
+    while !checkpoint_path.exists() {
+        if started.elapsed() > CHECKPOINT_TIMEOUT {
+            let _ = fs::remove_file(&request_path);
+            bail!(
+                "the node didn't create the checkpoint in {CHECKPOINT_TIMEOUT:?}, the node has to be running to take a backup"
+            );
+        }
+        std::thread::sleep(REQUEST_CHECK_PERIOD.unsigned_abs() / 10);
+    }
+    Ok(())
+}
+
+fn db_path(home_dir: &Path, store_config: &StoreConfig) -> PathBuf {
+    home_dir.join(store_config.path.as_deref().unwrap_or_else(|| Path::new("data")))
+}
+
+fn backup_dir(backups_dir: &Path, id: u64) -> PathBuf {
+    backups_dir.join("backups").join(format!("{id:06}"))
+}
+
+fn read_manifest(backup_dir: &Path) -> anyhow::Result<BackupManifest> {
+    let path = backup_dir.join(MANIFEST_FILE_NAME);
+    let file =
+        fs::File::open(&path).with_context(|| format!("failed to open {}", path.display()))?;
+    serde_json::from_reader(io::BufReader::new(file))
+        .with_context(|| format!("failed to parse {}", path.display()))
+}
+
+/// Returns the manifest of the latest complete backup, if any.
+fn latest_backup(backups_dir: &Path) -> anyhow::Result<Option<BackupManifest>> {
+    let dir = backups_dir.join("backups");
+    if !dir.exists() {
+        return Ok(None);
+    }
+    let mut latest = None;
+    for entry in fs::read_dir(&dir)? {
+        let path = entry?.path();
+        // Directories of incomplete backups have an extension.
+        let Some(id) = path.file_name().and_then(|name| name.to_str()?.parse::<u64>().ok()) else {
+            continue;
+        };
+        if latest.map_or(true, |latest| latest < id) {
+            latest = Some(id);
+        }
+    }
+    latest.map(|id| read_manifest(&backup_dir(backups_dir, id))).transpose()
+}
+
+/// Checks that this neard is able to use the backed up database.
+fn verify_manifest(manifest: &BackupManifest) -> anyhow::Result<()> {
+    if manifest.format_version != BACKUP_FORMAT_VERSION {
+        bail!(
+            "unsupported backup format {}, expected {BACKUP_FORMAT_VERSION}",
+            manifest.format_version
+        );
+    }
+    if manifest.db_version > DB_VERSION {
+        bail!(
+            "backup has database version {} which is newer than {DB_VERSION} supported by this neard",
+            manifest.db_version
+        );
+    }
+    for column in &manifest.columns {
+        resolve_column(column).context("backup was taken by a newer neard")?;
+    }
+    Ok(())
+}
+
+/// Copies the file through a temporary file and returns its crc32 checksum.
+fn copy_file(source: &Path, target: &Path) -> anyhow::Result<u32> {
+    let tmp = target.with_extension("tmp");
+    let mut reader =
+        fs::File::open(source).with_context(|| format!("failed to open {}", source.display()))?;
+    let mut writer = io::BufWriter::new(fs::File::create(&tmp)?);
+    let mut hasher = crc32fast::Hasher::new();
+    let mut buf = vec![0; 1 << 20];
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+        writer.write_all(&buf[..n])?;
+    }
+    writer.into_inner().map_err(|err| err.into_error())?.sync_all()?;
+    fs::rename(&tmp, target)?;
+    Ok(hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BackupCommand, RestoreCommand};
+    use near_primitives::block::Tip;
+    use near_primitives::hash::CryptoHash;
+    use near_store::backup_checkpoint::create_requested_checkpoint;
+    use near_store::db::HEAD_KEY;
+    use near_store::{DBCol, Mode, NodeStorage, StoreConfig, Temperature};
+    use std::path::Path;
+
+    fn write_head(home_dir: &std::path::Path, store_config: &StoreConfig, height: u64) {
+        let node_storage = NodeStorage::opener(home_dir, store_config, None).open().unwrap();
+        let mut store_update = node_storage.get_hot_store().store_update();
+        let head = Tip {
+            height,
+            last_block_hash: CryptoHash::hash_bytes(&height.to_le_bytes()),
+            prev_block_hash: CryptoHash::default(),
+            epoch_id: Default::default(),
+            next_epoch_id: Default::default(),
+        };
+        store_update.set_ser(DBCol::BlockMisc, HEAD_KEY, &head).unwrap();
+        store_update.insert(DBCol::Block, height.to_le_bytes().to_vec(), vec![42]);
+        store_update.commit().unwrap();
+    }
+
+    /// Takes a backup while serving the checkpoint requests as the node would.
+    fn backup_running_node(home_dir: &Path, store_config: &StoreConfig, backup: &BackupCommand) {
+        let opener = NodeStorage::opener(home_dir, store_config, None);
+        let db_path = opener.path().to_path_buf();
+        let db = opener.open().unwrap().into_inner(Temperature::Hot);
+        std::thread::scope(|scope| {
+            let backup = scope.spawn(|| backup.run(home_dir, store_config));
+            while !backup.is_finished() {
+                create_requested_checkpoint(db.as_ref(), &db_path).unwrap();
+                std::thread::sleep(std::time::Duration::from_millis(10));
+            }
+            backup.join().unwrap().unwrap();
+        });
+    }
+
+    /// Takes two backups and restores the older one.
+    #[test]
+    fn test_backup_and_restore() {
+        let home_dir = tempfile::tempdir().unwrap();
+        let backups = tempfile::tempdir().unwrap();
+        let store_config = StoreConfig::test_config();
+
+        write_head(home_dir.path(), &store_config, 1);
+        let backup = BackupCommand { destination: backups.path().to_path_buf() };
+        backup_running_node(home_dir.path(), &store_config, &backup);
+        write_head(home_dir.path(), &store_config, 2);
+        backup_running_node(home_dir.path(), &store_config, &backup);
+
+        let restore_home = tempfile::tempdir().unwrap();
+        let restore = RestoreCommand { source: backups.path().to_path_buf(), id: Some(1) };
+        restore.run(restore_home.path(), &store_config).unwrap();
+        // Restoring over an existing database is not allowed.
+        assert!(restore.run(restore_home.path(), &store_config).is_err());
+
+        let node_storage = NodeStorage::opener(restore_home.path(), &store_config, None)
+            .open_in_mode(Mode::ReadOnly)
+            .unwrap();
+        let store = node_storage.get_hot_store();
+        let head = store.get_ser::<Tip>(DBCol::BlockMisc, HEAD_KEY).unwrap().unwrap();
+        assert_eq!(head.height, 1);
+        assert!(store.exists(DBCol::Block, &1u64.to_le_bytes()).unwrap());
+        assert!(!store.exists(DBCol::Block, &2u64.to_le_bytes()).unwrap());
+    }
+}
@@ -4,6 +4,8 @@ use crate::analyse_gas_usage::AnalyseGasUsageCommand;
 use crate::analyse_high_load::HighLoadStatsCommand;
 use crate::analyze_contract_sizes::AnalyzeContractSizesCommand;
 use crate::analyze_delayed_receipt::AnalyzeDelayedReceiptCommand;
+use crate::archival_flat_state::BackfillArchivalFlatStateCommand;
+use crate::backup::{BackupCommand, RestoreCommand};
 use crate::compact::RunCompactionCommand;
 use crate::corrupt::CorruptStateSnapshotCommand;
 use crate::make_snapshot::MakeSnapshotCommand;
@@ -11,6 +13,7 @@ use crate::memtrie::LoadMemTrieCommand;
 use crate::resharding_v2::ReshardingV2Command;
 use crate::run_migrations::RunMigrationsCommand;
 use crate::state_perf::StatePerfCommand;
+use crate::trie_gc_check::TrieGcCheckCommand;
 use crate::write_to_db::WriteCryptoHashCommand;
 use clap::Parser;
 use near_chain_configs::GenesisValidationMode;
@@ -31,6 +34,14 @@ enum SubCommand {
     /// Analyse gas usage in a chosen sequnce of blocks
     AnalyseGasUsage(AnalyseGasUsageCommand),
 
+    /// Fill the archival flat state with the heights before it was enabled.
+    BackfillArchivalFlatState(BackfillArchivalFlatStateCommand),
+
+    /// Back up the hot database incrementally.  The database is only held
+    /// open while a checkpoint is created, so the node can be restarted right
+    /// after the command logs that the checkpoint was created.
+    Backup(BackupCommand),
+
     /// Change DbKind of hot or cold db.
     ChangeDbKind(ChangeDbKindCommand),
 
@@ -43,6 +54,10 @@ enum SubCommand {
     /// Make snapshot of the database
     MakeSnapshot(MakeSnapshotCommand),
 
+    /// Restore the hot database from a backup made with the backup command.
+    /// Fails if the database exists.
+    Restore(RestoreCommand),
+
     /// Run migrations
     RunMigrations(RunMigrationsCommand),
 
@@ -50,6 +65,10 @@ enum SubCommand {
     /// Uses RocksDB data specified via --home argument.
     StatePerf(StatePerfCommand),
 
+    /// Check the reference counts of the trie nodes against the tries of the
+    /// retained blocks and optionally fix them.
+    TrieGcCheck(TrieGcCheckCommand),
+
     /// Loads an in-memory trie for research purposes.
     LoadMemTrie(LoadMemTrieCommand),
     /// Write CryptoHash to DB
@@ -75,6 +94,11 @@ impl DatabaseCommand {
         match &self.subcmd {
             SubCommand::AnalyseDataSizeDistribution(cmd) => cmd.run(home),
             SubCommand::AnalyseGasUsage(cmd) => cmd.run(home, genesis_validation),
+            SubCommand::BackfillArchivalFlatState(cmd) => cmd.run(home, genesis_validation),
+            SubCommand::Backup(cmd) => {
+                let near_config = load_config(home, genesis_validation);
+                cmd.run(home, &near_config.config.store)
+            }
             SubCommand::ChangeDbKind(cmd) => cmd.run(home, genesis_validation),
             SubCommand::CompactDatabase(cmd) => cmd.run(home),
             SubCommand::CorruptStateSnapshot(cmd) => cmd.run(home),
@@ -82,8 +106,13 @@ impl DatabaseCommand {
                 let near_config = load_config(home, genesis_validation);
                 cmd.run(home, &near_config.config.store, near_config.config.archival_config())
             }
+            SubCommand::Restore(cmd) => {
+                let near_config = load_config(home, genesis_validation);
+                cmd.run(home, &near_config.config.store)
+            }
             SubCommand::RunMigrations(cmd) => cmd.run(home, genesis_validation),
             SubCommand::StatePerf(cmd) => cmd.run(home),
+            SubCommand::TrieGcCheck(cmd) => cmd.run(home),
             SubCommand::LoadMemTrie(cmd) => cmd.run(home, genesis_validation),
             SubCommand::WriteCryptoHash(cmd) => cmd.run(home, genesis_validation),
             SubCommand::HighLoadStats(cmd) => cmd.run(home),
@@ -4,6 +4,8 @@ mod analyse_gas_usage;
 mod analyse_high_load;
 mod analyze_contract_sizes;
 mod analyze_delayed_receipt;
+mod archival_flat_state;
+mod backup;
 mod block_iterators;
 pub mod commands;
 mod compact;
@@ -13,5 +15,6 @@ mod memtrie;
 mod resharding_v2;
 mod run_migrations;
 mod state_perf;
+mod trie_gc_check;
 mod utils;
 mod write_to_db;
@@ -1,8 +1,41 @@
+use anyhow::Context;
+use bytesize::ByteSize;
+use near_chain::store_validator::StoreValidator;
 use near_chain_configs::GenesisValidationMode;
-use std::path::Path;
+use near_epoch_manager::shard_tracker::{ShardTracker, TrackedConfig};
+use near_epoch_manager::EpochManager;
+use near_store::{DatabaseDryRun, MigrationDryRun};
+use nearcore::{NearConfig, NightshadeRuntime, NightshadeRuntimeExt};
+use std::path::{Path, PathBuf};
 
+/// Runs the database migrations.
+///
+/// With `--dry-run` the migrations are first run on a checkpoint of the
+/// database, which reports the changes they make to every column and is
+/// checked with the store validator. The database is migrated only after
+/// that succeeds and it's confirmed.
+///
+/// Example usage: neard database run-migrations --dry-run
 #[derive(clap::Args)]
-pub(crate) struct RunMigrationsCommand {}
+pub(crate) struct RunMigrationsCommand {
+    /// Run the migrations on a checkpoint of the database first.
+    #[clap(long)]
+    dry_run: bool,
+    /// Directory for the checkpoint of the dry run. It should be on the same
+    /// file system as the database, so that the checkpoint consists of hard
+    /// links. Defaults to `migration-dry-run` in the home directory.
+    #[clap(long)]
+    checkpoint_dir: Option<PathBuf>,
+    /// Stop validating the migrated checkpoint after that many seconds.
+    #[clap(long)]
+    validation_timeout_secs: Option<u64>,
+    /// Keep the migrated checkpoint instead of deleting it.
+    #[clap(long)]
+    keep_checkpoint: bool,
+    /// Migrate the database after a successful dry run without asking.
+    #[clap(long)]
+    yes: bool,
+}
 
 impl RunMigrationsCommand {
     pub(crate) fn run(
@@ -12,7 +45,145 @@ impl RunMigrationsCommand {
     ) -> anyhow::Result<()> {
         let mut near_config = nearcore::config::load_config(&home_dir, genesis_validation)
             .unwrap_or_else(|e| panic!("Error loading config: {:#}", e));
+        if self.dry_run {
+            let checkpoint_dir =
+                self.checkpoint_dir.clone().unwrap_or_else(|| home_dir.join("migration-dry-run"));
+            anyhow::ensure!(
+                !checkpoint_dir.exists(),
+                "{} already exists, delete it or choose another --checkpoint-dir",
+                checkpoint_dir.display()
+            );
+            let result =
+                nearcore::dry_run_storage_migrations(home_dir, &near_config, &checkpoint_dir)
+                    .and_then(|dry_run| match dry_run {
+                        Some(dry_run) => {
+                            self.check_dry_run(home_dir, &near_config, dry_run).map(|()| true)
+                        }
+                        None => Ok(false),
+                    });
+            if checkpoint_dir.exists() {
+                if self.keep_checkpoint {
+                    println!("The migrated checkpoint is kept at {}", checkpoint_dir.display());
+                } else {
+                    std::fs::remove_dir_all(&checkpoint_dir).with_context(|| {
+                        format!("failed to delete {}", checkpoint_dir.display())
+                    })?;
+                }
+            }
+            if !result? {
+                println!("The database is up to date, there is nothing to migrate");
+                return Ok(());
+            }
+            if !self.should_apply() {
+                println!("The database was not migrated");
+                return Ok(());
+            }
+        }
         nearcore::open_storage(home_dir, &mut near_config)?;
         Ok(())
     }
+
+    /// Prints the changes made by the migrations and validates the migrated
+    /// checkpoint.
+    fn check_dry_run(
+        &self,
+        home_dir: &Path,
+        near_config: &NearConfig,
+        dry_run: MigrationDryRun,
+    ) -> anyhow::Result<()> {
+        print_database_dry_run("Hot", &dry_run.hot);
+        if let Some(cold) = &dry_run.cold {
+            print_database_dry_run("Cold", cold);
+        }
+
+        println!("Validating the migrated checkpoint");
+        let store = dry_run.storage.get_hot_store();
+        let epoch_manager = EpochManager::new_arc_handle(
+            store.clone(),
+            &near_config.genesis.config,
+            Some(home_dir),
+        );
+        let shard_tracker = ShardTracker::new(
+            TrackedConfig::from_config(&near_config.client_config),
+            epoch_manager.clone(),
+        );
+        let runtime = NightshadeRuntime::from_config(
+            home_dir,
+            store.clone(),
+            near_config,
+            epoch_manager.clone(),
+        )
+        .context("could not create the transaction runtime")?;
+        let mut store_validator = StoreValidator::new(
+            near_config.validator_signer.get().map(|signer| signer.validator_id().clone()),
+            near_config.genesis.config.clone(),
+            epoch_manager,
+            shard_tracker,
+            runtime,
+            store,
+            near_config.client_config.archive,
+        );
+        if let Some(timeout) = self.validation_timeout_secs {
+            store_validator.set_timeout(timeout.saturating_mul(1000).try_into()?);
+        }
+        store_validator.validate();
+        println!("Conditions validated: {}", store_validator.tests_done());
+        for error in &store_validator.errors {
+            println!("  {}  {}  {}", error.col, error.key, error.err);
+        }
+        anyhow::ensure!(
+            !store_validator.is_failed(),
+            "validation of the migrated checkpoint failed with {} errors",
+            store_validator.num_failed()
+        );
+        println!("No errors found");
+        Ok(())
+    }
+
+    fn should_apply(&self) -> bool {
+        if self.yes {
+            return true;
+        }
+        println!("Do you want to migrate the database? [y/N]");
+        let mut input = String::new();
+        if std::io::stdin().read_line(&mut input).is_err() {
+            return false;
+        }
+        let input = input.trim().to_lowercase();
+        input == "y" || input == "yes"
+    }
+}
+
+fn print_database_dry_run(which: &str, dry_run: &DatabaseDryRun) {
+    println!(
+        "{which} database: version {} -> {}, checkpoint at {}",
+        dry_run.from_version,
+        dry_run.to_version,
+        dry_run.checkpoint_path.display()
+    );
+    if !dry_run.new_columns.is_empty() {
+        let new_columns = dry_run.new_columns.iter().map(|col| col.to_string());
+        println!("  New columns: {}", new_columns.collect::<Vec<_>>().join(", "));
+    }
+    let changed_columns: Vec<_> =
+        dry_run.diff.iter().filter(|(_, col_diff)| !col_diff.is_empty()).collect();
+    if changed_columns.is_empty() {
+        println!("  No changes");
+        return;
+    }
+    println!(
+        "  {:<32} {:>12} {:>12} {:>12} {:>12} {:>12}",
+        "Column", "Added", "Updated", "Removed", "Bytes added", "Bytes removed"
+    );
+    for (col, col_diff) in changed_columns {
+        println!(
+            "  {:<32} {:>12} {:>12} {:>12} {:>12} {:>12}",
+            col.to_string(),
+            col_diff.rows_added,
+            col_diff.rows_updated,
+            col_diff.rows_removed,
+            ByteSize::b(col_diff.bytes_added).to_string(),
+            ByteSize::b(col_diff.bytes_removed).to_string(),
+        );
+    }
 }
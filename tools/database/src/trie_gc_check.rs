@@ -0,0 +1,557 @@
+use crate::utils::open_rocksdb;
+use anyhow::{anyhow, Context};
+use borsh::BorshDeserialize;
+use near_primitives::block::Tip;
+use near_primitives::block_header::BlockHeader;
+use near_primitives::errors::StorageError;
+use near_primitives::hash::CryptoHash;
+use near_primitives::shard_layout::{get_block_shard_uid, get_block_shard_uid_rev, ShardUId};
+use near_primitives::types::chunk_extra::ChunkExtra;
+use near_primitives::types::StateRoot;
+use near_primitives::utils::index_to_bytes;
+use near_store::adapter::StoreAdapter;
+use near_store::db::refcount::decode_value_with_rc;
+use near_store::trie::{RawTrieNode, RawTrieNodeWithSize};
+use near_store::{
+    DBCol, NodeStorage, Store, Trie, TrieChanges, TrieDBStorage, TrieStorage, HEAD_KEY,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const PROGRESS_FILENAME: &str = "trie_gc_check_progress.json";
+
+/// Checks the reference counts of the trie nodes and values in `DBCol::State`.
+///
+/// The expected reference count of a node or value is the number of times it
+/// occurs in the tries of the state roots in `ChunkExtra` of the head block,
+/// plus the references which are released when the retained blocks are
+/// garbage collected: deletions from `TrieChanges` of the canonical blocks
+/// and insertions from `TrieChanges` of the blocks on forks.
+///
+/// The node must be stopped while the command runs. The expected reference
+/// counts of a shard are kept in memory, so checking big shards needs a lot
+/// of RAM.
+///
+/// Example usage: neard database trie-gc-check --shard-uid s0.v3 --fix
+#[derive(clap::Parser)]
+pub(crate) struct TrieGcCheckCommand {
+    /// Check only these shards. Shards which share the state after
+    /// resharding are always checked together.
+    #[clap(long, use_value_delimiter = true, value_delimiter = ',')]
+    shard_uid: Option<Vec<ShardUId>>,
+    /// Fix the reference counts of the leaked, over-referenced and
+    /// under-referenced nodes.
+    #[clap(long)]
+    fix: bool,
+    /// Number of reference count fixes written to the database at once.
+    #[clap(long, default_value_t = 10_000)]
+    batch_size: usize,
+    /// Maximum number of nodes printed for every kind of problem.
+    #[clap(long, default_value_t = 10)]
+    max_reported: usize,
+    /// File recording the shards which were already checked, so that an
+    /// interrupted run is resumed. The file is ignored if the head moved
+    /// since it was written. Defaults to `trie_gc_check_progress.json` in
+    /// the home directory.
+    #[clap(long)]
+    progress_file: Option<PathBuf>,
+}
+
+/// Shards which use the same prefix of the keys in `DBCol::State`.
+#[derive(Default)]
+struct ShardGroup {
+    /// State roots of the head block.
+    roots: Vec<(ShardUId, StateRoot)>,
+    /// Keys of the `TrieChanges` which weren't garbage collected yet.
+    trie_changes: Vec<(CryptoHash, ShardUId)>,
+}
+
+impl TrieGcCheckCommand {
+    pub(crate) fn run(&self, home: &Path) -> anyhow::Result<()> {
+        let mode = if self.fix { near_store::Mode::ReadWrite } else { near_store::Mode::ReadOnly };
+        let rocksdb = Arc::new(open_rocksdb(home, mode)?);
+        let store = NodeStorage::new(rocksdb).get_hot_store();
+        let head = store
+            .get_ser::<Tip>(DBCol::BlockMisc, HEAD_KEY)?
+            .ok_or_else(|| anyhow!("head not found"))?;
+
+        let progress_file =
+            self.progress_file.clone().unwrap_or_else(|| home.join(PROGRESS_FILENAME));
+        let mut progress = Progress::load(&progress_file, &head.last_block_hash)?;
+
+        let mut groups: Vec<_> = self
+            .shard_groups(&store, &head)?
+            .into_iter()
+            .filter(|(group_uid, _)| {
+                let checked = progress.checked_shard_uids.contains(&group_uid.to_string());
+                if checked {
+                    println!("Skipping {group_uid}, already checked");
+                }
+                !checked
+            })
+            .collect();
+        // Ordered as the keys in `DBCol::State`.
+        groups.sort_by_key(|(group_uid, _)| group_uid.to_bytes());
+        self.check_refcounts(&store, &groups, |group_uid, report| {
+            report.print(group_uid, self.fix);
+            progress.checked_shard_uids.push(group_uid.to_string());
+            progress.save(&progress_file)
+        })?;
+        // All shards are checked, the next run should start from scratch.
+        if progress_file.exists() {
+            std::fs::remove_file(&progress_file)?;
+        }
+        Ok(())
+    }
+
+    /// Groups the shards of the head block and of the retained `TrieChanges`
+    /// by the shard whose keys they use in `DBCol::State`.
+    fn shard_groups(
+        &self,
+        store: &Store,
+        head: &Tip,
+    ) -> anyhow::Result<BTreeMap<ShardUId, ShardGroup>> {
+        let mut groups = BTreeMap::<ShardUId, ShardGroup>::new();
+        for item in
+            store.iter_prefix_ser::<ChunkExtra>(DBCol::ChunkExtra, head.last_block_hash.as_ref())
+        {
+            let (key, chunk_extra) = item?;
+            let (_, shard_uid) = get_block_shard_uid_rev(&key).map_err(|err| anyhow!(err))?;
+            let group_uid = mapped_shard_uid(store, shard_uid)?;
+            groups.entry(group_uid).or_default().roots.push((shard_uid, *chunk_extra.state_root()));
+        }
+        for item in store.iter(DBCol::TrieChanges) {
+            let (key, _) = item?;
+            let (block_hash, shard_uid) =
+                get_block_shard_uid_rev(&key).map_err(|err| anyhow!(err))?;
+            let group_uid = mapped_shard_uid(store, shard_uid)?;
+            groups.entry(group_uid).or_default().trie_changes.push((block_hash, shard_uid));
+        }
+
+        if let Some(selected) = &self.shard_uid {
+            groups.retain(|group_uid, group| {
+                selected.contains(group_uid)
+                    || group.roots.iter().any(|(shard_uid, _)| selected.contains(shard_uid))
+                    || group.trie_changes.iter().any(|(_, shard_uid)| selected.contains(shard_uid))
+            });
+        }
+        Ok(groups)
+    }
+
+    /// Compares the stored reference counts of the nodes of the groups with
+    /// the expected ones and fixes them if requested.
+    ///
+    /// `DBCol::State` is scanned once for all the groups, which have to be
+    /// ordered as their keys. The expected reference counts of a group are
+    /// computed when the scan reaches it, and `on_checked` gets its report
+    /// once the scan is past it.
+    fn check_refcounts(
+        &self,
+        store: &Store,
+        groups: &[(ShardUId, ShardGroup)],
+        mut on_checked: impl FnMut(ShardUId, Report) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()> {
+        let mut groups = groups.iter().peekable();
+        let mut current: Option<GroupCheck> = None;
+        let mut store_update = store.store_update();
+        let mut batch_len = 0;
+        // `iter_prefix` doesn't support the State column, and it would strip
+        // the reference counts anyway.
+        for item in store.iter_raw_bytes(DBCol::State) {
+            let (key, value) = item?;
+            let prefix = &key[..8];
+            if current.as_ref().is_some_and(|check| check.group_uid.to_bytes() != prefix) {
+                // The fixes are committed before the group is reported as
+                // checked.
+                std::mem::replace(&mut store_update, store.store_update()).commit()?;
+                batch_len = 0;
+                let check = current.take().unwrap();
+                on_checked(check.group_uid, check.finish())?;
+            }
+            if current.is_none() {
+                // Groups without any rows before this one.
+                while let Some((group_uid, group)) =
+                    groups.next_if(|(group_uid, _)| group_uid.to_bytes().as_slice() < prefix)
+                {
+                    on_checked(*group_uid, self.start_check(store, *group_uid, group)?.finish())?;
+                }
+                match groups.next_if(|(group_uid, _)| group_uid.to_bytes().as_slice() == prefix) {
+                    Some((group_uid, group)) => {
+                        current = Some(self.start_check(store, *group_uid, group)?)
+                    }
+                    None if groups.peek().is_none() => break,
+                    None => continue,
+                }
+            }
+            let GroupCheck { expected, report, .. } = current.as_mut().unwrap();
+
+            let hash = CryptoHash::try_from(&key[8..]).map_err(|err| anyhow!(err))?;
+            let expected_rc = expected.remove(&hash).unwrap_or(0);
+            let (data, rc) = decode_value_with_rc(&value);
+            let Some(data) = data else {
+                if rc < 0 {
+                    report.negative.add(hash, rc, expected_rc);
+                }
+                if expected_rc > 0 {
+                    report.missing.add(hash, rc, expected_rc);
+                }
+                continue;
+            };
+
+            let stored_rc = rc as u64;
+            if stored_rc == expected_rc {
+                continue;
+            }
+            if expected_rc == 0 {
+                report.leaked.add(hash, rc, expected_rc);
+            } else if stored_rc > expected_rc {
+                report.over_referenced.add(hash, rc, expected_rc);
+            } else {
+                report.under_referenced.add(hash, rc, expected_rc);
+            }
+            if !self.fix {
+                continue;
+            }
+            if stored_rc > expected_rc {
+                let decrease = refcount_delta(stored_rc - expected_rc)?;
+                store_update.decrement_refcount_by(DBCol::State, &key, decrease);
+            } else {
+                let increase = refcount_delta(expected_rc - stored_rc)?;
+                store_update.increment_refcount_by(DBCol::State, &key, data, increase);
+            }
+            report.fixed += 1;
+            batch_len += 1;
+            if batch_len >= self.batch_size {
+                std::mem::replace(&mut store_update, store.store_update()).commit()?;
+                batch_len = 0;
+            }
+        }
+        store_update.commit()?;
+
+        if let Some(check) = current {
+            on_checked(check.group_uid, check.finish())?;
+        }
+        // Groups without any rows after the last one.
+        for (group_uid, group) in groups {
+            on_checked(*group_uid, self.start_check(store, *group_uid, group)?.finish())?;
+        }
+        Ok(())
+    }
+
+    fn start_check(
+        &self,
+        store: &Store,
+        group_uid: ShardUId,
+        group: &ShardGroup,
+    ) -> anyhow::Result<GroupCheck> {
+        println!("Checking {group_uid}...");
+        let expected = expected_refcounts(store, group_uid, group)?;
+        Ok(GroupCheck { group_uid, expected, report: Report::new(self.max_reported) })
+    }
+}
+
+/// Check of the rows of a group in `DBCol::State` in progress.
+struct GroupCheck {
+    group_uid: ShardUId,
+    /// Expected reference counts of the nodes which weren't reached yet.
+    expected: HashMap<CryptoHash, u64>,
+    report: Report,
+}
+
+impl GroupCheck {
+    /// Reports the expected nodes which weren't found.
+    fn finish(self) -> Report {
+        let mut report = self.report;
+        for (hash, expected_rc) in self.expected {
+            report.missing.add(hash, 0, expected_rc);
+        }
+        report
+    }
+}
+
+/// Returns the shard whose keys in `DBCol::State` are used by given shard.
+fn mapped_shard_uid(store: &Store, shard_uid: ShardUId) -> anyhow::Result<ShardUId> {
+    Ok(store
+        .get_ser::<ShardUId>(DBCol::StateShardUIdMapping, &shard_uid.to_bytes())?
+        .unwrap_or(shard_uid))
+}
+
+/// Computes the expected reference counts of the nodes and values of the
+/// group, see [`TrieGcCheckCommand`].
+fn expected_refcounts(
+    store: &Store,
+    group_uid: ShardUId,
+    group: &ShardGroup,
+) -> anyhow::Result<HashMap<CryptoHash, u64>> {
+    let mut refcounts = HashMap::new();
+    let storage = TrieDBStorage::new(store.trie_store(), group_uid);
+    for (shard_uid, root) in &group.roots {
+        println!("Counting nodes of {shard_uid} under {root}");
+        count_trie_nodes(&storage, *root, &mut refcounts)?;
+    }
+    for (block_hash, shard_uid) in &group.trie_changes {
+        let trie_changes = store
+            .get_ser::<TrieChanges>(
+                DBCol::TrieChanges,
+                &get_block_shard_uid(block_hash, shard_uid),
+            )?
+            .with_context(|| format!("trie changes of {block_hash} {shard_uid} not found"))?;
+        // Garbage collection applies the deletions of the canonical blocks
+        // and reverts the insertions of the blocks on forks.
+        if is_canonical(store, block_hash)? {
+            for deletion in trie_changes.deletions() {
+                *refcounts.entry(*deletion.hash()).or_default() += u64::from(deletion.rc().get());
+            }
+        } else {
+            for insertion in trie_changes.insertions() {
+                *refcounts.entry(*insertion.hash()).or_default() += u64::from(insertion.rc().get());
+            }
+        }
+    }
+    Ok(refcounts)
+}
+
+/// Adds the occurrences of the nodes and values of the trie to `refcounts`.
+///
+/// Every node is counted once for each path under which it occurs, which is
+/// how the reference counts are maintained when the trie is updated. Nodes
+/// missing in the storage are counted but their subtrees are skipped.
+fn count_trie_nodes(
+    storage: &TrieDBStorage,
+    root: StateRoot,
+    refcounts: &mut HashMap<CryptoHash, u64>,
+) -> anyhow::Result<()> {
+    let mut stack = vec![root];
+    while let Some(hash) = stack.pop() {
+        if hash == Trie::EMPTY_ROOT {
+            continue;
+        }
+        *refcounts.entry(hash).or_default() += 1;
+        let bytes = match storage.retrieve_raw_bytes(&hash) {
+            Ok(bytes) => bytes,
+            Err(StorageError::MissingTrieValue(..)) => continue,
+            Err(err) => return Err(err.into()),
+        };
+        let (value, children) = match RawTrieNodeWithSize::try_from_slice(&bytes)?.node {
+            RawTrieNode::Leaf(_, value) => (Some(value), None),
+            RawTrieNode::BranchNoValue(children) => (None, Some(children)),
+            RawTrieNode::BranchWithValue(value, children) => (Some(value), Some(children)),
+            RawTrieNode::Extension(_, child) => {
+                stack.push(child);
+                (None, None)
+            }
+        };
+        if let Some(value) = value {
+            *refcounts.entry(value.hash).or_default() += 1;
+        }
+        if let Some(children) = children {
+            stack.extend(children.iter().map(|(_, child)| *child));
+        }
+    }
+    Ok(())
+}
+
+fn is_canonical(store: &Store, block_hash: &CryptoHash) -> anyhow::Result<bool> {
+    let header = store
+        .get_ser::<BlockHeader>(DBCol::BlockHeader, block_hash.as_ref())?
+        .with_context(|| format!("header of {block_hash} not found"))?;
+    let canonical_hash =
+        store.get_ser::<CryptoHash>(DBCol::BlockHeight, &index_to_bytes(header.height()))?;
+    Ok(canonical_hash.as_ref() == Some(block_hash))
+}
+
+fn refcount_delta(delta: u64) -> anyhow::Result<NonZeroU32> {
+    u32::try_from(delta)
+        .ok()
+        .and_then(NonZeroU32::new)
+        .ok_or_else(|| anyhow!("reference count difference {delta} out of range"))
+}
+
+/// Checked shards of an interrupted run.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Progress {
+    head: CryptoHash,
+    checked_shard_uids: Vec<String>,
+}
+
+impl Progress {
+    fn load(path: &Path, head: &CryptoHash) -> anyhow::Result<Self> {
+        let fresh = Self { head: *head, checked_shard_uids: vec![] };
+        if !path.exists() {
+            return Ok(fresh);
+        }
+        let progress: Self = serde_json::from_slice(&std::fs::read(path)?)
+            .with_context(|| format!("failed to parse {}", path.display()))?;
+        if &progress.head != head {
+            println!("Head moved since {} was written, starting from scratch", path.display());
+            return Ok(fresh);
+        }
+        Ok(progress)
+    }
+
+    fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let tmp_path = path.with_extension("tmp");
+        std::fs::write(&tmp_path, serde_json::to_vec_pretty(self)?)?;
+        std::fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+}
+
+/// Nodes with one kind of problem.
+struct Problems {
+    count: u64,
+    /// Hash, stored and expected reference count of the first nodes.
+    samples: Vec<(CryptoHash, i64, u64)>,
+    max_samples: usize,
+}
+
+impl Problems {
+    fn new(max_samples: usize) -> Self {
+        Self { count: 0, samples: vec![], max_samples }
+    }
+
+    fn add(&mut self, hash: CryptoHash, stored_rc: i64, expected_rc: u64) {
+        self.count += 1;
+        if self.samples.len() < self.max_samples {
+            self.samples.push((hash, stored_rc, expected_rc));
+        }
+    }
+
+    fn print(&self, name: &str) {
+        println!("  {name}: {}", self.count);
+        for (hash, stored_rc, expected_rc) in &self.samples {
+            println!("    {hash} stored {stored_rc} expected {expected_rc}");
+        }
+    }
+}
+
+struct Report {
+    /// Nodes which are referenced but not stored.
+    missing: Problems,
+    /// Nodes which are stored but not referenced.
+    leaked: Problems,
+    over_referenced: Problems,
+    under_referenced: Problems,
+    /// Rows with a negative reference count, which can't be fixed safely.
+    negative: Problems,
+    fixed: u64,
+}
+
+impl Report {
+    fn new(max_samples: usize) -> Self {
+        Self {
+            missing: Problems::new(max_samples),
+            leaked: Problems::new(max_samples),
+            over_referenced: Problems::new(max_samples),
+            under_referenced: Problems::new(max_samples),
+            negative: Problems::new(max_samples),
+            fixed: 0,
+        }
+    }
+
+    fn print(&self, group_uid: ShardUId, fix: bool) {
+        println!("Results for {group_uid}:");
+        self.missing.print("missing");
+        self.leaked.print("leaked");
+        self.over_referenced.print("over-referenced");
+        self.under_referenced.print("under-referenced");
+        self.negative.print("negative reference count");
+        if fix {
+            println!("  fixed: {}", self.fixed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ShardGroup, TrieGcCheckCommand};
+    use near_primitives::hash::CryptoHash;
+    use near_primitives::shard_layout::ShardUId;
+    use near_store::adapter::StoreAdapter;
+    use near_store::test_utils::{test_populate_trie, TestTriesBuilder};
+    use near_store::{DBCol, Trie};
+    use std::num::NonZeroU32;
+
+    fn state_key(shard_uid: ShardUId, hash: &CryptoHash) -> Vec<u8> {
+        [&shard_uid.to_bytes()[..], hash.as_ref()].concat()
+    }
+
+    /// Breaks the reference counts of a trie and checks that the problems are
+    /// reported and fixed.
+    #[test]
+    fn test_check_and_fix_refcounts() {
+        let tries = TestTriesBuilder::new().build();
+        let shard_uid = ShardUId::single_shard();
+        // Every value is shared by ten keys.
+        let changes = (0..50u8).map(|i| (vec![i, i + 1], Some(vec![i % 5]))).collect();
+        let root = test_populate_trie(&tries, &Trie::EMPTY_ROOT, shard_uid, changes);
+        let store = tries.store().store();
+        // A group without any rows is checked as well.
+        let empty_shard_uid = ShardUId { version: u32::MAX, shard_id: 0 };
+        let groups = [
+            (shard_uid, ShardGroup { roots: vec![(shard_uid, root)], trie_changes: vec![] }),
+            (empty_shard_uid, ShardGroup::default()),
+        ];
+        let mut cmd = TrieGcCheckCommand {
+            shard_uid: None,
+            fix: false,
+            batch_size: 2,
+            max_reported: 10,
+            progress_file: None,
+        };
+        let check = |cmd: &TrieGcCheckCommand| {
+            let mut reports = vec![];
+            cmd.check_refcounts(&store, &groups, |group_uid, report| {
+                reports.push((group_uid, report));
+                Ok(())
+            })
+            .unwrap();
+            let (group_uid, report) = reports.remove(0);
+            assert_eq!(group_uid, shard_uid);
+            assert_eq!(reports.len(), 1);
+            assert_eq!(reports[0].0, empty_shard_uid);
+            assert_eq!(reports[0].1.missing.count + reports[0].1.leaked.count, 0);
+            report
+        };
+
+        let report = check(&cmd);
+        assert_eq!(report.leaked.count + report.over_referenced.count, 0);
+        assert_eq!(report.under_referenced.count + report.missing.count, 0);
+
+        let value_hash = CryptoHash::hash_bytes(&[0]);
+        let leaked_hash = CryptoHash::hash_bytes(b"leaked");
+        let mut store_update = store.store_update();
+        store_update.increment_refcount_by(
+            DBCol::State,
+            &state_key(shard_uid, &root),
+            &store.trie_store().get(shard_uid, &root).unwrap(),
+            NonZeroU32::new(1).unwrap(),
+        );
+        store_update.decrement_refcount_by(
+            DBCol::State,
+            &state_key(shard_uid, &value_hash),
+            NonZeroU32::new(3).unwrap(),
+        );
+        store_update.increment_refcount_by(
+            DBCol::State,
+            &state_key(shard_uid, &leaked_hash),
+            b"leaked",
+            NonZeroU32::new(1).unwrap(),
+        );
+        store_update.commit().unwrap();
+
+        cmd.fix = true;
+        let report = check(&cmd);
+        assert_eq!(report.over_referenced.samples, vec![(root, 2, 1)]);
+        assert_eq!(report.under_referenced.samples, vec![(value_hash, 7, 10)]);
+        assert_eq!(report.leaked.samples, vec![(leaked_hash, 1, 0)]);
+        assert_eq!(report.fixed, 3);
+
+        cmd.fix = false;
+        let report = check(&cmd);
+        assert_eq!(report.leaked.count + report.over_referenced.count, 0);
+        assert_eq!(report.under_referenced.count + report.missing.count, 0);
+    }
+}
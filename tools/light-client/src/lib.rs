@@ -0,0 +1,577 @@
+//! A light client which follows the chain through the light client blocks
+//! returned by the `next_light_client_block` RPC and verifies the proofs
+//! returned by `light_client_proof`.
+//!
+//! The client keeps the header of the last block it verified, the head, and
+//! the block producers of the epoch of the head and of the next one. A new
+//! block is accepted if it's from one of these epochs and if block producers
+//! holding more than two thirds of the stake of its epoch approved it. When
+//! the block is from the next epoch, it has to include the block producers of
+//! the epoch after it, which have to match its `next_bp_hash`.
+//!
+//! See <https://nomicon.io/ChainSpec/LightClient> for the specification.
+
+use near_jsonrpc_primitives::types::light_client::{
+    RpcLightClientBlockProofResponse, RpcLightClientExecutionProofResponse,
+};
+use near_primitives::block_header::{Approval, ApprovalInner};
+use near_primitives::hash::CryptoHash;
+use near_primitives::merkle::{combine_hash, compute_root_from_path, MerklePath};
+use near_primitives::types::{AccountId, Balance, BlockHeight};
+use near_primitives::views::validator_stake_view::ValidatorStakeView;
+use near_primitives::views::{LightClientBlockLiteView, LightClientBlockView};
+use std::io;
+use std::path::Path;
+
+#[derive(thiserror::Error, Debug)]
+pub enum LightClientError {
+    #[error("block at height {height} is not after the head at height {head_height}")]
+    NotAfterHead { height: BlockHeight, head_height: BlockHeight },
+    #[error("block {block_hash} is from epoch {epoch_id}, which is neither the epoch of the head nor the next one")]
+    UnexpectedEpoch { block_hash: CryptoHash, epoch_id: CryptoHash },
+    #[error("block producers of epoch {0} are not known")]
+    UnknownBlockProducers(CryptoHash),
+    #[error("block {0} is the first one of the next epoch but doesn't include the block producers of the epoch after it")]
+    MissingNextBlockProducers(CryptoHash),
+    #[error("block {block_hash} has {approvals} approvals but its epoch has only {block_producers} block producers")]
+    TooManyApprovals { block_hash: CryptoHash, approvals: usize, block_producers: usize },
+    #[error("approval of {account_id} for block {block_hash} has an invalid signature")]
+    InvalidApprovalSignature { block_hash: CryptoHash, account_id: AccountId },
+    #[error("block {block_hash} is approved by {approved_stake} out of {total_stake} stake, more than two thirds are required")]
+    NotEnoughApprovals { block_hash: CryptoHash, approved_stake: Balance, total_stake: Balance },
+    #[error("next block producers of block {block_hash} hash to {computed} but its next_bp_hash is {expected}")]
+    InvalidNextBlockProducers { block_hash: CryptoHash, computed: CryptoHash, expected: CryptoHash },
+    #[error("block hash {header_hash} of the header doesn't match the block hash {block_hash} of the outcome")]
+    OutcomeBlockMismatch { header_hash: CryptoHash, block_hash: CryptoHash },
+    #[error("outcome proof of {id} leads to outcome root {computed} but block {block_hash} has {expected}")]
+    InvalidOutcomeProof {
+        id: CryptoHash,
+        block_hash: CryptoHash,
+        computed: CryptoHash,
+        expected: CryptoHash,
+    },
+    #[error("proof of block {block_hash} leads to block merkle root {computed} but the head has {expected}")]
+    InvalidBlockProof { block_hash: CryptoHash, computed: CryptoHash, expected: CryptoHash },
+}
+
+/// Block producers of one epoch.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct EpochBlockProducers {
+    pub epoch_id: CryptoHash,
+    pub block_producers: Vec<ValidatorStakeView>,
+}
+
+/// State of the light client, which is persisted between runs.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct LightClient {
+    head: LightClientBlockLiteView,
+    /// Block producers of the epoch of the head and, once known, of the next
+    /// epoch.
+    epochs: Vec<EpochBlockProducers>,
+}
+
+impl LightClient {
+    /// Creates a light client whose head is the trusted block `checkpoint`.
+    ///
+    /// The block has to include the block producers of the next epoch, which
+    /// the light client blocks returned by the RPC always do, and
+    /// `block_producers` have to be the ordered block producers of its epoch,
+    /// e.g. as returned by `EXPERIMENTAL_validators_ordered`. Both are checked
+    /// against the block, but the block itself is trusted.
+    pub fn from_checkpoint(
+        checkpoint: &LightClientBlockView,
+        block_producers: Vec<ValidatorStakeView>,
+    ) -> Result<Self, LightClientError> {
+        let epoch_id = checkpoint.inner_lite.epoch_id;
+        let block_hash = block_hash(checkpoint);
+        verify_approvals(checkpoint, &block_producers)?;
+        let next_block_producers = checkpoint
+            .next_bps
+            .clone()
+            .ok_or(LightClientError::MissingNextBlockProducers(block_hash))?;
+        verify_next_block_producers(block_hash, checkpoint, &next_block_producers)?;
+        Ok(Self {
+            head: lite_view(checkpoint),
+            epochs: vec![
+                EpochBlockProducers { epoch_id, block_producers },
+                EpochBlockProducers {
+                    epoch_id: checkpoint.inner_lite.next_epoch_id,
+                    block_producers: next_block_producers,
+                },
+            ],
+        })
+    }
+
+    /// Reads the light client saved with [`Self::save`].
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let data = std::fs::read(path)?;
+        serde_json::from_slice(&data).map_err(io::Error::from)
+    }
+
+    /// Saves the light client to `path`, replacing the previous state
+    /// atomically.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let tmp_path = path.with_extension("tmp");
+        std::fs::write(&tmp_path, serde_json::to_vec_pretty(self)?)?;
+        std::fs::rename(&tmp_path, path)
+    }
+
+    pub fn head(&self) -> &LightClientBlockLiteView {
+        &self.head
+    }
+
+    pub fn head_hash(&self) -> CryptoHash {
+        self.head.hash()
+    }
+
+    pub fn block_producers(&self, epoch_id: &CryptoHash) -> Option<&[ValidatorStakeView]> {
+        self.epochs
+            .iter()
+            .find(|epoch| &epoch.epoch_id == epoch_id)
+            .map(|epoch| epoch.block_producers.as_slice())
+    }
+
+    /// Verifies `block` and makes it the new head. The light client isn't
+    /// changed if the verification fails.
+    pub fn validate_and_update_head(
+        &mut self,
+        block: &LightClientBlockView,
+    ) -> Result<(), LightClientError> {
+        let block_hash = block_hash(block);
+        let height = block.inner_lite.height;
+        let epoch_id = block.inner_lite.epoch_id;
+        if height <= self.head.inner_lite.height {
+            return Err(LightClientError::NotAfterHead {
+                height,
+                head_height: self.head.inner_lite.height,
+            });
+        }
+        if epoch_id != self.head.inner_lite.epoch_id
+            && epoch_id != self.head.inner_lite.next_epoch_id
+        {
+            return Err(LightClientError::UnexpectedEpoch { block_hash, epoch_id });
+        }
+        let is_new_epoch = epoch_id == self.head.inner_lite.next_epoch_id;
+        if is_new_epoch && block.next_bps.is_none() {
+            return Err(LightClientError::MissingNextBlockProducers(block_hash));
+        }
+
+        let block_producers = self
+            .block_producers(&epoch_id)
+            .ok_or(LightClientError::UnknownBlockProducers(epoch_id))?;
+        verify_approvals(block, block_producers)?;
+        if let Some(next_block_producers) = &block.next_bps {
+            verify_next_block_producers(block_hash, block, next_block_producers)?;
+        }
+
+        self.head = lite_view(block);
+        if let Some(next_block_producers) = &block.next_bps {
+            let next_epoch_id = block.inner_lite.next_epoch_id;
+            self.epochs.retain(|epoch| epoch.epoch_id == epoch_id);
+            self.epochs.push(EpochBlockProducers {
+                epoch_id: next_epoch_id,
+                block_producers: next_block_producers.clone(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Verifies the execution outcome proof returned by `light_client_proof`
+    /// called with the hash of the head as `light_client_head`.
+    pub fn verify_execution_proof(
+        &self,
+        proof: &RpcLightClientExecutionProofResponse,
+    ) -> Result<(), LightClientError> {
+        verify_outcome_proof(proof)?;
+        self.verify_block_proof(&proof.outcome_proof.block_hash, &proof.block_proof)
+    }
+
+    /// Verifies the proof returned by `EXPERIMENTAL_light_client_block_proof`
+    /// called with the hash of the head as `light_client_head`.
+    pub fn verify_block_header_proof(
+        &self,
+        proof: &RpcLightClientBlockProofResponse,
+    ) -> Result<(), LightClientError> {
+        self.verify_block_proof(&proof.block_header_lite.hash(), &proof.block_proof)
+    }
+
+    /// Verifies that the block with `block_hash` is one of the ancestors of
+    /// the head, i.e. that `proof` leads to the block merkle root of the head.
+    pub fn verify_block_proof(
+        &self,
+        block_hash: &CryptoHash,
+        proof: &MerklePath,
+    ) -> Result<(), LightClientError> {
+        let computed = compute_root_from_path(proof, *block_hash);
+        let expected = self.head.inner_lite.block_merkle_root;
+        if computed != expected {
+            return Err(LightClientError::InvalidBlockProof {
+                block_hash: *block_hash,
+                computed,
+                expected,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Verifies that the execution outcome in `proof` is included in the outcome
+/// root of the block header in it, without checking that the block is known
+/// to the light client.
+pub fn verify_outcome_proof(
+    proof: &RpcLightClientExecutionProofResponse,
+) -> Result<(), LightClientError> {
+    let outcome_proof = &proof.outcome_proof;
+    let header_hash = proof.block_header_lite.hash();
+    if header_hash != outcome_proof.block_hash {
+        return Err(LightClientError::OutcomeBlockMismatch {
+            header_hash,
+            block_hash: outcome_proof.block_hash,
+        });
+    }
+    let outcome_hash = CryptoHash::hash_borsh(outcome_proof.to_hashes());
+    let shard_outcome_root = compute_root_from_path(&outcome_proof.proof, outcome_hash);
+    let computed = compute_root_from_path(
+        &proof.outcome_root_proof,
+        CryptoHash::hash_borsh(shard_outcome_root),
+    );
+    let expected = proof.block_header_lite.inner_lite.outcome_root;
+    if computed != expected {
+        return Err(LightClientError::InvalidOutcomeProof {
+            id: outcome_proof.id,
+            block_hash: header_hash,
+            computed,
+            expected,
+        });
+    }
+    Ok(())
+}
+
+/// Returns the hash of the block of the light client block.
+pub fn block_hash(block: &LightClientBlockView) -> CryptoHash {
+    lite_view(block).hash()
+}
+
+fn lite_view(block: &LightClientBlockView) -> LightClientBlockLiteView {
+    LightClientBlockLiteView {
+        prev_block_hash: block.prev_block_hash,
+        inner_rest_hash: block.inner_rest_hash,
+        inner_lite: block.inner_lite.clone(),
+    }
+}
+
+/// Checks that block producers with more than two thirds of the stake signed
+/// the approvals of the block two blocks after `block`, which endorse the
+/// block after it.
+fn verify_approvals(
+    block: &LightClientBlockView,
+    block_producers: &[ValidatorStakeView],
+) -> Result<(), LightClientError> {
+    let block_hash = block_hash(block);
+    if block.approvals_after_next.len() > block_producers.len() {
+        return Err(LightClientError::TooManyApprovals {
+            block_hash,
+            approvals: block.approvals_after_next.len(),
+            block_producers: block_producers.len(),
+        });
+    }
+    let next_block_hash = combine_hash(&block.next_block_inner_hash, &block_hash);
+    let approval_message = Approval::get_data_for_sig(
+        &ApprovalInner::Endorsement(next_block_hash),
+        block.inner_lite.height + 2,
+    );
+
+    let mut total_stake: Balance = 0;
+    let mut approved_stake: Balance = 0;
+    for (i, block_producer) in block_producers.iter().enumerate() {
+        let ValidatorStakeView::V1(block_producer) = block_producer;
+        total_stake += block_producer.stake;
+        let Some(Some(signature)) = block.approvals_after_next.get(i) else {
+            continue;
+        };
+        if !signature.verify(&approval_message, &block_producer.public_key) {
+            return Err(LightClientError::InvalidApprovalSignature {
+                block_hash,
+                account_id: block_producer.account_id.clone(),
+            });
+        }
+        approved_stake += block_producer.stake;
+    }
+    if approved_stake <= total_stake * 2 / 3 {
+        return Err(LightClientError::NotEnoughApprovals {
+            block_hash,
+            approved_stake,
+            total_stake,
+        });
+    }
+    Ok(())
+}
+
+fn verify_next_block_producers(
+    block_hash: CryptoHash,
+    block: &LightClientBlockView,
+    next_block_producers: &[ValidatorStakeView],
+) -> Result<(), LightClientError> {
+    let computed = CryptoHash::hash_borsh_iter(
+        next_block_producers.iter().map(|bp| bp.clone().into_validator_stake()),
+    );
+    let expected = block.inner_lite.next_bp_hash;
+    if computed != expected {
+        return Err(LightClientError::InvalidNextBlockProducers { block_hash, computed, expected });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_crypto::{InMemorySigner, KeyType};
+    use near_jsonrpc_primitives::types::light_client::RpcLightClientExecutionProofResponse;
+    use near_primitives::merkle::merklize;
+    use near_primitives::views::validator_stake_view::ValidatorStakeViewV1;
+    use near_primitives::views::{
+        BlockHeaderInnerLiteView, ExecutionOutcomeView, ExecutionOutcomeWithIdView,
+        ExecutionStatusView,
+    };
+
+    struct TestEpoch {
+        id: CryptoHash,
+        signers: Vec<InMemorySigner>,
+        stakes: Vec<Balance>,
+    }
+
+    impl TestEpoch {
+        fn new(name: &str, stakes: Vec<Balance>) -> Self {
+            let signers = (0..stakes.len())
+                .map(|i| {
+                    let account_id: AccountId = format!("bp{i}.{name}").parse().unwrap();
+                    InMemorySigner::from_seed(
+                        account_id.clone(),
+                        KeyType::ED25519,
+                        account_id.as_str(),
+                    )
+                })
+                .collect();
+            Self { id: CryptoHash::hash_bytes(name.as_bytes()), signers, stakes }
+        }
+
+        fn block_producers(&self) -> Vec<ValidatorStakeView> {
+            self.signers
+                .iter()
+                .zip(&self.stakes)
+                .map(|(signer, stake)| {
+                    ValidatorStakeView::V1(ValidatorStakeViewV1 {
+                        account_id: signer.account_id.clone(),
+                        public_key: signer.public_key(),
+                        stake: *stake,
+                    })
+                })
+                .collect()
+        }
+
+        fn bp_hash(&self) -> CryptoHash {
+            CryptoHash::hash_borsh_iter(
+                self.block_producers().into_iter().map(|bp| bp.into_validator_stake()),
+            )
+        }
+    }
+
+    /// Creates a light client block at `height` in `epoch` approved by the
+    /// block producers in `approvers`.
+    fn make_block(
+        height: BlockHeight,
+        epoch: &TestEpoch,
+        next_epoch: &TestEpoch,
+        with_next_bps: bool,
+        approvers: &[usize],
+    ) -> LightClientBlockView {
+        let mut block = LightClientBlockView {
+            prev_block_hash: CryptoHash::hash_borsh(height - 1),
+            next_block_inner_hash: CryptoHash::hash_borsh(height + 1),
+            inner_lite: BlockHeaderInnerLiteView {
+                height,
+                epoch_id: epoch.id,
+                next_epoch_id: next_epoch.id,
+                prev_state_root: CryptoHash::default(),
+                outcome_root: CryptoHash::default(),
+                timestamp: height,
+                timestamp_nanosec: height,
+                next_bp_hash: next_epoch.bp_hash(),
+                block_merkle_root: CryptoHash::default(),
+            },
+            inner_rest_hash: CryptoHash::hash_borsh(height),
+            next_bps: with_next_bps.then(|| next_epoch.block_producers()),
+            approvals_after_next: vec![],
+        };
+        let next_block_hash = combine_hash(&block.next_block_inner_hash, &block_hash(&block));
+        let message =
+            Approval::get_data_for_sig(&ApprovalInner::Endorsement(next_block_hash), height + 2);
+        block.approvals_after_next = (0..epoch.signers.len())
+            .map(|i| approvers.contains(&i).then(|| Box::new(epoch.signers[i].sign(&message))))
+            .collect();
+        block
+    }
+
+    fn new_client(epochs: &[TestEpoch]) -> LightClient {
+        let checkpoint = make_block(10, &epochs[0], &epochs[1], true, &[0, 1, 2]);
+        LightClient::from_checkpoint(&checkpoint, epochs[0].block_producers()).unwrap()
+    }
+
+    fn test_epochs() -> Vec<TestEpoch> {
+        vec![
+            TestEpoch::new("epoch0", vec![10, 10, 10, 10]),
+            TestEpoch::new("epoch1", vec![30, 10, 10, 10]),
+            TestEpoch::new("epoch2", vec![10, 10]),
+        ]
+    }
+
+    #[test]
+    fn test_follow_epochs() {
+        let epochs = test_epochs();
+        let mut client = new_client(&epochs);
+
+        // A block in the same epoch doesn't need the next block producers.
+        let block = make_block(12, &epochs[0], &epochs[1], false, &[1, 2, 3]);
+        client.validate_and_update_head(&block).unwrap();
+        assert_eq!(client.head_hash(), block_hash(&block));
+
+        // The first block of the next epoch switches to its block producers
+        // and records the ones of the epoch after it.
+        let block = make_block(20, &epochs[1], &epochs[2], true, &[0, 1, 2]);
+        client.validate_and_update_head(&block).unwrap();
+        assert_eq!(client.head().inner_lite.height, 20);
+        assert!(client.block_producers(&epochs[0].id).is_none());
+        assert_eq!(client.block_producers(&epochs[2].id).unwrap(), epochs[2].block_producers());
+
+        let block = make_block(30, &epochs[2], &epochs[0], true, &[0, 1]);
+        client.validate_and_update_head(&block).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_blocks() {
+        let epochs = test_epochs();
+        let mut client = new_client(&epochs);
+        let head_hash = client.head_hash();
+
+        let block = make_block(10, &epochs[0], &epochs[1], false, &[0, 1, 2]);
+        let err = client.validate_and_update_head(&block).unwrap_err();
+        assert!(matches!(err, LightClientError::NotAfterHead { .. }), "{err}");
+
+        let block = make_block(30, &epochs[2], &epochs[0], true, &[0, 1]);
+        let err = client.validate_and_update_head(&block).unwrap_err();
+        assert!(matches!(err, LightClientError::UnexpectedEpoch { .. }), "{err}");
+
+        let block = make_block(20, &epochs[1], &epochs[2], false, &[0, 1, 2]);
+        let err = client.validate_and_update_head(&block).unwrap_err();
+        assert!(matches!(err, LightClientError::MissingNextBlockProducers(_)), "{err}");
+
+        // Exactly two thirds of the stake is not enough.
+        let block = make_block(20, &epochs[1], &epochs[2], true, &[0, 1]);
+        let err = client.validate_and_update_head(&block).unwrap_err();
+        assert!(matches!(err, LightClientError::NotEnoughApprovals { .. }), "{err}");
+
+        let mut block = make_block(20, &epochs[1], &epochs[2], true, &[0, 1, 2]);
+        block.approvals_after_next.swap(0, 1);
+        let err = client.validate_and_update_head(&block).unwrap_err();
+        assert!(matches!(err, LightClientError::InvalidApprovalSignature { .. }), "{err}");
+
+        let mut block = make_block(20, &epochs[1], &epochs[2], true, &[0, 1, 2]);
+        block.approvals_after_next.push(None);
+        let err = client.validate_and_update_head(&block).unwrap_err();
+        assert!(matches!(err, LightClientError::TooManyApprovals { .. }), "{err}");
+
+        // The approvals sign the block hash, so changing the block after
+        // signing invalidates them.
+        let mut block = make_block(20, &epochs[1], &epochs[2], true, &[0, 1, 2]);
+        block.inner_lite.outcome_root = CryptoHash::hash_bytes(b"outcome");
+        let err = client.validate_and_update_head(&block).unwrap_err();
+        assert!(matches!(err, LightClientError::InvalidApprovalSignature { .. }), "{err}");
+
+        let mut block = make_block(20, &epochs[1], &epochs[2], true, &[0, 1, 2]);
+        block.next_bps = Some(epochs[0].block_producers());
+        let err = client.validate_and_update_head(&block).unwrap_err();
+        assert!(matches!(err, LightClientError::InvalidNextBlockProducers { .. }), "{err}");
+
+        assert_eq!(client.head_hash(), head_hash);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let epochs = test_epochs();
+        let client = new_client(&epochs);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("light_client.json");
+        client.save(&path).unwrap();
+        let mut loaded = LightClient::load(&path).unwrap();
+        assert_eq!(loaded.head_hash(), client.head_hash());
+        let block = make_block(20, &epochs[1], &epochs[2], true, &[0, 1, 2]);
+        loaded.validate_and_update_head(&block).unwrap();
+    }
+
+    #[test]
+    fn test_verify_execution_proof() {
+        let epochs = test_epochs();
+        let mut client = new_client(&epochs);
+
+        let outcomes: Vec<ExecutionOutcomeWithIdView> = (0..3)
+            .map(|i| ExecutionOutcomeWithIdView {
+                proof: vec![],
+                block_hash: CryptoHash::default(),
+                id: CryptoHash::hash_borsh(i),
+                outcome: ExecutionOutcomeView {
+                    logs: vec![format!("log {i}")],
+                    receipt_ids: vec![],
+                    gas_burnt: 0,
+                    tokens_burnt: 0,
+                    executor_id: "test".parse().unwrap(),
+                    status: ExecutionStatusView::SuccessValue(vec![]),
+                    metadata: Default::default(),
+                },
+            })
+            .collect();
+        let outcome_hashes: Vec<_> = outcomes.iter().map(|outcome| outcome.to_hashes()).collect();
+        let (shard_outcome_root, outcome_paths) = merklize(&outcome_hashes);
+        let (outcome_root, outcome_root_paths) =
+            merklize(&[shard_outcome_root, CryptoHash::default()]);
+        let block_header_lite = LightClientBlockLiteView {
+            prev_block_hash: CryptoHash::hash_bytes(b"prev"),
+            inner_rest_hash: CryptoHash::hash_bytes(b"rest"),
+            inner_lite: BlockHeaderInnerLiteView {
+                outcome_root,
+                ..client.head().inner_lite.clone()
+            },
+        };
+        let block_hash = block_header_lite.hash();
+        // Block hashes are the leaves of the block merkle tree as they are,
+        // so only the path from `merklize` is used.
+        let (_, block_paths) =
+            merklize(&[CryptoHash::default(), block_hash, CryptoHash::default()]);
+        let block_proof = block_paths[1].clone();
+        client.head.inner_lite.block_merkle_root = compute_root_from_path(&block_proof, block_hash);
+
+        let mut proof = RpcLightClientExecutionProofResponse {
+            outcome_proof: ExecutionOutcomeWithIdView {
+                proof: outcome_paths[1].clone(),
+                block_hash,
+                ..outcomes[1].clone()
+            },
+            outcome_root_proof: outcome_root_paths[0].clone(),
+            block_header_lite,
+            block_proof,
+        };
+        client.verify_execution_proof(&proof).unwrap();
+
+        proof.outcome_proof.outcome.logs.push("forged".to_string());
+        let err = client.verify_execution_proof(&proof).unwrap_err();
+        assert!(matches!(err, LightClientError::InvalidOutcomeProof { .. }), "{err}");
+        proof.outcome_proof.outcome.logs.pop();
+
+        proof.block_proof.pop();
+        let err = client.verify_execution_proof(&proof).unwrap_err();
+        assert!(matches!(err, LightClientError::InvalidBlockProof { .. }), "{err}");
+
+        proof.outcome_proof.block_hash = CryptoHash::default();
+        let err = client.verify_execution_proof(&proof).unwrap_err();
+        assert!(matches!(err, LightClientError::OutcomeBlockMismatch { .. }), "{err}");
+    }
+}
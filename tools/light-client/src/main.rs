@@ -0,0 +1,211 @@
+use anyhow::Context;
+use clap::Parser;
+use near_jsonrpc_client::JsonRpcClient;
+use near_jsonrpc_primitives::types::light_client::{
+    RpcLightClientExecutionProofRequest, RpcLightClientNextBlockRequest,
+};
+use near_jsonrpc_primitives::types::validator::RpcValidatorsOrderedRequest;
+use near_light_client::LightClient;
+use near_primitives::hash::CryptoHash;
+use near_primitives::types::{AccountId, BlockId, TransactionOrReceiptId};
+use near_primitives::views::LightClientBlockView;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Follows a node's RPC as a light client, verifying every light client block
+/// and execution proof it returns.
+#[derive(Parser)]
+struct Cli {
+    /// Address of the node's RPC.
+    #[clap(long, default_value = "http://localhost:3030")]
+    rpc_url: String,
+    /// File in which the state of the light client is kept.
+    #[clap(long, default_value = "light_client.json")]
+    state_file: PathBuf,
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(clap::Subcommand)]
+enum Command {
+    /// Initializes the light client with a checkpoint, which is the light
+    /// client block following the given block. The checkpoint and the block
+    /// producers of its epoch are taken from the node without verification,
+    /// so the node has to be trusted at this point.
+    Init {
+        /// Hash of a final block known to be on the canonical chain.
+        #[clap(long)]
+        block_hash: CryptoHash,
+    },
+    /// Follows the node, verifying the light client blocks it returns and
+    /// saving every new head. Blocks which fail verification are reported and
+    /// aren't applied.
+    Follow {
+        /// How long to wait for the next light client block.
+        #[clap(long, default_value = "10")]
+        poll_interval_secs: u64,
+    },
+    /// Verifies that the outcome of a transaction or a receipt is included in
+    /// a block which is an ancestor of the head of the light client.
+    VerifyOutcome {
+        /// Hash of the transaction, requires --sender-id.
+        #[clap(long, conflicts_with = "receipt_id", requires = "sender_id")]
+        transaction_hash: Option<CryptoHash>,
+        #[clap(long)]
+        sender_id: Option<AccountId>,
+        /// ID of the receipt, requires --receiver-id.
+        #[clap(long, requires = "receiver_id")]
+        receipt_id: Option<CryptoHash>,
+        #[clap(long)]
+        receiver_id: Option<AccountId>,
+    },
+}
+
+fn main() -> anyhow::Result<()> {
+    let env_filter = near_o11y::EnvFilterBuilder::from_env().verbose(Some("")).finish().unwrap();
+    let _subscriber = near_o11y::default_subscriber(env_filter, &Default::default()).global();
+
+    let cli = Cli::parse();
+    actix::System::new().block_on(async move {
+        let client = near_jsonrpc_client::new_client(&cli.rpc_url);
+        match cli.command {
+            Command::Init { block_hash } => init(&client, &cli.state_file, block_hash).await,
+            Command::Follow { poll_interval_secs } => {
+                follow(&client, &cli.state_file, Duration::from_secs(poll_interval_secs)).await
+            }
+            Command::VerifyOutcome { transaction_hash, sender_id, receipt_id, receiver_id } => {
+                let id = match (transaction_hash, sender_id, receipt_id, receiver_id) {
+                    (Some(transaction_hash), Some(sender_id), None, _) => {
+                        TransactionOrReceiptId::Transaction { transaction_hash, sender_id }
+                    }
+                    (None, _, Some(receipt_id), Some(receiver_id)) => {
+                        TransactionOrReceiptId::Receipt { receipt_id, receiver_id }
+                    }
+                    _ => anyhow::bail!("either --transaction-hash or --receipt-id is required"),
+                };
+                verify_outcome(&client, &cli.state_file, id).await
+            }
+        }
+    })
+}
+
+async fn next_light_client_block(
+    client: &JsonRpcClient,
+    last_block_hash: CryptoHash,
+) -> anyhow::Result<Option<LightClientBlockView>> {
+    let response = client
+        .next_light_client_block(RpcLightClientNextBlockRequest { last_block_hash })
+        .await
+        .map_err(|err| anyhow::anyhow!("next_light_client_block failed: {err}"))?;
+    Ok(response.light_client_block.map(|block| block.as_ref().clone()))
+}
+
+async fn init(
+    client: &JsonRpcClient,
+    state_file: &Path,
+    block_hash: CryptoHash,
+) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        !state_file.exists(),
+        "{} already exists, delete it to initialize the light client again",
+        state_file.display()
+    );
+    let checkpoint = next_light_client_block(client, block_hash)
+        .await?
+        .context("the node has no light client block after the given block yet")?;
+    let checkpoint_hash = near_light_client::block_hash(&checkpoint);
+    let block_producers = client
+        .EXPERIMENTAL_validators_ordered(RpcValidatorsOrderedRequest {
+            block_id: Some(BlockId::Hash(checkpoint_hash)),
+        })
+        .await
+        .map_err(|err| anyhow::anyhow!("EXPERIMENTAL_validators_ordered failed: {err}"))?;
+    let light_client = LightClient::from_checkpoint(&checkpoint, block_producers)
+        .context("the checkpoint is invalid")?;
+    light_client
+        .save(state_file)
+        .with_context(|| format!("failed to write {}", state_file.display()))?;
+    println!(
+        "Initialized the light client at block {} at height {}",
+        checkpoint_hash, checkpoint.inner_lite.height
+    );
+    Ok(())
+}
+
+fn load(state_file: &Path) -> anyhow::Result<LightClient> {
+    LightClient::load(state_file).with_context(|| {
+        format!("failed to read {}, initialize the light client with `init`", state_file.display())
+    })
+}
+
+async fn follow(
+    client: &JsonRpcClient,
+    state_file: &Path,
+    poll_interval: Duration,
+) -> anyhow::Result<()> {
+    let mut light_client = load(state_file)?;
+    tracing::info!(
+        target: "light_client",
+        head = %light_client.head_hash(),
+        height = light_client.head().inner_lite.height,
+        "Following the chain"
+    );
+    loop {
+        let block = match next_light_client_block(client, light_client.head_hash()).await {
+            Ok(Some(block)) => block,
+            Ok(None) => {
+                actix::clock::sleep(poll_interval).await;
+                continue;
+            }
+            Err(err) => {
+                tracing::warn!(target: "light_client", ?err, "Failed to fetch the next block");
+                actix::clock::sleep(poll_interval).await;
+                continue;
+            }
+        };
+        let height = block.inner_lite.height;
+        if let Err(err) = light_client.validate_and_update_head(&block) {
+            tracing::error!(
+                target: "light_client",
+                height,
+                %err,
+                "Verification of the light client block failed"
+            );
+            actix::clock::sleep(poll_interval).await;
+            continue;
+        }
+        light_client
+            .save(state_file)
+            .with_context(|| format!("failed to write {}", state_file.display()))?;
+        tracing::info!(
+            target: "light_client",
+            head = %light_client.head_hash(),
+            height,
+            epoch_id = %light_client.head().inner_lite.epoch_id,
+            "Verified block"
+        );
+    }
+}
+
+async fn verify_outcome(
+    client: &JsonRpcClient,
+    state_file: &Path,
+    id: TransactionOrReceiptId,
+) -> anyhow::Result<()> {
+    let light_client = load(state_file)?;
+    let light_client_head = light_client.head_hash();
+    let proof = client
+        .light_client_proof(RpcLightClientExecutionProofRequest { id, light_client_head })
+        .await
+        .map_err(|err| anyhow::anyhow!("light_client_proof failed: {err}"))?;
+    light_client.verify_execution_proof(&proof).context("verification of the proof failed")?;
+    println!(
+        "Outcome of {} in block {} at height {} is verified against the head {}",
+        proof.outcome_proof.id,
+        proof.outcome_proof.block_hash,
+        proof.block_header_lite.inner_lite.height,
+        light_client_head
+    );
+    println!("{}", serde_json::to_string_pretty(&proof.outcome_proof.outcome)?);
+    Ok(())
+}
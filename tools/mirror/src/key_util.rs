@@ -94,7 +94,10 @@ pub(crate) fn keys_from_source_db(
             header.prev_hash(),
             header.hash(),
             header.epoch_id(),
-            &QueryRequest::ViewAccessKeyList { account_id: account_id.clone() },
+            &QueryRequest::ViewAccessKeyList {
+                account_id: account_id.clone(),
+                include_proof: false,
+            },
         )
         .with_context(|| format!("failed fetching access keys for {}", &account_id))?
         .kind
@@ -128,7 +131,10 @@ pub(crate) async fn keys_from_rpc(
     };
     let request = RpcQueryRequest {
         block_reference,
-        request: QueryRequest::ViewAccessKeyList { account_id: account_id.clone() },
+        request: QueryRequest::ViewAccessKeyList {
+            account_id: account_id.clone(),
+            include_proof: false,
+        },
     };
 
     let response = match rpc_client.query(request).await {
@@ -775,7 +775,7 @@ async fn account_exists(
         .send(
             Query::new(
                 BlockReference::Finality(Finality::None),
-                QueryRequest::ViewAccount { account_id: account_id.clone() },
+                QueryRequest::ViewAccount { account_id: account_id.clone(), include_proof: false },
             )
             .with_span_context(),
         )
@@ -806,6 +806,7 @@ async fn fetch_access_key_nonce(
                 QueryRequest::ViewAccessKey {
                     account_id: account_id.clone(),
                     public_key: public_key.clone(),
+                    include_proof: false,
                 },
             )
             .with_span_context(),
@@ -198,7 +198,10 @@ impl crate::ChainAccess for ChainAccess {
                 header.prev_hash(),
                 header.hash(),
                 header.epoch_id(),
-                &QueryRequest::ViewAccessKeyList { account_id: account_id.clone() },
+                &QueryRequest::ViewAccessKeyList {
+                    account_id: account_id.clone(),
+                    include_proof: false,
+                },
             )?
             .kind
         {
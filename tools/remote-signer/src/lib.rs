@@ -0,0 +1,340 @@
+//! Reference implementation of the signer process used by
+//! [`RemoteValidatorSigner`](near_primitives::validator_signer::remote::RemoteValidatorSigner).
+//!
+//! The signer holds the validator key from a key file, accepts connections
+//! only from nodes whose identity keys are allowed and appends every payload
+//! it's asked to sign to an audit log before signing it.
+
+use near_crypto::{InMemorySigner, PublicKey, Signer};
+use near_primitives::hash::hash;
+use near_primitives::validator_signer::remote::{
+    handshake_hash, random_nonce, read_message, session_id, write_message, ClientAuth, ClientHello,
+    Envelope, RemoteSignerAddr, RemoteSignerStream, ServerHello, Side, SignerRequest,
+    SignerResponse, PROTOCOL_VERSION,
+};
+use std::collections::HashSet;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Time in which a node has to complete the handshake.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Time in which a response has to be written to the node.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub struct SignerServer {
+    validator_signer: InMemorySigner,
+    identity_signer: Signer,
+    allowed_clients: HashSet<PublicKey>,
+    audit_log: Mutex<Box<dyn Write + Send>>,
+}
+
+/// Socket on which the signer accepts connections.
+pub enum SignerListener {
+    Tcp(std::net::TcpListener),
+    #[cfg(unix)]
+    Unix(std::os::unix::net::UnixListener),
+}
+
+impl SignerListener {
+    /// Binds to `addr`. A unix socket left behind by a previous run is
+    /// replaced and is made accessible only to the current user.
+    pub fn bind(addr: &RemoteSignerAddr) -> io::Result<Self> {
+        match addr {
+            RemoteSignerAddr::Tcp(addr) => Ok(Self::Tcp(std::net::TcpListener::bind(addr)?)),
+            #[cfg(unix)]
+            RemoteSignerAddr::Unix(path) => {
+                use std::os::unix::fs::PermissionsExt;
+
+                match std::fs::remove_file(path) {
+                    Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+                    _ => {}
+                }
+                let listener = std::os::unix::net::UnixListener::bind(path)?;
+                std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+                Ok(Self::Unix(listener))
+            }
+            #[cfg(not(unix))]
+            RemoteSignerAddr::Unix(_) => {
+                Err(io::Error::new(io::ErrorKind::Unsupported, "unix sockets unsupported"))
+            }
+        }
+    }
+
+    /// Address to connect to, useful when binding to port 0.
+    pub fn local_addr(&self) -> io::Result<RemoteSignerAddr> {
+        match self {
+            Self::Tcp(listener) => Ok(RemoteSignerAddr::Tcp(listener.local_addr()?.to_string())),
+            #[cfg(unix)]
+            Self::Unix(listener) => {
+                let addr = listener.local_addr()?;
+                let path = addr.as_pathname().ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidInput, "unnamed unix socket")
+                })?;
+                Ok(RemoteSignerAddr::Unix(path.to_path_buf()))
+            }
+        }
+    }
+
+    fn accept(&self) -> io::Result<RemoteSignerStream> {
+        match self {
+            Self::Tcp(listener) => {
+                let (stream, _) = listener.accept()?;
+                stream.set_nodelay(true)?;
+                Ok(RemoteSignerStream::Tcp(stream))
+            }
+            #[cfg(unix)]
+            Self::Unix(listener) => Ok(RemoteSignerStream::Unix(listener.accept()?.0)),
+        }
+    }
+}
+
+impl SignerServer {
+    /// Creates the signer of `validator_signer` which identifies itself to the
+    /// nodes with `identity_signer` and serves only `allowed_clients`.
+    pub fn new(
+        validator_signer: InMemorySigner,
+        identity_signer: Signer,
+        allowed_clients: impl IntoIterator<Item = PublicKey>,
+        audit_log: impl Write + Send + 'static,
+    ) -> Self {
+        Self {
+            validator_signer,
+            identity_signer,
+            allowed_clients: allowed_clients.into_iter().collect(),
+            audit_log: Mutex::new(Box::new(audit_log)),
+        }
+    }
+
+    /// Serves the connections accepted on `listener`, each on its own thread.
+    pub fn serve(self: Arc<Self>, listener: SignerListener) -> io::Result<()> {
+        loop {
+            let stream = match listener.accept() {
+                Ok(stream) => stream,
+                Err(err) => {
+                    tracing::warn!(target: "remote_signer", ?err, "Failed to accept a connection");
+                    continue;
+                }
+            };
+            let server = self.clone();
+            std::thread::spawn(move || {
+                if let Err(err) = server.handle_connection(stream) {
+                    tracing::info!(target: "remote_signer", ?err, "Connection closed");
+                }
+            });
+        }
+    }
+
+    fn handle_connection(&self, mut stream: RemoteSignerStream) -> io::Result<()> {
+        stream.set_timeouts(HANDSHAKE_TIMEOUT)?;
+        let hello: ClientHello = read_message(&mut stream)?;
+        if hello.version != PROTOCOL_VERSION {
+            return Err(io::Error::other(format!(
+                "node uses protocol version {}, expected {PROTOCOL_VERSION}",
+                hello.version
+            )));
+        }
+        if !self.allowed_clients.contains(&hello.client_key) {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("node {} is not allowed", hello.client_key),
+            ));
+        }
+        let server_nonce = random_nonce();
+        let server_hash = handshake_hash(Side::Server, &hello.client_nonce, &server_nonce);
+        write_message(
+            &mut stream,
+            &ServerHello {
+                version: PROTOCOL_VERSION,
+                account_id: self.validator_signer.account_id.clone(),
+                validator_key: self.validator_signer.public_key(),
+                server_nonce,
+                signature: self.identity_signer.sign(server_hash.as_ref()),
+            },
+        )?;
+        let auth: ClientAuth = read_message(&mut stream)?;
+        let client_hash = handshake_hash(Side::Client, &hello.client_nonce, &server_nonce);
+        if !auth.signature.verify(client_hash.as_ref(), &hello.client_key) {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("node {} failed to prove its identity", hello.client_key),
+            ));
+        }
+        let client_key = hello.client_key;
+        let session_id = session_id(&hello.client_nonce, &server_nonce);
+        tracing::info!(target: "remote_signer", %client_key, "Node connected");
+
+        // The node keeps the connection open between the requests.
+        match &stream {
+            RemoteSignerStream::Tcp(stream) => {
+                stream.set_read_timeout(None)?;
+                stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+            }
+            #[cfg(unix)]
+            RemoteSignerStream::Unix(stream) => {
+                stream.set_read_timeout(None)?;
+                stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+            }
+        }
+        let mut seq = 0;
+        loop {
+            let request: Envelope<SignerRequest> = read_message(&mut stream)?;
+            if !request.verify(Side::Client, &session_id, seq, &client_key) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid signature of the request {seq} of node {client_key}"),
+                ));
+            }
+            let response = self.handle_request(&client_key, request.message);
+            write_message(
+                &mut stream,
+                &Envelope::sign(Side::Server, &session_id, seq, response, &self.identity_signer),
+            )?;
+            seq += 1;
+        }
+    }
+
+    /// Signs the request after recording it in the audit log. Nothing is
+    /// signed if it can't be recorded.
+    fn handle_request(&self, client_key: &PublicKey, request: SignerRequest) -> SignerResponse {
+        let (kind, payload) = match &request {
+            SignerRequest::SignBytes(data) => ("sign_bytes", data),
+            SignerRequest::ComputeVrfWithProof(data) => ("compute_vrf_with_proof", data),
+        };
+        let entry = serde_json::json!({
+            "time": chrono::Utc::now().to_rfc3339(),
+            "client": client_key.to_string(),
+            "request": kind,
+            "payload_hash": hash(payload).to_string(),
+            "payload": hex::encode(payload),
+        });
+        if let Err(err) = self.write_audit_log(&entry) {
+            tracing::error!(target: "remote_signer", ?err, "Failed to write to the audit log, refusing to sign");
+            return SignerResponse::Error(format!("failed to write to the audit log: {err}"));
+        }
+        match request {
+            SignerRequest::SignBytes(data) => {
+                SignerResponse::Signature(self.validator_signer.sign(&data))
+            }
+            SignerRequest::ComputeVrfWithProof(data) => {
+                let (value, proof) = self.validator_signer.compute_vrf_with_proof(&data);
+                SignerResponse::VrfWithProof(value, proof)
+            }
+        }
+    }
+
+    fn write_audit_log(&self, entry: &serde_json::Value) -> io::Result<()> {
+        let mut audit_log = self.audit_log.lock().unwrap();
+        serde_json::to_writer(&mut *audit_log, entry)?;
+        audit_log.write_all(b"\n")?;
+        audit_log.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_crypto::KeyType;
+    use near_primitives::validator_signer::remote::RemoteValidatorSigner;
+    use near_primitives::validator_signer::ValidatorSigner;
+
+    /// Audit log shared with the test.
+    #[derive(Clone, Default)]
+    struct SharedLog(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedLog {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn start_server(
+        validator_signer: InMemorySigner,
+        identity_signer: Signer,
+        allowed_client: PublicKey,
+        audit_log: SharedLog,
+    ) -> RemoteSignerAddr {
+        let listener = SignerListener::bind(&RemoteSignerAddr::Tcp("127.0.0.1:0".into())).unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = Arc::new(SignerServer::new(
+            validator_signer,
+            identity_signer,
+            [allowed_client],
+            audit_log,
+        ));
+        std::thread::spawn(move || server.serve(listener));
+        addr
+    }
+
+    fn remote_signer(
+        validator_signer: &InMemorySigner,
+        addr: RemoteSignerAddr,
+        identity_key: PublicKey,
+        client_signer: Signer,
+    ) -> ValidatorSigner {
+        RemoteValidatorSigner::new(
+            validator_signer.account_id.clone(),
+            validator_signer.public_key(),
+            addr,
+            identity_key,
+            client_signer,
+            Duration::from_secs(5),
+            Duration::from_secs(5),
+        )
+        .into()
+    }
+
+    #[test]
+    fn test_remote_signer() {
+        let validator_signer =
+            InMemorySigner::from_seed("test".parse().unwrap(), KeyType::ED25519, "validator");
+        let identity_signer: Signer =
+            InMemorySigner::from_seed("signer".parse().unwrap(), KeyType::ED25519, "signer").into();
+        let client_signer: Signer =
+            InMemorySigner::from_seed("node".parse().unwrap(), KeyType::ED25519, "node").into();
+        let identity_key = identity_signer.public_key();
+        let audit_log = SharedLog::default();
+        let addr = start_server(
+            validator_signer.clone(),
+            identity_signer,
+            client_signer.public_key(),
+            audit_log.clone(),
+        );
+
+        let signer =
+            remote_signer(&validator_signer, addr.clone(), identity_key.clone(), client_signer);
+        for data in [b"block".as_slice(), b"chunk"] {
+            let signature = signer.sign_bytes(data).unwrap();
+            assert!(signature.verify(data, &validator_signer.public_key()));
+        }
+        assert_eq!(
+            signer.compute_vrf_with_proof(b"random").unwrap(),
+            validator_signer.compute_vrf_with_proof(b"random")
+        );
+        let audit_log = String::from_utf8(audit_log.0.lock().unwrap().clone()).unwrap();
+        let entries: Vec<serde_json::Value> =
+            audit_log.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0]["request"], "sign_bytes");
+        assert_eq!(entries[0]["payload"], hex::encode(b"block"));
+        assert_eq!(entries[2]["request"], "compute_vrf_with_proof");
+
+        // A node with an unknown identity key isn't served.
+        let unknown_signer: Signer =
+            InMemorySigner::from_seed("other".parse().unwrap(), KeyType::ED25519, "other").into();
+        let signer = remote_signer(&validator_signer, addr.clone(), identity_key, unknown_signer);
+        assert!(signer.sign_bytes(b"block").is_err());
+
+        // Neither is one expecting a different identity of the signer.
+        let client_signer: Signer =
+            InMemorySigner::from_seed("node".parse().unwrap(), KeyType::ED25519, "node").into();
+        let signer =
+            remote_signer(&validator_signer, addr, client_signer.public_key(), client_signer);
+        assert!(signer.sign_bytes(b"block").is_err());
+    }
+}
@@ -0,0 +1,75 @@
+use anyhow::Context;
+use clap::Parser;
+use near_crypto::{EncryptedKeyFile, InMemorySigner, KeyFile, PublicKey};
+use near_primitives::validator_signer::remote::RemoteSignerAddr;
+use near_remote_signer::{SignerListener, SignerServer};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Signs blocks, chunks and approvals for a validator node configured with
+/// `remote_signer` in its config.json.
+#[derive(Parser)]
+struct Cli {
+    /// Key file of the validator key, may be encrypted.
+    #[clap(long)]
+    validator_key: PathBuf,
+    /// File with the password of an encrypted validator key. The password is
+    /// prompted for if it's not given.
+    #[clap(long)]
+    password_file: Option<PathBuf>,
+    /// Key file of the key with which the signer proves its identity to the
+    /// nodes.
+    #[clap(long)]
+    identity_key: PathBuf,
+    /// Address to listen on, either `host:port` or `unix:<path>`.
+    #[clap(long)]
+    listen: RemoteSignerAddr,
+    /// Public key of a node allowed to connect, i.e. the key from its
+    /// node_key.json. Can be given multiple times.
+    #[clap(long = "allowed-client", required = true)]
+    allowed_clients: Vec<PublicKey>,
+    /// File to which every signed payload is appended.
+    #[clap(long)]
+    audit_log: PathBuf,
+}
+
+fn main() -> anyhow::Result<()> {
+    let env_filter = near_o11y::EnvFilterBuilder::from_env().verbose(Some("")).finish().unwrap();
+    let _subscriber = near_o11y::default_subscriber(env_filter, &Default::default()).global();
+
+    let cli = Cli::parse();
+    let validator_signer = InMemorySigner::from(read_validator_key(&cli)?);
+    let identity_signer = InMemorySigner::from_file(&cli.identity_key)
+        .with_context(|| format!("failed to read {}", cli.identity_key.display()))?;
+    let audit_log = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&cli.audit_log)
+        .with_context(|| format!("failed to open {}", cli.audit_log.display()))?;
+    let listener = SignerListener::bind(&cli.listen)
+        .with_context(|| format!("failed to listen on {}", cli.listen))?;
+    tracing::info!(
+        target: "remote_signer",
+        account_id = %validator_signer.account_id,
+        public_key = %validator_signer.public_key(),
+        listen = %cli.listen,
+        "Serving the validator key"
+    );
+    let server =
+        SignerServer::new(validator_signer, identity_signer.into(), cli.allowed_clients, audit_log);
+    Arc::new(server).serve(listener)?;
+    Ok(())
+}
+
+fn read_validator_key(cli: &Cli) -> anyhow::Result<KeyFile> {
+    let path = &cli.validator_key;
+    if !KeyFile::is_encrypted(path).with_context(|| format!("failed to read {}", path.display()))? {
+        return Ok(KeyFile::from_file(path)?);
+    }
+    let password = match &cli.password_file {
+        Some(password_file) => std::fs::read(password_file)
+            .with_context(|| format!("failed to read {}", password_file.display()))?,
+        None => rpassword::prompt_password("Password: ")?.into_bytes(),
+    };
+    Ok(EncryptedKeyFile::from_file(path)?.decrypt(&password)?)
+}
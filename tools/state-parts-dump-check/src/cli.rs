@@ -5,6 +5,9 @@ use near_client::sync::external::{
     create_bucket_readonly, external_storage_location, external_storage_location_directory,
     get_num_parts_from_filename, ExternalConnection, StateFileType,
 };
+use near_client::sync::external_manifest::{
+    decode_state_part, state_part_content_location, StatePartsManifest,
+};
 use near_jsonrpc::client::{new_client, JsonRpcClient};
 use near_jsonrpc::primitives::types::config::RpcProtocolConfigRequest;
 use near_primitives::hash::CryptoHash;
@@ -422,6 +425,9 @@ fn reset_num_parts_metrics(chain_id: &str, shard_id: ShardId) -> () {
     crate::metrics::STATE_SYNC_DUMP_CHECK_NUM_HEADERS_INVALID
         .with_label_values(&[&shard_id.to_string(), chain_id])
         .set(0);
+    crate::metrics::STATE_SYNC_DUMP_CHECK_MANIFEST_VALID
+        .with_label_values(&[&shard_id.to_string(), chain_id])
+        .set(0);
 }
 
 async fn run_single_check_with_3_retries(
@@ -489,6 +495,20 @@ async fn check_parts(
     state_root: StateRoot,
     external: &ExternalConnection,
 ) -> anyhow::Result<bool> {
+    if external
+        .is_state_parts_manifest_stored_for_epoch(shard_id, chain_id, epoch_id, epoch_height)
+        .await?
+    {
+        return check_content_addressed_parts(
+            chain_id,
+            epoch_id,
+            epoch_height,
+            shard_id,
+            state_root,
+            external,
+        )
+        .await;
+    }
     let directory_path = external_storage_location_directory(
         &chain_id,
         &epoch_id,
@@ -568,6 +588,86 @@ async fn check_parts(
                 shard_id,
                 state_root,
                 num_parts,
+                None,
+                external,
+            )
+            .await
+        });
+        handles.push(handle);
+    }
+
+    for handle in handles {
+        let _ = handle.await?;
+    }
+
+    let duration = start.elapsed();
+    tracing::info!("Time elapsed in downloading and validating the parts is: {:?}", duration);
+    Ok(true)
+}
+
+// validate the manifest of the content-addressed state parts, then download and validate the parts it lists.
+// The manifest is only dumped once all parts are dumped.
+async fn check_content_addressed_parts(
+    chain_id: &String,
+    epoch_id: &EpochId,
+    epoch_height: u64,
+    shard_id: ShardId,
+    state_root: StateRoot,
+    external: &ExternalConnection,
+) -> anyhow::Result<bool> {
+    let file_type = StateFileType::StatePartsManifest;
+    let location =
+        external_storage_location(&chain_id, &epoch_id, epoch_height, shard_id, &file_type);
+    tracing::info!(location, "the manifest of the state parts being checked:");
+    let data = external.get_file(shard_id, &location, &file_type).await?;
+    let manifest = match StatePartsManifest::from_bytes(&data) {
+        Ok(manifest) => Arc::new(manifest),
+        Err(err) => {
+            // Nodes can't download any part without a valid manifest.
+            tracing::info!(epoch_height, ?shard_id, ?err, "The manifest is invalid.");
+            crate::metrics::STATE_SYNC_DUMP_CHECK_MANIFEST_VALID
+                .with_label_values(&[&shard_id.to_string(), &chain_id.to_string()])
+                .set(0);
+            return Ok(true);
+        }
+    };
+    crate::metrics::STATE_SYNC_DUMP_CHECK_MANIFEST_VALID
+        .with_label_values(&[&shard_id.to_string(), &chain_id.to_string()])
+        .set(1);
+
+    let num_parts = manifest.parts.len() as u64;
+    tracing::info!(epoch_height, %state_root, num_parts, compression = ?manifest.compression);
+    crate::metrics::STATE_SYNC_DUMP_CHECK_NUM_PARTS_TOTAL
+        .with_label_values(&[&shard_id.to_string(), &chain_id.to_string()])
+        .set(num_parts as i64);
+    crate::metrics::STATE_SYNC_DUMP_CHECK_NUM_PARTS_DUMPED
+        .with_label_values(&[&shard_id.to_string(), &chain_id.to_string()])
+        .set(num_parts as i64);
+
+    tracing::info!(
+        ?shard_id,
+        epoch_height,
+        num_parts,
+        "Spawning threads to download and validate content-addressed state parts."
+    );
+
+    let start = Instant::now();
+    let mut handles = vec![];
+    for part_id in 0..num_parts {
+        let chain_id = chain_id.clone();
+        let external = external.clone();
+        let epoch_id = *epoch_id;
+        let manifest = manifest.clone();
+        let handle = tokio::spawn(async move {
+            process_part_with_3_retries(
+                part_id,
+                chain_id,
+                epoch_id,
+                epoch_height,
+                shard_id,
+                state_root,
+                num_parts,
+                Some(manifest),
                 external,
             )
             .await
@@ -702,12 +802,14 @@ async fn process_part_with_3_retries(
     shard_id: ShardId,
     state_root: StateRoot,
     num_parts: u64,
+    manifest: Option<Arc<StatePartsManifest>>,
     external: ExternalConnection,
 ) -> anyhow::Result<()> {
     let mut retries = 0;
     let mut res;
     loop {
         let chain_id = chain_id.clone();
+        let manifest = manifest.clone();
         let external = external.clone();
         // timeout is needed to deal with edge cases where process_part awaits forever, i.e. the get_file().await somehow waits forever
         // this is set to a long duration because the timer for each task, i.e. process_part, starts when the task is started, i.e. tokio::spawn is called,
@@ -724,6 +826,7 @@ async fn process_part_with_3_retries(
                 shard_id,
                 state_root,
                 num_parts,
+                manifest,
                 external,
             ),
         )
@@ -807,14 +910,35 @@ async fn process_part(
     shard_id: ShardId,
     state_root: StateRoot,
     num_parts: u64,
+    manifest: Option<Arc<StatePartsManifest>>,
     external: ExternalConnection,
 ) -> anyhow::Result<()> {
     tracing::info!(part_id, "process_part started.");
     let file_type = StateFileType::StatePart { part_id, num_parts };
-    let location =
-        external_storage_location(&chain_id, &epoch_id, epoch_height, shard_id, &file_type);
-    let part = external.get_file(shard_id, &location, &file_type).await?;
-    let is_part_valid = validate_state_part(&state_root, PartId::new(part_id, num_parts), &part);
+    let is_part_valid = match manifest {
+        None => {
+            let location =
+                external_storage_location(&chain_id, &epoch_id, epoch_height, shard_id, &file_type);
+            let part = external.get_file(shard_id, &location, &file_type).await?;
+            validate_state_part(&state_root, PartId::new(part_id, num_parts), &part)
+        }
+        Some(manifest) => {
+            let entry = &manifest.parts[part_id as usize];
+            let location =
+                state_part_content_location(&chain_id, &entry.hash, manifest.compression);
+            let stored = external.get_file(shard_id, &location, &file_type).await?;
+            match decode_state_part(entry, manifest.compression, &stored) {
+                Ok(part) => {
+                    validate_state_part(&state_root, PartId::new(part_id, num_parts), &part)
+                }
+                // The stored part doesn't match its entry in the manifest.
+                Err(err) => {
+                    tracing::error!(target: "state-parts", ?err, "State part decoding error");
+                    false
+                }
+            }
+        }
+    };
     if is_part_valid {
         crate::metrics::STATE_SYNC_DUMP_CHECK_NUM_PARTS_VALID
             .with_label_values(&[&shard_id.to_string(), &chain_id.to_string()])
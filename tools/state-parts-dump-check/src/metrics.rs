@@ -99,3 +99,14 @@ pub(crate) static STATE_SYNC_DUMP_CHECK_PROCESS_IS_UP: LazyLock<IntGaugeVec> =
         )
         .unwrap()
     });
+
+pub(crate) static STATE_SYNC_DUMP_CHECK_MANIFEST_VALID: LazyLock<IntGaugeVec> = LazyLock::new(
+    || {
+        try_create_int_gauge_vec(
+            "near_state_sync_dump_check_manifest_valid",
+            "Whether the manifest of the content-addressed state parts of the epoch is valid, 1 if valid, 0 if invalid or not checked",
+            &["shard_id", "chain_id"],
+        )
+        .unwrap()
+    },
+);